@@ -0,0 +1,299 @@
+//! Command-line parsing.
+//!
+//! Options take the form `--name value` (or a bare `--name` for switches) and
+//! may appear anywhere; the first bare argument names the command and the
+//! rest are its positionals.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    path::PathBuf,
+    str::FromStr,
+};
+
+use floating_point_protocol_solana::state::RootHistory;
+use fpp_wallet::stealth::StealthAddress;
+use solana_sdk::{derivation_path::DerivationPath, pubkey::Pubkey};
+
+use crate::error::CliError;
+
+pub const USAGE: &str = "\
+usage: fpp [options] <command> [args]
+
+options:
+    --url <url>              RPC endpoint (default http://127.0.0.1:8899)
+    --keypair <path>         Signer keypair file (default ~/.config/solana/id.json)
+    --ledger                 Sign with a Ledger running the Solana app instead
+    --ledger-key <a[/c]>     Ledger account and change, as m/44'/501'/a'/c' (default m/44'/501')
+    --program-id <pubkey>    Deployed program (or FPP_PROGRAM_ID)
+    --notes <path>           Note file (default fpp-notes.bin)
+
+commands:
+    init --mint <pubkey> [--deposit-fee <bps>] [--withdrawal-fee <bps>]
+         [--root-history <n>]
+    deposit --amount <base units> [--denomination <base units>] [--mint <pubkey>]
+    request-withdraw [--nonce <n>] [<commitment>...]
+    complete-withdraw --nonce <n> [--unwrap-sol]
+    cancel-withdraw --nonce <n> [--permanent]
+    prove --to <stealth address> [--ring-size <n>] [--merkle-root <hex>] [--memo <text>]
+          [--out <path>] <commitment>...
+    status
+    export-notes <path>
+    import-notes <path>
+
+The note file and backups are encrypted under FPP_NOTES_PASSPHRASE when it is set;
+export-notes and import-notes require it.";
+
+const DEFAULT_URL: &str = "http://127.0.0.1:8899";
+const DEFAULT_NOTES: &str = "fpp-notes.bin";
+const DEFAULT_FEE_RATE: u16 = 10;
+const DEFAULT_RING_SIZE: usize = 8;
+const DEFAULT_PROOF_PATH: &str = "fpp-proof.bin";
+
+/// Options that take no value
+const SWITCHES: &[&str] = &["unwrap-sol", "permanent", "ledger"];
+
+pub struct Args {
+    pub url: String,
+    pub signer: SignerSource,
+    pub program_id: Pubkey,
+    pub notes: PathBuf,
+    pub command: Command,
+}
+
+/// Where the fee payer and authority signs
+pub enum SignerSource {
+    Keypair(PathBuf),
+    /// A Ledger at this derivation path, so the key never touches the disk
+    Ledger(DerivationPath),
+}
+
+pub enum Command {
+    Init {
+        mint: Pubkey,
+        deposit_fee_rate: u16,
+        withdrawal_fee_rate: u16,
+        root_history_size: u16,
+    },
+    Deposit {
+        amount: u64,
+        /// Defaults to `amount`, depositing a single point
+        denomination: Option<u64>,
+        /// Defaults to the protocol's primary mint
+        mint: Option<Pubkey>,
+    },
+    RequestWithdraw {
+        /// Defaults to the first nonce with no request on chain
+        nonce: Option<u64>,
+        /// Defaults to every live note not already being withdrawn
+        commitments: Vec<[u8; 32]>,
+    },
+    CompleteWithdraw {
+        nonce: u64,
+        unwrap_sol: bool,
+    },
+    CancelWithdraw {
+        nonce: u64,
+        permanent: bool,
+    },
+    Prove {
+        to: StealthAddress,
+        commitments: Vec<[u8; 32]>,
+        ring_size: usize,
+        /// Defaults to the latest root in the root history
+        merkle_root: Option<[u8; 32]>,
+        memo: Option<String>,
+        out: PathBuf,
+    },
+    Status,
+    /// Write a passphrase-encrypted backup of the note file
+    ExportNotes {
+        out: PathBuf,
+    },
+    /// Merge a backup into the note file
+    ImportNotes {
+        backup: PathBuf,
+    },
+}
+
+/// Parse the command line; `None` means help was asked for
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Args>, CliError> {
+    let mut parsed = Parsed::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            return Ok(None);
+        }
+        match arg.strip_prefix("--") {
+            Some(name) if SWITCHES.contains(&name) => {
+                parsed.switches.insert(name.to_string());
+            }
+            Some(name) => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| CliError::Usage(format!("--{} needs a value", name)))?;
+                if parsed.options.insert(name.to_string(), value).is_some() {
+                    return Err(CliError::Usage(format!("--{} given twice", name)));
+                }
+            }
+            None => parsed.positionals.push(arg),
+        }
+    }
+    if parsed.positionals.is_empty() {
+        return Ok(None);
+    }
+    let name = parsed.positionals.remove(0);
+    
+    let url = parsed.take("url").unwrap_or_else(|| DEFAULT_URL.to_string());
+    let ledger_key = parsed.take("ledger-key");
+    let signer = match (parsed.switch("ledger"), parsed.take("keypair")) {
+        (true, Some(_)) => {
+            return Err(CliError::Usage("--keypair and --ledger conflict".to_string()))
+        }
+        (true, None) => SignerSource::Ledger(match ledger_key {
+            Some(raw) => DerivationPath::from_key_str(&raw)
+                .map_err(|_| CliError::Usage(format!("invalid --ledger-key: {}", raw)))?,
+            None => DerivationPath::default(),
+        }),
+        (false, _) if ledger_key.is_some() => {
+            return Err(CliError::Usage("--ledger-key needs --ledger".to_string()))
+        }
+        (false, Some(path)) => SignerSource::Keypair(PathBuf::from(path)),
+        (false, None) => SignerSource::Keypair(default_keypair()?),
+    };
+    let program_id = match parsed.take("program-id").or_else(|| env::var("FPP_PROGRAM_ID").ok()) {
+        Some(raw) => parse_value("program-id", &raw)?,
+        None => return Err(CliError::Usage("--program-id is required".to_string())),
+    };
+    let notes = PathBuf::from(parsed.take("notes").unwrap_or_else(|| DEFAULT_NOTES.to_string()));
+    
+    let command = match name.as_str() {
+        "init" => Command::Init {
+            mint: parsed.required("mint")?,
+            deposit_fee_rate: parsed.optional("deposit-fee")?.unwrap_or(DEFAULT_FEE_RATE),
+            withdrawal_fee_rate: parsed.optional("withdrawal-fee")?.unwrap_or(DEFAULT_FEE_RATE),
+            root_history_size: parsed
+                .optional("root-history")?
+                .unwrap_or(RootHistory::DEFAULT_SIZE),
+        },
+        "deposit" => Command::Deposit {
+            amount: parsed.required("amount")?,
+            denomination: parsed.optional("denomination")?,
+            mint: parsed.optional("mint")?,
+        },
+        "request-withdraw" => Command::RequestWithdraw {
+            nonce: parsed.optional("nonce")?,
+            commitments: parsed
+                .positionals
+                .drain(..)
+                .map(|raw| parse_hash("commitment", &raw))
+                .collect::<Result<_, _>>()?,
+        },
+        "complete-withdraw" => Command::CompleteWithdraw {
+            nonce: parsed.required("nonce")?,
+            unwrap_sol: parsed.switch("unwrap-sol"),
+        },
+        "cancel-withdraw" => Command::CancelWithdraw {
+            nonce: parsed.required("nonce")?,
+            permanent: parsed.switch("permanent"),
+        },
+        "prove" => Command::Prove {
+            to: parsed.required("to")?,
+            commitments: parsed
+                .positionals
+                .drain(..)
+                .map(|raw| parse_hash("commitment", &raw))
+                .collect::<Result<_, _>>()?,
+            ring_size: parsed.optional("ring-size")?.unwrap_or(DEFAULT_RING_SIZE),
+            merkle_root: parsed
+                .take("merkle-root")
+                .map(|raw| parse_hash("merkle root", &raw))
+                .transpose()?,
+            memo: parsed.take("memo"),
+            out: PathBuf::from(
+                parsed
+                    .take("out")
+                    .unwrap_or_else(|| DEFAULT_PROOF_PATH.to_string()),
+            ),
+        },
+        "status" => Command::Status,
+        "export-notes" => Command::ExportNotes {
+            out: parsed.positional("backup path")?,
+        },
+        "import-notes" => Command::ImportNotes {
+            backup: parsed.positional("backup path")?,
+        },
+        other => return Err(CliError::Usage(format!("unknown command `{}`", other))),
+    };
+    parsed.finish(&name)?;
+    
+    Ok(Some(Args {
+        url,
+        signer,
+        program_id,
+        notes,
+        command,
+    }))
+}
+
+#[derive(Default)]
+struct Parsed {
+    options: BTreeMap<String, String>,
+    switches: BTreeSet<String>,
+    positionals: Vec<String>,
+}
+
+impl Parsed {
+    fn take(&mut self, name: &str) -> Option<String> {
+        self.options.remove(name)
+    }
+    
+    fn optional<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, CliError> {
+        self.take(name).map(|raw| parse_value(name, &raw)).transpose()
+    }
+    
+    fn required<T: FromStr>(&mut self, name: &str) -> Result<T, CliError> {
+        self.optional(name)?
+            .ok_or_else(|| CliError::Usage(format!("--{} is required", name)))
+    }
+    
+    fn positional(&mut self, what: &str) -> Result<PathBuf, CliError> {
+        if self.positionals.is_empty() {
+            return Err(CliError::Usage(format!("a {} is required", what)));
+        }
+        Ok(PathBuf::from(self.positionals.remove(0)))
+    }
+    
+    fn switch(&mut self, name: &str) -> bool {
+        self.switches.remove(name)
+    }
+    
+    /// Reject anything the command didn't consume
+    fn finish(self, command: &str) -> Result<(), CliError> {
+        if let Some(name) = self.options.keys().chain(self.switches.iter()).next() {
+            return Err(CliError::Usage(format!("`{}` takes no --{}", command, name)));
+        }
+        if let Some(arg) = self.positionals.first() {
+            return Err(CliError::Usage(format!("unexpected argument `{}`", arg)));
+        }
+        Ok(())
+    }
+}
+
+fn parse_value<T: FromStr>(name: &str, raw: &str) -> Result<T, CliError> {
+    raw.parse()
+        .map_err(|_| CliError::Usage(format!("invalid --{}: {}", name, raw)))
+}
+
+fn parse_hash(what: &str, raw: &str) -> Result<[u8; 32], CliError> {
+    hex::decode(raw)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| CliError::Usage(format!("invalid {}: {}", what, raw)))
+}
+
+fn default_keypair() -> Result<PathBuf, CliError> {
+    let home = env::var("HOME")
+        .map_err(|_| CliError::Usage("--keypair is required when HOME is unset".to_string()))?;
+    Ok(PathBuf::from(home).join(".config/solana/id.json"))
+}
@@ -0,0 +1,37 @@
+use fpp_client::ClientError;
+use fpp_wallet::error::WalletError;
+use solana_sdk::program_error::ProgramError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CliError {
+    /// Bad command line; printed with the usage text
+    #[error("{0}")]
+    Usage(String),
+    
+    #[error("Config Error: {0}")]
+    Config(String),
+    
+    #[error("IO Error: {0}")]
+    Io(#[from] std::io::Error),
+    
+    #[error("RPC Error: {0}")]
+    Rpc(String),
+    
+    /// The keypair or Ledger couldn't be opened, or refused to sign
+    #[error("Signer Error: {0}")]
+    Signer(String),
+    
+    #[error("Client Error: {0}")]
+    Client(#[from] ClientError),
+    
+    #[error("Program Error: {0}")]
+    Program(#[from] ProgramError),
+    
+    #[error("Wallet Error: {0}")]
+    Wallet(#[from] WalletError),
+    
+    /// On-chain or note state doesn't allow the command
+    #[error("State Error: {0}")]
+    State(String),
+}
@@ -0,0 +1,27 @@
+//! Signing with a Ledger running the Solana app.
+//!
+//! The device derives the key at the requested path and signs each
+//! transaction after showing it; the Solana app needs blind signing enabled,
+//! as it can't decode the program's instructions.
+
+use solana_remote_wallet::{
+    locator::Locator,
+    remote_keypair::{generate_remote_keypair, RemoteKeypair},
+    remote_wallet::maybe_wallet_manager,
+};
+use solana_sdk::derivation_path::DerivationPath;
+
+use crate::error::CliError;
+
+/// The first Ledger found over USB, at `derivation_path`
+pub fn signer(derivation_path: DerivationPath) -> Result<RemoteKeypair, CliError> {
+    let wallet_manager = maybe_wallet_manager()
+        .map_err(|e| CliError::Signer(e.to_string()))?
+        .ok_or_else(|| {
+            CliError::Signer("no Ledger found; connect it and open the Solana app".to_string())
+        })?;
+    let locator =
+        Locator::new_from_path("usb://ledger").map_err(|e| CliError::Signer(e.to_string()))?;
+    generate_remote_keypair(locator, derivation_path, &wallet_manager, false, "signer")
+        .map_err(|e| CliError::Signer(e.to_string()))
+}
@@ -0,0 +1,745 @@
+//! `fpp`: exercise a deployed program from the command line.
+//!
+//! Signs with a keypair file or a Ledger and talks to one RPC endpoint. Notes
+//! created by `deposit` are written to the note file before the deposit is sent, so
+//! their secrets survive a failed or interrupted run; withdrawals draw their
+//! points from the same file, and `prove` signs spends of them for
+//! `PrivacyPayment`. `export-notes` and `import-notes` move the note file
+//! between machines as an encrypted backup. Run `fpp --help` for the commands.
+
+mod args;
+mod error;
+#[cfg(feature = "ledger")]
+mod ledger;
+mod notes;
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use floating_point_protocol_solana::{
+    crypto::ring,
+    instruction, pda,
+    state::{
+        FloatingPoint, ProtocolState, RootHistory, WithdrawalRequest, POINT_VALUE,
+        WITHDRAWAL_DELAY,
+    },
+};
+use fpp_client::{budget, ComputeBudget, MintAccounts, PriorityFeePolicy, Protocol, SpendProof};
+use fpp_wallet::{
+    note::Note,
+    spend,
+    stealth::{self, StealthAddress},
+};
+use solana_client::{
+    rpc_client::RpcClient, rpc_config::RpcProgramAccountsConfig, rpc_filter::RpcFilterType,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Signature, Signer},
+    transaction::Transaction,
+};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+
+use crate::{
+    args::{Args, Command, SignerSource, USAGE},
+    error::CliError,
+    notes::{NoteFile, PASSPHRASE_VAR},
+};
+
+/// Points per `RequestWithdrawal`; each adds two accounts and 64 bytes of data
+const MAX_POINTS_PER_REQUEST: usize = 6;
+
+/// `getMultipleAccounts` limit
+const MAX_ACCOUNTS_PER_FETCH: usize = 100;
+
+struct Cli {
+    rpc: RpcClient,
+    /// Fee payer, and the authority for admin commands
+    payer: Box<dyn Signer>,
+    program_id: Pubkey,
+    notes_path: PathBuf,
+    /// Seals the note file when set
+    notes_passphrase: Option<String>,
+}
+
+fn main() {
+    let args = match args::parse(env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("fpp: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if let Err(e) = run(args) {
+        eprintln!("fpp: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), CliError> {
+    let payer = signer(args.signer)?;
+    let cli = Cli {
+        rpc: RpcClient::new_with_commitment(args.url, CommitmentConfig::confirmed()),
+        payer,
+        program_id: args.program_id,
+        notes_path: args.notes,
+        notes_passphrase: env::var(PASSPHRASE_VAR).ok(),
+    };
+    
+    match args.command {
+        Command::Init {
+            mint,
+            deposit_fee_rate,
+            withdrawal_fee_rate,
+            root_history_size,
+        } => cli.init(mint, deposit_fee_rate, withdrawal_fee_rate, root_history_size),
+        Command::Deposit {
+            amount,
+            denomination,
+            mint,
+        } => cli.deposit(amount, denomination, mint),
+        Command::RequestWithdraw { nonce, commitments } => {
+            cli.request_withdraw(nonce, commitments)
+        }
+        Command::CompleteWithdraw { nonce, unwrap_sol } => cli.complete_withdraw(nonce, unwrap_sol),
+        Command::CancelWithdraw { nonce, permanent } => cli.cancel_withdraw(nonce, permanent),
+        Command::Prove {
+            to,
+            commitments,
+            ring_size,
+            merkle_root,
+            memo,
+            out,
+        } => cli.prove(&to, commitments, ring_size, merkle_root, memo, &out),
+        Command::Status => cli.status(),
+        Command::ExportNotes { out } => cli.export_notes(&out),
+        Command::ImportNotes { backup } => cli.import_notes(&backup),
+    }
+}
+
+fn signer(source: SignerSource) -> Result<Box<dyn Signer>, CliError> {
+    match source {
+        SignerSource::Keypair(path) => {
+            let keypair = read_keypair_file(&path)
+                .map_err(|e| CliError::Config(format!("{}: {}", path.display(), e)))?;
+            Ok(Box::new(keypair))
+        }
+        #[cfg(feature = "ledger")]
+        SignerSource::Ledger(derivation_path) => Ok(Box::new(ledger::signer(derivation_path)?)),
+        #[cfg(not(feature = "ledger"))]
+        SignerSource::Ledger(_) => {
+            Err(CliError::Signer("built without the `ledger` feature".to_string()))
+        }
+    }
+}
+
+impl Cli {
+    /// Initialize the protocol with the signer as authority, and create the
+    /// treasury token account deposits pay into
+    fn init(
+        &self,
+        mint: Pubkey,
+        deposit_fee_rate: u16,
+        withdrawal_fee_rate: u16,
+        root_history_size: u16,
+    ) -> Result<(), CliError> {
+        let protocol_state = pda::find_protocol_state(&self.program_id).0;
+        let mint = self.mint_accounts(&mint)?;
+        let treasury = ProtocolState::find_treasury_authority(&self.program_id).0;
+        let signature = self.send(&[
+            instruction::initialize(
+                &self.program_id,
+                &self.payer.pubkey(),
+                &protocol_state,
+                &mint.mint,
+                deposit_fee_rate,
+                withdrawal_fee_rate,
+                root_history_size,
+            )?,
+            create_associated_token_account_idempotent(
+                &self.payer.pubkey(),
+                &treasury,
+                &mint.mint,
+                &mint.token_program,
+            ),
+        ])?;
+        println!("protocol state {} initialized ({})", protocol_state, signature);
+        Ok(())
+    }
+    
+    /// Deposit `amount` as fresh notes of `denomination` each
+    fn deposit(
+        &self,
+        amount: u64,
+        denomination: Option<u64>,
+        mint: Option<Pubkey>,
+    ) -> Result<(), CliError> {
+        let protocol = self.protocol()?;
+        let mint = match mint {
+            Some(mint) if mint != protocol.usdt.mint => self.mint_accounts(&mint)?,
+            _ => protocol.usdt,
+        };
+        let denomination = denomination.unwrap_or(amount);
+        if denomination == 0 || amount % denomination != 0 {
+            return Err(CliError::Usage(format!(
+                "amount {} is not a whole number of {} denominations",
+                amount, denomination
+            )));
+        }
+        
+        let epoch = self
+            .rpc
+            .get_epoch_info()
+            .map_err(|e| CliError::Rpc(e.to_string()))?
+            .epoch;
+        let notes: Vec<Note> = (0..amount / denomination)
+            .map(|_| Note::generate(denomination / POINT_VALUE, epoch))
+            .collect();
+        let opening_proofs = notes
+            .iter()
+            .map(|note| note.opening_proof(denomination, &self.payer.pubkey()))
+            .collect::<Result<_, _>>()?;
+        let deposit = protocol
+            .deposit(
+                self.payer.pubkey(),
+                denomination,
+                notes.iter().map(|note| note.commitment).collect(),
+            )
+            .opening_proofs(opening_proofs)
+            .mint(mint)
+            .epoch(epoch)
+            .build()?;
+        
+        // Saved first: a deposit whose note secrets were lost could never be withdrawn
+        let mut file = self.load_notes()?;
+        for note in &notes {
+            file.wallet.add_note(note.clone());
+        }
+        self.save_notes(&file)?;
+        
+        let signature = self.send(&[deposit])?;
+        println!(
+            "deposited {} as {} point(s) of {} ({})",
+            amount,
+            notes.len(),
+            denomination,
+            signature
+        );
+        for note in &notes {
+            println!("  {}", hex::encode(note.commitment));
+        }
+        Ok(())
+    }
+    
+    /// Open a withdrawal request for `commitments`, or for the live notes
+    /// not already being withdrawn
+    fn request_withdraw(
+        &self,
+        nonce: Option<u64>,
+        commitments: Vec<[u8; 32]>,
+    ) -> Result<(), CliError> {
+        let protocol = self.protocol()?;
+        let user = self.payer.pubkey();
+        let mut file = self.load_notes()?;
+        
+        let commitments = if commitments.is_empty() {
+            let mut live = self.live_notes(&file)?;
+            live.truncate(MAX_POINTS_PER_REQUEST);
+            live
+        } else {
+            commitments
+        };
+        if commitments.is_empty() {
+            return Err(CliError::State("no live notes to withdraw".to_string()));
+        }
+        if commitments.len() > MAX_POINTS_PER_REQUEST {
+            return Err(CliError::Usage(format!(
+                "at most {} points fit in one request",
+                MAX_POINTS_PER_REQUEST
+            )));
+        }
+        let nonce = match nonce {
+            Some(nonce) => nonce,
+            None => self.free_nonce(&user, &file)?,
+        };
+        
+        let mut builder = protocol.request_withdrawal(user, nonce);
+        let mut inputs = Vec::with_capacity(commitments.len());
+        for commitment in &commitments {
+            builder = builder.point(pda::find_point_address(commitment, &self.program_id).0);
+            inputs.push(file.note(commitment)?.clone());
+        }
+        // Withdrawals name their points, so the ring is just them
+        let (spend_signature, _) = spend::sign(&builder.spend_message(), &inputs, &[])?;
+        let signature = self.send(&[builder.spend_signature(spend_signature).build()?])?;
+        
+        let count = commitments.len();
+        file.pending.insert(nonce, commitments);
+        self.save_notes(&file)?;
+        println!(
+            "withdrawal request {} opened for {} point(s) under nonce {} ({})",
+            protocol.withdrawal_request(&user, nonce),
+            count,
+            nonce,
+            signature
+        );
+        let delay = self
+            .fetch::<ProtocolState>(&pda::find_protocol_state(&self.program_id).0)?
+            .map_or(WITHDRAWAL_DELAY, |state| state.withdrawal_delay);
+        // Devnet deployments may run with delays well under an hour
+        let wait = if delay % 3600 == 0 {
+            format!("{} hours", delay / 3600)
+        } else {
+            format!("{} minutes", (delay + 59) / 60)
+        };
+        println!("complete it after {} with `fpp complete-withdraw --nonce {}`", wait, nonce);
+        Ok(())
+    }
+    
+    /// Pay out the request under `nonce` to the signer's token account
+    fn complete_withdraw(&self, nonce: u64, unwrap_sol: bool) -> Result<(), CliError> {
+        let protocol = self.protocol()?;
+        let user = self.payer.pubkey();
+        let address = protocol.withdrawal_request(&user, nonce);
+        let request = self.open_request(&address, nonce)?;
+        let now = self.cluster_time()?;
+        if now < request.unlock_time {
+            return Err(CliError::State(format!(
+                "withdrawal under nonce {} unlocks in {} seconds",
+                nonce,
+                request.unlock_time - now
+            )));
+        }
+        
+        let mint = self.mint_accounts(&request.mint)?;
+        let epoch = self
+            .rpc
+            .get_epoch_info()
+            .map_err(|e| CliError::Rpc(e.to_string()))?
+            .epoch;
+        let signature = self.send(&[
+            create_associated_token_account_idempotent(
+                &user,
+                &user,
+                &mint.mint,
+                &mint.token_program,
+            ),
+            instruction::complete_withdrawal(
+                &self.program_id,
+                &user,
+                &mint.token_account(&user),
+                &protocol.treasury_token(&mint),
+                &protocol.protocol_state,
+                &address,
+                &mint.mint,
+                &mint.token_program,
+                unwrap_sol,
+                epoch,
+            )?,
+        ])?;
+        
+        let mut file = self.load_notes()?;
+        for commitment in file.pending.remove(&nonce).unwrap_or_default() {
+            file.wallet.mark_spent(commitment);
+        }
+        self.save_notes(&file)?;
+        println!("withdrew {} under nonce {} ({})", request.amount, nonce, signature);
+        Ok(())
+    }
+    
+    /// Cancel the request under `nonce`, returning its points unless `permanent`
+    fn cancel_withdraw(&self, nonce: u64, permanent: bool) -> Result<(), CliError> {
+        let protocol = self.protocol()?;
+        let user = self.payer.pubkey();
+        let address = protocol.withdrawal_request(&user, nonce);
+        self.open_request(&address, nonce)?;
+        
+        let mut file = self.load_notes()?;
+        let commitments = file.pending.get(&nonce).cloned().ok_or_else(|| {
+            CliError::State(format!(
+                "{} has no points for nonce {}",
+                self.notes_path.display(),
+                nonce
+            ))
+        })?;
+        let points: Vec<Pubkey> = commitments
+            .iter()
+            .map(|commitment| pda::find_point_address(commitment, &self.program_id).0)
+            .collect();
+        let nullifiers = commitments
+            .iter()
+            .map(|commitment| file.note(commitment).map(|note| note.nullifier))
+            .collect::<Result<Vec<_>, _>>()?;
+        let signature = self.send(&[instruction::cancel_withdrawal(
+            &self.program_id,
+            &user,
+            &protocol.protocol_state,
+            &address,
+            &points,
+            &nullifiers,
+            permanent,
+        )?])?;
+        
+        file.pending.remove(&nonce);
+        if permanent {
+            for commitment in commitments {
+                file.wallet.mark_spent(commitment);
+            }
+        }
+        self.save_notes(&file)?;
+        println!("withdrawal under nonce {} cancelled ({})", nonce, signature);
+        Ok(())
+    }
+    
+    /// Sign a spend of `commitments` to `to` among decoys, and write the
+    /// `SpendProof` for a `PrivacyPayment` to `out`
+    ///
+    /// Nothing is sent and the notes stay unspent here until the payment lands.
+    /// The proof bytes are left empty: no spend circuit is verified on chain
+    /// yet, and the ring signature already binds whatever proof is attached.
+    fn prove(
+        &self,
+        to: &StealthAddress,
+        commitments: Vec<[u8; 32]>,
+        ring_size: usize,
+        merkle_root: Option<[u8; 32]>,
+        memo: Option<String>,
+        out: &Path,
+    ) -> Result<(), CliError> {
+        if commitments.is_empty() {
+            return Err(CliError::Usage("name the notes to spend".to_string()));
+        }
+        let state: ProtocolState = self
+            .fetch(&pda::find_protocol_state(&self.program_id).0)?
+            .ok_or_else(|| CliError::State("protocol is not initialized".to_string()))?;
+        let file = self.load_notes()?;
+        let inputs = commitments
+            .iter()
+            .map(|commitment| {
+                let note = file.note(commitment)?;
+                if !file.available().any(|available| available == note) {
+                    return Err(CliError::State(format!(
+                        "note {} is spent or being withdrawn",
+                        hex::encode(commitment)
+                    )));
+                }
+                Ok(note.clone())
+            })
+            .collect::<Result<Vec<Note>, _>>()?;
+        let merkle_root = match merkle_root {
+            Some(root) => root,
+            None => self.latest_root()?,
+        };
+        
+        let candidates = self.decoy_candidates(state.min_exit_slots)?;
+        let decoys = spend::select_decoys(&inputs, &candidates, ring_size)?;
+        let mass = inputs.iter().map(|note| note.mass).sum();
+        let output = stealth::pay(to, mass, memo.unwrap_or_default().into_bytes())?;
+        let nullifiers: Vec<[u8; 32]> = inputs.iter().map(|note| note.nullifier).collect();
+        let proof = Vec::new();
+        let message = ring::signing_message(&nullifiers, &[output.commitment], &proof);
+        let (ring_signature, ring) = spend::sign(&message, &inputs, &decoys)?;
+        
+        let spend = SpendProof {
+            merkle_root,
+            ring,
+            ring_signature,
+            output_commitments: vec![output.commitment],
+            encrypted_notes: vec![output.encrypted_note],
+            proof,
+        };
+        fs::write(out, spend.try_to_vec()?)?;
+        println!(
+            "spend of {} note(s), mass {}, in a ring of {} written to {}",
+            inputs.len(),
+            mass,
+            ring_size,
+            out.display()
+        );
+        println!("merkle root  {}", hex::encode(merkle_root));
+        for nullifier in &nullifiers {
+            println!("nullifier    {}", hex::encode(nullifier));
+        }
+        println!("output       {}", hex::encode(output.commitment));
+        Ok(())
+    }
+    
+    /// Print the protocol state, the signer's notes and its pending withdrawals
+    fn status(&self) -> Result<(), CliError> {
+        let protocol_state = pda::find_protocol_state(&self.program_id).0;
+        let state: ProtocolState = self
+            .fetch(&protocol_state)?
+            .ok_or_else(|| CliError::State("protocol is not initialized".to_string()))?;
+        let denominations: Vec<String> = state
+            .denominations
+            .iter()
+            .filter(|denomination| **denomination != 0)
+            .map(|denomination| denomination.to_string())
+            .collect();
+        println!("program           {}", self.program_id);
+        println!("protocol state    {}", protocol_state);
+        println!("authority         {}", state.authority);
+        println!("mint              {} ({})", state.usdt_mint, state.token_program);
+        println!("deposited         {}", state.total_deposited);
+        println!("withdrawn         {}", state.total_withdrawn);
+        println!("uncollected fees  {}", state.total_fees);
+        println!("points            {}", state.total_points);
+        println!(
+            "fees              deposit {} bps, withdrawal {} bps",
+            state.deposit_fee_rate, state.withdrawal_fee_rate
+        );
+        println!("denominations     {}", denominations.join(", "));
+        println!("paused flags      {:#04x}", state.paused_flags);
+        println!();
+        
+        let user = self.payer.pubkey();
+        let file = self.load_notes()?;
+        let live = self.live_notes(&file)?;
+        let mass: u64 = live
+            .iter()
+            .filter_map(|commitment| file.wallet.notes.get(commitment))
+            .map(|note| note.mass)
+            .sum();
+        println!("signer            {}", user);
+        println!("live notes        {} (mass {})", live.len(), mass);
+        for (nonce, commitments) in &file.pending {
+            let address = pda::find_withdrawal_request(&user, *nonce, &self.program_id).0;
+            let status = match self.fetch::<WithdrawalRequest>(&address)? {
+                None => "not found on chain".to_string(),
+                Some(request) if request.completed => "completed".to_string(),
+                Some(request) if request.cancelled => "cancelled".to_string(),
+                Some(request) => {
+                    format!("{} unlocking at {}", request.amount, request.unlock_time)
+                }
+            };
+            println!(
+                "withdrawal {:<6} {} point(s), {}",
+                nonce,
+                commitments.len(),
+                status
+            );
+        }
+        Ok(())
+    }
+    
+    /// Write the note file, sealed under the passphrase, to `out`
+    fn export_notes(&self, out: &Path) -> Result<(), CliError> {
+        let passphrase = self.passphrase()?;
+        let file = self.load_notes()?;
+        fs::write(out, file.export(passphrase)?)?;
+        println!(
+            "{} notes and {} pending withdrawals backed up to {}",
+            file.wallet.notes.len(),
+            file.pending.len(),
+            out.display()
+        );
+        Ok(())
+    }
+    
+    /// Merge a backup into the note file, which is created if it doesn't exist
+    fn import_notes(&self, backup: &Path) -> Result<(), CliError> {
+        let restored = NoteFile::import(&fs::read(backup)?, self.passphrase()?)?;
+        let mut file = self.load_notes()?;
+        let before = file.wallet.notes.len();
+        file.merge(restored);
+        self.save_notes(&file)?;
+        println!(
+            "restored {} new notes into {}",
+            file.wallet.notes.len() - before,
+            self.notes_path.display()
+        );
+        Ok(())
+    }
+    
+    fn load_notes(&self) -> Result<NoteFile, CliError> {
+        NoteFile::load(&self.notes_path, self.notes_passphrase.as_deref())
+    }
+    
+    fn save_notes(&self, file: &NoteFile) -> Result<(), CliError> {
+        file.save(&self.notes_path, self.notes_passphrase.as_deref())
+    }
+    
+    fn passphrase(&self) -> Result<&str, CliError> {
+        self.notes_passphrase
+            .as_deref()
+            .ok_or_else(|| CliError::Usage(format!("set {} to encrypt backups", PASSPHRASE_VAR)))
+    }
+    
+    fn protocol(&self) -> Result<Protocol, CliError> {
+        let address = pda::find_protocol_state(&self.program_id).0;
+        let state: ProtocolState = self
+            .fetch(&address)?
+            .ok_or_else(|| CliError::State("protocol is not initialized".to_string()))?;
+        Ok(Protocol::from_state(self.program_id, &state))
+    }
+    
+    fn latest_root(&self) -> Result<[u8; 32], CliError> {
+        let history: RootHistory = self
+            .fetch(&RootHistory::find_address(&self.program_id).0)?
+            .ok_or_else(|| CliError::State("root history is not initialized".to_string()))?;
+        history
+            .roots
+            .get(history.current_index as usize)
+            .copied()
+            .ok_or_else(|| CliError::State("root history is empty".to_string()))
+    }
+    
+    /// Points the program would accept as ring members right now
+    fn decoy_candidates(&self, min_exit_slots: u64) -> Result<Vec<FloatingPoint>, CliError> {
+        let slot = self.rpc.get_slot().map_err(|e| CliError::Rpc(e.to_string()))?;
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::DataSize(FloatingPoint::LEN as u64)]),
+            ..RpcProgramAccountsConfig::default()
+        };
+        let accounts = self
+            .rpc
+            .get_program_accounts_with_config(&self.program_id, config)
+            .map_err(|e| CliError::Rpc(e.to_string()))?;
+        Ok(accounts
+            .into_iter()
+            .filter_map(|(address, account)| {
+                let point = FloatingPoint::try_from_slice(&account.data).ok()?;
+                let eligible = point.is_initialized
+                    && point.is_active
+                    && point.can_exit_at(slot, min_exit_slots)
+                    && address == pda::find_point_address(&point.commitment, &self.program_id).0;
+                eligible.then_some(point)
+            })
+            .collect())
+    }
+    
+    /// The request under `nonce`, if it is still open
+    fn open_request(&self, address: &Pubkey, nonce: u64) -> Result<WithdrawalRequest, CliError> {
+        let request: WithdrawalRequest = self.fetch(address)?.ok_or_else(|| {
+            CliError::State(format!("no withdrawal request under nonce {}", nonce))
+        })?;
+        if request.completed || request.cancelled {
+            return Err(CliError::State(format!(
+                "withdrawal under nonce {} is already closed",
+                nonce
+            )));
+        }
+        Ok(request)
+    }
+    
+    /// Available notes whose point is active on chain, skipping deposits that never landed
+    fn live_notes(&self, file: &NoteFile) -> Result<Vec<[u8; 32]>, CliError> {
+        let notes: Vec<&Note> = file.available().collect();
+        let mut live = Vec::new();
+        for chunk in notes.chunks(MAX_ACCOUNTS_PER_FETCH) {
+            let addresses: Vec<Pubkey> = chunk
+                .iter()
+                .map(|note| pda::find_point_address(&note.commitment, &self.program_id).0)
+                .collect();
+            let accounts = self
+                .rpc
+                .get_multiple_accounts(&addresses)
+                .map_err(|e| CliError::Rpc(e.to_string()))?;
+            for (note, account) in chunk.iter().zip(accounts) {
+                let active = account
+                    .filter(|account| account.owner == self.program_id)
+                    .and_then(|account| FloatingPoint::deserialize(&mut &account.data[..]).ok())
+                    .map_or(false, |point| point.is_active);
+                if active {
+                    live.push(note.commitment);
+                }
+            }
+        }
+        Ok(live)
+    }
+    
+    /// Lowest nonce with no request on chain and none recorded as pending
+    fn free_nonce(&self, user: &Pubkey, file: &NoteFile) -> Result<u64, CliError> {
+        for nonce in 0.. {
+            if file.pending.contains_key(&nonce) {
+                continue;
+            }
+            let address = pda::find_withdrawal_request(user, nonce, &self.program_id).0;
+            let existing = self
+                .rpc
+                .get_account_with_commitment(&address, self.rpc.commitment())
+                .map_err(|e| CliError::Rpc(e.to_string()))?
+                .value;
+            if existing.is_none() {
+                return Ok(nonce);
+            }
+        }
+        unreachable!("nonce space exhausted")
+    }
+    
+    /// `mint` with its owning token program, read from the mint account
+    fn mint_accounts(&self, mint: &Pubkey) -> Result<MintAccounts, CliError> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(mint, self.rpc.commitment())
+            .map_err(|e| CliError::Rpc(e.to_string()))?
+            .value
+            .ok_or_else(|| CliError::State(format!("mint {} does not exist", mint)))?;
+        Ok(MintAccounts {
+            mint: *mint,
+            token_program: account.owner,
+        })
+    }
+    
+    /// Decode the program account at `address`, if there is one
+    fn fetch<T: BorshDeserialize>(&self, address: &Pubkey) -> Result<Option<T>, CliError> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())
+            .map_err(|e| CliError::Rpc(e.to_string()))?
+            .value;
+        match account {
+            Some(account) if account.owner == self.program_id => {
+                T::deserialize(&mut &account.data[..])
+                    .map(Some)
+                    .map_err(|e| CliError::State(format!("account {}: {}", address, e)))
+            }
+            _ => Ok(None),
+        }
+    }
+    
+    fn cluster_time(&self) -> Result<i64, CliError> {
+        let slot = self.rpc.get_slot().map_err(|e| CliError::Rpc(e.to_string()))?;
+        self.rpc
+            .get_block_time(slot)
+            .map_err(|e| CliError::Rpc(e.to_string()))
+    }
+    
+    /// Send `instructions` after a compute budget sized for them, priced from
+    /// recent prioritization fees on the accounts they write-lock
+    fn send(&self, instructions: &[Instruction]) -> Result<Signature, CliError> {
+        let recent_fees: Vec<u64> = self
+            .rpc
+            .get_recent_prioritization_fees(&budget::write_locked_accounts(instructions))
+            .map_err(|e| CliError::Rpc(e.to_string()))?
+            .into_iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect();
+        let unit_price = PriorityFeePolicy::default().unit_price(&recent_fees);
+        let mut with_budget =
+            ComputeBudget::estimate(&self.program_id, instructions, unit_price).instructions();
+        with_budget.extend_from_slice(instructions);
+        
+        let blockhash = self
+            .rpc
+            .get_latest_blockhash()
+            .map_err(|e| CliError::Rpc(e.to_string()))?;
+        // A Ledger can refuse, so sign fallibly rather than panicking
+        let mut tx = Transaction::new_with_payer(&with_budget, Some(&self.payer.pubkey()));
+        tx.try_sign(&vec![self.payer.as_ref()], blockhash)
+            .map_err(|e| CliError::Signer(e.to_string()))?;
+        self.rpc
+            .send_and_confirm_transaction(&tx)
+            .map_err(|e| CliError::Rpc(e.to_string()))
+    }
+}
@@ -0,0 +1,90 @@
+//! The note file: the wallet's notes plus the points tied up in each pending
+//! withdrawal, Borsh-encoded. It holds every note secret, so keep it as safe
+//! as the keypair. With `FPP_NOTES_PASSPHRASE` set it is sealed with
+//! `fpp_wallet::vault` on every save, and backups always are.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use fpp_wallet::{
+    note::Note,
+    sync::WalletState,
+    vault::{self, KdfParams},
+};
+
+use crate::error::CliError;
+
+/// Environment variable holding the note file and backup passphrase
+pub const PASSPHRASE_VAR: &str = "FPP_NOTES_PASSPHRASE";
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default)]
+pub struct NoteFile {
+    pub wallet: WalletState,
+    /// Commitments withdrawn under each open request nonce, in request order
+    pub pending: BTreeMap<u64, Vec<[u8; 32]>>,
+}
+
+impl NoteFile {
+    /// Load `path`, or start empty if it doesn't exist yet; a sealed file needs `passphrase`
+    pub fn load(path: &Path, passphrase: Option<&str>) -> Result<Self, CliError> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let data = fs::read(path)?;
+        let data = if vault::is_sealed(&data) {
+            let passphrase = passphrase.ok_or_else(|| {
+                CliError::Config(format!("{} is encrypted; set {}", path.display(), PASSPHRASE_VAR))
+            })?;
+            vault::open(&data, passphrase.as_bytes())?
+        } else {
+            data
+        };
+        Self::try_from_slice(&data)
+            .map_err(|e| CliError::Config(format!("{}: {}", path.display(), e)))
+    }
+    
+    /// Write via a temporary file so an interrupted save never truncates the notes,
+    /// sealed under `passphrase` if one is given
+    pub fn save(&self, path: &Path, passphrase: Option<&str>) -> Result<(), CliError> {
+        let data = match passphrase {
+            Some(passphrase) => self.export(passphrase)?,
+            None => self.try_to_vec()?,
+        };
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, data)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+    
+    /// The whole file sealed under `passphrase`, for a backup
+    pub fn export(&self, passphrase: &str) -> Result<Vec<u8>, CliError> {
+        Ok(vault::seal(&self.try_to_vec()?, passphrase.as_bytes(), KdfParams::default())?)
+    }
+    
+    /// Open a backup made by `export`
+    pub fn import(sealed: &[u8], passphrase: &str) -> Result<Self, CliError> {
+        Self::try_from_slice(&vault::open(sealed, passphrase.as_bytes())?)
+            .map_err(|e| CliError::Config(format!("backup: {}", e)))
+    }
+    
+    /// Fold a restored backup in; a nonce pending in both keeps this file's points
+    pub fn merge(&mut self, backup: NoteFile) {
+        self.wallet.merge(&backup.wallet);
+        for (nonce, commitments) in backup.pending {
+            self.pending.entry(nonce).or_insert(commitments);
+        }
+    }
+    
+    pub fn note(&self, commitment: &[u8; 32]) -> Result<&Note, CliError> {
+        self.wallet.notes.get(commitment).ok_or_else(|| {
+            CliError::State(format!("no note for commitment {}", hex::encode(commitment)))
+        })
+    }
+    
+    /// Unspent notes not already in a pending withdrawal
+    pub fn available(&self) -> impl Iterator<Item = &Note> {
+        self.wallet
+            .unspent()
+            .filter(move |note| !self.pending.values().any(|c| c.contains(&note.commitment)))
+    }
+}
@@ -0,0 +1,171 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use fpp_wallet::{note::Note, sync::WalletState, vault};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{write_keypair_file, Keypair},
+};
+
+const PASSPHRASE_VAR: &str = "FPP_NOTES_PASSPHRASE";
+
+/// A scratch directory holding a signer keypair, removed on drop
+struct Scratch(PathBuf);
+
+impl Scratch {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("fpp-cli-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        write_keypair_file(&Keypair::new(), dir.join("id.json")).unwrap();
+        Self(dir)
+    }
+    
+    fn path(&self, name: &str) -> PathBuf {
+        self.0.join(name)
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Run `fpp` in `dir`, with none of its environment variables inherited
+fn fpp(dir: &Path, args: &[&str], passphrase: Option<&str>) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_fpp"));
+    command
+        .args(args)
+        .current_dir(dir)
+        .env_remove("FPP_PROGRAM_ID")
+        .env_remove(PASSPHRASE_VAR);
+    if let Some(passphrase) = passphrase {
+        command.env(PASSPHRASE_VAR, passphrase);
+    }
+    command.output().unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn help_prints_the_usage() {
+    let scratch = Scratch::new("help");
+    for args in [&["--help"][..], &[][..], &["--url", "http://localhost:8899"][..]] {
+        let output = fpp(&scratch.0, args, None);
+        assert!(output.status.success());
+        assert!(String::from_utf8_lossy(&output.stdout).starts_with("usage: fpp"));
+    }
+}
+
+#[test]
+fn bad_command_lines_exit_with_the_usage() {
+    let scratch = Scratch::new("usage");
+    let program_id = Pubkey::new_unique().to_string();
+    let id = program_id.as_str();
+    let cases: &[(&[&str], &str)] = &[
+        (&["status"], "--program-id is required"),
+        (&["--program-id", "nope", "status"], "invalid --program-id: nope"),
+        (&["--program-id", id, "frobnicate"], "unknown command `frobnicate`"),
+        (&["--program-id", id, "status", "extra"], "unexpected argument `extra`"),
+        (&["--program-id", id, "status", "--nonce", "1"], "`status` takes no --nonce"),
+        (&["--program-id", id, "--url", "a", "--url", "b", "status"], "--url given twice"),
+        (&["--program-id", id, "deposit"], "--amount is required"),
+        (&["--program-id", id, "cancel-withdraw", "--nonce"], "--nonce needs a value"),
+        (&["--program-id", id, "request-withdraw", "00ff"], "invalid commitment: 00ff"),
+        (&["--program-id", id, "export-notes"], "a backup path is required"),
+    ];
+    for (args, message) in cases {
+        let output = fpp(&scratch.0, args, None);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(stderr(&output).contains(message), "{:?}: {}", args, stderr(&output));
+        assert!(stderr(&output).contains("usage: fpp"));
+    }
+}
+
+#[test]
+fn ledger_signing_is_chosen_on_the_command_line() {
+    let scratch = Scratch::new("ledger");
+    let program_id = Pubkey::new_unique().to_string();
+    let with = |args: &[&str]| {
+        let mut all = vec!["--program-id", program_id.as_str()];
+        all.extend_from_slice(args);
+        all.push("status");
+        fpp(&scratch.0, &all, None)
+    };
+    
+    let conflict = with(&["--ledger", "--keypair", "id.json"]);
+    assert_eq!(conflict.status.code(), Some(2));
+    assert!(stderr(&conflict).contains("--keypair and --ledger conflict"));
+    let orphan = with(&["--ledger-key", "0/0"]);
+    assert_eq!(orphan.status.code(), Some(2));
+    assert!(stderr(&orphan).contains("--ledger-key needs --ledger"));
+    let invalid = with(&["--ledger", "--ledger-key", "x/y"]);
+    assert_eq!(invalid.status.code(), Some(2));
+    assert!(stderr(&invalid).contains("invalid --ledger-key: x/y"));
+    
+    // No device is attached here, and the key is never read from disk instead
+    let unplugged = with(&["--ledger", "--ledger-key", "1/0"]);
+    assert_eq!(unplugged.status.code(), Some(1));
+    assert!(stderr(&unplugged).contains("Signer Error"));
+}
+
+/// A plain note file holding one note and a pending withdrawal of it
+fn note_file(path: &Path, commitment: [u8; 32]) {
+    let mut wallet = WalletState::default();
+    wallet.add_note(Note {
+        commitment,
+        secret: [1u8; 32],
+        nullifier: [2u8; 32],
+        mass: 1,
+        leaf_index: Some(0),
+        tree_generation: 0,
+        created_epoch: 3,
+    });
+    let pending = BTreeMap::from([(0u64, vec![commitment])]);
+    fs::write(path, (wallet, pending).try_to_vec().unwrap()).unwrap();
+}
+
+#[test]
+fn notes_move_between_machines_as_encrypted_backups() {
+    let scratch = Scratch::new("backup");
+    let program_id = Pubkey::new_unique().to_string();
+    note_file(&scratch.path("laptop.bin"), [7u8; 32]);
+    let run = |notes: &str, command: &[&str], passphrase| {
+        let mut args = vec!["--program-id", program_id.as_str(), "--keypair", "id.json"];
+        args.extend_from_slice(&["--notes", notes]);
+        args.extend_from_slice(command);
+        fpp(&scratch.0, &args, passphrase)
+    };
+    
+    let unencrypted = run("laptop.bin", &["export-notes", "backup.bin"], None);
+    assert_eq!(unencrypted.status.code(), Some(1));
+    assert!(stderr(&unencrypted).contains(PASSPHRASE_VAR));
+    
+    let exported = run("laptop.bin", &["export-notes", "backup.bin"], Some("hunter2"));
+    assert!(exported.status.success(), "{}", stderr(&exported));
+    let backup = fs::read(scratch.path("backup.bin")).unwrap();
+    assert!(vault::is_sealed(&backup));
+    
+    let wrong = run("phone.bin", &["import-notes", "backup.bin"], Some("hunter3"));
+    assert_eq!(wrong.status.code(), Some(1));
+    assert!(!scratch.path("phone.bin").exists());
+    
+    let imported = run("phone.bin", &["import-notes", "backup.bin"], Some("hunter2"));
+    assert!(imported.status.success(), "{}", stderr(&imported));
+    // With a passphrase set the note file itself is sealed too
+    let sealed = fs::read(scratch.path("phone.bin")).unwrap();
+    let (wallet, pending) = <(WalletState, BTreeMap<u64, Vec<[u8; 32]>>)>::try_from_slice(
+        &vault::open(&sealed, b"hunter2").unwrap(),
+    )
+    .unwrap();
+    assert_eq!(wallet.notes[&[7u8; 32]].created_epoch, 3);
+    assert_eq!(pending[&0], vec![[7u8; 32]]);
+}
@@ -0,0 +1,223 @@
+/**
+ * Minimal Borsh reader/writer used by the generated client
+ */
+
+import { PublicKey } from '@solana/web3.js';
+
+export class BorshWriter {
+    private buf: Buffer = Buffer.alloc(256);
+    private length = 0;
+
+    private reserve(size: number): void {
+        if (this.length + size <= this.buf.length) {
+            return;
+        }
+        const next = Buffer.alloc(Math.max(this.buf.length * 2, this.length + size));
+        this.buf.copy(next, 0, 0, this.length);
+        this.buf = next;
+    }
+
+    u8(v: number): void {
+        this.reserve(1);
+        this.buf.writeUInt8(v, this.length);
+        this.length += 1;
+    }
+
+    u16(v: number): void {
+        this.reserve(2);
+        this.buf.writeUInt16LE(v, this.length);
+        this.length += 2;
+    }
+
+    u32(v: number): void {
+        this.reserve(4);
+        this.buf.writeUInt32LE(v, this.length);
+        this.length += 4;
+    }
+
+    i8(v: number): void {
+        this.reserve(1);
+        this.buf.writeInt8(v, this.length);
+        this.length += 1;
+    }
+
+    i16(v: number): void {
+        this.reserve(2);
+        this.buf.writeInt16LE(v, this.length);
+        this.length += 2;
+    }
+
+    i32(v: number): void {
+        this.reserve(4);
+        this.buf.writeInt32LE(v, this.length);
+        this.length += 4;
+    }
+
+    u64(v: bigint): void {
+        this.reserve(8);
+        this.buf.writeBigUInt64LE(v, this.length);
+        this.length += 8;
+    }
+
+    i64(v: bigint): void {
+        this.reserve(8);
+        this.buf.writeBigInt64LE(v, this.length);
+        this.length += 8;
+    }
+
+    u128(v: bigint): void {
+        this.u64(v & BigInt('0xffffffffffffffff'));
+        this.u64(v >> BigInt(64));
+    }
+
+    i128(v: bigint): void {
+        this.u128(BigInt.asUintN(128, v));
+    }
+
+    bool(v: boolean): void {
+        this.u8(v ? 1 : 0);
+    }
+
+    string(v: string): void {
+        this.bytes(Buffer.from(v, 'utf8'));
+    }
+
+    fixedBytes(v: Uint8Array, length: number): void {
+        if (v.length !== length) {
+            throw new Error(`expected ${length} bytes, got ${v.length}`);
+        }
+        this.reserve(length);
+        this.buf.set(v, this.length);
+        this.length += length;
+    }
+
+    bytes(v: Uint8Array): void {
+        this.u32(v.length);
+        this.fixedBytes(v, v.length);
+    }
+
+    pubkey(v: PublicKey): void {
+        this.fixedBytes(v.toBytes(), 32);
+    }
+
+    fixedArray<T>(items: T[], length: number, write: (item: T) => void): void {
+        if (items.length !== length) {
+            throw new Error(`expected ${length} items, got ${items.length}`);
+        }
+        items.forEach(write);
+    }
+
+    vec<T>(items: T[], write: (item: T) => void): void {
+        this.u32(items.length);
+        items.forEach(write);
+    }
+
+    option<T>(value: T | null, write: (item: T) => void): void {
+        if (value === null) {
+            this.u8(0);
+        } else {
+            this.u8(1);
+            write(value);
+        }
+    }
+
+    toBuffer(): Buffer {
+        return Buffer.from(this.buf.subarray(0, this.length));
+    }
+}
+
+export class BorshReader {
+    private readonly buf: Buffer;
+    private offset = 0;
+
+    constructor(data: Uint8Array) {
+        this.buf = Buffer.from(data.buffer, data.byteOffset, data.byteLength);
+    }
+
+    private advance(size: number): number {
+        if (this.offset + size > this.buf.length) {
+            throw new Error('unexpected end of Borsh data');
+        }
+        const start = this.offset;
+        this.offset += size;
+        return start;
+    }
+
+    u8(): number {
+        return this.buf.readUInt8(this.advance(1));
+    }
+
+    u16(): number {
+        return this.buf.readUInt16LE(this.advance(2));
+    }
+
+    u32(): number {
+        return this.buf.readUInt32LE(this.advance(4));
+    }
+
+    i8(): number {
+        return this.buf.readInt8(this.advance(1));
+    }
+
+    i16(): number {
+        return this.buf.readInt16LE(this.advance(2));
+    }
+
+    i32(): number {
+        return this.buf.readInt32LE(this.advance(4));
+    }
+
+    u64(): bigint {
+        return this.buf.readBigUInt64LE(this.advance(8));
+    }
+
+    i64(): bigint {
+        return this.buf.readBigInt64LE(this.advance(8));
+    }
+
+    u128(): bigint {
+        const low = this.u64();
+        return (this.u64() << BigInt(64)) | low;
+    }
+
+    i128(): bigint {
+        return BigInt.asIntN(128, this.u128());
+    }
+
+    bool(): boolean {
+        const value = this.u8();
+        if (value > 1) {
+            throw new Error(`invalid bool ${value}`);
+        }
+        return value === 1;
+    }
+
+    string(): string {
+        return Buffer.from(this.bytes()).toString('utf8');
+    }
+
+    fixedBytes(length: number): Uint8Array {
+        const start = this.advance(length);
+        return new Uint8Array(this.buf.subarray(start, start + length));
+    }
+
+    bytes(): Uint8Array {
+        return this.fixedBytes(this.u32());
+    }
+
+    pubkey(): PublicKey {
+        return new PublicKey(this.fixedBytes(32));
+    }
+
+    fixedArray<T>(length: number, read: () => T): T[] {
+        return Array.from({ length }, read);
+    }
+
+    vec<T>(read: () => T): T[] {
+        return this.fixedArray(this.u32(), read);
+    }
+
+    option<T>(read: () => T): T | null {
+        return this.u8() === 0 ? null : read();
+    }
+}
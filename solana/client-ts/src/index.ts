@@ -0,0 +1,8 @@
+/**
+ * FPP Solana Client
+ * Instruction builders and account decoders generated from the program's
+ * Borsh schemas (see `solana/codegen`), so layouts never drift from Rust.
+ */
+
+export * from './borsh';
+export * from './generated';
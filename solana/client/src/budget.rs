@@ -0,0 +1,292 @@
+//! Compute budgets and priority fees.
+//!
+//! Without a `SetComputeUnitLimit` instruction a transaction gets 200k compute
+//! units per instruction, which a `PrivacyPayment` checking its ring signature
+//! in-program overruns long before its ring is full. Asking for the 1.4M
+//! maximum everywhere isn't free either: the priority fee is charged on the
+//! requested units, not the used ones. `compute_units` estimates what each
+//! instruction of a transaction needs from its decoded arguments, and
+//! `ComputeBudget` turns the total and a unit price into the two budget
+//! instructions. `PriorityFeePolicy` picks the price from recent
+//! prioritization fees; with the `rpc` feature, `Protocol::with_priority_fee`
+//! fetches those for the accounts the transaction write-locks and does both.
+
+use borsh::BorshDeserialize;
+use floating_point_protocol_solana::{
+    crypto::ring::{RingSignature, MAX_RING_SIZE},
+    instruction::FPPInstruction,
+};
+#[cfg(feature = "rpc")]
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_program::{instruction::Instruction, pubkey, pubkey::Pubkey};
+
+#[cfg(feature = "rpc")]
+use crate::ClientError;
+use crate::Protocol;
+
+/// The native compute budget program
+pub const COMPUTE_BUDGET_ID: Pubkey = pubkey!("ComputeBudget111111111111111111111111111111");
+
+/// The native ZK Token Proof program, which verifies `PrivacyPayment` range proofs
+pub const ZK_TOKEN_PROOF_ID: Pubkey = pubkey!("ZkTokenProof1111111111111111111111111111111");
+
+/// Most compute units a transaction can request
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// What the runtime grants an instruction when no limit is requested
+pub const DEFAULT_INSTRUCTION_UNITS: u32 = 200_000;
+
+/// Each budget instruction costs this much itself
+const BUDGET_INSTRUCTION_UNITS: u32 = 150;
+
+/// Any instruction of another program, except range proofs: token transfers
+/// and associated token account creation stay well below this
+const OTHER_PROGRAM_UNITS: u32 = 50_000;
+
+/// A batched range proof, up to the widest (`U256`) the program accepts
+const RANGE_PROOF_UNITS: u32 = 370_000;
+
+/// Fixed cost of an instruction: decoding, protocol state, PDA checks
+const BASE_UNITS: u32 = 40_000;
+
+/// Creating a point account (and its note account, for a payment output)
+const POINT_UNITS: u32 = 30_000;
+
+/// Creating a nullifier account and recording it in the filter and its shard
+const NULLIFIER_UNITS: u32 = 25_000;
+
+/// Checking a commitment opening proof in-program
+const OPENING_PROOF_UNITS: u32 = 60_000;
+
+/// One ring member of a ring signature, two curve multiplications in-program
+const RING_MEMBER_UNITS: u32 = 60_000;
+
+/// A Groth16 pairing check through the verifier program
+const GROTH16_UNITS: u32 = 450_000;
+
+/// Token transfer out of the treasury, fees and epoch stats
+const PAYOUT_UNITS: u32 = 60_000;
+
+/// Estimated compute units for one instruction of a transaction
+///
+/// Instructions of the program are costed from their arguments (ring size,
+/// points, nullifiers, proofs); those of other programs get a flat allowance.
+/// The estimates lean high, since an instruction that runs out of units fails.
+pub fn compute_units(program_id: &Pubkey, instruction: &Instruction) -> u32 {
+    if instruction.program_id == COMPUTE_BUDGET_ID {
+        return BUDGET_INSTRUCTION_UNITS;
+    }
+    if instruction.program_id == ZK_TOKEN_PROOF_ID {
+        return RANGE_PROOF_UNITS;
+    }
+    if instruction.program_id != *program_id {
+        return OTHER_PROGRAM_UNITS;
+    }
+    let Ok(decoded) = FPPInstruction::try_from_slice(&instruction.data) else {
+        return DEFAULT_INSTRUCTION_UNITS;
+    };
+    
+    let units = |count: usize, each: u32| (count as u32).saturating_mul(each);
+    match decoded {
+        FPPInstruction::Deposit {
+            commitments,
+            opening_proofs,
+            ..
+        } => BASE_UNITS
+            .saturating_add(units(commitments.len(), POINT_UNITS))
+            .saturating_add(units(opening_proofs.len(), OPENING_PROOF_UNITS)),
+        FPPInstruction::PrivacyPayment {
+            input_nullifiers,
+            output_commitments,
+            proof,
+            ring_signature,
+            ..
+        } => {
+            // A ring signature that doesn't decode fails anyway; cost the widest ring
+            let ring_members = RingSignature::try_from_slice(&ring_signature)
+                .map(|signature| signature.key_images.len() * signature.ring_size())
+                .unwrap_or(input_nullifiers.len() * MAX_RING_SIZE);
+            let proof_units = if proof.is_empty() { 0 } else { GROTH16_UNITS };
+            BASE_UNITS
+                .saturating_add(units(ring_members, RING_MEMBER_UNITS))
+                .saturating_add(units(input_nullifiers.len(), NULLIFIER_UNITS))
+                .saturating_add(units(output_commitments.len(), POINT_UNITS * 2))
+                .saturating_add(proof_units)
+        }
+        // One batched pairing check costs about a single proof's per pairing, `n + 3` of them
+        FPPInstruction::BatchPrivacyPayment { payments } => payments.iter().fold(
+            BASE_UNITS.saturating_add(units(payments.len() + 3, GROTH16_UNITS / 4)),
+            |total, payment| {
+                let inputs = payment.input_nullifiers.len();
+                let ring_members = RingSignature::try_from_slice(&payment.ring_signature)
+                    .map(|signature| signature.key_images.len() * signature.ring_size())
+                    .unwrap_or(inputs * MAX_RING_SIZE);
+                total
+                    .saturating_add(units(ring_members, RING_MEMBER_UNITS))
+                    .saturating_add(units(inputs, NULLIFIER_UNITS))
+                    .saturating_add(units(payment.output_commitments.len(), POINT_UNITS * 2))
+            },
+        ),
+        // Spend signatures are one-row rings, a member per point
+        FPPInstruction::RequestWithdrawal { nullifiers, .. }
+        | FPPInstruction::RelayWithdrawal { nullifiers, .. } => BASE_UNITS
+            .saturating_add(units(nullifiers.len(), NULLIFIER_UNITS + RING_MEMBER_UNITS)),
+        FPPInstruction::CompleteWithdrawal { .. } | FPPInstruction::CompleteRelayedWithdrawal => {
+            BASE_UNITS.saturating_add(PAYOUT_UNITS)
+        }
+        FPPInstruction::CancelWithdrawal { .. } => BASE_UNITS,
+        // Takes as many rows as the budget allows
+        FPPInstruction::VerifyStagedProof { .. } => MAX_COMPUTE_UNIT_LIMIT,
+        _ => DEFAULT_INSTRUCTION_UNITS,
+    }
+}
+
+/// The compute budget instructions a transaction starts with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComputeBudget {
+    pub unit_limit: u32,
+    /// Priority fee in micro-lamports per requested unit
+    pub unit_price: u64,
+}
+
+impl ComputeBudget {
+    /// A limit covering every instruction in `instructions` and the budget
+    /// instructions themselves, capped at `MAX_COMPUTE_UNIT_LIMIT`
+    pub fn estimate(program_id: &Pubkey, instructions: &[Instruction], unit_price: u64) -> Self {
+        let unit_limit = instructions
+            .iter()
+            .map(|instruction| compute_units(program_id, instruction))
+            .fold(BUDGET_INSTRUCTION_UNITS * 2, u32::saturating_add)
+            .min(MAX_COMPUTE_UNIT_LIMIT);
+        Self {
+            unit_limit,
+            unit_price,
+        }
+    }
+    
+    /// `SetComputeUnitLimit`, then `SetComputeUnitPrice` when there is a price
+    pub fn instructions(&self) -> Vec<Instruction> {
+        let mut instructions = vec![set_compute_unit_limit(self.unit_limit)];
+        if self.unit_price > 0 {
+            instructions.push(set_compute_unit_price(self.unit_price));
+        }
+        instructions
+    }
+    
+    /// Priority fee in lamports, on top of the signature fee
+    pub fn priority_fee(&self) -> u64 {
+        let micro_lamports = self.unit_limit as u128 * self.unit_price as u128;
+        ((micro_lamports + 999_999) / 1_000_000).min(u64::MAX as u128) as u64
+    }
+}
+
+/// `ComputeBudgetInstruction::SetComputeUnitLimit`
+pub fn set_compute_unit_limit(units: u32) -> Instruction {
+    let mut data = vec![2];
+    data.extend_from_slice(&units.to_le_bytes());
+    Instruction::new_with_bytes(COMPUTE_BUDGET_ID, &data, Vec::new())
+}
+
+/// `ComputeBudgetInstruction::SetComputeUnitPrice`, in micro-lamports
+pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    let mut data = vec![3];
+    data.extend_from_slice(&micro_lamports.to_le_bytes());
+    Instruction::new_with_bytes(COMPUTE_BUDGET_ID, &data, Vec::new())
+}
+
+/// How a unit price is picked from recent prioritization fees
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriorityFeePolicy {
+    /// Percentile of the recent fees to pay, 0 to 100
+    pub percentile: u8,
+    /// Micro-lamports per unit paid even when recent blocks were uncontested
+    pub min_unit_price: u64,
+    /// Ceiling, so a fee spike can't drain the payer
+    pub max_unit_price: u64,
+}
+
+impl Default for PriorityFeePolicy {
+    /// The 75th percentile, at most 0.1 lamports per unit (0.14 SOL at the maximum limit)
+    fn default() -> Self {
+        Self {
+            percentile: 75,
+            min_unit_price: 0,
+            max_unit_price: 100_000,
+        }
+    }
+}
+
+impl PriorityFeePolicy {
+    /// The unit price to pay given recent per-slot fees, as `getRecentPrioritizationFees`
+    /// reports them (the lowest fee that landed in each slot)
+    pub fn unit_price(&self, recent_fees: &[u64]) -> u64 {
+        let mut fees = recent_fees.to_vec();
+        fees.sort_unstable();
+        let price = match fees.len() {
+            0 => 0,
+            len => fees[(len - 1) * self.percentile.min(100) as usize / 100],
+        };
+        price.clamp(self.min_unit_price, self.max_unit_price.max(self.min_unit_price))
+    }
+}
+
+/// Accounts `instructions` write-lock, whose contention sets the fee that lands;
+/// at most the 128 `getRecentPrioritizationFees` accepts
+pub fn write_locked_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts: Vec<Pubkey> = Vec::new();
+    for meta in instructions.iter().flat_map(|instruction| &instruction.accounts) {
+        if meta.is_writable && !accounts.contains(&meta.pubkey) {
+            accounts.push(meta.pubkey);
+        }
+    }
+    accounts.truncate(128);
+    accounts
+}
+
+impl Protocol {
+    /// `instructions` after the budget instructions they need, at `unit_price`
+    ///
+    /// The budget goes first, so relative offsets between the given instructions
+    /// (`range_proof_offset`) are unchanged.
+    pub fn with_compute_budget(
+        &self,
+        instructions: Vec<Instruction>,
+        unit_price: u64,
+    ) -> Vec<Instruction> {
+        let budget = ComputeBudget::estimate(&self.program_id, &instructions, unit_price);
+        let mut with_budget = budget.instructions();
+        with_budget.extend(instructions);
+        with_budget
+    }
+    
+    /// A unit price for `instructions`, from the fees recently paid to write-lock
+    /// the same accounts
+    #[cfg(feature = "rpc")]
+    pub async fn estimate_unit_price(
+        &self,
+        rpc: &RpcClient,
+        instructions: &[Instruction],
+        policy: PriorityFeePolicy,
+    ) -> Result<u64, ClientError> {
+        let recent_fees: Vec<u64> = rpc
+            .get_recent_prioritization_fees(&write_locked_accounts(instructions))
+            .await
+            .map_err(|e| ClientError::Rpc(e.to_string()))?
+            .into_iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect();
+        Ok(policy.unit_price(&recent_fees))
+    }
+    
+    /// `with_compute_budget`, priced by `estimate_unit_price`
+    #[cfg(feature = "rpc")]
+    pub async fn with_priority_fee(
+        &self,
+        rpc: &RpcClient,
+        instructions: Vec<Instruction>,
+        policy: PriorityFeePolicy,
+    ) -> Result<Vec<Instruction>, ClientError> {
+        let unit_price = self.estimate_unit_price(rpc, &instructions, policy).await?;
+        Ok(self.with_compute_budget(instructions, unit_price))
+    }
+}
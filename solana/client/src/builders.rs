@@ -0,0 +1,368 @@
+//! Builders for the user-facing instructions.
+//!
+//! Builders validate on `build`, so a mistake surfaces as a `ClientError`
+//! naming the input instead of a failed transaction.
+
+use borsh::BorshSerialize;
+use floating_point_protocol_solana::{
+    crypto::{
+        opening::OpeningProof,
+        ring::{self, RingSignature},
+    },
+    instruction,
+    state::{EncryptedNote, FloatingPoint, WithdrawalRequest, POINT_VALUE},
+};
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::{ClientError, MintAccounts, Protocol, SpendProof};
+
+/// Builds a `Deposit`; see `Protocol::deposit`
+pub struct DepositBuilder<'a> {
+    protocol: &'a Protocol,
+    user: Pubkey,
+    denomination: u64,
+    commitments: Vec<[u8; 32]>,
+    opening_proofs: Vec<OpeningProof>,
+    mint: MintAccounts,
+    user_token: Option<Pubkey>,
+    referrer: Option<Pubkey>,
+    epoch: Option<u64>,
+}
+
+impl<'a> DepositBuilder<'a> {
+    pub(crate) fn new(
+        protocol: &'a Protocol,
+        user: Pubkey,
+        denomination: u64,
+        commitments: Vec<[u8; 32]>,
+    ) -> Self {
+        Self {
+            protocol,
+            user,
+            denomination,
+            commitments,
+            opening_proofs: Vec::new(),
+            mint: protocol.usdt,
+            user_token: None,
+            referrer: None,
+            epoch: None,
+        }
+    }
+    
+    /// Deposit a supported mint other than USDT
+    pub fn mint(mut self, mint: MintAccounts) -> Self {
+        self.mint = mint;
+        self
+    }
+    
+    /// Pay from this token account instead of the user's associated one
+    pub fn user_token(mut self, user_token: Pubkey) -> Self {
+        self.user_token = Some(user_token);
+        self
+    }
+    
+    /// One opening proof per commitment, in the same order
+    pub fn opening_proofs(mut self, opening_proofs: Vec<OpeningProof>) -> Self {
+        self.opening_proofs = opening_proofs;
+        self
+    }
+    
+    /// Credit `referrer` with its share of the deposit fee
+    pub fn referrer(mut self, referrer: Pubkey) -> Self {
+        self.referrer = Some(referrer);
+        self
+    }
+    
+    /// The cluster's current epoch, whose stats account tallies the deposit
+    pub fn epoch(mut self, epoch: u64) -> Self {
+        self.epoch = Some(epoch);
+        self
+    }
+    
+    pub fn build(self) -> Result<Instruction, ClientError> {
+        if self.commitments.is_empty() {
+            return Err(ClientError::InvalidInput("no commitments".into()));
+        }
+        if self.denomination == 0 || self.denomination % POINT_VALUE != 0 {
+            return Err(ClientError::InvalidInput(format!(
+                "denomination {} is not a multiple of the point value",
+                self.denomination
+            )));
+        }
+        if !self.opening_proofs.is_empty() && self.opening_proofs.len() != self.commitments.len() {
+            return Err(ClientError::InvalidInput(format!(
+                "{} opening proofs for {} commitments",
+                self.opening_proofs.len(),
+                self.commitments.len()
+            )));
+        }
+        let amount = self
+            .denomination
+            .checked_mul(self.commitments.len() as u64)
+            .ok_or_else(|| ClientError::InvalidInput("amount overflows".into()))?;
+        
+        let protocol = self.protocol;
+        let user_token = self
+            .user_token
+            .unwrap_or_else(|| self.mint.token_account(&self.user));
+        if self.referrer == Some(self.user) {
+            return Err(ClientError::InvalidInput("a user can't refer themselves".into()));
+        }
+        let epoch = self
+            .epoch
+            .ok_or_else(|| ClientError::InvalidInput("no epoch".into()))?;
+        Ok(instruction::deposit_with_referrer(
+            &protocol.program_id,
+            &self.user,
+            &user_token,
+            &protocol.treasury_token(&self.mint),
+            &protocol.protocol_state,
+            &self.mint.mint,
+            &self.mint.token_program,
+            amount,
+            self.denomination,
+            self.commitments,
+            self.opening_proofs,
+            self.referrer,
+            epoch,
+        )?)
+    }
+}
+
+/// Builds a `RequestWithdrawal`; see `Protocol::request_withdrawal`
+///
+/// Nullifiers are the spend signature's key images, so they aren't passed separately.
+pub struct RequestWithdrawalBuilder<'a> {
+    protocol: &'a Protocol,
+    user: Pubkey,
+    nonce: u64,
+    points: Vec<Pubkey>,
+    spend_signature: Option<RingSignature>,
+}
+
+impl<'a> RequestWithdrawalBuilder<'a> {
+    pub(crate) fn new(protocol: &'a Protocol, user: Pubkey, nonce: u64) -> Self {
+        Self {
+            protocol,
+            user,
+            nonce,
+            points: Vec::new(),
+            spend_signature: None,
+        }
+    }
+    
+    /// Withdraw `point`; the spend signature signs for the points in the order they are added
+    pub fn point(mut self, point: Pubkey) -> Self {
+        self.points.push(point);
+        self
+    }
+    
+    /// What the points' keys sign: `WithdrawalRequest::spend_message` of the request
+    pub fn spend_message(&self) -> [u8; 32] {
+        WithdrawalRequest::spend_message(&self.protocol.withdrawal_request(&self.user, self.nonce))
+    }
+    
+    /// The points' one-row ring signature over `spend_message`
+    pub fn spend_signature(mut self, signature: RingSignature) -> Self {
+        self.spend_signature = Some(signature);
+        self
+    }
+    
+    pub fn build(self) -> Result<Instruction, ClientError> {
+        if self.points.is_empty() {
+            return Err(ClientError::InvalidInput("no points to withdraw".into()));
+        }
+        for (i, point) in self.points.iter().enumerate() {
+            if self.points[..i].contains(point) {
+                return Err(ClientError::InvalidInput(format!("point {} is listed twice", point)));
+            }
+        }
+        let signature = self
+            .spend_signature
+            .ok_or_else(|| ClientError::InvalidInput("no spend signature".into()))?;
+        if signature.key_images.len() != self.points.len() || signature.ring_size() != 1 {
+            return Err(ClientError::InvalidInput(format!(
+                "spend signature must be one row over the {} points",
+                self.points.len()
+            )));
+        }
+        
+        let nullifiers = signature.key_images.clone();
+        let spend_signature = signature
+            .try_to_vec()
+            .map_err(|e| ClientError::Encoding(e.to_string()))?;
+        Ok(instruction::request_withdrawal(
+            &self.protocol.program_id,
+            &self.user,
+            &self.protocol.protocol_state,
+            self.points,
+            nullifiers,
+            self.nonce,
+            spend_signature,
+        )?)
+    }
+}
+
+/// Builds a `PrivacyPayment`; see `Protocol::privacy_payment`
+///
+/// Nullifiers are the ring signature's key images and output point accounts are
+/// derived from their commitments, so neither is passed separately.
+pub struct PrivacyPaymentBuilder<'a> {
+    protocol: &'a Protocol,
+    sender: Pubkey,
+    recipient: Pubkey,
+    merkle_root: [u8; 32],
+    ring: Option<(RingSignature, Vec<Pubkey>)>,
+    outputs: Vec<([u8; 32], Vec<u8>)>,
+    proof: Vec<u8>,
+    range_proof_offset: Option<i8>,
+    verifier_program: Option<Pubkey>,
+    archived_tree: Option<u32>,
+}
+
+impl<'a> PrivacyPaymentBuilder<'a> {
+    pub(crate) fn new(
+        protocol: &'a Protocol,
+        sender: Pubkey,
+        recipient: Pubkey,
+        merkle_root: [u8; 32],
+    ) -> Self {
+        Self {
+            protocol,
+            sender,
+            recipient,
+            merkle_root,
+            ring: None,
+            outputs: Vec::new(),
+            proof: Vec::new(),
+            range_proof_offset: None,
+            verifier_program: None,
+            archived_tree: None,
+        }
+    }
+    
+    /// The signature over the inputs and the point accounts of its ring, row-major
+    pub fn ring_signature(mut self, signature: RingSignature, members: Vec<Pubkey>) -> Self {
+        self.ring = Some((signature, members));
+        self
+    }
+    
+    /// Pay into `commitment`, with its opening encrypted to the recipient
+    ///
+    /// A payment has one output, holding everything its inputs held.
+    pub fn output(mut self, commitment: [u8; 32], encrypted_note: Vec<u8>) -> Self {
+        self.outputs.push((commitment, encrypted_note));
+        self
+    }
+    
+    pub fn proof(mut self, proof: Vec<u8>) -> Self {
+        self.proof = proof;
+        self
+    }
+    
+    /// Take the root, ring, outputs and proof from an off-chain `SpendProof`
+    pub fn spend_proof(mut self, spend: SpendProof) -> Self {
+        let program_id = self.protocol.program_id;
+        let members = spend
+            .ring
+            .iter()
+            .map(|commitment| FloatingPoint::find_address(commitment, &program_id).0)
+            .collect();
+        self.merkle_root = spend.merkle_root;
+        self.ring = Some((spend.ring_signature, members));
+        self.outputs = spend
+            .output_commitments
+            .into_iter()
+            .zip(spend.encrypted_notes)
+            .collect();
+        self.proof = spend.proof;
+        self
+    }
+    
+    /// Relative index of the batched range proof instruction over the outputs
+    pub fn range_proof_offset(mut self, offset: i8) -> Self {
+        self.range_proof_offset = Some(offset);
+        self
+    }
+    
+    pub fn verifier_program(mut self, verifier_program: Pubkey) -> Self {
+        self.verifier_program = Some(verifier_program);
+        self
+    }
+    
+    /// `merkle_root` is the final root of this archived tree generation
+    pub fn archived_tree(mut self, generation: u32) -> Self {
+        self.archived_tree = Some(generation);
+        self
+    }
+    
+    pub fn build(self) -> Result<Instruction, ClientError> {
+        let (signature, members) = self
+            .ring
+            .ok_or_else(|| ClientError::InvalidInput("no ring signature".into()))?;
+        let inputs = signature.key_images.len();
+        let ring_size = signature.ring_size();
+        if inputs == 0 || ring_size == 0 || ring_size > ring::MAX_RING_SIZE {
+            return Err(ClientError::InvalidInput("ring signature has no valid ring".into()));
+        }
+        if members.len() != ring_size * inputs {
+            return Err(ClientError::InvalidInput(format!(
+                "{} ring members for {} inputs of ring size {}",
+                members.len(),
+                inputs,
+                ring_size
+            )));
+        }
+        if self.outputs.is_empty() {
+            return Err(ClientError::InvalidInput("no outputs".into()));
+        }
+        if self.outputs.len() > 1 {
+            return Err(ClientError::InvalidInput("a payment has a single output".into()));
+        }
+        if self
+            .outputs
+            .iter()
+            .any(|(_, note)| note.is_empty() || note.len() > EncryptedNote::MAX_CIPHERTEXT_LEN)
+        {
+            return Err(ClientError::InvalidInput(format!(
+                "encrypted notes must be 1 to {} bytes",
+                EncryptedNote::MAX_CIPHERTEXT_LEN
+            )));
+        }
+        let range_proof_offset = self
+            .range_proof_offset
+            .ok_or_else(|| ClientError::InvalidInput("no range proof offset".into()))?;
+        let verifier_program = self
+            .verifier_program
+            .ok_or_else(|| ClientError::InvalidInput("no verifier program".into()))?;
+        
+        let program_id = &self.protocol.program_id;
+        let output_points: Vec<Pubkey> = self
+            .outputs
+            .iter()
+            .map(|(commitment, _)| FloatingPoint::find_address(commitment, program_id).0)
+            .collect();
+        let input_nullifiers = signature.key_images.clone();
+        let ring_signature = signature
+            .try_to_vec()
+            .map_err(|e| ClientError::Encoding(e.to_string()))?;
+        let (output_commitments, encrypted_notes) = self.outputs.into_iter().unzip();
+        Ok(instruction::privacy_payment(
+            program_id,
+            &self.sender,
+            &self.protocol.protocol_state,
+            &self.recipient,
+            &members,
+            &output_points,
+            &verifier_program,
+            self.merkle_root,
+            input_nullifiers,
+            output_commitments,
+            self.proof,
+            ring_signature,
+            range_proof_offset,
+            encrypted_notes,
+            self.archived_tree,
+        )?)
+    }
+}
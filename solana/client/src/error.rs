@@ -0,0 +1,17 @@
+use solana_program::program_error::ProgramError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error("Invalid Input: {0}")]
+    InvalidInput(String),
+    
+    #[error("Encoding Error: {0}")]
+    Encoding(String),
+    
+    #[error("RPC Error: {0}")]
+    Rpc(String),
+    
+    #[error("Program Error: {0}")]
+    Program(#[from] ProgramError),
+}
@@ -0,0 +1,140 @@
+//! Typed instruction builders for the Floating Point Protocol.
+//!
+//! `Protocol` holds the addresses every instruction of a deployment shares.
+//! Each builder takes only what varies per call, derives the rest (token
+//! accounts, and PDAs via `floating_point_protocol_solana::pda`) and checks
+//! its inputs against what the program will accept, then hands off to
+//! `floating_point_protocol_solana::instruction`, which fixes the account
+//! order documented on `FPPInstruction`.
+//!
+//! `Protocol::with_compute_budget` prepends the compute budget a transaction's
+//! instructions need; with the `rpc` feature, `Protocol::with_priority_fee`
+//! also prices it from recent prioritization fees.
+//!
+//! With the `subscribe` feature, `Protocol::subscribe_events` streams the
+//! program's events and protocol state over websockets.
+
+pub mod budget;
+pub mod builders;
+pub mod error;
+#[cfg(feature = "subscribe")]
+pub mod subscribe;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use floating_point_protocol_solana::{
+    crypto::ring::RingSignature,
+    pda,
+    state::{EpochStats, ProtocolState},
+};
+use solana_program::pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+pub use budget::{ComputeBudget, PriorityFeePolicy};
+pub use builders::{DepositBuilder, PrivacyPaymentBuilder, RequestWithdrawalBuilder};
+pub use error::ClientError;
+#[cfg(feature = "subscribe")]
+pub use subscribe::{EventStream, ProtocolUpdate, SubscribeConfig};
+
+/// What a spender proves off chain for a `PrivacyPayment`, attached with
+/// `PrivacyPaymentBuilder::spend_proof`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct SpendProof {
+    pub merkle_root: [u8; 32],
+    /// Commitments of the ring's point accounts, row-major
+    pub ring: Vec<[u8; 32]>,
+    /// Its key images are the input nullifiers
+    pub ring_signature: RingSignature,
+    pub output_commitments: Vec<[u8; 32]>,
+    /// One per output, in the same order
+    pub encrypted_notes: Vec<Vec<u8>>,
+    pub proof: Vec<u8>,
+}
+
+/// A mint and the token program that owns it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MintAccounts {
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl MintAccounts {
+    /// Associated token account of `owner` for this mint
+    pub fn token_account(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(owner, &self.mint, &self.token_program)
+    }
+}
+
+/// Addresses shared by every instruction of one protocol deployment
+#[derive(Clone, Debug)]
+pub struct Protocol {
+    pub program_id: Pubkey,
+    pub protocol_state: Pubkey,
+    /// The primary mint, used unless a builder is given another
+    pub usdt: MintAccounts,
+}
+
+impl Protocol {
+    pub fn new(program_id: Pubkey, usdt: MintAccounts) -> Self {
+        Self {
+            program_id,
+            protocol_state: pda::find_protocol_state(&program_id).0,
+            usdt,
+        }
+    }
+    
+    /// Take the primary mint from a fetched protocol state account
+    pub fn from_state(program_id: Pubkey, state: &ProtocolState) -> Self {
+        Self::new(
+            program_id,
+            MintAccounts {
+                mint: state.usdt_mint,
+                token_program: state.token_program,
+            },
+        )
+    }
+    
+    /// The treasury authority PDA, which owns every treasury token account
+    pub fn treasury(&self) -> Pubkey {
+        ProtocolState::find_treasury_authority(&self.program_id).0
+    }
+    
+    /// Treasury token account for `mint`
+    pub fn treasury_token(&self, mint: &MintAccounts) -> Pubkey {
+        mint.token_account(&self.treasury())
+    }
+    
+    /// Deposit one point of `denomination` per commitment, each at its commitment's PDA
+    pub fn deposit(
+        &self,
+        user: Pubkey,
+        denomination: u64,
+        commitments: Vec<[u8; 32]>,
+    ) -> DepositBuilder<'_> {
+        DepositBuilder::new(self, user, denomination, commitments)
+    }
+    
+    /// Withdraw points into the request `user` opens under `nonce`; add them with `point`
+    pub fn request_withdrawal(&self, user: Pubkey, nonce: u64) -> RequestWithdrawalBuilder<'_> {
+        RequestWithdrawalBuilder::new(self, user, nonce)
+    }
+    
+    /// Address of the withdrawal request `user` opened under `nonce`
+    pub fn withdrawal_request(&self, user: &Pubkey, nonce: u64) -> Pubkey {
+        pda::find_withdrawal_request(user, nonce, &self.program_id).0
+    }
+    
+    /// Address of the statistics account for `epoch`, which exists once anything happened in it
+    pub fn epoch_stats(&self, epoch: u64) -> Pubkey {
+        EpochStats::find_address(epoch, &self.program_id).0
+    }
+    
+    /// Spend ring-signed inputs proven against `merkle_root` into new outputs
+    pub fn privacy_payment(
+        &self,
+        sender: Pubkey,
+        recipient: Pubkey,
+        merkle_root: [u8; 32],
+    ) -> PrivacyPaymentBuilder<'_> {
+        PrivacyPaymentBuilder::new(self, sender, recipient, merkle_root)
+    }
+}
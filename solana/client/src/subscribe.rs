@@ -0,0 +1,358 @@
+//! Live protocol events over websockets.
+//!
+//! `Protocol::subscribe_events` follows the program the way the indexer does:
+//! each session subscribes to the program's logs and the protocol state
+//! account, backfills every transaction since the last one delivered, then
+//! streams live notifications. A dropped session reconnects after a delay and
+//! backfills what it missed; transactions seen in the overlap are delivered
+//! once.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    str::FromStr,
+    time::Duration,
+};
+
+use base64::Engine;
+use borsh::BorshDeserialize;
+use floating_point_protocol_solana::{events::FPPEvent, state::ProtocolState};
+use futures_util::StreamExt;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::{
+        RpcAccountInfoConfig, RpcTransactionConfig, RpcTransactionLogsConfig,
+        RpcTransactionLogsFilter,
+    },
+};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, signature::Signature};
+use solana_transaction_status::UiTransactionEncoding;
+use tokio::sync::mpsc;
+
+use crate::{ClientError, Protocol};
+
+/// Signatures per `getSignaturesForAddress` page
+const SIGNATURE_PAGE: usize = 1000;
+
+/// Recent signatures remembered to drop duplicates between backfill and live
+const SEEN_SIGNATURES: usize = 4096;
+
+/// Updates buffered before the follower waits on the consumer
+const CHANNEL_CAPACITY: usize = 1024;
+
+const DATA_LOG_PREFIX: &str = "Program data: ";
+
+/// Where and how `subscribe_events` connects
+#[derive(Debug, Clone)]
+pub struct SubscribeConfig {
+    pub rpc_url: String,
+    pub ws_url: String,
+    pub commitment: CommitmentConfig,
+    /// Backfill the transactions after this one first; `None` starts with live notifications
+    pub since: Option<Signature>,
+    /// Pause before reconnecting after a dropped session
+    pub retry_delay: Duration,
+}
+
+impl SubscribeConfig {
+    pub fn new(rpc_url: impl Into<String>, ws_url: impl Into<String>) -> Self {
+        Self {
+            rpc_url: rpc_url.into(),
+            ws_url: ws_url.into(),
+            commitment: CommitmentConfig::confirmed(),
+            since: None,
+            retry_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+/// One update from `EventStream::next`
+#[derive(Debug, Clone)]
+pub enum ProtocolUpdate {
+    /// An event a successful transaction emitted, in emission order
+    Event {
+        signature: Signature,
+        slot: u64,
+        event: FPPEvent,
+    },
+    /// The protocol state account changed (pause flags, fees, roots, ...)
+    State { slot: u64, state: Box<ProtocolState> },
+    /// The session dropped; the stream reconnects and backfills what it misses meanwhile
+    Disconnected { error: String },
+}
+
+/// Updates from a background follower, which stops when the stream is dropped
+pub struct EventStream {
+    updates: mpsc::Receiver<ProtocolUpdate>,
+}
+
+impl EventStream {
+    /// The next update; `None` only once the follower has stopped
+    pub async fn next(&mut self) -> Option<ProtocolUpdate> {
+        self.updates.recv().await
+    }
+}
+
+impl Protocol {
+    /// Follow the program's events and protocol state from a background task
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn subscribe_events(&self, config: SubscribeConfig) -> EventStream {
+        let (sender, updates) = mpsc::channel(CHANNEL_CAPACITY);
+        let follower = Follower {
+            rpc: RpcClient::new_with_commitment(config.rpc_url.clone(), config.commitment),
+            program_id: self.program_id,
+            protocol_state: self.protocol_state,
+            cursor: config.since,
+            seen: SeenSignatures::default(),
+            sender,
+            config,
+        };
+        tokio::spawn(follower.run());
+        EventStream { updates }
+    }
+}
+
+/// Decode one log line, if it carries an event of the program
+pub fn parse_log(line: &str) -> Option<FPPEvent> {
+    let fields = line
+        .strip_prefix(DATA_LOG_PREFIX)?
+        .split_whitespace()
+        .map(|field| base64::engine::general_purpose::STANDARD.decode(field))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    FPPEvent::from_log_data(&fields)
+}
+
+/// Bounded set of recently delivered signatures
+#[derive(Default)]
+struct SeenSignatures {
+    set: HashSet<Signature>,
+    order: VecDeque<Signature>,
+}
+
+impl SeenSignatures {
+    /// Remember `signature`; false if it was already delivered
+    fn insert(&mut self, signature: Signature) -> bool {
+        if !self.set.insert(signature) {
+            return false;
+        }
+        self.order.push_back(signature);
+        if self.order.len() > SEEN_SIGNATURES {
+            if let Some(oldest) = self.order.pop_front() {
+                self.set.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Why a session ended
+enum SessionEnd {
+    /// The stream was dropped; stop following
+    Closed,
+    Failed(ClientError),
+}
+
+impl From<ClientError> for SessionEnd {
+    fn from(e: ClientError) -> Self {
+        SessionEnd::Failed(e)
+    }
+}
+
+struct Follower {
+    rpc: RpcClient,
+    config: SubscribeConfig,
+    program_id: Pubkey,
+    protocol_state: Pubkey,
+    /// Last transaction delivered, which the next backfill starts after
+    cursor: Option<Signature>,
+    seen: SeenSignatures,
+    sender: mpsc::Sender<ProtocolUpdate>,
+}
+
+impl Follower {
+    async fn run(mut self) {
+        loop {
+            let error = match self.session().await {
+                SessionEnd::Closed => return,
+                SessionEnd::Failed(e) => e,
+            };
+            let update = ProtocolUpdate::Disconnected {
+                error: error.to_string(),
+            };
+            if self.sender.send(update).await.is_err() {
+                return;
+            }
+            tokio::time::sleep(self.config.retry_delay).await;
+        }
+    }
+    
+    async fn session(&mut self) -> SessionEnd {
+        match self.follow().await {
+            Ok(()) => SessionEnd::Failed(rpc_error("subscription closed")),
+            Err(end) => end,
+        }
+    }
+    
+    /// Subscribe, backfill, then stream until the subscription or the consumer goes away
+    async fn follow(&mut self) -> Result<(), SessionEnd> {
+        let pubsub = PubsubClient::new(&self.config.ws_url).await.map_err(rpc_error)?;
+        let (mut logs, logs_unsubscribe) = pubsub
+            .logs_subscribe(
+                RpcTransactionLogsFilter::Mentions(vec![self.program_id.to_string()]),
+                RpcTransactionLogsConfig {
+                    commitment: Some(self.config.commitment),
+                },
+            )
+            .await
+            .map_err(rpc_error)?;
+        let (mut accounts, accounts_unsubscribe) = pubsub
+            .account_subscribe(
+                &self.protocol_state,
+                Some(RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(self.config.commitment),
+                    ..RpcAccountInfoConfig::default()
+                }),
+            )
+            .await
+            .map_err(rpc_error)?;
+    
+        // Notifications arriving meanwhile wait in the subscriptions
+        self.backfill().await?;
+    
+        loop {
+            tokio::select! {
+                notification = logs.next() => {
+                    let Some(notification) = notification else {
+                        break;
+                    };
+                    let value = notification.value;
+                    if value.err.is_some() {
+                        continue;
+                    }
+                    let signature = Signature::from_str(&value.signature)
+                        .map_err(|e| ClientError::Encoding(e.to_string()))?;
+                    self.deliver(signature, notification.context.slot, &value.logs).await?;
+                }
+                notification = accounts.next() => {
+                    let Some(notification) = notification else {
+                        break;
+                    };
+                    let state = notification
+                        .value
+                        .decode::<Account>()
+                        .and_then(|account| {
+                            ProtocolState::deserialize(&mut &account.data[..]).ok()
+                        });
+                    if let Some(state) = state {
+                        self.send(ProtocolUpdate::State {
+                            slot: notification.context.slot,
+                            state: Box::new(state),
+                        })
+                        .await?;
+                    }
+                }
+            }
+        }
+        logs_unsubscribe().await;
+        accounts_unsubscribe().await;
+        Ok(())
+    }
+    
+    /// Deliver every transaction after the cursor, oldest first
+    async fn backfill(&mut self) -> Result<(), SessionEnd> {
+        let Some(until) = self.cursor else {
+            return Ok(());
+        };
+        let mut statuses = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self
+                .rpc
+                .get_signatures_for_address_with_config(
+                    &self.program_id,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until: Some(until),
+                        limit: Some(SIGNATURE_PAGE),
+                        commitment: Some(self.config.commitment),
+                    },
+                )
+                .await
+                .map_err(rpc_error)?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            before = Some(
+                Signature::from_str(&last.signature)
+                    .map_err(|e| ClientError::Encoding(e.to_string()))?,
+            );
+            let full = page.len() == SIGNATURE_PAGE;
+            statuses.extend(page);
+            if !full {
+                break;
+            }
+        }
+    
+        for status in statuses.iter().rev().filter(|status| status.err.is_none()) {
+            let signature = Signature::from_str(&status.signature)
+                .map_err(|e| ClientError::Encoding(e.to_string()))?;
+            let tx = self
+                .rpc
+                .get_transaction_with_config(
+                    &signature,
+                    RpcTransactionConfig {
+                        encoding: Some(UiTransactionEncoding::Base64),
+                        commitment: Some(self.config.commitment),
+                        max_supported_transaction_version: Some(0),
+                    },
+                )
+                .await
+                .map_err(rpc_error)?;
+            let logs: Vec<String> = tx
+                .transaction
+                .meta
+                .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages))
+                .unwrap_or_default();
+            self.deliver(signature, status.slot, &logs).await?;
+        }
+        Ok(())
+    }
+    
+    /// Send a transaction's events unless it was already delivered
+    async fn deliver(
+        &mut self,
+        signature: Signature,
+        slot: u64,
+        logs: &[String],
+    ) -> Result<(), SessionEnd> {
+        if !self.seen.insert(signature) {
+            return Ok(());
+        }
+        for event in logs.iter().filter_map(|line| parse_log(line)) {
+            self.send(ProtocolUpdate::Event {
+                signature,
+                slot,
+                event,
+            })
+            .await?;
+        }
+        self.cursor = Some(signature);
+        Ok(())
+    }
+    
+    async fn send(&self, update: ProtocolUpdate) -> Result<(), SessionEnd> {
+        self.sender
+            .send(update)
+            .await
+            .map_err(|_| SessionEnd::Closed)
+    }
+}
+
+fn rpc_error(e: impl ToString) -> ClientError {
+    ClientError::Rpc(e.to_string())
+}
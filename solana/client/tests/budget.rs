@@ -0,0 +1,145 @@
+use floating_point_protocol_solana::{crypto::ring::RingSignature, state::POINT_VALUE};
+use fpp_client::{
+    budget::{
+        self, compute_units, write_locked_accounts, COMPUTE_BUDGET_ID, DEFAULT_INSTRUCTION_UNITS,
+        MAX_COMPUTE_UNIT_LIMIT, ZK_TOKEN_PROOF_ID,
+    },
+    ComputeBudget, MintAccounts, PriorityFeePolicy, Protocol,
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+fn protocol() -> Protocol {
+    Protocol::new(
+        Pubkey::new_unique(),
+        MintAccounts {
+            mint: Pubkey::new_unique(),
+            token_program: Pubkey::new_unique(),
+        },
+    )
+}
+
+/// A payment spending `inputs` points from rings of `ring_size`
+fn payment(protocol: &Protocol, inputs: usize, ring_size: usize) -> Instruction {
+    let signature = RingSignature {
+        key_images: (0..inputs).map(|i| [i as u8 + 1; 32]).collect(),
+        challenge: [0u8; 32],
+        responses: vec![[0u8; 32]; inputs * ring_size],
+    };
+    protocol
+        .privacy_payment(Pubkey::new_unique(), Pubkey::new_unique(), [0u8; 32])
+        .ring_signature(signature, vec![Pubkey::new_unique(); inputs * ring_size])
+        .output([9u8; 32], vec![1])
+        .range_proof_offset(-1)
+        .verifier_program(Pubkey::new_unique())
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn units_grow_with_what_an_instruction_does() {
+    let protocol = protocol();
+    let program_id = protocol.program_id;
+    let deposit = |points: u8| {
+        let commitments = (0..points).map(|i| [i; 32]).collect();
+        protocol.deposit(Pubkey::new_unique(), POINT_VALUE, commitments).epoch(0).build().unwrap()
+    };
+    
+    assert!(compute_units(&program_id, &deposit(1)) < compute_units(&program_id, &deposit(4)));
+    assert!(
+        compute_units(&program_id, &payment(&protocol, 1, 4))
+            < compute_units(&program_id, &payment(&protocol, 1, 8))
+    );
+    assert!(
+        compute_units(&program_id, &payment(&protocol, 1, 8))
+            < compute_units(&program_id, &payment(&protocol, 2, 8))
+    );
+}
+
+#[test]
+fn other_instructions_get_flat_allowances() {
+    let program_id = Pubkey::new_unique();
+    let limit = budget::set_compute_unit_limit(1);
+    let range_proof = Instruction::new_with_bytes(ZK_TOKEN_PROOF_ID, &[], Vec::new());
+    let transfer = Instruction::new_with_bytes(Pubkey::new_unique(), &[], Vec::new());
+    let garbled = Instruction::new_with_bytes(program_id, &[0xff; 3], Vec::new());
+    
+    assert!(compute_units(&program_id, &limit) < compute_units(&program_id, &transfer));
+    assert!(compute_units(&program_id, &transfer) < compute_units(&program_id, &range_proof));
+    assert_eq!(compute_units(&program_id, &garbled), DEFAULT_INSTRUCTION_UNITS);
+}
+
+#[test]
+fn budgets_cover_their_instructions_up_to_the_maximum() {
+    let protocol = protocol();
+    let program_id = protocol.program_id;
+    let instructions = vec![payment(&protocol, 1, 4)];
+    let budget = ComputeBudget::estimate(&program_id, &instructions, 0);
+    assert!(budget.unit_limit > compute_units(&program_id, &instructions[0]));
+    assert_eq!(budget.instructions().len(), 1);
+    assert_eq!(budget.priority_fee(), 0);
+    
+    let crowded = vec![payment(&protocol, 4, 16); 4];
+    let budget = ComputeBudget::estimate(&program_id, &crowded, 3);
+    assert_eq!(budget.unit_limit, MAX_COMPUTE_UNIT_LIMIT);
+    assert_eq!(budget.instructions().len(), 2);
+    // 1.4M units at 3 micro-lamports is 4.2 lamports, rounded up
+    assert_eq!(budget.priority_fee(), 5);
+}
+
+#[test]
+fn budget_instructions_encode_like_the_native_program() {
+    let limit = budget::set_compute_unit_limit(300_000);
+    assert_eq!(limit.program_id, COMPUTE_BUDGET_ID);
+    assert_eq!(limit.data, [&[2u8][..], &300_000u32.to_le_bytes()].concat());
+    let price = budget::set_compute_unit_price(7);
+    assert_eq!(price.data, [&[3u8][..], &7u64.to_le_bytes()].concat());
+}
+
+#[test]
+fn prepended_budgets_keep_relative_offsets() {
+    let protocol = protocol();
+    let range_proof = Instruction::new_with_bytes(ZK_TOKEN_PROOF_ID, &[], Vec::new());
+    let instructions = vec![range_proof, payment(&protocol, 1, 4)];
+    let with_budget = protocol.with_compute_budget(instructions.clone(), 10);
+    assert_eq!(with_budget.len(), 4);
+    assert!(with_budget[..2].iter().all(|ix| ix.program_id == COMPUTE_BUDGET_ID));
+    assert_eq!(with_budget[2..], instructions[..]);
+}
+
+#[test]
+fn unit_prices_follow_the_policy() {
+    let policy = PriorityFeePolicy {
+        percentile: 50,
+        min_unit_price: 10,
+        max_unit_price: 1_000,
+    };
+    assert_eq!(policy.unit_price(&[]), 10);
+    assert_eq!(policy.unit_price(&[500, 100, 300]), 300);
+    assert_eq!(policy.unit_price(&[0, 0, 5]), 10);
+    assert_eq!(policy.unit_price(&[5_000, 9_000, 7_000]), 1_000);
+    
+    let top = PriorityFeePolicy {
+        percentile: 100,
+        ..PriorityFeePolicy::default()
+    };
+    assert_eq!(top.unit_price(&[1, 9, 4]), 9);
+}
+
+#[test]
+fn only_writable_accounts_are_locked_once() {
+    let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    let instruction = |accounts| Instruction::new_with_bytes(Pubkey::new_unique(), &[], accounts);
+    let instructions = vec![
+        instruction(vec![AccountMeta::new(a, true), AccountMeta::new_readonly(b, false)]),
+        instruction(vec![AccountMeta::new(c, false), AccountMeta::new(a, false)]),
+    ];
+    assert_eq!(write_locked_accounts(&instructions), vec![a, c]);
+    
+    let many: Vec<Instruction> = (0..200)
+        .map(|_| instruction(vec![AccountMeta::new(Pubkey::new_unique(), false)]))
+        .collect();
+    assert_eq!(write_locked_accounts(&many).len(), 128);
+}
@@ -0,0 +1,287 @@
+use borsh::BorshDeserialize;
+use floating_point_protocol_solana::{
+    crypto::{opening::OpeningProof, ring::RingSignature},
+    instruction::FPPInstruction,
+    pda,
+    state::{EncryptedNote, FloatingPoint, POINT_VALUE},
+};
+use fpp_client::{ClientError, MintAccounts, Protocol, SpendProof};
+use solana_program::pubkey::Pubkey;
+
+fn protocol() -> Protocol {
+    Protocol::new(
+        Pubkey::new_unique(),
+        MintAccounts {
+            mint: Pubkey::new_unique(),
+            token_program: Pubkey::new_unique(),
+        },
+    )
+}
+
+/// A signature of the right shape; builders check shapes, the program checks curves
+fn signature(inputs: usize, ring_size: usize) -> RingSignature {
+    RingSignature {
+        key_images: (0..inputs).map(|i| [i as u8 + 1; 32]).collect(),
+        challenge: [9u8; 32],
+        responses: vec![[3u8; 32]; inputs * ring_size],
+    }
+}
+
+fn invalid_input(result: Result<impl std::fmt::Debug, ClientError>) -> String {
+    match result {
+        Err(ClientError::InvalidInput(reason)) => reason,
+        other => panic!("expected an invalid input, got {:?}", other),
+    }
+}
+
+#[test]
+fn deposits_pay_from_the_users_token_account_into_the_treasury() {
+    let protocol = protocol();
+    let user = Pubkey::new_unique();
+    let commitments = vec![[1u8; 32], [2u8; 32]];
+    let ix = protocol
+        .deposit(user, 10 * POINT_VALUE, commitments.clone())
+        .epoch(4)
+        .build()
+        .unwrap();
+    
+    assert_eq!(ix.program_id, protocol.program_id);
+    assert_eq!(ix.accounts[0].pubkey, user);
+    assert!(ix.accounts[0].is_signer);
+    assert_eq!(ix.accounts[1].pubkey, protocol.usdt.token_account(&user));
+    assert_eq!(ix.accounts[2].pubkey, protocol.treasury_token(&protocol.usdt));
+    assert_eq!(ix.accounts[3].pubkey, protocol.protocol_state);
+    let first_point = pda::find_point_address(&commitments[0], &protocol.program_id).0;
+    assert_eq!(ix.accounts[4].pubkey, first_point);
+    match FPPInstruction::try_from_slice(&ix.data).unwrap() {
+        FPPInstruction::Deposit {
+            amount,
+            denomination,
+            commitments: encoded,
+            referrer,
+            ..
+        } => {
+            assert_eq!(amount, 20 * POINT_VALUE);
+            assert_eq!(denomination, 10 * POINT_VALUE);
+            assert_eq!(encoded, commitments);
+            assert_eq!(referrer, None);
+        }
+        other => panic!("expected a deposit, got {:?}", other),
+    }
+}
+
+#[test]
+fn deposits_take_another_mint_and_token_account() {
+    let protocol = protocol();
+    let user = Pubkey::new_unique();
+    let mint = MintAccounts {
+        mint: Pubkey::new_unique(),
+        token_program: Pubkey::new_unique(),
+    };
+    let user_token = Pubkey::new_unique();
+    let ix = protocol
+        .deposit(user, POINT_VALUE, vec![[1u8; 32]])
+        .mint(mint)
+        .user_token(user_token)
+        .epoch(0)
+        .build()
+        .unwrap();
+    assert_eq!(ix.accounts[1].pubkey, user_token);
+    assert_eq!(ix.accounts[2].pubkey, protocol.treasury_token(&mint));
+    assert_eq!(ix.accounts[5].pubkey, mint.mint);
+    assert_eq!(ix.accounts[6].pubkey, mint.token_program);
+}
+
+#[test]
+fn invalid_deposits_are_refused_before_sending() {
+    let protocol = protocol();
+    let user = Pubkey::new_unique();
+    let proof = OpeningProof {
+        nonce_commitment: [0u8; 32],
+        response: [0u8; 32],
+    };
+    let deposit = |denomination, commitments| {
+        protocol.deposit(user, denomination, commitments).epoch(0)
+    };
+    
+    assert_eq!(invalid_input(deposit(POINT_VALUE, vec![]).build()), "no commitments");
+    invalid_input(deposit(0, vec![[1u8; 32]]).build());
+    invalid_input(deposit(POINT_VALUE + 1, vec![[1u8; 32]]).build());
+    invalid_input(
+        deposit(POINT_VALUE, vec![[1u8; 32], [2u8; 32]])
+            .opening_proofs(vec![proof])
+            .build(),
+    );
+    let largest = u64::MAX / POINT_VALUE * POINT_VALUE;
+    assert_eq!(invalid_input(deposit(largest, vec![[1u8; 32]; 2]).build()), "amount overflows");
+    invalid_input(deposit(POINT_VALUE, vec![[1u8; 32]]).referrer(user).build());
+    assert_eq!(
+        invalid_input(protocol.deposit(user, POINT_VALUE, vec![[1u8; 32]]).build()),
+        "no epoch"
+    );
+}
+
+#[test]
+fn withdrawal_requests_take_their_nullifiers_from_the_spend_signature() {
+    let protocol = protocol();
+    let user = Pubkey::new_unique();
+    let points = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let builder = protocol.request_withdrawal(user, 7).point(points[0]).point(points[1]);
+    assert_ne!(builder.spend_message(), protocol.request_withdrawal(user, 8).spend_message());
+    
+    let ix = builder.spend_signature(signature(2, 1)).build().unwrap();
+    assert_eq!(ix.accounts[2].pubkey, protocol.withdrawal_request(&user, 7));
+    assert_eq!(ix.accounts[3].pubkey, points[0]);
+    assert_eq!(ix.accounts[4].pubkey, points[1]);
+    match FPPInstruction::try_from_slice(&ix.data).unwrap() {
+        FPPInstruction::RequestWithdrawal {
+            point_ids,
+            nullifiers,
+            nonce,
+            spend_signature,
+        } => {
+            assert_eq!(point_ids, points);
+            assert_eq!(nullifiers, signature(2, 1).key_images);
+            assert_eq!(nonce, 7);
+            assert_eq!(RingSignature::try_from_slice(&spend_signature).unwrap(), signature(2, 1));
+        }
+        other => panic!("expected a withdrawal request, got {:?}", other),
+    }
+}
+
+#[test]
+fn invalid_withdrawal_requests_are_refused_before_sending() {
+    let protocol = protocol();
+    let user = Pubkey::new_unique();
+    let point = Pubkey::new_unique();
+    let request = || protocol.request_withdrawal(user, 0);
+    
+    assert_eq!(
+        invalid_input(request().spend_signature(signature(1, 1)).build()),
+        "no points to withdraw"
+    );
+    invalid_input(request().point(point).point(point).spend_signature(signature(2, 1)).build());
+    assert_eq!(invalid_input(request().point(point).build()), "no spend signature");
+    // One key image per point, and a single row: a spend signature has no decoys
+    invalid_input(request().point(point).spend_signature(signature(2, 1)).build());
+    invalid_input(request().point(point).spend_signature(signature(1, 2)).build());
+}
+
+#[test]
+fn payments_derive_output_points_and_notes_from_their_commitments() {
+    let protocol = protocol();
+    let (sender, recipient) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let verifier = Pubkey::new_unique();
+    let members: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+    let ix = protocol
+        .privacy_payment(sender, recipient, [5u8; 32])
+        .ring_signature(signature(2, 2), members.clone())
+        .output([6u8; 32], vec![1, 2, 3])
+        .range_proof_offset(-1)
+        .verifier_program(verifier)
+        .build()
+        .unwrap();
+    
+    let program_id = protocol.program_id;
+    let accounts: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(accounts[..3], [sender, protocol.protocol_state, recipient]);
+    assert_eq!(accounts[3..7], members[..]);
+    assert!(accounts.contains(&FloatingPoint::find_address(&[6u8; 32], &program_id).0));
+    assert!(accounts.contains(&EncryptedNote::find_address(&[6u8; 32], &program_id).0));
+    assert!(accounts.contains(&verifier));
+    match FPPInstruction::try_from_slice(&ix.data).unwrap() {
+        FPPInstruction::PrivacyPayment {
+            merkle_root,
+            input_nullifiers,
+            output_commitments,
+            range_proof_offset,
+            encrypted_notes,
+            ..
+        } => {
+            assert_eq!(merkle_root, [5u8; 32]);
+            assert_eq!(input_nullifiers, signature(2, 2).key_images);
+            assert_eq!(output_commitments, vec![[6u8; 32]]);
+            assert_eq!(range_proof_offset, -1);
+            assert_eq!(encrypted_notes, vec![vec![1, 2, 3]]);
+        }
+        other => panic!("expected a payment, got {:?}", other),
+    }
+}
+
+#[test]
+fn spend_proofs_fill_in_a_payment() {
+    let protocol = protocol();
+    let spend = SpendProof {
+        merkle_root: [5u8; 32],
+        ring: vec![[7u8; 32], [8u8; 32]],
+        ring_signature: signature(1, 2),
+        output_commitments: vec![[6u8; 32]],
+        encrypted_notes: vec![vec![1]],
+        proof: vec![4u8; 256],
+    };
+    let (sender, recipient) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let verifier = Pubkey::new_unique();
+    let ix = protocol
+        .privacy_payment(sender, recipient, [0u8; 32])
+        .spend_proof(spend.clone())
+        .range_proof_offset(-1)
+        .verifier_program(verifier)
+        .build()
+        .unwrap();
+    
+    let program_id = protocol.program_id;
+    assert_eq!(ix.accounts[3].pubkey, FloatingPoint::find_address(&[7u8; 32], &program_id).0);
+    assert_eq!(ix.accounts[4].pubkey, FloatingPoint::find_address(&[8u8; 32], &program_id).0);
+    match FPPInstruction::try_from_slice(&ix.data).unwrap() {
+        FPPInstruction::PrivacyPayment {
+            merkle_root, proof, ..
+        } => {
+            assert_eq!(merkle_root, spend.merkle_root);
+            assert_eq!(proof, spend.proof);
+        }
+        other => panic!("expected a payment, got {:?}", other),
+    }
+}
+
+#[test]
+fn invalid_payments_are_refused_before_sending() {
+    let protocol = protocol();
+    let members = vec![Pubkey::new_unique(); 2];
+    let payment = || {
+        protocol
+            .privacy_payment(Pubkey::new_unique(), Pubkey::new_unique(), [0u8; 32])
+            .range_proof_offset(-1)
+            .verifier_program(Pubkey::new_unique())
+    };
+    
+    assert_eq!(
+        invalid_input(payment().output([6u8; 32], vec![1]).build()),
+        "no ring signature"
+    );
+    let ring_of = |ring_size, members: &[Pubkey]| {
+        payment().ring_signature(signature(1, ring_size), members.to_vec())
+    };
+    invalid_input(ring_of(0, &[]).output([6u8; 32], vec![1]).build());
+    invalid_input(ring_of(2, &members[..1]).output([6u8; 32], vec![1]).build());
+    assert_eq!(invalid_input(ring_of(2, &members[..]).build()), "no outputs");
+    assert_eq!(
+        invalid_input(
+            ring_of(2, &members[..]).output([6u8; 32], vec![1]).output([7u8; 32], vec![1]).build()
+        ),
+        "a payment has a single output"
+    );
+    invalid_input(ring_of(2, &members[..]).output([6u8; 32], vec![]).build());
+    let too_long = vec![1; EncryptedNote::MAX_CIPHERTEXT_LEN + 1];
+    invalid_input(ring_of(2, &members[..]).output([6u8; 32], too_long).build());
+    assert_eq!(
+        invalid_input(
+            protocol
+                .privacy_payment(Pubkey::new_unique(), Pubkey::new_unique(), [0u8; 32])
+                .ring_signature(signature(1, 2), members)
+                .output([6u8; 32], vec![1])
+                .verifier_program(Pubkey::new_unique())
+                .build()
+        ),
+        "no range proof offset"
+    );
+}
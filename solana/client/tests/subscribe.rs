@@ -0,0 +1,32 @@
+#![cfg(feature = "subscribe")]
+
+use base64::Engine;
+use borsh::BorshSerialize;
+use floating_point_protocol_solana::events::{FPPEvent, EVENT_TAG};
+use fpp_client::subscribe::parse_log;
+
+fn encode(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+#[test]
+fn events_are_decoded_from_the_programs_data_lines() {
+    let event = FPPEvent::NullifiersSpent {
+        nullifiers: vec![[1u8; 32], [2u8; 32]],
+    };
+    let line = format!(
+        "Program data: {} {}",
+        encode(EVENT_TAG),
+        encode(&event.try_to_vec().unwrap())
+    );
+    assert_eq!(parse_log(&line), Some(event));
+}
+
+#[test]
+fn other_lines_are_skipped() {
+    let data = encode(&[0xffu8; 8]);
+    assert_eq!(parse_log("Program log: Instruction: Deposit"), None);
+    assert_eq!(parse_log(&format!("Program data: {}", encode(b"other"))), None);
+    assert_eq!(parse_log(&format!("Program data: {} {}", encode(EVENT_TAG), data)), None);
+    assert_eq!(parse_log("Program data: not base64!"), None);
+}
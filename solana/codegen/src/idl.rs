@@ -0,0 +1,20 @@
+//! Shank IDL for explorers and other codegen, read from the program's
+//! `ShankInstruction`/`ShankAccount` annotations.
+
+use std::path::Path;
+
+use shank_idl::{extract_idl, ParseIdlOpts};
+
+/// Render the program's IDL as JSON from the crate rooted at `lib_rs`
+pub fn generate(lib_rs: &Path) -> Result<String, String> {
+    let opts = ParseIdlOpts {
+        // The program id is chosen at deploy time, not declared in source
+        require_program_address: false,
+        ..ParseIdlOpts::default()
+    };
+    let path = lib_rs.to_str().ok_or("program path is not valid UTF-8")?;
+    let idl = extract_idl(path, opts)
+        .map_err(|e| format!("failed to parse program: {}", e))?
+        .ok_or("program has no Shank annotations")?;
+    idl.try_into_json().map_err(|e| format!("failed to encode IDL: {}", e))
+}
@@ -0,0 +1,77 @@
+//! Generates the TypeScript client in `client-ts/src/generated.ts` from the
+//! program's Borsh schemas, so web integrators never hand-port layouts.
+//!
+//! `npm run build` in `client-ts/` runs this first; run it by hand with
+//! `cargo run -p fpp-codegen [output]`. `cargo run -p fpp-codegen -- idl [output]`
+//! writes the Shank IDL to `idl/fpp.json` instead.
+
+mod idl;
+mod ts;
+
+use std::{env, fs, path::PathBuf, process};
+
+use borsh::{schema::BorshSchemaContainer, BorshSchema};
+use floating_point_protocol_solana::{
+    instruction::FPPInstruction,
+    state::{
+        AdminMultisig, AdminProposal, CircuitInfo, CircuitKey, CommitmentTree, ConcurrentTree,
+        ContactBook, EncryptedNote, FloatingPoint, NullifierSet, PoolConfig, ProtocolState,
+        RootHistory, SecurityEvent, WithdrawalBatch, WithdrawalRequest,
+    },
+};
+
+/// Account types that get a `decode<Name>Account` helper
+fn account_schemas() -> Vec<BorshSchemaContainer> {
+    vec![
+        ProtocolState::schema_container(),
+        FloatingPoint::schema_container(),
+        WithdrawalRequest::schema_container(),
+        WithdrawalBatch::schema_container(),
+        NullifierSet::schema_container(),
+        CommitmentTree::schema_container(),
+        ConcurrentTree::schema_container(),
+        RootHistory::schema_container(),
+        PoolConfig::schema_container(),
+        CircuitInfo::schema_container(),
+        CircuitKey::schema_container(),
+        EncryptedNote::schema_container(),
+        SecurityEvent::schema_container(),
+        ContactBook::schema_container(),
+        AdminMultisig::schema_container(),
+        AdminProposal::schema_container(),
+    ]
+}
+
+fn main() {
+    let mut args = env::args().skip(1).peekable();
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let is_idl = args.peek().map(String::as_str) == Some("idl");
+    
+    let (output, source) = if is_idl {
+        args.next();
+        let output = args.next().map(PathBuf::from).unwrap_or_else(|| {
+            manifest_dir.join("../idl/fpp.json")
+        });
+        match idl::generate(&manifest_dir.join("../src/lib.rs")) {
+            Ok(json) => (output, json),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+    } else {
+        let output = args.next().map(PathBuf::from).unwrap_or_else(|| {
+            manifest_dir.join("../client-ts/src/generated.ts")
+        });
+        (output, ts::generate(&FPPInstruction::schema_container(), &account_schemas()))
+    };
+    
+    if let Some(dir) = output.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    if let Err(e) = fs::write(&output, source) {
+        eprintln!("failed to write {}: {}", output.display(), e);
+        process::exit(1);
+    }
+    println!("wrote {}", output.display());
+}
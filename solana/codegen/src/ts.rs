@@ -0,0 +1,433 @@
+//! TypeScript emitter for Borsh schema containers.
+
+use std::collections::{BTreeSet, HashMap};
+
+use borsh::schema::{BorshSchemaContainer, Declaration, Definition, Fields};
+
+const HEADER: &str = "\
+// Generated by `cargo run -p fpp-codegen` from the program's Borsh schemas.
+// Do not edit by hand.
+
+/* eslint-disable */
+
+import { AccountMeta, PublicKey, TransactionInstruction } from '@solana/web3.js';
+import { BorshReader, BorshWriter } from './borsh';
+";
+
+pub fn generate(instruction: &BorshSchemaContainer, accounts: &[BorshSchemaContainer]) -> String {
+    let mut generator = Generator::default();
+    for container in std::iter::once(instruction).chain(accounts) {
+        generator.definitions.extend(
+            container
+                .definitions
+                .iter()
+                .map(|(declaration, definition)| (declaration.clone(), definition.clone())),
+        );
+    }
+    
+    let mut out = String::from(HEADER);
+    generator.emit_named(&instruction.declaration, &mut out);
+    for container in accounts {
+        generator.emit_named(&container.declaration, &mut out);
+    }
+    
+    generator.emit_instruction_builders(&instruction.declaration, &mut out);
+    for container in accounts {
+        let name = type_name(&container.declaration);
+        out.push_str(&format!(
+            "\nexport function decode{name}Account(data: Uint8Array): {name} {{\n    \
+             return decode{name}(new BorshReader(data));\n}}\n"
+        ));
+    }
+    out
+}
+
+#[derive(Default)]
+struct Generator {
+    definitions: HashMap<Declaration, Definition>,
+    emitted: BTreeSet<Declaration>,
+}
+
+impl Generator {
+    /// Emit a named struct or enum and, first, every named type it references
+    fn emit_named(&mut self, declaration: &str, out: &mut String) {
+        if is_pubkey(declaration) || !self.emitted.insert(declaration.to_string()) {
+            return;
+        }
+        let definition = match self.definitions.get(declaration) {
+            Some(definition) => definition.clone(),
+            None => return,
+        };
+        for dependency in dependencies(&definition) {
+            self.visit(&dependency, out);
+        }
+        
+        match definition {
+            Definition::Struct { fields } => self.emit_struct(declaration, &fields, out),
+            Definition::Enum { variants } => self.emit_enum(declaration, &variants, out),
+            _ => {}
+        }
+    }
+    
+    /// Walk anonymous containers (arrays, vecs, options) down to named types
+    fn visit(&mut self, declaration: &str, out: &mut String) {
+        match self.definitions.get(declaration).cloned() {
+            Some(Definition::Struct { .. }) => self.emit_named(declaration, out),
+            Some(Definition::Enum { .. }) if !is_option(declaration) => {
+                self.emit_named(declaration, out)
+            }
+            Some(definition) => {
+                for dependency in dependencies(&definition) {
+                    self.visit(&dependency, out);
+                }
+            }
+            None => {}
+        }
+    }
+    
+    fn emit_struct(&self, declaration: &str, fields: &Fields, out: &mut String) {
+        let name = type_name(declaration);
+        match fields {
+            Fields::NamedFields(fields) => {
+                out.push_str(&format!("\nexport interface {name} {{\n"));
+                for (field, field_type) in fields {
+                    out.push_str(&format!(
+                        "    {}: {};\n",
+                        camel_case(field),
+                        self.ts_type(field_type)
+                    ));
+                }
+                out.push_str("}\n");
+                
+                out.push_str(&format!(
+                    "\nexport function encode{name}(w: BorshWriter, v: {name}): void {{\n"
+                ));
+                for (field, field_type) in fields {
+                    let value = format!("v.{}", camel_case(field));
+                    out.push_str(&format!("    {};\n", self.encode(field_type, &value)));
+                }
+                out.push_str("}\n");
+                
+                out.push_str(&format!(
+                    "\nexport function decode{name}(r: BorshReader): {name} {{\n    return {{\n"
+                ));
+                for (field, field_type) in fields {
+                    out.push_str(&format!(
+                        "        {}: {},\n",
+                        camel_case(field),
+                        self.decode(field_type)
+                    ));
+                }
+                out.push_str("    };\n}\n");
+            }
+            Fields::UnnamedFields(elements) => {
+                let types: Vec<String> = elements.iter().map(|e| self.ts_type(e)).collect();
+                out.push_str(&format!("\nexport type {name} = [{}];\n", types.join(", ")));
+                
+                out.push_str(&format!(
+                    "\nexport function encode{name}(w: BorshWriter, v: {name}): void {{\n"
+                ));
+                for (i, element) in elements.iter().enumerate() {
+                    let value = format!("v[{i}]");
+                    out.push_str(&format!("    {};\n", self.encode(element, &value)));
+                }
+                out.push_str("}\n");
+                
+                let decoded: Vec<String> = elements.iter().map(|e| self.decode(e)).collect();
+                out.push_str(&format!(
+                    "\nexport function decode{name}(r: BorshReader): {name} {{\n    \
+                     return [{}];\n}}\n",
+                    decoded.join(", ")
+                ));
+            }
+            // Unit variants are inlined into their enum
+            Fields::Empty => {}
+        }
+    }
+    
+    fn emit_enum(&self, declaration: &str, variants: &[(String, Declaration)], out: &mut String) {
+        let name = type_name(declaration);
+        
+        // Fieldless enums map onto a TypeScript numeric enum
+        if variants.iter().all(|(_, variant)| self.is_unit(variant)) {
+            out.push_str(&format!("\nexport enum {name} {{\n"));
+            for (i, (variant, _)) in variants.iter().enumerate() {
+                out.push_str(&format!("    {variant} = {i},\n"));
+            }
+            out.push_str("}\n");
+            out.push_str(&format!(
+                "\nexport function encode{name}(w: BorshWriter, v: {name}): void {{\n    \
+                 w.u8(v);\n}}\n"
+            ));
+            out.push_str(&format!(
+                "\nexport function decode{name}(r: BorshReader): {name} {{\n    \
+                 const tag = r.u8();\n    \
+                 if ({name}[tag] === undefined) {{\n        \
+                 throw new Error(`invalid {name} variant ${{tag}}`);\n    \
+                 }}\n    \
+                 return tag as {name};\n}}\n"
+            ));
+            return;
+        }
+        
+        out.push_str(&format!("\nexport type {name} =\n"));
+        for (i, (variant, variant_type)) in variants.iter().enumerate() {
+            let terminator = if i + 1 == variants.len() { ";" } else { "" };
+            if self.is_unit(variant_type) {
+                out.push_str(&format!("    | {{ variant: '{variant}' }}{terminator}\n"));
+            } else {
+                out.push_str(&format!(
+                    "    | ({{ variant: '{variant}' }} & {}){terminator}\n",
+                    type_name(variant_type)
+                ));
+            }
+        }
+        
+        out.push_str(&format!(
+            "\nexport function encode{name}(w: BorshWriter, v: {name}): void {{\n    \
+             switch (v.variant) {{\n"
+        ));
+        for (i, (variant, variant_type)) in variants.iter().enumerate() {
+            out.push_str(&format!("        case '{variant}':\n            w.u8({i});\n"));
+            if !self.is_unit(variant_type) {
+                out.push_str(&format!(
+                    "            encode{}(w, v);\n",
+                    type_name(variant_type)
+                ));
+            }
+            out.push_str("            break;\n");
+        }
+        out.push_str("    }\n}\n");
+        
+        out.push_str(&format!(
+            "\nexport function decode{name}(r: BorshReader): {name} {{\n    \
+             const tag = r.u8();\n    \
+             switch (tag) {{\n"
+        ));
+        for (i, (variant, variant_type)) in variants.iter().enumerate() {
+            if self.is_unit(variant_type) {
+                out.push_str(&format!(
+                    "        case {i}:\n            return {{ variant: '{variant}' }};\n"
+                ));
+            } else {
+                out.push_str(&format!(
+                    "        case {i}:\n            \
+                     return {{ variant: '{variant}', ...decode{}(r) }};\n",
+                    type_name(variant_type)
+                ));
+            }
+        }
+        out.push_str(&format!(
+            "        default:\n            \
+             throw new Error(`invalid {name} variant ${{tag}}`);\n    }}\n}}\n"
+        ));
+    }
+    
+    fn emit_instruction_builders(&self, declaration: &str, out: &mut String) {
+        let name = type_name(declaration);
+        out.push_str(&format!(
+            "\nexport function encodeInstruction(ix: {name}): Buffer {{\n    \
+             const w = new BorshWriter();\n    \
+             encode{name}(w, ix);\n    \
+             return w.toBuffer();\n}}\n"
+        ));
+        
+        let variants = match self.definitions.get(declaration) {
+            Some(Definition::Enum { variants }) => variants,
+            _ => return,
+        };
+        for (variant, variant_type) in variants {
+            let (params, data) = if self.is_unit(variant_type) {
+                (String::new(), format!("{{ variant: '{variant}' }}"))
+            } else {
+                (
+                    format!("    args: {},\n", type_name(variant_type)),
+                    format!("{{ variant: '{variant}', ...args }}"),
+                )
+            };
+            out.push_str(&format!(
+                "\n/** Accounts are documented on `{name}::{variant}` in instruction.rs */\n\
+                 export function create{variant}Instruction(\n    \
+                 programId: PublicKey,\n    \
+                 keys: AccountMeta[],\n\
+                 {params}\
+                 ): TransactionInstruction {{\n    \
+                 return new TransactionInstruction({{\n        \
+                 programId,\n        \
+                 keys,\n        \
+                 data: encodeInstruction({data}),\n    \
+                 }});\n}}\n"
+            ));
+        }
+    }
+    
+    fn is_unit(&self, declaration: &str) -> bool {
+        matches!(
+            self.definitions.get(declaration),
+            Some(Definition::Struct {
+                fields: Fields::Empty
+            })
+        )
+    }
+    
+    fn ts_type(&self, declaration: &str) -> String {
+        if let Some(primitive) = primitive(declaration) {
+            return primitive.ts_type.to_string();
+        }
+        if is_pubkey(declaration) {
+            return "PublicKey".to_string();
+        }
+        match self.definitions.get(declaration) {
+            Some(Definition::Array { elements, .. }) | Some(Definition::Sequence { elements })
+                if elements == "u8" =>
+            {
+                "Uint8Array".to_string()
+            }
+            Some(Definition::Array { elements, .. }) | Some(Definition::Sequence { elements }) => {
+                format!("{}[]", self.ts_type(elements))
+            }
+            Some(Definition::Tuple { elements }) => {
+                let types: Vec<String> = elements.iter().map(|e| self.ts_type(e)).collect();
+                format!("[{}]", types.join(", "))
+            }
+            Some(Definition::Enum { variants }) if is_option(declaration) => {
+                format!("{} | null", self.ts_type(&variants[1].1))
+            }
+            _ => type_name(declaration),
+        }
+    }
+    
+    fn encode(&self, declaration: &str, value: &str) -> String {
+        if let Some(primitive) = primitive(declaration) {
+            return format!("w.{}({value})", primitive.method);
+        }
+        if is_pubkey(declaration) {
+            return format!("w.pubkey({value})");
+        }
+        match self.definitions.get(declaration) {
+            Some(Definition::Array { length, elements }) if elements == "u8" => {
+                format!("w.fixedBytes({value}, {length})")
+            }
+            Some(Definition::Array { length, elements }) => format!(
+                "w.fixedArray({value}, {length}, (x) => {})",
+                self.encode(elements, "x")
+            ),
+            Some(Definition::Sequence { elements }) if elements == "u8" => {
+                format!("w.bytes({value})")
+            }
+            Some(Definition::Sequence { elements }) => {
+                format!("w.vec({value}, (x) => {})", self.encode(elements, "x"))
+            }
+            Some(Definition::Tuple { elements }) => {
+                let parts: Vec<String> = elements
+                    .iter()
+                    .enumerate()
+                    .map(|(i, e)| self.encode(e, &format!("{value}[{i}]")))
+                    .collect();
+                format!("({})", parts.join(", "))
+            }
+            Some(Definition::Enum { variants }) if is_option(declaration) => format!(
+                "w.option({value}, (x) => {})",
+                self.encode(&variants[1].1, "x")
+            ),
+            _ => format!("encode{}(w, {value})", type_name(declaration)),
+        }
+    }
+    
+    fn decode(&self, declaration: &str) -> String {
+        if let Some(primitive) = primitive(declaration) {
+            return format!("r.{}()", primitive.method);
+        }
+        if is_pubkey(declaration) {
+            return "r.pubkey()".to_string();
+        }
+        match self.definitions.get(declaration) {
+            Some(Definition::Array { length, elements }) if elements == "u8" => {
+                format!("r.fixedBytes({length})")
+            }
+            Some(Definition::Array { length, elements }) => {
+                format!("r.fixedArray({length}, () => {})", self.decode(elements))
+            }
+            Some(Definition::Sequence { elements }) if elements == "u8" => "r.bytes()".to_string(),
+            Some(Definition::Sequence { elements }) => {
+                format!("r.vec(() => {})", self.decode(elements))
+            }
+            Some(Definition::Tuple { elements }) => {
+                let parts: Vec<String> = elements.iter().map(|e| self.decode(e)).collect();
+                format!("[{}]", parts.join(", "))
+            }
+            Some(Definition::Enum { variants }) if is_option(declaration) => {
+                format!("r.option(() => {})", self.decode(&variants[1].1))
+            }
+            _ => format!("decode{}(r)", type_name(declaration)),
+        }
+    }
+}
+
+struct Primitive {
+    ts_type: &'static str,
+    method: &'static str,
+}
+
+fn primitive(declaration: &str) -> Option<Primitive> {
+    let (ts_type, method) = match declaration {
+        "bool" => ("boolean", "bool"),
+        "u8" => ("number", "u8"),
+        "u16" => ("number", "u16"),
+        "u32" => ("number", "u32"),
+        "i8" => ("number", "i8"),
+        "i16" => ("number", "i16"),
+        "i32" => ("number", "i32"),
+        "u64" => ("bigint", "u64"),
+        "u128" => ("bigint", "u128"),
+        "i64" => ("bigint", "i64"),
+        "i128" => ("bigint", "i128"),
+        "string" => ("string", "string"),
+        _ => return None,
+    };
+    Some(Primitive { ts_type, method })
+}
+
+fn dependencies(definition: &Definition) -> Vec<Declaration> {
+    match definition {
+        Definition::Array { elements, .. } | Definition::Sequence { elements } => {
+            vec![elements.clone()]
+        }
+        Definition::Tuple { elements } => elements.clone(),
+        Definition::Enum { variants } => variants.iter().map(|(_, d)| d.clone()).collect(),
+        Definition::Struct { fields } => match fields {
+            Fields::NamedFields(fields) => fields.iter().map(|(_, d)| d.clone()).collect(),
+            Fields::UnnamedFields(elements) => elements.clone(),
+            Fields::Empty => vec![],
+        },
+    }
+}
+
+fn is_pubkey(declaration: &str) -> bool {
+    declaration == "Pubkey"
+}
+
+fn is_option(declaration: &str) -> bool {
+    declaration.starts_with("Option<")
+}
+
+/// TypeScript identifier for a schema declaration
+fn type_name(declaration: &str) -> String {
+    declaration.chars().filter(|c| c.is_ascii_alphanumeric()).collect()
+}
+
+fn camel_case(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    let mut upper = false;
+    for c in field.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
@@ -0,0 +1,460 @@
+//! Cross-program invocation helpers for the Floating Point Protocol.
+//!
+//! Programs that hold or move points on behalf of their users (a DEX vault, a
+//! payroll program) call these with the `AccountInfo`s they were given. Each
+//! helper lays the accounts out in the order documented on `FPPInstruction`,
+//! marks them writable and signer as the program expects, and invokes it with
+//! `signer_seeds`, so the depositor, sender or holder can be a PDA of the
+//! calling program. Pass `&[]` when that account signed the transaction itself.
+//!
+//! Metas are built from the accounts passed in rather than re-derived, which
+//! keeps the compute cost off the caller; the protocol still checks every PDA.
+//! `floating_point_protocol_solana::pda` and the state types' `find_address`
+//! give the addresses to pass.
+//!
+//! `PrivacyPayment`'s `range_proof_offset` is relative to the caller's
+//! top-level instruction, since that is what the instructions sysvar sees.
+//!
+//! Once the authority sets `ProtocolState::reject_cpi`, `deposit_cpi` and
+//! `request_withdrawal_cpi` fail with `FPPError::CpiNotAllowed`.
+
+use borsh::BorshSerialize;
+use floating_point_protocol_solana::{crypto::opening::OpeningProof, instruction::FPPInstruction};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// Accounts for `deposit_cpi`
+pub struct Deposit<'a, 'info> {
+    /// Pays point rent, and activity rent on first use
+    pub user: &'a AccountInfo<'info>,
+    pub user_token: &'a AccountInfo<'info>,
+    pub treasury_token: &'a AccountInfo<'info>,
+    pub protocol_state: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    pub commitment_tree: &'a AccountInfo<'info>,
+    pub root_history: &'a AccountInfo<'info>,
+    pub user_activity: &'a AccountInfo<'info>,
+    pub deny_list: &'a AccountInfo<'info>,
+    pub supported_mint: &'a AccountInfo<'info>,
+    /// Created on first use, at the user's expense
+    pub reward_account: &'a AccountInfo<'info>,
+    pub fee_exemptions: &'a AccountInfo<'info>,
+    /// At `EpochStats::find_address` of the current epoch; created at the user's expense
+    pub epoch_stats: &'a AccountInfo<'info>,
+    pub instructions_sysvar: &'a AccountInfo<'info>,
+    /// One point account per commitment, in the same order
+    pub points: &'a [AccountInfo<'info>],
+}
+
+/// Accounts for `privacy_payment_cpi`
+pub struct PrivacyPayment<'a, 'info> {
+    /// Pays nullifier, output point and note rent
+    pub sender: &'a AccountInfo<'info>,
+    pub protocol_state: &'a AccountInfo<'info>,
+    /// Becomes the output point's creator
+    pub recipient: &'a AccountInfo<'info>,
+    /// Ring members, `ring_size * inputs`, row-major
+    pub ring_members: &'a [AccountInfo<'info>],
+    /// One per input nullifier
+    pub nullifiers: &'a [AccountInfo<'info>],
+    /// At `pda::find_point_address` of each output commitment
+    pub output_points: &'a [AccountInfo<'info>],
+    /// One per output commitment
+    pub encrypted_notes: &'a [AccountInfo<'info>],
+    pub verifier_program: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    /// Root history, or the archived tree holding the proof's root
+    pub root_source: &'a AccountInfo<'info>,
+    pub instructions_sysvar: &'a AccountInfo<'info>,
+    pub nullifier_filter: &'a AccountInfo<'info>,
+    pub commitment_tree: &'a AccountInfo<'info>,
+    pub root_history: &'a AccountInfo<'info>,
+}
+
+/// Accounts for `request_withdrawal_cpi`
+pub struct RequestWithdrawal<'a, 'info> {
+    /// Pays request, nullifier and queue rent
+    pub user: &'a AccountInfo<'info>,
+    pub protocol_state: &'a AccountInfo<'info>,
+    /// At `pda::find_withdrawal_request(user, nonce)`
+    pub withdrawal_request: &'a AccountInfo<'info>,
+    pub points: &'a [AccountInfo<'info>],
+    /// One per point
+    pub nullifiers: &'a [AccountInfo<'info>],
+    pub system_program: &'a AccountInfo<'info>,
+    pub user_activity: &'a AccountInfo<'info>,
+    pub nullifier_filter: &'a AccountInfo<'info>,
+    pub reward_account: &'a AccountInfo<'info>,
+    /// At `WithdrawalQueue::find_address`
+    pub withdrawal_queue: &'a AccountInfo<'info>,
+    pub instructions_sysvar: &'a AccountInfo<'info>,
+}
+
+/// Accounts for `complete_withdrawal_cpi`
+pub struct CompleteWithdrawal<'a, 'info> {
+    /// Pays epoch stats rent on first use
+    pub user: &'a AccountInfo<'info>,
+    pub user_token: &'a AccountInfo<'info>,
+    pub treasury_token: &'a AccountInfo<'info>,
+    pub protocol_state: &'a AccountInfo<'info>,
+    pub withdrawal_request: &'a AccountInfo<'info>,
+    pub treasury_authority: &'a AccountInfo<'info>,
+    pub token_program: &'a AccountInfo<'info>,
+    pub deny_list: &'a AccountInfo<'info>,
+    pub association_set: &'a AccountInfo<'info>,
+    pub mint: &'a AccountInfo<'info>,
+    pub supported_mint: &'a AccountInfo<'info>,
+    pub fee_exemptions: &'a AccountInfo<'info>,
+    pub epoch_stats: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    /// May not exist yet
+    pub withdrawal_queue: &'a AccountInfo<'info>,
+}
+
+/// Accounts for `cancel_withdrawal_cpi`
+pub struct CancelWithdrawal<'a, 'info> {
+    /// Receives released nullifier rent
+    pub user: &'a AccountInfo<'info>,
+    pub protocol_state: &'a AccountInfo<'info>,
+    pub withdrawal_request: &'a AccountInfo<'info>,
+    /// In request order
+    pub points: &'a [AccountInfo<'info>],
+    /// One per point
+    pub nullifiers: &'a [AccountInfo<'info>],
+    pub user_activity: &'a AccountInfo<'info>,
+    /// May not exist
+    pub reward_account: &'a AccountInfo<'info>,
+    /// May not exist yet
+    pub withdrawal_queue: &'a AccountInfo<'info>,
+}
+
+/// Accounts for `split_point_cpi`
+pub struct SplitPoint<'a, 'info> {
+    /// Pays rent
+    pub holder: &'a AccountInfo<'info>,
+    pub protocol_state: &'a AccountInfo<'info>,
+    pub point: &'a AccountInfo<'info>,
+    pub nullifier: &'a AccountInfo<'info>,
+    /// One per output commitment
+    pub output_points: &'a [AccountInfo<'info>],
+    pub system_program: &'a AccountInfo<'info>,
+    pub commitment_tree: &'a AccountInfo<'info>,
+    pub root_history: &'a AccountInfo<'info>,
+    pub nullifier_filter: &'a AccountInfo<'info>,
+}
+
+/// Accounts for `transfer_point_cpi`
+pub struct TransferPoint<'a, 'info> {
+    /// Pays the new point's rent and receives the old one's
+    pub holder: &'a AccountInfo<'info>,
+    pub protocol_state: &'a AccountInfo<'info>,
+    pub point: &'a AccountInfo<'info>,
+    pub commitment_tree: &'a AccountInfo<'info>,
+    pub root_history: &'a AccountInfo<'info>,
+    /// At the PDA of the new commitment
+    pub new_point: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+    /// For the old point's key image
+    pub nullifier: &'a AccountInfo<'info>,
+    pub nullifier_filter: &'a AccountInfo<'info>,
+}
+
+/// Account list of one invocation: metas and the infos backing them, kept in step
+struct Accounts<'info> {
+    metas: Vec<AccountMeta>,
+    infos: Vec<AccountInfo<'info>>,
+}
+
+impl<'info> Accounts<'info> {
+    fn new(program: &AccountInfo<'info>, capacity: usize) -> Self {
+        let mut infos = Vec::with_capacity(capacity + 1);
+        infos.push(program.clone());
+        Self {
+            metas: Vec::with_capacity(capacity),
+            infos,
+        }
+    }
+    
+    fn push(&mut self, info: &AccountInfo<'info>, is_writable: bool, is_signer: bool) {
+        self.metas.push(AccountMeta {
+            pubkey: *info.key,
+            is_signer,
+            is_writable,
+        });
+        self.infos.push(info.clone());
+    }
+    
+    fn writable(&mut self, info: &AccountInfo<'info>) {
+        self.push(info, true, false);
+    }
+    
+    fn readonly(&mut self, info: &AccountInfo<'info>) {
+        self.push(info, false, false);
+    }
+    
+    fn writable_all(&mut self, infos: &[AccountInfo<'info>]) {
+        for info in infos {
+            self.writable(info);
+        }
+    }
+    
+    fn invoke(
+        self,
+        program_id: &Pubkey,
+        instruction: FPPInstruction,
+        signer_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let ix = Instruction {
+            program_id: *program_id,
+            accounts: self.metas,
+            data: instruction.try_to_vec()?,
+        };
+        invoke_signed(&ix, &self.infos, signer_seeds)
+    }
+}
+
+/// Deposit into the protocol, creating one point per commitment
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_cpi<'a, 'info>(
+    fpp_program: &'a AccountInfo<'info>,
+    accounts: Deposit<'a, 'info>,
+    amount: u64,
+    denomination: u64,
+    commitments: Vec<[u8; 32]>,
+    opening_proofs: Vec<OpeningProof>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let (first_point, other_points) = accounts
+        .points
+        .split_first()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    let mut metas = Accounts::new(fpp_program, 16 + accounts.points.len());
+    metas.push(accounts.user, true, true);
+    metas.writable(accounts.user_token);
+    metas.writable(accounts.treasury_token);
+    metas.writable(accounts.protocol_state);
+    metas.writable(first_point);
+    metas.readonly(accounts.mint);
+    metas.readonly(accounts.token_program);
+    metas.readonly(accounts.system_program);
+    metas.writable(accounts.commitment_tree);
+    metas.writable(accounts.root_history);
+    metas.writable(accounts.user_activity);
+    metas.readonly(accounts.deny_list);
+    metas.writable(accounts.supported_mint);
+    metas.writable(accounts.reward_account);
+    metas.readonly(accounts.fee_exemptions);
+    metas.writable(accounts.epoch_stats);
+    metas.readonly(accounts.instructions_sysvar);
+    metas.writable_all(other_points);
+    
+    let instruction = FPPInstruction::Deposit {
+        amount,
+        denomination,
+        commitments,
+        opening_proofs,
+        referrer: None,
+    };
+    metas.invoke(fpp_program.key, instruction, signer_seeds)
+}
+
+/// Spend points through a ring-signed, zero-knowledge `PrivacyPayment`
+#[allow(clippy::too_many_arguments)]
+pub fn privacy_payment_cpi<'a, 'info>(
+    fpp_program: &'a AccountInfo<'info>,
+    accounts: PrivacyPayment<'a, 'info>,
+    merkle_root: [u8; 32],
+    input_nullifiers: Vec<[u8; 32]>,
+    output_commitments: Vec<[u8; 32]>,
+    proof: Vec<u8>,
+    ring_signature: Vec<u8>,
+    range_proof_offset: i8,
+    encrypted_notes: Vec<Vec<u8>>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut metas = Accounts::new(
+        fpp_program,
+        10 + accounts.ring_members.len()
+            + accounts.nullifiers.len()
+            + accounts.output_points.len()
+            + accounts.encrypted_notes.len(),
+    );
+    metas.push(accounts.sender, true, true);
+    metas.writable(accounts.protocol_state);
+    metas.readonly(accounts.recipient);
+    metas.writable_all(accounts.ring_members);
+    metas.writable_all(accounts.nullifiers);
+    metas.writable_all(accounts.output_points);
+    metas.writable_all(accounts.encrypted_notes);
+    metas.readonly(accounts.verifier_program);
+    metas.readonly(accounts.system_program);
+    metas.readonly(accounts.root_source);
+    metas.readonly(accounts.instructions_sysvar);
+    metas.writable(accounts.nullifier_filter);
+    metas.writable(accounts.commitment_tree);
+    metas.writable(accounts.root_history);
+    
+    let instruction = FPPInstruction::PrivacyPayment {
+        merkle_root,
+        input_nullifiers,
+        output_commitments,
+        proof,
+        ring_signature,
+        range_proof_offset,
+        encrypted_notes,
+    };
+    metas.invoke(fpp_program.key, instruction, signer_seeds)
+}
+
+/// Open a withdrawal request for the given points
+pub fn request_withdrawal_cpi<'a, 'info>(
+    fpp_program: &'a AccountInfo<'info>,
+    accounts: RequestWithdrawal<'a, 'info>,
+    nullifiers: Vec<[u8; 32]>,
+    nonce: u64,
+    spend_signature: Vec<u8>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut metas =
+        Accounts::new(fpp_program, 9 + accounts.points.len() + accounts.nullifiers.len());
+    metas.push(accounts.user, true, true);
+    metas.writable(accounts.protocol_state);
+    metas.writable(accounts.withdrawal_request);
+    metas.writable_all(accounts.points);
+    metas.writable_all(accounts.nullifiers);
+    metas.readonly(accounts.system_program);
+    metas.writable(accounts.user_activity);
+    metas.writable(accounts.nullifier_filter);
+    metas.writable(accounts.reward_account);
+    metas.writable(accounts.withdrawal_queue);
+    metas.readonly(accounts.instructions_sysvar);
+    
+    let instruction = FPPInstruction::RequestWithdrawal {
+        point_ids: accounts.points.iter().map(|point| *point.key).collect(),
+        nullifiers,
+        nonce,
+        spend_signature,
+    };
+    metas.invoke(fpp_program.key, instruction, signer_seeds)
+}
+
+/// Pay out a withdrawal request once its delay has passed
+pub fn complete_withdrawal_cpi<'a, 'info>(
+    fpp_program: &'a AccountInfo<'info>,
+    accounts: CompleteWithdrawal<'a, 'info>,
+    unwrap_sol: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut metas = Accounts::new(fpp_program, 15);
+    metas.push(accounts.user, true, true);
+    metas.writable(accounts.user_token);
+    metas.writable(accounts.treasury_token);
+    metas.writable(accounts.protocol_state);
+    metas.writable(accounts.withdrawal_request);
+    metas.readonly(accounts.treasury_authority);
+    metas.readonly(accounts.token_program);
+    metas.readonly(accounts.deny_list);
+    metas.readonly(accounts.association_set);
+    metas.readonly(accounts.mint);
+    metas.writable(accounts.supported_mint);
+    metas.readonly(accounts.fee_exemptions);
+    metas.writable(accounts.epoch_stats);
+    metas.readonly(accounts.system_program);
+    metas.writable(accounts.withdrawal_queue);
+    
+    let instruction = FPPInstruction::CompleteWithdrawal { unwrap_sol };
+    metas.invoke(fpp_program.key, instruction, signer_seeds)
+}
+
+/// Cancel a withdrawal request, reactivating its points or burning them when `permanent`
+pub fn cancel_withdrawal_cpi<'a, 'info>(
+    fpp_program: &'a AccountInfo<'info>,
+    accounts: CancelWithdrawal<'a, 'info>,
+    permanent: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut metas =
+        Accounts::new(fpp_program, 6 + accounts.points.len() + accounts.nullifiers.len());
+    metas.push(accounts.user, true, true);
+    metas.writable(accounts.protocol_state);
+    metas.writable(accounts.withdrawal_request);
+    metas.writable_all(accounts.points);
+    metas.writable_all(accounts.nullifiers);
+    metas.writable(accounts.user_activity);
+    metas.writable(accounts.reward_account);
+    metas.writable(accounts.withdrawal_queue);
+    
+    let instruction = FPPInstruction::CancelWithdrawal { permanent };
+    metas.invoke(fpp_program.key, instruction, signer_seeds)
+}
+
+/// Split a point into outputs whose masses sum to the original
+#[allow(clippy::too_many_arguments)]
+pub fn split_point_cpi<'a, 'info>(
+    fpp_program: &'a AccountInfo<'info>,
+    accounts: SplitPoint<'a, 'info>,
+    nullifier: [u8; 32],
+    output_commitments: Vec<[u8; 32]>,
+    output_masses: Vec<u64>,
+    opening_proofs: Vec<OpeningProof>,
+    spend_signature: Vec<u8>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut metas = Accounts::new(fpp_program, 8 + accounts.output_points.len());
+    metas.push(accounts.holder, true, true);
+    metas.writable(accounts.protocol_state);
+    metas.writable(accounts.point);
+    metas.writable(accounts.nullifier);
+    metas.writable_all(accounts.output_points);
+    metas.readonly(accounts.system_program);
+    metas.writable(accounts.commitment_tree);
+    metas.writable(accounts.root_history);
+    metas.writable(accounts.nullifier_filter);
+    
+    let instruction = FPPInstruction::SplitPoint {
+        nullifier,
+        output_commitments,
+        output_masses,
+        opening_proofs,
+        spend_signature,
+    };
+    metas.invoke(fpp_program.key, instruction, signer_seeds)
+}
+
+/// Hand a point to `new_owner` at the PDA of `new_commitment`
+pub fn transfer_point_cpi<'a, 'info>(
+    fpp_program: &'a AccountInfo<'info>,
+    accounts: TransferPoint<'a, 'info>,
+    new_owner: Pubkey,
+    new_commitment: [u8; 32],
+    nullifier: [u8; 32],
+    spend_signature: Vec<u8>,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let mut metas = Accounts::new(fpp_program, 9);
+    metas.push(accounts.holder, true, true);
+    metas.readonly(accounts.protocol_state);
+    metas.writable(accounts.point);
+    metas.writable(accounts.commitment_tree);
+    metas.writable(accounts.root_history);
+    metas.writable(accounts.new_point);
+    metas.readonly(accounts.system_program);
+    metas.writable(accounts.nullifier);
+    metas.writable(accounts.nullifier_filter);
+    
+    let instruction = FPPInstruction::TransferPoint {
+        new_owner,
+        new_commitment,
+        nullifier,
+        spend_signature,
+    };
+    metas.invoke(fpp_program.key, instruction, signer_seeds)
+}
@@ -0,0 +1,145 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use floating_point_protocol_solana::state::{RateLimits, RootHistory};
+use serde::{de, Deserialize, Deserializer};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::DeployError;
+
+/// Deployment description, read from a TOML file
+///
+/// Optional settings are only enforced when present; anything left out keeps
+/// the program's defaults or whatever an earlier run applied.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct DeployConfig {
+    pub rpc_url: String,
+    #[serde(deserialize_with = "pubkey")]
+    pub program_id: Pubkey,
+    /// Keypair that pays for everything and becomes the protocol authority
+    pub authority_keypair: PathBuf,
+    #[serde(deserialize_with = "pubkey")]
+    pub usdt_mint: Pubkey,
+    /// Where the deployment manifest is written
+    #[serde(default = "default_manifest")]
+    pub manifest: PathBuf,
+    
+    pub deposit_fee_rate: u16,
+    pub withdrawal_fee_rate: u16,
+    #[serde(default = "default_root_history_size")]
+    pub root_history_size: u16,
+    
+    pub denominations: Option<Vec<u64>>,
+    pub emergency_penalty_rate: Option<u16>,
+    pub require_commitment_openings: Option<bool>,
+    #[serde(default, deserialize_with = "optional_pubkey")]
+    pub guardian: Option<Pubkey>,
+    #[serde(default, deserialize_with = "optional_pubkey")]
+    pub fee_receiver: Option<Pubkey>,
+    pub pool_bounds: Option<PoolBounds>,
+    pub rate_limits: Option<RateLimitConfig>,
+    
+    #[serde(default)]
+    pub circuits: Vec<CircuitConfig>,
+    
+    /// Create an address lookup table of the accounts most instructions use
+    #[serde(default = "default_true")]
+    pub lookup_table: bool,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PoolBounds {
+    pub min_denomination: u64,
+    pub max_denomination: u64,
+    /// Lamports
+    pub creation_bond: u64,
+}
+
+/// Per-user limits; a limit left out (or zero) isn't enforced
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Seconds
+    #[serde(default = "default_rate_limit_window")]
+    pub window: i64,
+    #[serde(default)]
+    pub max_deposits: u32,
+    #[serde(default)]
+    pub max_deposit_volume: u64,
+    #[serde(default)]
+    pub max_withdrawals: u32,
+    #[serde(default)]
+    pub max_withdrawal_volume: u64,
+}
+
+impl RateLimitConfig {
+    pub fn to_state(self) -> RateLimits {
+        RateLimits {
+            window: self.window,
+            max_deposits: self.max_deposits,
+            max_deposit_volume: self.max_deposit_volume,
+            max_withdrawals: self.max_withdrawals,
+            max_withdrawal_volume: self.max_withdrawal_volume,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CircuitConfig {
+    pub circuit_id: u16,
+    pub tree_depth: u8,
+    pub max_inputs: u8,
+    pub max_outputs: u8,
+    /// Borsh-encoded verifying key; its SHA-256 hash is registered on chain
+    pub verifying_key: PathBuf,
+}
+
+impl DeployConfig {
+    pub fn load(path: &Path) -> Result<Self, DeployError> {
+        let raw = fs::read_to_string(path)?;
+        let mut config: Self = toml::from_str(&raw)
+            .map_err(|e| DeployError::Config(format!("{}: {}", path.display(), e)))?;
+        
+        // Relative paths are relative to the config file, not the working directory
+        if let Some(base) = path.parent() {
+            for file in [&mut config.authority_keypair, &mut config.manifest] {
+                *file = base.join(&*file);
+            }
+            for circuit in &mut config.circuits {
+                circuit.verifying_key = base.join(&circuit.verifying_key);
+            }
+        }
+        Ok(config)
+    }
+}
+
+fn default_manifest() -> PathBuf {
+    PathBuf::from("deployment.toml")
+}
+
+fn default_root_history_size() -> u16 {
+    RootHistory::DEFAULT_SIZE
+}
+
+fn default_rate_limit_window() -> i64 {
+    RateLimits::DEFAULT_WINDOW
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+    let raw = String::deserialize(deserializer)?;
+    raw.parse()
+        .map_err(|_| de::Error::custom(format!("invalid pubkey: {}", raw)))
+}
+
+fn optional_pubkey<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Pubkey>, D::Error> {
+    pubkey(deserializer).map(Some)
+}
@@ -0,0 +1,17 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum DeployError {
+    #[error("Config Error: {0}")]
+    Config(String),
+    
+    #[error("IO Error: {0}")]
+    Io(#[from] std::io::Error),
+    
+    #[error("RPC Error: {0}")]
+    Rpc(String),
+    
+    /// On-chain state disagrees with the config in a way a re-run can't fix
+    #[error("Conflict: {0}")]
+    Conflict(String),
+}
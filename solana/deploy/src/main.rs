@@ -0,0 +1,561 @@
+//! Deploys a complete FPP instance from a TOML config.
+//!
+//! Each step reads chain state first and only sends what is missing or
+//! differs, so a re-run after a partial failure, or with an edited config,
+//! picks up where the last run stopped. Run with
+//! `cargo run -p fpp-deploy -- deploy.toml`; the deployment manifest is
+//! printed and written next to the config.
+
+mod config;
+mod error;
+mod manifest;
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+};
+
+use borsh::BorshDeserialize;
+use floating_point_protocol_solana::{
+    crypto::groth16::Groth16VerifyingKey,
+    instruction, migration::Versioned, pda,
+    state::{CircuitInfo, CommitmentTree, ProtocolState, RootHistory},
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    address_lookup_table::{
+        instruction::{create_lookup_table, extend_lookup_table},
+        state::AddressLookupTable,
+    },
+    commitment_config::CommitmentConfig,
+    hash::hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    system_program, sysvar,
+    transaction::Transaction,
+};
+use solana_zk_token_sdk::zk_token_proof_program;
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+
+use crate::{
+    config::DeployConfig,
+    error::DeployError,
+    manifest::{CircuitEntry, Manifest},
+};
+
+struct Deployer {
+    config: DeployConfig,
+    rpc: RpcClient,
+    authority: Keypair,
+}
+
+fn main() {
+    let path = env::args().nth(1).map(PathBuf::from).unwrap_or_else(|| {
+        eprintln!("usage: fpp-deploy <config.toml>");
+        process::exit(2);
+    });
+    if let Err(e) = run(&path) {
+        eprintln!("deployment failed: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run(path: &Path) -> Result<(), DeployError> {
+    let config = DeployConfig::load(path)?;
+    let authority = read_keypair_file(&config.authority_keypair).map_err(|e| {
+        DeployError::Config(format!("{}: {}", config.authority_keypair.display(), e))
+    })?;
+    let rpc = RpcClient::new_with_commitment(config.rpc_url.clone(), CommitmentConfig::confirmed());
+    let deployer = Deployer {
+        config,
+        rpc,
+        authority,
+    };
+    
+    deployer.check_program()?;
+    deployer.initialize()?;
+    let treasury_token_account = deployer.create_treasury_token_account()?;
+    deployer.apply_settings()?;
+    let circuits = deployer.register_circuits()?;
+    
+    let previous = Manifest::load(&deployer.config.manifest)?;
+    let lookup_table = if deployer.config.lookup_table {
+        let existing = previous
+            .and_then(|manifest| manifest.lookup_table)
+            .and_then(|table| table.parse().ok());
+        Some(deployer.sync_lookup_table(existing, &treasury_token_account)?)
+    } else {
+        None
+    };
+    
+    let program_id = &deployer.config.program_id;
+    let manifest = Manifest {
+        rpc_url: deployer.config.rpc_url.clone(),
+        program_id: program_id.to_string(),
+        authority: deployer.authority.pubkey().to_string(),
+        protocol_state: deployer.protocol_state().to_string(),
+        usdt_mint: deployer.config.usdt_mint.to_string(),
+        treasury: deployer.treasury().to_string(),
+        treasury_token_account: treasury_token_account.to_string(),
+        commitment_tree: CommitmentTree::find_address(program_id).0.to_string(),
+        root_history: RootHistory::find_address(program_id).0.to_string(),
+        lookup_table: lookup_table.map(|table| table.to_string()),
+        circuits,
+    };
+    let manifest = manifest.to_toml()?;
+    fs::write(&deployer.config.manifest, &manifest)?;
+    println!("\n# {}\n{}", deployer.config.manifest.display(), manifest);
+    Ok(())
+}
+
+impl Deployer {
+    fn protocol_state(&self) -> Pubkey {
+        pda::find_protocol_state(&self.config.program_id).0
+    }
+    
+    fn treasury(&self) -> Pubkey {
+        ProtocolState::find_treasury_authority(&self.config.program_id).0
+    }
+    
+    /// SPL Token or Token-2022, whichever owns the USDT mint
+    fn token_program(&self) -> Result<Pubkey, DeployError> {
+        let mint = self
+            .rpc
+            .get_account(&self.config.usdt_mint)
+            .map_err(|e| DeployError::Rpc(e.to_string()))?;
+        if mint.owner != spl_token::id() && mint.owner != spl_token_2022::id() {
+            return Err(DeployError::Config(format!(
+                "{} is not a token mint",
+                self.config.usdt_mint
+            )));
+        }
+        Ok(mint.owner)
+    }
+    
+    fn check_program(&self) -> Result<(), DeployError> {
+        let program = self
+            .rpc
+            .get_account(&self.config.program_id)
+            .map_err(|e| DeployError::Rpc(e.to_string()))?;
+        if !program.executable {
+            return Err(DeployError::Config(format!(
+                "{} is not a deployed program",
+                self.config.program_id
+            )));
+        }
+        Ok(())
+    }
+    
+    /// Initialize the protocol, which creates the protocol state, commitment tree and root
+    /// history PDAs
+    fn initialize(&self) -> Result<(), DeployError> {
+        let address = self.protocol_state();
+        let existing = self
+            .rpc
+            .get_account_with_commitment(&address, self.rpc.commitment())
+            .map_err(|e| DeployError::Rpc(e.to_string()))?
+            .value;
+        
+        // A pre-funded system account at the address is adopted by `Initialize`
+        if let Some(account) = existing.filter(|account| account.owner != system_program::id()) {
+            if account.owner != self.config.program_id {
+                return Err(DeployError::Conflict(format!(
+                    "protocol state {} is not owned by the program",
+                    address
+                )));
+            }
+            let state = ProtocolState::try_from_slice(&account.data).map_err(|e| {
+                DeployError::Conflict(format!("protocol state {}: {}", address, e))
+            })?;
+            if state.usdt_mint != self.config.usdt_mint || state.treasury != self.treasury() {
+                return Err(DeployError::Conflict(format!(
+                    "protocol state {} was initialized with mint {} and treasury {}",
+                    address, state.usdt_mint, state.treasury
+                )));
+            }
+            println!("protocol state {} already initialized", address);
+            return Ok(());
+        }
+        
+        let initialize = instruction::initialize(
+            &self.config.program_id,
+            &self.authority.pubkey(),
+            &address,
+            &self.config.usdt_mint,
+            self.config.deposit_fee_rate,
+            self.config.withdrawal_fee_rate,
+            self.config.root_history_size,
+        )
+        .map_err(|e| DeployError::Config(e.to_string()))?;
+        let signature = self.send(&[initialize], &[])?;
+        println!("protocol state {} initialized ({})", address, signature);
+        Ok(())
+    }
+    
+    fn create_treasury_token_account(&self) -> Result<Pubkey, DeployError> {
+        let token_program = self.token_program()?;
+        let address = get_associated_token_address_with_program_id(
+            &self.treasury(),
+            &self.config.usdt_mint,
+            &token_program,
+        );
+        let existing = self
+            .rpc
+            .get_account_with_commitment(&address, self.rpc.commitment())
+            .map_err(|e| DeployError::Rpc(e.to_string()))?
+            .value;
+        if existing.is_some() {
+            println!("treasury token account {} exists", address);
+            return Ok(address);
+        }
+        
+        let ix = create_associated_token_account_idempotent(
+            &self.authority.pubkey(),
+            &self.treasury(),
+            &self.config.usdt_mint,
+            &token_program,
+        );
+        let signature = self.send(&[ix], &[])?;
+        println!("treasury token account {} created ({})", address, signature);
+        Ok(address)
+    }
+    
+    /// Bring admin settings in line with the config
+    fn apply_settings(&self) -> Result<(), DeployError> {
+        let program_id = &self.config.program_id;
+        let authority = &self.authority.pubkey();
+        let protocol_state = &self.protocol_state();
+        let data = self
+            .rpc
+            .get_account_data(protocol_state)
+            .map_err(|e| DeployError::Rpc(e.to_string()))?;
+        let state = ProtocolState::try_from_slice(&data)
+            .map_err(|e| DeployError::Conflict(format!("protocol state: {}", e)))?;
+        
+        let mut changes = Vec::new();
+        
+        // Fee changes are queued behind the config timelock and applied on a later run
+        let fees = (self.config.deposit_fee_rate, self.config.withdrawal_fee_rate);
+        let pending = state.pending_config;
+        let queued = pending.is_pending()
+            && (pending.deposit_fee_rate, pending.withdrawal_fee_rate) == fees;
+        if (state.deposit_fee_rate, state.withdrawal_fee_rate) == fees {
+            if pending.is_pending() {
+                // Cancel a queued change the config no longer asks for
+                changes.push(instruction::update_fees(
+                    program_id,
+                    authority,
+                    protocol_state,
+                    fees.0,
+                    fees.1,
+                ));
+            }
+        } else if !queued {
+            changes.push(instruction::update_fees(
+                program_id,
+                authority,
+                protocol_state,
+                fees.0,
+                fees.1,
+            ));
+        } else if self.cluster_time()? >= pending.effective_at {
+            changes.push(instruction::apply_pending_config(program_id, protocol_state));
+        } else {
+            println!("fee change queued, effective at unix time {}", pending.effective_at);
+        }
+        if let Some(denominations) = &self.config.denominations {
+            let current: Vec<u64> =
+                state.denominations.iter().copied().filter(|d| *d != 0).collect();
+            if current != *denominations {
+                changes.push(instruction::set_denominations(
+                    program_id,
+                    authority,
+                    protocol_state,
+                    denominations.clone(),
+                ));
+            }
+        }
+        if let Some(rate) = self.config.emergency_penalty_rate {
+            if state.emergency_penalty_rate != rate {
+                changes.push(instruction::set_emergency_penalty(
+                    program_id,
+                    authority,
+                    protocol_state,
+                    rate,
+                ));
+            }
+        }
+        if let Some(required) = self.config.require_commitment_openings {
+            if state.require_commitment_openings != required {
+                changes.push(instruction::set_commitment_validation(
+                    program_id,
+                    authority,
+                    protocol_state,
+                    required,
+                ));
+            }
+        }
+        if let Some(guardian) = self.config.guardian {
+            if state.guardian != guardian {
+                changes.push(instruction::set_guardian(
+                    program_id,
+                    authority,
+                    protocol_state,
+                    guardian,
+                ));
+            }
+        }
+        if let Some(fee_receiver) = self.config.fee_receiver {
+            if state.fee_receiver != fee_receiver {
+                changes.push(instruction::set_fee_receiver(
+                    program_id,
+                    authority,
+                    protocol_state,
+                    fee_receiver,
+                ));
+            }
+        }
+        if let Some(bounds) = self.config.pool_bounds {
+            if (state.min_pool_denomination, state.max_pool_denomination, state.pool_creation_bond)
+                != (bounds.min_denomination, bounds.max_denomination, bounds.creation_bond)
+            {
+                changes.push(instruction::set_pool_bounds(
+                    program_id,
+                    authority,
+                    protocol_state,
+                    bounds.min_denomination,
+                    bounds.max_denomination,
+                    bounds.creation_bond,
+                ));
+            }
+        }
+        if let Some(limits) = self.config.rate_limits {
+            let limits = limits.to_state();
+            if state.rate_limits != limits {
+                changes.push(instruction::set_rate_limits(
+                    program_id,
+                    authority,
+                    protocol_state,
+                    limits,
+                ));
+            }
+        }
+        
+        if changes.is_empty() {
+            println!("protocol settings up to date");
+            return Ok(());
+        }
+        if state.authority != *authority {
+            return Err(DeployError::Conflict(format!(
+                "{} setting(s) differ from the config, but the protocol authority is {}; \
+                 apply them through that authority",
+                changes.len(),
+                state.authority
+            )));
+        }
+        let changes = changes
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| DeployError::Config(e.to_string()))?;
+        let signature = self.send(&changes, &[])?;
+        println!("applied {} setting change(s) ({})", changes.len(), signature);
+        Ok(())
+    }
+    
+    /// Register each configured circuit and publish its key as `circuit_version` 1
+    /// 
+    /// Registered circuits are immutable here; their keys are replaced with
+    /// `UpdateVerifyingKey`.
+    fn register_circuits(&self) -> Result<Vec<CircuitEntry>, DeployError> {
+        let program_id = &self.config.program_id;
+        let mut entries = Vec::with_capacity(self.config.circuits.len());
+        for circuit in &self.config.circuits {
+            let verifying_key = fs::read(&circuit.verifying_key).map_err(|e| {
+                DeployError::Config(format!("{}: {}", circuit.verifying_key.display(), e))
+            })?;
+            let verifying_key_hash = hash(&verifying_key).to_bytes();
+            let wanted = CircuitInfo {
+                version: CircuitInfo::VERSION,
+                is_initialized: true,
+                circuit_id: circuit.circuit_id,
+                tree_depth: circuit.tree_depth,
+                max_inputs: circuit.max_inputs,
+                max_outputs: circuit.max_outputs,
+                verifying_key_hash,
+                is_active: true,
+            };
+            let (address, _) = CircuitInfo::find_address(circuit.circuit_id, program_id);
+            let existing = self
+                .rpc
+                .get_account_with_commitment(&address, self.rpc.commitment())
+                .map_err(|e| DeployError::Rpc(e.to_string()))?
+                .value;
+            
+            match existing {
+                Some(account) => {
+                    let registered = CircuitInfo::try_from_slice(&account.data).map_err(|e| {
+                        DeployError::Conflict(format!("circuit {}: {}", circuit.circuit_id, e))
+                    })?;
+                    if (
+                        registered.tree_depth,
+                        registered.max_inputs,
+                        registered.max_outputs,
+                        registered.verifying_key_hash,
+                    ) != (
+                        wanted.tree_depth,
+                        wanted.max_inputs,
+                        wanted.max_outputs,
+                        wanted.verifying_key_hash,
+                    ) {
+                        return Err(DeployError::Conflict(format!(
+                            "circuit {} is registered with different parameters; \
+                             register the new circuit under a new id",
+                            circuit.circuit_id
+                        )));
+                    }
+                    println!("circuit {} already registered", circuit.circuit_id);
+                }
+                None => {
+                    let key = Groth16VerifyingKey::try_from_slice(&verifying_key).map_err(|e| {
+                        DeployError::Config(format!("{}: {}", circuit.verifying_key.display(), e))
+                    })?;
+                    let register = instruction::register_circuit(
+                        program_id,
+                        &self.authority.pubkey(),
+                        &self.protocol_state(),
+                        &self.authority.pubkey(),
+                        wanted.circuit_id,
+                        wanted.tree_depth,
+                        wanted.max_inputs,
+                        wanted.max_outputs,
+                        wanted.verifying_key_hash,
+                    )
+                    .map_err(|e| DeployError::Config(e.to_string()))?;
+                    let publish = instruction::update_verifying_key(
+                        program_id,
+                        &self.authority.pubkey(),
+                        &self.protocol_state(),
+                        &self.authority.pubkey(),
+                        wanted.circuit_id,
+                        1,
+                        key,
+                    )
+                    .map_err(|e| DeployError::Config(e.to_string()))?;
+                    let signature = self.send(&[register, publish], &[])?;
+                    println!("circuit {} registered ({})", circuit.circuit_id, signature);
+                }
+            }
+            
+            entries.push(CircuitEntry {
+                circuit_id: circuit.circuit_id,
+                address: address.to_string(),
+                verifying_key_hash: hex::encode(verifying_key_hash),
+            });
+        }
+        Ok(entries)
+    }
+    
+    /// Create the lookup table, or extend the existing one with any missing addresses
+    fn sync_lookup_table(
+        &self,
+        existing: Option<Pubkey>,
+        treasury_token_account: &Pubkey,
+    ) -> Result<Pubkey, DeployError> {
+        let program_id = &self.config.program_id;
+        let authority = self.authority.pubkey();
+        let addresses = [
+            *program_id,
+            self.protocol_state(),
+            CommitmentTree::find_address(program_id).0,
+            RootHistory::find_address(program_id).0,
+            self.config.usdt_mint,
+            *treasury_token_account,
+            self.token_program()?,
+            system_program::id(),
+            sysvar::instructions::id(),
+            zk_token_proof_program::id(),
+        ];
+        
+        let current = match existing {
+            Some(table) => self
+                .rpc
+                .get_account_with_commitment(&table, self.rpc.commitment())
+                .map_err(|e| DeployError::Rpc(e.to_string()))?
+                .value
+                .map(|account| {
+                    AddressLookupTable::deserialize(&account.data)
+                        .map(|state| (table, state.addresses.to_vec()))
+                        .map_err(|e| {
+                            DeployError::Conflict(format!("lookup table {}: {}", table, e))
+                        })
+                })
+                .transpose()?,
+            None => None,
+        };
+        
+        let mut instructions = Vec::new();
+        let (table, present) = match current {
+            Some(current) => current,
+            None => {
+                let slot = self
+                    .rpc
+                    .get_slot_with_commitment(CommitmentConfig::finalized())
+                    .map_err(|e| DeployError::Rpc(e.to_string()))?;
+                let (ix, table) = create_lookup_table(authority, authority, slot);
+                instructions.push(ix);
+                (table, Vec::new())
+            }
+        };
+        let missing: Vec<Pubkey> = addresses
+            .iter()
+            .filter(|address| !present.contains(address))
+            .copied()
+            .collect();
+        if missing.is_empty() {
+            println!("lookup table {} up to date", table);
+            return Ok(table);
+        }
+        
+        instructions.push(extend_lookup_table(table, authority, Some(authority), missing));
+        let signature = self.send(&instructions, &[])?;
+        println!("lookup table {} updated ({})", table, signature);
+        Ok(table)
+    }
+    
+    fn cluster_time(&self) -> Result<i64, DeployError> {
+        let slot = self
+            .rpc
+            .get_slot()
+            .map_err(|e| DeployError::Rpc(e.to_string()))?;
+        self.rpc
+            .get_block_time(slot)
+            .map_err(|e| DeployError::Rpc(e.to_string()))
+    }
+    
+    fn send(
+        &self,
+        instructions: &[Instruction],
+        extra_signers: &[&Keypair],
+    ) -> Result<Signature, DeployError> {
+        let blockhash = self
+            .rpc
+            .get_latest_blockhash()
+            .map_err(|e| DeployError::Rpc(e.to_string()))?;
+        let mut signers = vec![&self.authority];
+        signers.extend_from_slice(extra_signers);
+        let tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.authority.pubkey()),
+            &signers,
+            blockhash,
+        );
+        self.rpc
+            .send_and_confirm_transaction(&tx)
+            .map_err(|e| DeployError::Rpc(e.to_string()))
+    }
+}
@@ -0,0 +1,47 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::DeployError;
+
+/// Addresses of a deployed instance, written after every run
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Manifest {
+    pub rpc_url: String,
+    pub program_id: String,
+    pub authority: String,
+    pub protocol_state: String,
+    pub usdt_mint: String,
+    pub treasury: String,
+    pub treasury_token_account: String,
+    pub commitment_tree: String,
+    pub root_history: String,
+    pub lookup_table: Option<String>,
+    #[serde(default)]
+    pub circuits: Vec<CircuitEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CircuitEntry {
+    pub circuit_id: u16,
+    pub address: String,
+    /// Hex SHA-256 of the verifying key
+    pub verifying_key_hash: String,
+}
+
+impl Manifest {
+    /// Manifest from an earlier run, if there is one
+    pub fn load(path: &Path) -> Result<Option<Self>, DeployError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = fs::read_to_string(path)?;
+        toml::from_str(&raw)
+            .map(Some)
+            .map_err(|e| DeployError::Config(format!("{}: {}", path.display(), e)))
+    }
+    
+    pub fn to_toml(&self) -> Result<String, DeployError> {
+        toml::to_string_pretty(self).map_err(|e| DeployError::Config(e.to_string()))
+    }
+}
@@ -0,0 +1,367 @@
+//! Accounting export of a business's disclosed transaction history.
+//!
+//! Records come from notes the business decrypted with its own viewing key,
+//! so nothing outside that scope is ever exported. Each record is valued in
+//! USD at transaction time through a `PriceFeed` and written as CSV (with
+//! configurable columns) or OFX for import into ERP and bookkeeping tools.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Token amounts are in base units with this many decimals
+pub const TOKEN_DECIMALS: u32 = 6;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Deposit,
+    Incoming,
+    Outgoing,
+    Withdrawal,
+}
+
+impl Direction {
+    fn label(self) -> &'static str {
+        match self {
+            Direction::Deposit => "deposit",
+            Direction::Incoming => "incoming",
+            Direction::Outgoing => "outgoing",
+            Direction::Withdrawal => "withdrawal",
+        }
+    }
+
+    /// Whether value leaves the business's shielded balance
+    fn is_debit(self) -> bool {
+        matches!(self, Direction::Outgoing | Direction::Withdrawal)
+    }
+}
+
+/// One transaction visible to the business's viewing key
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DisclosedTransaction {
+    pub signature: String,
+    pub timestamp: i64,
+    pub direction: Direction,
+    /// Gross amount in token base units
+    pub amount: u64,
+    /// Protocol fee in token base units
+    pub fee: u64,
+    pub counterparty: Option<String>,
+    pub memo: Option<String>,
+}
+
+/// USD price of one whole token at a point in time
+pub trait PriceFeed {
+    fn usd_price_at(&self, timestamp: i64) -> Option<f64>;
+}
+
+/// Fixed price, e.g. 1.0 for a USD stablecoin
+pub struct FixedPriceFeed(pub f64);
+
+impl PriceFeed for FixedPriceFeed {
+    fn usd_price_at(&self, _timestamp: i64) -> Option<f64> {
+        Some(self.0)
+    }
+}
+
+/// Historical samples; a transaction takes the latest sample at or before it
+#[derive(Debug, Default, Clone)]
+pub struct HistoricalPriceFeed {
+    samples: BTreeMap<i64, f64>,
+}
+
+impl HistoricalPriceFeed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, timestamp: i64, usd_price: f64) {
+        self.samples.insert(timestamp, usd_price);
+    }
+}
+
+impl PriceFeed for HistoricalPriceFeed {
+    fn usd_price_at(&self, timestamp: i64) -> Option<f64> {
+        self.samples.range(..=timestamp).next_back().map(|(_, price)| *price)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Date,
+    Signature,
+    Type,
+    Amount,
+    Fee,
+    NetAmount,
+    UsdPrice,
+    UsdValue,
+    UsdFee,
+    Counterparty,
+    Memo,
+}
+
+impl Column {
+    pub const DEFAULT: &'static [Column] = &[
+        Column::Date,
+        Column::Signature,
+        Column::Type,
+        Column::Amount,
+        Column::Fee,
+        Column::NetAmount,
+        Column::UsdPrice,
+        Column::UsdValue,
+        Column::UsdFee,
+        Column::Counterparty,
+        Column::Memo,
+    ];
+
+    fn header(self) -> &'static str {
+        match self {
+            Column::Date => "date",
+            Column::Signature => "signature",
+            Column::Type => "type",
+            Column::Amount => "amount",
+            Column::Fee => "fee",
+            Column::NetAmount => "net_amount",
+            Column::UsdPrice => "usd_price",
+            Column::UsdValue => "usd_value",
+            Column::UsdFee => "usd_fee",
+            Column::Counterparty => "counterparty",
+            Column::Memo => "memo",
+        }
+    }
+}
+
+/// Export as CSV with the given columns; amounts are signed from the business's view
+pub fn export_csv(
+    transactions: &[DisclosedTransaction],
+    feed: &dyn PriceFeed,
+    columns: &[Column],
+) -> String {
+    let mut out = String::new();
+    let headers: Vec<&str> = columns.iter().map(|column| column.header()).collect();
+    out.push_str(&headers.join(","));
+    out.push_str("\r\n");
+
+    for tx in transactions {
+        let price = feed.usd_price_at(tx.timestamp);
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|column| match column {
+                Column::Date => format_iso8601(tx.timestamp),
+                Column::Signature => tx.signature.clone(),
+                Column::Type => tx.direction.label().to_string(),
+                Column::Amount => format_signed(tx.amount, tx.direction.is_debit()),
+                Column::Fee => format_token(tx.fee),
+                Column::NetAmount => format_signed(net_amount(tx), tx.direction.is_debit()),
+                Column::UsdPrice => price.map(|p| format!("{:.6}", p)).unwrap_or_default(),
+                Column::UsdValue => price
+                    .map(|p| format_usd(signed_units(net_amount(tx), tx.direction), p))
+                    .unwrap_or_default(),
+                Column::UsdFee => price
+                    .map(|p| format_usd(tx.fee as i128, p))
+                    .unwrap_or_default(),
+                Column::Counterparty => tx.counterparty.clone().unwrap_or_default(),
+                Column::Memo => tx.memo.clone().unwrap_or_default(),
+            })
+            .map(|cell| csv_escape(&cell))
+            .collect();
+        out.push_str(&cells.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// Export as an OFX 2.2 bank statement; fees become separate `FEE` entries
+pub fn export_ofx(
+    transactions: &[DisclosedTransaction],
+    feed: &dyn PriceFeed,
+    account_id: &str,
+    currency: &str,
+) -> String {
+    let start = transactions.iter().map(|tx| tx.timestamp).min().unwrap_or(0);
+    let end = transactions.iter().map(|tx| tx.timestamp).max().unwrap_or(0);
+
+    let mut entries = String::new();
+    for tx in transactions {
+        let memo = match (&tx.memo, feed.usd_price_at(tx.timestamp)) {
+            (Some(memo), Some(price)) => format!("{} (USD {:.6})", memo, price),
+            (Some(memo), None) => memo.clone(),
+            (None, Some(price)) => format!("USD {:.6}", price),
+            (None, None) => String::new(),
+        };
+        entries.push_str(&ofx_entry(
+            if tx.direction.is_debit() { "DEBIT" } else { "CREDIT" },
+            tx.timestamp,
+            &format_signed(net_amount(tx), tx.direction.is_debit()),
+            &tx.signature,
+            tx.counterparty.as_deref().unwrap_or(tx.direction.label()),
+            &memo,
+        ));
+        if tx.fee > 0 {
+            entries.push_str(&ofx_entry(
+                "FEE",
+                tx.timestamp,
+                &format_signed(tx.fee, true),
+                &format!("{}-fee", tx.signature),
+                "Floating Point Protocol",
+                "protocol fee",
+            ));
+        }
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n\
+         <?OFX OFXHEADER=\"200\" VERSION=\"220\" SECURITY=\"NONE\" \
+         OLDFILEUID=\"NONE\" NEWFILEUID=\"NONE\"?>\n\
+         <OFX>\n\
+         <BANKMSGSRSV1>\n\
+         <STMTTRNRS>\n\
+         <TRNUID>0</TRNUID>\n\
+         <STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>\n\
+         <STMTRS>\n\
+         <CURDEF>{}</CURDEF>\n\
+         <BANKACCTFROM><BANKID>FPP</BANKID><ACCTID>{}</ACCTID>\
+         <ACCTTYPE>CHECKING</ACCTTYPE></BANKACCTFROM>\n\
+         <BANKTRANLIST>\n\
+         <DTSTART>{}</DTSTART>\n\
+         <DTEND>{}</DTEND>\n\
+         {}\
+         </BANKTRANLIST>\n\
+         </STMTRS>\n\
+         </STMTTRNRS>\n\
+         </BANKMSGSRSV1>\n\
+         </OFX>\n",
+        xml_escape(currency),
+        xml_escape(account_id),
+        format_ofx_date(start),
+        format_ofx_date(end),
+        entries
+    )
+}
+
+fn ofx_entry(
+    kind: &str,
+    timestamp: i64,
+    amount: &str,
+    fitid: &str,
+    name: &str,
+    memo: &str,
+) -> String {
+    format!(
+        "<STMTTRN>\n\
+         <TRNTYPE>{}</TRNTYPE>\n\
+         <DTPOSTED>{}</DTPOSTED>\n\
+         <TRNAMT>{}</TRNAMT>\n\
+         <FITID>{}</FITID>\n\
+         <NAME>{}</NAME>\n\
+         <MEMO>{}</MEMO>\n\
+         </STMTTRN>\n",
+        kind,
+        format_ofx_date(timestamp),
+        amount,
+        xml_escape(fitid),
+        xml_escape(name),
+        xml_escape(memo)
+    )
+}
+
+/// Amount that actually moved after the protocol fee
+fn net_amount(tx: &DisclosedTransaction) -> u64 {
+    tx.amount.saturating_sub(tx.fee)
+}
+
+fn signed_units(amount: u64, direction: Direction) -> i128 {
+    if direction.is_debit() {
+        -(amount as i128)
+    } else {
+        amount as i128
+    }
+}
+
+fn format_token(units: u64) -> String {
+    let scale = 10u64.pow(TOKEN_DECIMALS);
+    format!(
+        "{}.{:0width$}",
+        units / scale,
+        units % scale,
+        width = TOKEN_DECIMALS as usize
+    )
+}
+
+fn format_signed(units: u64, negative: bool) -> String {
+    if negative && units > 0 {
+        format!("-{}", format_token(units))
+    } else {
+        format_token(units)
+    }
+}
+
+fn format_usd(units: i128, price: f64) -> String {
+    let value = units as f64 / 10f64.powi(TOKEN_DECIMALS as i32) * price;
+    format!("{:.2}", value)
+}
+
+/// Days since 1970-01-01 to a proleptic Gregorian date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn utc_parts(timestamp: i64) -> (i64, u32, u32, i64, i64, i64) {
+    let (year, month, day) = civil_from_days(timestamp.div_euclid(86_400));
+    let seconds = timestamp.rem_euclid(86_400);
+    (year, month, day, seconds / 3600, seconds % 3600 / 60, seconds % 60)
+}
+
+fn format_iso8601(timestamp: i64) -> String {
+    let (year, month, day, hour, minute, second) = utc_parts(timestamp);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, hour, minute, second
+    )
+}
+
+fn format_ofx_date(timestamp: i64) -> String {
+    let (year, month, day, hour, minute, second) = utc_parts(timestamp);
+    format!(
+        "{:04}{:02}{:02}{:02}{:02}{:02}[0:GMT]",
+        year, month, day, hour, minute, second
+    )
+}
+
+fn csv_escape(cell: &str) -> String {
+    // Leading formula characters are neutralised so spreadsheets don't execute them
+    let formula = cell.starts_with(['=', '+', '@']) || (cell.starts_with('-') && !is_number(cell));
+    let cell = if formula {
+        format!("'{}", cell)
+    } else {
+        cell.to_string()
+    };
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell
+    }
+}
+
+fn is_number(cell: &str) -> bool {
+    cell.trim_start_matches('-').chars().all(|c| c.is_ascii_digit() || c == '.')
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,110 @@
+//! HTTP API over the mirror.
+//!
+//! Wallets look up a commitment's leaf, page through a tree's leaves to build
+//! Merkle paths, and check nullifiers without an RPC call per note.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use fpp_indexer::{
+    error::IndexerError,
+    mirror::{CommitmentRecord, Mirror, NullifierRecord, Stats, MAX_PAGE},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    NotFound,
+    Internal(String),
+}
+
+impl From<IndexerError> for ApiError {
+    fn from(e: IndexerError) -> Self {
+        ApiError::Internal(e.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "not found".to_string()),
+            ApiError::Internal(message) => (StatusCode::INTERNAL_SERVER_ERROR, message),
+        };
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
+
+#[derive(Deserialize)]
+struct LeafRange {
+    #[serde(default)]
+    generation: u32,
+    #[serde(default)]
+    from: u64,
+    limit: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct NullifierStatus {
+    spent: bool,
+    #[serde(flatten)]
+    record: Option<NullifierRecord>,
+}
+
+pub fn router(mirror: Arc<Mirror>) -> Router {
+    Router::new()
+        .route("/commitments", get(commitments))
+        .route("/commitments/:commitment", get(commitment))
+        .route("/nullifiers/:nullifier", get(nullifier))
+        .route("/stats", get(stats))
+        .with_state(mirror)
+}
+
+async fn commitment(
+    State(mirror): State<Arc<Mirror>>,
+    Path(commitment): Path<String>,
+) -> Result<Json<CommitmentRecord>, ApiError> {
+    let commitment = parse_hash(&commitment)?;
+    let record = mirror.commitment(&commitment).await?;
+    record.map(Json).ok_or(ApiError::NotFound)
+}
+
+async fn commitments(
+    State(mirror): State<Arc<Mirror>>,
+    Query(range): Query<LeafRange>,
+) -> Result<Json<Vec<CommitmentRecord>>, ApiError> {
+    let limit = range.limit.unwrap_or(MAX_PAGE);
+    Ok(Json(mirror.commitments(range.generation, range.from, limit).await?))
+}
+
+async fn nullifier(
+    State(mirror): State<Arc<Mirror>>,
+    Path(nullifier): Path<String>,
+) -> Result<Json<NullifierStatus>, ApiError> {
+    let nullifier = parse_hash(&nullifier)?;
+    let record = mirror.nullifier(&nullifier).await?;
+    Ok(Json(NullifierStatus {
+        spent: record.is_some(),
+        record,
+    }))
+}
+
+async fn stats(State(mirror): State<Arc<Mirror>>) -> Result<Json<Stats>, ApiError> {
+    Ok(Json(mirror.stats().await?))
+}
+
+fn parse_hash(value: &str) -> Result<[u8; 32], ApiError> {
+    let bytes = hex::decode(value)
+        .map_err(|_| ApiError::BadRequest(format!("invalid hex: {}", value)))?;
+    bytes
+        .try_into()
+        .map_err(|_| ApiError::BadRequest("expected 32 bytes".into()))
+}
@@ -0,0 +1,102 @@
+//! Per-epoch nullifier bloom filter publishing.
+//!
+//! Filters are written as borsh-encoded `EpochBloom` files next to a JSON
+//! manifest, so they can be served from any static file host.
+
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use borsh::BorshSerialize;
+use floating_point_protocol_solana::bloom::{EpochBloom, NullifierBloom};
+use serde::{Deserialize, Serialize};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Entry in the published manifest
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ManifestEntry {
+    pub epoch: u64,
+    pub last_slot: u64,
+    pub count: u32,
+    pub file: String,
+}
+
+pub struct BloomPublisher {
+    out_dir: PathBuf,
+    expected_per_epoch: usize,
+    false_positive_rate: f64,
+    epochs: BTreeMap<u64, EpochBloom>,
+}
+
+impl BloomPublisher {
+    pub fn new(out_dir: impl Into<PathBuf>, expected_per_epoch: usize, false_positive_rate: f64) -> Self {
+        Self {
+            out_dir: out_dir.into(),
+            expected_per_epoch,
+            false_positive_rate,
+            epochs: BTreeMap::new(),
+        }
+    }
+    
+    /// Record a nullifier spent at `slot` in `epoch`
+    pub fn record(&mut self, epoch: u64, slot: u64, nullifier: &[u8; 32]) {
+        let entry = self.entry(epoch, slot);
+        entry.filter.insert(nullifier);
+        entry.count = entry.count.saturating_add(1);
+    }
+    
+    /// Mark the epoch as indexed through `slot` even if no spends occurred
+    pub fn advance(&mut self, epoch: u64, slot: u64) {
+        self.entry(epoch, slot);
+    }
+    
+    fn entry(&mut self, epoch: u64, slot: u64) -> &mut EpochBloom {
+        let (expected, rate) = (self.expected_per_epoch, self.false_positive_rate);
+        let entry = self.epochs.entry(epoch).or_insert_with(|| EpochBloom {
+            epoch,
+            first_slot: slot,
+            last_slot: slot,
+            count: 0,
+            filter: NullifierBloom::with_capacity(expected, rate),
+        });
+        entry.first_slot = entry.first_slot.min(slot);
+        entry.last_slot = entry.last_slot.max(slot);
+        entry
+    }
+    
+    pub fn get(&self, epoch: u64) -> Option<&EpochBloom> {
+        self.epochs.get(&epoch)
+    }
+    
+    /// Write every filter and the manifest to the output directory
+    pub fn publish_all(&self) -> io::Result<()> {
+        fs::create_dir_all(&self.out_dir)?;
+        let mut manifest = Vec::with_capacity(self.epochs.len());
+        for bloom in self.epochs.values() {
+            let file = file_name(bloom.epoch);
+            write_atomic(&self.out_dir.join(&file), &bloom.try_to_vec()?)?;
+            manifest.push(ManifestEntry {
+                epoch: bloom.epoch,
+                last_slot: bloom.last_slot,
+                count: bloom.count,
+                file,
+            });
+        }
+        let encoded = serde_json::to_vec_pretty(&manifest)?;
+        write_atomic(&self.out_dir.join(MANIFEST_FILE), &encoded)
+    }
+}
+
+pub fn file_name(epoch: u64) -> String {
+    format!("nullifiers-{}.bloom", epoch)
+}
+
+// Readers must never observe a half-written filter
+fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, bytes)?;
+    fs::rename(tmp, path)
+}
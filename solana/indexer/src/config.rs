@@ -0,0 +1,40 @@
+use std::{env, net::SocketAddr, str::FromStr};
+
+use solana_sdk::pubkey::Pubkey;
+
+/// Indexer configuration, read from `FPP_*` environment variables
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub bind: SocketAddr,
+    pub rpc_url: String,
+    pub ws_url: String,
+    pub program_id: Pubkey,
+    pub database_url: String,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, String> {
+        let rpc_url = env::var("FPP_RPC_URL")
+            .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string());
+        // Public RPC nodes serve websockets on the same host
+        let ws_url = env::var("FPP_WS_URL").unwrap_or_else(|_| {
+            rpc_url
+                .replacen("https://", "wss://", 1)
+                .replacen("http://", "ws://", 1)
+        });
+        Ok(Self {
+            bind: parse_var("FPP_BIND", "0.0.0.0:8090")?,
+            rpc_url,
+            ws_url,
+            program_id: parse_var("FPP_PROGRAM_ID", "11111111111111111111111111111111")?,
+            database_url: env::var("FPP_DATABASE_URL")
+                .unwrap_or_else(|_| "sqlite://fpp-indexer.db?mode=rwc".to_string()),
+        })
+    }
+}
+
+fn parse_var<T: FromStr>(name: &str, default: &str) -> Result<T, String> {
+    let raw = env::var(name).unwrap_or_else(|_| default.to_string());
+    raw.parse()
+        .map_err(|_| format!("invalid value for {}: {}", name, raw))
+}
@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum IndexerError {
+    #[error("Database Error: {0}")]
+    Database(#[from] sqlx::Error),
+    
+    #[error("RPC Error: {0}")]
+    Rpc(String),
+    
+    #[error("Invalid Encoding: {0}")]
+    InvalidEncoding(String),
+}
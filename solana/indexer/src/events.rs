@@ -0,0 +1,26 @@
+//! Decoding program events from transaction logs.
+//!
+//! Events arrive as `Program data:` lines, both in `getTransaction` metadata
+//! and in `logsSubscribe` notifications, so backfill and live updates share
+//! this decoder.
+
+use base64::Engine;
+use floating_point_protocol_solana::events::FPPEvent;
+
+const DATA_LOG_PREFIX: &str = "Program data: ";
+
+/// Decode one log line, if it carries an event of the program
+pub fn parse_log(line: &str) -> Option<FPPEvent> {
+    let fields = line
+        .strip_prefix(DATA_LOG_PREFIX)?
+        .split_whitespace()
+        .map(|field| base64::engine::general_purpose::STANDARD.decode(field))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    FPPEvent::from_log_data(&fields)
+}
+
+/// Every event in a transaction's logs, in emission order
+pub fn parse_logs(logs: &[String]) -> Vec<FPPEvent> {
+    logs.iter().filter_map(|line| parse_log(line)).collect()
+}
@@ -0,0 +1,147 @@
+//! Keeping the mirror in step with the chain.
+//!
+//! Each session subscribes to the program's logs first, then backfills every
+//! transaction since the mirror's cursor over RPC, then applies live
+//! notifications. Notifications that arrive during the backfill are buffered
+//! by the subscription, and applying a transaction twice is a no-op, so the
+//! overlap loses nothing.
+
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use fpp_indexer::{error::IndexerError, events, mirror::Mirror};
+use futures_util::StreamExt;
+use solana_client::{
+    nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::{RpcTransactionConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::UiTransactionEncoding;
+
+/// Signatures per `getSignaturesForAddress` page
+const SIGNATURE_PAGE: usize = 1000;
+
+/// Pause before reconnecting after a dropped subscription or RPC failure
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+pub struct Follower {
+    pub rpc: RpcClient,
+    pub ws_url: String,
+    pub program_id: Pubkey,
+    pub mirror: Arc<Mirror>,
+}
+
+impl Follower {
+    /// Follow the chain forever, reconnecting on errors
+    pub async fn run(self) {
+        loop {
+            if let Err(e) = self.session().await {
+                eprintln!("indexer session ended: {}", e);
+            }
+            tokio::time::sleep(RETRY_DELAY).await;
+        }
+    }
+    
+    async fn session(&self) -> Result<(), IndexerError> {
+        let pubsub = PubsubClient::new(&self.ws_url)
+            .await
+            .map_err(|e| IndexerError::Rpc(e.to_string()))?;
+        let (mut notifications, unsubscribe) = pubsub
+            .logs_subscribe(
+                RpcTransactionLogsFilter::Mentions(vec![self.program_id.to_string()]),
+                RpcTransactionLogsConfig {
+                    commitment: Some(self.rpc.commitment()),
+                },
+            )
+            .await
+            .map_err(|e| IndexerError::Rpc(e.to_string()))?;
+        
+        let applied = self.backfill().await?;
+        println!("backfilled {} transactions", applied);
+        
+        while let Some(notification) = notifications.next().await {
+            let logs = notification.value;
+            if logs.err.is_some() {
+                continue;
+            }
+            let events = events::parse_logs(&logs.logs);
+            self.mirror
+                .apply(&logs.signature, notification.context.slot, &events)
+                .await?;
+        }
+        unsubscribe().await;
+        Err(IndexerError::Rpc("log subscription closed".to_string()))
+    }
+    
+    /// Apply every transaction newer than the cursor, oldest first
+    async fn backfill(&self) -> Result<usize, IndexerError> {
+        let until = self
+            .mirror
+            .cursor()
+            .await?
+            .as_deref()
+            .map(Signature::from_str)
+            .transpose()
+            .map_err(|e| IndexerError::InvalidEncoding(e.to_string()))?;
+        let mut statuses = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self
+                .rpc
+                .get_signatures_for_address_with_config(
+                    &self.program_id,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until,
+                        limit: Some(SIGNATURE_PAGE),
+                        commitment: Some(self.rpc.commitment()),
+                    },
+                )
+                .await
+                .map_err(|e| IndexerError::Rpc(e.to_string()))?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            before = Some(
+                Signature::from_str(&last.signature)
+                    .map_err(|e| IndexerError::InvalidEncoding(e.to_string()))?,
+            );
+            let full = page.len() == SIGNATURE_PAGE;
+            statuses.extend(page);
+            if !full {
+                break;
+            }
+        }
+        
+        let mut applied = 0;
+        for status in statuses.iter().rev().filter(|status| status.err.is_none()) {
+            let signature = Signature::from_str(&status.signature)
+                .map_err(|e| IndexerError::InvalidEncoding(e.to_string()))?;
+            let tx = self
+                .rpc
+                .get_transaction_with_config(
+                    &signature,
+                    RpcTransactionConfig {
+                        encoding: Some(UiTransactionEncoding::Base64),
+                        commitment: Some(self.rpc.commitment()),
+                        max_supported_transaction_version: Some(0),
+                    },
+                )
+                .await
+                .map_err(|e| IndexerError::Rpc(e.to_string()))?;
+            let logs: Vec<String> = tx
+                .transaction
+                .meta
+                .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages))
+                .unwrap_or_default();
+            if self
+                .mirror
+                .apply(&status.signature, status.slot, &events::parse_logs(&logs))
+                .await?
+            {
+                applied += 1;
+            }
+        }
+        Ok(applied)
+    }
+}
@@ -0,0 +1,8 @@
+//! Floating Point Protocol indexer.
+
+pub mod accounting;
+pub mod bloom;
+pub mod error;
+pub mod events;
+pub mod metrics;
+pub mod mirror;
@@ -0,0 +1,44 @@
+//! FPP indexer service.
+//!
+//! Follows the program over RPC and websockets, mirrors the commitments,
+//! nullifiers and totals its events report into SQLite or Postgres, and
+//! serves them to wallets over HTTP.
+
+mod api;
+mod config;
+mod follow;
+
+use std::sync::Arc;
+
+use fpp_indexer::mirror::Mirror;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+
+use crate::{config::Config, follow::Follower};
+
+#[tokio::main]
+async fn main() {
+    let config = Config::from_env().unwrap_or_else(|e| {
+        eprintln!("configuration error: {}", e);
+        std::process::exit(1);
+    });
+    let mirror = Mirror::connect(&config.database_url).await.unwrap_or_else(|e| {
+        eprintln!("failed to open {}: {}", config.database_url, e);
+        std::process::exit(1);
+    });
+    let mirror = Arc::new(mirror);
+    
+    let follower = Follower {
+        rpc: RpcClient::new_with_commitment(config.rpc_url.clone(), CommitmentConfig::confirmed()),
+        ws_url: config.ws_url.clone(),
+        program_id: config.program_id,
+        mirror: mirror.clone(),
+    };
+    tokio::spawn(follower.run());
+    
+    println!("FPP indexer listening on {}", config.bind);
+    axum::Server::bind(&config.bind)
+        .serve(api::router(mirror).into_make_service())
+        .await
+        .expect("server error");
+}
@@ -0,0 +1,45 @@
+//! Prometheus-style metrics derived from on-chain state.
+//!
+//! The indexer renders the text exposition format so any scraper can alert
+//! on commitment tree occupancy well before a rollover is due.
+
+use std::fmt::Write;
+
+use borsh::BorshDeserialize;
+use floating_point_protocol_solana::state::{CommitmentTree, TreeHealth};
+
+/// Decode a commitment tree account and report its health at `now`
+pub fn tree_health(account_data: &[u8], now: i64) -> std::io::Result<TreeHealth> {
+    let tree = CommitmentTree::try_from_slice(account_data)?;
+    Ok(tree.health(now))
+}
+
+/// Render tree health in the Prometheus text format
+pub fn render_tree_health(health: &TreeHealth) -> String {
+    // NaN marks an unknown estimate rather than dropping the series
+    let seconds_to_full = health
+        .seconds_to_full
+        .map(|seconds| seconds.to_string())
+        .unwrap_or_else(|| "NaN".to_string());
+    let gauges = [
+        ("fpp_tree_generation", "Commitment tree generation", health.generation.to_string()),
+        ("fpp_tree_leaves", "Leaves in the active tree", health.leaves.to_string()),
+        ("fpp_tree_capacity", "Leaf capacity of the tree", health.capacity.to_string()),
+        ("fpp_tree_remaining", "Leaves left before the tree is full", health.remaining.to_string()),
+        ("fpp_tree_leaves_per_day", "Recent insertion rate", health.leaves_per_day.to_string()),
+        ("fpp_tree_seconds_to_full", "Projected seconds until full", seconds_to_full),
+        (
+            "fpp_tree_needs_rollover",
+            "Whether governance should roll the tree over",
+            (health.needs_rollover() as u8).to_string(),
+        ),
+    ];
+    
+    let mut out = String::new();
+    for (name, help, value) in gauges {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} gauge", name);
+        let _ = writeln!(out, "{} {}", name, value);
+    }
+    out
+}
@@ -0,0 +1,471 @@
+//! SQL mirror of the program's commitments, nullifiers and totals.
+//!
+//! Built from program events only, so the tables can be rebuilt from chain
+//! history at any time. The same queries run on SQLite and Postgres through
+//! sqlx's `Any` driver; the backend is picked by the database URL. Hashes are
+//! stored as lowercase hex and keys as base58, matching what the API serves.
+
+use floating_point_protocol_solana::events::FPPEvent;
+use serde::Serialize;
+use solana_sdk::pubkey::Pubkey;
+use sqlx::{
+    any::{AnyConnection, AnyPoolOptions, AnyRow},
+    AnyPool, Row,
+};
+
+use crate::error::IndexerError;
+
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE IF NOT EXISTS transactions (
+        signature TEXT PRIMARY KEY,
+        slot BIGINT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS commitments (
+        commitment TEXT PRIMARY KEY,
+        tree_generation BIGINT NOT NULL,
+        leaf_index BIGINT NOT NULL,
+        merkle_root TEXT NOT NULL,
+        signature TEXT NOT NULL,
+        slot BIGINT NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS commitments_by_leaf
+        ON commitments (tree_generation, leaf_index)",
+    "CREATE TABLE IF NOT EXISTS nullifiers (
+        nullifier TEXT PRIMARY KEY,
+        signature TEXT NOT NULL,
+        slot BIGINT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS mint_stats (
+        mint TEXT PRIMARY KEY,
+        deposits BIGINT NOT NULL,
+        deposited BIGINT NOT NULL,
+        withdrawals_requested BIGINT NOT NULL,
+        withdrawals BIGINT NOT NULL,
+        withdrawn BIGINT NOT NULL,
+        fees BIGINT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS counters (
+        name TEXT PRIMARY KEY,
+        value BIGINT NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS sync_cursor (
+        id BIGINT PRIMARY KEY,
+        signature TEXT NOT NULL
+    )",
+];
+
+const PRIVACY_PAYMENTS: &str = "privacy_payments";
+const DEPOSIT_FEE_RATE: &str = "deposit_fee_rate";
+const WITHDRAWAL_FEE_RATE: &str = "withdrawal_fee_rate";
+
+/// Upper bound on one page of `commitments`
+pub const MAX_PAGE: u32 = 1000;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CommitmentRecord {
+    pub commitment: String,
+    pub tree_generation: u32,
+    pub leaf_index: u64,
+    pub merkle_root: String,
+    pub signature: String,
+    pub slot: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct NullifierRecord {
+    pub nullifier: String,
+    pub signature: String,
+    pub slot: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct MintStats {
+    pub mint: String,
+    pub deposits: u64,
+    pub deposited: u64,
+    pub withdrawals_requested: u64,
+    pub withdrawals: u64,
+    pub withdrawn: u64,
+    pub fees: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Stats {
+    /// Newest transaction applied, where the next backfill stops
+    pub cursor: Option<String>,
+    pub slot: Option<u64>,
+    pub tree_generation: Option<u32>,
+    pub commitments: u64,
+    pub nullifiers: u64,
+    pub privacy_payments: u64,
+    /// Protocol fee rates as of the last `FeeUpdated`, unknown before one is seen
+    pub deposit_fee_rate: Option<u16>,
+    pub withdrawal_fee_rate: Option<u16>,
+    pub mints: Vec<MintStats>,
+}
+
+pub struct Mirror {
+    pool: AnyPool,
+}
+
+impl Mirror {
+    /// Open `database_url` (`sqlite:` or `postgres:`) and create any missing tables
+    pub async fn connect(database_url: &str) -> Result<Self, IndexerError> {
+        sqlx::any::install_default_drivers();
+        let pool = AnyPoolOptions::new()
+            .max_connections(8)
+            .connect(database_url)
+            .await?;
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&pool).await?;
+        }
+        Ok(Self { pool })
+    }
+    
+    /// Apply one successful transaction's events; false if it was already applied
+    pub async fn apply(
+        &self,
+        signature: &str,
+        slot: u64,
+        events: &[FPPEvent],
+    ) -> Result<bool, IndexerError> {
+        let mut tx = self.pool.begin().await?;
+        let seen = sqlx::query("SELECT 1 FROM transactions WHERE signature = $1")
+            .bind(signature)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if seen.is_some() {
+            return Ok(false);
+        }
+        sqlx::query("INSERT INTO transactions (signature, slot) VALUES ($1, $2)")
+            .bind(signature)
+            .bind(slot as i64)
+            .execute(&mut *tx)
+            .await?;
+        
+        for event in events {
+            match event {
+                FPPEvent::Deposit {
+                    mint,
+                    amount,
+                    fee,
+                    commitments,
+                    first_leaf_index,
+                    tree_generation,
+                    merkle_root,
+                    ..
+                } => {
+                    insert_commitments(
+                        &mut tx,
+                        commitments,
+                        *first_leaf_index,
+                        *tree_generation,
+                        merkle_root,
+                        signature,
+                        slot,
+                    )
+                    .await?;
+                    let delta = MintStats {
+                        deposits: 1,
+                        deposited: *amount,
+                        fees: *fee,
+                        ..MintStats::default()
+                    };
+                    add_mint_stats(&mut tx, mint, &delta).await?;
+                }
+                FPPEvent::CommitmentsInserted {
+                    commitments,
+                    first_leaf_index,
+                    tree_generation,
+                    merkle_root,
+                } => {
+                    insert_commitments(
+                        &mut tx,
+                        commitments,
+                        *first_leaf_index,
+                        *tree_generation,
+                        merkle_root,
+                        signature,
+                        slot,
+                    )
+                    .await?;
+                }
+                FPPEvent::NullifiersSpent { nullifiers } => {
+                    for nullifier in nullifiers {
+                        sqlx::query(
+                            "INSERT INTO nullifiers (nullifier, signature, slot) VALUES ($1, $2, $3)
+                            ON CONFLICT (nullifier) DO NOTHING",
+                        )
+                        .bind(hex::encode(nullifier))
+                        .bind(signature)
+                        .bind(slot as i64)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+                FPPEvent::PrivacyPayment { .. } => {
+                    add_counter(&mut tx, PRIVACY_PAYMENTS, 1).await?;
+                }
+                FPPEvent::WithdrawalRequested { mint, .. } => {
+                    let delta = MintStats {
+                        withdrawals_requested: 1,
+                        ..MintStats::default()
+                    };
+                    add_mint_stats(&mut tx, mint, &delta).await?;
+                }
+                FPPEvent::WithdrawalCompleted {
+                    mint,
+                    amount,
+                    fee,
+                    penalty,
+                    ..
+                } => {
+                    let delta = MintStats {
+                        withdrawals: 1,
+                        withdrawn: *amount,
+                        fees: fee.saturating_add(*penalty),
+                        ..MintStats::default()
+                    };
+                    add_mint_stats(&mut tx, mint, &delta).await?;
+                }
+                FPPEvent::FeeUpdated {
+                    deposit_fee_rate,
+                    withdrawal_fee_rate,
+                } => {
+                    set_counter(&mut tx, DEPOSIT_FEE_RATE, *deposit_fee_rate as i64).await?;
+                    set_counter(&mut tx, WITHDRAWAL_FEE_RATE, *withdrawal_fee_rate as i64).await?;
+                }
+                // Pools keep their own trees and nullifiers, and the rest change no totals
+                FPPEvent::FeeExemptionAdded { .. }
+                | FPPEvent::FeeExemptionRemoved { .. }
+                | FPPEvent::PoolDeposit { .. }
+                | FPPEvent::PoolWithdrawal { .. }
+                | FPPEvent::PoolTransfer { .. }
+                | FPPEvent::VerifyingKeyUpdated { .. }
+                | FPPEvent::ConcurrentTreeAppend { .. }
+                | FPPEvent::AdminActionLogged { .. }
+                | FPPEvent::UnpauseQueued { .. }
+                | FPPEvent::Unpaused { .. } => {}
+            }
+        }
+        
+        sqlx::query(
+            "INSERT INTO sync_cursor (id, signature) VALUES (0, $1)
+            ON CONFLICT (id) DO UPDATE SET signature = excluded.signature",
+        )
+        .bind(signature)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+    
+    /// Signature of the newest applied transaction
+    pub async fn cursor(&self) -> Result<Option<String>, IndexerError> {
+        let row = sqlx::query("SELECT signature FROM sync_cursor WHERE id = 0")
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|row| row.try_get("signature")).transpose()?)
+    }
+    
+    pub async fn commitment(
+        &self,
+        commitment: &[u8; 32],
+    ) -> Result<Option<CommitmentRecord>, IndexerError> {
+        let row = sqlx::query(
+            "SELECT commitment, tree_generation, leaf_index, merkle_root, signature, slot
+            FROM commitments WHERE commitment = $1",
+        )
+        .bind(hex::encode(commitment))
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(row.as_ref().map(commitment_record).transpose()?)
+    }
+    
+    /// Leaves of one tree generation in index order, starting at `from`
+    pub async fn commitments(
+        &self,
+        tree_generation: u32,
+        from: u64,
+        limit: u32,
+    ) -> Result<Vec<CommitmentRecord>, IndexerError> {
+        let rows = sqlx::query(
+            "SELECT commitment, tree_generation, leaf_index, merkle_root, signature, slot
+            FROM commitments WHERE tree_generation = $1 AND leaf_index >= $2
+            ORDER BY leaf_index LIMIT $3",
+        )
+        .bind(tree_generation as i64)
+        .bind(from as i64)
+        .bind(limit.min(MAX_PAGE) as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.iter().map(commitment_record).collect::<Result<_, _>>()?)
+    }
+    
+    pub async fn nullifier(
+        &self,
+        nullifier: &[u8; 32],
+    ) -> Result<Option<NullifierRecord>, IndexerError> {
+        let row = sqlx::query(
+            "SELECT nullifier, signature, slot FROM nullifiers WHERE nullifier = $1",
+        )
+        .bind(hex::encode(nullifier))
+        .fetch_optional(&self.pool)
+        .await?;
+        let record = |row: &AnyRow| -> Result<NullifierRecord, sqlx::Error> {
+            Ok(NullifierRecord {
+                nullifier: row.try_get("nullifier")?,
+                signature: row.try_get("signature")?,
+                slot: row.try_get::<i64, _>("slot")? as u64,
+            })
+        };
+        Ok(row.as_ref().map(record).transpose()?)
+    }
+    
+    pub async fn stats(&self) -> Result<Stats, IndexerError> {
+        let totals = sqlx::query(
+            "SELECT
+                (SELECT MAX(slot) FROM transactions) AS slot,
+                (SELECT MAX(tree_generation) FROM commitments) AS tree_generation,
+                (SELECT COUNT(*) FROM commitments) AS commitments,
+                (SELECT COUNT(*) FROM nullifiers) AS nullifiers",
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        
+        let mints = sqlx::query(
+            "SELECT mint, deposits, deposited, withdrawals_requested, withdrawals, withdrawn, fees
+            FROM mint_stats ORDER BY mint",
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .iter()
+        .map(|row| -> Result<MintStats, sqlx::Error> {
+            Ok(MintStats {
+                mint: row.try_get("mint")?,
+                deposits: row.try_get::<i64, _>("deposits")? as u64,
+                deposited: row.try_get::<i64, _>("deposited")? as u64,
+                withdrawals_requested: row.try_get::<i64, _>("withdrawals_requested")? as u64,
+                withdrawals: row.try_get::<i64, _>("withdrawals")? as u64,
+                withdrawn: row.try_get::<i64, _>("withdrawn")? as u64,
+                fees: row.try_get::<i64, _>("fees")? as u64,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+        
+        Ok(Stats {
+            cursor: self.cursor().await?,
+            slot: totals.try_get::<Option<i64>, _>("slot")?.map(|slot| slot as u64),
+            tree_generation: totals
+                .try_get::<Option<i64>, _>("tree_generation")?
+                .map(|generation| generation as u32),
+            commitments: totals.try_get::<i64, _>("commitments")? as u64,
+            nullifiers: totals.try_get::<i64, _>("nullifiers")? as u64,
+            privacy_payments: self.counter(PRIVACY_PAYMENTS).await?.unwrap_or(0) as u64,
+            deposit_fee_rate: self.counter(DEPOSIT_FEE_RATE).await?.map(|rate| rate as u16),
+            withdrawal_fee_rate: self.counter(WITHDRAWAL_FEE_RATE).await?.map(|rate| rate as u16),
+            mints,
+        })
+    }
+    
+    async fn counter(&self, name: &str) -> Result<Option<i64>, IndexerError> {
+        let row = sqlx::query("SELECT value FROM counters WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|row| row.try_get("value")).transpose()?)
+    }
+}
+
+fn commitment_record(row: &AnyRow) -> Result<CommitmentRecord, sqlx::Error> {
+    Ok(CommitmentRecord {
+        commitment: row.try_get("commitment")?,
+        tree_generation: row.try_get::<i64, _>("tree_generation")? as u32,
+        leaf_index: row.try_get::<i64, _>("leaf_index")? as u64,
+        merkle_root: row.try_get("merkle_root")?,
+        signature: row.try_get("signature")?,
+        slot: row.try_get::<i64, _>("slot")? as u64,
+    })
+}
+
+async fn insert_commitments(
+    conn: &mut AnyConnection,
+    commitments: &[[u8; 32]],
+    first_leaf_index: u64,
+    tree_generation: u32,
+    merkle_root: &[u8; 32],
+    signature: &str,
+    slot: u64,
+) -> Result<(), sqlx::Error> {
+    for (leaf_index, commitment) in (first_leaf_index..).zip(commitments) {
+        sqlx::query(
+            "INSERT INTO commitments
+                (commitment, tree_generation, leaf_index, merkle_root, signature, slot)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (commitment) DO NOTHING",
+        )
+        .bind(hex::encode(commitment))
+        .bind(tree_generation as i64)
+        .bind(leaf_index as i64)
+        .bind(hex::encode(merkle_root))
+        .bind(signature)
+        .bind(slot as i64)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn add_mint_stats(
+    conn: &mut AnyConnection,
+    mint: &Pubkey,
+    delta: &MintStats,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO mint_stats
+            (mint, deposits, deposited, withdrawals_requested, withdrawals, withdrawn, fees)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (mint) DO UPDATE SET
+            deposits = mint_stats.deposits + excluded.deposits,
+            deposited = mint_stats.deposited + excluded.deposited,
+            withdrawals_requested = mint_stats.withdrawals_requested
+                + excluded.withdrawals_requested,
+            withdrawals = mint_stats.withdrawals + excluded.withdrawals,
+            withdrawn = mint_stats.withdrawn + excluded.withdrawn,
+            fees = mint_stats.fees + excluded.fees",
+    )
+    .bind(mint.to_string())
+    .bind(delta.deposits as i64)
+    .bind(delta.deposited as i64)
+    .bind(delta.withdrawals_requested as i64)
+    .bind(delta.withdrawals as i64)
+    .bind(delta.withdrawn as i64)
+    .bind(delta.fees as i64)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+async fn add_counter(conn: &mut AnyConnection, name: &str, amount: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO counters (name, value) VALUES ($1, $2)
+        ON CONFLICT (name) DO UPDATE SET value = counters.value + excluded.value",
+    )
+    .bind(name)
+    .bind(amount)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+async fn set_counter(conn: &mut AnyConnection, name: &str, value: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO counters (name, value) VALUES ($1, $2)
+        ON CONFLICT (name) DO UPDATE SET value = excluded.value",
+    )
+    .bind(name)
+    .bind(value)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use fpp_indexer::accounting::{
+    export_csv, export_ofx, Column, Direction, DisclosedTransaction, FixedPriceFeed,
+    HistoricalPriceFeed,
+};
+
+/// 2023-11-14T22:13:20Z
+const TIMESTAMP: i64 = 1_700_000_000;
+
+fn transaction(direction: Direction, amount: u64, fee: u64) -> DisclosedTransaction {
+    DisclosedTransaction {
+        signature: "sig".to_string(),
+        timestamp: TIMESTAMP,
+        direction,
+        amount,
+        fee,
+        counterparty: None,
+        memo: None,
+    }
+}
+
+#[test]
+fn csv_rows_are_signed_and_valued_from_the_business_view() {
+    let transactions = [
+        transaction(Direction::Withdrawal, 3_000_000, 1_000_000),
+        transaction(Direction::Incoming, 250_000, 0),
+    ];
+    let columns = [
+        Column::Date,
+        Column::Type,
+        Column::Amount,
+        Column::Fee,
+        Column::NetAmount,
+        Column::UsdValue,
+        Column::UsdFee,
+    ];
+    let csv = export_csv(&transactions, &FixedPriceFeed(1.5), &columns);
+    let lines: Vec<&str> = csv.split("\r\n").collect();
+    assert_eq!(
+        lines,
+        [
+            "date,type,amount,fee,net_amount,usd_value,usd_fee",
+            "2023-11-14T22:13:20Z,withdrawal,-3.000000,1.000000,-2.000000,-3.00,1.50",
+            "2023-11-14T22:13:20Z,incoming,0.250000,0.000000,0.250000,0.38,0.00",
+            "",
+        ]
+    );
+    
+    let header = export_csv(&[], &FixedPriceFeed(1.0), Column::DEFAULT);
+    assert_eq!(
+        header,
+        concat!(
+            "date,signature,type,amount,fee,net_amount,",
+            "usd_price,usd_value,usd_fee,counterparty,memo\r\n",
+        )
+    );
+}
+
+#[test]
+fn csv_cells_cant_inject_formulas_or_break_rows() {
+    let transactions = [DisclosedTransaction {
+        counterparty: Some("=HYPERLINK(\"x\")".to_string()),
+        memo: Some("rent, march\nsecond line".to_string()),
+        ..transaction(Direction::Outgoing, 1_000_000, 0)
+    }];
+    let columns = [Column::Counterparty, Column::Memo];
+    let csv = export_csv(&transactions, &FixedPriceFeed(1.0), &columns);
+    assert_eq!(
+        csv,
+        "counterparty,memo\r\n\"'=HYPERLINK(\"\"x\"\")\",\"rent, march\nsecond line\"\r\n"
+    );
+}
+
+#[test]
+fn historical_prices_use_the_latest_sample_at_or_before_each_transaction() {
+    let mut feed = HistoricalPriceFeed::new();
+    feed.insert(TIMESTAMP - 60, 2.0);
+    feed.insert(TIMESTAMP + 60, 3.0);
+    let early = DisclosedTransaction {
+        timestamp: TIMESTAMP - 120,
+        ..transaction(Direction::Deposit, 1_000_000, 0)
+    };
+    let transactions = [early, transaction(Direction::Deposit, 1_000_000, 0)];
+    let csv = export_csv(&transactions, &feed, &[Column::UsdPrice, Column::UsdValue]);
+    // No sample precedes the first deposit, so it is left unvalued rather than guessed
+    assert_eq!(csv, "usd_price,usd_value\r\n,\r\n2.000000,2.00\r\n");
+}
+
+#[test]
+fn ofx_statements_split_out_fees_and_escape_text() {
+    let transactions = [DisclosedTransaction {
+        counterparty: Some("Acme <Supplies> & Co".to_string()),
+        memo: Some("invoice 7".to_string()),
+        ..transaction(Direction::Outgoing, 3_000_000, 1_000_000)
+    }];
+    let ofx = export_ofx(&transactions, &FixedPriceFeed(1.0), "treasury", "USD");
+    assert!(ofx.contains("<CURDEF>USD</CURDEF>"));
+    assert!(ofx.contains("<ACCTID>treasury</ACCTID>"));
+    assert!(ofx.contains("<DTSTART>20231114221320[0:GMT]</DTSTART>"));
+    assert!(ofx.contains(
+        "<TRNTYPE>DEBIT</TRNTYPE>\n<DTPOSTED>20231114221320[0:GMT]</DTPOSTED>\n\
+         <TRNAMT>-2.000000</TRNAMT>\n<FITID>sig</FITID>\n\
+         <NAME>Acme &lt;Supplies&gt; &amp; Co</NAME>\n<MEMO>invoice 7 (USD 1.000000)</MEMO>"
+    ));
+    assert!(ofx.contains("<TRNTYPE>FEE</TRNTYPE>"));
+    assert!(ofx.contains("<TRNAMT>-1.000000</TRNAMT>\n<FITID>sig-fee</FITID>"));
+    
+    // A fee-free credit adds no fee entry
+    let ofx = export_ofx(
+        &[transaction(Direction::Incoming, 1_000_000, 0)],
+        &FixedPriceFeed(1.0),
+        "treasury",
+        "USD",
+    );
+    assert_eq!(ofx.matches("<STMTTRN>").count(), 1);
+    assert!(ofx.contains("<TRNTYPE>CREDIT</TRNTYPE>"));
+}
@@ -0,0 +1,69 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use borsh::BorshDeserialize;
+use floating_point_protocol_solana::bloom::EpochBloom;
+use fpp_indexer::bloom::{self, BloomPublisher, ManifestEntry, MANIFEST_FILE};
+
+/// Fresh output directory for one test
+fn out_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fpp-bloom-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn published(dir: &Path, epoch: u64) -> EpochBloom {
+    let bytes = fs::read(dir.join(bloom::file_name(epoch))).unwrap();
+    EpochBloom::try_from_slice(&bytes).unwrap()
+}
+
+#[test]
+fn published_filters_match_their_manifest() {
+    let dir = out_dir("manifest");
+    let mut publisher = BloomPublisher::new(&dir, 100, 0.01);
+    publisher.record(3, 120, &[1u8; 32]);
+    publisher.record(3, 100, &[2u8; 32]);
+    publisher.record(4, 150, &[3u8; 32]);
+    // An epoch without spends is still published, so wallets know it was indexed
+    publisher.advance(5, 200);
+    publisher.publish_all().unwrap();
+    
+    let manifest: Vec<ManifestEntry> =
+        serde_json::from_slice(&fs::read(dir.join(MANIFEST_FILE)).unwrap()).unwrap();
+    let summary: Vec<(u64, u64, u32)> =
+        manifest.iter().map(|entry| (entry.epoch, entry.last_slot, entry.count)).collect();
+    assert_eq!(summary, [(3, 120, 2), (4, 150, 1), (5, 200, 0)]);
+    
+    let epoch = published(&dir, 3);
+    assert_eq!(epoch, *publisher.get(3).unwrap());
+    assert_eq!((epoch.first_slot, epoch.last_slot), (100, 120));
+    assert!(epoch.filter.contains(&[1u8; 32]));
+    assert!(epoch.filter.contains(&[2u8; 32]));
+    assert!(!published(&dir, 5).filter.contains(&[1u8; 32]));
+    
+    // Nothing half-written is left behind for a static host to serve
+    let leftovers = fs::read_dir(&dir)
+        .unwrap()
+        .filter(|entry| entry.as_ref().unwrap().path().extension().unwrap() == "tmp")
+        .count();
+    assert_eq!(leftovers, 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn the_current_epoch_is_republished_as_it_grows() {
+    let dir = out_dir("republish");
+    let mut publisher = BloomPublisher::new(&dir, 100, 0.01);
+    publisher.record(7, 10, &[1u8; 32]);
+    publisher.publish_all().unwrap();
+    assert!(!published(&dir, 7).filter.contains(&[9u8; 32]));
+    
+    publisher.record(7, 20, &[9u8; 32]);
+    publisher.publish_all().unwrap();
+    let epoch = published(&dir, 7);
+    assert_eq!((epoch.last_slot, epoch.count), (20, 2));
+    assert!(epoch.filter.contains(&[9u8; 32]));
+    fs::remove_dir_all(&dir).unwrap();
+}
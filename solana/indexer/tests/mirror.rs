@@ -0,0 +1,143 @@
+use base64::Engine;
+use borsh::BorshSerialize;
+use floating_point_protocol_solana::events::{FPPEvent, EVENT_TAG};
+use fpp_indexer::{
+    events,
+    mirror::{MintStats, Mirror},
+};
+use solana_sdk::pubkey::Pubkey;
+
+/// The log line the program writes for `event`
+fn log_line(event: &FPPEvent) -> String {
+    let encode = |bytes: &[u8]| base64::engine::general_purpose::STANDARD.encode(bytes);
+    format!("Program data: {} {}", encode(EVENT_TAG), encode(&event.try_to_vec().unwrap()))
+}
+
+/// A mirror on a fresh SQLite file; in-memory databases aren't shared across a pool
+async fn mirror(name: &str) -> Mirror {
+    let path = std::env::temp_dir().join(format!("fpp-mirror-{}-{}.db", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    Mirror::connect(&format!("sqlite://{}?mode=rwc", path.display())).await.unwrap()
+}
+
+#[test]
+fn only_the_programs_data_lines_are_decoded() {
+    let event = FPPEvent::NullifiersSpent {
+        nullifiers: vec![[1u8; 32]],
+    };
+    let other_program = format!(
+        "Program data: {}",
+        base64::engine::general_purpose::STANDARD.encode(b"other")
+    );
+    let logs = vec![
+        "Program log: Instruction: PrivacyPayment".to_string(),
+        other_program,
+        log_line(&event),
+        "Program data: not base64!".to_string(),
+    ];
+    assert_eq!(events::parse_logs(&logs), vec![event]);
+}
+
+#[tokio::test]
+async fn events_build_the_mirror_once_per_transaction() {
+    let mirror = mirror("apply").await;
+    let mint = Pubkey::new_unique();
+    let deposit = vec![
+        FPPEvent::Deposit {
+            depositor: Pubkey::new_unique(),
+            mint,
+            amount: 1_000_000,
+            fee: 1_000,
+            denomination: 500_000,
+            commitments: vec![[1u8; 32], [2u8; 32]],
+            first_leaf_index: 0,
+            tree_generation: 0,
+            merkle_root: [9u8; 32],
+        },
+        FPPEvent::FeeUpdated {
+            deposit_fee_rate: 10,
+            withdrawal_fee_rate: 20,
+        },
+    ];
+    assert!(mirror.apply("first", 10, &deposit).await.unwrap());
+    // Backfill and the live feed can both deliver a transaction
+    assert!(!mirror.apply("first", 10, &deposit).await.unwrap());
+    
+    let request = Pubkey::new_unique();
+    let logs: Vec<String> = [
+        FPPEvent::CommitmentsInserted {
+            commitments: vec![[3u8; 32]],
+            first_leaf_index: 2,
+            tree_generation: 0,
+            merkle_root: [8u8; 32],
+        },
+        FPPEvent::NullifiersSpent {
+            nullifiers: vec![[4u8; 32]],
+        },
+        FPPEvent::PrivacyPayment {
+            merkle_root: [8u8; 32],
+            input_nullifiers: vec![[4u8; 32]],
+            output_commitments: vec![[3u8; 32]],
+        },
+        FPPEvent::WithdrawalRequested {
+            requester: Pubkey::new_unique(),
+            request,
+            nonce: 0,
+            mint,
+            amount: 500_000,
+            point_count: 1,
+            unlock_time: 0,
+        },
+        FPPEvent::WithdrawalCompleted {
+            requester: Pubkey::new_unique(),
+            request,
+            mint,
+            amount: 500_000,
+            fee: 500,
+            penalty: 0,
+            token_fee: 0,
+            emergency: false,
+        },
+        // Pool leaves live in the pool's own tree, not the mirrored one
+        FPPEvent::PoolDeposit {
+            pool: Pubkey::new_unique(),
+            commitment: [5u8; 32],
+            leaf_index: 0,
+            merkle_root: [7u8; 32],
+        },
+    ]
+    .iter()
+    .map(log_line)
+    .collect();
+    assert!(mirror.apply("second", 11, &events::parse_logs(&logs)).await.unwrap());
+    
+    let leaf = mirror.commitment(&[2u8; 32]).await.unwrap().unwrap();
+    assert_eq!((leaf.leaf_index, leaf.slot), (1, 10));
+    assert_eq!((leaf.signature.as_str(), leaf.merkle_root), ("first", hex::encode([9u8; 32])));
+    assert!(mirror.commitment(&[5u8; 32]).await.unwrap().is_none());
+    let page = mirror.commitments(0, 1, 10).await.unwrap();
+    let indexes: Vec<u64> = page.iter().map(|record| record.leaf_index).collect();
+    assert_eq!(indexes, [1, 2]);
+    
+    let spent = mirror.nullifier(&[4u8; 32]).await.unwrap().unwrap();
+    assert_eq!((spent.signature.as_str(), spent.slot), ("second", 11));
+    assert!(mirror.nullifier(&[1u8; 32]).await.unwrap().is_none());
+    
+    let stats = mirror.stats().await.unwrap();
+    assert_eq!(stats.cursor.as_deref(), Some("second"));
+    assert_eq!((stats.slot, stats.tree_generation), (Some(11), Some(0)));
+    assert_eq!((stats.commitments, stats.nullifiers, stats.privacy_payments), (3, 1, 1));
+    assert_eq!((stats.deposit_fee_rate, stats.withdrawal_fee_rate), (Some(10), Some(20)));
+    assert_eq!(
+        stats.mints,
+        [MintStats {
+            mint: mint.to_string(),
+            deposits: 1,
+            deposited: 1_000_000,
+            withdrawals_requested: 1,
+            withdrawals: 1,
+            withdrawn: 500_000,
+            fees: 1_500,
+        }]
+    );
+}
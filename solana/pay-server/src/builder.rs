@@ -0,0 +1,158 @@
+use borsh::BorshDeserialize;
+use floating_point_protocol_solana::{instruction, pda, state::ProtocolState};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, transaction::Transaction};
+use solana_zk_token_sdk::zk_token_proof_program;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::{config::Config, error::ApiError, store::PaymentBundle};
+
+/// Build an unsigned deposit transaction with `account` as fee payer and depositor
+pub async fn deposit_transaction(
+    config: &Config,
+    rpc: &RpcClient,
+    account: &Pubkey,
+    amount: u64,
+    commitments: Vec<[u8; 32]>,
+) -> Result<Transaction, ApiError> {
+    if commitments.is_empty() {
+        return Err(ApiError::BadRequest("at least one commitment is required".into()));
+    }
+    
+    let (protocol_state, _) = pda::find_protocol_state(&config.program_id);
+    let data = rpc
+        .get_account_data(&protocol_state)
+        .await
+        .map_err(|_| ApiError::ProtocolNotInitialized)?;
+    let state = ProtocolState::deserialize(&mut &data[..])
+        .map_err(|_| ApiError::ProtocolNotInitialized)?;
+    
+    // Each commitment becomes one point, so the count fixes the denomination
+    let denomination = amount / commitments.len() as u64;
+    if !state.is_denomination(denomination) || denomination * commitments.len() as u64 != amount {
+        return Err(ApiError::BadRequest(format!(
+            "amount must be a configured denomination times the commitment count, got {}",
+            amount
+        )));
+    }
+    
+    let user_token = get_associated_token_address_with_program_id(
+        account,
+        &state.usdt_mint,
+        &state.token_program,
+    );
+    let treasury_token = get_associated_token_address_with_program_id(
+        &state.treasury,
+        &state.usdt_mint,
+        &state.token_program,
+    );
+    
+    // The deposit is tallied in the current epoch's stats account, so an unsigned
+    // transaction held across an epoch boundary has to be rebuilt
+    let epoch = rpc
+        .get_epoch_info()
+        .await
+        .map_err(|e| ApiError::Rpc(e.to_string()))?
+        .epoch;
+    let ix = instruction::deposit(
+        &config.program_id,
+        account,
+        &user_token,
+        &treasury_token,
+        &protocol_state,
+        &state.usdt_mint,
+        &state.token_program,
+        amount,
+        denomination,
+        commitments,
+        vec![],
+        epoch,
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    
+    finalize(rpc, account, &[ix]).await
+}
+
+/// Build an unsigned privacy payment transaction from a registered bundle
+pub async fn privacy_payment_transaction(
+    config: &Config,
+    rpc: &RpcClient,
+    account: &Pubkey,
+    bundle: PaymentBundle,
+) -> Result<Transaction, ApiError> {
+    let (protocol_state, _) = pda::find_protocol_state(&config.program_id);
+    
+    let ix = instruction::privacy_payment(
+        &config.program_id,
+        account,
+        &protocol_state,
+        &parse_pubkey(&bundle.recipient)?,
+        &parse_pubkeys(&bundle.ring_members)?,
+        &parse_pubkeys(&bundle.output_points)?,
+        &config.verifier_program,
+        parse_hash(&bundle.merkle_root)?,
+        parse_hashes(&bundle.input_nullifiers)?,
+        parse_hashes(&bundle.output_commitments)?,
+        decode_base64(&bundle.proof)?,
+        decode_base64(&bundle.ring_signature)?,
+        -1,
+        bundle
+            .encrypted_notes
+            .iter()
+            .map(|note| decode_base64(note))
+            .collect::<Result<Vec<_>, _>>()?,
+        bundle.archived_tree,
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    
+    // The range proof is verified natively in the instruction right before the payment
+    let range_proof_ix = Instruction {
+        program_id: zk_token_proof_program::id(),
+        accounts: vec![],
+        data: decode_base64(&bundle.range_proof)?,
+    };
+    
+    finalize(rpc, account, &[range_proof_ix, ix]).await
+}
+
+async fn finalize(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    instructions: &[Instruction],
+) -> Result<Transaction, ApiError> {
+    let blockhash = rpc
+        .get_latest_blockhash()
+        .await
+        .map_err(|e| ApiError::Rpc(e.to_string()))?;
+    let mut tx = Transaction::new_with_payer(instructions, Some(payer));
+    tx.message.recent_blockhash = blockhash;
+    Ok(tx)
+}
+
+pub fn parse_pubkey(value: &str) -> Result<Pubkey, ApiError> {
+    value
+        .parse()
+        .map_err(|_| ApiError::BadRequest(format!("invalid pubkey: {}", value)))
+}
+
+pub fn parse_pubkeys(values: &[String]) -> Result<Vec<Pubkey>, ApiError> {
+    values.iter().map(|v| parse_pubkey(v)).collect()
+}
+
+pub fn parse_hash(value: &str) -> Result<[u8; 32], ApiError> {
+    let mut out = [0u8; 32];
+    hex::decode_to_slice(value, &mut out)
+        .map_err(|_| ApiError::BadRequest(format!("invalid 32-byte hex value: {}", value)))?;
+    Ok(out)
+}
+
+pub fn parse_hashes(values: &[String]) -> Result<Vec<[u8; 32]>, ApiError> {
+    values.iter().map(|v| parse_hash(v)).collect()
+}
+
+pub fn decode_base64(value: &str) -> Result<Vec<u8>, ApiError> {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD
+        .decode(value)
+        .map_err(|_| ApiError::BadRequest("invalid base64 payload".into()))
+}
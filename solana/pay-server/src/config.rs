@@ -0,0 +1,38 @@
+use std::{env, net::SocketAddr, str::FromStr};
+
+use solana_sdk::pubkey::Pubkey;
+
+/// Server configuration, read from `FPP_*` environment variables
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub bind: SocketAddr,
+    pub rpc_url: String,
+    pub program_id: Pubkey,
+    pub verifier_program: Pubkey,
+    pub label: String,
+    pub icon: String,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, String> {
+        Ok(Self {
+            bind: parse_var("FPP_BIND", "0.0.0.0:8080")?,
+            rpc_url: env::var("FPP_RPC_URL")
+                .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string()),
+            program_id: parse_var("FPP_PROGRAM_ID", "11111111111111111111111111111111")?,
+            verifier_program: parse_var(
+                "FPP_VERIFIER_PROGRAM_ID",
+                "11111111111111111111111111111111",
+            )?,
+            label: env::var("FPP_LABEL").unwrap_or_else(|_| "Floating Point Protocol".to_string()),
+            icon: env::var("FPP_ICON")
+                .unwrap_or_else(|_| "https://fpp.network/icon.svg".to_string()),
+        })
+    }
+}
+
+fn parse_var<T: FromStr>(name: &str, default: &str) -> Result<T, String> {
+    let raw = env::var(name).unwrap_or_else(|_| default.to_string());
+    raw.parse()
+        .map_err(|_| format!("invalid value for {}: {}", name, raw))
+}
@@ -0,0 +1,169 @@
+//! Solana Pay transaction-request server for FPP.
+//!
+//! Wallets scan `solana:https://<host>/tx?...` links, `GET` the label and icon,
+//! then `POST` their account to receive an unsigned deposit or privacy-payment
+//! transaction built for them on demand.
+
+mod builder;
+mod config;
+mod error;
+mod store;
+mod validate;
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    routing::{get, post},
+    Json, Router,
+};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use tower_http::cors::CorsLayer;
+
+use crate::{
+    config::Config,
+    error::ApiError,
+    store::{BundleStore, PaymentBundle},
+    validate::ValidationReport,
+};
+
+struct AppState {
+    config: Config,
+    rpc: RpcClient,
+    bundles: BundleStore,
+}
+
+#[derive(Serialize)]
+struct MetadataResponse {
+    label: String,
+    icon: String,
+}
+
+/// Query parameters encoded in the Solana Pay link
+#[derive(Deserialize)]
+struct TxParams {
+    /// Deposit amount in token base units
+    amount: Option<u64>,
+    /// Comma-separated hex commitments for a deposit
+    commitments: Option<String>,
+    /// Registered payment bundle id for a privacy payment
+    payment: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TxRequest {
+    account: String,
+}
+
+#[derive(Serialize)]
+struct TxResponse {
+    transaction: String,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct BundleResponse {
+    id: String,
+}
+
+async fn metadata(State(state): State<Arc<AppState>>) -> Json<MetadataResponse> {
+    Json(MetadataResponse {
+        label: state.config.label.clone(),
+        icon: state.config.icon.clone(),
+    })
+}
+
+async fn transaction(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<TxParams>,
+    Json(request): Json<TxRequest>,
+) -> Result<Json<TxResponse>, ApiError> {
+    let account = builder::parse_pubkey(&request.account)?;
+    
+    let (tx, message) = match (params.payment, params.amount) {
+        (Some(id), _) => {
+            let bundle = state.bundles.take(&id).ok_or(ApiError::BundleNotFound)?;
+            let tx = builder::privacy_payment_transaction(&state.config, &state.rpc, &account, bundle)
+                .await?;
+            (tx, "Private payment via Floating Point Protocol".to_string())
+        }
+        (None, Some(amount)) => {
+            let commitments = params
+                .commitments
+                .as_deref()
+                .unwrap_or_default()
+                .split(',')
+                .filter(|c| !c.is_empty())
+                .map(builder::parse_hash)
+                .collect::<Result<Vec<_>, _>>()?;
+            let tx = builder::deposit_transaction(
+                &state.config,
+                &state.rpc,
+                &account,
+                amount,
+                commitments,
+            )
+            .await?;
+            (tx, format!("Deposit {} into Floating Point Protocol", amount))
+        }
+        (None, None) => {
+            return Err(ApiError::BadRequest(
+                "expected either `payment` or `amount` parameter".into(),
+            ))
+        }
+    };
+    
+    let serialized = bincode::serialize(&tx).map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(Json(TxResponse {
+        transaction: base64::engine::general_purpose::STANDARD.encode(serialized),
+        message,
+    }))
+}
+
+async fn register_bundle(
+    State(state): State<Arc<AppState>>,
+    Json(bundle): Json<PaymentBundle>,
+) -> Result<Json<BundleResponse>, ApiError> {
+    let id = state
+        .bundles
+        .insert(bundle)
+        .ok_or_else(|| ApiError::Internal("bundle store is full".into()))?;
+    Ok(Json(BundleResponse { id }))
+}
+
+async fn validate_bundle(
+    State(state): State<Arc<AppState>>,
+    Json(bundle): Json<PaymentBundle>,
+) -> Result<Json<ValidationReport>, ApiError> {
+    let report = validate::validate_bundle(&state.config, &state.rpc, &bundle).await?;
+    Ok(Json(report))
+}
+
+#[tokio::main]
+async fn main() {
+    let config = Config::from_env().unwrap_or_else(|e| {
+        eprintln!("configuration error: {}", e);
+        std::process::exit(1);
+    });
+    let bind = config.bind;
+    let state = Arc::new(AppState {
+        rpc: RpcClient::new(config.rpc_url.clone()),
+        config,
+        bundles: BundleStore::default(),
+    });
+    
+    let app = Router::new()
+        .route("/tx", get(metadata).post(transaction))
+        .route("/bundles", post(register_bundle))
+        .route("/bundles/validate", post(validate_bundle))
+        .layer(CorsLayer::permissive())
+        .with_state(state);
+    
+    println!("FPP Solana Pay server listening on {}", bind);
+    axum::Server::bind(&bind)
+        .serve(app.into_make_service())
+        .await
+        .expect("server error");
+}
@@ -0,0 +1,62 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use solana_sdk::hash::hash;
+
+/// Upper bound on pending bundles so the store can't be used to exhaust memory
+const MAX_PENDING_BUNDLES: usize = 10_000;
+
+/// Privacy payment prepared by the payer's prover, referenced from a Solana Pay link
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PaymentBundle {
+    /// Recipient account (base58)
+    pub recipient: String,
+    /// Ring member point accounts, row-major (base58)
+    pub ring_members: Vec<String>,
+    /// Output point accounts (base58)
+    pub output_points: Vec<String>,
+    /// Commitment tree root the proof was generated against (hex)
+    pub merkle_root: String,
+    /// Input nullifiers (hex)
+    pub input_nullifiers: Vec<String>,
+    /// Output commitments (hex)
+    pub output_commitments: Vec<String>,
+    /// ZK proof bytes (base64)
+    pub proof: String,
+    /// Borsh-encoded ring signature (base64)
+    pub ring_signature: String,
+    /// ZK Token Proof batched range proof instruction data (base64), placed before the payment
+    pub range_proof: String,
+    /// Output notes encrypted to the recipients' viewing keys (base64), one per output
+    #[serde(default)]
+    pub encrypted_notes: Vec<String>,
+    /// Generation of the archived tree `merkle_root` belongs to, if not a recent root
+    #[serde(default)]
+    pub archived_tree: Option<u32>,
+}
+
+/// In-memory store of payment bundles awaiting a wallet scan
+#[derive(Default)]
+pub struct BundleStore {
+    bundles: Mutex<HashMap<String, PaymentBundle>>,
+}
+
+impl BundleStore {
+    /// Store a bundle and return its content-derived id
+    pub fn insert(&self, bundle: PaymentBundle) -> Option<String> {
+        let encoded = serde_json::to_vec(&bundle).ok()?;
+        let id = hex::encode(&hash(&encoded).to_bytes()[..16]);
+        
+        let mut bundles = self.bundles.lock().unwrap();
+        if bundles.len() >= MAX_PENDING_BUNDLES && !bundles.contains_key(&id) {
+            return None;
+        }
+        bundles.insert(id.clone(), bundle);
+        Some(id)
+    }
+    
+    /// Remove and return a bundle; each bundle backs a single transaction
+    pub fn take(&self, id: &str) -> Option<PaymentBundle> {
+        self.bundles.lock().unwrap().remove(id)
+    }
+}
@@ -0,0 +1,428 @@
+//! Pre-validation of payment bundles against current chain state.
+//!
+//! Runs the checks `PrivacyPayment` performs on chain (encoding, encrypted
+//! notes, ring signature, range proof context, root recency and nullifier
+//! freshness) before a bundle is turned into a transaction, and reports every
+//! failure with the field it concerns. The ZK proof is only checked for
+//! presence; the verifier program remains the authority on its validity.
+
+use borsh::BorshDeserialize;
+use floating_point_protocol_solana::{
+    crypto::{
+        range_proof,
+        ring::{self, RingSignature},
+    },
+    state::{
+        CommitmentTree, EncryptedNote, FloatingPoint, NullifierFilter, NullifierSet, NullifierShard,
+        RootHistory,
+    },
+};
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::{
+    builder::{decode_base64, parse_hash, parse_hashes, parse_pubkey, parse_pubkeys},
+    config::Config,
+    error::ApiError,
+    store::PaymentBundle,
+};
+
+/// Accounts fetched per `getMultipleAccounts` call
+const RPC_BATCH_SIZE: usize = 100;
+
+/// Which on-chain check a diagnostic corresponds to
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    Encoding,
+    Shape,
+    EncryptedNote,
+    RingSignature,
+    RangeProof,
+    MerkleRoot,
+    Nullifier,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Diagnostic {
+    pub check: Check,
+    /// Bundle field at fault, with an index for list fields
+    pub field: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct ValidationReport {
+    pub valid: bool,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    fn fail(&mut self, check: Check, field: impl Into<String>, message: impl Into<String>) {
+        self.diagnostics.push(Diagnostic {
+            check,
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+    
+    fn finish(mut self) -> Self {
+        self.valid = self.diagnostics.is_empty();
+        self
+    }
+}
+
+/// Bundle fields decoded into their on-chain types
+struct DecodedBundle {
+    ring_members: Vec<Pubkey>,
+    output_points: Vec<Pubkey>,
+    merkle_root: [u8; 32],
+    input_nullifiers: Vec<[u8; 32]>,
+    output_commitments: Vec<[u8; 32]>,
+    proof: Vec<u8>,
+    ring_signature: Vec<u8>,
+    range_proof: Vec<u8>,
+    encrypted_notes: Vec<Vec<u8>>,
+}
+
+/// Check a bundle as `PrivacyPayment` would, without submitting anything
+pub async fn validate_bundle(
+    config: &Config,
+    rpc: &RpcClient,
+    bundle: &PaymentBundle,
+) -> Result<ValidationReport, ApiError> {
+    let mut report = ValidationReport::default();
+    let decoded = match decode(bundle, &mut report) {
+        Some(decoded) => decoded,
+        None => return Ok(report.finish()),
+    };
+    
+    check_shape(&decoded, &mut report);
+    check_range_proof(&decoded, &mut report);
+    check_ring_signature(config, rpc, &decoded, &mut report).await?;
+    check_merkle_root(config, rpc, &decoded, bundle.archived_tree, &mut report).await?;
+    check_nullifiers(config, rpc, &decoded, &mut report).await?;
+    
+    Ok(report.finish())
+}
+
+fn decode(bundle: &PaymentBundle, report: &mut ValidationReport) -> Option<DecodedBundle> {
+    fn field<T>(
+        report: &mut ValidationReport,
+        name: &str,
+        value: Result<T, ApiError>,
+    ) -> Option<T> {
+        value
+            .map_err(|e| report.fail(Check::Encoding, name, e.to_string()))
+            .ok()
+    }
+    
+    let recipient = field(report, "recipient", parse_pubkey(&bundle.recipient));
+    let ring_members = field(report, "ring_members", parse_pubkeys(&bundle.ring_members));
+    let output_points = field(report, "output_points", parse_pubkeys(&bundle.output_points));
+    let merkle_root = field(report, "merkle_root", parse_hash(&bundle.merkle_root));
+    let input_nullifiers =
+        field(report, "input_nullifiers", parse_hashes(&bundle.input_nullifiers));
+    let output_commitments =
+        field(report, "output_commitments", parse_hashes(&bundle.output_commitments));
+    let proof = field(report, "proof", decode_base64(&bundle.proof));
+    let ring_signature = field(report, "ring_signature", decode_base64(&bundle.ring_signature));
+    let range_proof = field(report, "range_proof", decode_base64(&bundle.range_proof));
+    let encrypted_notes = field(
+        report,
+        "encrypted_notes",
+        bundle.encrypted_notes.iter().map(|note| decode_base64(note)).collect(),
+    );
+    
+    recipient?;
+    Some(DecodedBundle {
+        ring_members: ring_members?,
+        output_points: output_points?,
+        merkle_root: merkle_root?,
+        input_nullifiers: input_nullifiers?,
+        output_commitments: output_commitments?,
+        proof: proof?,
+        ring_signature: ring_signature?,
+        range_proof: range_proof?,
+        encrypted_notes: encrypted_notes?,
+    })
+}
+
+fn check_shape(bundle: &DecodedBundle, report: &mut ValidationReport) {
+    if bundle.proof.is_empty() {
+        report.fail(Check::Shape, "proof", "proof is empty");
+    }
+    if bundle.output_points.len() != bundle.output_commitments.len() {
+        report.fail(
+            Check::Shape,
+            "output_points",
+            format!(
+                "{} output points for {} output commitments",
+                bundle.output_points.len(),
+                bundle.output_commitments.len()
+            ),
+        );
+    }
+    
+    // Every output carries a memo so recipients can find it with their viewing key
+    if bundle.encrypted_notes.len() != bundle.output_commitments.len() {
+        report.fail(
+            Check::EncryptedNote,
+            "encrypted_notes",
+            format!(
+                "{} encrypted notes for {} output commitments",
+                bundle.encrypted_notes.len(),
+                bundle.output_commitments.len()
+            ),
+        );
+    }
+    for (i, note) in bundle.encrypted_notes.iter().enumerate() {
+        if note.is_empty() || note.len() > EncryptedNote::MAX_CIPHERTEXT_LEN {
+            report.fail(
+                Check::EncryptedNote,
+                format!("encrypted_notes[{}]", i),
+                format!(
+                    "note is {} bytes, expected 1 to {}",
+                    note.len(),
+                    EncryptedNote::MAX_CIPHERTEXT_LEN
+                ),
+            );
+        }
+    }
+}
+
+fn check_range_proof(bundle: &DecodedBundle, report: &mut ValidationReport) {
+    if range_proof::check_range_proof_data(&bundle.range_proof, &bundle.output_commitments)
+        .is_err()
+    {
+        report.fail(
+            Check::RangeProof,
+            "range_proof",
+            format!(
+                "not a batched range proof covering the {} output commitments in order \
+                 (at most {})",
+                bundle.output_commitments.len(),
+                range_proof::MAX_RANGE_PROOF_OUTPUTS
+            ),
+        );
+    }
+}
+
+async fn check_ring_signature(
+    config: &Config,
+    rpc: &RpcClient,
+    bundle: &DecodedBundle,
+    report: &mut ValidationReport,
+) -> Result<(), ApiError> {
+    let signature = match RingSignature::try_from_slice(&bundle.ring_signature) {
+        Ok(signature) => signature,
+        Err(e) => {
+            report.fail(Check::RingSignature, "ring_signature", format!("undecodable: {}", e));
+            return Ok(());
+        }
+    };
+    
+    // Key images double as nullifiers, so they must match one-to-one
+    if bundle.input_nullifiers.is_empty() || signature.key_images != bundle.input_nullifiers {
+        report.fail(
+            Check::RingSignature,
+            "input_nullifiers",
+            "input nullifiers must be non-empty and equal the signature's key images",
+        );
+        return Ok(());
+    }
+    
+    let inputs = bundle.input_nullifiers.len();
+    let ring_size = signature.ring_size();
+    if ring_size == 0 || ring_size > ring::MAX_RING_SIZE {
+        report.fail(
+            Check::RingSignature,
+            "ring_signature",
+            format!("ring size {} is outside 1 to {}", ring_size, ring::MAX_RING_SIZE),
+        );
+        return Ok(());
+    }
+    if bundle.ring_members.len() != ring_size * inputs {
+        report.fail(
+            Check::RingSignature,
+            "ring_members",
+            format!(
+                "{} ring members for a {} x {} ring",
+                bundle.ring_members.len(),
+                ring_size,
+                inputs
+            ),
+        );
+        return Ok(());
+    }
+    
+    // Ring members are the commitments of the input point accounts (row-major)
+    let accounts = fetch_accounts(rpc, &bundle.ring_members).await?;
+    let mut ring_members = Vec::with_capacity(accounts.len());
+    for (i, account) in accounts.iter().enumerate() {
+        let point = account
+            .as_ref()
+            .filter(|account| account.owner == config.program_id)
+            .and_then(|account| FloatingPoint::try_from_slice(&account.data).ok())
+            .filter(|point| point.is_initialized);
+        match point {
+            Some(point) => ring_members.push(point.commitment),
+            None => report.fail(
+                Check::RingSignature,
+                format!("ring_members[{}]", i),
+                format!("{} is not an initialized point account", bundle.ring_members[i]),
+            ),
+        }
+    }
+    if ring_members.len() != accounts.len() {
+        return Ok(());
+    }
+    
+    let message = ring::signing_message(
+        &bundle.input_nullifiers,
+        &bundle.output_commitments,
+        &bundle.proof,
+    );
+    if !ring::verify(&message, &ring_members, &signature) {
+        report.fail(
+            Check::RingSignature,
+            "ring_signature",
+            "signature does not verify over the nullifiers, outputs and proof",
+        );
+    }
+    Ok(())
+}
+
+async fn check_merkle_root(
+    config: &Config,
+    rpc: &RpcClient,
+    bundle: &DecodedBundle,
+    archived_tree: Option<u32>,
+    report: &mut ValidationReport,
+) -> Result<(), ApiError> {
+    let program_id = &config.program_id;
+    match archived_tree {
+        None => {
+            let address = RootHistory::find_address(program_id).0;
+            let history = fetch_accounts(rpc, &[address])
+                .await?
+                .remove(0)
+                .filter(|account| account.owner == *program_id)
+                .and_then(|account| RootHistory::try_from_slice(&account.data).ok())
+                .ok_or(ApiError::ProtocolNotInitialized)?;
+            if !history.contains(&bundle.merkle_root) {
+                report.fail(
+                    Check::MerkleRoot,
+                    "merkle_root",
+                    format!(
+                        "root is not among the last {} tree roots; rebuild the proof against \
+                         the current root, or name its archived tree",
+                        history.roots.len()
+                    ),
+                );
+            }
+        }
+        Some(generation) => {
+            let address = CommitmentTree::find_archive_address(generation, program_id).0;
+            let archived = fetch_accounts(rpc, &[address])
+                .await?
+                .remove(0)
+                .filter(|account| account.owner == *program_id)
+                .and_then(|account| CommitmentTree::try_from_slice(&account.data).ok());
+            match archived {
+                None => report.fail(
+                    Check::MerkleRoot,
+                    "archived_tree",
+                    format!("no archived tree for generation {}", generation),
+                ),
+                Some(tree) if !tree.is_frozen || tree.current_root() != bundle.merkle_root => {
+                    report.fail(
+                        Check::MerkleRoot,
+                        "merkle_root",
+                        format!("root is not the final root of archived tree {}", generation),
+                    )
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn check_nullifiers(
+    config: &Config,
+    rpc: &RpcClient,
+    bundle: &DecodedBundle,
+    report: &mut ValidationReport,
+) -> Result<(), ApiError> {
+    for (i, nullifier) in bundle.input_nullifiers.iter().enumerate() {
+        if bundle.input_nullifiers[..i].contains(nullifier) {
+            report.fail(
+                Check::Nullifier,
+                format!("input_nullifiers[{}]", i),
+                "nullifier is repeated within the bundle",
+            );
+        }
+    }
+    
+    // An existing nullifier PDA means the input was already spent, as does a nullifier
+    // compacted into its shard or, before shards, into the filter account
+    let count = bundle.input_nullifiers.len();
+    let mut addresses: Vec<Pubkey> = bundle
+        .input_nullifiers
+        .iter()
+        .map(|nullifier| NullifierSet::find_address(nullifier, &config.program_id).0)
+        .collect();
+    addresses.extend(
+        bundle
+            .input_nullifiers
+            .iter()
+            .map(|nullifier| NullifierShard::find_address(nullifier, &config.program_id).0),
+    );
+    addresses.push(NullifierFilter::find_address(&config.program_id).0);
+    let mut accounts = fetch_accounts(rpc, &addresses).await?;
+    let filter = accounts
+        .pop()
+        .flatten()
+        .filter(|account| account.owner == config.program_id);
+    let shards = accounts.split_off(count);
+    for (i, account) in accounts.iter().enumerate() {
+        if let Some(account) = account {
+            if account.owner == config.program_id || !account.data.is_empty() {
+                report.fail(
+                    Check::Nullifier,
+                    format!("input_nullifiers[{}]", i),
+                    format!("nullifier already spent (account {})", addresses[i]),
+                );
+            }
+        } else if shards[i].as_ref().is_some_and(|shard| {
+            shard.owner == config.program_id
+                && NullifierShard::contains(&shard.data, &bundle.input_nullifiers[i])
+        }) || filter.as_ref().is_some_and(|filter| {
+            NullifierFilter::is_compacted(&filter.data, &bundle.input_nullifiers[i])
+        }) {
+            report.fail(
+                Check::Nullifier,
+                format!("input_nullifiers[{}]", i),
+                "nullifier already spent (compacted)",
+            );
+        }
+    }
+    Ok(())
+}
+
+async fn fetch_accounts(
+    rpc: &RpcClient,
+    addresses: &[Pubkey],
+) -> Result<Vec<Option<Account>>, ApiError> {
+    let mut accounts = Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(RPC_BATCH_SIZE) {
+        accounts.extend(
+            rpc.get_multiple_accounts(chunk)
+                .await
+                .map_err(|e| ApiError::Rpc(e.to_string()))?,
+        );
+    }
+    Ok(accounts)
+}
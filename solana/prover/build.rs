@@ -0,0 +1,15 @@
+//! With the `ffi` feature, regenerates the C header for `src/ffi.rs`.
+
+fn main() {
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+            .expect("invalid cbindgen.toml");
+        cbindgen::generate_with_config(&crate_dir, config)
+            .expect("failed to generate the C header")
+            .write_to_file(format!("{}/include/fpp_prover.h", crate_dir));
+    }
+}
@@ -0,0 +1,86 @@
+/* Floating Point Protocol spend prover. Generated by cbindgen; do not edit. */
+
+#ifndef FPP_PROVER_H
+#define FPP_PROVER_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Outcome of an FFI call
+ */
+typedef enum FppStatus {
+  FPP_STATUS_OK = 0,
+  FPP_STATUS_NULL_POINTER = 1,
+  /**
+   * Malformed key or witness, or a secret that isn't a field element
+   */
+  FPP_STATUS_INVALID_INPUT = 2,
+  /**
+   * The witness doesn't satisfy the circuit
+   */
+  FPP_STATUS_PROOF_FAILED = 3,
+  FPP_STATUS_PANIC = 4,
+} FppStatus;
+
+/**
+ * Bytes owned by the library; release with `fpp_buffer_free`
+ */
+typedef struct FppBuffer {
+  uint8_t *data;
+  size_t len;
+} FppBuffer;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Prove a Borsh-encoded `SpendWitness` and write the Borsh-encoded `Spend` to `out`
+ *
+ * # Safety
+ *
+ * `proving_key` and `witness` must point to `proving_key_len` and `witness_len` readable
+ * bytes, and `out` to a writable `FppBuffer`.
+ */
+FppStatus fpp_prove_spend(const uint8_t *proving_key,
+                          size_t proving_key_len,
+                          const uint8_t *witness,
+                          size_t witness_len,
+                          FppBuffer *out);
+
+/**
+ * Write the note commitment `Poseidon(secret, mass)` to `out`
+ *
+ * # Safety
+ *
+ * `secret` must point to 32 readable bytes and `out` to 32 writable bytes.
+ */
+FppStatus fpp_derive_commitment(const uint8_t *secret, uint64_t mass, uint8_t *out);
+
+/**
+ * Write the nullifier `Poseidon(secret, leaf_index)` to `out`
+ *
+ * # Safety
+ *
+ * `secret` must point to 32 readable bytes and `out` to 32 writable bytes.
+ */
+FppStatus fpp_derive_nullifier(const uint8_t *secret, uint64_t leaf_index, uint8_t *out);
+
+/**
+ * Release a buffer returned by this library
+ *
+ * # Safety
+ *
+ * `buffer` must come from this library and not have been freed already.
+ */
+void fpp_buffer_free(FppBuffer buffer);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FPP_PROVER_H */
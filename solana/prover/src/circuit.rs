@@ -0,0 +1,230 @@
+//! The spend circuit and the native values it commits to.
+
+use ark_bn254::Fr;
+use ark_ff::Zero;
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*, uint64::UInt64};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use floating_point_protocol_solana::{
+    merkle::{self, TREE_DEPTH},
+    state::CommitmentTree,
+};
+
+use crate::{encoding, error::ProverError, poseidon};
+
+/// A note being spent, with its place in the commitment tree
+#[derive(Debug, Clone)]
+pub struct SpendInput {
+    pub secret: Fr,
+    pub mass: u64,
+    pub leaf_index: u64,
+    /// Sibling hashes from the leaf up, `TREE_DEPTH` of them (`merkle_path`)
+    pub path: Vec<Fr>,
+}
+
+/// A note being created
+#[derive(Debug, Clone)]
+pub struct SpendOutput {
+    pub secret: Fr,
+    pub mass: u64,
+}
+
+/// Everything a spend makes public, computed natively
+#[derive(Debug, Clone)]
+pub struct PublicValues {
+    pub merkle_root: Fr,
+    pub nullifiers: Vec<Fr>,
+    pub output_commitments: Vec<Fr>,
+    /// `spend_binding` over the context, nullifiers, output commitments and exit mass
+    pub binding: Fr,
+}
+
+/// Spend of `inputs` into `outputs` and `exit_mass` paid out, against `merkle_root`
+///
+/// Proving and verifying keys fix the number of inputs and outputs; a circuit
+/// of a different shape needs keys of its own. A pool withdrawal is one input,
+/// no outputs and the pool's denomination as `exit_mass`; a pool transfer has
+/// outputs and no exit.
+#[derive(Debug, Clone)]
+pub struct SpendCircuit {
+    pub merkle_root: Fr,
+    /// Seed of the binding: `PoolState::withdrawal_context` or
+    /// `PoolRoute::transfer_context`, reduced with `groth16::hash_to_field`
+    pub context: Fr,
+    pub inputs: Vec<SpendInput>,
+    pub outputs: Vec<SpendOutput>,
+    pub exit_mass: u64,
+}
+
+impl SpendCircuit {
+    /// A circuit of the given shape with placeholder witnesses, for key generation
+    pub fn blank(inputs: usize, outputs: usize) -> Self {
+        Self {
+            merkle_root: Fr::zero(),
+            context: Fr::zero(),
+            inputs: vec![
+                SpendInput {
+                    secret: Fr::zero(),
+                    mass: 0,
+                    leaf_index: 0,
+                    path: vec![Fr::zero(); TREE_DEPTH],
+                };
+                inputs
+            ],
+            outputs: vec![
+                SpendOutput {
+                    secret: Fr::zero(),
+                    mass: 0,
+                };
+                outputs
+            ],
+            exit_mass: 0,
+        }
+    }
+    
+    pub fn public_values(&self) -> Result<PublicValues, ProverError> {
+        if self.inputs.is_empty() {
+            return Err(ProverError::InvalidWitness("a spend needs at least one input".into()));
+        }
+        if self.inputs.iter().any(|input| {
+            input.path.len() != TREE_DEPTH || input.leaf_index >= CommitmentTree::CAPACITY
+        }) {
+            return Err(ProverError::InvalidWitness(format!(
+                "inputs need a leaf index below {} and a path of {} siblings",
+                CommitmentTree::CAPACITY,
+                TREE_DEPTH
+            )));
+        }
+        let nullifiers = self
+            .inputs
+            .iter()
+            .map(|input| nullifier(&input.secret, input.leaf_index))
+            .collect::<Result<Vec<_>, _>>()?;
+        let output_commitments = self
+            .outputs
+            .iter()
+            .map(|output| note_commitment(&output.secret, output.mass))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(PublicValues {
+            merkle_root: self.merkle_root,
+            binding: spend_binding(
+                &self.context,
+                &nullifiers,
+                &output_commitments,
+                self.exit_mass,
+            )?,
+            nullifiers,
+            output_commitments,
+        })
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for SpendCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let public = self
+            .public_values()
+            .map_err(|_| SynthesisError::AssignmentMissing)?;
+        let root = FpVar::new_input(cs.clone(), || Ok(self.merkle_root))?;
+        let binding = FpVar::new_input(cs.clone(), || Ok(public.binding))?;
+        
+        let context = FpVar::new_witness(cs.clone(), || Ok(self.context))?;
+        let mut bound = Vec::with_capacity(self.inputs.len() + self.outputs.len() + 1);
+        let mut mass_in = FpVar::zero();
+        for input in &self.inputs {
+            let secret = FpVar::new_witness(cs.clone(), || Ok(input.secret))?;
+            let mass = mass_var(cs.clone(), input.mass)?;
+            let index_bits = (0..TREE_DEPTH)
+                .map(|level| {
+                    Boolean::new_witness(cs.clone(), || Ok((input.leaf_index >> level) & 1 == 1))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            
+            // Membership: the leaf hashes up to the root, left or right by index bit
+            let mut node = poseidon::hash_var(&[secret.clone(), mass.clone()])?;
+            for (bit, sibling) in index_bits.iter().zip(&input.path) {
+                let sibling = FpVar::new_witness(cs.clone(), || Ok(*sibling))?;
+                let left = bit.select(&sibling, &node)?;
+                let right = bit.select(&node, &sibling)?;
+                node = poseidon::hash_var(&[left, right])?;
+            }
+            node.enforce_equal(&root)?;
+            
+            let index = Boolean::le_bits_to_fp_var(&index_bits)?;
+            bound.push(poseidon::hash_var(&[secret, index])?);
+            mass_in += mass;
+        }
+        
+        let mut mass_out = FpVar::zero();
+        for output in &self.outputs {
+            let secret = FpVar::new_witness(cs.clone(), || Ok(output.secret))?;
+            let mass = mass_var(cs.clone(), output.mass)?;
+            bound.push(poseidon::hash_var(&[secret, mass.clone()])?);
+            mass_out += mass;
+        }
+        let exit_mass = mass_var(cs.clone(), self.exit_mass)?;
+        bound.push(exit_mass.clone());
+        // Masses are range-checked to 64 bits, so neither sum can wrap the field
+        mass_in.enforce_equal(&(mass_out + exit_mass))?;
+        
+        let mut chain = context;
+        for value in bound {
+            chain = poseidon::hash_var(&[chain, value])?;
+        }
+        chain.enforce_equal(&binding)
+    }
+}
+
+/// A 64-bit mass witness as a field element
+fn mass_var(cs: ConstraintSystemRef<Fr>, mass: u64) -> Result<FpVar<Fr>, SynthesisError> {
+    let bits = UInt64::new_witness(cs, || Ok(mass))?.to_bits_le();
+    Boolean::le_bits_to_fp_var(&bits)
+}
+
+/// Note commitment and commitment tree leaf: `Poseidon(secret, mass)`
+pub fn note_commitment(secret: &Fr, mass: u64) -> Result<Fr, ProverError> {
+    poseidon::hash(&[*secret, Fr::from(mass)])
+}
+
+/// Nullifier of the note at `leaf_index`: `Poseidon(secret, leaf_index)`
+pub fn nullifier(secret: &Fr, leaf_index: u64) -> Result<Fr, ProverError> {
+    poseidon::hash(&[*secret, Fr::from(leaf_index)])
+}
+
+/// Hash chain `h = Poseidon(h, x)` from `context` over the nullifiers, the outputs, then
+/// the exit mass; the program computes the same with `crypto::poseidon::spend_binding`
+pub fn spend_binding(
+    context: &Fr,
+    nullifiers: &[Fr],
+    output_commitments: &[Fr],
+    exit_mass: u64,
+) -> Result<Fr, ProverError> {
+    nullifiers
+        .iter()
+        .chain(output_commitments)
+        .chain([&Fr::from(exit_mass)])
+        .try_fold(*context, |chain, value| poseidon::hash(&[chain, *value]))
+}
+
+/// Sibling path of leaf `index` in a tree holding `leaves` in insertion order
+pub fn merkle_path(leaves: &[Fr], index: u64) -> Result<Vec<Fr>, ProverError> {
+    if index >= leaves.len() as u64 {
+        return Err(ProverError::InvalidWitness(format!(
+            "leaf {} of a tree with {} leaves",
+            index,
+            leaves.len()
+        )));
+    }
+    let zeros = merkle::zero_hashes().map_err(|e| ProverError::Hash(e.to_string()))?;
+    let mut path = Vec::with_capacity(TREE_DEPTH);
+    let mut nodes = leaves.to_vec();
+    let mut position = index as usize;
+    for zero in &zeros[..TREE_DEPTH] {
+        let zero = encoding::parse_field_element(zero)?;
+        path.push(nodes.get(position ^ 1).copied().unwrap_or(zero));
+        nodes = nodes
+            .chunks(2)
+            .map(|pair| poseidon::hash(&[pair[0], pair.get(1).copied().unwrap_or(zero)]))
+            .collect::<Result<Vec<_>, _>>()?;
+        position /= 2;
+    }
+    Ok(path)
+}
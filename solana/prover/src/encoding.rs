@@ -0,0 +1,136 @@
+//! Conversions to and from the byte layouts `crypto::groth16` verifies.
+//!
+//! Field elements are 32 bytes big-endian. G1 points are `x || y` and G2
+//! points `x_im || x_re || y_im || y_re` (EIP-197), with the point at
+//! infinity all zeros. Proof `A` is written as is; the program negates it.
+
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::{
+    short_weierstrass::{Affine, SWCurveConfig},
+    AffineRepr,
+};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Proof, VerifyingKey};
+use floating_point_protocol_solana::crypto::{
+    groth16::{Groth16Proof, Groth16VerifyingKey, ASSOCIATION_PUBLIC_INPUTS},
+    poseidon,
+};
+
+use crate::error::ProverError;
+
+pub fn field_element(element: &Fr) -> [u8; 32] {
+    be_bytes(element.into_bigint().to_bytes_be())
+}
+
+/// Parse a canonical big-endian field element
+pub fn parse_field_element(bytes: &[u8; 32]) -> Result<Fr, ProverError> {
+    if !poseidon::is_field_element(bytes) {
+        return Err(ProverError::InvalidWitness("not a canonical field element".into()));
+    }
+    Ok(Fr::from_be_bytes_mod_order(bytes))
+}
+
+pub fn g1(point: &G1Affine) -> [u8; 64] {
+    let mut bytes = [0u8; 64];
+    if let Some((x, y)) = point.xy() {
+        bytes[..32].copy_from_slice(&base_field(x));
+        bytes[32..].copy_from_slice(&base_field(y));
+    }
+    bytes
+}
+
+pub fn g2(point: &G2Affine) -> [u8; 128] {
+    let mut bytes = [0u8; 128];
+    if let Some((x, y)) = point.xy() {
+        bytes[..32].copy_from_slice(&base_field(&x.c1));
+        bytes[32..64].copy_from_slice(&base_field(&x.c0));
+        bytes[64..96].copy_from_slice(&base_field(&y.c1));
+        bytes[96..].copy_from_slice(&base_field(&y.c0));
+    }
+    bytes
+}
+
+pub fn proof(proof: &Proof<Bn254>) -> Groth16Proof {
+    Groth16Proof {
+        a: g1(&proof.a),
+        b: g2(&proof.b),
+        c: g1(&proof.c),
+    }
+}
+
+/// The on-chain form of a spend circuit verifying key
+pub fn verifying_key(key: &VerifyingKey<Bn254>) -> Result<Groth16VerifyingKey, ProverError> {
+    let ic: [[u8; 64]; ASSOCIATION_PUBLIC_INPUTS + 1] = key
+        .gamma_abc_g1
+        .iter()
+        .map(g1)
+        .collect::<Vec<_>>()
+        .try_into()
+        .map_err(|ic: Vec<[u8; 64]>| {
+            ProverError::InvalidWitness(format!(
+                "verifying key has {} public inputs, the program takes {}",
+                ic.len().saturating_sub(1),
+                ASSOCIATION_PUBLIC_INPUTS
+            ))
+        })?;
+    Ok(Groth16VerifyingKey {
+        alpha_g1: g1(&key.alpha_g1),
+        beta_g2: g2(&key.beta_g2),
+        gamma_g2: g2(&key.gamma_g2),
+        delta_g2: g2(&key.delta_g2),
+        ic,
+    })
+}
+
+pub fn parse_proof(proof: &Groth16Proof) -> Result<Proof<Bn254>, ProverError> {
+    Ok(Proof {
+        a: parse_g1(&proof.a)?,
+        b: parse_g2(&proof.b)?,
+        c: parse_g1(&proof.c)?,
+    })
+}
+
+fn parse_g1(bytes: &[u8; 64]) -> Result<G1Affine, ProverError> {
+    if *bytes == [0u8; 64] {
+        return Ok(G1Affine::zero());
+    }
+    let point = G1Affine::new_unchecked(parse_base(&bytes[..32])?, parse_base(&bytes[32..])?);
+    checked(point)
+}
+
+fn parse_g2(bytes: &[u8; 128]) -> Result<G2Affine, ProverError> {
+    if *bytes == [0u8; 128] {
+        return Ok(G2Affine::zero());
+    }
+    let x = Fq2::new(parse_base(&bytes[32..64])?, parse_base(&bytes[..32])?);
+    let y = Fq2::new(parse_base(&bytes[96..])?, parse_base(&bytes[64..96])?);
+    checked(G2Affine::new_unchecked(x, y))
+}
+
+fn checked<P: SWCurveConfig>(point: Affine<P>) -> Result<Affine<P>, ProverError> {
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(ProverError::InvalidWitness("point is not on the curve".into()));
+    }
+    Ok(point)
+}
+
+fn base_field(element: &Fq) -> [u8; 32] {
+    be_bytes(element.into_bigint().to_bytes_be())
+}
+
+fn parse_base(bytes: &[u8]) -> Result<Fq, ProverError> {
+    let element = Fq::from_be_bytes_mod_order(bytes);
+    if base_field(&element)[..] != *bytes {
+        return Err(ProverError::InvalidWitness(
+            "coordinate is not a canonical base field element".into(),
+        ));
+    }
+    Ok(element)
+}
+
+/// Left-pad a big-endian integer to 32 bytes
+fn be_bytes(bytes: Vec<u8>) -> [u8; 32] {
+    let mut padded = [0u8; 32];
+    padded[32 - bytes.len()..].copy_from_slice(&bytes);
+    padded
+}
@@ -0,0 +1,18 @@
+use ark_relations::r1cs::SynthesisError;
+use ark_serialize::SerializationError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ProverError {
+    #[error("Invalid Witness: {0}")]
+    InvalidWitness(String),
+    
+    #[error("Hash Error: {0}")]
+    Hash(String),
+    
+    #[error("Synthesis Error: {0}")]
+    Synthesis(#[from] SynthesisError),
+    
+    #[error("Serialization Error: {0}")]
+    Serialization(#[from] SerializationError),
+}
@@ -0,0 +1,154 @@
+//! C ABI for embedding the prover in mobile wallets.
+//!
+//! Swift and Kotlin call these through `include/fpp_prover.h`, which the
+//! build generates with cbindgen. Inputs and outputs are the same bytes the
+//! wasm bindings use: compressed proving keys, a Borsh `SpendWitness` in and
+//! a Borsh `Spend` out, field elements 32 bytes big-endian. Every function
+//! returns an `FppStatus`, and no panic unwinds into the caller.
+
+use std::{panic, ptr, slice};
+
+use ark_bn254::Fr;
+use borsh::{BorshDeserialize, BorshSerialize};
+use rand::rngs::OsRng;
+
+use crate::{circuit, encoding, error::ProverError, keys, witness::SpendWitness};
+
+/// Outcome of an FFI call
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FppStatus {
+    Ok = 0,
+    NullPointer = 1,
+    /// Malformed key or witness, or a secret that isn't a field element
+    InvalidInput = 2,
+    /// The witness doesn't satisfy the circuit
+    ProofFailed = 3,
+    Panic = 4,
+}
+
+/// Bytes owned by the library; release with `fpp_buffer_free`
+#[repr(C)]
+pub struct FppBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl FppBuffer {
+    fn from_vec(bytes: Vec<u8>) -> Self {
+        let len = bytes.len();
+        let data = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+        Self { data, len }
+    }
+}
+
+impl From<ProverError> for FppStatus {
+    fn from(e: ProverError) -> Self {
+        match e {
+            ProverError::Synthesis(_) => FppStatus::ProofFailed,
+            _ => FppStatus::InvalidInput,
+        }
+    }
+}
+
+fn guard(f: impl FnOnce() -> Result<(), FppStatus>) -> FppStatus {
+    match panic::catch_unwind(panic::AssertUnwindSafe(f)) {
+        Ok(Ok(())) => FppStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => FppStatus::Panic,
+    }
+}
+
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Result<&'a [u8], FppStatus> {
+    if data.is_null() {
+        return Err(FppStatus::NullPointer);
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+unsafe fn field_element(data: *const u8) -> Result<Fr, FppStatus> {
+    let element: &[u8; 32] = bytes(data, 32)?.try_into().map_err(|_| FppStatus::InvalidInput)?;
+    Ok(encoding::parse_field_element(element)?)
+}
+
+unsafe fn write_field_element(element: &Fr, out: *mut u8) -> Result<(), FppStatus> {
+    if out.is_null() {
+        return Err(FppStatus::NullPointer);
+    }
+    ptr::copy_nonoverlapping(encoding::field_element(element).as_ptr(), out, 32);
+    Ok(())
+}
+
+/// Prove a Borsh-encoded `SpendWitness` and write the Borsh-encoded `Spend` to `out`
+///
+/// # Safety
+///
+/// `proving_key` and `witness` must point to `proving_key_len` and `witness_len` readable
+/// bytes, and `out` to a writable `FppBuffer`.
+#[no_mangle]
+pub unsafe extern "C" fn fpp_prove_spend(
+    proving_key: *const u8,
+    proving_key_len: usize,
+    witness: *const u8,
+    witness_len: usize,
+    out: *mut FppBuffer,
+) -> FppStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(FppStatus::NullPointer);
+        }
+        let proving_key = keys::read_proving_key(bytes(proving_key, proving_key_len)?)?;
+        let witness = SpendWitness::try_from_slice(bytes(witness, witness_len)?)
+            .map_err(|_| FppStatus::InvalidInput)?;
+        let spend = crate::prove(&proving_key, witness.into_circuit()?, &mut OsRng)?;
+        let encoded = spend.try_to_vec().map_err(|_| FppStatus::InvalidInput)?;
+        out.write(FppBuffer::from_vec(encoded));
+        Ok(())
+    })
+}
+
+/// Write the note commitment `Poseidon(secret, mass)` to `out`
+///
+/// # Safety
+///
+/// `secret` must point to 32 readable bytes and `out` to 32 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn fpp_derive_commitment(
+    secret: *const u8,
+    mass: u64,
+    out: *mut u8,
+) -> FppStatus {
+    guard(|| {
+        let commitment = circuit::note_commitment(&field_element(secret)?, mass)?;
+        write_field_element(&commitment, out)
+    })
+}
+
+/// Write the nullifier `Poseidon(secret, leaf_index)` to `out`
+///
+/// # Safety
+///
+/// `secret` must point to 32 readable bytes and `out` to 32 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn fpp_derive_nullifier(
+    secret: *const u8,
+    leaf_index: u64,
+    out: *mut u8,
+) -> FppStatus {
+    guard(|| {
+        let nullifier = circuit::nullifier(&field_element(secret)?, leaf_index)?;
+        write_field_element(&nullifier, out)
+    })
+}
+
+/// Release a buffer returned by this library
+///
+/// # Safety
+///
+/// `buffer` must come from this library and not have been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn fpp_buffer_free(buffer: FppBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(buffer.data, buffer.len)));
+    }
+}
@@ -0,0 +1,48 @@
+//! Key generation and key files.
+//!
+//! `setup` is a single-party setup: whoever runs it can forge proofs, so it
+//! suits devnets and tests, and production keys should come from a ceremony.
+//! Proving keys are stored in arkworks' compressed encoding; verifying keys
+//! are stored in the program's Borsh layout, the file `fpp-deploy` hashes
+//! when it registers a circuit.
+
+use std::io::{Read, Write};
+
+use ark_bn254::Bn254;
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use ark_std::rand::{CryptoRng, RngCore};
+use borsh::BorshSerialize;
+
+use crate::{circuit::SpendCircuit, encoding, error::ProverError};
+
+/// Proving and verifying keys for spends of `inputs` notes into `outputs`
+pub fn setup<R: RngCore + CryptoRng>(
+    inputs: usize,
+    outputs: usize,
+    rng: &mut R,
+) -> Result<(ProvingKey<Bn254>, VerifyingKey<Bn254>), ProverError> {
+    Ok(Groth16::<Bn254>::circuit_specific_setup(
+        SpendCircuit::blank(inputs, outputs),
+        rng,
+    )?)
+}
+
+pub fn write_proving_key<W: Write>(key: &ProvingKey<Bn254>, writer: W) -> Result<(), ProverError> {
+    Ok(key.serialize_compressed(writer)?)
+}
+
+pub fn read_proving_key<R: Read>(reader: R) -> Result<ProvingKey<Bn254>, ProverError> {
+    Ok(ProvingKey::deserialize_compressed(reader)?)
+}
+
+/// Write `key` as the Borsh-encoded `Groth16VerifyingKey` the program verifies with
+pub fn write_verifying_key<W: Write>(
+    key: &VerifyingKey<Bn254>,
+    mut writer: W,
+) -> Result<(), ProverError> {
+    encoding::verifying_key(key)?
+        .serialize(&mut writer)
+        .map_err(|e| ProverError::Serialization(e.into()))
+}
@@ -0,0 +1,92 @@
+//! Groth16 prover for the Floating Point Protocol spend circuit.
+//!
+//! The circuit covers pool notes: `PoolWithdraw` and `PoolTransfer` verify it
+//! through the verifier program. Points are Ristretto commitments spent with
+//! ring signatures (`crypto::ring`) and never go through this circuit.
+//!
+//! A spend proves, without revealing which notes were spent, that every
+//! input note is a leaf of the pool's tree under a public root, that each
+//! published nullifier is `Poseidon(secret, leaf_index)` of its input, and
+//! that input mass equals output mass plus the mass paid out. Notes commit as
+//! `Poseidon(secret, mass)`, hashed with the same circom parameters as
+//! `crypto::poseidon`, so tree paths and nullifiers computed here match the
+//! program's.
+//!
+//! The circuit has two public inputs, `[merkle_root, binding]`, where the
+//! binding chains the spend's context, nullifiers, output commitments and
+//! exit mass (`spend_binding`), equal to `PoolState::withdrawal_binding` or
+//! `PoolRoute::transfer_binding`. That is the shape `crypto::groth16::verify`
+//! takes, and `encoding` writes proofs and verifying keys in the byte layout it
+//! expects.
+//!
+//! The library only reads and writes through `std::io` traits, so it builds
+//! for `wasm32-unknown-unknown`; the `wasm` feature adds the browser entry
+//! points in `wasm`, and the `ffi` feature a C ABI for mobile wallets in `ffi`.
+
+pub mod circuit;
+pub mod encoding;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod keys;
+pub mod poseidon;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod witness;
+
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_snark::SNARK;
+use ark_std::rand::{CryptoRng, RngCore};
+use borsh::{BorshDeserialize, BorshSerialize};
+use floating_point_protocol_solana::crypto::groth16::{Groth16Proof, ASSOCIATION_PUBLIC_INPUTS};
+
+pub use circuit::{SpendCircuit, SpendInput, SpendOutput};
+pub use error::ProverError;
+
+/// Public inputs of the spend circuit; the same count as the on-chain verifier's
+pub const SPEND_PUBLIC_INPUTS: usize = ASSOCIATION_PUBLIC_INPUTS;
+
+/// A spend proof with the public data it commits to, big-endian throughout
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Spend {
+    pub proof: Groth16Proof,
+    /// `[merkle_root, binding]`, as passed to `crypto::groth16::verify`
+    pub public_inputs: [[u8; 32]; SPEND_PUBLIC_INPUTS],
+    pub nullifiers: Vec<[u8; 32]>,
+    pub output_commitments: Vec<[u8; 32]>,
+}
+
+/// Prove `circuit` with a proving key from `keys::setup` for its shape
+pub fn prove<R: RngCore + CryptoRng>(
+    proving_key: &ProvingKey<Bn254>,
+    circuit: SpendCircuit,
+    rng: &mut R,
+) -> Result<Spend, ProverError> {
+    let public = circuit.public_values()?;
+    let proof = Groth16::<Bn254>::prove(proving_key, circuit, rng)?;
+    Ok(Spend {
+        proof: encoding::proof(&proof),
+        public_inputs: [
+            encoding::field_element(&public.merkle_root),
+            encoding::field_element(&public.binding),
+        ],
+        nullifiers: public.nullifiers.iter().map(encoding::field_element).collect(),
+        output_commitments: public
+            .output_commitments
+            .iter()
+            .map(encoding::field_element)
+            .collect(),
+    })
+}
+
+/// Check a spend natively, as the program would with the encoded verifying key
+pub fn verify(verifying_key: &VerifyingKey<Bn254>, spend: &Spend) -> Result<bool, ProverError> {
+    let public_inputs = spend
+        .public_inputs
+        .iter()
+        .map(encoding::parse_field_element)
+        .collect::<Result<Vec<Fr>, _>>()?;
+    let proof = encoding::parse_proof(&spend.proof)?;
+    Ok(Groth16::<Bn254>::verify(verifying_key, &public_inputs, &proof)?)
+}
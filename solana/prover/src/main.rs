@@ -0,0 +1,44 @@
+//! Generates spend circuit keys: `cargo run -p fpp-prover --release --
+//! <inputs> <outputs> <prefix>` writes `<prefix>.pk` and `<prefix>.vk`, the
+//! latter ready for `fpp-deploy`'s `[[circuits]]` entries.
+
+use std::{env, fs::File, io::BufWriter, process};
+
+use fpp_prover::{keys, ProverError};
+use rand::rngs::OsRng;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let shape = match args.as_slice() {
+        [inputs, outputs, prefix] => inputs
+            .parse::<usize>()
+            .ok()
+            .zip(outputs.parse::<usize>().ok())
+            .map(|(inputs, outputs)| (inputs, outputs, prefix.clone())),
+        _ => None,
+    };
+    let Some((inputs, outputs, prefix)) = shape else {
+        eprintln!("usage: fpp-prover <inputs> <outputs> <prefix>");
+        process::exit(2);
+    };
+    if let Err(e) = run(inputs, outputs, &prefix) {
+        eprintln!("key generation failed: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run(inputs: usize, outputs: usize, prefix: &str) -> Result<(), ProverError> {
+    let (proving_key, verifying_key) = keys::setup(inputs, outputs, &mut OsRng)?;
+    let create = |path: String| {
+        File::create(&path)
+            .map(BufWriter::new)
+            .map_err(|e| ProverError::Serialization(e.into()))
+    };
+    keys::write_proving_key(&proving_key, create(format!("{}.pk", prefix))?)?;
+    keys::write_verifying_key(&verifying_key, create(format!("{}.vk", prefix))?)?;
+    println!(
+        "{}x{} spend keys written to {}.pk and {}.vk",
+        inputs, outputs, prefix, prefix
+    );
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! Poseidon in and out of the circuit.
+//!
+//! The gadget replays `light-poseidon`'s permutation over its circom
+//! parameters (state `[0, inputs..]`, x^5 S-box, full rounds split around the
+//! partial ones) so in-circuit hashes equal `crypto::poseidon::hashv`, which
+//! `hash` calls for the native side.
+
+use ark_bn254::Fr;
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::SynthesisError;
+use floating_point_protocol_solana::crypto::poseidon;
+use light_poseidon::parameters::bn254_x5;
+
+use crate::{encoding, error::ProverError};
+
+/// Native Poseidon of 1-12 field elements
+pub fn hash(inputs: &[Fr]) -> Result<Fr, ProverError> {
+    let encoded: Vec<[u8; 32]> = inputs.iter().map(encoding::field_element).collect();
+    let slices: Vec<&[u8]> = encoded.iter().map(|input| &input[..]).collect();
+    let hash = poseidon::hashv(&slices).map_err(|e| ProverError::Hash(e.to_string()))?;
+    encoding::parse_field_element(&hash)
+}
+
+/// In-circuit Poseidon of 1-12 field elements
+pub fn hash_var(inputs: &[FpVar<Fr>]) -> Result<FpVar<Fr>, SynthesisError> {
+    let width = inputs.len() + 1;
+    let params = bn254_x5::get_poseidon_parameters::<Fr>(width as u8)
+        .map_err(|_| SynthesisError::Unsatisfiable)?;
+    let half_full = params.full_rounds / 2;
+    
+    let mut state: Vec<FpVar<Fr>> = Some(FpVar::zero())
+        .into_iter()
+        .chain(inputs.iter().cloned())
+        .collect();
+    for round in 0..params.full_rounds + params.partial_rounds {
+        for (i, element) in state.iter_mut().enumerate() {
+            *element += FpVar::constant(params.ark[round * width + i]);
+        }
+        let full = round < half_full || round >= half_full + params.partial_rounds;
+        for (i, element) in state.iter_mut().enumerate() {
+            if full || i == 0 {
+                *element = sbox(element)?;
+            }
+        }
+        state = params
+            .mds
+            .iter()
+            .map(|row| {
+                state
+                    .iter()
+                    .zip(row)
+                    .fold(FpVar::zero(), |acc, (element, m)| acc + element * *m)
+            })
+            .collect();
+    }
+    Ok(state.swap_remove(0))
+}
+
+fn sbox(x: &FpVar<Fr>) -> Result<FpVar<Fr>, SynthesisError> {
+    let x4 = x.square()?.square()?;
+    Ok(x4 * x)
+}
@@ -0,0 +1,20 @@
+//! wasm-bindgen entry points, so browser wallets prove spends client-side.
+//!
+//! Values cross the boundary as bytes: proving keys in the compressed
+//! encoding `keys::write_proving_key` produces, witnesses and spends Borsh
+//! encoded with field elements 32 bytes big-endian, as everywhere else.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use rand::rngs::OsRng;
+use wasm_bindgen::prelude::*;
+
+use crate::{keys, witness::SpendWitness};
+
+/// Prove a Borsh-encoded `SpendWitness`, returning the Borsh-encoded `Spend`
+#[wasm_bindgen(js_name = proveSpend)]
+pub fn prove_spend(proving_key: &[u8], witness: &[u8]) -> Result<Vec<u8>, JsError> {
+    let proving_key = keys::read_proving_key(proving_key)?;
+    let witness = SpendWitness::try_from_slice(witness)?;
+    let spend = crate::prove(&proving_key, witness.into_circuit()?, &mut OsRng)?;
+    Ok(spend.try_to_vec()?)
+}
@@ -0,0 +1,80 @@
+//! Borsh form of a spend's witness, for callers outside Rust.
+//!
+//! The wasm and C bindings take a `SpendWitness` as bytes rather than
+//! arkworks field elements; secrets and path hashes are 32 bytes big-endian,
+//! like every field element in `encoding`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use floating_point_protocol_solana::crypto::groth16;
+
+use crate::{
+    circuit::{SpendCircuit, SpendInput, SpendOutput},
+    encoding,
+    error::ProverError,
+};
+
+/// A note being spent, as bindings pass it
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct WitnessInput {
+    pub secret: [u8; 32],
+    pub mass: u64,
+    pub leaf_index: u64,
+    /// Sibling hashes from the leaf up
+    pub path: Vec<[u8; 32]>,
+}
+
+/// A note being created, as bindings pass it
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct WitnessOutput {
+    pub secret: [u8; 32],
+    pub mass: u64,
+}
+
+/// Borsh form of a `SpendCircuit`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct SpendWitness {
+    pub merkle_root: [u8; 32],
+    /// `PoolState::withdrawal_context` or `PoolRoute::transfer_context`, as is
+    pub context: [u8; 32],
+    pub inputs: Vec<WitnessInput>,
+    pub outputs: Vec<WitnessOutput>,
+    pub exit_mass: u64,
+}
+
+impl SpendWitness {
+    pub fn into_circuit(self) -> Result<SpendCircuit, ProverError> {
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|input| {
+                Ok(SpendInput {
+                    secret: encoding::parse_field_element(&input.secret)?,
+                    mass: input.mass,
+                    leaf_index: input.leaf_index,
+                    path: input
+                        .path
+                        .iter()
+                        .map(encoding::parse_field_element)
+                        .collect::<Result<_, _>>()?,
+                })
+            })
+            .collect::<Result<_, ProverError>>()?;
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|output| {
+                Ok(SpendOutput {
+                    secret: encoding::parse_field_element(&output.secret)?,
+                    mass: output.mass,
+                })
+            })
+            .collect::<Result<_, ProverError>>()?;
+        Ok(SpendCircuit {
+            merkle_root: encoding::parse_field_element(&self.merkle_root)?,
+            context: encoding::parse_field_element(&groth16::hash_to_field(&self.context))?,
+            inputs,
+            outputs,
+            exit_mass: self.exit_mass,
+        })
+    }
+}
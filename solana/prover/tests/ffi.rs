@@ -0,0 +1,129 @@
+#![cfg(feature = "ffi")]
+
+use std::{ptr, slice};
+
+use ark_bn254::Fr;
+use borsh::{BorshDeserialize, BorshSerialize};
+use floating_point_protocol_solana::state::CommitmentTree;
+use fpp_prover::{
+    circuit, encoding,
+    ffi::{self, FppBuffer, FppStatus},
+    keys,
+    witness::{SpendWitness, WitnessInput},
+    Spend,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+const DENOMINATION: u64 = 1_000_000;
+
+/// A withdrawal of the second of three notes, with secrets 1 to 3
+fn witness() -> SpendWitness {
+    let leaves: Vec<Fr> = (1..=3u64)
+        .map(|secret| circuit::note_commitment(&Fr::from(secret), DENOMINATION).unwrap())
+        .collect();
+    let mut tree = CommitmentTree::new().unwrap();
+    for leaf in &leaves {
+        tree.insert(encoding::field_element(leaf)).unwrap();
+    }
+    SpendWitness {
+        merkle_root: tree.current_root(),
+        context: [4u8; 32],
+        inputs: vec![WitnessInput {
+            secret: encoding::field_element(&Fr::from(2u64)),
+            mass: DENOMINATION,
+            leaf_index: 1,
+            path: circuit::merkle_path(&leaves, 1)
+                .unwrap()
+                .iter()
+                .map(encoding::field_element)
+                .collect(),
+        }],
+        outputs: Vec::new(),
+        exit_mass: DENOMINATION,
+    }
+}
+
+fn prove(proving_key: &[u8], witness: &[u8]) -> Result<Vec<u8>, FppStatus> {
+    let mut out = FppBuffer {
+        data: ptr::null_mut(),
+        len: 0,
+    };
+    let status = unsafe {
+        ffi::fpp_prove_spend(
+            proving_key.as_ptr(),
+            proving_key.len(),
+            witness.as_ptr(),
+            witness.len(),
+            &mut out,
+        )
+    };
+    if status != FppStatus::Ok {
+        return Err(status);
+    }
+    let bytes = unsafe { slice::from_raw_parts(out.data, out.len) }.to_vec();
+    unsafe { ffi::fpp_buffer_free(out) };
+    Ok(bytes)
+}
+
+#[test]
+fn spends_proven_over_the_c_abi_verify() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let (proving_key, verifying_key) = keys::setup(1, 0, &mut rng).unwrap();
+    let mut key_bytes = Vec::new();
+    keys::write_proving_key(&proving_key, &mut key_bytes).unwrap();
+    let witness = witness().try_to_vec().unwrap();
+    
+    let spend = Spend::try_from_slice(&prove(&key_bytes, &witness).unwrap()).unwrap();
+    assert!(fpp_prover::verify(&verifying_key, &spend).unwrap());
+    
+    assert_eq!(prove(&key_bytes[..64], &witness), Err(FppStatus::InvalidInput));
+    assert_eq!(prove(&key_bytes, &witness[..witness.len() - 1]), Err(FppStatus::InvalidInput));
+    let status = unsafe {
+        ffi::fpp_prove_spend(
+            key_bytes.as_ptr(),
+            key_bytes.len(),
+            witness.as_ptr(),
+            witness.len(),
+            ptr::null_mut(),
+        )
+    };
+    assert_eq!(status, FppStatus::NullPointer);
+}
+
+#[test]
+fn derivations_match_the_circuit() {
+    let secret = encoding::field_element(&Fr::from(5u64));
+    let mut out = [0u8; 32];
+    
+    let status = unsafe { ffi::fpp_derive_commitment(secret.as_ptr(), 7, out.as_mut_ptr()) };
+    assert_eq!(status, FppStatus::Ok);
+    let commitment = circuit::note_commitment(&Fr::from(5u64), 7).unwrap();
+    assert_eq!(out, encoding::field_element(&commitment));
+    
+    let status = unsafe { ffi::fpp_derive_nullifier(secret.as_ptr(), 9, out.as_mut_ptr()) };
+    assert_eq!(status, FppStatus::Ok);
+    let nullifier = circuit::nullifier(&Fr::from(5u64), 9).unwrap();
+    assert_eq!(out, encoding::field_element(&nullifier));
+}
+
+#[test]
+fn bad_pointers_and_secrets_are_reported() {
+    let secret = [0xffu8; 32];
+    let mut out = [0u8; 32];
+    unsafe {
+        let status = ffi::fpp_derive_commitment(secret.as_ptr(), 7, out.as_mut_ptr());
+        assert_eq!(status, FppStatus::InvalidInput);
+        let status = ffi::fpp_derive_nullifier(ptr::null(), 9, out.as_mut_ptr());
+        assert_eq!(status, FppStatus::NullPointer);
+        let valid = [0u8; 32];
+        let status = ffi::fpp_derive_nullifier(valid.as_ptr(), 9, ptr::null_mut());
+        assert_eq!(status, FppStatus::NullPointer);
+        
+        // Freeing an empty buffer is a no-op
+        ffi::fpp_buffer_free(FppBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        });
+    }
+    assert_eq!(out, [0u8; 32]);
+}
@@ -0,0 +1,390 @@
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::{Groth16, ProvingKey};
+use ark_r1cs_std::{fields::fp::FpVar, prelude::*};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_snark::SNARK;
+use borsh::{BorshDeserialize, BorshSerialize};
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    crypto::{groth16::Groth16Proof, ring},
+    error::FPPError,
+    instruction,
+    migration::Versioned,
+    pda,
+    state::{
+        BatchedPayment, CircuitInfo, CircuitKey, CommitmentTree, EncryptedNote, FloatingPoint,
+        RootHistory,
+    },
+};
+use fpp_prover::encoding;
+use rand::{rngs::StdRng, SeedableRng};
+use solana_program_test::ProgramTest;
+use solana_sdk::{
+    hash::hash,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    transaction::TransactionError,
+};
+use solana_zk_token_sdk::{
+    encryption::pedersen::Pedersen, instruction::BatchedRangeProofU64Data,
+    zk_token_proof_instruction::ProofInstruction,
+};
+
+/// Stand-in for the payment circuit: any statement over the root and the binding will do,
+/// since the program only routes the proof to the verifier
+#[derive(Clone)]
+struct PaymentCircuit {
+    merkle_root: Fr,
+    binding: Fr,
+}
+
+impl ConstraintSynthesizer<Fr> for PaymentCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let root = FpVar::new_input(cs.clone(), || Ok(self.merkle_root))?;
+        let binding = FpVar::new_input(cs.clone(), || Ok(self.binding))?;
+        let product = FpVar::new_witness(cs, || Ok(self.merkle_root * self.binding))?;
+        product.enforce_equal(&(root * binding))
+    }
+}
+
+/// A spendable point of `key`, added to `program_test`
+fn add_point(
+    program_test: &mut ProgramTest,
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    key: &SpendKey,
+) -> Pubkey {
+    let point = FloatingPoint {
+        version: FloatingPoint::VERSION,
+        is_initialized: true,
+        commitment: key.commitment(),
+        created_at: 0,
+        mass: 1,
+        is_active: true,
+        creator: Pubkey::new_unique(),
+        locked_until: 0,
+        created_slot: 0,
+        mint: *mint,
+        rent_payer: Pubkey::new_unique(),
+        accrued: 0,
+        mass_updated_at: 0,
+    };
+    common::add_point(program_test, program_id, &point)
+}
+
+/// Initialize the protocol and register a key for the payment circuit
+async fn register_payment_circuit(
+    harness: &mut TestHarness,
+    mint: &Pubkey,
+) -> (ProvingKey<Bn254>, CircuitKey) {
+    let program_id = harness.program_id;
+    let user = harness.payer();
+    let (protocol_state, _) = pda::find_protocol_state(&program_id);
+    let blank = PaymentCircuit {
+        merkle_root: Fr::from(0u64),
+        binding: Fr::from(0u64),
+    };
+    let (proving_key, verifying_key) =
+        Groth16::<Bn254>::circuit_specific_setup(blank, &mut StdRng::seed_from_u64(3)).unwrap();
+    let verifying_key = encoding::verifying_key(&verifying_key).unwrap();
+    let setup = [
+        instruction::initialize(
+            &program_id,
+            &user,
+            &protocol_state,
+            mint,
+            10,
+            10,
+            RootHistory::DEFAULT_SIZE,
+        )
+        .unwrap(),
+        instruction::register_circuit(
+            &program_id,
+            &user,
+            &protocol_state,
+            &user,
+            CircuitInfo::PAYMENT_CIRCUIT_ID,
+            20,
+            1,
+            1,
+            hash(&verifying_key.try_to_vec().unwrap()).to_bytes(),
+        )
+        .unwrap(),
+        instruction::update_verifying_key(
+            &program_id,
+            &user,
+            &protocol_state,
+            &user,
+            CircuitInfo::PAYMENT_CIRCUIT_ID,
+            1,
+            verifying_key,
+        )
+        .unwrap(),
+    ];
+    harness.process(&setup, &[]).await.unwrap();
+    // Past the points' minimum exit delay
+    harness.warp_seconds(60).await;
+    let (key_address, _) = CircuitKey::find_address(CircuitInfo::PAYMENT_CIRCUIT_ID, &program_id);
+    let account = harness.context.banks_client.get_account(key_address).await.unwrap().unwrap();
+    (proving_key, CircuitKey::try_from_slice(&account.data).unwrap())
+}
+
+fn prove(
+    proving_key: &ProvingKey<Bn254>,
+    merkle_root: &[u8; 32],
+    binding: [u8; 32],
+) -> Groth16Proof {
+    let circuit = PaymentCircuit {
+        merkle_root: encoding::parse_field_element(merkle_root).unwrap(),
+        binding: encoding::parse_field_element(&binding).unwrap(),
+    };
+    let proof = Groth16::<Bn254>::prove(proving_key, circuit, &mut StdRng::seed_from_u64(4));
+    encoding::proof(&proof.unwrap())
+}
+
+/// A commitment to `amount` and the range proof instruction covering it
+fn range_proven_output(amount: u64) -> ([u8; 32], Instruction) {
+    let (commitment, opening) = Pedersen::new(amount);
+    let data =
+        BatchedRangeProofU64Data::new(vec![&commitment], vec![amount], vec![64], vec![&opening])
+            .unwrap();
+    let instruction =
+        ProofInstruction::VerifyBatchedRangeProofU64.encode_verify_proof(None, &data);
+    (commitment.to_bytes(), instruction)
+}
+
+#[tokio::test]
+async fn payment_proofs_are_checked_by_the_verifier_program() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let (protocol_state, _) = pda::find_protocol_state(&program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let key = SpendKey::new(1);
+    let point = add_point(&mut program_test, &program_id, &mint, &key);
+    let mut harness = TestHarness::start(program_test, program_id).await;
+    let user = harness.payer();
+    let (proving_key, circuit_key) = register_payment_circuit(&mut harness, &mint).await;
+    
+    let merkle_root = CommitmentTree::new().unwrap().current_root();
+    let nullifiers = vec![key.nullifier()];
+    let (output, range_proof) = range_proven_output(5);
+    let outputs = vec![output];
+    let recipient = Pubkey::new_unique();
+    let pay = |proof: Vec<u8>| -> Vec<Instruction> {
+        let message = ring::signing_message(&nullifiers, &outputs, &proof);
+        let payment = instruction::privacy_payment(
+            &program_id,
+            &user,
+            &protocol_state,
+            &recipient,
+            &[point],
+            &[pda::find_point_address(&outputs[0], &program_id).0],
+            &fpp_verifier::id(),
+            merkle_root,
+            nullifiers.clone(),
+            outputs.clone(),
+            proof,
+            common::sign_spend(&message, &[&key]),
+            -1,
+            vec![vec![7u8; 80]],
+            None,
+        )
+        .unwrap();
+        vec![range_proof.clone(), payment]
+    };
+    let invalid_proof = TransactionError::InstructionError(
+        1,
+        InstructionError::Custom(FPPError::InvalidProof as u32),
+    );
+    
+    // Bytes that aren't a proof, and a proof of other outputs, are both refused
+    let err = harness.process(&pay(vec![1u8; 16]), &[]).await.unwrap_err().unwrap();
+    assert_eq!(err, invalid_proof);
+    let other_outputs = circuit_key.payment_binding(&nullifiers, &[[9u8; 32]]);
+    let proof = prove(&proving_key, &merkle_root, other_outputs).try_to_vec().unwrap();
+    let err = harness.process(&pay(proof), &[]).await.unwrap_err().unwrap();
+    assert_eq!(err, invalid_proof);
+    
+    let binding = circuit_key.payment_binding(&nullifiers, &outputs);
+    let proof = prove(&proving_key, &merkle_root, binding).try_to_vec().unwrap();
+    // The output point must be the one at its commitment's address
+    let mut misplaced = pay(proof.clone());
+    misplaced[1] = common::with_account(misplaced[1].clone(), 5, Pubkey::new_unique());
+    let err = harness.process(&misplaced, &[]).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(FPPError::InvalidAccount as u32)
+        )
+    );
+    harness.process(&pay(proof), &[]).await.unwrap();
+    let banks = &mut harness.context.banks_client;
+    let (nullifier, _) = pda::find_nullifier(&nullifiers[0], &program_id);
+    assert!(banks.get_account(nullifier).await.unwrap().is_some());
+    let (note, _) = EncryptedNote::find_address(&outputs[0], &program_id);
+    assert!(banks.get_account(note).await.unwrap().is_some());
+    
+    // The recipient holds what was spent, and can find it in the tree
+    let (output, _) = pda::find_point_address(&outputs[0], &program_id);
+    let output = banks.get_account(output).await.unwrap().unwrap();
+    let output = FloatingPoint::try_from_slice(&output.data).unwrap();
+    assert_eq!((output.creator, output.mint, output.mass), (recipient, mint, 1));
+    assert!(output.is_active);
+    let (tree, _) = CommitmentTree::find_address(&program_id);
+    let tree = banks.get_account(tree).await.unwrap().unwrap();
+    assert_eq!(CommitmentTree::try_from_slice(&tree.data).unwrap().next_index, 1);
+}
+
+#[tokio::test]
+async fn payments_spend_a_single_mint() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let (protocol_state, _) = pda::find_protocol_state(&program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let other_mint = common::add_mint(&mut program_test, 6);
+    let keys = [SpendKey::new(1), SpendKey::new(2)];
+    let points = [
+        add_point(&mut program_test, &program_id, &mint, &keys[0]),
+        add_point(&mut program_test, &program_id, &other_mint, &keys[1]),
+    ];
+    let mut harness = TestHarness::start(program_test, program_id).await;
+    let user = harness.payer();
+    register_payment_circuit(&mut harness, &mint).await;
+    
+    // Its one output couldn't say how much of each mint it holds
+    let nullifiers: Vec<[u8; 32]> = keys.iter().map(SpendKey::nullifier).collect();
+    let (output, range_proof) = range_proven_output(5);
+    let message = ring::signing_message(&nullifiers, &[output], &[]);
+    let payment = instruction::privacy_payment(
+        &program_id,
+        &user,
+        &protocol_state,
+        &Pubkey::new_unique(),
+        &points,
+        &[pda::find_point_address(&output, &program_id).0],
+        &fpp_verifier::id(),
+        CommitmentTree::new().unwrap().current_root(),
+        nullifiers,
+        vec![output],
+        Vec::new(),
+        common::sign_spend(&message, &[&keys[0], &keys[1]]),
+        -1,
+        vec![vec![7u8; 80]],
+        None,
+    )
+    .unwrap();
+    let err = harness.process(&[range_proof, payment], &[]).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(FPPError::MassNotConserved as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn one_bad_proof_fails_a_batch_of_payments() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let (protocol_state, _) = pda::find_protocol_state(&program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let keys = [SpendKey::new(1), SpendKey::new(2)];
+    let points: Vec<Pubkey> = keys
+        .iter()
+        .map(|key| add_point(&mut program_test, &program_id, &mint, key))
+        .collect();
+    let mut harness = TestHarness::start(program_test, program_id).await;
+    let user = harness.payer();
+    let (proving_key, circuit_key) = register_payment_circuit(&mut harness, &mint).await;
+    
+    let merkle_root = CommitmentTree::new().unwrap().current_root();
+    let (outputs, range_proofs): (Vec<[u8; 32]>, Vec<Instruction>) =
+        [5, 6].into_iter().map(range_proven_output).unzip();
+    // Range proofs go first, each payment pointing back at its own
+    let payment = |index: usize, proof: Groth16Proof| {
+        let nullifiers = vec![keys[index].nullifier()];
+        let outputs = vec![outputs[index]];
+        let message = ring::signing_message(&nullifiers, &outputs, &proof.try_to_vec().unwrap());
+        BatchedPayment {
+            merkle_root,
+            input_nullifiers: nullifiers,
+            output_commitments: outputs,
+            proof,
+            ring_signature: common::sign_spend(&message, &[&keys[index]]),
+            range_proof_offset: index as i8 - 2,
+            encrypted_notes: vec![vec![7u8; 80]],
+        }
+    };
+    let recipients = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let batch = |payments: Vec<BatchedPayment>| {
+        let rings: Vec<Vec<Pubkey>> =
+            points[..payments.len()].iter().map(|point| vec![*point]).collect();
+        let mut instructions = range_proofs[..payments.len()].to_vec();
+        instructions.push(
+            instruction::batch_privacy_payment(
+                &program_id,
+                &user,
+                &protocol_state,
+                &recipients[..payments.len()],
+                &rings,
+                payments,
+                None,
+            )
+            .unwrap(),
+        );
+        instructions
+    };
+    let proof_of = |index: usize, outputs: &[[u8; 32]]| {
+        let binding = circuit_key.payment_binding(&[keys[index].nullifier()], outputs);
+        prove(&proving_key, &merkle_root, binding)
+    };
+    
+    let err = harness.process(&batch(Vec::new()), &[]).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(FPPError::InvalidInstruction as u32)
+        )
+    );
+    
+    // The second payment's proof is for other outputs, so neither payment goes through
+    let payments = vec![
+        payment(0, proof_of(0, &[outputs[0]])),
+        payment(1, proof_of(1, &[[9u8; 32]])),
+    ];
+    let err = harness.process(&batch(payments), &[]).await.unwrap_err().unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            2,
+            InstructionError::Custom(FPPError::InvalidProof as u32)
+        )
+    );
+    let (first_nullifier, _) = pda::find_nullifier(&keys[0].nullifier(), &program_id);
+    let banks = &mut harness.context.banks_client;
+    assert!(banks.get_account(first_nullifier).await.unwrap().is_none());
+    
+    let payments = vec![
+        payment(0, proof_of(0, &[outputs[0]])),
+        payment(1, proof_of(1, &[outputs[1]])),
+    ];
+    harness.process(&batch(payments), &[]).await.unwrap();
+    let banks = &mut harness.context.banks_client;
+    for ((key, output), recipient) in keys.iter().zip(&outputs).zip(&recipients) {
+        let (nullifier, _) = pda::find_nullifier(&key.nullifier(), &program_id);
+        assert!(banks.get_account(nullifier).await.unwrap().is_some());
+        let (note, _) = EncryptedNote::find_address(output, &program_id);
+        assert!(banks.get_account(note).await.unwrap().is_some());
+        // Each recipient holds the point its payment spent
+        let (point, _) = pda::find_point_address(output, &program_id);
+        let point = banks.get_account(point).await.unwrap().unwrap();
+        let point = FloatingPoint::try_from_slice(&point.data).unwrap();
+        assert_eq!((point.creator, point.mass), (*recipient, 1));
+    }
+}
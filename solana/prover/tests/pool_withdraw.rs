@@ -0,0 +1,200 @@
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+use ark_bn254::Fr;
+use borsh::{BorshDeserialize, BorshSerialize};
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    crypto::groth16,
+    error::FPPError,
+    instruction, pda,
+    state::{CommitmentTree, PoolConfig, PoolState, ProtocolState, RootHistory},
+};
+use fpp_prover::{circuit, encoding, keys, SpendCircuit, SpendInput};
+use rand::{rngs::StdRng, SeedableRng};
+use solana_sdk::{
+    account::Account,
+    hash::hash,
+    instruction::InstructionError,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+
+#[tokio::test]
+async fn a_proven_pool_withdrawal_pays_the_bound_recipient_once() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let (protocol_state, _) = pda::find_protocol_state(&program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token = common::add_token_account(&mut program_test, &mint, &treasury, 0);
+    let denomination = 100_000_000;
+    let depositor = Keypair::new();
+    let depositor_token =
+        common::add_token_account(&mut program_test, &mint, &depositor.pubkey(), 2 * denomination);
+    let relayer = Keypair::new();
+    program_test.add_account(
+        relayer.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            ..Account::default()
+        },
+    );
+    let relayer_token = common::add_token_account(&mut program_test, &mint, &relayer.pubkey(), 0);
+    let recipient_token =
+        common::add_token_account(&mut program_test, &mint, &Pubkey::new_unique(), 0);
+    let stranger_token =
+        common::add_token_account(&mut program_test, &mint, &Pubkey::new_unique(), 0);
+    let mut harness = TestHarness::start(program_test, program_id).await;
+    let user = harness.payer();
+    
+    let mut rng = StdRng::seed_from_u64(5);
+    let (proving_key, verifying_key) = keys::setup(1, 0, &mut rng).unwrap();
+    let verifying_key = encoding::verifying_key(&verifying_key).unwrap();
+    let setup = [
+        instruction::initialize(
+            &program_id,
+            &user,
+            &protocol_state,
+            &mint,
+            10,
+            10,
+            RootHistory::DEFAULT_SIZE,
+        )
+        .unwrap(),
+        instruction::register_circuit(
+            &program_id,
+            &user,
+            &protocol_state,
+            &user,
+            1,
+            20,
+            1,
+            1,
+            hash(&verifying_key.try_to_vec().unwrap()).to_bytes(),
+        )
+        .unwrap(),
+        instruction::update_verifying_key(
+            &program_id,
+            &user,
+            &protocol_state,
+            &user,
+            1,
+            1,
+            verifying_key,
+        )
+        .unwrap(),
+        instruction::create_pool(&program_id, &user, &protocol_state, &mint, denomination, 1)
+            .unwrap(),
+    ];
+    harness.process(&setup, &[]).await.unwrap();
+    let (pool, _) = PoolConfig::find_address(&mint, denomination, &program_id);
+    let open = instruction::open_pool(&program_id, &user, &pool, 1).unwrap();
+    harness.process(&[open], &[]).await.unwrap();
+    
+    // Two deposits, so the spent note hides among others
+    let secrets = [Fr::from(21u64), Fr::from(22u64)];
+    let leaves: Vec<Fr> = secrets
+        .iter()
+        .map(|secret| circuit::note_commitment(secret, denomination).unwrap())
+        .collect();
+    for leaf in &leaves {
+        let deposit = instruction::pool_deposit(
+            &program_id,
+            &depositor.pubkey(),
+            &depositor_token,
+            &treasury_token,
+            &protocol_state,
+            &pool,
+            &mint,
+            &spl_token::id(),
+            encoding::field_element(leaf),
+        )
+        .unwrap();
+        harness.process(&[deposit], &[&depositor]).await.unwrap();
+    }
+    let (tree_address, _) = PoolState::find_tree_address(&pool, &program_id);
+    let account = harness.context.banks_client.get_account(tree_address).await.unwrap().unwrap();
+    let merkle_root = CommitmentTree::try_from_slice(&account.data).unwrap().root;
+    
+    let relayer_fee = 1_000_000;
+    let context =
+        PoolState::withdrawal_context(&pool, 1, &recipient_token, &relayer.pubkey(), relayer_fee);
+    let spend_circuit = SpendCircuit {
+        merkle_root: encoding::parse_field_element(&merkle_root).unwrap(),
+        context: encoding::parse_field_element(&groth16::hash_to_field(&context)).unwrap(),
+        inputs: vec![SpendInput {
+            secret: secrets[1],
+            mass: denomination,
+            leaf_index: 1,
+            path: circuit::merkle_path(&leaves, 1).unwrap(),
+        }],
+        outputs: Vec::new(),
+        exit_mass: denomination,
+    };
+    let spend = fpp_prover::prove(&proving_key, spend_circuit, &mut rng).unwrap();
+    assert_eq!(spend.public_inputs[0], merkle_root);
+    
+    let withdraw = |recipient_token: &Pubkey, merkle_root| {
+        instruction::pool_withdraw(
+            &program_id,
+            &relayer.pubkey(),
+            recipient_token,
+            &relayer_token,
+            &treasury_token,
+            &protocol_state,
+            &pool,
+            1,
+            &mint,
+            &spl_token::id(),
+            merkle_root,
+            spend.nullifiers[0],
+            relayer_fee,
+            spend.proof.clone(),
+        )
+        .unwrap()
+    };
+    let custom = |error: FPPError| {
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+    };
+    
+    let err = harness
+        .process(&[withdraw(&recipient_token, [9u8; 32])], &[&relayer])
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(FPPError::UnknownMerkleRoot));
+    
+    // The relayer can't redirect the payout
+    let err = harness
+        .process(&[withdraw(&stranger_token, merkle_root)], &[&relayer])
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(FPPError::InvalidProof));
+    
+    let treasury_before = harness.token_balance(&treasury_token).await;
+    harness
+        .process(&[withdraw(&recipient_token, merkle_root)], &[&relayer])
+        .await
+        .unwrap();
+    let fee = denomination / 1000;
+    assert_eq!(harness.token_balance(&recipient_token).await, denomination - fee - relayer_fee);
+    assert_eq!(harness.token_balance(&relayer_token).await, relayer_fee);
+    assert_eq!(
+        harness.token_balance(&treasury_token).await,
+        treasury_before - denomination + fee
+    );
+    let (state_address, _) = PoolState::find_address(&pool, &program_id);
+    let account = harness.context.banks_client.get_account(state_address).await.unwrap().unwrap();
+    assert_eq!(PoolState::try_from_slice(&account.data).unwrap().withdrawal_count, 1);
+    
+    let err = harness
+        .process(&[withdraw(&recipient_token, merkle_root)], &[&relayer])
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom(FPPError::NullifierAlreadyUsed));
+}
@@ -0,0 +1,156 @@
+use ark_bn254::Fr;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use floating_point_protocol_solana::{
+    crypto::groth16,
+    state::{CommitmentTree, PoolRoute, PoolState},
+};
+use fpp_prover::{
+    circuit, encoding, keys,
+    witness::{SpendWitness, WitnessInput},
+    SpendCircuit, SpendInput, SpendOutput,
+};
+use rand::{rngs::StdRng, SeedableRng};
+use solana_program::pubkey::Pubkey;
+
+const DENOMINATION: u64 = 1_000_000;
+
+/// Root of the program's tree after inserting `leaves`
+fn program_root(leaves: &[Fr]) -> [u8; 32] {
+    let mut tree = CommitmentTree::new().unwrap();
+    for leaf in leaves {
+        tree.insert(encoding::field_element(leaf)).unwrap();
+    }
+    tree.current_root()
+}
+
+fn notes(secrets: &[u64], mass: u64) -> Vec<Fr> {
+    secrets
+        .iter()
+        .map(|secret| circuit::note_commitment(&Fr::from(*secret), mass).unwrap())
+        .collect()
+}
+
+#[test]
+fn withdrawal_proofs_verify_against_the_programs_binding() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let (proving_key, verifying_key) = keys::setup(1, 0, &mut rng).unwrap();
+    let vk = encoding::verifying_key(&verifying_key).unwrap();
+    let leaves = notes(&[1, 2, 3], DENOMINATION);
+    let merkle_root = program_root(&leaves);
+    let pool = Pubkey::new_unique();
+    let recipient = Pubkey::new_unique();
+    let relayer = Pubkey::new_unique();
+    
+    let witness = SpendWitness {
+        merkle_root,
+        context: PoolState::withdrawal_context(&pool, 1, &recipient, &relayer, 500),
+        inputs: vec![WitnessInput {
+            secret: encoding::field_element(&Fr::from(2u64)),
+            mass: DENOMINATION,
+            leaf_index: 1,
+            path: circuit::merkle_path(&leaves, 1)
+                .unwrap()
+                .iter()
+                .map(encoding::field_element)
+                .collect(),
+        }],
+        outputs: Vec::new(),
+        exit_mass: DENOMINATION,
+    };
+    let circuit = witness.into_circuit().unwrap();
+    let spend = fpp_prover::prove(&proving_key, circuit, &mut rng).unwrap();
+    assert!(fpp_prover::verify(&verifying_key, &spend).unwrap());
+    
+    let nullifier = spend.nullifiers[0];
+    let binding = |recipient: &Pubkey, denomination: u64| {
+        let binding = PoolState::withdrawal_binding(
+            &pool,
+            1,
+            denomination,
+            &nullifier,
+            recipient,
+            &relayer,
+            500,
+        );
+        [merkle_root, binding.unwrap()]
+    };
+    assert_eq!(spend.public_inputs, binding(&recipient, DENOMINATION));
+    assert!(groth16::verify(&vk, &spend.proof, &binding(&recipient, DENOMINATION)));
+    
+    // The submitter can change neither the payee nor the amount
+    let stranger = Pubkey::new_unique();
+    assert!(!groth16::verify(&vk, &spend.proof, &binding(&stranger, DENOMINATION)));
+    assert!(!groth16::verify(&vk, &spend.proof, &binding(&recipient, DENOMINATION * 2)));
+}
+
+#[test]
+fn a_withdrawal_cant_pay_out_more_than_its_note() {
+    let leaves = notes(&[1], DENOMINATION);
+    let circuit = SpendCircuit {
+        merkle_root: encoding::parse_field_element(&program_root(&leaves)).unwrap(),
+        context: Fr::from(9u64),
+        inputs: vec![SpendInput {
+            secret: Fr::from(1u64),
+            mass: DENOMINATION,
+            leaf_index: 0,
+            path: circuit::merkle_path(&leaves, 0).unwrap(),
+        }],
+        outputs: Vec::new(),
+        exit_mass: DENOMINATION,
+    };
+    let cs = ConstraintSystem::new_ref();
+    circuit.clone().generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    
+    let cs = ConstraintSystem::new_ref();
+    SpendCircuit {
+        exit_mass: DENOMINATION * 2,
+        ..circuit
+    }
+    .generate_constraints(cs.clone())
+    .unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn transfer_proofs_verify_against_the_programs_binding() {
+    let mut rng = StdRng::seed_from_u64(11);
+    let (proving_key, verifying_key) = keys::setup(2, 2, &mut rng).unwrap();
+    let vk = encoding::verifying_key(&verifying_key).unwrap();
+    let leaves = notes(&[1, 2, 3, 4], DENOMINATION);
+    let merkle_root = program_root(&leaves);
+    let route = Pubkey::new_unique();
+    let context = PoolRoute::transfer_context(&route, 3);
+    
+    let input = |index: u64| SpendInput {
+        secret: Fr::from(index + 1),
+        mass: DENOMINATION,
+        leaf_index: index,
+        path: circuit::merkle_path(&leaves, index).unwrap(),
+    };
+    let output = |secret: u64| SpendOutput {
+        secret: Fr::from(secret),
+        mass: DENOMINATION,
+    };
+    let circuit = SpendCircuit {
+        merkle_root: encoding::parse_field_element(&merkle_root).unwrap(),
+        context: encoding::parse_field_element(&groth16::hash_to_field(&context)).unwrap(),
+        inputs: vec![input(0), input(3)],
+        outputs: vec![output(10), output(11)],
+        exit_mass: 0,
+    };
+    let spend = fpp_prover::prove(&proving_key, circuit, &mut rng).unwrap();
+    
+    let binding = |version: u16, commitments: &[[u8; 32]]| {
+        let binding = PoolRoute::transfer_binding(&route, version, &spend.nullifiers, commitments);
+        [merkle_root, binding.unwrap()]
+    };
+    assert_eq!(spend.public_inputs, binding(3, &spend.output_commitments));
+    assert!(groth16::verify(&vk, &spend.proof, &binding(3, &spend.output_commitments)));
+    
+    // Outputs swapped for others, or a proof for a replaced key, don't verify
+    let mut commitments = spend.output_commitments.clone();
+    commitments[1] = encoding::field_element(&notes(&[12], DENOMINATION)[0]);
+    assert!(!groth16::verify(&vk, &spend.proof, &binding(3, &commitments)));
+    assert!(!groth16::verify(&vk, &spend.proof, &binding(4, &spend.output_commitments)));
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "wasm")]
+
+use ark_bn254::Fr;
+use borsh::{BorshDeserialize, BorshSerialize};
+use floating_point_protocol_solana::state::CommitmentTree;
+use fpp_prover::{
+    circuit, encoding, keys,
+    witness::{SpendWitness, WitnessInput, WitnessOutput},
+    Spend,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+const DENOMINATION: u64 = 1_000_000;
+
+// Errors become `JsError`s, which only exist on wasm targets, so only the
+// success path runs natively; the failures are the library's own and tested there
+#[test]
+fn the_browser_entry_point_proves_borsh_witnesses() {
+    let mut rng = StdRng::seed_from_u64(5);
+    let (proving_key, verifying_key) = keys::setup(1, 1, &mut rng).unwrap();
+    let mut key_bytes = Vec::new();
+    keys::write_proving_key(&proving_key, &mut key_bytes).unwrap();
+    
+    let leaf = circuit::note_commitment(&Fr::from(1u64), DENOMINATION).unwrap();
+    let mut tree = CommitmentTree::new().unwrap();
+    tree.insert(encoding::field_element(&leaf)).unwrap();
+    let witness = SpendWitness {
+        merkle_root: tree.current_root(),
+        context: [6u8; 32],
+        inputs: vec![WitnessInput {
+            secret: encoding::field_element(&Fr::from(1u64)),
+            mass: DENOMINATION,
+            leaf_index: 0,
+            path: circuit::merkle_path(&[leaf], 0)
+                .unwrap()
+                .iter()
+                .map(encoding::field_element)
+                .collect(),
+        }],
+        outputs: vec![WitnessOutput {
+            secret: encoding::field_element(&Fr::from(2u64)),
+            mass: DENOMINATION,
+        }],
+        exit_mass: 0,
+    };
+    
+    let spend = fpp_prover::wasm::prove_spend(&key_bytes, &witness.try_to_vec().unwrap())
+        .unwrap_or_else(|_| panic!("proving failed"));
+    let spend = Spend::try_from_slice(&spend).unwrap();
+    assert!(fpp_prover::verify(&verifying_key, &spend).unwrap());
+    let commitment = circuit::note_commitment(&Fr::from(2u64), DENOMINATION).unwrap();
+    assert_eq!(spend.output_commitments, vec![encoding::field_element(&commitment)]);
+}
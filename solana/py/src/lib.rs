@@ -0,0 +1,355 @@
+//! Python bindings over `fpp-client`, for analytics and bots.
+//!
+//! Addresses cross the boundary as base58 `str` and hashes, secrets and
+//! ciphertexts as `bytes`. Builders return an `Instruction` whose fields map
+//! one to one onto `solders.instruction.Instruction`, so callers sign and send
+//! with whichever Solana library they already use. Invalid input raises
+//! `ValueError` with the `ClientError` message.
+
+use std::str::FromStr;
+
+use borsh::BorshDeserialize;
+use floating_point_protocol_solana::{pda, state::POINT_VALUE};
+use fpp_client::{ClientError, MintAccounts, SpendProof};
+use fpp_wallet::{note::Note, spend, viewing};
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes};
+use solana_program::{instruction, pubkey::Pubkey};
+
+fn value_error(e: impl ToString) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+fn pubkey(address: &str) -> PyResult<Pubkey> {
+    Pubkey::from_str(address).map_err(|_| value_error(format!("invalid address {}", address)))
+}
+
+fn hash(bytes: &[u8]) -> PyResult<[u8; 32]> {
+    bytes
+        .try_into()
+        .map_err(|_| value_error(format!("expected 32 bytes, got {}", bytes.len())))
+}
+
+fn client_error(e: ClientError) -> PyErr {
+    value_error(e)
+}
+
+fn address((address, bump): (Pubkey, u8)) -> (String, u8) {
+    (address.to_string(), bump)
+}
+
+/// An account an instruction reads or writes
+#[pyclass(module = "fpp", get_all)]
+#[derive(Clone)]
+pub struct AccountMeta {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[pymethods]
+impl AccountMeta {
+    fn __repr__(&self) -> String {
+        format!(
+            "AccountMeta(pubkey={}, is_signer={}, is_writable={})",
+            self.pubkey, self.is_signer, self.is_writable
+        )
+    }
+}
+
+/// An unsigned instruction, ready to add to a transaction
+#[pyclass(module = "fpp")]
+pub struct Instruction {
+    #[pyo3(get)]
+    pub program_id: String,
+    #[pyo3(get)]
+    pub accounts: Vec<AccountMeta>,
+    data: Vec<u8>,
+}
+
+#[pymethods]
+impl Instruction {
+    #[getter]
+    fn data<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, &self.data)
+    }
+    
+    fn __repr__(&self) -> String {
+        format!(
+            "Instruction(program_id={}, accounts={}, data={} bytes)",
+            self.program_id,
+            self.accounts.len(),
+            self.data.len()
+        )
+    }
+}
+
+impl From<instruction::Instruction> for Instruction {
+    fn from(ix: instruction::Instruction) -> Self {
+        Self {
+            program_id: ix.program_id.to_string(),
+            accounts: ix
+                .accounts
+                .into_iter()
+                .map(|meta| AccountMeta {
+                    pubkey: meta.pubkey.to_string(),
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: ix.data,
+        }
+    }
+}
+
+/// Addresses and instruction builders for one protocol deployment
+#[pyclass(module = "fpp")]
+pub struct Protocol {
+    inner: fpp_client::Protocol,
+}
+
+#[pymethods]
+impl Protocol {
+    /// `token_program` defaults to SPL Token
+    #[new]
+    #[pyo3(signature = (program_id, usdt_mint, token_program = None))]
+    fn new(program_id: &str, usdt_mint: &str, token_program: Option<&str>) -> PyResult<Self> {
+        let usdt = MintAccounts {
+            mint: pubkey(usdt_mint)?,
+            token_program: token_program.map(pubkey).transpose()?.unwrap_or(spl_token::id()),
+        };
+        Ok(Self {
+            inner: fpp_client::Protocol::new(pubkey(program_id)?, usdt),
+        })
+    }
+    
+    #[getter]
+    fn program_id(&self) -> String {
+        self.inner.program_id.to_string()
+    }
+    
+    #[getter]
+    fn protocol_state(&self) -> String {
+        self.inner.protocol_state.to_string()
+    }
+    
+    /// The treasury authority PDA
+    fn treasury(&self) -> String {
+        self.inner.treasury().to_string()
+    }
+    
+    /// Treasury token account for `mint`, the primary mint if omitted
+    #[pyo3(signature = (mint = None, token_program = None))]
+    fn treasury_token(&self, mint: Option<&str>, token_program: Option<&str>) -> PyResult<String> {
+        let mint = self.mint(mint, token_program)?;
+        Ok(self.inner.treasury_token(&mint).to_string())
+    }
+    
+    /// Address of the withdrawal request `user` opened under `nonce`
+    fn withdrawal_request(&self, user: &str, nonce: u64) -> PyResult<String> {
+        Ok(self.inner.withdrawal_request(&pubkey(user)?, nonce).to_string())
+    }
+    
+    /// Address of the statistics account for `epoch`
+    fn epoch_stats(&self, epoch: u64) -> String {
+        self.inner.epoch_stats(epoch).to_string()
+    }
+    
+    /// Deposit one point of `denomination` per commitment
+    #[pyo3(signature = (
+        user,
+        denomination,
+        commitments,
+        epoch,
+        referrer = None,
+        mint = None,
+        token_program = None,
+        user_token = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn deposit(
+        &self,
+        user: &str,
+        denomination: u64,
+        commitments: Vec<Vec<u8>>,
+        epoch: u64,
+        referrer: Option<&str>,
+        mint: Option<&str>,
+        token_program: Option<&str>,
+        user_token: Option<&str>,
+    ) -> PyResult<Instruction> {
+        let commitments = commitments
+            .iter()
+            .map(|commitment| hash(commitment))
+            .collect::<PyResult<_>>()?;
+        let mut builder = self
+            .inner
+            .deposit(pubkey(user)?, denomination, commitments)
+            .mint(self.mint(mint, token_program)?)
+            .epoch(epoch);
+        if let Some(referrer) = referrer {
+            builder = builder.referrer(pubkey(referrer)?);
+        }
+        if let Some(user_token) = user_token {
+            builder = builder.user_token(pubkey(user_token)?);
+        }
+        builder.build().map(Into::into).map_err(client_error)
+    }
+    
+    /// Withdraw the points of `notes`, a list of `(commitment, secret)` pairs, into the
+    /// request `user` opens under `nonce`, signing for them with their secrets
+    fn request_withdrawal(
+        &self,
+        user: &str,
+        nonce: u64,
+        notes: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> PyResult<Instruction> {
+        let mut builder = self.inner.request_withdrawal(pubkey(user)?, nonce);
+        let mut inputs = Vec::with_capacity(notes.len());
+        for (commitment, secret) in &notes {
+            let note = Note::from_secret(hash(commitment)?, hash(secret)?, 0)
+                .ok_or_else(|| value_error("secret doesn't open its commitment"))?;
+            let (point, _) = pda::find_point_address(&note.commitment, &self.inner.program_id);
+            builder = builder.point(point);
+            inputs.push(note);
+        }
+        let (signature, _) =
+            spend::sign(&builder.spend_message(), &inputs, &[]).map_err(value_error)?;
+        builder.spend_signature(signature).build().map(Into::into).map_err(client_error)
+    }
+    
+    /// Spend a Borsh-encoded `fpp_client::SpendProof`
+    #[pyo3(signature = (
+        sender,
+        recipient,
+        spend_proof,
+        range_proof_offset,
+        verifier_program,
+        archived_tree = None,
+    ))]
+    fn privacy_payment(
+        &self,
+        sender: &str,
+        recipient: &str,
+        spend_proof: &[u8],
+        range_proof_offset: i8,
+        verifier_program: &str,
+        archived_tree: Option<u32>,
+    ) -> PyResult<Instruction> {
+        let spend = SpendProof::try_from_slice(spend_proof).map_err(value_error)?;
+        let mut builder = self
+            .inner
+            .privacy_payment(pubkey(sender)?, pubkey(recipient)?, spend.merkle_root)
+            .spend_proof(spend)
+            .range_proof_offset(range_proof_offset)
+            .verifier_program(pubkey(verifier_program)?);
+        if let Some(generation) = archived_tree {
+            builder = builder.archived_tree(generation);
+        }
+        builder.build().map(Into::into).map_err(client_error)
+    }
+}
+
+impl Protocol {
+    fn mint(&self, mint: Option<&str>, token_program: Option<&str>) -> PyResult<MintAccounts> {
+        Ok(MintAccounts {
+            mint: mint.map(pubkey).transpose()?.unwrap_or(self.inner.usdt.mint),
+            token_program: token_program
+                .map(pubkey)
+                .transpose()?
+                .unwrap_or(self.inner.usdt.token_program),
+        })
+    }
+}
+
+/// Opening of a note decrypted with a viewing key
+#[pyclass(module = "fpp")]
+pub struct NotePayload {
+    inner: viewing::NotePayload,
+}
+
+#[pymethods]
+impl NotePayload {
+    /// Secret key of the commitment; `None` for stealth outputs
+    #[getter]
+    fn secret<'py>(&self, py: Python<'py>) -> Option<&'py PyBytes> {
+        self.inner.secret.map(|secret| PyBytes::new(py, &secret))
+    }
+    
+    #[getter]
+    fn mass(&self) -> u64 {
+        self.inner.mass
+    }
+    
+    #[getter]
+    fn memo<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, &self.inner.memo)
+    }
+}
+
+/// Detects and decrypts incoming notes without being able to spend them
+#[pyclass(module = "fpp")]
+pub struct ViewingKey {
+    inner: viewing::ViewingKey,
+}
+
+#[pymethods]
+impl ViewingKey {
+    #[new]
+    fn new(wallet_secret: &[u8]) -> PyResult<Self> {
+        Ok(Self {
+            inner: viewing::ViewingKey::derive(&hash(wallet_secret)?),
+        })
+    }
+    
+    /// Public half, shared with payers
+    fn public_key<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, &self.inner.public_key().0)
+    }
+    
+    /// Decrypt the note of `commitment`, or `None` if it wasn't sent to this key
+    fn decrypt(&self, commitment: &[u8], ciphertext: &[u8]) -> PyResult<Option<NotePayload>> {
+        Ok(self
+            .inner
+            .decrypt(&hash(commitment)?, ciphertext)
+            .map(|inner| NotePayload { inner }))
+    }
+}
+
+/// Protocol state PDA, as `(address, bump)`
+#[pyfunction]
+fn find_protocol_state(program_id: &str) -> PyResult<(String, u8)> {
+    Ok(address(pda::find_protocol_state(&pubkey(program_id)?)))
+}
+
+/// Point account of `commitment`, as `(address, bump)`
+#[pyfunction]
+fn find_point_address(commitment: &[u8], program_id: &str) -> PyResult<(String, u8)> {
+    Ok(address(pda::find_point_address(&hash(commitment)?, &pubkey(program_id)?)))
+}
+
+/// Withdrawal request `user` opened under `nonce`, as `(address, bump)`
+#[pyfunction]
+fn find_withdrawal_request(user: &str, nonce: u64, program_id: &str) -> PyResult<(String, u8)> {
+    Ok(address(pda::find_withdrawal_request(&pubkey(user)?, nonce, &pubkey(program_id)?)))
+}
+
+/// Nullifier record of `nullifier`, as `(address, bump)`
+#[pyfunction]
+fn find_nullifier(nullifier: &[u8], program_id: &str) -> PyResult<(String, u8)> {
+    Ok(address(pda::find_nullifier(&hash(nullifier)?, &pubkey(program_id)?)))
+}
+
+#[pymodule]
+fn fpp(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("POINT_VALUE", POINT_VALUE)?;
+    m.add_class::<AccountMeta>()?;
+    m.add_class::<Instruction>()?;
+    m.add_class::<Protocol>()?;
+    m.add_class::<NotePayload>()?;
+    m.add_class::<ViewingKey>()?;
+    m.add_function(wrap_pyfunction!(find_protocol_state, m)?)?;
+    m.add_function(wrap_pyfunction!(find_point_address, m)?)?;
+    m.add_function(wrap_pyfunction!(find_withdrawal_request, m)?)?;
+    m.add_function(wrap_pyfunction!(find_nullifier, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,98 @@
+import struct
+
+import pytest
+
+import fpp
+
+ALPHABET = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz"
+
+
+def address(seed):
+    """Base58 of 32 bytes derived from `seed`, as Solana writes addresses"""
+    raw = bytes([seed]) * 32
+    number = int.from_bytes(raw, "big")
+    encoded = ""
+    while number:
+        number, digit = divmod(number, 58)
+        encoded = ALPHABET[digit] + encoded
+    return encoded
+
+
+def vec(items):
+    """Borsh `Vec` of already encoded items"""
+    return struct.pack("<I", len(items)) + b"".join(items)
+
+
+PROGRAM_ID = address(1)
+USDT = address(2)
+USER = address(3)
+
+
+@pytest.fixture
+def protocol():
+    return fpp.Protocol(PROGRAM_ID, USDT)
+
+
+def test_addresses_match_the_pda_helpers(protocol):
+    assert protocol.program_id == PROGRAM_ID
+    assert protocol.protocol_state == fpp.find_protocol_state(PROGRAM_ID)[0]
+    request, _ = fpp.find_withdrawal_request(USER, 5, PROGRAM_ID)
+    assert protocol.withdrawal_request(USER, 5) == request
+    assert protocol.withdrawal_request(USER, 5) != protocol.withdrawal_request(USER, 6)
+    assert protocol.treasury_token() != protocol.treasury_token(mint=address(4))
+    point, bump = fpp.find_point_address(bytes(32), PROGRAM_ID)
+    assert 0 <= bump <= 255 and point != fpp.find_nullifier(bytes(32), PROGRAM_ID)[0]
+
+
+def test_deposits_build_unsigned_instructions(protocol):
+    commitments = [bytes([1]) * 32, bytes([2]) * 32]
+    ix = protocol.deposit(USER, 10 * fpp.POINT_VALUE, commitments, 3)
+
+    assert ix.program_id == PROGRAM_ID
+    assert ix.accounts[0].pubkey == USER
+    assert ix.accounts[0].is_signer and ix.accounts[0].is_writable
+    assert ix.accounts[2].pubkey == protocol.treasury_token()
+    assert ix.accounts[4].pubkey == fpp.find_point_address(commitments[0], PROGRAM_ID)[0]
+    assert isinstance(ix.data, bytes)
+    # Borsh variant index, then amount and denomination
+    amount, denomination = struct.unpack_from("<QQ", ix.data, 1)
+    assert (amount, denomination) == (20 * fpp.POINT_VALUE, 10 * fpp.POINT_VALUE)
+
+
+def test_invalid_input_raises_value_error(protocol):
+    with pytest.raises(ValueError, match="no commitments"):
+        protocol.deposit(USER, fpp.POINT_VALUE, [], 0)
+    with pytest.raises(ValueError, match="expected 32 bytes, got 3"):
+        protocol.deposit(USER, fpp.POINT_VALUE, [b"abc"], 0)
+    with pytest.raises(ValueError, match="invalid address"):
+        protocol.deposit("not an address", fpp.POINT_VALUE, [bytes(32)], 0)
+    with pytest.raises(ValueError, match="doesn't open its commitment"):
+        protocol.request_withdrawal(USER, 0, [(bytes([1]) * 32, bytes([2]) * 32)])
+    with pytest.raises(ValueError):
+        protocol.privacy_payment(USER, address(5), b"\x00", -1, address(6))
+
+
+def test_payments_take_a_borsh_spend_proof(protocol):
+    ring = [bytes([7]) * 32, bytes([8]) * 32]
+    signature = vec([bytes([1]) * 32]) + bytes(32) + vec([bytes(32)] * 2)
+    spend_proof = (
+        bytes([5]) * 32
+        + vec(ring)
+        + signature
+        + vec([bytes([6]) * 32])
+        + vec([vec([b"\x01"])])
+        + vec([])
+    )
+    ix = protocol.privacy_payment(USER, address(5), spend_proof, -1, address(6))
+
+    assert [meta.pubkey for meta in ix.accounts[:3]] == [USER, protocol.protocol_state, address(5)]
+    assert ix.accounts[3].pubkey == fpp.find_point_address(ring[0], PROGRAM_ID)[0]
+    assert ix.accounts[4].pubkey == fpp.find_point_address(ring[1], PROGRAM_ID)[0]
+    assert "Instruction(program_id=" in repr(ix)
+
+
+def test_viewing_keys_ignore_notes_for_others():
+    key = fpp.ViewingKey(bytes([9]) * 32)
+    assert len(key.public_key()) == 32
+    assert key.public_key() != fpp.ViewingKey(bytes([10]) * 32).public_key()
+    assert key.decrypt(bytes(32), bytes(100)) is None
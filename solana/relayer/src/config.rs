@@ -0,0 +1,44 @@
+use std::{env, net::SocketAddr, path::PathBuf, str::FromStr, time::Duration};
+
+use solana_sdk::pubkey::Pubkey;
+
+/// Relayer configuration, read from `FPP_*` environment variables
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub bind: SocketAddr,
+    pub rpc_url: String,
+    pub program_id: Pubkey,
+    /// Keypair that signs and pays for every relayed request and completion
+    pub keypair: PathBuf,
+    /// Smallest fee accepted, in basis points of the withdrawn amount
+    pub min_fee_rate: u16,
+    /// Smallest fee accepted in token base units, whatever the amount; should cover the
+    /// rent and transaction fees the relayer pays
+    pub min_fee: u64,
+    /// How often submitted relays are checked for confirmation and completion
+    pub poll_interval: Duration,
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self, String> {
+        let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
+        Ok(Self {
+            bind: parse_var("FPP_BIND", "0.0.0.0:8090")?,
+            rpc_url: env::var("FPP_RPC_URL")
+                .unwrap_or_else(|_| "https://api.devnet.solana.com".to_string()),
+            program_id: parse_var("FPP_PROGRAM_ID", "11111111111111111111111111111111")?,
+            keypair: env::var("FPP_RELAYER_KEYPAIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from(home).join(".config/solana/id.json")),
+            min_fee_rate: parse_var("FPP_MIN_FEE_RATE", "10")?,
+            min_fee: parse_var("FPP_MIN_FEE", "0")?,
+            poll_interval: Duration::from_secs(parse_var("FPP_POLL_INTERVAL_SECS", "5")?),
+        })
+    }
+}
+
+fn parse_var<T: FromStr>(name: &str, default: &str) -> Result<T, String> {
+    let raw = env::var(name).unwrap_or_else(|_| default.to_string());
+    raw.parse()
+        .map_err(|_| format!("invalid value for {}: {}", name, raw))
+}
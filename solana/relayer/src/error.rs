@@ -0,0 +1,48 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error("Bad Request: {0}")]
+    BadRequest(String),
+    
+    #[error("Invalid Spend Signature")]
+    InvalidSignature,
+    
+    #[error("Insufficient Relayer Fee: {0}")]
+    InsufficientFee(String),
+    
+    #[error("Withdrawal Already Relayed")]
+    AlreadyRelayed,
+    
+    #[error("Relay Not Found")]
+    RelayNotFound,
+    
+    #[error("Protocol Not Initialized")]
+    ProtocolNotInitialized,
+    
+    #[error("RPC Error: {0}")]
+    Rpc(String),
+    
+    #[error("Internal Error: {0}")]
+    Internal(String),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            ApiError::BadRequest(_) | ApiError::InvalidSignature => StatusCode::BAD_REQUEST,
+            ApiError::InsufficientFee(_) => StatusCode::PAYMENT_REQUIRED,
+            ApiError::AlreadyRelayed => StatusCode::CONFLICT,
+            ApiError::RelayNotFound => StatusCode::NOT_FOUND,
+            ApiError::ProtocolNotInitialized | ApiError::Rpc(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(json!({ "error": self.to_string() }))).into_response()
+    }
+}
@@ -0,0 +1,123 @@
+//! Withdrawal relayer for FPP.
+//!
+//! Recipients `POST` a relay bundle (points, nullifiers and a spend signature
+//! binding them to the recipient, this relayer and its fee). The relayer checks
+//! the bundle off chain, submits `RelayWithdrawal` paying every fee and rent
+//! itself, then follows the request and submits `CompleteRelayedWithdrawal` once
+//! the delay has passed, which pays the recipient and the relayer's fee.
+
+mod config;
+mod error;
+mod relay;
+mod store;
+mod validate;
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::{read_keypair_file, Keypair, Signer},
+};
+use tower_http::cors::CorsLayer;
+
+use crate::{
+    config::Config,
+    error::ApiError,
+    store::{Relay, RelayStore},
+    validate::RelayBundle,
+};
+
+struct AppState {
+    config: Config,
+    rpc: RpcClient,
+    relayer: Keypair,
+    relays: RelayStore,
+}
+
+/// What a recipient needs to sign a relay bundle for this relayer
+#[derive(Serialize)]
+struct InfoResponse {
+    /// The relayer address the spend signature must bind
+    relayer: String,
+    min_fee_rate: u16,
+    min_fee: u64,
+}
+
+async fn info(State(state): State<Arc<AppState>>) -> Json<InfoResponse> {
+    Json(InfoResponse {
+        relayer: state.relayer.pubkey().to_string(),
+        min_fee_rate: state.config.min_fee_rate,
+        min_fee: state.config.min_fee,
+    })
+}
+
+async fn submit_relay(
+    State(state): State<Arc<AppState>>,
+    Json(bundle): Json<RelayBundle>,
+) -> Result<Json<Relay>, ApiError> {
+    let relay = validate::validate_relay(
+        &state.config,
+        &state.rpc,
+        &state.relayer.pubkey(),
+        &bundle,
+    )
+    .await?;
+    if state.relays.is_active(&relay.request.to_string()) {
+        return Err(ApiError::AlreadyRelayed);
+    }
+    
+    let tracked = relay::submit(&state, &relay).await?;
+    state
+        .relays
+        .insert(tracked.clone())
+        .ok_or_else(|| ApiError::Internal("relay store is full".into()))?;
+    Ok(Json(tracked))
+}
+
+async fn relay_status(
+    State(state): State<Arc<AppState>>,
+    Path(request): Path<String>,
+) -> Result<Json<Relay>, ApiError> {
+    state.relays.get(&request).map(Json).ok_or(ApiError::RelayNotFound)
+}
+
+#[tokio::main]
+async fn main() {
+    let config = Config::from_env().unwrap_or_else(|e| {
+        eprintln!("configuration error: {}", e);
+        std::process::exit(1);
+    });
+    let relayer = read_keypair_file(&config.keypair).unwrap_or_else(|e| {
+        eprintln!("cannot read keypair {}: {}", config.keypair.display(), e);
+        std::process::exit(1);
+    });
+    let bind = config.bind;
+    let state = Arc::new(AppState {
+        rpc: RpcClient::new_with_commitment(config.rpc_url.clone(), CommitmentConfig::confirmed()),
+        config,
+        relayer,
+        relays: RelayStore::default(),
+    });
+    
+    tokio::spawn(relay::track(state.clone()));
+    
+    let app = Router::new()
+        .route("/relayer", get(info))
+        .route("/relays", post(submit_relay))
+        .route("/relays/:request", get(relay_status))
+        .layer(CorsLayer::permissive())
+        .with_state(state.clone());
+    
+    println!("FPP relayer {} listening on {}", state.relayer.pubkey(), bind);
+    axum::Server::bind(&bind)
+        .serve(app.into_make_service())
+        .await
+        .expect("server error");
+}
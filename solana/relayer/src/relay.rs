@@ -0,0 +1,246 @@
+//! Submitting relays and following them to completion.
+//!
+//! The relayer is only paid when `CompleteRelayedWithdrawal` runs, so after a
+//! `RelayWithdrawal` confirms the tracker waits out the withdrawal delay and
+//! completes the request itself, creating the recipient's and its own token
+//! accounts if they don't exist yet.
+
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use borsh::BorshDeserialize;
+use floating_point_protocol_solana::{
+    instruction, pda,
+    state::{ProtocolState, WithdrawalRequest},
+};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+
+use crate::{
+    error::ApiError,
+    store::{Relay, RelayStatus},
+    validate::{parse_pubkey, ValidRelay},
+    AppState,
+};
+
+/// Compute units requested for `RelayWithdrawal`, enough for a spend signature and
+/// nullifiers over `validate::MAX_RELAY_POINTS` points
+const RELAY_COMPUTE_UNITS: u32 = 600_000;
+
+/// Send `RelayWithdrawal` for a validated bundle without waiting for confirmation
+pub async fn submit(state: &AppState, relay: &ValidRelay) -> Result<Relay, ApiError> {
+    let program_id = &state.config.program_id;
+    let ix = instruction::relay_withdrawal(
+        program_id,
+        &state.relayer.pubkey(),
+        &pda::find_protocol_state(program_id).0,
+        relay.point_ids.clone(),
+        relay.nullifiers.clone(),
+        relay.nonce,
+        relay.recipient,
+        relay.relayer_fee,
+        relay.spend_signature.clone(),
+    )
+    .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let budget = ComputeBudgetInstruction::set_compute_unit_limit(RELAY_COMPUTE_UNITS);
+    
+    let (blockhash, last_valid_block_height) = state
+        .rpc
+        .get_latest_blockhash_with_commitment(state.rpc.commitment())
+        .await
+        .map_err(|e| ApiError::Rpc(e.to_string()))?;
+    let tx = Transaction::new_signed_with_payer(
+        &[budget, ix],
+        Some(&state.relayer.pubkey()),
+        &[&state.relayer],
+        blockhash,
+    );
+    // Preflight simulation catches whatever changed on chain since validation
+    let signature = state
+        .rpc
+        .send_transaction(&tx)
+        .await
+        .map_err(|e| ApiError::Rpc(e.to_string()))?;
+    
+    Ok(Relay {
+        request: relay.request.to_string(),
+        recipient: relay.recipient.to_string(),
+        relayer_fee: relay.relayer_fee,
+        status: RelayStatus::Submitted {
+            signature: signature.to_string(),
+            last_valid_block_height,
+        },
+    })
+}
+
+/// Advance every relay in progress once per poll interval, forever
+pub async fn track(state: Arc<AppState>) {
+    let mut interval = tokio::time::interval(state.config.poll_interval);
+    loop {
+        interval.tick().await;
+        for relay in state.relays.in_progress() {
+            match advance(&state, &relay).await {
+                Ok(Some(status)) => state.relays.set_status(&relay.request, status),
+                Ok(None) => {}
+                // Transient; the next tick retries
+                Err(e) => eprintln!("relay {}: {}", relay.request, e),
+            }
+        }
+    }
+}
+
+/// The relay's next status, or `None` while it has to keep waiting
+async fn advance(state: &AppState, relay: &Relay) -> Result<Option<RelayStatus>, ApiError> {
+    match &relay.status {
+        RelayStatus::Submitted {
+            signature,
+            last_valid_block_height,
+        } => {
+            let parsed: Signature = signature
+                .parse()
+                .map_err(|_| ApiError::Internal(format!("bad signature {}", signature)))?;
+            let status = state
+                .rpc
+                .get_signature_statuses(&[parsed])
+                .await
+                .map_err(|e| ApiError::Rpc(e.to_string()))?
+                .value
+                .into_iter()
+                .next()
+                .flatten();
+            match status {
+                None => {
+                    let height = state
+                        .rpc
+                        .get_block_height()
+                        .await
+                        .map_err(|e| ApiError::Rpc(e.to_string()))?;
+                    Ok((height > *last_valid_block_height).then(|| RelayStatus::Failed {
+                        error: "transaction expired before landing".into(),
+                    }))
+                }
+                Some(status) => match status.err {
+                    Some(err) => Ok(Some(RelayStatus::Failed {
+                        error: err.to_string(),
+                    })),
+                    None if status.satisfies_commitment(CommitmentConfig::confirmed()) => {
+                        let address = parse_pubkey(&relay.request)?;
+                        let request = fetch_request(&state.rpc, &address).await?;
+                        Ok(Some(RelayStatus::Requested {
+                            signature: signature.clone(),
+                            unlock_time: request.unlock_time,
+                        }))
+                    }
+                    None => Ok(None),
+                },
+            }
+        }
+        RelayStatus::Requested { unlock_time, .. } => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|e| ApiError::Internal(e.to_string()))?
+                .as_secs() as i64;
+            if now < *unlock_time {
+                return Ok(None);
+            }
+            let signature = complete(state, relay).await?;
+            Ok(Some(RelayStatus::Completed {
+                signature: signature.to_string(),
+            }))
+        }
+        RelayStatus::Completed { .. } | RelayStatus::Failed { .. } => Ok(None),
+    }
+}
+
+/// Pay out an unlocked relayed withdrawal to the recipient and this relayer
+async fn complete(state: &AppState, relay: &Relay) -> Result<Signature, ApiError> {
+    let program_id = &state.config.program_id;
+    let relayer = state.relayer.pubkey();
+    let address = parse_pubkey(&relay.request)?;
+    let request = fetch_request(&state.rpc, &address).await?;
+    let recipient = parse_pubkey(&relay.recipient)?;
+    // Whichever token program owns the mint, as the withdrawn points may be Token-2022
+    let token_program = state
+        .rpc
+        .get_account(&request.mint)
+        .await
+        .map_err(|e| ApiError::Rpc(e.to_string()))?
+        .owner;
+    let epoch = state
+        .rpc
+        .get_epoch_info()
+        .await
+        .map_err(|e| ApiError::Rpc(e.to_string()))?
+        .epoch;
+    
+    let treasury = ProtocolState::find_treasury_authority(program_id).0;
+    let token_account = |owner: &Pubkey| {
+        get_associated_token_address_with_program_id(owner, &request.mint, &token_program)
+    };
+    let instructions: Vec<Instruction> = vec![
+        create_associated_token_account_idempotent(
+            &relayer,
+            &recipient,
+            &request.mint,
+            &token_program,
+        ),
+        create_associated_token_account_idempotent(
+            &relayer,
+            &relayer,
+            &request.mint,
+            &token_program,
+        ),
+        instruction::complete_relayed_withdrawal(
+            program_id,
+            &relayer,
+            &token_account(&recipient),
+            &token_account(&relayer),
+            &token_account(&treasury),
+            &pda::find_protocol_state(program_id).0,
+            &address,
+            &request.mint,
+            &token_program,
+            epoch,
+        )
+        .map_err(|e| ApiError::Internal(e.to_string()))?,
+    ];
+    
+    let blockhash = state
+        .rpc
+        .get_latest_blockhash()
+        .await
+        .map_err(|e| ApiError::Rpc(e.to_string()))?;
+    let tx = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&relayer),
+        &[&state.relayer],
+        blockhash,
+    );
+    state
+        .rpc
+        .send_and_confirm_transaction(&tx)
+        .await
+        .map_err(|e| ApiError::Rpc(e.to_string()))
+}
+
+async fn fetch_request(rpc: &RpcClient, address: &Pubkey) -> Result<WithdrawalRequest, ApiError> {
+    let data = rpc
+        .get_account_data(address)
+        .await
+        .map_err(|e| ApiError::Rpc(e.to_string()))?;
+    WithdrawalRequest::try_from_slice(&data)
+        .map_err(|_| ApiError::Internal(format!("{} is not a withdrawal request", address)))
+}
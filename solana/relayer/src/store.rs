@@ -0,0 +1,194 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use serde::Serialize;
+
+/// Upper bound on tracked relays so the store can't be used to exhaust memory
+const MAX_TRACKED_RELAYS: usize = 10_000;
+
+/// Where a relayed withdrawal stands
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RelayStatus {
+    /// `RelayWithdrawal` was sent and hasn't confirmed yet
+    Submitted {
+        signature: String,
+        /// Block height after which the transaction can no longer land
+        last_valid_block_height: u64,
+    },
+    /// The withdrawal request is open and completes at `unlock_time`
+    Requested { signature: String, unlock_time: i64 },
+    /// `CompleteRelayedWithdrawal` paid the recipient and the relayer
+    Completed { signature: String },
+    Failed { error: String },
+}
+
+impl RelayStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, RelayStatus::Completed { .. } | RelayStatus::Failed { .. })
+    }
+}
+
+/// A relayed withdrawal, keyed by its withdrawal request address
+#[derive(Serialize, Debug, Clone)]
+pub struct Relay {
+    /// Withdrawal request account (base58)
+    pub request: String,
+    /// Recipient account (base58)
+    pub recipient: String,
+    pub relayer_fee: u64,
+    #[serde(flatten)]
+    pub status: RelayStatus,
+}
+
+impl Relay {
+    /// Anything but a failed relay holds its request address
+    fn is_active(&self) -> bool {
+        !matches!(self.status, RelayStatus::Failed { .. })
+    }
+}
+
+/// In-memory record of relays, from submission to completion
+#[derive(Default)]
+pub struct RelayStore {
+    relays: Mutex<HashMap<String, Relay>>,
+}
+
+impl RelayStore {
+    /// Start tracking `relay`; `None` if it is already tracked or the store is full
+    ///
+    /// A failed relay may be resubmitted, and finished relays make room for new ones.
+    pub fn insert(&self, relay: Relay) -> Option<()> {
+        let mut relays = self.relays.lock().unwrap();
+        if relays.get(&relay.request).map_or(false, Relay::is_active) {
+            return None;
+        }
+        if relays.len() >= MAX_TRACKED_RELAYS {
+            relays.retain(|_, relay| !relay.status.is_finished());
+            if relays.len() >= MAX_TRACKED_RELAYS {
+                return None;
+            }
+        }
+        relays.insert(relay.request.clone(), relay);
+        Some(())
+    }
+    
+    pub fn get(&self, request: &str) -> Option<Relay> {
+        self.relays.lock().unwrap().get(request).cloned()
+    }
+    
+    /// Whether `request` is being relayed or already was
+    pub fn is_active(&self, request: &str) -> bool {
+        self.relays.lock().unwrap().get(request).map_or(false, Relay::is_active)
+    }
+    
+    /// Relays still waiting on confirmation or completion
+    pub fn in_progress(&self) -> Vec<Relay> {
+        self.relays
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|relay| !relay.status.is_finished())
+            .cloned()
+            .collect()
+    }
+    
+    pub fn set_status(&self, request: &str, status: RelayStatus) {
+        if let Some(relay) = self.relays.lock().unwrap().get_mut(request) {
+            relay.status = status;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn relay(request: &str, status: RelayStatus) -> Relay {
+        Relay {
+            request: request.to_string(),
+            recipient: "recipient".to_string(),
+            relayer_fee: 5,
+            status,
+        }
+    }
+    
+    fn submitted() -> RelayStatus {
+        RelayStatus::Submitted {
+            signature: "sig".to_string(),
+            last_valid_block_height: 100,
+        }
+    }
+    
+    fn failed() -> RelayStatus {
+        RelayStatus::Failed {
+            error: "expired".to_string(),
+        }
+    }
+    
+    #[test]
+    fn a_request_is_relayed_once_unless_it_failed() {
+        let store = RelayStore::default();
+        assert_eq!(store.insert(relay("a", submitted())), Some(()));
+        assert_eq!(store.insert(relay("a", submitted())), None);
+        assert!(store.is_active("a"));
+        assert!(!store.is_active("b"));
+        
+        store.set_status("a", failed());
+        assert!(!store.is_active("a"));
+        assert_eq!(store.insert(relay("a", submitted())), Some(()));
+        assert_eq!(store.get("a").unwrap().status, submitted());
+        
+        // A completed relay still holds its request
+        let completed = RelayStatus::Completed {
+            signature: "done".to_string(),
+        };
+        store.set_status("a", completed.clone());
+        assert_eq!(store.insert(relay("a", submitted())), None);
+        assert_eq!(store.get("a").unwrap().status, completed);
+    }
+    
+    #[test]
+    fn only_unfinished_relays_are_in_progress() {
+        let store = RelayStore::default();
+        store.insert(relay("a", submitted()));
+        store.insert(relay("b", failed()));
+        let requested = RelayStatus::Requested {
+            signature: "sig".to_string(),
+            unlock_time: 10,
+        };
+        store.insert(relay("c", requested));
+        
+        let mut in_progress: Vec<String> =
+            store.in_progress().into_iter().map(|relay| relay.request).collect();
+        in_progress.sort();
+        assert_eq!(in_progress, ["a", "c"]);
+    }
+    
+    #[test]
+    fn a_full_store_makes_room_by_dropping_finished_relays() {
+        let store = RelayStore::default();
+        for i in 0..MAX_TRACKED_RELAYS - 1 {
+            store.insert(relay(&i.to_string(), submitted())).unwrap();
+        }
+        store.insert(relay("done", failed())).unwrap();
+        
+        assert_eq!(store.insert(relay("new", submitted())), Some(()));
+        assert!(store.get("done").is_none());
+        assert_eq!(store.insert(relay("newer", submitted())), None);
+    }
+    
+    #[test]
+    fn statuses_serialize_flat_and_tagged() {
+        let json = serde_json::to_value(relay("a", failed())).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "request": "a",
+                "recipient": "recipient",
+                "relayer_fee": 5,
+                "status": "failed",
+                "error": "expired",
+            })
+        );
+    }
+}
@@ -0,0 +1,352 @@
+//! Off-chain checks on a relay bundle before the relayer spends anything on it.
+//!
+//! Repeats what `RelayWithdrawal` checks on chain (the points' spend signature
+//! over this relay, active points of one mint, the protocol's fee cap) and adds
+//! the relayer's own floor on the fee, so a bundle that would fail or leave the
+//! relayer out of pocket is refused before it is submitted.
+
+use borsh::BorshDeserialize;
+use floating_point_protocol_solana::{
+    crypto::ring::{self, RingSignature},
+    pda,
+    state::{FloatingPoint, ProtocolState, RelayerConfig, WithdrawalRequest},
+};
+use serde::Deserialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{config::Config, error::ApiError};
+
+/// Most points one relay withdraws, so the transaction fits its size and compute budget
+pub const MAX_RELAY_POINTS: usize = 4;
+
+/// Withdrawal a recipient asks the relayer to request on their behalf
+///
+/// The spend signature stands in for the recipient's: the points' keys sign
+/// their key images, the recipient, this relayer and `relayer_fee`, so nothing
+/// in the bundle can be changed in transit.
+#[derive(Deserialize, Debug, Clone)]
+pub struct RelayBundle {
+    /// Recipient account (base58)
+    pub recipient: String,
+    /// Nonce the withdrawal request address is derived from
+    pub nonce: u64,
+    /// Point accounts to withdraw (base58)
+    pub points: Vec<String>,
+    /// Nullifiers, one per point (hex)
+    pub nullifiers: Vec<String>,
+    /// Fee in token base units, paid to the relayer on completion
+    pub relayer_fee: u64,
+    /// Borsh-encoded `RingSignature` by the points' keys over
+    /// `WithdrawalRequest::relay_spend_message` (base64)
+    pub spend_signature: String,
+}
+
+/// A bundle that passed every check, decoded into its on-chain types
+pub struct ValidRelay {
+    pub recipient: Pubkey,
+    pub nonce: u64,
+    pub point_ids: Vec<Pubkey>,
+    pub nullifiers: Vec<[u8; 32]>,
+    pub relayer_fee: u64,
+    pub spend_signature: Vec<u8>,
+    /// Withdrawal request account the relay opens
+    pub request: Pubkey,
+}
+
+/// Check that `bundle` would succeed on chain and pays at least the configured fee
+pub async fn validate_relay(
+    config: &Config,
+    rpc: &RpcClient,
+    relayer: &Pubkey,
+    bundle: &RelayBundle,
+) -> Result<ValidRelay, ApiError> {
+    let recipient = parse_pubkey(&bundle.recipient)?;
+    let point_ids = bundle
+        .points
+        .iter()
+        .map(|point| parse_pubkey(point))
+        .collect::<Result<Vec<_>, _>>()?;
+    let nullifiers = bundle
+        .nullifiers
+        .iter()
+        .map(|nullifier| parse_hash(nullifier))
+        .collect::<Result<Vec<_>, _>>()?;
+    let spend_signature = decode_base64(&bundle.spend_signature)?;
+    let signature = RingSignature::try_from_slice(&spend_signature).map_err(|_| {
+        ApiError::BadRequest("spend signature is not a Borsh-encoded ring signature".into())
+    })?;
+    
+    if point_ids.len() > MAX_RELAY_POINTS {
+        return Err(ApiError::BadRequest(format!(
+            "at most {} points can be relayed at once",
+            MAX_RELAY_POINTS
+        )));
+    }
+    if point_ids.is_empty() || point_ids.len() != nullifiers.len() {
+        return Err(ApiError::BadRequest(format!(
+            "expected one nullifier per point, got {} points and {} nullifiers",
+            point_ids.len(),
+            nullifiers.len()
+        )));
+    }
+    for i in 0..point_ids.len() {
+        if point_ids[..i].contains(&point_ids[i]) || nullifiers[..i].contains(&nullifiers[i]) {
+            return Err(ApiError::BadRequest(format!(
+                "point {} or its nullifier is listed twice",
+                point_ids[i]
+            )));
+        }
+    }
+    
+    let state = fetch::<ProtocolState>(rpc, &pda::find_protocol_state(&config.program_id).0)
+        .await?
+        .ok_or(ApiError::ProtocolNotInitialized)?;
+    if state.is_paused(ProtocolState::PAUSE_WITHDRAWALS) {
+        return Err(ApiError::BadRequest("withdrawals are paused".into()));
+    }
+    let relayer_config =
+        fetch::<RelayerConfig>(rpc, &RelayerConfig::find_address(&config.program_id).0)
+            .await?
+            .filter(|relayer_config| relayer_config.is_initialized)
+            .ok_or_else(|| ApiError::BadRequest("relayed withdrawals are not enabled".into()))?;
+    
+    let (amount, commitments) = withdrawn_points(config, rpc, &point_ids).await?;
+    
+    // The same message `RelayWithdrawal` checks, with this relayer as the payee
+    let relay_hash =
+        WithdrawalRequest::relay_hash(&recipient, relayer, bundle.relayer_fee, bundle.nonce);
+    let message = WithdrawalRequest::relay_spend_message(&relay_hash);
+    if !ring::verify_spend(&message, &commitments, &nullifiers, &signature) {
+        return Err(ApiError::InvalidSignature);
+    }
+    
+    check_fee(config, &relayer_config, amount, bundle.relayer_fee)?;
+    
+    let request = pda::find_withdrawal_request(&recipient, bundle.nonce, &config.program_id).0;
+    if rpc
+        .get_account_with_commitment(&request, rpc.commitment())
+        .await
+        .map_err(|e| ApiError::Rpc(e.to_string()))?
+        .value
+        .is_some()
+    {
+        return Err(ApiError::AlreadyRelayed);
+    }
+    
+    Ok(ValidRelay {
+        recipient,
+        nonce: bundle.nonce,
+        point_ids,
+        nullifiers,
+        relayer_fee: bundle.relayer_fee,
+        spend_signature,
+        request,
+    })
+}
+
+/// Value and commitments of `point_ids`, which must be active points of a single mint
+async fn withdrawn_points(
+    config: &Config,
+    rpc: &RpcClient,
+    point_ids: &[Pubkey],
+) -> Result<(u64, Vec<[u8; 32]>), ApiError> {
+    let accounts = rpc
+        .get_multiple_accounts(point_ids)
+        .await
+        .map_err(|e| ApiError::Rpc(e.to_string()))?;
+    let mut amount = 0u64;
+    let mut mint = None;
+    let mut commitments = Vec::with_capacity(point_ids.len());
+    for (point_id, account) in point_ids.iter().zip(accounts) {
+        let point = account
+            .filter(|account| account.owner == config.program_id)
+            .and_then(|account| FloatingPoint::try_from_slice(&account.data).ok())
+            .filter(|point| point.is_initialized && point.is_active)
+            .ok_or_else(|| ApiError::BadRequest(format!("{} is not an active point", point_id)))?;
+        if *mint.get_or_insert(point.mint) != point.mint {
+            return Err(ApiError::BadRequest("points must share one mint".into()));
+        }
+        amount = point
+            .value()
+            .and_then(|value| amount.checked_add(value))
+            .ok_or_else(|| ApiError::BadRequest("withdrawn amount overflows".into()))?;
+        commitments.push(point.commitment);
+    }
+    Ok((amount, commitments))
+}
+
+/// The fee must fit under the protocol's cap and cover this relayer's floor
+fn check_fee(
+    config: &Config,
+    relayer_config: &RelayerConfig,
+    amount: u64,
+    relayer_fee: u64,
+) -> Result<(), ApiError> {
+    if relayer_fee as u128 * 10000 > amount as u128 * relayer_config.max_fee_rate as u128 {
+        return Err(ApiError::BadRequest(format!(
+            "fee {} exceeds the protocol cap of {} basis points of {}",
+            relayer_fee, relayer_config.max_fee_rate, amount
+        )));
+    }
+    let min_fee = (amount as u128 * config.min_fee_rate as u128 / 10000) as u64;
+    let min_fee = min_fee.max(config.min_fee);
+    if relayer_fee < min_fee {
+        return Err(ApiError::InsufficientFee(format!(
+            "this relayer charges at least {} to withdraw {}",
+            min_fee, amount
+        )));
+    }
+    Ok(())
+}
+
+async fn fetch<T: BorshDeserialize>(
+    rpc: &RpcClient,
+    address: &Pubkey,
+) -> Result<Option<T>, ApiError> {
+    let account = rpc
+        .get_account_with_commitment(address, rpc.commitment())
+        .await
+        .map_err(|e| ApiError::Rpc(e.to_string()))?
+        .value;
+    Ok(account.and_then(|account| T::deserialize(&mut &account.data[..]).ok()))
+}
+
+pub fn parse_pubkey(value: &str) -> Result<Pubkey, ApiError> {
+    value
+        .parse()
+        .map_err(|_| ApiError::BadRequest(format!("invalid pubkey: {}", value)))
+}
+
+pub fn parse_hash(value: &str) -> Result<[u8; 32], ApiError> {
+    let mut out = [0u8; 32];
+    hex::decode_to_slice(value, &mut out)
+        .map_err(|_| ApiError::BadRequest(format!("invalid 32-byte hex value: {}", value)))?;
+    Ok(out)
+}
+
+pub fn decode_base64(value: &str) -> Result<Vec<u8>, ApiError> {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD
+        .decode(value)
+        .map_err(|_| ApiError::BadRequest("invalid base64 payload".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    
+    use base64::Engine;
+    use borsh::BorshSerialize;
+    
+    use super::*;
+    
+    fn config() -> Config {
+        Config {
+            bind: "127.0.0.1:0".parse().unwrap(),
+            rpc_url: "http://127.0.0.1:1".to_string(),
+            program_id: Pubkey::new_unique(),
+            keypair: "id.json".into(),
+            min_fee_rate: 10,
+            min_fee: 1_000,
+            poll_interval: Duration::from_secs(5),
+        }
+    }
+    
+    /// A bundle withdrawing `points` points; its signature is well formed but never checked
+    /// by the tests, which all fail before anything is fetched
+    fn bundle(points: usize) -> RelayBundle {
+        let signature = RingSignature {
+            key_images: vec![[1u8; 32]; points],
+            challenge: [0u8; 32],
+            responses: vec![[0u8; 32]; points],
+        };
+        RelayBundle {
+            recipient: Pubkey::new_unique().to_string(),
+            nonce: 0,
+            points: (0..points).map(|_| Pubkey::new_unique().to_string()).collect(),
+            nullifiers: (0..points).map(|i| hex::encode([i as u8; 32])).collect(),
+            relayer_fee: 5_000,
+            spend_signature: base64::engine::general_purpose::STANDARD
+                .encode(signature.try_to_vec().unwrap()),
+        }
+    }
+    
+    /// Validate against an endpoint nothing listens on, so any RPC call fails
+    async fn validate(bundle: &RelayBundle) -> Result<ValidRelay, ApiError> {
+        let config = config();
+        let rpc = RpcClient::new(config.rpc_url.clone());
+        validate_relay(&config, &rpc, &Pubkey::new_unique(), bundle).await
+    }
+    
+    fn bad_request(result: Result<ValidRelay, ApiError>) -> String {
+        match result {
+            Err(ApiError::BadRequest(reason)) => reason,
+            Err(e) => panic!("expected a bad request, got {}", e),
+            Ok(_) => panic!("expected a bad request"),
+        }
+    }
+    
+    #[tokio::test]
+    async fn malformed_bundles_are_refused_before_any_fetch() {
+        let mut invalid = bundle(1);
+        invalid.recipient = "nope".to_string();
+        assert_eq!(bad_request(validate(&invalid).await), "invalid pubkey: nope");
+        
+        let mut invalid = bundle(1);
+        invalid.nullifiers[0] = "abcd".to_string();
+        assert_eq!(bad_request(validate(&invalid).await), "invalid 32-byte hex value: abcd");
+        
+        let mut invalid = bundle(1);
+        invalid.spend_signature = "!".to_string();
+        assert_eq!(bad_request(validate(&invalid).await), "invalid base64 payload");
+        invalid.spend_signature = base64::engine::general_purpose::STANDARD.encode([1u8; 3]);
+        assert!(bad_request(validate(&invalid).await).contains("ring signature"));
+    }
+    
+    #[tokio::test]
+    async fn bundles_must_list_each_point_once_with_its_nullifier() {
+        assert!(bad_request(validate(&bundle(MAX_RELAY_POINTS + 1)).await).contains("at most"));
+        assert!(bad_request(validate(&bundle(0)).await).contains("one nullifier per point"));
+        
+        let mut uneven = bundle(2);
+        uneven.nullifiers.pop();
+        assert!(bad_request(validate(&uneven).await).contains("one nullifier per point"));
+        
+        let mut repeated = bundle(2);
+        repeated.points[1] = repeated.points[0].clone();
+        assert!(bad_request(validate(&repeated).await).contains("listed twice"));
+        let mut repeated = bundle(2);
+        repeated.nullifiers[1] = repeated.nullifiers[0].clone();
+        assert!(bad_request(validate(&repeated).await).contains("listed twice"));
+        
+        // A well-formed bundle gets as far as the protocol state
+        assert!(matches!(validate(&bundle(2)).await, Err(ApiError::Rpc(_))));
+    }
+    
+    #[test]
+    fn fees_must_fit_the_cap_and_cover_the_floor() {
+        let config = config();
+        let relayer_config = RelayerConfig {
+            version: 1,
+            is_initialized: true,
+            max_fee_rate: 100,
+        };
+        // 1% cap, and at least 0.1% or 1,000 base units
+        assert!(check_fee(&config, &relayer_config, 10_000_000, 100_000).is_ok());
+        assert!(matches!(
+            check_fee(&config, &relayer_config, 10_000_000, 100_001),
+            Err(ApiError::BadRequest(_))
+        ));
+        assert!(check_fee(&config, &relayer_config, 10_000_000, 10_000).is_ok());
+        assert!(matches!(
+            check_fee(&config, &relayer_config, 10_000_000, 9_999),
+            Err(ApiError::InsufficientFee(_))
+        ));
+        assert!(matches!(
+            check_fee(&config, &relayer_config, 200_000, 999),
+            Err(ApiError::InsufficientFee(_))
+        ));
+        assert!(check_fee(&config, &relayer_config, 200_000, 1_000).is_ok());
+    }
+}
@@ -0,0 +1,104 @@
+//! Bloom filters over nullifiers.
+//!
+//! The indexer publishes one `EpochBloom` per epoch and wallets test their
+//! unspent notes against them; only a filter hit needs an exact lookup. The
+//! program keeps one more filter on chain, `state::NullifierFilter`, whose
+//! bytes are the little-endian words of a `NullifierBloom`.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::hash::hashv;
+
+const BLOOM_DOMAIN: &[u8] = b"fpp-nullifier-bloom-v1";
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct NullifierBloom {
+    pub num_hashes: u8,
+    pub bits: Vec<u64>,
+}
+
+impl NullifierBloom {
+    /// Create an empty filter with at least `num_bits` bits
+    pub fn new(num_bits: usize, num_hashes: u8) -> Self {
+        let words = num_bits.max(64).div_ceil(64);
+        Self {
+            num_hashes: num_hashes.max(1),
+            bits: vec![0; words],
+        }
+    }
+    
+    /// Size a filter for `expected_items` at the given false-positive rate
+    pub fn with_capacity(expected_items: usize, false_positive_rate: f64) -> Self {
+        let n = expected_items.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = (-n * false_positive_rate.ln() / (ln2 * ln2)).ceil() as usize;
+        let num_hashes = ((num_bits as f64 / n) * ln2).round().clamp(1.0, 32.0) as u8;
+        Self::new(num_bits, num_hashes)
+    }
+    
+    /// Read filter bits stored as little-endian `u64` words
+    pub fn from_le_bytes(bytes: &[u8], num_hashes: u8) -> Self {
+        let mut filter = Self::new(bytes.len() * 8, num_hashes);
+        for (word, chunk) in filter.bits.iter_mut().zip(bytes.chunks(8)) {
+            let mut buf = [0u8; 8];
+            buf[..chunk.len()].copy_from_slice(chunk);
+            *word = u64::from_le_bytes(buf);
+        }
+        filter
+    }
+    
+    pub fn num_bits(&self) -> usize {
+        self.bits.len() * 64
+    }
+    
+    pub fn insert(&mut self, nullifier: &[u8; 32]) {
+        for index in bit_indexes(nullifier, self.num_bits(), self.num_hashes) {
+            self.bits[index / 64] |= 1 << (index % 64);
+        }
+    }
+    
+    /// `false` is definitive; `true` may be a false positive
+    pub fn contains(&self, nullifier: &[u8; 32]) -> bool {
+        bit_indexes(nullifier, self.num_bits(), self.num_hashes)
+            .all(|index| self.bits[index / 64] & (1 << (index % 64)) != 0)
+    }
+}
+
+/// Set the bits of `nullifier` in filter bytes laid out like `NullifierBloom::bits`
+/// 
+/// Bit `i` of a little-endian word array is bit `i % 8` of byte `i / 8`, so account data
+/// can be updated in place without decoding the words.
+pub fn insert_bytes(bits: &mut [u8], num_hashes: u8, nullifier: &[u8; 32]) {
+    for index in bit_indexes(nullifier, bits.len() * 8, num_hashes) {
+        bits[index / 8] |= 1 << (index % 8);
+    }
+}
+
+/// Byte-level counterpart of `NullifierBloom::contains`
+pub fn contains_bytes(bits: &[u8], num_hashes: u8, nullifier: &[u8; 32]) -> bool {
+    bit_indexes(nullifier, bits.len() * 8, num_hashes)
+        .all(|index| bits[index / 8] & (1 << (index % 8)) != 0)
+}
+
+// Kirsch-Mitzenmacher double hashing over one SHA-256 digest
+fn bit_indexes(
+    nullifier: &[u8; 32],
+    num_bits: usize,
+    num_hashes: u8,
+) -> impl Iterator<Item = usize> {
+    let digest = hashv(&[BLOOM_DOMAIN, nullifier]).to_bytes();
+    let h1 = u64::from_le_bytes(digest[..8].try_into().unwrap());
+    let h2 = u64::from_le_bytes(digest[8..16].try_into().unwrap()) | 1;
+    let num_bits = num_bits as u64;
+    (0..num_hashes as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+}
+
+/// Nullifiers spent during one epoch, as published by the indexer
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct EpochBloom {
+    pub epoch: u64,
+    pub first_slot: u64,
+    /// Last slot included; the current epoch is republished as it grows
+    pub last_slot: u64,
+    pub count: u32,
+    pub filter: NullifierBloom,
+}
@@ -0,0 +1,31 @@
+//! Clock provider used by every time-dependent processor path.
+//!
+//! On-chain the time always comes from the Clock sysvar, read through the
+//! `sol_get_clock_sysvar` syscall so instructions don't need to pass it.
+//! Integration tests move it by overwriting that sysvar in
+//! `solana-program-test` (see `tests/common`).
+
+use solana_program::{clock::Clock, program_error::ProgramError, sysvar::Sysvar};
+
+/// Source of the current cluster time
+pub trait ClockProvider {
+    fn clock(&self) -> Result<Clock, ProgramError>;
+    
+    fn unix_timestamp(&self) -> Result<i64, ProgramError> {
+        Ok(self.clock()?.unix_timestamp)
+    }
+    
+    fn slot(&self) -> Result<u64, ProgramError> {
+        Ok(self.clock()?.slot)
+    }
+}
+
+/// Reads the Clock sysvar through its syscall
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyscallClock;
+
+impl ClockProvider for SyscallClock {
+    fn clock(&self) -> Result<Clock, ProgramError> {
+        Clock::get()
+    }
+}
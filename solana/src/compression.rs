@@ -0,0 +1,73 @@
+//! Floating points held as Bubblegum compressed NFTs.
+//!
+//! `CompressPoint` closes a point account and mints a cNFT to its holder in
+//! the protocol's `PointTree`, so users holding many points stop paying rent
+//! for each one. The leaf metadata URI is the hex-encoded `CompressedPoint`.
+//! `DecompressPoint` burns the cNFT and restores a point account. Bubblegum
+//! only burns a leaf whose data hash matches, so the restored point is the
+//! one that was compressed: the tree is private to the protocol and nothing
+//! else can mint into it.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use mpl_bubblegum::{
+    hash::{hash_creators, hash_metadata},
+    types::{MetadataArgs, TokenProgramVersion, TokenStandard},
+};
+use solana_program::{program_error::ProgramError, pubkey, pubkey::Pubkey};
+
+use crate::state::CompressedPoint;
+
+pub use mpl_bubblegum::ID as BUBBLEGUM_ID;
+
+pub const ACCOUNT_COMPRESSION_ID: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+pub const NOOP_ID: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+
+pub const POINT_NFT_NAME: &str = "FPP Point";
+pub const POINT_NFT_SYMBOL: &str = "FPP";
+
+/// Bubblegum's tree config PDA for `merkle_tree`
+pub fn find_tree_config(merkle_tree: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[merkle_tree.as_ref()], &BUBBLEGUM_ID)
+}
+
+/// Leaf metadata of a compressed point
+pub fn point_metadata(point: &CompressedPoint) -> Result<MetadataArgs, ProgramError> {
+    let uri = point
+        .try_to_vec()?
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    Ok(MetadataArgs {
+        name: POINT_NFT_NAME.to_string(),
+        symbol: POINT_NFT_SYMBOL.to_string(),
+        uri,
+        seller_fee_basis_points: 0,
+        primary_sale_happened: false,
+        is_mutable: false,
+        edition_nonce: None,
+        token_standard: Some(TokenStandard::NonFungible),
+        collection: None,
+        uses: None,
+        token_program_version: TokenProgramVersion::Original,
+        creators: Vec::new(),
+    })
+}
+
+/// Data and creator hashes Bubblegum stores in a compressed point's leaf
+pub fn leaf_hashes(point: &CompressedPoint) -> Result<([u8; 32], [u8; 32]), ProgramError> {
+    let metadata = point_metadata(point)?;
+    let data_hash = hash_metadata(&metadata).map_err(|_| ProgramError::InvalidArgument)?;
+    Ok((data_hash, hash_creators(&metadata.creators)))
+}
+
+/// Read a point back from its cNFT's metadata URI, as returned by a DAS API
+pub fn parse_point_uri(uri: &str) -> Option<CompressedPoint> {
+    if uri.len() != 2 * CompressedPoint::LEN {
+        return None;
+    }
+    let bytes = (0..uri.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(uri.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    CompressedPoint::try_from_slice(&bytes).ok()
+}
@@ -0,0 +1,171 @@
+//! Deposit commitments mirrored into an spl-account-compression concurrent
+//! Merkle tree.
+//!
+//! The Poseidon `CommitmentTree` holds 2^20 leaves per generation and must be
+//! rolled over; a concurrent tree of depth up to 30 holds every deposit in one
+//! account whose proofs only need the nodes below its canopy. Its nodes are
+//! Keccak hashes, as the compression program computes them, so membership is
+//! proven to `verify_leaf` rather than inside the spend circuits.
+//!
+//! The compression program is an Anchor program; its instructions are built
+//! here from their discriminators instead of pulling in Anchor.
+
+use solana_program::{
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    keccak,
+    pubkey::Pubkey,
+};
+
+use crate::compression::{ACCOUNT_COMPRESSION_ID, NOOP_ID};
+
+/// Size of the compression program's `ConcurrentMerkleTreeHeader`
+pub const HEADER_LEN: usize = 2 + 54;
+
+/// `(max_depth, max_buffer_size)` pairs the compression program accepts
+pub const SUPPORTED_SIZES: [(u32, u32); 26] = [
+    (3, 8),
+    (5, 8),
+    (14, 64),
+    (14, 256),
+    (14, 1024),
+    (14, 2048),
+    (15, 64),
+    (16, 64),
+    (17, 64),
+    (18, 64),
+    (19, 64),
+    (20, 64),
+    (20, 256),
+    (20, 1024),
+    (20, 2048),
+    (24, 64),
+    (24, 256),
+    (24, 512),
+    (24, 1024),
+    (24, 2048),
+    (26, 512),
+    (26, 1024),
+    (26, 2048),
+    (30, 512),
+    (30, 1024),
+    (30, 2048),
+];
+
+/// Longest proof that fits in a `VerifyConcurrentCommitment` transaction; deeper trees
+/// need a canopy
+pub const MAX_PROOF_LEN: u32 = 17;
+
+pub fn is_supported(max_depth: u32, max_buffer_size: u32) -> bool {
+    SUPPORTED_SIZES.contains(&(max_depth, max_buffer_size))
+}
+
+/// Whether a tree of `max_depth` caching `canopy_depth` levels can be proven against
+pub fn is_valid_canopy(max_depth: u32, canopy_depth: u32) -> bool {
+    canopy_depth < max_depth && max_depth - canopy_depth <= MAX_PROOF_LEN
+}
+
+/// Bytes of the `ConcurrentMerkleTree` after the header: three counters, the change log
+/// buffer and the rightmost path, each log and the path being a node list plus a `u32`
+/// index padded to 8 bytes
+pub fn tree_len(max_depth: u32, max_buffer_size: u32) -> usize {
+    let path_len = 32 * (max_depth as usize + 1) + 8;
+    3 * 8 + (max_buffer_size as usize + 1) * path_len
+}
+
+/// Bytes of a canopy caching the top `canopy_depth` levels below the root
+pub fn canopy_len(canopy_depth: u32) -> usize {
+    ((1usize << (canopy_depth + 1)) - 2) * 32
+}
+
+/// Size to allocate for the Merkle tree account, owned by the compression program
+pub fn account_len(max_depth: u32, max_buffer_size: u32, canopy_depth: u32) -> usize {
+    HEADER_LEN + tree_len(max_depth, max_buffer_size) + canopy_len(canopy_depth)
+}
+
+/// Number of proof nodes a leaf needs once the canopy supplies the rest
+pub fn proof_len(max_depth: u32, canopy_depth: u32) -> usize {
+    max_depth.saturating_sub(canopy_depth) as usize
+}
+
+/// Root reached from `leaf` at `index` along `proof`, hashing as the compression program does
+///
+/// `proof` must be the full path, from the leaf's sibling up to the root's children.
+pub fn compute_root(leaf: &[u8; 32], index: u32, proof: &[[u8; 32]]) -> [u8; 32] {
+    let mut node = *leaf;
+    for (level, sibling) in proof.iter().enumerate() {
+        node = if (index >> level) & 1 == 0 {
+            keccak::hashv(&[&node, sibling]).to_bytes()
+        } else {
+            keccak::hashv(&[sibling, &node]).to_bytes()
+        };
+    }
+    node
+}
+
+fn discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash(format!("global:{}", name).as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+/// `init_empty_merkle_tree`; `merkle_tree` must be allocated with `account_len` zero bytes
+pub fn init_empty_merkle_tree(
+    merkle_tree: &Pubkey,
+    authority: &Pubkey,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Instruction {
+    let mut data = discriminator("init_empty_merkle_tree").to_vec();
+    data.extend_from_slice(&max_depth.to_le_bytes());
+    data.extend_from_slice(&max_buffer_size.to_le_bytes());
+    Instruction {
+        program_id: ACCOUNT_COMPRESSION_ID,
+        accounts: vec![
+            AccountMeta::new(*merkle_tree, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(NOOP_ID, false),
+        ],
+        data,
+    }
+}
+
+/// `append`, which needs no proof
+pub fn append(merkle_tree: &Pubkey, authority: &Pubkey, leaf: &[u8; 32]) -> Instruction {
+    let mut data = discriminator("append").to_vec();
+    data.extend_from_slice(leaf);
+    Instruction {
+        program_id: ACCOUNT_COMPRESSION_ID,
+        accounts: vec![
+            AccountMeta::new(*merkle_tree, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(NOOP_ID, false),
+        ],
+        data,
+    }
+}
+
+/// `verify_leaf`, with the proof nodes below the canopy passed as account keys
+pub fn verify_leaf(
+    merkle_tree: &Pubkey,
+    root: &[u8; 32],
+    leaf: &[u8; 32],
+    index: u32,
+    proof: &[[u8; 32]],
+) -> Instruction {
+    let mut data = discriminator("verify_leaf").to_vec();
+    data.extend_from_slice(root);
+    data.extend_from_slice(leaf);
+    data.extend_from_slice(&index.to_le_bytes());
+    let mut accounts = vec![AccountMeta::new_readonly(*merkle_tree, false)];
+    accounts.extend(
+        proof
+            .iter()
+            .map(|node| AccountMeta::new_readonly(Pubkey::new_from_array(*node), false)),
+    );
+    Instruction {
+        program_id: ACCOUNT_COMPRESSION_ID,
+        accounts,
+        data,
+    }
+}
@@ -0,0 +1,81 @@
+//! Checks for off-chain ed25519 signatures.
+//!
+//! Signature verification runs in the native Ed25519 program as a sibling
+//! instruction, which fails the whole transaction on a bad signature. We only
+//! introspect that instruction to confirm it covers the expected signer and
+//! message, the same way `range_proof` checks the ZK Token Proof program.
+
+use solana_program::{
+    account_info::AccountInfo,
+    ed25519_program,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+
+use crate::error::FPPError;
+
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_LEN: usize = 14;
+
+/// Instruction index meaning "this instruction's own data"
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Check that the instruction at `offset` from the current one verifies `signer`'s signature
+/// over `message`
+pub fn verify_signature_instruction(
+    instructions_sysvar_info: &AccountInfo,
+    offset: i8,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<(), ProgramError> {
+    if offset == 0 {
+        return Err(FPPError::InvalidPermit.into());
+    }
+    
+    let current = load_current_index_checked(instructions_sysvar_info)? as i64;
+    let index = current + offset as i64;
+    if index < 0 {
+        return Err(FPPError::InvalidPermit.into());
+    }
+    let signature_ix = load_instruction_at_checked(index as usize, instructions_sysvar_info)?;
+    if signature_ix.program_id != ed25519_program::id() {
+        return Err(FPPError::InvalidPermit.into());
+    }
+    
+    check_signature_data(&signature_ix.data, signer, message)
+}
+
+/// Check that Ed25519 program instruction data holds a single signature by `signer` over
+/// `message`
+///
+/// The key and message must live in the instruction's own data, so another instruction
+/// can't be swapped in to supply them.
+pub fn check_signature_data(
+    data: &[u8],
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<(), ProgramError> {
+    if data.len() < SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_LEN || data[0] != 1 {
+        return Err(FPPError::InvalidPermit.into());
+    }
+    
+    let offsets = &data[SIGNATURE_OFFSETS_START..SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_LEN];
+    let field = |i: usize| u16::from_le_bytes([offsets[2 * i], offsets[2 * i + 1]]);
+    let (signature_index, key_offset, key_index) = (field(1), field(2) as usize, field(3));
+    let (message_offset, message_size, message_index) =
+        (field(4) as usize, field(5) as usize, field(6));
+    if signature_index != CURRENT_INSTRUCTION
+        || key_index != CURRENT_INSTRUCTION
+        || message_index != CURRENT_INSTRUCTION
+    {
+        return Err(FPPError::InvalidPermit.into());
+    }
+    
+    let key = data.get(key_offset..key_offset + 32);
+    let signed = data.get(message_offset..message_offset + message_size);
+    if key != Some(signer.as_ref()) || signed != Some(message) {
+        return Err(FPPError::InvalidPermit.into());
+    }
+    Ok(())
+}
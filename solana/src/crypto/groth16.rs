@@ -0,0 +1,9 @@
+//! Groth16 types and verification, from the standalone verifier program.
+//!
+//! The processor never verifies in-program: it hands proofs to the verifier
+//! program by CPI (`processor::verify_groth16`), so a circuit fix or a new
+//! verifier ships without redeploying the program that holds the funds. The
+//! same code is re-exported here for off-chain callers and for the types the
+//! state accounts embed.
+
+pub use fpp_verifier::groth16::*;
@@ -0,0 +1,24 @@
+//! Cryptographic primitives used by the on-chain verifier paths.
+
+use solana_program::hash::hashv;
+
+pub mod ed25519;
+pub mod groth16;
+pub mod opening;
+pub mod poseidon;
+pub mod range_proof;
+pub mod ring;
+
+/// 64 bytes of domain-separated SHA-256 output, for uniform scalars and points
+pub(crate) fn wide_hash(domain: &[u8], parts: &[&[u8]]) -> [u8; 64] {
+    let mut wide = [0u8; 64];
+    for (half, chunk) in wide.chunks_mut(32).enumerate() {
+        let counter = [half as u8];
+        let mut input: Vec<&[u8]> = Vec::with_capacity(parts.len() + 2);
+        input.push(domain);
+        input.push(&counter);
+        input.extend_from_slice(parts);
+        chunk.copy_from_slice(&hashv(&input).to_bytes());
+    }
+    wide
+}
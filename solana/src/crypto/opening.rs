@@ -0,0 +1,112 @@
+//! Opening proofs for point commitments.
+//!
+//! A point's commitment is its ring public key `C = secret*G`, the same key
+//! every spend signs with, and its value is the public mass on the point
+//! account rather than anything hidden in `C`. An opening proof is a Schnorr
+//! proof of knowledge of `secret`, bound to the amount and the depositor so it
+//! can't be replayed for someone else's deposit. It shows a commitment isn't a
+//! copy of another depositor's key, which could never be spent.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::CompressedRistretto, scalar::Scalar,
+};
+use solana_program::pubkey::Pubkey;
+
+use super::wide_hash;
+
+const OPENING_DOMAIN: &[u8] = b"fpp-opening-v2";
+
+/// Schnorr proof of knowledge of a commitment's secret key
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq)]
+pub struct OpeningProof {
+    /// `R = k*G`
+    pub nonce_commitment: [u8; 32],
+    /// `s = k + c*secret`
+    pub response: [u8; 32],
+}
+
+/// Fiat-Shamir challenge of an opening proof, shared with the wallet that proves it
+pub fn challenge(
+    commitment: &[u8; 32],
+    amount: u64,
+    depositor: &Pubkey,
+    nonce_commitment: &[u8; 32],
+) -> Scalar {
+    Scalar::from_bytes_mod_order_wide(&wide_hash(
+        OPENING_DOMAIN,
+        &[commitment, &amount.to_le_bytes(), depositor.as_ref(), nonce_commitment],
+    ))
+}
+
+/// Verify that the depositor knows the secret key of `commitment`, deposited for `amount`
+pub fn verify_opening(
+    commitment: &[u8; 32],
+    amount: u64,
+    depositor: &Pubkey,
+    proof: &OpeningProof,
+) -> bool {
+    verify_opening_inner(commitment, amount, depositor, proof).unwrap_or(false)
+}
+
+fn verify_opening_inner(
+    commitment: &[u8; 32],
+    amount: u64,
+    depositor: &Pubkey,
+    proof: &OpeningProof,
+) -> Option<bool> {
+    let c_point = CompressedRistretto(*commitment).decompress()?;
+    let r_point = CompressedRistretto(proof.nonce_commitment).decompress()?;
+    let s = Scalar::from_canonical_bytes(proof.response)?;
+    let c = challenge(commitment, amount, depositor, &proof.nonce_commitment);
+    
+    // s*G == R + c*C
+    Some(s * RISTRETTO_BASEPOINT_POINT == r_point + c * c_point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn prove(secret: &Scalar, amount: u64, depositor: &Pubkey) -> ([u8; 32], OpeningProof) {
+        let commitment = (secret * RISTRETTO_BASEPOINT_POINT).compress().to_bytes();
+        let nonce = Scalar::from(7u64);
+        let nonce_commitment = (nonce * RISTRETTO_BASEPOINT_POINT).compress().to_bytes();
+        let c = challenge(&commitment, amount, depositor, &nonce_commitment);
+        let proof = OpeningProof {
+            nonce_commitment,
+            response: (nonce + c * secret).to_bytes(),
+        };
+        (commitment, proof)
+    }
+    
+    #[test]
+    fn openings_verify_only_for_their_deposit() {
+        let secret = Scalar::from(42u64);
+        let depositor = Pubkey::new_unique();
+        let (commitment, proof) = prove(&secret, 100, &depositor);
+        assert!(verify_opening(&commitment, 100, &depositor, &proof));
+        
+        // Replayed for another amount, depositor or commitment, the challenge no longer fits
+        assert!(!verify_opening(&commitment, 101, &depositor, &proof));
+        assert!(!verify_opening(&commitment, 100, &Pubkey::new_unique(), &proof));
+        let (other, _) = prove(&Scalar::from(43u64), 100, &depositor);
+        assert!(!verify_opening(&other, 100, &depositor, &proof));
+    }
+    
+    #[test]
+    fn malformed_openings_are_refused() {
+        let depositor = Pubkey::new_unique();
+        let (commitment, proof) = prove(&Scalar::from(42u64), 100, &depositor);
+        let non_canonical = OpeningProof {
+            response: [0xff; 32],
+            ..proof.clone()
+        };
+        assert!(!verify_opening(&commitment, 100, &depositor, &non_canonical));
+        let off_curve = OpeningProof {
+            nonce_commitment: [0xff; 32],
+            ..proof
+        };
+        assert!(!verify_opening(&commitment, 100, &depositor, &off_curve));
+    }
+}
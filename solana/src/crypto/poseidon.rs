@@ -0,0 +1,70 @@
+//! Poseidon hashing over BN254 (x^5, circom-compatible parameters).
+//!
+//! Uses the `sol_poseidon` syscall through `solana_program::poseidon`. On
+//! clusters where the syscall isn't active yet, build with the
+//! `poseidon-fallback` feature to hash in-program with `light-poseidon`
+//! (same outputs, far more compute units).
+
+use crate::{crypto::groth16, error::FPPError};
+
+/// BN254 scalar field modulus, big-endian
+pub const FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+/// Whether `bytes` (big-endian) is a canonical field element
+pub fn is_field_element(bytes: &[u8; 32]) -> bool {
+    *bytes < FIELD_MODULUS
+}
+
+/// Poseidon hash of 1-12 big-endian field elements
+pub fn hashv(inputs: &[&[u8]]) -> Result<[u8; 32], FPPError> {
+    if inputs.is_empty() || inputs.len() > 12 {
+        return Err(FPPError::InvalidCommitment);
+    }
+    hash_inner(inputs)
+}
+
+/// Binding public input of the spend circuit (`fpp_prover::circuit::spend_binding`)
+///
+/// The chain `h = Poseidon(h, x)` from `context` reduced to a field element, over the
+/// nullifiers, the output commitments, then `exit_mass`: whatever the spend pays out of the
+/// shielded set. `context` hashes the rest of the statement (pool, recipient, relayer, key
+/// version) so a proof can't be replayed with any of it changed.
+pub fn spend_binding(
+    context: &[u8; 32],
+    nullifiers: &[[u8; 32]],
+    commitments: &[[u8; 32]],
+    exit_mass: u64,
+) -> Result<[u8; 32], FPPError> {
+    let mut exit = [0u8; 32];
+    exit[24..].copy_from_slice(&exit_mass.to_be_bytes());
+    nullifiers
+        .iter()
+        .chain(commitments)
+        .chain([&exit])
+        .try_fold(groth16::hash_to_field(context), |chain, value| {
+            hashv(&[&chain, value])
+        })
+}
+
+#[cfg(not(feature = "poseidon-fallback"))]
+fn hash_inner(inputs: &[&[u8]]) -> Result<[u8; 32], FPPError> {
+    use solana_program::poseidon::{self, Endianness, Parameters};
+    
+    poseidon::hashv(Parameters::Bn254X5, Endianness::BigEndian, inputs)
+        .map(|hash| hash.to_bytes())
+        .map_err(|_| FPPError::InvalidCommitment)
+}
+
+#[cfg(feature = "poseidon-fallback")]
+fn hash_inner(inputs: &[&[u8]]) -> Result<[u8; 32], FPPError> {
+    use ark_bn254::Fr;
+    use light_poseidon::{Poseidon, PoseidonBytesHasher};
+    
+    let mut hasher = Poseidon::<Fr>::new_circom(inputs.len()).map_err(|_| FPPError::InvalidCommitment)?;
+    hasher
+        .hash_bytes_be(inputs)
+        .map_err(|_| FPPError::InvalidCommitment)
+}
@@ -0,0 +1,141 @@
+//! Range proof checks for output amount commitments.
+//!
+//! Bulletproof verification is far too expensive in-program, so it runs in
+//! the native ZK Token Proof program as a sibling instruction. We introspect
+//! that instruction and check its context covers every output commitment,
+//! the same approach Token-2022 confidential transfers use.
+
+use solana_program::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+use solana_zk_token_sdk::{
+    instruction::{
+        BatchedRangeProofContext, BatchedRangeProofU128Data, BatchedRangeProofU256Data,
+        BatchedRangeProofU64Data,
+    },
+    zk_token_proof_instruction::{decode_instruction_data, decode_instruction_type, ProofInstruction},
+    zk_token_proof_program,
+};
+
+use crate::error::FPPError;
+
+/// Commitments a single batched range proof can cover
+pub const MAX_RANGE_PROOF_OUTPUTS: usize = 8;
+
+/// Check that the instruction at `offset` from the current one range-proves `output_commitments`
+pub fn verify_range_proof_instruction(
+    instructions_sysvar_info: &AccountInfo,
+    offset: i8,
+    output_commitments: &[[u8; 32]],
+) -> Result<(), ProgramError> {
+    if offset == 0
+        || output_commitments.is_empty()
+        || output_commitments.len() > MAX_RANGE_PROOF_OUTPUTS
+    {
+        return Err(FPPError::InvalidRangeProof.into());
+    }
+    
+    let current = load_current_index_checked(instructions_sysvar_info)? as i64;
+    let index = current + offset as i64;
+    if index < 0 {
+        return Err(FPPError::InvalidRangeProof.into());
+    }
+    let proof_ix = load_instruction_at_checked(index as usize, instructions_sysvar_info)?;
+    if proof_ix.program_id != zk_token_proof_program::id() {
+        return Err(FPPError::InvalidRangeProof.into());
+    }
+    
+    check_range_proof_data(&proof_ix.data, output_commitments)
+}
+
+/// Check that ZK Token Proof instruction data is a batched range proof over `output_commitments`
+///
+/// Only the proof context is inspected; the proof itself is verified by the native program.
+pub fn check_range_proof_data(
+    data: &[u8],
+    output_commitments: &[[u8; 32]],
+) -> Result<(), ProgramError> {
+    if output_commitments.is_empty() || output_commitments.len() > MAX_RANGE_PROOF_OUTPUTS {
+        return Err(FPPError::InvalidRangeProof.into());
+    }
+    
+    let context = match decode_instruction_type(data) {
+        Some(ProofInstruction::VerifyBatchedRangeProofU64) => {
+            decode_instruction_data::<BatchedRangeProofU64Data>(data).map(|d| d.context)
+        }
+        Some(ProofInstruction::VerifyBatchedRangeProofU128) => {
+            decode_instruction_data::<BatchedRangeProofU128Data>(data).map(|d| d.context)
+        }
+        Some(ProofInstruction::VerifyBatchedRangeProofU256) => {
+            decode_instruction_data::<BatchedRangeProofU256Data>(data).map(|d| d.context)
+        }
+        _ => None,
+    }
+    .ok_or(FPPError::InvalidRangeProof)?;
+    
+    check_context(&context, output_commitments)
+}
+
+// Every output must be covered, in order, with a non-zero bit length. With at
+// most 8 outputs of at most 64 bits, their sum can't wrap the group order.
+fn check_context(
+    context: &BatchedRangeProofContext,
+    output_commitments: &[[u8; 32]],
+) -> Result<(), ProgramError> {
+    for (i, commitment) in output_commitments.iter().enumerate() {
+        if context.commitments[i].0 != *commitment || context.bit_lengths[i] == 0 {
+            return Err(FPPError::InvalidRangeProof.into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_zk_token_sdk::encryption::pedersen::Pedersen;
+    
+    /// Instruction data range-proving `amounts`, and their commitments
+    fn proof_data(amounts: &[u64]) -> (Vec<u8>, Vec<[u8; 32]>) {
+        let (commitments, openings): (Vec<_>, Vec<_>) =
+            amounts.iter().map(|amount| Pedersen::new(*amount)).unzip();
+        let data = BatchedRangeProofU64Data::new(
+            commitments.iter().collect(),
+            amounts.to_vec(),
+            vec![64 / amounts.len(); amounts.len()],
+            openings.iter().collect(),
+        )
+        .unwrap();
+        let instruction =
+            ProofInstruction::VerifyBatchedRangeProofU64.encode_verify_proof(None, &data);
+        (instruction.data, commitments.iter().map(|c| c.to_bytes()).collect())
+    }
+    
+    #[test]
+    fn range_proofs_must_cover_every_output_in_order() {
+        let (data, commitments) = proof_data(&[5, 6]);
+        assert_eq!(check_range_proof_data(&data, &commitments), Ok(()));
+        // A prefix of the covered outputs is still covered
+        assert_eq!(check_range_proof_data(&data, &commitments[..1]), Ok(()));
+        
+        let invalid: Result<(), ProgramError> = Err(FPPError::InvalidRangeProof.into());
+        let swapped = [commitments[1], commitments[0]];
+        assert_eq!(check_range_proof_data(&data, &swapped), invalid);
+        let extra = [commitments[0], commitments[1], [3u8; 32]];
+        assert_eq!(check_range_proof_data(&data, &extra), invalid);
+        assert_eq!(check_range_proof_data(&data, &[]), invalid);
+    }
+    
+    #[test]
+    fn other_proof_instructions_are_refused() {
+        let (_, commitments) = proof_data(&[5]);
+        let invalid: Result<(), ProgramError> = Err(FPPError::InvalidRangeProof.into());
+        assert_eq!(check_range_proof_data(&[], &commitments), invalid);
+        assert_eq!(check_range_proof_data(&[0xff; 8], &commitments), invalid);
+        // The right instruction type with its data cut short
+        let (data, commitments) = proof_data(&[5]);
+        assert_eq!(check_range_proof_data(&data[..data.len() / 2], &commitments), invalid);
+    }
+}
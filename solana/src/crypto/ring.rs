@@ -0,0 +1,279 @@
+//! MLSAG-style linkable ring signature verification over Ristretto.
+//!
+//! A signature spends `m` inputs at once. The ring is an `n × m` matrix of
+//! public keys laid out row-major, and each column carries one key image.
+//! Key images are deterministic per secret key, which is what makes them
+//! usable as nullifiers.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::IsIdentity,
+};
+use solana_program::hash::hashv;
+
+use super::wide_hash;
+
+const MESSAGE_DOMAIN: &[u8] = b"fpp-ring-msg-v1";
+const HASH_TO_POINT_DOMAIN: &[u8] = b"fpp-ring-hp-v1";
+const CHALLENGE_DOMAIN: &[u8] = b"fpp-ring-c-v1";
+const SPEND_DOMAIN: &[u8] = b"fpp-spend-msg-v1";
+
+/// Upper bound on ring rows, keeps verification within the compute budget
+pub const MAX_RING_SIZE: usize = 16;
+
+/// Serialized ring signature as carried in `PrivacyPayment::ring_signature`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RingSignature {
+    /// One key image per spent input column
+    pub key_images: Vec<[u8; 32]>,
+    /// Initial challenge `c_0`
+    pub challenge: [u8; 32],
+    /// Responses `s_ij`, row-major, `ring_size * key_images.len()` entries
+    pub responses: Vec<[u8; 32]>,
+}
+
+impl RingSignature {
+    /// Number of ring rows (decoys plus the real signer)
+    pub fn ring_size(&self) -> usize {
+        if self.key_images.is_empty() {
+            return 0;
+        }
+        self.responses.len() / self.key_images.len()
+    }
+}
+
+/// Message signed by the ring: binds nullifiers, outputs and the ZK proof
+pub fn signing_message(
+    input_nullifiers: &[[u8; 32]],
+    output_commitments: &[[u8; 32]],
+    proof: &[u8],
+) -> [u8; 32] {
+    let mut parts: Vec<&[u8]> =
+        Vec::with_capacity(2 + input_nullifiers.len() + output_commitments.len());
+    parts.push(MESSAGE_DOMAIN);
+    parts.extend(input_nullifiers.iter().map(|n| &n[..]));
+    parts.extend(output_commitments.iter().map(|c| &c[..]));
+    parts.push(proof);
+    hashv(&parts).to_bytes()
+}
+
+/// Message signed to spend known points outside a payment: the `action`, and what it binds
+pub fn spend_message(action: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut input: Vec<&[u8]> = Vec::with_capacity(2 + parts.len());
+    input.push(SPEND_DOMAIN);
+    input.push(action);
+    input.extend_from_slice(parts);
+    hashv(&input).to_bytes()
+}
+
+/// Verify a spend signature: a one-row ring of the spent points' own commitments
+///
+/// Withdrawals, splits, transfers and compression name the points they spend, so there is
+/// nothing to hide them among, but they still sign as a payment's ring does. Their key
+/// images are then the nullifiers a payment would publish for the same points, and a
+/// point is spent once whichever way it leaves.
+pub fn verify_spend(
+    message: &[u8; 32],
+    commitments: &[[u8; 32]],
+    nullifiers: &[[u8; 32]],
+    signature: &RingSignature,
+) -> bool {
+    // One column per point; with fewer nullifiers the ring would hide which points it spends
+    commitments.len() == nullifiers.len()
+        && signature.key_images == nullifiers
+        && verify(message, commitments, signature)
+}
+
+/// Verify `signature` over `message` against the row-major `ring`
+pub fn verify(message: &[u8; 32], ring: &[[u8; 32]], signature: &RingSignature) -> bool {
+    verify_inner(message, ring, signature).unwrap_or(false)
+}
+
+fn verify_inner(message: &[u8; 32], ring: &[[u8; 32]], signature: &RingSignature) -> Option<bool> {
+    if ring.is_empty() || signature.responses.len() != ring.len() {
+        return Some(false);
+    }
+    let c = verify_rows(message, ring, signature, 0, &signature.challenge)?;
+    Some(c == signature.challenge)
+}
+
+/// Run the verification over some of the ring's rows, from the challenge entering them
+///
+/// `ring` holds the rows starting at `first_row`. Returns the challenge leaving the last
+/// of them, or `None` if the rows don't fit the signature or a point fails to decode. The
+/// signature is valid when running every row from `signature.challenge` returns it
+/// unchanged, so a long ring can be checked over several calls.
+pub fn verify_rows(
+    message: &[u8; 32],
+    ring: &[[u8; 32]],
+    signature: &RingSignature,
+    first_row: usize,
+    challenge: &[u8; 32],
+) -> Option<[u8; 32]> {
+    let columns = signature.key_images.len();
+    if columns == 0 || ring.len() % columns != 0 || signature.responses.len() % columns != 0 {
+        return None;
+    }
+    let rows = ring.len() / columns;
+    if signature.ring_size() > MAX_RING_SIZE || first_row + rows > signature.ring_size() {
+        return None;
+    }
+    
+    let key_images = decompress_all(&signature.key_images)?;
+    if key_images.iter().any(|image| image.is_identity()) {
+        return None;
+    }
+    let members = decompress_all(ring)?;
+    let responses = signature.responses[first_row * columns..(first_row + rows) * columns]
+        .iter()
+        .map(|s| Scalar::from_canonical_bytes(*s))
+        .collect::<Option<Vec<Scalar>>>()?;
+    
+    let mut c = Scalar::from_canonical_bytes(*challenge)?;
+    for row in 0..rows {
+        let mut transcript = Vec::with_capacity(32 + 64 * columns);
+        transcript.extend_from_slice(message);
+        for col in 0..columns {
+            let idx = row * columns + col;
+            let s = responses[idx];
+            // L = s*G + c*P, R = s*Hp(P) + c*I
+            let l = RistrettoPoint::vartime_double_scalar_mul_basepoint(&c, &members[idx], &s);
+            let r = s * hash_to_point(&ring[idx]) + c * key_images[col];
+            transcript.extend_from_slice(l.compress().as_bytes());
+            transcript.extend_from_slice(r.compress().as_bytes());
+        }
+        c = hash_to_scalar(&transcript);
+    }
+    
+    Some(c.to_bytes())
+}
+
+fn decompress_all(points: &[[u8; 32]]) -> Option<Vec<RistrettoPoint>> {
+    points
+        .iter()
+        .map(|bytes| CompressedRistretto(*bytes).decompress())
+        .collect()
+}
+
+/// Hash a public key to a curve point with unknown discrete log
+pub fn hash_to_point(key: &[u8; 32]) -> RistrettoPoint {
+    RistrettoPoint::from_uniform_bytes(&wide_hash(HASH_TO_POINT_DOMAIN, &[key]))
+}
+
+/// Challenge hash over one row's transcript, shared with off-chain signers
+pub fn hash_to_scalar(data: &[u8]) -> Scalar {
+    Scalar::from_bytes_mod_order_wide(&wide_hash(CHALLENGE_DOMAIN, &[data]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+    
+    fn key(seed: u8) -> Scalar {
+        hash_to_scalar(&[b"ring-test-key".as_slice(), &[seed]].concat())
+    }
+    
+    fn public(secret: &Scalar) -> [u8; 32] {
+        (secret * RISTRETTO_BASEPOINT_POINT).compress().to_bytes()
+    }
+    
+    fn key_image(secret: &Scalar) -> [u8; 32] {
+        (secret * hash_to_point(&public(secret))).compress().to_bytes()
+    }
+    
+    /// One-column signature by `secret`, hidden at row `real` of `ring`
+    fn sign(message: &[u8; 32], ring: &[[u8; 32]], real: usize, secret: &Scalar) -> RingSignature {
+        let image = CompressedRistretto(key_image(secret)).decompress().unwrap();
+        let next_challenge = |l: RistrettoPoint, r: RistrettoPoint| {
+            let mut transcript = message.to_vec();
+            transcript.extend_from_slice(l.compress().as_bytes());
+            transcript.extend_from_slice(r.compress().as_bytes());
+            hash_to_scalar(&transcript)
+        };
+        
+        let alpha = hash_to_scalar(&[b"ring-test-alpha".as_slice(), message].concat());
+        let mut responses = vec![[0u8; 32]; ring.len()];
+        let mut challenges = vec![Scalar::zero(); ring.len()];
+        let mut c = next_challenge(
+            alpha * RISTRETTO_BASEPOINT_POINT,
+            alpha * hash_to_point(&ring[real]),
+        );
+        for step in 1..ring.len() {
+            let row = (real + step) % ring.len();
+            challenges[row] = c;
+            let s = hash_to_scalar(&[b"ring-test-decoy".as_slice(), &[row as u8]].concat());
+            responses[row] = s.to_bytes();
+            let member = CompressedRistretto(ring[row]).decompress().unwrap();
+            let l = s * RISTRETTO_BASEPOINT_POINT + c * member;
+            let r = s * hash_to_point(&ring[row]) + c * image;
+            c = next_challenge(l, r);
+        }
+        challenges[real] = c;
+        responses[real] = (alpha - c * secret).to_bytes();
+        
+        RingSignature {
+            key_images: vec![key_image(secret)],
+            challenge: challenges[0].to_bytes(),
+            responses,
+        }
+    }
+    
+    #[test]
+    fn ring_signatures_round_trip() {
+        let secrets: Vec<Scalar> = (0..4).map(key).collect();
+        let ring: Vec<[u8; 32]> = secrets.iter().map(public).collect();
+        let message = signing_message(&[key_image(&secrets[2])], &[[1u8; 32]], b"proof");
+        let signature = sign(&message, &ring, 2, &secrets[2]);
+        assert_eq!(signature.ring_size(), 4);
+        assert!(verify(&message, &ring, &signature));
+        
+        // Checking the rows over two calls ends at the same challenge
+        let middle = verify_rows(&message, &ring[..1], &signature, 0, &signature.challenge);
+        let end = verify_rows(&message, &ring[1..], &signature, 1, &middle.unwrap());
+        assert_eq!(end, Some(signature.challenge));
+        
+        // Nor does it verify over another message or ring
+        assert!(!verify(&[0u8; 32], &ring, &signature));
+        let mut other_ring = ring.clone();
+        other_ring[0] = public(&key(9));
+        assert!(!verify(&message, &other_ring, &signature));
+    }
+    
+    #[test]
+    fn a_tampered_challenge_fails() {
+        let secrets: Vec<Scalar> = (0..3).map(key).collect();
+        let ring: Vec<[u8; 32]> = secrets.iter().map(public).collect();
+        let message = spend_message(b"test", &[]);
+        let mut signature = sign(&message, &ring, 0, &secrets[0]);
+        signature.challenge = (Scalar::from_canonical_bytes(signature.challenge).unwrap()
+            + Scalar::one())
+        .to_bytes();
+        assert!(!verify(&message, &ring, &signature));
+        
+        // A non-canonical challenge is refused rather than reduced
+        signature.challenge = [0xff; 32];
+        assert!(!verify(&message, &ring, &signature));
+    }
+    
+    #[test]
+    fn a_key_image_of_another_key_fails() {
+        let secret = key(0);
+        let ring = vec![public(&secret)];
+        let message = spend_message(b"test", &[]);
+        let signature = sign(&message, &ring, 0, &secret);
+        assert!(verify_spend(&message, &ring, &[key_image(&secret)], &signature));
+        
+        // The signature only holds under the signer's own image, so it can't pick its nullifier
+        let mut forged = signature.clone();
+        forged.key_images = vec![key_image(&key(1))];
+        assert!(!verify(&message, &ring, &forged));
+        assert!(!verify_spend(&message, &ring, &[key_image(&key(1))], &signature));
+        
+        // And the identity, which every key would share, is refused outright
+        forged.key_images = vec![RistrettoPoint::default().compress().to_bytes()];
+        assert!(!verify(&message, &ring, &forged));
+    }
+}
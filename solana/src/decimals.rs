@@ -0,0 +1,42 @@
+//! Conversion between token amounts and the protocol's base unit.
+//!
+//! Point values, denominations, fees and per-mint totals are all kept in a
+//! base unit with `BASE_DECIMALS` decimals, so a point of mass 1 is worth
+//! `POINT_VALUE` whichever mint it was deposited in. Amounts are converted by
+//! the mint's decimals only where tokens actually move; rounding always
+//! favours the treasury.
+
+/// Decimals of the base unit (those of USDT)
+pub const BASE_DECIMALS: u8 = 6;
+
+/// Token amount of a mint with `decimals` worth `base_amount`, rounded down
+pub fn to_token_amount(base_amount: u64, decimals: u8) -> Option<u64> {
+    if decimals >= BASE_DECIMALS {
+        base_amount.checked_mul(scale(decimals - BASE_DECIMALS)?)
+    } else {
+        Some(base_amount / scale(BASE_DECIMALS - decimals)?)
+    }
+}
+
+/// Token amount of a mint with `decimals` worth `base_amount`, rounded up
+pub fn to_token_amount_ceil(base_amount: u64, decimals: u8) -> Option<u64> {
+    if decimals >= BASE_DECIMALS {
+        to_token_amount(base_amount, decimals)
+    } else {
+        let scale = scale(BASE_DECIMALS - decimals)?;
+        Some(base_amount / scale + (base_amount % scale != 0) as u64)
+    }
+}
+
+/// Base units worth `token_amount` of a mint with `decimals`, rounded down
+pub fn to_base_amount(token_amount: u64, decimals: u8) -> Option<u64> {
+    if decimals >= BASE_DECIMALS {
+        Some(token_amount / scale(decimals - BASE_DECIMALS)?)
+    } else {
+        token_amount.checked_mul(scale(BASE_DECIMALS - decimals)?)
+    }
+}
+
+fn scale(decimals: u8) -> Option<u64> {
+    10u64.checked_pow(decimals as u32)
+}
@@ -53,6 +53,81 @@ pub enum FPPError {
     
     #[error("Account Not Initialized")]
     AccountNotInitialized,
+    
+    #[error("Commitment Tree Full")]
+    CommitmentTreeFull,
+    
+    #[error("Unknown Merkle Root")]
+    UnknownMerkleRoot,
+    
+    #[error("Mint Not Supported")]
+    MintNotSupported,
+    
+    #[error("Invalid Range Proof")]
+    InvalidRangeProof,
+    
+    #[error("Invalid Circuit")]
+    InvalidCircuit,
+    
+    #[error("Invalid Encrypted Note")]
+    InvalidEncryptedNote,
+    
+    #[error("Invalid Security Event")]
+    InvalidSecurityEvent,
+    
+    #[error("Mass Not Conserved")]
+    MassNotConserved,
+    
+    #[error("Stale Contact Book")]
+    StaleContactBook,
+    
+    #[error("Invalid Multisig")]
+    InvalidMultisig,
+    
+    #[error("Proposal Not Approved")]
+    ProposalNotApproved,
+    
+    #[error("Proposal Already Executed")]
+    ProposalAlreadyExecuted,
+    
+    #[error("Config Timelock Active")]
+    ConfigTimelockActive,
+    
+    #[error("Denied Address")]
+    DeniedAddress,
+    
+    #[error("Invalid Compliance Proof")]
+    InvalidComplianceProof,
+    
+    #[error("Deposit Cap Exceeded")]
+    DepositCapExceeded,
+    
+    #[error("Invalid Relay Proof")]
+    InvalidRelayProof,
+    
+    #[error("Invalid Permit")]
+    InvalidPermit,
+    
+    #[error("Unsupported Account Version")]
+    UnsupportedVersion,
+    
+    #[error("Account Still In Use")]
+    AccountInUse,
+    
+    #[error("Staged Proof Expired")]
+    StagedProofExpired,
+    
+    #[error("Staged Proof Not Ready")]
+    StagedProofNotReady,
+    
+    #[error("Treasury Deployment Limit Exceeded")]
+    DeploymentLimitExceeded,
+    
+    #[error("Withdrawal Queue Full")]
+    WithdrawalQueueFull,
+    
+    #[error("Cross-Program Invocation Not Allowed")]
+    CpiNotAllowed,
 }
 
 impl From<FPPError> for ProgramError {
@@ -0,0 +1,166 @@
+//! Structured program events.
+//!
+//! Handlers emit each event as Borsh through `sol_log_data`, which shows up in
+//! transaction logs as `Program data: <base64 fields>`: first `EVENT_TAG`,
+//! then the encoded event. Indexers and wallet scanners decode these instead
+//! of parsing `msg!` text, and they see events from cross-program calls too.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::{log::sol_log_data, pubkey::Pubkey};
+
+use crate::state::AuditEntry;
+
+/// First field of every event log, versioned with the event layout
+pub const EVENT_TAG: &[u8] = b"fpp-event-v1";
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq)]
+pub enum FPPEvent {
+    /// Points were minted; commitment `i` sits at leaf `first_leaf_index + i`
+    Deposit {
+        depositor: Pubkey,
+        mint: Pubkey,
+        amount: u64,
+        fee: u64,
+        denomination: u64,
+        commitments: Vec<[u8; 32]>,
+        first_leaf_index: u64,
+        tree_generation: u32,
+        merkle_root: [u8; 32],
+    },
+    
+    /// A split or transfer appended commitments starting at `first_leaf_index`
+    CommitmentsInserted {
+        commitments: Vec<[u8; 32]>,
+        first_leaf_index: u64,
+        tree_generation: u32,
+        merkle_root: [u8; 32],
+    },
+    
+    /// Nullifiers were recorded as spent, by any instruction that consumes points
+    NullifiersSpent {
+        nullifiers: Vec<[u8; 32]>,
+    },
+    
+    /// Inputs were nullified and output memos published
+    PrivacyPayment {
+        merkle_root: [u8; 32],
+        input_nullifiers: Vec<[u8; 32]>,
+        output_commitments: Vec<[u8; 32]>,
+    },
+    
+    /// A withdrawal request was opened, directly or by finalizing a batch
+    WithdrawalRequested {
+        requester: Pubkey,
+        request: Pubkey,
+        nonce: u64,
+        mint: Pubkey,
+        amount: u64,
+        point_count: u32,
+        unlock_time: i64,
+    },
+    
+    /// A withdrawal request paid out; `amount` is before the fee and penalty
+    WithdrawalCompleted {
+        requester: Pubkey,
+        request: Pubkey,
+        mint: Pubkey,
+        amount: u64,
+        fee: u64,
+        penalty: u64,
+        token_fee: u64,
+        emergency: bool,
+    },
+    
+    /// Queued protocol fee rates took effect
+    FeeUpdated {
+        deposit_fee_rate: u16,
+        withdrawal_fee_rate: u16,
+    },
+    
+    /// An address stopped paying deposit and withdrawal fees
+    FeeExemptionAdded {
+        address: Pubkey,
+    },
+    
+    /// An address pays fees again
+    FeeExemptionRemoved {
+        address: Pubkey,
+    },
+    
+    /// A denomination was deposited into a pool; the commitment sits at `leaf_index` of the
+    /// pool's own tree
+    PoolDeposit {
+        pool: Pubkey,
+        commitment: [u8; 32],
+        leaf_index: u64,
+        merkle_root: [u8; 32],
+    },
+    
+    /// A pool deposit was withdrawn; `fee` is the protocol's, on top of the relayer's
+    PoolWithdrawal {
+        pool: Pubkey,
+        nullifier: [u8; 32],
+        recipient: Pubkey,
+        relayer: Pubkey,
+        relayer_fee: u64,
+        fee: u64,
+    },
+    
+    /// Notes of `source` were spent into notes of `destination`, starting at
+    /// `first_leaf_index` of the destination's tree
+    PoolTransfer {
+        source: Pubkey,
+        destination: Pubkey,
+        nullifiers: Vec<[u8; 32]>,
+        commitments: Vec<[u8; 32]>,
+        first_leaf_index: u64,
+        merkle_root: [u8; 32],
+    },
+    
+    /// Proofs for `circuit_id` are now checked against its key at `circuit_version`
+    VerifyingKeyUpdated {
+        circuit_id: u16,
+        circuit_version: u16,
+    },
+    
+    /// A deposit's commitments were also appended to the concurrent tree at
+    /// `first_leaf_index..first_leaf_index + count`
+    ConcurrentTreeAppend {
+        merkle_tree: Pubkey,
+        first_leaf_index: u64,
+        count: u32,
+    },
+    
+    /// An admin action was appended to the `AuditLog`
+    AdminActionLogged {
+        entry: AuditEntry,
+    },
+    
+    /// Paused operations resume at `effective_at` unless paused again first
+    UnpauseQueued {
+        paused_flags: u8,
+        effective_at: i64,
+    },
+    
+    /// A queued unpause took effect
+    Unpaused {
+        paused_flags: u8,
+    },
+}
+
+impl FPPEvent {
+    /// Log the event for off-chain readers
+    pub fn emit(&self) {
+        // Serializing plain data into a Vec cannot fail
+        let data = self.try_to_vec().unwrap_or_default();
+        sol_log_data(&[EVENT_TAG, &data]);
+    }
+    
+    /// Decode the fields of one `Program data:` line; other programs' data is skipped
+    pub fn from_log_data<T: AsRef<[u8]>>(fields: &[T]) -> Option<Self> {
+        match fields {
+            [tag, data] if tag.as_ref() == EVENT_TAG => Self::try_from_slice(data.as_ref()).ok(),
+            _ => None,
+        }
+    }
+}
@@ -1,110 +1,6027 @@
-use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::pubkey::Pubkey;
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use shank::ShankInstruction;
+use solana_program::{
+    entrypoint::MAX_PERMITTED_DATA_INCREASE,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+    sysvar,
+};
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+use crate::{
+    compression::{self, ACCOUNT_COMPRESSION_ID, BUBBLEGUM_ID, NOOP_ID},
+    crypto::{
+        groth16::{Groth16Proof, Groth16VerifyingKey},
+        opening::OpeningProof,
+    },
+    lending::ReserveKeys,
+    migration::AccountKind,
+    nft::{self, TOKEN_METADATA_ID},
+    pda,
+    state::{
+        AdminAction, AdminMultisig, AdminProposal, AssociationSet, AuditLog, BatchedPayment,
+        CircuitInfo, CircuitKey, CommitmentTree, CompressedPoint, ConcurrentTree, ContactBook,
+        DenyList, DepositPermit, EncryptedNote, EpochStats, FeeExemptions, FeeTier,
+        MassDynamics, NullifierFilter, NullifierSet, NullifierShard, PointTree, PoolConfig,
+        PoolNullifier, PoolRoute, PoolState, ProtocolState, RateLimits, ReferralAccount,
+        RelayerConfig, RewardAccount, RootHistory, SecurityEvent, SecurityEventKind,
+        SecuritySeverity, StagedPayment, StagedProof, SupportedMint, SupportedMintConfig,
+        TreasuryDeployment, UserActivity, WithdrawalBatch, WithdrawalQueue, MAX_FEE_TIERS,
+    },
+};
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankInstruction, Debug, Clone)]
 pub enum FPPInstruction {
     /// Initialize the protocol
     /// 
     /// Accounts expected:
     /// 0. `[signer, writable]` Protocol authority
-    /// 1. `[writable]` Protocol state account
-    /// 2. `[]` Treasury account
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[]` Treasury authority (PDA)
     /// 3. `[]` USDT mint
     /// 4. `[]` System program
-    /// 5. `[]` Rent sysvar
+    /// 5. `[writable]` Commitment tree account (PDA)
+    /// 6. `[writable]` Root history account (PDA)
+    #[account(0, writable, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, name = "treasury_authority", desc = "Treasury authority (PDA)")]
+    #[account(3, name = "usdt_mint", desc = "USDT mint")]
+    #[account(4, name = "system_program", desc = "System program")]
+    #[account(5, writable, name = "commitment_tree", desc = "Commitment tree account (PDA)")]
+    #[account(6, writable, name = "root_history", desc = "Root history account (PDA)")]
     Initialize {
         deposit_fee_rate: u16,
         withdrawal_fee_rate: u16,
+        /// Number of recent tree roots accepted by spend proofs
+        root_history_size: u16,
     },
     
-    /// Deposit USDT and create floating points
+    /// Deposit USDT or another supported mint and create floating points
+    /// 
+    /// The user pays `amount` plus the deposit fee; the fee stays in the treasury until
+    /// `CollectFees` sweeps it. Denominations, fee rate and caps are the mint's own.
+    /// For wrapped SOL, lamports are moved from the user into their wSOL account to cover
+    /// any shortfall.
+    /// 
+    /// `amount` must be a whole number of `denomination`s with one commitment per point,
+    /// so 100 USDT in 10 USDT points takes 10 commitments and creates all 10 point
+    /// accounts in this instruction.
+    /// 
+    /// A `referrer` is credited `ProtocolState::referral_fee_share` of the deposit fee in
+    /// its referral account, which is then passed after the epoch stats account.
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` User account
-    /// 1. `[writable]` User USDT token account
-    /// 2. `[writable]` Treasury USDT token account
-    /// 3. `[writable]` Protocol state account
-    /// 4. `[writable]` New floating point account (PDA)
-    /// 5. `[]` USDT mint
-    /// 6. `[]` Token program
+    /// 0. `[signer, writable]` User account (pays point rent, and activity rent on first use)
+    /// 1. `[writable]` User token account for the mint
+    /// 2. `[writable]` Treasury token account for the mint
+    /// 3. `[writable]` Protocol state account (PDA)
+    /// 4. `[writable]` Floating point account (PDA) for the first commitment
+    /// 5. `[]` Mint (USDT or a supported mint)
+    /// 6. `[]` Token program (SPL Token or Token-2022, whichever owns the mint)
     /// 7. `[]` System program
-    /// 8. `[]` Clock sysvar
+    /// 8. `[writable]` Commitment tree account (PDA)
+    /// 9. `[writable]` Root history account (PDA)
+    /// 10. `[writable]` User activity account (PDA)
+    /// 11. `[]` Deny list account (PDA; may not exist yet)
+    /// 12. `[writable]` Supported mint account (PDA; unused for USDT)
+    /// 13. `[writable]` Reward account (PDA)
+    /// 14. `[]` Fee exemptions account (PDA; may not exist yet)
+    /// 15. `[writable]` Epoch stats account (PDA of the current epoch)
+    /// 16. `[]` Instructions sysvar, read when `ProtocolState::reject_cpi` is set
+    /// 17. `[writable]` Referral account (PDA of the referrer and mint), only with a `referrer`
+    /// 17/18-N. `[writable]` Floating point accounts (PDAs) for the remaining commitments
+    /// N+1..N+4. Concurrent tree accounts, once `CreateConcurrentTree` has run
+    ///   (see `with_concurrent_tree`)
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "user",
+        desc = "User account (pays point rent, and activity rent on first use)"
+    )]
+    #[account(1, writable, name = "user_token", desc = "User token account for the mint")]
+    #[account(2, writable, name = "treasury_token", desc = "Treasury token account for the mint")]
+    #[account(3, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        4,
+        writable,
+        name = "floating_point",
+        desc = "Floating point account (PDA) for the first commitment"
+    )]
+    #[account(5, name = "mint", desc = "Mint (USDT or a supported mint)")]
+    #[account(
+        6,
+        name = "token_program",
+        desc = "Token program (SPL Token or Token-2022, whichever owns the mint)"
+    )]
+    #[account(7, name = "system_program", desc = "System program")]
+    #[account(8, writable, name = "commitment_tree", desc = "Commitment tree account (PDA)")]
+    #[account(9, writable, name = "root_history", desc = "Root history account (PDA)")]
+    #[account(10, writable, name = "user_activity", desc = "User activity account (PDA)")]
+    #[account(11, name = "deny_list", desc = "Deny list account (PDA; may not exist yet)")]
+    #[account(
+        12,
+        writable,
+        name = "supported_mint",
+        desc = "Supported mint account (PDA; unused for USDT)"
+    )]
+    #[account(13, writable, name = "reward_account", desc = "Reward account (PDA)")]
+    #[account(
+        14,
+        name = "fee_exemptions",
+        desc = "Fee exemptions account (PDA; may not exist yet)"
+    )]
+    #[account(
+        15,
+        writable,
+        name = "epoch_stats",
+        desc = "Epoch stats account (PDA of the current epoch)"
+    )]
+    #[account(
+        16,
+        name = "instructions_sysvar",
+        desc = "Instructions sysvar, read when `ProtocolState::reject_cpi` is set"
+    )]
+    #[account(
+        17,
+        writable,
+        name = "referral_account",
+        desc = "Referral account (PDA of the referrer and mint), only with a `referrer`"
+    )]
+    #[account(
+        18,
+        writable,
+        name = "remaining_points",
+        desc = "17/18-N: Floating point accounts (PDAs) for the remaining commitments"
+    )]
     Deposit {
         amount: u64,
+        /// One of the mint's configured denominations; each point holds this much
+        denomination: u64,
         commitments: Vec<[u8; 32]>,
+        /// One opening proof per commitment; required when the protocol enforces openings
+        opening_proofs: Vec<OpeningProof>,
+        referrer: Option<Pubkey>,
     },
     
     /// Privacy payment using zero-knowledge proof
     /// 
+    /// A non-empty `proof` is checked through the verifier program against the key of
+    /// `CircuitInfo::PAYMENT_CIRCUIT_ID`, with `merkle_root` and `CircuitKey::payment_binding`
+    /// as its public inputs.
+    /// 
+    /// A payment has a single output: a point of the recipient's holding the combined mass
+    /// of the inputs. Every member of a ring column must hold the same mass, and every
+    /// column the same mint, so what is spent is known without revealing which member.
+    /// 
     /// Accounts expected:
-    /// 0. `[signer]` Sender account
-    /// 1. `[writable]` Protocol state account
-    /// 2. `[]` Recipient account
-    /// 3-N. `[writable]` Input point accounts
-    /// N+1-M. `[writable]` Output point accounts (PDAs)
-    /// M+1. `[]` ZK verifier program
-    /// M+2. `[]` System program
-    /// M+3. `[]` Clock sysvar
+    /// 0. `[signer, writable]` Sender account (pays nullifier, output point and note rent)
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[]` Recipient account (creator of the output point)
+    /// 3-N. `[writable]` Input point accounts (ring members, `ring_size * inputs`, row-major)
+    /// N+1-K. `[writable]` Nullifier accounts (PDAs), one per input nullifier
+    /// K+1-M. `[writable]` Output point accounts (PDAs), one per output commitment
+    /// M+1-P. `[writable]` Encrypted note accounts (PDAs), one per output commitment
+    /// P+1. `[]` ZK verifier program
+    /// P+2. `[]` System program
+    /// P+3. `[]` Root history account (PDA), or the archived tree holding `merkle_root`
+    /// P+4. `[]` Instructions sysvar
+    /// P+5. `[writable]` Nullifier filter account (PDA; may not exist yet)
+    /// P+6. `[writable]` Commitment tree account (PDA)
+    /// P+7. `[writable]` Root history account (PDA)
+    /// P+8. `[]` Payment circuit key account (PDA), only with a `proof`
+    /// P+8/9.. `[]` Nullifier shard accounts (PDAs), one per nullifier prefix
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "sender",
+        desc = "Sender account (pays nullifier, output point and note rent)"
+    )]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, name = "recipient", desc = "Recipient account (creator of the output point)")]
+    #[account(
+        3,
+        writable,
+        name = "input_points",
+        desc = "3-N: Input point accounts (ring members, `ring_size * inputs`, row-major)"
+    )]
+    #[account(
+        4,
+        writable,
+        name = "nullifiers",
+        desc = "N+1-K: Nullifier accounts (PDAs), one per input nullifier"
+    )]
+    #[account(
+        5,
+        writable,
+        name = "output_points",
+        desc = "K+1-M: Output point accounts (PDAs), one per output commitment"
+    )]
+    #[account(
+        6,
+        writable,
+        name = "encrypted_notes",
+        desc = "M+1-P: Encrypted note accounts (PDAs), one per output commitment"
+    )]
+    #[account(7, name = "zk_verifier_program", desc = "P+1: ZK verifier program")]
+    #[account(8, name = "system_program", desc = "P+2: System program")]
+    #[account(
+        9,
+        name = "root_history",
+        desc = "P+3: Root history account (PDA), or the archived tree holding `merkle_root`"
+    )]
+    #[account(10, name = "instructions_sysvar", desc = "P+4: Instructions sysvar")]
+    #[account(
+        11,
+        writable,
+        name = "nullifier_filter",
+        desc = "P+5: Nullifier filter account (PDA; may not exist yet)"
+    )]
+    #[account(
+        12,
+        writable,
+        name = "commitment_tree",
+        desc = "P+6: Commitment tree account (PDA)"
+    )]
+    #[account(13, writable, name = "root_history", desc = "P+7: Root history account (PDA)")]
+    #[account(
+        14,
+        name = "circuit_key",
+        desc = "P+8: Payment circuit key account (PDA), only with a `proof`"
+    )]
+    #[account(
+        15,
+        name = "nullifier_shards",
+        desc = "P+8/9..: Nullifier shard accounts (PDAs), one per nullifier prefix"
+    )]
     PrivacyPayment {
+        /// Commitment tree root the proof was generated against
+        merkle_root: [u8; 32],
+        /// Key images of the spent inputs, one per ring column
         input_nullifiers: Vec<[u8; 32]>,
+        /// The payment's one output
         output_commitments: Vec<[u8; 32]>,
+        /// Borsh-encoded `Groth16Proof`, or empty for none
         proof: Vec<u8>,
+        /// Borsh-encoded `crypto::ring::RingSignature`
         ring_signature: Vec<u8>,
+        /// Relative index of the ZK Token Proof batched range proof over `output_commitments`
+        range_proof_offset: i8,
+        /// Note openings encrypted to each recipient's viewing key, one per output
+        encrypted_notes: Vec<Vec<u8>>,
     },
     
     /// Request withdrawal
     /// 
+    /// The request is created at `pda::find_withdrawal_request(user, nonce)` and appended to
+    /// the withdrawal queue, where `ProcessWithdrawalQueue` can settle it once it unlocks. A
+    /// request made while the queue is full isn't queued; its requester completes it.
+    /// 
     /// Accounts expected:
-    /// 0. `[signer]` User account
-    /// 1. `[writable]` Protocol state account
+    /// 0. `[signer, writable]` User account (pays request, nullifier and queue rent)
+    /// 1. `[writable]` Protocol state account (PDA)
     /// 2. `[writable]` Withdrawal request account (PDA)
     /// 3-N. `[writable]` Point accounts to withdraw
-    /// N+1. `[]` System program
-    /// N+2. `[]` Clock sysvar
+    /// N+1-K. `[writable]` Nullifier accounts (PDAs), one per point
+    /// K+1. `[]` System program
+    /// K+2. `[writable]` User activity account (PDA)
+    /// K+3. `[writable]` Nullifier filter account (PDA; may not exist yet)
+    /// K+4. `[writable]` Reward account (PDA; may not exist)
+    /// K+5. `[writable]` Withdrawal queue account (PDA, created on first use)
+    /// K+6. `[]` Instructions sysvar, read when `ProtocolState::reject_cpi` is set
+    /// K+7.. `[]` Nullifier shard accounts (PDAs), one per nullifier prefix
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "user",
+        desc = "User account (pays request, nullifier and queue rent)"
+    )]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "withdrawal_request", desc = "Withdrawal request account (PDA)")]
+    #[account(3, writable, name = "points", desc = "3-N: Point accounts to withdraw")]
+    #[account(
+        4,
+        writable,
+        name = "nullifiers",
+        desc = "N+1-K: Nullifier accounts (PDAs), one per point"
+    )]
+    #[account(5, name = "system_program", desc = "K+1: System program")]
+    #[account(6, writable, name = "user_activity", desc = "K+2: User activity account (PDA)")]
+    #[account(
+        7,
+        writable,
+        name = "nullifier_filter",
+        desc = "K+3: Nullifier filter account (PDA; may not exist yet)"
+    )]
+    #[account(
+        8,
+        writable,
+        name = "reward_account",
+        desc = "K+4: Reward account (PDA; may not exist)"
+    )]
+    #[account(
+        9,
+        writable,
+        name = "withdrawal_queue",
+        desc = "K+5: Withdrawal queue account (PDA, created on first use)"
+    )]
+    #[account(
+        10,
+        name = "instructions_sysvar",
+        desc = "K+6: Instructions sysvar, read when `ProtocolState::reject_cpi` is set"
+    )]
+    #[account(
+        11,
+        name = "nullifier_shards",
+        desc = "K+7..: Nullifier shard accounts (PDAs), one per nullifier prefix"
+    )]
     RequestWithdrawal {
         point_ids: Vec<Pubkey>,
+        /// The points' key images, in order
         nullifiers: Vec<[u8; 32]>,
+        /// Any value not yet used by this user for a request or withdrawal batch
+        nonce: u64,
+        /// Borsh-encoded `crypto::ring::RingSignature` by the points' keys over
+        /// `WithdrawalRequest::spend_message` of the request
+        spend_signature: Vec<u8>,
     },
     
     /// Complete withdrawal after delay
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` User account
-    /// 1. `[writable]` User USDT token account
-    /// 2. `[writable]` Treasury USDT token account
-    /// 3. `[writable]` Protocol state account
-    /// 4. `[writable]` Withdrawal request account
+    /// 0. `[signer, writable]` User account (pays epoch stats rent on first use)
+    /// 1. `[writable]` User token account for the request's mint
+    /// 2. `[writable]` Treasury token account for the request's mint
+    /// 3. `[writable]` Protocol state account (PDA)
+    /// 4. `[writable]` Withdrawal request account (PDA)
     /// 5. `[]` Treasury authority (PDA)
-    /// 6. `[]` Token program
-    /// 7. `[]` Clock sysvar
-    CompleteWithdrawal,
+    /// 6. `[]` Token program (SPL Token or Token-2022, whichever owns the mint)
+    /// 7. `[]` Deny list account (PDA; may not exist yet)
+    /// 8. `[]` Association set account (PDA; may not exist yet)
+    /// 9. `[]` Mint the withdrawn points were deposited in
+    /// 10. `[writable]` Supported mint account (PDA; unused for USDT)
+    /// 11. `[]` Fee exemptions account (PDA; may not exist yet)
+    /// 12. `[writable]` Epoch stats account (PDA of the current epoch)
+    /// 13. `[]` System program
+    /// 14. `[writable]` Withdrawal queue account (PDA; may not exist yet)
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "user",
+        desc = "User account (pays epoch stats rent on first use)"
+    )]
+    #[account(1, writable, name = "user_token", desc = "User token account for the request's mint")]
+    #[account(
+        2,
+        writable,
+        name = "treasury_token",
+        desc = "Treasury token account for the request's mint"
+    )]
+    #[account(3, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(4, writable, name = "withdrawal_request", desc = "Withdrawal request account (PDA)")]
+    #[account(5, name = "treasury_authority", desc = "Treasury authority (PDA)")]
+    #[account(
+        6,
+        name = "token_program",
+        desc = "Token program (SPL Token or Token-2022, whichever owns the mint)"
+    )]
+    #[account(7, name = "deny_list", desc = "Deny list account (PDA; may not exist yet)")]
+    #[account(
+        8,
+        name = "association_set",
+        desc = "Association set account (PDA; may not exist yet)"
+    )]
+    #[account(
+        9,
+        name = "mint",
+        desc = "Mint the withdrawn points were deposited in"
+    )]
+    #[account(
+        10,
+        writable,
+        name = "supported_mint",
+        desc = "Supported mint account (PDA; unused for USDT)"
+    )]
+    #[account(
+        11,
+        name = "fee_exemptions",
+        desc = "Fee exemptions account (PDA; may not exist yet)"
+    )]
+    #[account(
+        12,
+        writable,
+        name = "epoch_stats",
+        desc = "Epoch stats account (PDA of the current epoch)"
+    )]
+    #[account(13, name = "system_program", desc = "System program")]
+    #[account(
+        14,
+        writable,
+        name = "withdrawal_queue",
+        desc = "Withdrawal queue account (PDA; may not exist yet)"
+    )]
+    CompleteWithdrawal {
+        /// Close the user's wSOL token account afterwards, paying it out as SOL
+        unwrap_sol: bool,
+    },
     
-    /// Cancel withdrawal
+    /// Cancel withdrawal, reactivating the points or burning them when `permanent`
+    /// 
+    /// The request's rate limit and reward counters are rolled back; burned points also
+    /// release their value from `value_locked`.
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` User account
-    /// 1. `[writable]` Withdrawal request account
-    /// 2-N. `[writable]` Point accounts
+    /// 0. `[signer, writable]` User account (receives released nullifier rent)
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Withdrawal request account (PDA)
+    /// 3-N. `[writable]` Point accounts, in request order
+    /// N+1-K. `[writable]` Nullifier accounts (PDAs), one per point
+    /// K+1. `[writable]` User activity account (PDA)
+    /// K+2. `[writable]` Reward account (PDA; may not exist)
+    /// K+3. `[writable]` Withdrawal queue account (PDA; may not exist yet)
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "user",
+        desc = "User account (receives released nullifier rent)"
+    )]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "withdrawal_request", desc = "Withdrawal request account (PDA)")]
+    #[account(3, writable, name = "points", desc = "3-N: Point accounts, in request order")]
+    #[account(
+        4,
+        writable,
+        name = "nullifiers",
+        desc = "N+1-K: Nullifier accounts (PDAs), one per point"
+    )]
+    #[account(5, writable, name = "user_activity", desc = "K+1: User activity account (PDA)")]
+    #[account(
+        6,
+        writable,
+        name = "reward_account",
+        desc = "K+2: Reward account (PDA; may not exist)"
+    )]
+    #[account(
+        7,
+        writable,
+        name = "withdrawal_queue",
+        desc = "K+3: Withdrawal queue account (PDA; may not exist yet)"
+    )]
     CancelWithdrawal {
         permanent: bool,
     },
     
-    /// Update protocol fees (admin only)
+    /// Queue new protocol fees, applied after `CONFIG_TIMELOCK` (admin only)
+    /// 
+    /// Replaces any queued change; passing the current rates cancels it.
     /// 
     /// Accounts expected:
     /// 0. `[signer]` Protocol authority
-    /// 1. `[writable]` Protocol state account
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Audit log account (PDA), once `CreateAuditLog` has run
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "audit_log",
+        desc = "Audit log account (PDA), once `CreateAuditLog` has run"
+    )]
     UpdateFees {
         deposit_fee_rate: u16,
         withdrawal_fee_rate: u16,
     },
     
-    /// Pause/unpause protocol (admin only)
+    /// Pause/unpause every operation at once (admin only, or the guardian to pause)
+    /// 
+    /// Pausing is immediate; unpausing is queued for `ApplyUnpause` after `UNPAUSE_TIMELOCK`.
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` Protocol authority
-    /// 1. `[writable]` Protocol state account
+    /// 0. `[signer]` Protocol authority, or the guardian when pausing
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Audit log account (PDA), once `CreateAuditLog` has run
+    #[account(
+        0,
+        signer,
+        name = "protocol_authority",
+        desc = "Protocol authority, or the guardian when pausing"
+    )]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "audit_log",
+        desc = "Audit log account (PDA), once `CreateAuditLog` has run"
+    )]
     SetPaused {
         paused: bool,
     },
+    
+    /// Create a denomination pool (permissionless, bonded)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Creator account (pays rent and bond)
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Pool config account (PDA)
+    /// 3. `[]` Pool mint
+    /// 4. `[]` System program
+    /// 5. `[]` Circuit account (PDA)
+    #[account(0, writable, signer, name = "creator", desc = "Creator account (pays rent and bond)")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "pool_config", desc = "Pool config account (PDA)")]
+    #[account(3, name = "pool_mint", desc = "Pool mint")]
+    #[account(4, name = "system_program", desc = "System program")]
+    #[account(5, name = "circuit", desc = "Circuit account (PDA)")]
+    CreatePool {
+        denomination: u64,
+        /// Registered spend circuit, fixed for the lifetime of the pool
+        circuit_id: u16,
+    },
+    
+    /// Set bounds for permissionless pool creation (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    SetPoolBounds {
+        min_denomination: u64,
+        max_denomination: u64,
+        creation_bond: u64,
+    },
+    
+    /// Register a spend circuit that pools can select (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Circuit account (PDA)
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "circuit", desc = "Circuit account (PDA)")]
+    #[account(3, writable, signer, name = "payer", desc = "Payer")]
+    #[account(4, name = "system_program", desc = "System program")]
+    RegisterCircuit {
+        circuit_id: u16,
+        tree_depth: u8,
+        max_inputs: u8,
+        max_outputs: u8,
+        verifying_key_hash: [u8; 32],
+    },
+    
+    /// Require or waive commitment opening proofs on deposit (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    SetCommitmentValidation {
+        required: bool,
+    },
+    
+    /// Open a withdrawal batch for requests too large for one transaction
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` User account (pays rent)
+    /// 1. `[writable]` Withdrawal batch account (PDA)
+    /// 2. `[]` System program
+    #[account(0, writable, signer, name = "user", desc = "User account (pays rent)")]
+    #[account(1, writable, name = "withdrawal_batch", desc = "Withdrawal batch account (PDA)")]
+    #[account(2, name = "system_program", desc = "System program")]
+    OpenWithdrawalBatch {
+        batch_id: u64,
+    },
+    
+    /// Append points to an open withdrawal batch
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` User account (pays nullifier rent)
+    /// 1. `[writable]` Withdrawal batch account (PDA)
+    /// 2-N. `[writable]` Point accounts to withdraw
+    /// N+1-K. `[writable]` Nullifier accounts (PDAs), one per point
+    /// K+1. `[]` System program
+    /// K+2. `[]` Protocol state account (PDA)
+    /// K+3. `[writable]` Nullifier filter account (PDA; may not exist yet)
+    /// K+4.. `[]` Nullifier shard accounts (PDAs), one per nullifier prefix
+    #[account(0, writable, signer, name = "user", desc = "User account (pays nullifier rent)")]
+    #[account(1, writable, name = "withdrawal_batch", desc = "Withdrawal batch account (PDA)")]
+    #[account(2, writable, name = "points", desc = "2-N: Point accounts to withdraw")]
+    #[account(
+        3,
+        writable,
+        name = "nullifiers",
+        desc = "N+1-K: Nullifier accounts (PDAs), one per point"
+    )]
+    #[account(4, name = "system_program", desc = "K+1: System program")]
+    #[account(5, name = "protocol_state", desc = "K+2: Protocol state account (PDA)")]
+    #[account(
+        6,
+        writable,
+        name = "nullifier_filter",
+        desc = "K+3: Nullifier filter account (PDA; may not exist yet)"
+    )]
+    #[account(
+        7,
+        name = "nullifier_shards",
+        desc = "K+4..: Nullifier shard accounts (PDAs), one per nullifier prefix"
+    )]
+    AppendToWithdrawal {
+        batch_id: u64,
+        point_ids: Vec<Pubkey>,
+        /// The points' key images, in order
+        nullifiers: Vec<[u8; 32]>,
+        /// Borsh-encoded `crypto::ring::RingSignature` by the points' keys over
+        /// `WithdrawalRequest::spend_message` of the request the batch finalizes into
+        spend_signature: Vec<u8>,
+    },
+    
+    /// Turn a withdrawal batch into a withdrawal request and close the batch
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` User account (receives batch rent, pays request and activity rent)
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Withdrawal batch account (PDA)
+    /// 3. `[writable]` Withdrawal request account (PDA, with `batch_id` as its nonce)
+    /// 4. `[writable]` User activity account (PDA)
+    /// 5. `[]` System program
+    /// 6. `[writable]` Reward account (PDA; may not exist)
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "user",
+        desc = "User account (receives batch rent, pays request and activity rent)"
+    )]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "withdrawal_batch", desc = "Withdrawal batch account (PDA)")]
+    #[account(
+        3,
+        writable,
+        name = "withdrawal_request",
+        desc = "Withdrawal request account (PDA, with `batch_id` as its nonce)"
+    )]
+    #[account(4, writable, name = "user_activity", desc = "User activity account (PDA)")]
+    #[account(5, name = "system_program", desc = "System program")]
+    #[account(6, writable, name = "reward_account", desc = "Reward account (PDA; may not exist)")]
+    FinalizeWithdrawal {
+        batch_id: u64,
+    },
+    
+    /// Appoint the guardian that maintains the security-event log and can pause (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Audit log account (PDA), once `CreateAuditLog` has run
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "audit_log",
+        desc = "Audit log account (PDA), once `CreateAuditLog` has run"
+    )]
+    SetGuardian {
+        guardian: Pubkey,
+    },
+    
+    /// Record a security event in the on-chain incident log (guardian only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Guardian (pays rent)
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Security event account (PDA, next index)
+    /// 3. `[]` System program
+    #[account(0, writable, signer, name = "guardian", desc = "Guardian (pays rent)")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "security_event",
+        desc = "Security event account (PDA, next index)"
+    )]
+    #[account(3, name = "system_program", desc = "System program")]
+    RecordSecurityEvent {
+        kind: SecurityEventKind,
+        severity: SecuritySeverity,
+        subject: Pubkey,
+        message: String,
+    },
+    
+    /// Mark a security event as resolved (guardian only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Guardian
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Security event account (PDA)
+    #[account(0, signer, name = "guardian", desc = "Guardian")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "security_event", desc = "Security event account (PDA)")]
+    ResolveSecurityEvent {
+        index: u64,
+    },
+    
+    /// Split a point into several points whose masses sum to the original
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Point holder (pays rent)
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Point account to split
+    /// 3. `[writable]` Nullifier account (PDA) for the split point
+    /// 4-N. `[writable]` Output point accounts (PDAs), one per output commitment
+    /// N+1. `[]` System program
+    /// N+2. `[writable]` Commitment tree account (PDA)
+    /// N+3. `[writable]` Root history account (PDA)
+    /// N+4. `[writable]` Nullifier filter account (PDA; may not exist yet)
+    /// N+5. `[]` Nullifier shard account (PDA) for the split point's nullifier
+    #[account(0, writable, signer, name = "point_holder", desc = "Point holder (pays rent)")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "point", desc = "Point account to split")]
+    #[account(
+        3,
+        writable,
+        name = "nullifier",
+        desc = "Nullifier account (PDA) for the split point"
+    )]
+    #[account(
+        4,
+        writable,
+        name = "output_points",
+        desc = "4-N: Output point accounts (PDAs), one per output commitment"
+    )]
+    #[account(5, name = "system_program", desc = "N+1: System program")]
+    #[account(6, writable, name = "commitment_tree", desc = "N+2: Commitment tree account (PDA)")]
+    #[account(7, writable, name = "root_history", desc = "N+3: Root history account (PDA)")]
+    #[account(
+        8,
+        writable,
+        name = "nullifier_filter",
+        desc = "N+4: Nullifier filter account (PDA; may not exist yet)"
+    )]
+    #[account(
+        9,
+        name = "nullifier_shard",
+        desc = "N+5: Nullifier shard account (PDA) for the split point's nullifier"
+    )]
+    SplitPoint {
+        /// The split point's key image
+        nullifier: [u8; 32],
+        output_commitments: Vec<[u8; 32]>,
+        output_masses: Vec<u64>,
+        /// One opening proof per output; required when the protocol enforces openings
+        opening_proofs: Vec<OpeningProof>,
+        /// Borsh-encoded `crypto::ring::RingSignature` by the point's key over
+        /// `FloatingPoint::split_message`
+        spend_signature: Vec<u8>,
+    },
+    
+    /// Hand a point to a new owner without a ZK proof (signed by the current holder)
+    /// 
+    /// The point moves to the PDA of `new_commitment`; the old account is closed.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Current holder (pays the new point's rent, receives the old one's)
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Point account (PDA)
+    /// 3. `[writable]` Commitment tree account (PDA)
+    /// 4. `[writable]` Root history account (PDA)
+    /// 5. `[writable]` New point account (PDA of `new_commitment`)
+    /// 6. `[]` System program
+    /// 7. `[writable]` Nullifier account (PDA) for the old point
+    /// 8. `[writable]` Nullifier filter account (PDA; may not exist yet)
+    /// 9. `[]` Nullifier shard account (PDA) for the old point's nullifier
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "current_holder",
+        desc = "Current holder (pays the new point's rent, receives the old one's)"
+    )]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "point", desc = "Point account (PDA)")]
+    #[account(3, writable, name = "commitment_tree", desc = "Commitment tree account (PDA)")]
+    #[account(4, writable, name = "root_history", desc = "Root history account (PDA)")]
+    #[account(
+        5,
+        writable,
+        name = "new_point",
+        desc = "New point account (PDA of `new_commitment`)"
+    )]
+    #[account(6, name = "system_program", desc = "System program")]
+    #[account(7, writable, name = "nullifier", desc = "Nullifier account (PDA) for the old point")]
+    #[account(
+        8,
+        writable,
+        name = "nullifier_filter",
+        desc = "Nullifier filter account (PDA; may not exist yet)"
+    )]
+    #[account(
+        9,
+        name = "nullifier_shard",
+        desc = "Nullifier shard account (PDA) for the old point's nullifier"
+    )]
+    TransferPoint {
+        new_owner: Pubkey,
+        /// Commitment chosen by the new owner, appended to the commitment tree
+        new_commitment: [u8; 32],
+        /// The old point's key image, spent so its commitment can't be paid with again
+        nullifier: [u8; 32],
+        /// Borsh-encoded `crypto::ring::RingSignature` by the point's key over
+        /// `FloatingPoint::transfer_message`
+        spend_signature: Vec<u8>,
+    },
+    
+    /// Report commitment tree occupancy and time-to-full as `TreeHealth` return data
+    /// 
+    /// Permissionless; also advances the deposit-rate sampling window.
+    /// 
+    /// Accounts expected:
+    /// 0. `[writable]` Commitment tree account (PDA)
+    #[account(0, writable, name = "commitment_tree", desc = "Commitment tree account (PDA)")]
+    ReportTreeHealth,
+    
+    /// Archive the commitment tree and start a fresh one
+    /// 
+    /// The authority may roll over at any time; anyone may once fewer than
+    /// `CommitmentTree::AUTO_ROLLOVER_REMAINING` leaves are left. The authority either pays
+    /// itself or signs as the trailing account, which lets the multisig roll over early.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Payer (pays rent), or the protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Commitment tree account (PDA)
+    /// 3. `[writable]` Archived tree account (PDA for the current generation)
+    /// 4. `[writable]` Root history account (PDA)
+    /// 5. `[]` System program
+    /// 6. `[signer]` Protocol authority, for an early rollover paid by someone else
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "payer",
+        desc = "Payer (pays rent), or the protocol authority"
+    )]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "commitment_tree", desc = "Commitment tree account (PDA)")]
+    #[account(
+        3,
+        writable,
+        name = "archived_tree",
+        desc = "Archived tree account (PDA for the current generation)"
+    )]
+    #[account(4, writable, name = "root_history", desc = "Root history account (PDA)")]
+    #[account(5, name = "system_program", desc = "System program")]
+    #[account(
+        6,
+        signer,
+        name = "protocol_authority",
+        desc = "Protocol authority, for an early rollover paid by someone else"
+    )]
+    RolloverTree,
+    
+    /// Replace the deposit denominations (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    SetDenominations {
+        /// Ascending multiples of `POINT_VALUE`, at most `MAX_DENOMINATIONS`
+        denominations: Vec<u64>,
+    },
+    
+    /// Complete a pending withdrawal before its delay ends, paying the emergency penalty
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` User account (the requester; pays epoch stats rent on first use)
+    /// 1. `[writable]` User token account for the request's mint
+    /// 2. `[writable]` Treasury token account for the request's mint
+    /// 3. `[writable]` Protocol state account (PDA)
+    /// 4. `[writable]` Withdrawal request account (PDA)
+    /// 5. `[]` Treasury authority (PDA)
+    /// 6. `[]` Token program (SPL Token or Token-2022, whichever owns the mint)
+    /// 7. `[]` Deny list account (PDA; may not exist yet)
+    /// 8. `[]` Association set account (PDA; may not exist yet)
+    /// 9. `[]` Mint the withdrawn points were deposited in
+    /// 10. `[writable]` Supported mint account (PDA; unused for USDT)
+    /// 11. `[]` Fee exemptions account (PDA; may not exist yet)
+    /// 12. `[writable]` Epoch stats account (PDA of the current epoch)
+    /// 13. `[]` System program
+    /// 14. `[writable]` Withdrawal queue account (PDA; may not exist yet)
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "user",
+        desc = "User account (the requester; pays epoch stats rent on first use)"
+    )]
+    #[account(1, writable, name = "user_token", desc = "User token account for the request's mint")]
+    #[account(
+        2,
+        writable,
+        name = "treasury_token",
+        desc = "Treasury token account for the request's mint"
+    )]
+    #[account(3, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(4, writable, name = "withdrawal_request", desc = "Withdrawal request account (PDA)")]
+    #[account(5, name = "treasury_authority", desc = "Treasury authority (PDA)")]
+    #[account(
+        6,
+        name = "token_program",
+        desc = "Token program (SPL Token or Token-2022, whichever owns the mint)"
+    )]
+    #[account(7, name = "deny_list", desc = "Deny list account (PDA; may not exist yet)")]
+    #[account(
+        8,
+        name = "association_set",
+        desc = "Association set account (PDA; may not exist yet)"
+    )]
+    #[account(
+        9,
+        name = "mint",
+        desc = "Mint the withdrawn points were deposited in"
+    )]
+    #[account(
+        10,
+        writable,
+        name = "supported_mint",
+        desc = "Supported mint account (PDA; unused for USDT)"
+    )]
+    #[account(
+        11,
+        name = "fee_exemptions",
+        desc = "Fee exemptions account (PDA; may not exist yet)"
+    )]
+    #[account(
+        12,
+        writable,
+        name = "epoch_stats",
+        desc = "Epoch stats account (PDA of the current epoch)"
+    )]
+    #[account(13, name = "system_program", desc = "System program")]
+    #[account(
+        14,
+        writable,
+        name = "withdrawal_queue",
+        desc = "Withdrawal queue account (PDA; may not exist yet)"
+    )]
+    EmergencyWithdraw {
+        /// Close the user's wSOL token account afterwards, paying it out as SOL
+        unwrap_sol: bool,
+    },
+    
+    /// Set the emergency withdrawal penalty (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Audit log account (PDA), once `CreateAuditLog` has run
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "audit_log",
+        desc = "Audit log account (PDA), once `CreateAuditLog` has run"
+    )]
+    SetEmergencyPenalty {
+        /// Basis points, at most `ProtocolState::MAX_EMERGENCY_PENALTY_RATE`
+        penalty_rate: u16,
+    },
+    
+    /// Replace the encrypted contact book, creating it on first use
+    /// 
+    /// Fails with `StaleContactBook` unless `expected_revision` matches the
+    /// stored revision (0 for a book that doesn't exist yet).
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Owner (pays rent)
+    /// 1. `[writable]` Contact book account (PDA)
+    /// 2. `[]` System program
+    #[account(0, writable, signer, name = "owner", desc = "Owner (pays rent)")]
+    #[account(1, writable, name = "contact_book", desc = "Contact book account (PDA)")]
+    #[account(2, name = "system_program", desc = "System program")]
+    UpdateContactBook {
+        expected_revision: u64,
+        ciphertext: Vec<u8>,
+    },
+    
+    /// Delete the contact book and reclaim its rent
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Owner
+    /// 1. `[writable]` Contact book account (PDA)
+    #[account(0, writable, signer, name = "owner", desc = "Owner")]
+    #[account(1, writable, name = "contact_book", desc = "Contact book account (PDA)")]
+    CloseContactBook,
+    
+    /// Propose a new protocol authority; the default pubkey withdraws a proposal (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Audit log account (PDA), once `CreateAuditLog` has run
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "audit_log",
+        desc = "Audit log account (PDA), once `CreateAuditLog` has run"
+    )]
+    ProposeAuthority {
+        new_authority: Pubkey,
+    },
+    
+    /// Accept a pending authority proposal
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Proposed authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Audit log account (PDA), once `CreateAuditLog` has run
+    #[account(0, signer, name = "proposed_authority", desc = "Proposed authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "audit_log",
+        desc = "Audit log account (PDA), once `CreateAuditLog` has run"
+    )]
+    AcceptAuthority,
+    
+    /// Create the admin multisig that can take over the protocol authority (admin only)
+    /// 
+    /// Hand the authority to it with `ProposeAuthority` followed by an
+    /// `AdminAction::AcceptAuthority` proposal.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Protocol authority (pays rent)
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Admin multisig account (PDA)
+    /// 3. `[]` System program
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "protocol_authority",
+        desc = "Protocol authority (pays rent)"
+    )]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "admin_multisig", desc = "Admin multisig account (PDA)")]
+    #[account(3, name = "system_program", desc = "System program")]
+    CreateAdminMultisig {
+        /// At most `MAX_ADMIN_SIGNERS` distinct keys
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    },
+    
+    /// Propose an admin action; counts as the proposer's approval
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Multisig signer (pays rent)
+    /// 1. `[writable]` Admin multisig account
+    /// 2. `[writable]` Admin proposal account (PDA)
+    /// 3. `[]` System program
+    #[account(0, writable, signer, name = "multisig_signer", desc = "Multisig signer (pays rent)")]
+    #[account(1, writable, name = "admin_multisig", desc = "Admin multisig account")]
+    #[account(2, writable, name = "admin_proposal", desc = "Admin proposal account (PDA)")]
+    #[account(3, name = "system_program", desc = "System program")]
+    ProposeAdminAction {
+        action: AdminAction,
+    },
+    
+    /// Approve a pending admin proposal
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Multisig signer
+    /// 1. `[]` Admin multisig account
+    /// 2. `[writable]` Admin proposal account
+    #[account(0, signer, name = "multisig_signer", desc = "Multisig signer")]
+    #[account(1, name = "admin_multisig", desc = "Admin multisig account")]
+    #[account(2, writable, name = "admin_proposal", desc = "Admin proposal account")]
+    ApproveAdminAction,
+    
+    /// Execute an admin proposal that has reached the threshold (permissionless)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Executor (pays for any account the action creates)
+    /// 1. `[writable]` Admin multisig account
+    /// 2. `[writable]` Admin proposal account
+    /// 3. `[writable]` Protocol state account (PDA)
+    /// 4. `[]` This program, for the authority instruction invoked by the multisig
+    /// 5.. Further accounts the action needs (for deny-list, association set, relayer config
+    ///     and state migration actions: that account and the system program)
+    ///     followed, for actions recorded in the audit log once it exists, by the audit log
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "executor",
+        desc = "Executor (pays for any account the action creates)"
+    )]
+    #[account(1, writable, name = "admin_multisig", desc = "Admin multisig account")]
+    #[account(2, writable, name = "admin_proposal", desc = "Admin proposal account")]
+    #[account(3, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        4,
+        name = "program",
+        desc = "This program, for the authority instruction invoked by the multisig"
+    )]
+    #[account(
+        5,
+        name = "action_accounts",
+        desc = "5..: Further accounts the action needs"
+    )]
+    ExecuteAdminAction,
+    
+    /// Commit a queued fee change once its timelock has passed (permissionless)
+    /// 
+    /// Accounts expected:
+    /// 0. `[writable]` Protocol state account (PDA)
+    #[account(0, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    ApplyPendingConfig,
+    
+    /// Set the account that receives swept fees (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Audit log account (PDA), once `CreateAuditLog` has run
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "audit_log",
+        desc = "Audit log account (PDA), once `CreateAuditLog` has run"
+    )]
+    SetFeeReceiver {
+        fee_receiver: Pubkey,
+    },
+    
+    /// Sweep a mint's accrued fees from the treasury to the fee receiver (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Treasury token account for the mint
+    /// 3. `[]` Treasury authority (PDA)
+    /// 4. `[writable]` Fee receiver token account for the mint
+    /// 5. `[]` Token program (SPL Token or Token-2022, whichever owns the mint)
+    /// 6. `[]` Mint (USDT or a supported mint)
+    /// 7. `[writable]` Supported mint account (PDA; unused for USDT)
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "treasury_token", desc = "Treasury token account for the mint")]
+    #[account(3, name = "treasury_authority", desc = "Treasury authority (PDA)")]
+    #[account(
+        4,
+        writable,
+        name = "fee_receiver_token",
+        desc = "Fee receiver token account for the mint"
+    )]
+    #[account(
+        5,
+        name = "token_program",
+        desc = "Token program (SPL Token or Token-2022, whichever owns the mint)"
+    )]
+    #[account(6, name = "mint", desc = "Mint (USDT or a supported mint)")]
+    #[account(
+        7,
+        writable,
+        name = "supported_mint",
+        desc = "Supported mint account (PDA; unused for USDT)"
+    )]
+    CollectFees,
+    
+    /// Pause deposits, privacy payments and withdrawals independently (admin only)
+    /// 
+    /// Newly set flags apply at once. Cleared ones stay set until `ApplyUnpause` runs
+    /// after `UNPAUSE_TIMELOCK`; a call that clears nothing cancels a queued unpause.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Audit log account (PDA), once `CreateAuditLog` has run
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "audit_log",
+        desc = "Audit log account (PDA), once `CreateAuditLog` has run"
+    )]
+    SetPausedFlags {
+        /// `ProtocolState::PAUSE_*` bits; a cleared bit resumes that operation
+        flags: u8,
+    },
+    
+    /// Set the per-user deposit and withdrawal limits (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    SetRateLimits {
+        limits: RateLimits,
+    },
+    
+    /// Set how many slots a point must age before it can be spent or withdrawn (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Audit log account (PDA), once `CreateAuditLog` has run
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "audit_log",
+        desc = "Audit log account (PDA), once `CreateAuditLog` has run"
+    )]
+    SetMinExitSlots {
+        /// At most `ProtocolState::MAX_MIN_EXIT_SLOTS`
+        slots: u64,
+    },
+    
+    /// Bar an address or commitment from depositing and withdrawing (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Deny list account (PDA, created on first use)
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    /// 5. `[writable]` Audit log account (PDA), once `CreateAuditLog` has run
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "deny_list",
+        desc = "Deny list account (PDA, created on first use)"
+    )]
+    #[account(3, writable, signer, name = "payer", desc = "Payer")]
+    #[account(4, name = "system_program", desc = "System program")]
+    #[account(
+        5,
+        writable,
+        name = "audit_log",
+        desc = "Audit log account (PDA), once `CreateAuditLog` has run"
+    )]
+    AddToDenyList {
+        entry: [u8; 32],
+    },
+    
+    /// Lift a deny-list entry (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Deny list account (PDA)
+    /// 3. `[writable]` Audit log account (PDA), once `CreateAuditLog` has run
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "deny_list", desc = "Deny list account (PDA)")]
+    #[account(
+        3,
+        writable,
+        name = "audit_log",
+        desc = "Audit log account (PDA), once `CreateAuditLog` has run"
+    )]
+    RemoveFromDenyList {
+        entry: [u8; 32],
+    },
+    
+    /// Replace the flagged deposit set root, and optionally the circuit's verifying key
+    /// (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Association set account (PDA, created on first use)
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "association_set",
+        desc = "Association set account (PDA, created on first use)"
+    )]
+    #[account(3, writable, signer, name = "payer", desc = "Payer")]
+    #[account(4, name = "system_program", desc = "System program")]
+    UpdateAssociationSet {
+        /// Must be a BN254 scalar field element
+        flagged_root: [u8; 32],
+        /// Require a proof against the current root before withdrawals pay out
+        required: bool,
+        /// Needed when the account is created
+        verifying_key: Option<Groth16VerifyingKey>,
+    },
+    
+    /// Prove that a pending withdrawal's points don't descend from the flagged deposit set
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` User account (the requester, or the relayer of a relayed request)
+    /// 1. `[writable]` Withdrawal request account (PDA)
+    /// 2. `[]` Association set account (PDA)
+    /// 3. `[]` Groth16 verifier program
+    #[account(
+        0,
+        signer,
+        name = "user",
+        desc = "User account (the requester, or the relayer of a relayed request)"
+    )]
+    #[account(1, writable, name = "withdrawal_request", desc = "Withdrawal request account (PDA)")]
+    #[account(2, name = "association_set", desc = "Association set account (PDA)")]
+    #[account(3, name = "verifier_program", desc = "Groth16 verifier program")]
+    ProveWithdrawalCompliance {
+        proof: Groth16Proof,
+    },
+    
+    /// Accept deposits in a mint besides USDT, or update its settings (admin only)
+    /// 
+    /// Fee rates of an already supported mint can only be lowered.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Supported mint account (PDA, created on first use)
+    /// 3. `[]` Mint
+    /// 4. `[signer, writable]` Payer
+    /// 5. `[]` System program
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "supported_mint",
+        desc = "Supported mint account (PDA, created on first use)"
+    )]
+    #[account(3, name = "mint", desc = "Mint")]
+    #[account(4, writable, signer, name = "payer", desc = "Payer")]
+    #[account(5, name = "system_program", desc = "System program")]
+    AddSupportedMint {
+        mint: Pubkey,
+        config: SupportedMintConfig,
+    },
+    
+    /// Keep more recent roots spendable by growing the root history (admin only)
+    /// 
+    /// The account is reallocated in place and the payer covers the extra rent.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Root history account (PDA)
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "root_history", desc = "Root history account (PDA)")]
+    #[account(3, writable, signer, name = "payer", desc = "Payer")]
+    #[account(4, name = "system_program", desc = "System program")]
+    ResizeRootHistory {
+        /// New number of roots, larger than the current one and at most
+        /// `RootHistory::MAX_SIZE`
+        capacity: u16,
+    },
+    
+    /// Allocate the nullifier bloom filter, at most 10 KiB per call (admin only)
+    /// 
+    /// The first call creates the account; repeat it until the account reaches
+    /// `NullifierFilter::LEN`, which takes `nullifier_filter_calls()` calls in total. Spends
+    /// start recording into the filter once the final call completes it.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Nullifier filter account (PDA)
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "nullifier_filter", desc = "Nullifier filter account (PDA)")]
+    #[account(3, writable, signer, name = "payer", desc = "Payer")]
+    #[account(4, name = "system_program", desc = "System program")]
+    ExtendNullifierFilter,
+    
+    /// Set up the Bubblegum tree that points are compressed into (admin only)
+    /// 
+    /// The caller allocates `merkle_tree` beforehand, owned by the account compression
+    /// program and sized for `max_depth` and `max_buffer_size`.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Point tree account (PDA)
+    /// 3. `[writable]` Bubblegum tree config (PDA of the Merkle tree)
+    /// 4. `[writable]` Merkle tree account
+    /// 5. `[signer, writable]` Payer
+    /// 6. `[]` Noop program
+    /// 7. `[]` Account compression program
+    /// 8. `[]` Bubblegum program
+    /// 9. `[]` System program
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "point_tree", desc = "Point tree account (PDA)")]
+    #[account(
+        3,
+        writable,
+        name = "bubblegum_tree_config",
+        desc = "Bubblegum tree config (PDA of the Merkle tree)"
+    )]
+    #[account(4, writable, name = "merkle_tree", desc = "Merkle tree account")]
+    #[account(5, writable, signer, name = "payer", desc = "Payer")]
+    #[account(6, name = "noop_program", desc = "Noop program")]
+    #[account(7, name = "account_compression_program", desc = "Account compression program")]
+    #[account(8, name = "bubblegum_program", desc = "Bubblegum program")]
+    #[account(9, name = "system_program", desc = "System program")]
+    CreatePointTree {
+        max_depth: u32,
+        max_buffer_size: u32,
+    },
+    
+    /// Close a point account and mint it to the holder as a compressed NFT
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Point holder (receives the point's rent)
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Point account (PDA)
+    /// 3. `[writable]` Point tree account (PDA)
+    /// 4. `[writable]` Bubblegum tree config (PDA of the Merkle tree)
+    /// 5. `[writable]` Merkle tree account
+    /// 6. `[]` Noop program
+    /// 7. `[]` Account compression program
+    /// 8. `[]` Bubblegum program
+    /// 9. `[]` System program
+    /// 10. `[writable]` Nullifier account (PDA) for the point
+    /// 11. `[writable]` Nullifier filter account (PDA; may not exist yet)
+    /// 12. `[]` Nullifier shard account (PDA) for the point's nullifier
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "point_holder",
+        desc = "Point holder (receives the point's rent)"
+    )]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "point", desc = "Point account (PDA)")]
+    #[account(3, writable, name = "point_tree", desc = "Point tree account (PDA)")]
+    #[account(
+        4,
+        writable,
+        name = "bubblegum_tree_config",
+        desc = "Bubblegum tree config (PDA of the Merkle tree)"
+    )]
+    #[account(5, writable, name = "merkle_tree", desc = "Merkle tree account")]
+    #[account(6, name = "noop_program", desc = "Noop program")]
+    #[account(7, name = "account_compression_program", desc = "Account compression program")]
+    #[account(8, name = "bubblegum_program", desc = "Bubblegum program")]
+    #[account(9, name = "system_program", desc = "System program")]
+    #[account(10, writable, name = "nullifier", desc = "Nullifier account (PDA) for the point")]
+    #[account(
+        11,
+        writable,
+        name = "nullifier_filter",
+        desc = "Nullifier filter account (PDA; may not exist yet)"
+    )]
+    #[account(
+        12,
+        name = "nullifier_shard",
+        desc = "Nullifier shard account (PDA) for the point's nullifier"
+    )]
+    CompressPoint {
+        /// The point's key image; it is restored under a new commitment, so this one is spent
+        nullifier: [u8; 32],
+        /// Borsh-encoded `crypto::ring::RingSignature` by the point's key over
+        /// `FloatingPoint::compress_message`
+        spend_signature: Vec<u8>,
+    },
+    
+    /// Burn a compressed point NFT and restore the point under a new commitment
+    /// 
+    /// The point moves to the PDA of `new_commitment`, which is inserted into the commitment
+    /// tree as in `TransferPoint`, so the old commitment's address can't be squatted. The
+    /// cNFT must not be delegated.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Point holder (the cNFT owner, pays the point's rent)
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Point tree account (PDA)
+    /// 3. `[writable]` Bubblegum tree config (PDA of the Merkle tree)
+    /// 4. `[writable]` Merkle tree account
+    /// 5. `[]` Noop program
+    /// 6. `[]` Account compression program
+    /// 7. `[]` Bubblegum program
+    /// 8. `[]` System program
+    /// 9. `[writable]` New point account (PDA of `new_commitment`)
+    /// 10. `[writable]` Commitment tree account (PDA)
+    /// 11. `[writable]` Root history account (PDA)
+    /// 12-N. `[]` Merkle proof nodes of the cNFT's leaf, above the tree's canopy
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "point_holder",
+        desc = "Point holder (the cNFT owner, pays the point's rent)"
+    )]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "point_tree", desc = "Point tree account (PDA)")]
+    #[account(
+        3,
+        writable,
+        name = "bubblegum_tree_config",
+        desc = "Bubblegum tree config (PDA of the Merkle tree)"
+    )]
+    #[account(4, writable, name = "merkle_tree", desc = "Merkle tree account")]
+    #[account(5, name = "noop_program", desc = "Noop program")]
+    #[account(6, name = "account_compression_program", desc = "Account compression program")]
+    #[account(7, name = "bubblegum_program", desc = "Bubblegum program")]
+    #[account(8, name = "system_program", desc = "System program")]
+    #[account(
+        9,
+        writable,
+        name = "new_point",
+        desc = "New point account (PDA of `new_commitment`)"
+    )]
+    #[account(10, writable, name = "commitment_tree", desc = "Commitment tree account (PDA)")]
+    #[account(11, writable, name = "root_history", desc = "Root history account (PDA)")]
+    #[account(
+        12,
+        name = "merkle_proof_nodes",
+        desc = "12-N: Merkle proof nodes of the cNFT's leaf, above the tree's canopy"
+    )]
+    DecompressPoint {
+        point: CompressedPoint,
+        new_commitment: [u8; 32],
+        /// Root of the Bubblegum tree the proof is against
+        root: [u8; 32],
+        nonce: u64,
+        index: u32,
+    },
+    
+    /// Label a point as a Metaplex NFT in the holder's wallet
+    /// 
+    /// Mints one frozen token of a fresh mint with Token Metadata for the point's mass and
+    /// creation time. Display only; the NFT doesn't carry or move the point.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Point holder (pays rent)
+    /// 1. `[]` Point account (PDA)
+    /// 2. `[writable]` Point NFT mint (PDA of the point)
+    /// 3. `[writable]` Holder's associated token account for the NFT mint
+    /// 4. `[writable]` Token Metadata account of the NFT mint
+    /// 5. `[]` Point NFT authority (PDA)
+    /// 6. `[]` SPL Token program
+    /// 7. `[]` Associated Token Account program
+    /// 8. `[]` Token Metadata program
+    /// 9. `[]` System program
+    #[account(0, writable, signer, name = "point_holder", desc = "Point holder (pays rent)")]
+    #[account(1, name = "point", desc = "Point account (PDA)")]
+    #[account(2, writable, name = "point_nft_mint", desc = "Point NFT mint (PDA of the point)")]
+    #[account(
+        3,
+        writable,
+        name = "holder_token",
+        desc = "Holder's associated token account for the NFT mint"
+    )]
+    #[account(
+        4,
+        writable,
+        name = "token_metadata",
+        desc = "Token Metadata account of the NFT mint"
+    )]
+    #[account(5, name = "point_nft_authority", desc = "Point NFT authority (PDA)")]
+    #[account(6, name = "spl_token_program", desc = "SPL Token program")]
+    #[account(
+        7,
+        name = "associated_token_account_program",
+        desc = "Associated Token Account program"
+    )]
+    #[account(8, name = "token_metadata_program", desc = "Token Metadata program")]
+    #[account(9, name = "system_program", desc = "System program")]
+    AttachPointMetadata,
+    
+    /// Set the relayer fee cap (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Relayer config account (PDA, created on first use)
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "relayer_config",
+        desc = "Relayer config account (PDA, created on first use)"
+    )]
+    #[account(3, writable, signer, name = "payer", desc = "Payer")]
+    #[account(4, name = "system_program", desc = "System program")]
+    SetRelayerConfig {
+        /// Largest relayer fee in basis points of the withdrawn amount
+        max_fee_rate: u16,
+    },
+    
+    /// Request a withdrawal on the recipient's behalf, without their signature
+    /// 
+    /// The relayer pays all rent and fees; the points' spend signature binds them to the
+    /// recipient, the relayer and its fee, so the recipient never signs or pays, and the fee is
+    /// capped by `RelayerConfig`. The request lives
+    /// at `pda::find_withdrawal_request(recipient, nonce)` and only completes through
+    /// `CompleteRelayedWithdrawal`.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Relayer (pays request, nullifier and activity rent)
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Withdrawal request account (PDA of the recipient and nonce)
+    /// 3-N. `[writable]` Point accounts to withdraw
+    /// N+1-K. `[writable]` Nullifier accounts (PDAs), one per point
+    /// K+1. `[]` System program
+    /// K+2. `[writable]` User activity account (PDA of the recipient)
+    /// K+3. `[]` Relayer config account (PDA)
+    /// K+4. `[writable]` Nullifier filter account (PDA; may not exist yet)
+    /// K+5. `[writable]` Reward account (PDA of the recipient; may not exist)
+    /// K+6.. `[]` Nullifier shard accounts (PDAs), one per nullifier prefix
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "relayer",
+        desc = "Relayer (pays request, nullifier and activity rent)"
+    )]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "withdrawal_request",
+        desc = "Withdrawal request account (PDA of the recipient and nonce)"
+    )]
+    #[account(3, writable, name = "points", desc = "3-N: Point accounts to withdraw")]
+    #[account(
+        4,
+        writable,
+        name = "nullifiers",
+        desc = "N+1-K: Nullifier accounts (PDAs), one per point"
+    )]
+    #[account(5, name = "system_program", desc = "K+1: System program")]
+    #[account(
+        6,
+        writable,
+        name = "user_activity",
+        desc = "K+2: User activity account (PDA of the recipient)"
+    )]
+    #[account(7, name = "relayer_config", desc = "K+3: Relayer config account (PDA)")]
+    #[account(
+        8,
+        writable,
+        name = "nullifier_filter",
+        desc = "K+4: Nullifier filter account (PDA; may not exist yet)"
+    )]
+    #[account(
+        9,
+        writable,
+        name = "reward_account",
+        desc = "K+5: Reward account (PDA of the recipient; may not exist)"
+    )]
+    #[account(
+        10,
+        name = "nullifier_shards",
+        desc = "K+6..: Nullifier shard accounts (PDAs), one per nullifier prefix"
+    )]
+    RelayWithdrawal {
+        point_ids: Vec<Pubkey>,
+        nullifiers: Vec<[u8; 32]>,
+        nonce: u64,
+        recipient: Pubkey,
+        /// Paid to the relayer out of the withdrawn amount, after the protocol fee
+        relayer_fee: u64,
+        /// Borsh-encoded `crypto::ring::RingSignature` by the points' keys over
+        /// `WithdrawalRequest::relay_spend_message`
+        spend_signature: Vec<u8>,
+    },
+    
+    /// Pay out a relayed withdrawal after the delay (permissionless)
+    /// 
+    /// The recipient receives the amount less the protocol and relayer fees; the relayer's
+    /// token account receives `relayer_fee`.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Submitter (any account; pays epoch stats rent on first use)
+    /// 1. `[writable]` Recipient token account for the request's mint
+    /// 2. `[writable]` Relayer token account for the request's mint
+    /// 3. `[writable]` Treasury token account for the request's mint
+    /// 4. `[writable]` Protocol state account (PDA)
+    /// 5. `[writable]` Withdrawal request account (PDA)
+    /// 6. `[]` Treasury authority (PDA)
+    /// 7. `[]` Token program (SPL Token or Token-2022, whichever owns the mint)
+    /// 8. `[]` Deny list account (PDA; may not exist yet)
+    /// 9. `[]` Association set account (PDA; may not exist yet)
+    /// 10. `[]` Mint the withdrawn points were deposited in
+    /// 11. `[writable]` Supported mint account (PDA; unused for USDT)
+    /// 12. `[]` Fee exemptions account (PDA; may not exist yet)
+    /// 13. `[writable]` Epoch stats account (PDA of the current epoch)
+    /// 14. `[]` System program
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "submitter",
+        desc = "Submitter (any account; pays epoch stats rent on first use)"
+    )]
+    #[account(
+        1,
+        writable,
+        name = "recipient_token",
+        desc = "Recipient token account for the request's mint"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "relayer_token",
+        desc = "Relayer token account for the request's mint"
+    )]
+    #[account(
+        3,
+        writable,
+        name = "treasury_token",
+        desc = "Treasury token account for the request's mint"
+    )]
+    #[account(4, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(5, writable, name = "withdrawal_request", desc = "Withdrawal request account (PDA)")]
+    #[account(6, name = "treasury_authority", desc = "Treasury authority (PDA)")]
+    #[account(
+        7,
+        name = "token_program",
+        desc = "Token program (SPL Token or Token-2022, whichever owns the mint)"
+    )]
+    #[account(8, name = "deny_list", desc = "Deny list account (PDA; may not exist yet)")]
+    #[account(
+        9,
+        name = "association_set",
+        desc = "Association set account (PDA; may not exist yet)"
+    )]
+    #[account(
+        10,
+        name = "mint",
+        desc = "Mint the withdrawn points were deposited in"
+    )]
+    #[account(
+        11,
+        writable,
+        name = "supported_mint",
+        desc = "Supported mint account (PDA; unused for USDT)"
+    )]
+    #[account(
+        12,
+        name = "fee_exemptions",
+        desc = "Fee exemptions account (PDA; may not exist yet)"
+    )]
+    #[account(
+        13,
+        writable,
+        name = "epoch_stats",
+        desc = "Epoch stats account (PDA of the current epoch)"
+    )]
+    #[account(14, name = "system_program", desc = "System program")]
+    CompleteRelayedWithdrawal,
+    
+    /// Deposit on a user's behalf under a permit they signed off-chain
+    /// 
+    /// The user approves the permit authority PDA as a delegate on their token account once
+    /// and signs `DepositPermit::message`; a sponsor with SOL submits this with an Ed25519
+    /// program instruction verifying that signature, and pays every rent the deposit needs.
+    /// The permit record PDA keyed by the user and `nonce` stops the permit being replayed.
+    /// SOL deposits need the user to hold wSOL already; their lamports are never wrapped.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Sponsor account (pays permit, point and activity rent)
+    /// 1. `[writable]` Deposit permit account (PDA)
+    /// 2. `[]` Permit authority (PDA), the user token account's delegate
+    /// 3. `[]` Instructions sysvar
+    /// 4. `[]` User account
+    /// 5. `[writable]` User token account for the mint
+    /// 6. `[writable]` Treasury token account for the mint
+    /// 7. `[writable]` Protocol state account (PDA)
+    /// 8. `[writable]` Floating point account (PDA) for the first commitment
+    /// 9. `[]` Mint (USDT or a supported mint)
+    /// 10. `[]` Token program (SPL Token or Token-2022, whichever owns the mint)
+    /// 11. `[]` System program
+    /// 12. `[writable]` Commitment tree account (PDA)
+    /// 13. `[writable]` Root history account (PDA)
+    /// 14. `[writable]` User activity account (PDA)
+    /// 15. `[]` Deny list account (PDA; may not exist yet)
+    /// 16. `[writable]` Supported mint account (PDA; unused for USDT)
+    /// 17. `[writable]` Reward account (PDA of the user)
+    /// 18. `[]` Fee exemptions account (PDA; may not exist yet)
+    /// 19. `[writable]` Epoch stats account (PDA of the current epoch)
+    /// 20. `[]` Instructions sysvar again, as `Deposit` takes it
+    /// 21-N. `[writable]` Floating point accounts (PDAs) for the remaining commitments
+    /// N+1..N+4. Concurrent tree accounts, once `CreateConcurrentTree` has run
+    ///   (see `with_concurrent_tree`)
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "sponsor",
+        desc = "Sponsor account (pays permit, point and activity rent)"
+    )]
+    #[account(1, writable, name = "deposit_permit", desc = "Deposit permit account (PDA)")]
+    #[account(
+        2,
+        name = "permit_authority",
+        desc = "Permit authority (PDA), the user token account's delegate"
+    )]
+    #[account(3, name = "instructions_sysvar", desc = "Instructions sysvar")]
+    #[account(4, name = "user", desc = "User account")]
+    #[account(5, writable, name = "user_token", desc = "User token account for the mint")]
+    #[account(6, writable, name = "treasury_token", desc = "Treasury token account for the mint")]
+    #[account(7, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        8,
+        writable,
+        name = "floating_point",
+        desc = "Floating point account (PDA) for the first commitment"
+    )]
+    #[account(9, name = "mint", desc = "Mint (USDT or a supported mint)")]
+    #[account(
+        10,
+        name = "token_program",
+        desc = "Token program (SPL Token or Token-2022, whichever owns the mint)"
+    )]
+    #[account(11, name = "system_program", desc = "System program")]
+    #[account(12, writable, name = "commitment_tree", desc = "Commitment tree account (PDA)")]
+    #[account(13, writable, name = "root_history", desc = "Root history account (PDA)")]
+    #[account(14, writable, name = "user_activity", desc = "User activity account (PDA)")]
+    #[account(15, name = "deny_list", desc = "Deny list account (PDA; may not exist yet)")]
+    #[account(
+        16,
+        writable,
+        name = "supported_mint",
+        desc = "Supported mint account (PDA; unused for USDT)"
+    )]
+    #[account(17, writable, name = "reward_account", desc = "Reward account (PDA of the user)")]
+    #[account(
+        18,
+        name = "fee_exemptions",
+        desc = "Fee exemptions account (PDA; may not exist yet)"
+    )]
+    #[account(
+        19,
+        writable,
+        name = "epoch_stats",
+        desc = "Epoch stats account (PDA of the current epoch)"
+    )]
+    #[account(
+        20,
+        name = "cpi_guard_sysvar",
+        desc = "Instructions sysvar again, as `Deposit` takes it"
+    )]
+    #[account(
+        21,
+        writable,
+        name = "remaining_points",
+        desc = "21-N: Floating point accounts (PDAs) for the remaining commitments"
+    )]
+    DelegatedDeposit {
+        amount: u64,
+        denomination: u64,
+        commitments: Vec<[u8; 32]>,
+        opening_proofs: Vec<OpeningProof>,
+        /// Unix timestamp after which the permit is void
+        expiry: i64,
+        nonce: u64,
+        /// Position of the Ed25519 program instruction relative to this one
+        signature_offset: i8,
+    },
+    
+    /// Upgrade a state account to the layout this build uses (admin only)
+    /// 
+    /// Steps through every layout between the account's `version` and the current one,
+    /// reallocating in place when a layout adds fields; the payer covers the extra rent.
+    /// The upgraded account must decode as `kind`, so a mislabelled account is rejected.
+    /// Accounts already at the current version are left untouched.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Account to migrate (owned by the program)
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "account", desc = "Account to migrate (owned by the program)")]
+    #[account(3, writable, signer, name = "payer", desc = "Payer")]
+    #[account(4, name = "system_program", desc = "System program")]
+    MigrateState {
+        kind: AccountKind,
+    },
+    
+    /// Close a spent point, refunding its rent to whoever paid it (permissionless)
+    /// 
+    /// A point is spent once it is inactive and holds no mass: split, burned by a permanent
+    /// cancellation, or paid out by a request closed with `CloseWithdrawalRequest`.
+    /// 
+    /// Accounts expected:
+    /// 0. `[writable]` Floating point account (PDA)
+    /// 1. `[writable]` The point's rent payer
+    #[account(0, writable, name = "floating_point", desc = "Floating point account (PDA)")]
+    #[account(1, writable, name = "rent_payer", desc = "The point's rent payer")]
+    CloseSpentPoint,
+    
+    /// Close a completed or cancelled withdrawal request, refunding its rent (permissionless)
+    /// 
+    /// The rent goes to the requester, or to the relayer for a relayed request. A completed
+    /// request's points give up their mass, so `CloseSpentPoint` can close them too; they and
+    /// their nullifiers are passed as for `CancelWithdrawal`. Cancelled requests take neither.
+    /// 
+    /// Accounts expected:
+    /// 0. `[writable]` Withdrawal request account (PDA)
+    /// 1. `[writable]` Requester, or relayer of a relayed request
+    /// 2-N. `[writable]` Point accounts, in request order (completed requests only)
+    /// N+1-K. `[]` Nullifier accounts (PDAs), one per point (completed requests only)
+    #[account(0, writable, name = "withdrawal_request", desc = "Withdrawal request account (PDA)")]
+    #[account(
+        1,
+        writable,
+        name = "rent_payer",
+        desc = "Requester, or relayer of a relayed request"
+    )]
+    #[account(
+        2,
+        writable,
+        name = "points",
+        desc = "2-N: Point accounts, in request order (completed requests only)"
+    )]
+    #[account(
+        3,
+        name = "nullifiers",
+        desc = "N+1-K: Nullifier accounts (PDAs), one per point (completed requests only)"
+    )]
+    CloseWithdrawalRequest,
+    
+    /// Move nullifier records into their `NullifierShard` accounts and close their PDAs
+    /// (permissionless)
+    /// 
+    /// Each nullifier is kept as 30 bytes in the shard for its first two bytes, created on
+    /// first use, and its bits are set in the filter, so spends still reject it. The shard's
+    /// extra rent comes out of the record and the rest goes to whoever paid it, or to the fee
+    /// receiver for records from before `payer` was tracked. A nullifier spent by a withdrawal
+    /// request is held until the request is closed or cancelled, as cancelling releases it.
+    /// 
+    /// Accounts expected:
+    /// 0. `[]` Protocol state account (PDA)
+    /// 1. `[writable]` Nullifier filter account (PDA), which must be complete
+    /// 2. `[]` System program
+    /// 3.. `count` groups of:
+    ///    - `[writable]` Nullifier account (PDA)
+    ///    - `[writable]` The record's payer
+    ///    - `[]` The record's withdrawal request (the system program if none)
+    ///    - `[writable]` The nullifier's shard account (PDA)
+    #[account(0, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        1,
+        writable,
+        name = "nullifier_filter",
+        desc = "Nullifier filter account (PDA), which must be complete"
+    )]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(
+        3,
+        writable,
+        name = "nullifiers",
+        desc = "3..: Nullifier account (PDA), payer, withdrawal request and shard, per nullifier"
+    )]
+    CompactNullifiers {
+        count: u8,
+    },
+    
+    /// Prove several pending withdrawals compliant at once
+    /// 
+    /// As `ProveWithdrawalCompliance`, one proof per request, but the proofs are checked
+    /// together with a single batched pairing. At most `groth16::MAX_BATCH_PROOFS` proofs.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` User account (the requester, or the relayer, of every request)
+    /// 1. `[]` Association set account (PDA)
+    /// 2..N. `[writable]` Withdrawal request accounts (PDAs), one per proof
+    /// N+1. `[]` Groth16 verifier program
+    #[account(
+        0,
+        signer,
+        name = "user",
+        desc = "User account (the requester, or the relayer, of every request)"
+    )]
+    #[account(1, name = "association_set", desc = "Association set account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "withdrawal_requests",
+        desc = "2..N: Withdrawal request accounts (PDAs), one per proof"
+    )]
+    #[account(3, name = "verifier_program", desc = "N+1: Groth16 verifier program")]
+    BatchProveWithdrawalCompliance {
+        proofs: Vec<Groth16Proof>,
+    },
+    
+    /// Write the next chunk of a staged privacy payment, opening its account on the first
+    /// 
+    /// For payments too large to verify in one transaction. The payload is a Borsh
+    /// `StagedPayment`, appended in order; the owner pays rent for all `payload_len` bytes
+    /// up front and gets it back when the payment executes or the account is closed.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Owner account
+    /// 1. `[writable]` Staged proof account (PDA)
+    /// 2. `[]` System program
+    #[account(0, writable, signer, name = "owner", desc = "Owner account")]
+    #[account(1, writable, name = "staged_proof", desc = "Staged proof account (PDA)")]
+    #[account(2, name = "system_program", desc = "System program")]
+    StageProof {
+        nonce: u64,
+        /// Size of the whole payload; must match on every chunk
+        payload_len: u32,
+        chunk: Vec<u8>,
+    },
+    
+    /// Check the next `rows` rows of a staged payment's ring signature
+    /// 
+    /// Rows are checked in order over as many calls as the compute budget needs; the call
+    /// covering the last row marks the payment verified. Each row's points must hold the
+    /// masses and mint of the first row's, which the payment pays out.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Owner account
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Staged proof account (PDA)
+    /// 3..M. `[]` Ring point accounts of the first row, as in the payload
+    /// M+1..N. `[]` Ring point accounts of these rows, row-major as in the payload
+    #[account(0, signer, name = "owner", desc = "Owner account")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "staged_proof", desc = "Staged proof account (PDA)")]
+    #[account(
+        3,
+        name = "first_row_points",
+        desc = "3..M: Ring point accounts of the first row, as in the payload"
+    )]
+    #[account(
+        4,
+        name = "ring_points",
+        desc = "M+1..N: Ring point accounts of these rows, row-major as in the payload"
+    )]
+    VerifyStagedProof {
+        rows: u8,
+    },
+    
+    /// Apply a verified staged payment as `PrivacyPayment` would, then close its account
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Owner account (pays nullifier, output point and note rent)
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Staged proof account (PDA)
+    /// 3. `[]` System program
+    /// 4. `[]` Root history account (PDA), or the archived tree the root belongs to
+    /// 5. `[]` Instructions sysvar
+    /// 6. `[writable]` Nullifier filter account (PDA)
+    /// 7. `[]` Recipient account (creator of the output point)
+    /// 8. `[writable]` Commitment tree account (PDA)
+    /// 9. `[writable]` Root history account (PDA)
+    /// 10..M. `[]` Ring point accounts of the first row, as in the payload
+    /// M+1..N. `[writable]` Nullifier accounts (PDAs), the output point account (PDA), then
+    ///    the encrypted note account (PDA)
+    /// N+1, N+2. `[]` Verifier program and payment circuit key account (PDA), only when the
+    ///    payment carries a proof
+    /// N+1/N+3.. `[]` Nullifier shard accounts (PDAs), one per nullifier prefix
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "owner",
+        desc = "Owner account (pays nullifier, output point and note rent)"
+    )]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "staged_proof", desc = "Staged proof account (PDA)")]
+    #[account(3, name = "system_program", desc = "System program")]
+    #[account(
+        4,
+        name = "root_history",
+        desc = "Root history account (PDA), or the archived tree the root belongs to"
+    )]
+    #[account(5, name = "instructions_sysvar", desc = "Instructions sysvar")]
+    #[account(6, writable, name = "nullifier_filter", desc = "Nullifier filter account (PDA)")]
+    #[account(
+        7,
+        name = "recipient",
+        desc = "Recipient account (creator of the output point)"
+    )]
+    #[account(8, writable, name = "commitment_tree", desc = "Commitment tree account (PDA)")]
+    #[account(9, writable, name = "tree_root_history", desc = "Root history account (PDA)")]
+    #[account(
+        10,
+        name = "first_row_points",
+        desc = "10..M: Ring point accounts of the first row, as in the payload"
+    )]
+    #[account(
+        11,
+        writable,
+        name = "outputs",
+        desc = "M+1..N: Nullifier accounts (PDAs), the output point, then the encrypted note"
+    )]
+    #[account(
+        12,
+        name = "verifier_program",
+        desc = "N+1: Verifier program, only when the payment carries a proof"
+    )]
+    #[account(
+        13,
+        name = "circuit_key",
+        desc = "N+2: Payment circuit key account (PDA), only when the payment carries a proof"
+    )]
+    #[account(
+        14,
+        name = "nullifier_shards",
+        desc = "N+1/N+3..: Nullifier shard accounts (PDAs), one per nullifier prefix"
+    )]
+    ExecuteStagedPayment {
+        /// Position of the range proof instruction relative to this one
+        range_proof_offset: i8,
+    },
+    
+    /// Close a staged proof account, refunding its rent to the owner
+    /// 
+    /// The owner may close it at any time; anyone may once it has expired.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Caller
+    /// 1. `[writable]` Staged proof account (PDA)
+    /// 2. `[writable]` Owner account
+    #[account(0, signer, name = "caller", desc = "Caller")]
+    #[account(1, writable, name = "staged_proof", desc = "Staged proof account (PDA)")]
+    #[account(2, writable, name = "owner", desc = "Owner account")]
+    CloseStagedProof,
+    
+    /// Set the lending reserve a mint's treasury may be lent to (admin only)
+    /// 
+    /// `max_deployed_rate` is the largest share of the treasury that may be lent, in basis
+    /// points, at most `TreasuryDeployment::MAX_DEPLOYED_RATE`. The reserve can only be
+    /// changed while nothing is lent to it.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Treasury deployment account (PDA, created on first use)
+    /// 3. `[]` Mint
+    /// 4. `[]` Supported mint account (PDA; unused for USDT)
+    /// 5. `[]` Treasury collateral token account, owned by the treasury authority
+    /// 6. `[signer, writable]` Payer
+    /// 7. `[]` System program
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "treasury_deployment",
+        desc = "Treasury deployment account (PDA, created on first use)"
+    )]
+    #[account(3, name = "mint", desc = "Mint")]
+    #[account(4, name = "supported_mint", desc = "Supported mint account (PDA; unused for USDT)")]
+    #[account(
+        5,
+        name = "collateral_token",
+        desc = "Treasury collateral token account, owned by the treasury authority"
+    )]
+    #[account(6, writable, signer, name = "payer", desc = "Payer")]
+    #[account(7, name = "system_program", desc = "System program")]
+    SetTreasuryDeployment {
+        reserve: ReserveKeys,
+        max_deployed_rate: u16,
+    },
+    
+    /// Lend part of a mint's treasury to its reserve (admin only)
+    /// 
+    /// Must follow the lending program's `RefreshReserve` in the same transaction.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Treasury deployment account (PDA)
+    /// 3. `[writable]` Treasury token account for the mint
+    /// 4. `[]` Treasury authority (PDA)
+    /// 5. `[writable]` Treasury collateral token account
+    /// 6. `[]` Mint
+    /// 7. `[]` Supported mint account (PDA; unused for USDT)
+    /// 8. `[]` Token program
+    /// 9. `[]` Clock sysvar
+    /// 10. `[]` Lending program
+    /// 11. `[writable]` Reserve
+    /// 12. `[writable]` Reserve liquidity supply
+    /// 13. `[writable]` Reserve collateral mint
+    /// 14. `[]` Lending market
+    /// 15. `[]` Lending market authority
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "treasury_deployment",
+        desc = "Treasury deployment account (PDA)"
+    )]
+    #[account(3, writable, name = "treasury_token", desc = "Treasury token account for the mint")]
+    #[account(4, name = "treasury_authority", desc = "Treasury authority (PDA)")]
+    #[account(
+        5,
+        writable,
+        name = "collateral_token",
+        desc = "Treasury collateral token account"
+    )]
+    #[account(6, name = "mint", desc = "Mint")]
+    #[account(
+        7,
+        name = "supported_mint",
+        desc = "Supported mint account (PDA; unused for USDT)"
+    )]
+    #[account(8, name = "token_program", desc = "Token program")]
+    #[account(9, name = "clock_sysvar", desc = "Clock sysvar")]
+    #[account(10, name = "lending_program", desc = "Lending program")]
+    #[account(11, writable, name = "reserve", desc = "Reserve")]
+    #[account(12, writable, name = "reserve_liquidity_supply", desc = "Reserve liquidity supply")]
+    #[account(13, writable, name = "reserve_collateral_mint", desc = "Reserve collateral mint")]
+    #[account(14, name = "lending_market", desc = "Lending market")]
+    #[account(15, name = "lending_market_authority", desc = "Lending market authority")]
+    DeployTreasury {
+        /// Base units to lend
+        amount: u64,
+    },
+    
+    /// Redeem reserve collateral back into a mint's treasury (admin or guardian)
+    /// 
+    /// Must follow the lending program's `RefreshReserve` in the same transaction.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority or guardian
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Treasury deployment account (PDA)
+    /// 3. `[writable]` Treasury token account for the mint
+    /// 4. `[]` Treasury authority (PDA)
+    /// 5. `[writable]` Treasury collateral token account
+    /// 6. `[]` Mint
+    /// 7. `[writable]` Supported mint account (PDA; unused for USDT)
+    /// 8. `[]` Token program
+    /// 9. `[]` Clock sysvar
+    /// 10. `[]` Lending program
+    /// 11. `[writable]` Reserve
+    /// 12. `[writable]` Reserve liquidity supply
+    /// 13. `[writable]` Reserve collateral mint
+    /// 14. `[]` Lending market
+    /// 15. `[]` Lending market authority
+    #[account(0, signer, name = "caller", desc = "Protocol authority or guardian")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "treasury_deployment",
+        desc = "Treasury deployment account (PDA)"
+    )]
+    #[account(3, writable, name = "treasury_token", desc = "Treasury token account for the mint")]
+    #[account(4, name = "treasury_authority", desc = "Treasury authority (PDA)")]
+    #[account(
+        5,
+        writable,
+        name = "collateral_token",
+        desc = "Treasury collateral token account"
+    )]
+    #[account(6, name = "mint", desc = "Mint")]
+    #[account(
+        7,
+        writable, name = "supported_mint",
+        desc = "Supported mint account (PDA; unused for USDT)"
+    )]
+    #[account(8, name = "token_program", desc = "Token program")]
+    #[account(9, name = "clock_sysvar", desc = "Clock sysvar")]
+    #[account(10, name = "lending_program", desc = "Lending program")]
+    #[account(11, writable, name = "reserve", desc = "Reserve")]
+    #[account(12, writable, name = "reserve_liquidity_supply", desc = "Reserve liquidity supply")]
+    #[account(13, writable, name = "reserve_collateral_mint", desc = "Reserve collateral mint")]
+    #[account(14, name = "lending_market", desc = "Lending market")]
+    #[account(15, name = "lending_market_authority", desc = "Lending market authority")]
+    RecallTreasury {
+        /// Collateral tokens to redeem
+        collateral_amount: u64,
+    },
+    
+    /// Set the loyalty reward rate and the vault rewards are paid from (admin only)
+    /// 
+    /// `rate` is in the reward mint's units per day for each whole token deposited. The
+    /// vault is a token account owned by the rewards authority PDA, and can't be replaced
+    /// while it still holds unclaimed pool funding.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[]` Rewards vault token account
+    /// 3. `[]` Reward mint
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, name = "rewards_vault", desc = "Rewards vault token account")]
+    #[account(3, name = "reward_mint", desc = "Reward mint")]
+    SetRewardConfig {
+        rate: u64,
+    },
+    
+    /// Add tokens to the rewards pool
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Funder
+    /// 1. `[writable]` Funder token account for the reward mint
+    /// 2. `[writable]` Rewards vault token account
+    /// 3. `[writable]` Protocol state account (PDA)
+    /// 4. `[]` Reward mint
+    /// 5. `[]` Token program
+    #[account(0, signer, name = "funder", desc = "Funder")]
+    #[account(
+        1,
+        writable,
+        name = "funder_token",
+        desc = "Funder token account for the reward mint"
+    )]
+    #[account(2, writable, name = "rewards_vault", desc = "Rewards vault token account")]
+    #[account(3, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(4, name = "reward_mint", desc = "Reward mint")]
+    #[account(5, name = "token_program", desc = "Token program")]
+    FundRewards {
+        amount: u64,
+    },
+    
+    /// Pay out the caller's accrued loyalty rewards from the rewards pool
+    /// 
+    /// Pays as much as the pool holds; the rest stays accrued.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` User
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Reward account (PDA)
+    /// 3. `[writable]` Rewards vault token account
+    /// 4. `[]` Rewards authority (PDA)
+    /// 5. `[writable]` User token account for the reward mint
+    /// 6. `[]` Reward mint
+    /// 7. `[]` Token program
+    #[account(0, signer, name = "user", desc = "User")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "reward_account", desc = "Reward account (PDA)")]
+    #[account(3, writable, name = "rewards_vault", desc = "Rewards vault token account")]
+    #[account(4, name = "rewards_authority", desc = "Rewards authority (PDA)")]
+    #[account(5, writable, name = "user_token", desc = "User token account for the reward mint")]
+    #[account(6, name = "reward_mint", desc = "Reward mint")]
+    #[account(7, name = "token_program", desc = "Token program")]
+    ClaimRewards,
+    
+    /// Set the share of referred deposits' fees owed to referrers (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Audit log account (PDA), once `CreateAuditLog` has run
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "audit_log",
+        desc = "Audit log account (PDA), once `CreateAuditLog` has run"
+    )]
+    SetReferralFeeShare {
+        /// Basis points, at most `ProtocolState::MAX_REFERRAL_FEE_SHARE`
+        share: u16,
+    },
+    
+    /// Pay the caller the referral fees accrued in one mint, from the treasury
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Referrer
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Referral account (PDA of the referrer and mint)
+    /// 3. `[writable]` Treasury token account for the mint
+    /// 4. `[]` Treasury authority (PDA)
+    /// 5. `[writable]` Referrer token account for the mint
+    /// 6. `[]` Mint
+    /// 7. `[]` Supported mint account (PDA; unused for USDT)
+    /// 8. `[]` Token program
+    #[account(0, signer, name = "referrer", desc = "Referrer")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "referral_account",
+        desc = "Referral account (PDA of the referrer and mint)"
+    )]
+    #[account(3, writable, name = "treasury_token", desc = "Treasury token account for the mint")]
+    #[account(4, name = "treasury_authority", desc = "Treasury authority (PDA)")]
+    #[account(5, writable, name = "referrer_token", desc = "Referrer token account for the mint")]
+    #[account(6, name = "mint", desc = "Mint")]
+    #[account(7, name = "supported_mint", desc = "Supported mint account (PDA; unused for USDT)")]
+    #[account(8, name = "token_program", desc = "Token program")]
+    ClaimReferralFees,
+    
+    /// Replace the volume fee tiers (admin only)
+    /// 
+    /// Takes effect immediately: tiers can only lower a mint's rates.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Audit log account (PDA), once `CreateAuditLog` has run
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "audit_log",
+        desc = "Audit log account (PDA), once `CreateAuditLog` has run"
+    )]
+    UpdateFeeTiers {
+        /// Used tiers first, by rising `min_amount`; the rest zeroed
+        tiers: [FeeTier; MAX_FEE_TIERS],
+    },
+    
+    /// Waive deposit and withdrawal fees for an address (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Fee exemptions account (PDA, created on first use)
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "fee_exemptions",
+        desc = "Fee exemptions account (PDA, created on first use)"
+    )]
+    #[account(3, writable, signer, name = "payer", desc = "Payer")]
+    #[account(4, name = "system_program", desc = "System program")]
+    AddFeeExemption {
+        address: Pubkey,
+    },
+    
+    /// Make an exempt address pay fees again (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Fee exemptions account (PDA)
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "fee_exemptions", desc = "Fee exemptions account (PDA)")]
+    RemoveFeeExemption {
+        address: Pubkey,
+    },
+    
+    /// Set the global and per-user deposit caps (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    SetDepositCaps {
+        /// Most the protocol may hold across every mint, in base units; zero for no cap
+        max_tvl: u64,
+        /// Most one user may deposit over their lifetime; zero for no cap, and at most
+        /// `max_tvl` when that is set
+        max_user_deposit: u64,
+    },
+    
+    /// Settle matured requests from the front of the withdrawal queue (permissionless)
+    /// 
+    /// Each request pays out as `CompleteWithdrawal` would, and the crank is tipped
+    /// `WithdrawalQueue::TIP_SHARE` of its withdrawal fee in the request's mint. Queued
+    /// requests already completed, cancelled or closed are dropped without a payout.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Cranker (pays epoch stats rent on first use)
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Withdrawal queue account (PDA)
+    /// 3. `[]` Treasury authority (PDA)
+    /// 4. `[]` Token program (SPL Token or Token-2022, whichever owns the requests' mints)
+    /// 5. `[]` Deny list account (PDA; may not exist yet)
+    /// 6. `[]` Association set account (PDA; may not exist yet)
+    /// 7. `[]` Fee exemptions account (PDA; may not exist yet)
+    /// 8. `[writable]` Epoch stats account (PDA of the current epoch)
+    /// 9. `[]` System program
+    /// 
+    /// Then six accounts for each of the first `count` queued requests, in queue order:
+    /// 10+5i. `[writable]` Withdrawal request account (PDA)
+    /// 11+5i. `[writable]` Requester token account for the request's mint
+    /// 12+5i. `[writable]` Treasury token account for the request's mint
+    /// 13+5i. `[]` Mint of the request
+    /// 14+5i. `[writable]` Supported mint account (PDA; unused for USDT)
+    /// 15+5i. `[writable]` Token account receiving the tip, in the request's mint
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "cranker",
+        desc = "Cranker (pays epoch stats rent on first use)"
+    )]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "withdrawal_queue", desc = "Withdrawal queue account (PDA)")]
+    #[account(3, name = "treasury_authority", desc = "Treasury authority (PDA)")]
+    #[account(
+        4,
+        name = "token_program",
+        desc = "Token program (SPL Token or Token-2022, whichever owns the requests' mints)"
+    )]
+    #[account(5, name = "deny_list", desc = "Deny list account (PDA; may not exist yet)")]
+    #[account(
+        6,
+        name = "association_set",
+        desc = "Association set account (PDA; may not exist yet)"
+    )]
+    #[account(
+        7,
+        name = "fee_exemptions",
+        desc = "Fee exemptions account (PDA; may not exist yet)"
+    )]
+    #[account(
+        8,
+        writable,
+        name = "epoch_stats",
+        desc = "Epoch stats account (PDA of the current epoch)"
+    )]
+    #[account(9, name = "system_program", desc = "System program")]
+    #[account(
+        10,
+        writable,
+        name = "withdrawal_request",
+        desc = "10+5i: Withdrawal request account (PDA)"
+    )]
+    #[account(
+        11,
+        writable,
+        name = "requester_token",
+        desc = "11+5i: Requester token account for the request's mint"
+    )]
+    #[account(
+        12,
+        writable,
+        name = "treasury_token",
+        desc = "12+5i: Treasury token account for the request's mint"
+    )]
+    #[account(13, name = "mint", desc = "13+5i: Mint of the request")]
+    #[account(
+        14,
+        writable,
+        name = "supported_mint",
+        desc = "14+5i: Supported mint account (PDA; unused for USDT)"
+    )]
+    #[account(
+        15,
+        writable,
+        name = "tip_token",
+        desc = "15+5i: Token account receiving the tip, in the request's mint"
+    )]
+    ProcessWithdrawalQueue {
+        /// Number of queued requests to settle, drop or defer, at most the queue's length
+        count: u8,
+    },
+    
+    /// Set the withdrawal delay and the lock on new points (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    UpdateTimelocks {
+        /// Seconds from a withdrawal request to its completion, between
+        /// `ProtocolState::MIN_WITHDRAWAL_DELAY` and `MAX_WITHDRAWAL_DELAY`
+        withdrawal_delay: i64,
+        /// Seconds new points stay locked, up to `ProtocolState::MAX_POINT_LOCK_DURATION`
+        point_lock_duration: i64,
+    },
+    
+    /// Set the accretion and decay of point value (admin only)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    SetMassDynamics {
+        /// Rates within `MassDynamics::MAX_ACCRETION_RATE` and `MAX_DECAY_RATE`, and a
+        /// dormancy age past the maturity age unless zero
+        dynamics: MassDynamics,
+    },
+    
+    /// Settle accretion and decay on points since their last update (permissionless)
+    /// 
+    /// Accretion is funded from the uncollected fees of each point's mint; decay returns to
+    /// them. Points from before layout 3 are upgraded first, with the cranker paying the
+    /// extra rent.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Cranker
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[]` System program
+    /// 3+2i. `[writable]` Active point account (PDA)
+    /// 4+2i. `[writable]` Supported mint account of the point's mint (PDA; unused for USDT)
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "cranker",
+        desc = "Cranker (pays to upgrade older points)"
+    )]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, name = "system_program", desc = "System program")]
+    #[account(3, writable, name = "point", desc = "3+2i: Active point account (PDA)")]
+    #[account(
+        4,
+        writable,
+        name = "supported_mint",
+        desc = "4+2i: Supported mint account of the point's mint (PDA; unused for USDT)"
+    )]
+    UpdateMass,
+    
+    /// Open a pool for deposits with its own tree, root history and nullifiers (permissionless)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Payer
+    /// 1. `[]` Pool config account (PDA)
+    /// 2. `[]` Circuit account of the pool's circuit (PDA)
+    /// 3. `[writable]` Pool state account (PDA)
+    /// 4. `[writable]` Pool commitment tree (PDA)
+    /// 5. `[writable]` Pool root history (PDA)
+    /// 6. `[]` System program
+    #[account(0, writable, signer, name = "payer", desc = "Payer")]
+    #[account(1, name = "pool_config", desc = "Pool config account (PDA)")]
+    #[account(2, name = "circuit", desc = "Circuit account of the pool's circuit (PDA)")]
+    #[account(3, writable, name = "pool_state", desc = "Pool state account (PDA)")]
+    #[account(4, writable, name = "pool_tree", desc = "Pool commitment tree (PDA)")]
+    #[account(5, writable, name = "pool_root_history", desc = "Pool root history (PDA)")]
+    #[account(6, name = "system_program", desc = "System program")]
+    OpenPool,
+    
+    /// Deposit one denomination of an opened pool, plus the deposit fee
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Depositor
+    /// 1. `[writable]` Depositor token account
+    /// 2. `[writable]` Treasury token account
+    /// 3. `[writable]` Protocol state account (PDA)
+    /// 4. `[writable]` Pool config account (PDA)
+    /// 5. `[writable]` Pool state account (PDA)
+    /// 6. `[writable]` Pool commitment tree (PDA)
+    /// 7. `[writable]` Pool root history (PDA)
+    /// 8. `[]` Pool mint
+    /// 9. `[writable]` Supported mint account (PDA; unused for USDT)
+    /// 10. `[]` Token program
+    /// 11. `[]` Deny list account (PDA)
+    #[account(0, signer, name = "depositor", desc = "Depositor")]
+    #[account(1, writable, name = "depositor_token", desc = "Depositor token account")]
+    #[account(2, writable, name = "treasury_token", desc = "Treasury token account")]
+    #[account(3, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(4, writable, name = "pool_config", desc = "Pool config account (PDA)")]
+    #[account(5, writable, name = "pool_state", desc = "Pool state account (PDA)")]
+    #[account(6, writable, name = "pool_tree", desc = "Pool commitment tree (PDA)")]
+    #[account(7, writable, name = "pool_root_history", desc = "Pool root history (PDA)")]
+    #[account(8, name = "pool_mint", desc = "Pool mint")]
+    #[account(
+        9,
+        writable,
+        name = "supported_mint",
+        desc = "Supported mint account (PDA; unused for USDT)"
+    )]
+    #[account(10, name = "token_program", desc = "Token program")]
+    #[account(11, name = "deny_list", desc = "Deny list account (PDA)")]
+    PoolDeposit {
+        /// The note, `Poseidon(secret, denomination)`
+        commitment: [u8; 32],
+    },
+    
+    /// Withdraw one deposit from a pool with a Groth16 membership proof
+    /// 
+    /// The proof's public inputs are `merkle_root` and `PoolState::withdrawal_binding`,
+    /// binding the circuit version, nullifier, denomination, recipient, submitter and fee.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Submitter (relayer; pays for the nullifier record)
+    /// 1. `[writable]` Recipient token account
+    /// 2. `[writable]` Relayer token account (unused without a relayer fee)
+    /// 3. `[writable]` Treasury token account
+    /// 4. `[]` Treasury authority (PDA)
+    /// 5. `[writable]` Protocol state account (PDA)
+    /// 6. `[writable]` Pool config account (PDA)
+    /// 7. `[writable]` Pool state account (PDA)
+    /// 8. `[]` Pool root history (PDA)
+    /// 9. `[writable]` Pool nullifier account (PDA)
+    /// 10. `[]` Pool mint
+    /// 11. `[writable]` Supported mint account (PDA; unused for USDT)
+    /// 12. `[]` Token program
+    /// 13. `[]` System program
+    /// 14. `[]` Deny list account (PDA)
+    /// 15. `[]` Groth16 verifier program
+    /// 16. `[]` Circuit key account of the pool's circuit (PDA)
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "submitter",
+        desc = "Submitter (relayer; pays for the nullifier record)"
+    )]
+    #[account(1, writable, name = "recipient_token", desc = "Recipient token account")]
+    #[account(
+        2,
+        writable,
+        name = "relayer_token",
+        desc = "Relayer token account (unused without a relayer fee)"
+    )]
+    #[account(3, writable, name = "treasury_token", desc = "Treasury token account")]
+    #[account(4, name = "treasury_authority", desc = "Treasury authority (PDA)")]
+    #[account(5, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(6, writable, name = "pool_config", desc = "Pool config account (PDA)")]
+    #[account(7, writable, name = "pool_state", desc = "Pool state account (PDA)")]
+    #[account(8, name = "pool_root_history", desc = "Pool root history (PDA)")]
+    #[account(9, writable, name = "pool_nullifier", desc = "Pool nullifier account (PDA)")]
+    #[account(10, name = "pool_mint", desc = "Pool mint")]
+    #[account(
+        11,
+        writable,
+        name = "supported_mint",
+        desc = "Supported mint account (PDA; unused for USDT)"
+    )]
+    #[account(12, name = "token_program", desc = "Token program")]
+    #[account(13, name = "system_program", desc = "System program")]
+    #[account(14, name = "deny_list", desc = "Deny list account (PDA)")]
+    #[account(15, name = "verifier_program", desc = "Groth16 verifier program")]
+    #[account(
+        16,
+        name = "circuit_key",
+        desc = "Circuit key account of the pool's circuit (PDA)"
+    )]
+    PoolWithdraw {
+        merkle_root: [u8; 32],
+        nullifier: [u8; 32],
+        /// Token account paid the denomination less both fees
+        recipient: Pubkey,
+        relayer_fee: u64,
+        proof: Groth16Proof,
+    },
+    
+    /// Open a route for notes to move between two pools of the same mint (permissionless)
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Payer
+    /// 1. `[]` Source pool config account (PDA)
+    /// 2. `[]` Destination pool config account (PDA)
+    /// 3. `[]` Circuit account (PDA)
+    /// 4. `[writable]` Pool route account (PDA)
+    /// 5. `[]` System program
+    #[account(0, writable, signer, name = "payer", desc = "Payer")]
+    #[account(1, name = "source_pool", desc = "Source pool config account (PDA)")]
+    #[account(2, name = "destination_pool", desc = "Destination pool config account (PDA)")]
+    #[account(3, name = "circuit", desc = "Circuit account (PDA)")]
+    #[account(4, writable, name = "pool_route", desc = "Pool route account (PDA)")]
+    #[account(5, name = "system_program", desc = "System program")]
+    OpenPoolRoute {
+        /// Registered transfer circuit; its input and output counts bound each transfer
+        circuit_id: u16,
+    },
+    
+    /// Spend notes of one pool into notes of another of equal total value
+    /// 
+    /// The proof's public inputs are `merkle_root`, from the source pool's history, and
+    /// `PoolRoute::transfer_binding`.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Submitter (pays for the nullifier records)
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Pool route account (PDA)
+    /// 3. `[writable]` Source pool config account (PDA)
+    /// 4. `[writable]` Source pool state account (PDA)
+    /// 5. `[]` Source pool root history (PDA)
+    /// 6. `[writable]` Destination pool config account (PDA)
+    /// 7. `[writable]` Destination pool state account (PDA)
+    /// 8. `[writable]` Destination pool commitment tree (PDA)
+    /// 9. `[writable]` Destination pool root history (PDA)
+    /// 10. `[]` System program
+    /// 11. `[]` Groth16 verifier program
+    /// 12. `[]` Circuit key account of the route's circuit (PDA)
+    /// 13+i. `[writable]` Source pool nullifier account for each nullifier (PDA)
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "submitter",
+        desc = "Submitter (pays for the nullifier records)"
+    )]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "pool_route", desc = "Pool route account (PDA)")]
+    #[account(3, writable, name = "source_pool", desc = "Source pool config account (PDA)")]
+    #[account(4, writable, name = "source_state", desc = "Source pool state account (PDA)")]
+    #[account(5, name = "source_root_history", desc = "Source pool root history (PDA)")]
+    #[account(
+        6,
+        writable,
+        name = "destination_pool",
+        desc = "Destination pool config account (PDA)"
+    )]
+    #[account(
+        7,
+        writable,
+        name = "destination_state",
+        desc = "Destination pool state account (PDA)"
+    )]
+    #[account(
+        8,
+        writable,
+        name = "destination_tree",
+        desc = "Destination pool commitment tree (PDA)"
+    )]
+    #[account(
+        9,
+        writable,
+        name = "destination_root_history",
+        desc = "Destination pool root history (PDA)"
+    )]
+    #[account(10, name = "system_program", desc = "System program")]
+    #[account(11, name = "verifier_program", desc = "Groth16 verifier program")]
+    #[account(
+        12,
+        name = "circuit_key",
+        desc = "Circuit key account of the route's circuit (PDA)"
+    )]
+    #[account(
+        13,
+        writable,
+        name = "pool_nullifier",
+        desc = "13+i: Source pool nullifier account for each nullifier (PDA)"
+    )]
+    PoolTransfer {
+        merkle_root: [u8; 32],
+        nullifiers: Vec<[u8; 32]>,
+        commitments: Vec<[u8; 32]>,
+        proof: Groth16Proof,
+    },
+    
+    /// Publish the first verifying key of a circuit, or queue a replacement (admin only)
+    /// 
+    /// The first key must hash to the circuit's registered `verifying_key_hash` and applies
+    /// at once. A replacement needs a higher `circuit_version` and waits out the config
+    /// timelock; resubmitting the current key and version cancels it.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[]` Circuit account (PDA)
+    /// 3. `[writable]` Circuit key account (PDA)
+    /// 4. `[signer, writable]` Payer (pays for the first key's account)
+    /// 5. `[]` System program
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, name = "circuit", desc = "Circuit account (PDA)")]
+    #[account(3, writable, name = "circuit_key", desc = "Circuit key account (PDA)")]
+    #[account(
+        4,
+        writable,
+        signer,
+        name = "payer",
+        desc = "Payer (pays for the first key's account)"
+    )]
+    #[account(5, name = "system_program", desc = "System program")]
+    UpdateVerifyingKey {
+        circuit_id: u16,
+        /// Committed in every proof's binding input; only increases
+        circuit_version: u16,
+        verifying_key: Groth16VerifyingKey,
+    },
+    
+    /// Install a queued verifying key once its timelock has passed (permissionless)
+    /// 
+    /// Accounts expected:
+    /// 0. `[writable]` Circuit account (PDA)
+    /// 1. `[writable]` Circuit key account (PDA)
+    #[account(0, writable, name = "circuit", desc = "Circuit account (PDA)")]
+    #[account(1, writable, name = "circuit_key", desc = "Circuit key account (PDA)")]
+    ApplyVerifyingKey,
+    
+    /// Make an empty spl-account-compression tree the protocol's concurrent commitment
+    /// tree (admin only)
+    /// 
+    /// The Merkle tree account must already be allocated for the account compression
+    /// program with `concurrent_tree::account_len(max_depth, max_buffer_size, canopy_depth)`
+    /// bytes. From then on every deposit also appends its commitments to it.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[writable]` Concurrent tree account (PDA; the tree's authority)
+    /// 3. `[writable]` Commitment tree account (PDA)
+    /// 4. `[writable]` Merkle tree account
+    /// 5. `[]` Noop program
+    /// 6. `[]` Account compression program
+    /// 7. `[signer, writable]` Payer
+    /// 8. `[]` System program
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "concurrent_tree",
+        desc = "Concurrent tree account (PDA; the tree's authority)"
+    )]
+    #[account(3, writable, name = "commitment_tree", desc = "Commitment tree account (PDA)")]
+    #[account(4, writable, name = "merkle_tree", desc = "Merkle tree account")]
+    #[account(5, name = "noop_program", desc = "Noop program")]
+    #[account(6, name = "account_compression_program", desc = "Account compression program")]
+    #[account(7, writable, signer, name = "payer", desc = "Payer")]
+    #[account(8, name = "system_program", desc = "System program")]
+    CreateConcurrentTree {
+        max_depth: u32,
+        max_buffer_size: u32,
+        /// Levels below the root cached in the account, sparing proofs those nodes
+        canopy_depth: u32,
+    },
+    
+    /// Check a deposit commitment's membership in the concurrent tree (permissionless)
+    /// 
+    /// Fails unless `commitment` is the leaf at `leaf_index` under `root`, one of the tree's
+    /// recent roots.
+    /// 
+    /// Accounts expected:
+    /// 0. `[]` Concurrent tree account (PDA)
+    /// 1. `[]` Merkle tree account
+    /// 2. `[]` Account compression program
+    /// 3+i. `[]` Proof node `i` below the canopy, starting from the leaf's sibling
+    #[account(0, name = "concurrent_tree", desc = "Concurrent tree account (PDA)")]
+    #[account(1, name = "merkle_tree", desc = "Merkle tree account")]
+    #[account(2, name = "account_compression_program", desc = "Account compression program")]
+    #[account(
+        3,
+        name = "proof_node",
+        desc = "3+i: Proof node i below the canopy, starting from the leaf's sibling"
+    )]
+    VerifyConcurrentCommitment {
+        root: [u8; 32],
+        commitment: [u8; 32],
+        leaf_index: u32,
+    },
+    
+    /// Create the audit log that admin instructions append to from then on (admin only)
+    /// 
+    /// The log keeps the latest `AuditLog::CAPACITY` entries; older ones remain in the
+    /// `AdminActionLogged` events.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Audit log account (PDA)
+    /// 3. `[signer, writable]` Payer
+    /// 4. `[]` System program
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, writable, name = "audit_log", desc = "Audit log account (PDA)")]
+    #[account(3, writable, signer, name = "payer", desc = "Payer")]
+    #[account(4, name = "system_program", desc = "System program")]
+    CreateAuditLog,
+    
+    /// Lift the pause flags queued by `SetPaused` or `SetPausedFlags` once
+    /// `UNPAUSE_TIMELOCK` has passed (permissionless)
+    /// 
+    /// Accounts expected:
+    /// 0. `[writable]` Protocol state account (PDA)
+    #[account(0, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    ApplyUnpause,
+    
+    /// Reject or allow `Deposit`, `DelegatedDeposit` and `RequestWithdrawal` invoked through
+    /// CPI from another program (admin only)
+    /// 
+    /// A flash-loan wrapper can borrow, deposit and withdraw inside one top-level instruction,
+    /// which slot-based checks can't tell apart from an ordinary deposit.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer]` Protocol authority
+    /// 1. `[writable]` Protocol state account (PDA)
+    /// 2. `[writable]` Audit log account (PDA), once `CreateAuditLog` has run
+    #[account(0, signer, name = "protocol_authority", desc = "Protocol authority")]
+    #[account(1, writable, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(
+        2,
+        writable,
+        name = "audit_log",
+        desc = "Audit log account (PDA), once `CreateAuditLog` has run"
+    )]
+    SetCpiGuard {
+        enabled: bool,
+    },
+    
+    /// Apply several privacy payments, checking all their proofs with one batched pairing
+    /// 
+    /// Each payment is checked as a `PrivacyPayment` with a proof would be; the proofs go
+    /// to the verifier program together, for `n + 3` pairings instead of `4n`. One bad proof
+    /// fails the whole batch. At most `groth16::MAX_BATCH_PROOFS` payments, all proven
+    /// against roots held by the same root source. Each pays its one output point to its
+    /// own recipient.
+    /// 
+    /// Accounts expected:
+    /// 0. `[signer, writable]` Sender account (pays nullifier, output point and note rent)
+    /// 1. `[]` Protocol state account (PDA)
+    /// 2. `[]` Groth16 verifier program
+    /// 3. `[]` Payment circuit key account (PDA)
+    /// 4. `[]` System program
+    /// 5. `[]` Root history account (PDA), or the archived tree holding every `merkle_root`
+    /// 6. `[]` Instructions sysvar
+    /// 7. `[writable]` Nullifier filter account (PDA; may not exist yet)
+    /// 8. `[writable]` Commitment tree account (PDA)
+    /// 9. `[writable]` Root history account (PDA)
+    /// 10..N. `[writable]` For each payment in order: its recipient account, input point
+    ///    accounts (row-major), nullifier accounts, output point account and encrypted note
+    ///    account (PDAs)
+    /// N+1.. `[]` Nullifier shard accounts (PDAs), one per nullifier prefix of the batch
+    #[account(
+        0,
+        writable,
+        signer,
+        name = "sender",
+        desc = "Sender account (pays nullifier, output point and note rent)"
+    )]
+    #[account(1, name = "protocol_state", desc = "Protocol state account (PDA)")]
+    #[account(2, name = "verifier_program", desc = "Groth16 verifier program")]
+    #[account(3, name = "circuit_key", desc = "Payment circuit key account (PDA)")]
+    #[account(4, name = "system_program", desc = "System program")]
+    #[account(
+        5,
+        name = "root_history",
+        desc = "Root history account (PDA), or the archived tree holding every `merkle_root`"
+    )]
+    #[account(6, name = "instructions_sysvar", desc = "Instructions sysvar")]
+    #[account(
+        7,
+        writable,
+        name = "nullifier_filter",
+        desc = "Nullifier filter account (PDA; may not exist yet)"
+    )]
+    #[account(8, writable, name = "commitment_tree", desc = "Commitment tree account (PDA)")]
+    #[account(9, writable, name = "root_history", desc = "Root history account (PDA)")]
+    #[account(
+        10,
+        writable,
+        name = "payment_accounts",
+        desc = "10..N: Per payment, its recipient, input points, nullifiers, output point and note"
+    )]
+    #[account(
+        11,
+        name = "nullifier_shards",
+        desc = "N+1..: Nullifier shard accounts (PDAs), one per nullifier prefix of the batch"
+    )]
+    BatchPrivacyPayment {
+        payments: Vec<BatchedPayment>,
+    },
+}
+
+
+/// Creates an `Initialize` instruction
+pub fn initialize(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    usdt_mint: &Pubkey,
+    deposit_fee_rate: u16,
+    withdrawal_fee_rate: u16,
+    root_history_size: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::Initialize {
+        deposit_fee_rate,
+        withdrawal_fee_rate,
+        root_history_size,
+    }
+    .try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new_readonly(ProtocolState::find_treasury_authority(program_id).0, false),
+        AccountMeta::new_readonly(*usdt_mint, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(CommitmentTree::find_address(program_id).0, false),
+        AccountMeta::new(RootHistory::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `Deposit` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn deposit(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token: &Pubkey,
+    treasury_token: &Pubkey,
+    protocol_state: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+    denomination: u64,
+    commitments: Vec<[u8; 32]>,
+    opening_proofs: Vec<OpeningProof>,
+    epoch: u64,
+) -> Result<Instruction, ProgramError> {
+    deposit_with_referrer(
+        program_id,
+        user,
+        user_token,
+        treasury_token,
+        protocol_state,
+        mint,
+        token_program,
+        amount,
+        denomination,
+        commitments,
+        opening_proofs,
+        None,
+        epoch,
+    )
+}
+
+/// Creates a `Deposit` instruction crediting `referrer`, if any, with part of the fee
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_with_referrer(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token: &Pubkey,
+    treasury_token: &Pubkey,
+    protocol_state: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+    denomination: u64,
+    commitments: Vec<[u8; 32]>,
+    opening_proofs: Vec<OpeningProof>,
+    referrer: Option<Pubkey>,
+    epoch: u64,
+) -> Result<Instruction, ProgramError> {
+    let points: Vec<Pubkey> = commitments
+        .iter()
+        .map(|commitment| pda::find_point_address(commitment, program_id).0)
+        .collect();
+    let (first_point, other_points) = points.split_first().ok_or(ProgramError::InvalidArgument)?;
+    let data = FPPInstruction::Deposit {
+        amount,
+        denomination,
+        commitments,
+        opening_proofs,
+        referrer,
+    }
+    .try_to_vec()?;
+    let mut accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(*user_token, false),
+        AccountMeta::new(*treasury_token, false),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(*first_point, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(CommitmentTree::find_address(program_id).0, false),
+        AccountMeta::new(RootHistory::find_address(program_id).0, false),
+        AccountMeta::new(UserActivity::find_address(user, program_id).0, false),
+        AccountMeta::new_readonly(DenyList::find_address(program_id).0, false),
+        AccountMeta::new(SupportedMint::find_address(mint, program_id).0, false),
+        AccountMeta::new(RewardAccount::find_address(user, program_id).0, false),
+        AccountMeta::new_readonly(FeeExemptions::find_address(program_id).0, false),
+        AccountMeta::new(EpochStats::find_address(epoch, program_id).0, false),
+        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+    ];
+    if let Some(referrer) = referrer {
+        let (referral, _) = ReferralAccount::find_address(&referrer, mint, program_id);
+        accounts.push(AccountMeta::new(referral, false));
+    }
+    for point in other_points {
+        accounts.push(AccountMeta::new(*point, false));
+    }
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `PrivacyPayment` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn privacy_payment(
+    program_id: &Pubkey,
+    sender: &Pubkey,
+    protocol_state: &Pubkey,
+    recipient: &Pubkey,
+    ring_members: &[Pubkey],
+    output_points: &[Pubkey],
+    verifier_program: &Pubkey,
+    merkle_root: [u8; 32],
+    input_nullifiers: Vec<[u8; 32]>,
+    output_commitments: Vec<[u8; 32]>,
+    proof: Vec<u8>,
+    ring_signature: Vec<u8>,
+    range_proof_offset: i8,
+    encrypted_notes: Vec<Vec<u8>>,
+    archived_tree: Option<u32>,
+) -> Result<Instruction, ProgramError> {
+    let nullifier_accounts: Vec<Pubkey> = input_nullifiers
+        .iter()
+        .map(|nullifier| pda::find_nullifier(nullifier, program_id).0)
+        .collect();
+    let note_accounts: Vec<Pubkey> = output_commitments
+        .iter()
+        .map(|commitment| EncryptedNote::find_address(commitment, program_id).0)
+        .collect();
+    
+    let mut accounts = Vec::with_capacity(
+        11 + ring_members.len()
+            + nullifier_accounts.len()
+            + output_points.len()
+            + note_accounts.len(),
+    );
+    accounts.push(AccountMeta::new(*sender, true));
+    accounts.push(AccountMeta::new(*protocol_state, false));
+    accounts.push(AccountMeta::new_readonly(*recipient, false));
+    for member in ring_members {
+        accounts.push(AccountMeta::new(*member, false));
+    }
+    for nullifier in &nullifier_accounts {
+        accounts.push(AccountMeta::new(*nullifier, false));
+    }
+    for point in output_points {
+        accounts.push(AccountMeta::new(*point, false));
+    }
+    for note in &note_accounts {
+        accounts.push(AccountMeta::new(*note, false));
+    }
+    accounts.push(AccountMeta::new_readonly(*verifier_program, false));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    let root_source = match archived_tree {
+        Some(generation) => CommitmentTree::find_archive_address(generation, program_id).0,
+        None => RootHistory::find_address(program_id).0,
+    };
+    accounts.push(AccountMeta::new_readonly(root_source, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    accounts.push(AccountMeta::new(NullifierFilter::find_address(program_id).0, false));
+    accounts.push(AccountMeta::new(CommitmentTree::find_address(program_id).0, false));
+    accounts.push(AccountMeta::new(RootHistory::find_address(program_id).0, false));
+    if !proof.is_empty() {
+        let (circuit_key, _) =
+            CircuitKey::find_address(CircuitInfo::PAYMENT_CIRCUIT_ID, program_id);
+        accounts.push(AccountMeta::new_readonly(circuit_key, false));
+    }
+    accounts.extend(nullifier_shard_accounts(program_id, &input_nullifiers));
+    
+    let data = FPPInstruction::PrivacyPayment {
+        merkle_root,
+        input_nullifiers,
+        output_commitments,
+        proof,
+        ring_signature,
+        range_proof_offset,
+        encrypted_notes,
+    }
+    .try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RequestWithdrawal` instruction
+pub fn request_withdrawal(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    protocol_state: &Pubkey,
+    point_ids: Vec<Pubkey>,
+    nullifiers: Vec<[u8; 32]>,
+    nonce: u64,
+    spend_signature: Vec<u8>,
+) -> Result<Instruction, ProgramError> {
+    let (withdrawal_request, _) = pda::find_withdrawal_request(user, nonce, program_id);
+    let mut accounts = Vec::with_capacity(10 + point_ids.len() + nullifiers.len());
+    accounts.push(AccountMeta::new(*user, true));
+    accounts.push(AccountMeta::new(*protocol_state, false));
+    accounts.push(AccountMeta::new(withdrawal_request, false));
+    for point in &point_ids {
+        accounts.push(AccountMeta::new(*point, false));
+    }
+    for nullifier in &nullifiers {
+        let (address, _) = pda::find_nullifier(nullifier, program_id);
+        accounts.push(AccountMeta::new(address, false));
+    }
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new(UserActivity::find_address(user, program_id).0, false));
+    accounts.push(AccountMeta::new(NullifierFilter::find_address(program_id).0, false));
+    accounts.push(AccountMeta::new(RewardAccount::find_address(user, program_id).0, false));
+    accounts.push(AccountMeta::new(WithdrawalQueue::find_address(program_id).0, false));
+    accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    accounts.extend(nullifier_shard_accounts(program_id, &nullifiers));
+    
+    let data = FPPInstruction::RequestWithdrawal {
+        point_ids,
+        nullifiers,
+        nonce,
+        spend_signature,
+    }
+    .try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CompleteWithdrawal` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn complete_withdrawal(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token: &Pubkey,
+    treasury_token: &Pubkey,
+    protocol_state: &Pubkey,
+    withdrawal_request: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    unwrap_sol: bool,
+    epoch: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::CompleteWithdrawal { unwrap_sol }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(*user_token, false),
+        AccountMeta::new(*treasury_token, false),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(*withdrawal_request, false),
+        AccountMeta::new_readonly(ProtocolState::find_treasury_authority(program_id).0, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(DenyList::find_address(program_id).0, false),
+        AccountMeta::new_readonly(AssociationSet::find_address(program_id).0, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(SupportedMint::find_address(mint, program_id).0, false),
+        AccountMeta::new_readonly(FeeExemptions::find_address(program_id).0, false),
+        AccountMeta::new(EpochStats::find_address(epoch, program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(WithdrawalQueue::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CancelWithdrawal` instruction
+pub fn cancel_withdrawal(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    protocol_state: &Pubkey,
+    withdrawal_request: &Pubkey,
+    point_ids: &[Pubkey],
+    nullifiers: &[[u8; 32]],
+    permanent: bool,
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = Vec::with_capacity(6 + point_ids.len() + nullifiers.len());
+    accounts.push(AccountMeta::new(*user, true));
+    accounts.push(AccountMeta::new(*protocol_state, false));
+    accounts.push(AccountMeta::new(*withdrawal_request, false));
+    for point in point_ids {
+        accounts.push(AccountMeta::new(*point, false));
+    }
+    for nullifier in nullifiers {
+        let (address, _) = pda::find_nullifier(nullifier, program_id);
+        accounts.push(AccountMeta::new(address, false));
+    }
+    accounts.push(AccountMeta::new(UserActivity::find_address(user, program_id).0, false));
+    accounts.push(AccountMeta::new(RewardAccount::find_address(user, program_id).0, false));
+    accounts.push(AccountMeta::new(WithdrawalQueue::find_address(program_id).0, false));
+    
+    let data = FPPInstruction::CancelWithdrawal { permanent }.try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CreatePool` instruction
+pub fn create_pool(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    protocol_state: &Pubkey,
+    mint: &Pubkey,
+    denomination: u64,
+    circuit_id: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::CreatePool {
+        denomination,
+        circuit_id,
+    }
+    .try_to_vec()?;
+    let (pool_config, _) = PoolConfig::find_address(mint, denomination, program_id);
+    let accounts = vec![
+        AccountMeta::new(*creator, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(pool_config, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(CircuitInfo::find_address(circuit_id, program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetPoolBounds` instruction
+pub fn set_pool_bounds(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    min_denomination: u64,
+    max_denomination: u64,
+    creation_bond: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetPoolBounds {
+        min_denomination,
+        max_denomination,
+        creation_bond,
+    }
+    .try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RegisterCircuit` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn register_circuit(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    payer: &Pubkey,
+    circuit_id: u16,
+    tree_depth: u8,
+    max_inputs: u8,
+    max_outputs: u8,
+    verifying_key_hash: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::RegisterCircuit {
+        circuit_id,
+        tree_depth,
+        max_inputs,
+        max_outputs,
+        verifying_key_hash,
+    }
+    .try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(CircuitInfo::find_address(circuit_id, program_id).0, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetCommitmentValidation` instruction
+pub fn set_commitment_validation(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    required: bool,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetCommitmentValidation { required }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `OpenWithdrawalBatch` instruction
+pub fn open_withdrawal_batch(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    batch_id: u64,
+) -> Result<Instruction, ProgramError> {
+    let (batch, _) = WithdrawalBatch::find_address(user, batch_id, program_id);
+    let data = FPPInstruction::OpenWithdrawalBatch { batch_id }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(batch, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `AppendToWithdrawal` instruction
+pub fn append_to_withdrawal(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    protocol_state: &Pubkey,
+    batch_id: u64,
+    point_ids: Vec<Pubkey>,
+    nullifiers: Vec<[u8; 32]>,
+    spend_signature: Vec<u8>,
+) -> Result<Instruction, ProgramError> {
+    let (batch, _) = WithdrawalBatch::find_address(user, batch_id, program_id);
+    let mut accounts = Vec::with_capacity(6 + point_ids.len() + nullifiers.len());
+    accounts.push(AccountMeta::new(*user, true));
+    accounts.push(AccountMeta::new(batch, false));
+    for point in &point_ids {
+        accounts.push(AccountMeta::new(*point, false));
+    }
+    for nullifier in &nullifiers {
+        let (address, _) = pda::find_nullifier(nullifier, program_id);
+        accounts.push(AccountMeta::new(address, false));
+    }
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new_readonly(*protocol_state, false));
+    accounts.push(AccountMeta::new(NullifierFilter::find_address(program_id).0, false));
+    accounts.extend(nullifier_shard_accounts(program_id, &nullifiers));
+    
+    let data = FPPInstruction::AppendToWithdrawal {
+        batch_id,
+        point_ids,
+        nullifiers,
+        spend_signature,
+    }
+    .try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `FinalizeWithdrawal` instruction
+pub fn finalize_withdrawal(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    protocol_state: &Pubkey,
+    batch_id: u64,
+) -> Result<Instruction, ProgramError> {
+    let (batch, _) = WithdrawalBatch::find_address(user, batch_id, program_id);
+    let (withdrawal_request, _) = pda::find_withdrawal_request(user, batch_id, program_id);
+    let data = FPPInstruction::FinalizeWithdrawal { batch_id }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(batch, false),
+        AccountMeta::new(withdrawal_request, false),
+        AccountMeta::new(UserActivity::find_address(user, program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(RewardAccount::find_address(user, program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetGuardian` instruction
+pub fn set_guardian(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    guardian: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetGuardian { guardian }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RecordSecurityEvent` instruction; `index` is the protocol's current
+/// `security_event_count`
+#[allow(clippy::too_many_arguments)]
+pub fn record_security_event(
+    program_id: &Pubkey,
+    guardian: &Pubkey,
+    protocol_state: &Pubkey,
+    index: u64,
+    kind: SecurityEventKind,
+    severity: SecuritySeverity,
+    subject: Pubkey,
+    message: String,
+) -> Result<Instruction, ProgramError> {
+    let (event, _) = SecurityEvent::find_address(index, program_id);
+    let data = FPPInstruction::RecordSecurityEvent {
+        kind,
+        severity,
+        subject,
+        message,
+    }
+    .try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*guardian, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(event, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `ResolveSecurityEvent` instruction
+pub fn resolve_security_event(
+    program_id: &Pubkey,
+    guardian: &Pubkey,
+    protocol_state: &Pubkey,
+    index: u64,
+) -> Result<Instruction, ProgramError> {
+    let (event, _) = SecurityEvent::find_address(index, program_id);
+    let data = FPPInstruction::ResolveSecurityEvent { index }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*guardian, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(event, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SplitPoint` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn split_point(
+    program_id: &Pubkey,
+    holder: &Pubkey,
+    protocol_state: &Pubkey,
+    point: &Pubkey,
+    nullifier: [u8; 32],
+    output_commitments: Vec<[u8; 32]>,
+    output_masses: Vec<u64>,
+    opening_proofs: Vec<OpeningProof>,
+    spend_signature: Vec<u8>,
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = Vec::with_capacity(9 + output_commitments.len());
+    accounts.push(AccountMeta::new(*holder, true));
+    accounts.push(AccountMeta::new(*protocol_state, false));
+    accounts.push(AccountMeta::new(*point, false));
+    accounts.push(AccountMeta::new(pda::find_nullifier(&nullifier, program_id).0, false));
+    for commitment in &output_commitments {
+        let (address, _) = pda::find_point_address(commitment, program_id);
+        accounts.push(AccountMeta::new(address, false));
+    }
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new(CommitmentTree::find_address(program_id).0, false));
+    accounts.push(AccountMeta::new(RootHistory::find_address(program_id).0, false));
+    accounts.push(AccountMeta::new(NullifierFilter::find_address(program_id).0, false));
+    accounts.extend(nullifier_shard_accounts(program_id, &[nullifier]));
+    
+    let data = FPPInstruction::SplitPoint {
+        nullifier,
+        output_commitments,
+        output_masses,
+        opening_proofs,
+        spend_signature,
+    }
+    .try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `TransferPoint` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_point(
+    program_id: &Pubkey,
+    holder: &Pubkey,
+    protocol_state: &Pubkey,
+    point: &Pubkey,
+    new_owner: Pubkey,
+    new_commitment: [u8; 32],
+    nullifier: [u8; 32],
+    spend_signature: Vec<u8>,
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = vec![
+        AccountMeta::new(*holder, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(*point, false),
+        AccountMeta::new(CommitmentTree::find_address(program_id).0, false),
+        AccountMeta::new(RootHistory::find_address(program_id).0, false),
+        AccountMeta::new(pda::find_point_address(&new_commitment, program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(pda::find_nullifier(&nullifier, program_id).0, false),
+        AccountMeta::new(NullifierFilter::find_address(program_id).0, false),
+    ];
+    accounts.extend(nullifier_shard_accounts(program_id, &[nullifier]));
+    
+    let data = FPPInstruction::TransferPoint {
+        new_owner,
+        new_commitment,
+        nullifier,
+        spend_signature,
+    }
+    .try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `ReportTreeHealth` instruction
+pub fn report_tree_health(program_id: &Pubkey) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::ReportTreeHealth.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(CommitmentTree::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RolloverTree` instruction archiving tree `generation`
+/// 
+/// `authority` signs separately for an early rollover someone else pays for.
+pub fn rollover_tree(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    protocol_state: &Pubkey,
+    generation: u32,
+    authority: Option<&Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::RolloverTree.try_to_vec()?;
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(CommitmentTree::find_address(program_id).0, false),
+        AccountMeta::new(CommitmentTree::find_archive_address(generation, program_id).0, false),
+        AccountMeta::new(RootHistory::find_address(program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(authority) = authority {
+        accounts.push(AccountMeta::new_readonly(*authority, true));
+    }
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetDenominations` instruction
+pub fn set_denominations(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    denominations: Vec<u64>,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetDenominations { denominations }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `EmergencyWithdraw` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn emergency_withdraw(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_token: &Pubkey,
+    treasury_token: &Pubkey,
+    protocol_state: &Pubkey,
+    withdrawal_request: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    unwrap_sol: bool,
+    epoch: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::EmergencyWithdraw { unwrap_sol }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(*user_token, false),
+        AccountMeta::new(*treasury_token, false),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(*withdrawal_request, false),
+        AccountMeta::new_readonly(ProtocolState::find_treasury_authority(program_id).0, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(DenyList::find_address(program_id).0, false),
+        AccountMeta::new_readonly(AssociationSet::find_address(program_id).0, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(SupportedMint::find_address(mint, program_id).0, false),
+        AccountMeta::new_readonly(FeeExemptions::find_address(program_id).0, false),
+        AccountMeta::new(EpochStats::find_address(epoch, program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(WithdrawalQueue::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetEmergencyPenalty` instruction
+pub fn set_emergency_penalty(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    penalty_rate: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetEmergencyPenalty { penalty_rate }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `UpdateContactBook` instruction
+pub fn update_contact_book(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    expected_revision: u64,
+    ciphertext: Vec<u8>,
+) -> Result<Instruction, ProgramError> {
+    let (contact_book, _) = ContactBook::find_address(owner, program_id);
+    let data = FPPInstruction::UpdateContactBook {
+        expected_revision,
+        ciphertext,
+    }
+    .try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(contact_book, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CloseContactBook` instruction
+pub fn close_contact_book(program_id: &Pubkey, owner: &Pubkey) -> Result<Instruction, ProgramError> {
+    let (contact_book, _) = ContactBook::find_address(owner, program_id);
+    let data = FPPInstruction::CloseContactBook.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new(contact_book, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `ProposeAuthority` instruction
+pub fn propose_authority(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    new_authority: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::ProposeAuthority { new_authority }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `AcceptAuthority` instruction
+pub fn accept_authority(
+    program_id: &Pubkey,
+    new_authority: &Pubkey,
+    protocol_state: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::AcceptAuthority.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*new_authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `UpdateFees` instruction
+pub fn update_fees(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    deposit_fee_rate: u16,
+    withdrawal_fee_rate: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::UpdateFees {
+        deposit_fee_rate,
+        withdrawal_fee_rate,
+    }
+    .try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetPaused` instruction
+pub fn set_paused(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    paused: bool,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetPaused { paused }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CreateAdminMultisig` instruction
+pub fn create_admin_multisig(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    signers: Vec<Pubkey>,
+    threshold: u8,
+) -> Result<Instruction, ProgramError> {
+    let (multisig, _) = AdminMultisig::find_address(program_id);
+    let data = FPPInstruction::CreateAdminMultisig { signers, threshold }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(multisig, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `ProposeAdminAction` instruction for proposal number `index`
+///
+/// `index` is the multisig's current `proposal_count`.
+pub fn propose_admin_action(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    index: u64,
+    action: AdminAction,
+) -> Result<Instruction, ProgramError> {
+    let (multisig, _) = AdminMultisig::find_address(program_id);
+    let (proposal, _) = AdminProposal::find_address(index, program_id);
+    let data = FPPInstruction::ProposeAdminAction { action }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*proposer, true),
+        AccountMeta::new(multisig, false),
+        AccountMeta::new(proposal, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `ApproveAdminAction` instruction
+pub fn approve_admin_action(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    index: u64,
+) -> Result<Instruction, ProgramError> {
+    let (multisig, _) = AdminMultisig::find_address(program_id);
+    let (proposal, _) = AdminProposal::find_address(index, program_id);
+    let data = FPPInstruction::ApproveAdminAction.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*signer, true),
+        AccountMeta::new_readonly(multisig, false),
+        AccountMeta::new(proposal, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `ExecuteAdminAction` instruction
+pub fn execute_admin_action(
+    program_id: &Pubkey,
+    executor: &Pubkey,
+    protocol_state: &Pubkey,
+    index: u64,
+) -> Result<Instruction, ProgramError> {
+    let (multisig, _) = AdminMultisig::find_address(program_id);
+    let (proposal, _) = AdminProposal::find_address(index, program_id);
+    let data = FPPInstruction::ExecuteAdminAction.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*executor, true),
+        AccountMeta::new(multisig, false),
+        AccountMeta::new(proposal, false),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new_readonly(*program_id, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `ApplyPendingConfig` instruction
+pub fn apply_pending_config(
+    program_id: &Pubkey,
+    protocol_state: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::ApplyPendingConfig.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*protocol_state, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetFeeReceiver` instruction
+pub fn set_fee_receiver(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    fee_receiver: Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetFeeReceiver { fee_receiver }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CollectFees` instruction
+pub fn collect_fees(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    treasury_token: &Pubkey,
+    fee_receiver_token: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::CollectFees.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(*treasury_token, false),
+        AccountMeta::new_readonly(ProtocolState::find_treasury_authority(program_id).0, false),
+        AccountMeta::new(*fee_receiver_token, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(SupportedMint::find_address(mint, program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetPausedFlags` instruction
+pub fn set_paused_flags(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    flags: u8,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetPausedFlags { flags }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetRateLimits` instruction
+pub fn set_rate_limits(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    limits: RateLimits,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetRateLimits { limits }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetMinExitSlots` instruction
+pub fn set_min_exit_slots(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    slots: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetMinExitSlots { slots }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `AddToDenyList` instruction
+pub fn add_to_deny_list(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    payer: &Pubkey,
+    entry: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::AddToDenyList { entry }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(DenyList::find_address(program_id).0, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RemoveFromDenyList` instruction
+pub fn remove_from_deny_list(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    entry: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::RemoveFromDenyList { entry }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(DenyList::find_address(program_id).0, false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `UpdateAssociationSet` instruction
+pub fn update_association_set(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    payer: &Pubkey,
+    flagged_root: [u8; 32],
+    required: bool,
+    verifying_key: Option<Groth16VerifyingKey>,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::UpdateAssociationSet {
+        flagged_root,
+        required,
+        verifying_key,
+    }
+    .try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(AssociationSet::find_address(program_id).0, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `ProveWithdrawalCompliance` instruction
+pub fn prove_withdrawal_compliance(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    withdrawal_request: &Pubkey,
+    proof: Groth16Proof,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::ProveWithdrawalCompliance { proof }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*user, true),
+        AccountMeta::new(*withdrawal_request, false),
+        AccountMeta::new_readonly(AssociationSet::find_address(program_id).0, false),
+        AccountMeta::new_readonly(fpp_verifier::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `AddSupportedMint` instruction
+pub fn add_supported_mint(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    payer: &Pubkey,
+    mint: Pubkey,
+    config: SupportedMintConfig,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::AddSupportedMint { mint, config }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(SupportedMint::find_address(&mint, program_id).0, false),
+        AccountMeta::new_readonly(mint, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `ResizeRootHistory` instruction
+pub fn resize_root_history(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    payer: &Pubkey,
+    capacity: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::ResizeRootHistory { capacity }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(RootHistory::find_address(program_id).0, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Number of `ExtendNullifierFilter` calls that allocate the whole filter
+pub fn nullifier_filter_calls() -> usize {
+    NullifierFilter::LEN.div_ceil(MAX_PERMITTED_DATA_INCREASE)
+}
+
+/// The `NullifierShard` accounts a spend of `nullifiers` passes last, one per distinct prefix
+pub fn nullifier_shard_accounts(
+    program_id: &Pubkey,
+    nullifiers: &[[u8; 32]],
+) -> Vec<AccountMeta> {
+    let mut shards: Vec<Pubkey> = Vec::with_capacity(nullifiers.len());
+    for nullifier in nullifiers {
+        let (shard, _) = NullifierShard::find_address(nullifier, program_id);
+        if !shards.contains(&shard) {
+            shards.push(shard);
+        }
+    }
+    shards
+        .into_iter()
+        .map(|shard| AccountMeta::new_readonly(shard, false))
+        .collect()
+}
+
+/// Creates an `ExtendNullifierFilter` instruction
+pub fn extend_nullifier_filter(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    payer: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::ExtendNullifierFilter.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(NullifierFilter::find_address(program_id).0, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CreatePointTree` instruction
+pub fn create_point_tree(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    payer: &Pubkey,
+    merkle_tree: &Pubkey,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::CreatePointTree {
+        max_depth,
+        max_buffer_size,
+    }
+    .try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(PointTree::find_address(program_id).0, false),
+        AccountMeta::new(compression::find_tree_config(merkle_tree).0, false),
+        AccountMeta::new(*merkle_tree, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(NOOP_ID, false),
+        AccountMeta::new_readonly(ACCOUNT_COMPRESSION_ID, false),
+        AccountMeta::new_readonly(BUBBLEGUM_ID, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CompressPoint` instruction
+pub fn compress_point(
+    program_id: &Pubkey,
+    holder: &Pubkey,
+    protocol_state: &Pubkey,
+    point: &Pubkey,
+    merkle_tree: &Pubkey,
+    nullifier: [u8; 32],
+    spend_signature: Vec<u8>,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::CompressPoint {
+        nullifier,
+        spend_signature,
+    }
+    .try_to_vec()?;
+    let mut accounts = vec![
+        AccountMeta::new(*holder, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(*point, false),
+        AccountMeta::new(PointTree::find_address(program_id).0, false),
+        AccountMeta::new(compression::find_tree_config(merkle_tree).0, false),
+        AccountMeta::new(*merkle_tree, false),
+        AccountMeta::new_readonly(NOOP_ID, false),
+        AccountMeta::new_readonly(ACCOUNT_COMPRESSION_ID, false),
+        AccountMeta::new_readonly(BUBBLEGUM_ID, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(pda::find_nullifier(&nullifier, program_id).0, false),
+        AccountMeta::new(NullifierFilter::find_address(program_id).0, false),
+    ];
+    accounts.extend(nullifier_shard_accounts(program_id, &[nullifier]));
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `DecompressPoint` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn decompress_point(
+    program_id: &Pubkey,
+    holder: &Pubkey,
+    protocol_state: &Pubkey,
+    merkle_tree: &Pubkey,
+    point: CompressedPoint,
+    new_commitment: [u8; 32],
+    root: [u8; 32],
+    nonce: u64,
+    index: u32,
+    proof: &[Pubkey],
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::DecompressPoint {
+        point,
+        new_commitment,
+        root,
+        nonce,
+        index,
+    }
+    .try_to_vec()?;
+    let mut accounts = Vec::with_capacity(12 + proof.len());
+    accounts.push(AccountMeta::new(*holder, true));
+    accounts.push(AccountMeta::new_readonly(*protocol_state, false));
+    accounts.push(AccountMeta::new(PointTree::find_address(program_id).0, false));
+    accounts.push(AccountMeta::new(compression::find_tree_config(merkle_tree).0, false));
+    accounts.push(AccountMeta::new(*merkle_tree, false));
+    accounts.push(AccountMeta::new_readonly(NOOP_ID, false));
+    accounts.push(AccountMeta::new_readonly(ACCOUNT_COMPRESSION_ID, false));
+    accounts.push(AccountMeta::new_readonly(BUBBLEGUM_ID, false));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new(pda::find_point_address(&new_commitment, program_id).0, false));
+    accounts.push(AccountMeta::new(CommitmentTree::find_address(program_id).0, false));
+    accounts.push(AccountMeta::new(RootHistory::find_address(program_id).0, false));
+    for node in proof {
+        accounts.push(AccountMeta::new_readonly(*node, false));
+    }
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `AttachPointMetadata` instruction
+pub fn attach_point_metadata(
+    program_id: &Pubkey,
+    holder: &Pubkey,
+    point: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::AttachPointMetadata.try_to_vec()?;
+    let (mint, _) = nft::find_mint(point, program_id);
+    let holder_token = spl_associated_token_account::get_associated_token_address(holder, &mint);
+    let accounts = vec![
+        AccountMeta::new(*holder, true),
+        AccountMeta::new_readonly(*point, false),
+        AccountMeta::new(mint, false),
+        AccountMeta::new(holder_token, false),
+        AccountMeta::new(nft::find_metadata(&mint).0, false),
+        AccountMeta::new_readonly(nft::find_authority(program_id).0, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+        AccountMeta::new_readonly(TOKEN_METADATA_ID, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetRelayerConfig` instruction
+pub fn set_relayer_config(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    payer: &Pubkey,
+    max_fee_rate: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetRelayerConfig { max_fee_rate }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(RelayerConfig::find_address(program_id).0, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RelayWithdrawal` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn relay_withdrawal(
+    program_id: &Pubkey,
+    relayer: &Pubkey,
+    protocol_state: &Pubkey,
+    point_ids: Vec<Pubkey>,
+    nullifiers: Vec<[u8; 32]>,
+    nonce: u64,
+    recipient: Pubkey,
+    relayer_fee: u64,
+    spend_signature: Vec<u8>,
+) -> Result<Instruction, ProgramError> {
+    let (withdrawal_request, _) = pda::find_withdrawal_request(&recipient, nonce, program_id);
+    let mut accounts = Vec::with_capacity(9 + point_ids.len() + nullifiers.len());
+    accounts.push(AccountMeta::new(*relayer, true));
+    accounts.push(AccountMeta::new(*protocol_state, false));
+    accounts.push(AccountMeta::new(withdrawal_request, false));
+    for point in &point_ids {
+        accounts.push(AccountMeta::new(*point, false));
+    }
+    for nullifier in &nullifiers {
+        let (address, _) = pda::find_nullifier(nullifier, program_id);
+        accounts.push(AccountMeta::new(address, false));
+    }
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new(UserActivity::find_address(&recipient, program_id).0, false));
+    accounts.push(AccountMeta::new_readonly(RelayerConfig::find_address(program_id).0, false));
+    accounts.push(AccountMeta::new(NullifierFilter::find_address(program_id).0, false));
+    accounts.push(AccountMeta::new(RewardAccount::find_address(&recipient, program_id).0, false));
+    accounts.extend(nullifier_shard_accounts(program_id, &nullifiers));
+    
+    let data = FPPInstruction::RelayWithdrawal {
+        point_ids,
+        nullifiers,
+        nonce,
+        recipient,
+        relayer_fee,
+        spend_signature,
+    }
+    .try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CompleteRelayedWithdrawal` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn complete_relayed_withdrawal(
+    program_id: &Pubkey,
+    submitter: &Pubkey,
+    recipient_token: &Pubkey,
+    relayer_token: &Pubkey,
+    treasury_token: &Pubkey,
+    protocol_state: &Pubkey,
+    withdrawal_request: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    epoch: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::CompleteRelayedWithdrawal.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*submitter, true),
+        AccountMeta::new(*recipient_token, false),
+        AccountMeta::new(*relayer_token, false),
+        AccountMeta::new(*treasury_token, false),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(*withdrawal_request, false),
+        AccountMeta::new_readonly(ProtocolState::find_treasury_authority(program_id).0, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(DenyList::find_address(program_id).0, false),
+        AccountMeta::new_readonly(AssociationSet::find_address(program_id).0, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(SupportedMint::find_address(mint, program_id).0, false),
+        AccountMeta::new_readonly(FeeExemptions::find_address(program_id).0, false),
+        AccountMeta::new(EpochStats::find_address(epoch, program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `DelegatedDeposit` instruction
+/// 
+/// The transaction must also carry the user's Ed25519 signature over
+/// `DepositPermit::message`, `signature_offset` instructions away from this one.
+#[allow(clippy::too_many_arguments)]
+pub fn delegated_deposit(
+    program_id: &Pubkey,
+    sponsor: &Pubkey,
+    user: &Pubkey,
+    user_token: &Pubkey,
+    treasury_token: &Pubkey,
+    protocol_state: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+    denomination: u64,
+    commitments: Vec<[u8; 32]>,
+    opening_proofs: Vec<OpeningProof>,
+    expiry: i64,
+    nonce: u64,
+    signature_offset: i8,
+    epoch: u64,
+) -> Result<Instruction, ProgramError> {
+    let mut deposit = deposit(
+        program_id,
+        user,
+        user_token,
+        treasury_token,
+        protocol_state,
+        mint,
+        token_program,
+        amount,
+        denomination,
+        commitments.clone(),
+        opening_proofs.clone(),
+        epoch,
+    )?;
+    // Same accounts as `Deposit`, but the user doesn't sign
+    deposit.accounts[0].is_signer = false;
+    deposit.accounts[0].is_writable = false;
+    
+    let mut accounts = vec![
+        AccountMeta::new(*sponsor, true),
+        AccountMeta::new(DepositPermit::find_address(user, nonce, program_id).0, false),
+        AccountMeta::new_readonly(DepositPermit::find_authority(program_id).0, false),
+        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+    ];
+    accounts.append(&mut deposit.accounts);
+    
+    let data = FPPInstruction::DelegatedDeposit {
+        amount,
+        denomination,
+        commitments,
+        opening_proofs,
+        expiry,
+        nonce,
+        signature_offset,
+    }
+    .try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `MigrateState` instruction
+pub fn migrate_state(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    payer: &Pubkey,
+    kind: AccountKind,
+    account: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::MigrateState { kind }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(*account, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CloseSpentPoint` instruction
+pub fn close_spent_point(
+    program_id: &Pubkey,
+    point: &Pubkey,
+    rent_payer: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::CloseSpentPoint.try_to_vec()?;
+    let accounts = vec![AccountMeta::new(*point, false), AccountMeta::new(*rent_payer, false)];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CloseWithdrawalRequest` instruction
+///
+/// `point_ids` and `nullifiers` are the request's, and must be empty for a cancelled one.
+pub fn close_withdrawal_request(
+    program_id: &Pubkey,
+    withdrawal_request: &Pubkey,
+    rent_payer: &Pubkey,
+    point_ids: &[Pubkey],
+    nullifiers: &[[u8; 32]],
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = Vec::with_capacity(2 + point_ids.len() + nullifiers.len());
+    accounts.push(AccountMeta::new(*withdrawal_request, false));
+    accounts.push(AccountMeta::new(*rent_payer, false));
+    for point in point_ids {
+        accounts.push(AccountMeta::new(*point, false));
+    }
+    for nullifier in nullifiers {
+        let (address, _) = pda::find_nullifier(nullifier, program_id);
+        accounts.push(AccountMeta::new_readonly(address, false));
+    }
+    
+    let data = FPPInstruction::CloseWithdrawalRequest.try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CompactNullifiers` instruction for fetched nullifier records
+///
+/// `fee_receiver` is the protocol's, which is refunded for records with no payer.
+pub fn compact_nullifiers(
+    program_id: &Pubkey,
+    protocol_state: &Pubkey,
+    fee_receiver: &Pubkey,
+    records: &[NullifierSet],
+) -> Result<Instruction, ProgramError> {
+    let count = u8::try_from(records.len()).map_err(|_| ProgramError::InvalidArgument)?;
+    let mut accounts = Vec::with_capacity(3 + 4 * records.len());
+    accounts.push(AccountMeta::new_readonly(*protocol_state, false));
+    accounts.push(AccountMeta::new(NullifierFilter::find_address(program_id).0, false));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    for record in records {
+        let (address, _) = pda::find_nullifier(&record.nullifier, program_id);
+        let payer = if record.payer == Pubkey::default() {
+            fee_receiver
+        } else {
+            &record.payer
+        };
+        accounts.push(AccountMeta::new(address, false));
+        accounts.push(AccountMeta::new(*payer, false));
+        accounts.push(AccountMeta::new_readonly(record.withdrawal_request, false));
+        let (shard, _) = NullifierShard::find_address(&record.nullifier, program_id);
+        accounts.push(AccountMeta::new(shard, false));
+    }
+    
+    let data = FPPInstruction::CompactNullifiers { count }.try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `BatchProveWithdrawalCompliance` instruction
+pub fn batch_prove_withdrawal_compliance(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    withdrawal_requests: &[Pubkey],
+    proofs: Vec<Groth16Proof>,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::BatchProveWithdrawalCompliance { proofs }.try_to_vec()?;
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*user, true),
+        AccountMeta::new_readonly(AssociationSet::find_address(program_id).0, false),
+    ];
+    accounts.extend(withdrawal_requests.iter().map(|request| AccountMeta::new(*request, false)));
+    accounts.push(AccountMeta::new_readonly(fpp_verifier::id(), false));
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates the `StageProof` instructions that write `payment`, `chunk_len` bytes at a time
+pub fn stage_payment(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    nonce: u64,
+    payment: &StagedPayment,
+    chunk_len: usize,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let payload = payment.try_to_vec()?;
+    let payload_len = u32::try_from(payload.len()).map_err(|_| ProgramError::InvalidArgument)?;
+    let (staged_proof, _) = StagedProof::find_address(owner, nonce, program_id);
+    payload
+        .chunks(chunk_len.max(1))
+        .map(|chunk| {
+            let data = FPPInstruction::StageProof {
+                nonce,
+                payload_len,
+                chunk: chunk.to_vec(),
+            }
+            .try_to_vec()?;
+            Ok(Instruction {
+                program_id: *program_id,
+                accounts: vec![
+                    AccountMeta::new(*owner, true),
+                    AccountMeta::new(staged_proof, false),
+                    AccountMeta::new_readonly(system_program::id(), false),
+                ],
+                data,
+            })
+        })
+        .collect()
+}
+
+/// Creates a `VerifyStagedProof` instruction for the ring rows `ring_points` belong to
+///
+/// `first_row` and `ring_points` are the payload's ring entries for its first row and for
+/// these rows; `rows` is the latter's count divided by the number of inputs.
+pub fn verify_staged_proof(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    protocol_state: &Pubkey,
+    nonce: u64,
+    first_row: &[Pubkey],
+    ring_points: &[Pubkey],
+    rows: u8,
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(StagedProof::find_address(owner, nonce, program_id).0, false),
+    ];
+    accounts.extend(
+        first_row
+            .iter()
+            .chain(ring_points)
+            .map(|point| AccountMeta::new_readonly(*point, false)),
+    );
+    let data = FPPInstruction::VerifyStagedProof { rows }.try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `ExecuteStagedPayment` instruction for the payment staged under `nonce`
+#[allow(clippy::too_many_arguments)]
+pub fn execute_staged_payment(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    protocol_state: &Pubkey,
+    recipient: &Pubkey,
+    nonce: u64,
+    payment: &StagedPayment,
+    range_proof_offset: i8,
+    archived_tree: Option<u32>,
+) -> Result<Instruction, ProgramError> {
+    let root_source = match archived_tree {
+        Some(generation) => CommitmentTree::find_archive_address(generation, program_id).0,
+        None => RootHistory::find_address(program_id).0,
+    };
+    let mut accounts = vec![
+        AccountMeta::new(*owner, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(StagedProof::find_address(owner, nonce, program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(root_source, false),
+        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        AccountMeta::new(NullifierFilter::find_address(program_id).0, false),
+        AccountMeta::new_readonly(*recipient, false),
+        AccountMeta::new(CommitmentTree::find_address(program_id).0, false),
+        AccountMeta::new(RootHistory::find_address(program_id).0, false),
+    ];
+    let columns = payment.input_nullifiers.len().min(payment.ring.len());
+    for point in &payment.ring[..columns] {
+        accounts.push(AccountMeta::new_readonly(*point, false));
+    }
+    for nullifier in &payment.input_nullifiers {
+        accounts.push(AccountMeta::new(pda::find_nullifier(nullifier, program_id).0, false));
+    }
+    for commitment in &payment.output_commitments {
+        accounts.push(AccountMeta::new(pda::find_point_address(commitment, program_id).0, false));
+    }
+    for commitment in &payment.output_commitments {
+        let (note, _) = EncryptedNote::find_address(commitment, program_id);
+        accounts.push(AccountMeta::new(note, false));
+    }
+    if !payment.proof.is_empty() {
+        let (circuit_key, _) =
+            CircuitKey::find_address(CircuitInfo::PAYMENT_CIRCUIT_ID, program_id);
+        accounts.push(AccountMeta::new_readonly(fpp_verifier::id(), false));
+        accounts.push(AccountMeta::new_readonly(circuit_key, false));
+    }
+    accounts.extend(nullifier_shard_accounts(program_id, &payment.input_nullifiers));
+    let data = FPPInstruction::ExecuteStagedPayment { range_proof_offset }.try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CloseStagedProof` instruction
+pub fn close_staged_proof(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    owner: &Pubkey,
+    nonce: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::CloseStagedProof.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*caller, true),
+        AccountMeta::new(StagedProof::find_address(owner, nonce, program_id).0, false),
+        AccountMeta::new(*owner, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetTreasuryDeployment` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn set_treasury_deployment(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    payer: &Pubkey,
+    mint: &Pubkey,
+    collateral_token: &Pubkey,
+    reserve: ReserveKeys,
+    max_deployed_rate: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetTreasuryDeployment {
+        reserve,
+        max_deployed_rate,
+    }
+    .try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(TreasuryDeployment::find_address(mint, program_id).0, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(SupportedMint::find_address(mint, program_id).0, false),
+        AccountMeta::new_readonly(*collateral_token, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `DeployTreasury` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn deploy_treasury(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    treasury_token: &Pubkey,
+    collateral_token: &Pubkey,
+    reserve: &ReserveKeys,
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::DeployTreasury { amount }.try_to_vec()?;
+    let accounts = treasury_deployment_accounts(
+        program_id,
+        authority,
+        AccountMeta::new_readonly(*protocol_state, false),
+        mint,
+        token_program,
+        treasury_token,
+        collateral_token,
+        reserve,
+    );
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RecallTreasury` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn recall_treasury(
+    program_id: &Pubkey,
+    caller: &Pubkey,
+    protocol_state: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    treasury_token: &Pubkey,
+    collateral_token: &Pubkey,
+    reserve: &ReserveKeys,
+    collateral_amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::RecallTreasury { collateral_amount }.try_to_vec()?;
+    let mut accounts = treasury_deployment_accounts(
+        program_id,
+        caller,
+        AccountMeta::new(*protocol_state, false),
+        mint,
+        token_program,
+        treasury_token,
+        collateral_token,
+        reserve,
+    );
+    // Yield is credited to the fees of a secondary mint's entry
+    accounts[7].is_writable = true;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn treasury_deployment_accounts(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    protocol_state: AccountMeta,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    treasury_token: &Pubkey,
+    collateral_token: &Pubkey,
+    reserve: &ReserveKeys,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(*signer, true),
+        protocol_state,
+        AccountMeta::new(TreasuryDeployment::find_address(mint, program_id).0, false),
+        AccountMeta::new(*treasury_token, false),
+        AccountMeta::new_readonly(ProtocolState::find_treasury_authority(program_id).0, false),
+        AccountMeta::new(*collateral_token, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(SupportedMint::find_address(mint, program_id).0, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(reserve.lending_program, false),
+        AccountMeta::new(reserve.reserve, false),
+        AccountMeta::new(reserve.liquidity_supply, false),
+        AccountMeta::new(reserve.collateral_mint, false),
+        AccountMeta::new_readonly(reserve.lending_market, false),
+        AccountMeta::new_readonly(reserve.market_authority, false),
+    ]
+}
+
+/// Creates a `SetRewardConfig` instruction
+pub fn set_reward_config(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    vault: &Pubkey,
+    mint: &Pubkey,
+    rate: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetRewardConfig { rate }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new_readonly(*vault, false),
+        AccountMeta::new_readonly(*mint, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `FundRewards` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn fund_rewards(
+    program_id: &Pubkey,
+    funder: &Pubkey,
+    funder_token: &Pubkey,
+    vault: &Pubkey,
+    protocol_state: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::FundRewards { amount }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*funder, true),
+        AccountMeta::new(*funder_token, false),
+        AccountMeta::new(*vault, false),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*token_program, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `ClaimRewards` instruction
+pub fn claim_rewards(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    protocol_state: &Pubkey,
+    vault: &Pubkey,
+    user_token: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::ClaimRewards.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*user, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(RewardAccount::find_address(user, program_id).0, false),
+        AccountMeta::new(*vault, false),
+        AccountMeta::new_readonly(ProtocolState::find_rewards_authority(program_id).0, false),
+        AccountMeta::new(*user_token, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*token_program, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetReferralFeeShare` instruction
+pub fn set_referral_fee_share(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    share: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetReferralFeeShare { share }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `ClaimReferralFees` instruction
+pub fn claim_referral_fees(
+    program_id: &Pubkey,
+    referrer: &Pubkey,
+    protocol_state: &Pubkey,
+    treasury_token: &Pubkey,
+    referrer_token: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::ClaimReferralFees.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*referrer, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(ReferralAccount::find_address(referrer, mint, program_id).0, false),
+        AccountMeta::new(*treasury_token, false),
+        AccountMeta::new_readonly(ProtocolState::find_treasury_authority(program_id).0, false),
+        AccountMeta::new(*referrer_token, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(SupportedMint::find_address(mint, program_id).0, false),
+        AccountMeta::new_readonly(*token_program, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `UpdateFeeTiers` instruction
+pub fn update_fee_tiers(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    tiers: [FeeTier; MAX_FEE_TIERS],
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::UpdateFeeTiers { tiers }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `AddFeeExemption` instruction
+pub fn add_fee_exemption(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    payer: &Pubkey,
+    address: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::AddFeeExemption { address: *address }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(FeeExemptions::find_address(program_id).0, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `RemoveFeeExemption` instruction
+pub fn remove_fee_exemption(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    address: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::RemoveFeeExemption { address: *address }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(FeeExemptions::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetDepositCaps` instruction
+pub fn set_deposit_caps(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    max_tvl: u64,
+    max_user_deposit: u64,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetDepositCaps {
+        max_tvl,
+        max_user_deposit,
+    }
+    .try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Accounts settling one queued request in a `ProcessWithdrawalQueue` instruction
+#[derive(Debug, Clone, Copy)]
+pub struct QueuedPayout {
+    pub request: Pubkey,
+    /// The requester's token account for the request's mint
+    pub requester_token: Pubkey,
+    pub treasury_token: Pubkey,
+    pub mint: Pubkey,
+    /// The cranker's token account for the request's mint
+    pub tip_token: Pubkey,
+}
+
+/// Creates a `ProcessWithdrawalQueue` instruction settling `payouts`, the front of the queue
+pub fn process_withdrawal_queue(
+    program_id: &Pubkey,
+    cranker: &Pubkey,
+    protocol_state: &Pubkey,
+    token_program: &Pubkey,
+    payouts: &[QueuedPayout],
+    epoch: u64,
+) -> Result<Instruction, ProgramError> {
+    let count = u8::try_from(payouts.len()).map_err(|_| ProgramError::InvalidArgument)?;
+    let data = FPPInstruction::ProcessWithdrawalQueue { count }.try_to_vec()?;
+    let mut accounts = vec![
+        AccountMeta::new(*cranker, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(WithdrawalQueue::find_address(program_id).0, false),
+        AccountMeta::new_readonly(ProtocolState::find_treasury_authority(program_id).0, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(DenyList::find_address(program_id).0, false),
+        AccountMeta::new_readonly(AssociationSet::find_address(program_id).0, false),
+        AccountMeta::new_readonly(FeeExemptions::find_address(program_id).0, false),
+        AccountMeta::new(EpochStats::find_address(epoch, program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    for payout in payouts {
+        let (supported_mint, _) = SupportedMint::find_address(&payout.mint, program_id);
+        accounts.push(AccountMeta::new(payout.request, false));
+        accounts.push(AccountMeta::new(payout.requester_token, false));
+        accounts.push(AccountMeta::new(payout.treasury_token, false));
+        accounts.push(AccountMeta::new_readonly(payout.mint, false));
+        accounts.push(AccountMeta::new(supported_mint, false));
+        accounts.push(AccountMeta::new(payout.tip_token, false));
+    }
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `UpdateTimelocks` instruction
+pub fn update_timelocks(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    withdrawal_delay: i64,
+    point_lock_duration: i64,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::UpdateTimelocks {
+        withdrawal_delay,
+        point_lock_duration,
+    }
+    .try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetMassDynamics` instruction
+pub fn set_mass_dynamics(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    dynamics: MassDynamics,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetMassDynamics { dynamics }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `UpdateMass` instruction for `points`, each given with its mint
+pub fn update_mass(
+    program_id: &Pubkey,
+    cranker: &Pubkey,
+    protocol_state: &Pubkey,
+    points: &[(Pubkey, Pubkey)],
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::UpdateMass.try_to_vec()?;
+    let mut accounts = Vec::with_capacity(4 + 2 * points.len());
+    accounts.push(AccountMeta::new(*cranker, true));
+    accounts.push(AccountMeta::new(*protocol_state, false));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    for (point, mint) in points {
+        accounts.push(AccountMeta::new(*point, false));
+        accounts.push(AccountMeta::new(SupportedMint::find_address(mint, program_id).0, false));
+    }
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `OpenPool` instruction for the pool at `pool`, created with `circuit_id`
+pub fn open_pool(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    pool: &Pubkey,
+    circuit_id: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::OpenPool.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(*pool, false),
+        AccountMeta::new_readonly(CircuitInfo::find_address(circuit_id, program_id).0, false),
+        AccountMeta::new(PoolState::find_address(pool, program_id).0, false),
+        AccountMeta::new(PoolState::find_tree_address(pool, program_id).0, false),
+        AccountMeta::new(PoolState::find_root_history_address(pool, program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `PoolDeposit` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn pool_deposit(
+    program_id: &Pubkey,
+    depositor: &Pubkey,
+    depositor_token: &Pubkey,
+    treasury_token: &Pubkey,
+    protocol_state: &Pubkey,
+    pool: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    commitment: [u8; 32],
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::PoolDeposit { commitment }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*depositor, true),
+        AccountMeta::new(*depositor_token, false),
+        AccountMeta::new(*treasury_token, false),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(*pool, false),
+        AccountMeta::new(PoolState::find_address(pool, program_id).0, false),
+        AccountMeta::new(PoolState::find_tree_address(pool, program_id).0, false),
+        AccountMeta::new(PoolState::find_root_history_address(pool, program_id).0, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(SupportedMint::find_address(mint, program_id).0, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(DenyList::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `PoolWithdraw` instruction paying `recipient_token`
+/// 
+/// `relayer_token` is only read when `relayer_fee` is nonzero; pass the recipient's account
+/// to withdraw without a relayer.
+#[allow(clippy::too_many_arguments)]
+pub fn pool_withdraw(
+    program_id: &Pubkey,
+    relayer: &Pubkey,
+    recipient_token: &Pubkey,
+    relayer_token: &Pubkey,
+    treasury_token: &Pubkey,
+    protocol_state: &Pubkey,
+    pool: &Pubkey,
+    circuit_id: u16,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    merkle_root: [u8; 32],
+    nullifier: [u8; 32],
+    relayer_fee: u64,
+    proof: Groth16Proof,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::PoolWithdraw {
+        merkle_root,
+        nullifier,
+        recipient: *recipient_token,
+        relayer_fee,
+        proof,
+    }
+    .try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*relayer, true),
+        AccountMeta::new(*recipient_token, false),
+        AccountMeta::new(*relayer_token, false),
+        AccountMeta::new(*treasury_token, false),
+        AccountMeta::new_readonly(ProtocolState::find_treasury_authority(program_id).0, false),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(*pool, false),
+        AccountMeta::new(PoolState::find_address(pool, program_id).0, false),
+        AccountMeta::new_readonly(PoolState::find_root_history_address(pool, program_id).0, false),
+        AccountMeta::new(PoolNullifier::find_address(pool, &nullifier, program_id).0, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(SupportedMint::find_address(mint, program_id).0, false),
+        AccountMeta::new_readonly(*token_program, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(DenyList::find_address(program_id).0, false),
+        AccountMeta::new_readonly(fpp_verifier::id(), false),
+        AccountMeta::new_readonly(CircuitKey::find_address(circuit_id, program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `OpenPoolRoute` instruction from pool `source` to pool `destination`
+pub fn open_pool_route(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    source: &Pubkey,
+    destination: &Pubkey,
+    circuit_id: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::OpenPoolRoute { circuit_id }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(*source, false),
+        AccountMeta::new_readonly(*destination, false),
+        AccountMeta::new_readonly(CircuitInfo::find_address(circuit_id, program_id).0, false),
+        AccountMeta::new(PoolRoute::find_address(source, destination, program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `PoolTransfer` instruction from pool `source` to pool `destination`
+/// 
+/// `circuit_id` is the one the route was opened with.
+#[allow(clippy::too_many_arguments)]
+pub fn pool_transfer(
+    program_id: &Pubkey,
+    submitter: &Pubkey,
+    protocol_state: &Pubkey,
+    source: &Pubkey,
+    destination: &Pubkey,
+    circuit_id: u16,
+    merkle_root: [u8; 32],
+    nullifiers: Vec<[u8; 32]>,
+    commitments: Vec<[u8; 32]>,
+    proof: Groth16Proof,
+) -> Result<Instruction, ProgramError> {
+    let mut accounts = Vec::with_capacity(14 + nullifiers.len());
+    accounts.push(AccountMeta::new(*submitter, true));
+    accounts.push(AccountMeta::new_readonly(*protocol_state, false));
+    accounts.push(AccountMeta::new(
+        PoolRoute::find_address(source, destination, program_id).0,
+        false,
+    ));
+    accounts.push(AccountMeta::new(*source, false));
+    accounts.push(AccountMeta::new(PoolState::find_address(source, program_id).0, false));
+    accounts.push(AccountMeta::new_readonly(
+        PoolState::find_root_history_address(source, program_id).0,
+        false,
+    ));
+    accounts.push(AccountMeta::new(*destination, false));
+    accounts.push(AccountMeta::new(PoolState::find_address(destination, program_id).0, false));
+    accounts.push(AccountMeta::new(
+        PoolState::find_tree_address(destination, program_id).0,
+        false,
+    ));
+    accounts.push(AccountMeta::new(
+        PoolState::find_root_history_address(destination, program_id).0,
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    accounts.push(AccountMeta::new_readonly(fpp_verifier::id(), false));
+    accounts.push(AccountMeta::new_readonly(
+        CircuitKey::find_address(circuit_id, program_id).0,
+        false,
+    ));
+    for nullifier in &nullifiers {
+        let (address, _) = PoolNullifier::find_address(source, nullifier, program_id);
+        accounts.push(AccountMeta::new(address, false));
+    }
+    
+    let data = FPPInstruction::PoolTransfer {
+        merkle_root,
+        nullifiers,
+        commitments,
+        proof,
+    }
+    .try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `UpdateVerifyingKey` instruction
+pub fn update_verifying_key(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    payer: &Pubkey,
+    circuit_id: u16,
+    circuit_version: u16,
+    verifying_key: Groth16VerifyingKey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::UpdateVerifyingKey {
+        circuit_id,
+        circuit_version,
+        verifying_key,
+    }
+    .try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new_readonly(CircuitInfo::find_address(circuit_id, program_id).0, false),
+        AccountMeta::new(CircuitKey::find_address(circuit_id, program_id).0, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `ApplyVerifyingKey` instruction
+pub fn apply_verifying_key(
+    program_id: &Pubkey,
+    circuit_id: u16,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::ApplyVerifyingKey.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new(CircuitInfo::find_address(circuit_id, program_id).0, false),
+        AccountMeta::new(CircuitKey::find_address(circuit_id, program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CreateConcurrentTree` instruction
+/// 
+/// `merkle_tree` must first be created for `ACCOUNT_COMPRESSION_ID` with
+/// `concurrent_tree::account_len` bytes, in the same transaction or before it.
+#[allow(clippy::too_many_arguments)]
+pub fn create_concurrent_tree(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    payer: &Pubkey,
+    merkle_tree: &Pubkey,
+    max_depth: u32,
+    max_buffer_size: u32,
+    canopy_depth: u32,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::CreateConcurrentTree {
+        max_depth,
+        max_buffer_size,
+        canopy_depth,
+    }
+    .try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new(ConcurrentTree::find_address(program_id).0, false),
+        AccountMeta::new(CommitmentTree::find_address(program_id).0, false),
+        AccountMeta::new(*merkle_tree, false),
+        AccountMeta::new_readonly(NOOP_ID, false),
+        AccountMeta::new_readonly(ACCOUNT_COMPRESSION_ID, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Adds the concurrent tree accounts to a `Deposit` or `DelegatedDeposit` instruction
+/// 
+/// Required once the protocol has a concurrent tree; `merkle_tree` is its
+/// `ConcurrentTree::merkle_tree`.
+pub fn with_concurrent_tree(
+    mut instruction: Instruction,
+    program_id: &Pubkey,
+    merkle_tree: &Pubkey,
+) -> Instruction {
+    instruction.accounts.extend([
+        AccountMeta::new(ConcurrentTree::find_address(program_id).0, false),
+        AccountMeta::new(*merkle_tree, false),
+        AccountMeta::new_readonly(NOOP_ID, false),
+        AccountMeta::new_readonly(ACCOUNT_COMPRESSION_ID, false),
+    ]);
+    instruction
+}
+
+/// Creates a `VerifyConcurrentCommitment` instruction
+/// 
+/// `proof` holds only the nodes below the tree's canopy, as many as
+/// `concurrent_tree::proof_len` gives.
+pub fn verify_concurrent_commitment(
+    program_id: &Pubkey,
+    merkle_tree: &Pubkey,
+    root: [u8; 32],
+    commitment: [u8; 32],
+    leaf_index: u32,
+    proof: &[[u8; 32]],
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::VerifyConcurrentCommitment {
+        root,
+        commitment,
+        leaf_index,
+    }
+    .try_to_vec()?;
+    let mut accounts = vec![
+        AccountMeta::new_readonly(ConcurrentTree::find_address(program_id).0, false),
+        AccountMeta::new_readonly(*merkle_tree, false),
+        AccountMeta::new_readonly(ACCOUNT_COMPRESSION_ID, false),
+    ];
+    accounts.extend(
+        proof
+            .iter()
+            .map(|node| AccountMeta::new_readonly(Pubkey::new_from_array(*node), false)),
+    );
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `CreateAuditLog` instruction
+pub fn create_audit_log(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    payer: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::CreateAuditLog.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `ApplyUnpause` instruction
+pub fn apply_unpause(
+    program_id: &Pubkey,
+    protocol_state: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::ApplyUnpause.try_to_vec()?;
+    let accounts = vec![AccountMeta::new(*protocol_state, false)];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `SetCpiGuard` instruction
+pub fn set_cpi_guard(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    protocol_state: &Pubkey,
+    enabled: bool,
+) -> Result<Instruction, ProgramError> {
+    let data = FPPInstruction::SetCpiGuard { enabled }.try_to_vec()?;
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*protocol_state, false),
+        AccountMeta::new(AuditLog::find_address(program_id).0, false),
+    ];
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates a `BatchPrivacyPayment` instruction
+/// 
+/// `recipients` and `rings` hold each payment's recipient and input point accounts
+/// (row-major), in the order of `payments`.
+pub fn batch_privacy_payment(
+    program_id: &Pubkey,
+    sender: &Pubkey,
+    protocol_state: &Pubkey,
+    recipients: &[Pubkey],
+    rings: &[Vec<Pubkey>],
+    payments: Vec<BatchedPayment>,
+    archived_tree: Option<u32>,
+) -> Result<Instruction, ProgramError> {
+    if recipients.len() != payments.len() || rings.len() != payments.len() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let root_source = match archived_tree {
+        Some(generation) => CommitmentTree::find_archive_address(generation, program_id).0,
+        None => RootHistory::find_address(program_id).0,
+    };
+    let (circuit_key, _) = CircuitKey::find_address(CircuitInfo::PAYMENT_CIRCUIT_ID, program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*sender, true),
+        AccountMeta::new_readonly(*protocol_state, false),
+        AccountMeta::new_readonly(fpp_verifier::id(), false),
+        AccountMeta::new_readonly(circuit_key, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(root_source, false),
+        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        AccountMeta::new(NullifierFilter::find_address(program_id).0, false),
+        AccountMeta::new(CommitmentTree::find_address(program_id).0, false),
+        AccountMeta::new(RootHistory::find_address(program_id).0, false),
+    ];
+    for ((recipient, ring), payment) in recipients.iter().zip(rings).zip(&payments) {
+        accounts.push(AccountMeta::new_readonly(*recipient, false));
+        accounts.extend(ring.iter().map(|member| AccountMeta::new(*member, false)));
+        accounts.extend(payment.input_nullifiers.iter().map(|nullifier| {
+            AccountMeta::new(pda::find_nullifier(nullifier, program_id).0, false)
+        }));
+        accounts.extend(payment.output_commitments.iter().map(|commitment| {
+            AccountMeta::new(pda::find_point_address(commitment, program_id).0, false)
+        }));
+        accounts.extend(payment.output_commitments.iter().map(|commitment| {
+            AccountMeta::new(EncryptedNote::find_address(commitment, program_id).0, false)
+        }));
+    }
+    let nullifiers: Vec<[u8; 32]> = payments
+        .iter()
+        .flat_map(|payment| payment.input_nullifiers.iter().copied())
+        .collect();
+    accounts.extend(nullifier_shard_accounts(program_id, &nullifiers));
+    
+    let data = FPPInstruction::BatchPrivacyPayment { payments }.try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
 }
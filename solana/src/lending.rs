@@ -0,0 +1,186 @@
+//! Treasury liquidity lent out through SPL token-lending reserves.
+//!
+//! `DeployTreasury` deposits part of a mint's treasury into a reserve of the
+//! SPL token-lending program, or a fork that keeps its instruction layout
+//! such as Solend, and `RecallTreasury` redeems it. The treasury authority PDA
+//! signs as the user transfer authority and owns the collateral tokens the
+//! reserve mints. Lending programs reject stale reserves, so the transaction
+//! has to call the lending program's own `RefreshReserve` first.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    pubkey,
+    pubkey::Pubkey,
+};
+
+use crate::error::FPPError;
+
+pub const TOKEN_LENDING_ID: Pubkey = pubkey!("LendZqTs7gn5CTSJU1jWKhKuVpjJGom45nnwPb2AMTi");
+pub const SOLEND_ID: Pubkey = pubkey!("So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo");
+
+const DEPOSIT_RESERVE_LIQUIDITY: u8 = 4;
+const REDEEM_RESERVE_COLLATERAL: u8 = 5;
+
+/// Whether treasury funds may be lent through `program_id`
+pub fn is_lending_program(program_id: &Pubkey) -> bool {
+    [TOKEN_LENDING_ID, SOLEND_ID].contains(program_id)
+}
+
+/// Addresses of a lending reserve, fixed when a deployment is configured
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReserveKeys {
+    pub lending_program: Pubkey,
+    pub reserve: Pubkey,
+    pub liquidity_supply: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub lending_market: Pubkey,
+    /// The lending market's derived authority
+    pub market_authority: Pubkey,
+}
+
+impl ReserveKeys {
+    pub const LEN: usize = 32 * 6;
+}
+
+/// Reserve accounts passed to an instruction, in `ReserveKeys` order
+pub struct ReserveAccounts<'a, 'b> {
+    pub lending_program: &'a AccountInfo<'b>,
+    pub reserve: &'a AccountInfo<'b>,
+    pub liquidity_supply: &'a AccountInfo<'b>,
+    pub collateral_mint: &'a AccountInfo<'b>,
+    pub lending_market: &'a AccountInfo<'b>,
+    pub market_authority: &'a AccountInfo<'b>,
+}
+
+impl ReserveAccounts<'_, '_> {
+    /// Require exactly the accounts in `keys`
+    pub fn check(&self, keys: &ReserveKeys) -> ProgramResult {
+        let matches = *self.lending_program.key == keys.lending_program
+            && *self.reserve.key == keys.reserve
+            && *self.liquidity_supply.key == keys.liquidity_supply
+            && *self.collateral_mint.key == keys.collateral_mint
+            && *self.lending_market.key == keys.lending_market
+            && *self.market_authority.key == keys.market_authority;
+        if !matches || !is_lending_program(self.lending_program.key) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Ok(())
+    }
+}
+
+/// Lend `amount` tokens from `source`, receiving the reserve's collateral in `collateral`
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_reserve_liquidity<'a>(
+    reserve: &ReserveAccounts<'_, 'a>,
+    source: &AccountInfo<'a>,
+    collateral: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    clock: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let ix = lending_instruction(
+        reserve.lending_program.key,
+        DEPOSIT_RESERVE_LIQUIDITY,
+        amount,
+        vec![
+            AccountMeta::new(*source.key, false),
+            AccountMeta::new(*collateral.key, false),
+            AccountMeta::new(*reserve.reserve.key, false),
+            AccountMeta::new(*reserve.liquidity_supply.key, false),
+            AccountMeta::new(*reserve.collateral_mint.key, false),
+            AccountMeta::new_readonly(*reserve.lending_market.key, false),
+            AccountMeta::new_readonly(*reserve.market_authority.key, false),
+            AccountMeta::new_readonly(*authority.key, true),
+            AccountMeta::new_readonly(*clock.key, false),
+            AccountMeta::new_readonly(*token_program.key, false),
+        ],
+    );
+    invoke_signed(
+        &ix,
+        &[
+            source.clone(),
+            collateral.clone(),
+            reserve.reserve.clone(),
+            reserve.liquidity_supply.clone(),
+            reserve.collateral_mint.clone(),
+            reserve.lending_market.clone(),
+            reserve.market_authority.clone(),
+            authority.clone(),
+            clock.clone(),
+            token_program.clone(),
+            reserve.lending_program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Redeem `amount` collateral tokens from `collateral` for liquidity sent to `destination`
+#[allow(clippy::too_many_arguments)]
+pub fn redeem_reserve_collateral<'a>(
+    reserve: &ReserveAccounts<'_, 'a>,
+    collateral: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    clock: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let ix = lending_instruction(
+        reserve.lending_program.key,
+        REDEEM_RESERVE_COLLATERAL,
+        amount,
+        vec![
+            AccountMeta::new(*collateral.key, false),
+            AccountMeta::new(*destination.key, false),
+            AccountMeta::new(*reserve.reserve.key, false),
+            AccountMeta::new(*reserve.collateral_mint.key, false),
+            AccountMeta::new(*reserve.liquidity_supply.key, false),
+            AccountMeta::new_readonly(*reserve.lending_market.key, false),
+            AccountMeta::new_readonly(*reserve.market_authority.key, false),
+            AccountMeta::new_readonly(*authority.key, true),
+            AccountMeta::new_readonly(*clock.key, false),
+            AccountMeta::new_readonly(*token_program.key, false),
+        ],
+    );
+    invoke_signed(
+        &ix,
+        &[
+            collateral.clone(),
+            destination.clone(),
+            reserve.reserve.clone(),
+            reserve.collateral_mint.clone(),
+            reserve.liquidity_supply.clone(),
+            reserve.lending_market.clone(),
+            reserve.market_authority.clone(),
+            authority.clone(),
+            clock.clone(),
+            token_program.clone(),
+            reserve.lending_program.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+// Both instructions are a one-byte tag followed by a little-endian amount
+fn lending_instruction(
+    program_id: &Pubkey,
+    tag: u8,
+    amount: u64,
+    accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let mut data = Vec::with_capacity(9);
+    data.push(tag);
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
@@ -5,10 +5,25 @@ use solana_program::{
     pubkey::Pubkey,
 };
 
+pub mod bloom;
+pub mod clock;
+pub mod compression;
+pub mod concurrent_tree;
+pub mod crypto;
+pub mod decimals;
 pub mod error;
+pub mod events;
 pub mod instruction;
+pub mod lending;
+pub mod merkle;
+pub mod migration;
+pub mod nft;
+pub mod pda;
 pub mod processor;
 pub mod state;
+pub mod token;
+pub mod validation;
+pub mod zero_copy;
 
 #[cfg(not(feature = "no-entrypoint"))]
 entrypoint!(process_instruction);
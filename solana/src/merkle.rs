@@ -0,0 +1,25 @@
+//! Hashing for the incremental commitment tree.
+//!
+//! Nodes are Poseidon hashes so membership can be proven cheaply in-circuit;
+//! every leaf must therefore be a BN254 field element.
+
+use crate::{crypto::poseidon, error::FPPError};
+
+/// Tree depth, giving 2^20 (~1M) commitments per tree
+pub const TREE_DEPTH: usize = 20;
+
+/// Value of an empty leaf
+pub const ZERO_LEAF: [u8; 32] = [0u8; 32];
+
+pub fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> Result<[u8; 32], FPPError> {
+    poseidon::hashv(&[left, right])
+}
+
+/// Roots of empty subtrees for every level, `zeros[0]` being the empty leaf
+pub fn zero_hashes() -> Result<[[u8; 32]; TREE_DEPTH + 1], FPPError> {
+    let mut zeros = [ZERO_LEAF; TREE_DEPTH + 1];
+    for level in 1..=TREE_DEPTH {
+        zeros[level] = hash_pair(&zeros[level - 1], &zeros[level - 1])?;
+    }
+    Ok(zeros)
+}
@@ -0,0 +1,117 @@
+//! Account layout versions and in-place upgrades.
+//!
+//! Every state account records the layout it was written with in a `version`
+//! byte. Borsh accounts carry it as their first field; the zero-copy accounts
+//! keep it in what used to be padding, so their size and alignment are
+//! unchanged. Layout 0 is the unversioned one accounts had before the field
+//! existed. `MigrateState` walks an account forward one layout at a time,
+//! growing it when a layout adds fields, so adding a field never strands the
+//! accounts a deployment already has.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::program_error::ProgramError;
+
+use crate::error::FPPError;
+
+pub trait Versioned {
+    /// Layout this build reads and writes
+    const VERSION: u8;
+    
+    /// Leading bytes of the account the layout covers; anything after is left alone
+    const VERSIONED_LEN: usize = usize::MAX;
+    
+    /// Whether `data` was written before accounts had a `version` field
+    fn is_unversioned(data: &[u8]) -> bool;
+    
+    /// Layout `data` was written with
+    fn version_of(data: &[u8]) -> u8 {
+        if Self::is_unversioned(data) {
+            0
+        } else {
+            data.first().copied().unwrap_or_default()
+        }
+    }
+    
+    /// Rewrite `data` from layout `from` to the one after it
+    ///
+    /// Layout 1 puts `version` in front of the unversioned fields. Types override this
+    /// as their layouts change, keeping every earlier step.
+    fn upgrade(from: u8, data: &mut Vec<u8>) -> Result<(), ProgramError> {
+        match from {
+            0 => prefix_version(data),
+            _ => Err(FPPError::UnsupportedVersion.into()),
+        }
+    }
+    
+    /// Upgrade `data` to `VERSION`, returning the layout it started from
+    fn migrate(data: &mut Vec<u8>) -> Result<u8, ProgramError> {
+        let from = Self::version_of(data);
+        if from > Self::VERSION {
+            return Err(FPPError::UnsupportedVersion.into());
+        }
+        for version in from..Self::VERSION {
+            Self::upgrade(version, data)?;
+        }
+        Ok(from)
+    }
+}
+
+/// The step from layout 0, shared by every Borsh account
+pub(crate) fn prefix_version(data: &mut Vec<u8>) -> Result<(), ProgramError> {
+    data.insert(0, 1);
+    Ok(())
+}
+
+/// Whether unversioned `data` matches a layout ending in a `Vec` or `String` whose length
+/// prefix sits at `len_offset`
+///
+/// Accounts like these are allocated to fit their contents exactly, and adding the
+/// `version` byte moves the prefix, so only one of the two layouts adds up.
+pub(crate) fn is_unversioned_with_tail(data: &[u8], len_offset: usize, elem_size: usize) -> bool {
+    data.get(len_offset..len_offset + 4)
+        .map(|len| u32::from_le_bytes([len[0], len[1], len[2], len[3]]) as usize)
+        .is_some_and(|len| data.len() == len_offset + 4 + len * elem_size)
+}
+
+/// Layout of a zero-copy account, whose `version` byte at `offset` used to be padding
+pub(crate) fn version_at(data: &[u8], offset: usize) -> u8 {
+    data.get(offset).copied().unwrap_or_default()
+}
+
+/// Upgrade step for zero-copy accounts; layout 1 only sets the byte that was padding
+pub(crate) fn upgrade_at(from: u8, data: &mut [u8], offset: usize) -> Result<(), ProgramError> {
+    match (from, data.get_mut(offset)) {
+        (0, Some(version)) => {
+            *version = 1;
+            Ok(())
+        }
+        _ => Err(FPPError::UnsupportedVersion.into()),
+    }
+}
+
+/// State account types `MigrateState` can upgrade
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountKind {
+    ProtocolState,
+    CommitmentTree,
+    NullifierFilter,
+    FloatingPoint,
+    PointTree,
+    WithdrawalRequest,
+    WithdrawalBatch,
+    NullifierSet,
+    RootHistory,
+    PoolConfig,
+    CircuitInfo,
+    EncryptedNote,
+    SecurityEvent,
+    ContactBook,
+    AdminMultisig,
+    AdminProposal,
+    UserActivity,
+    DenyList,
+    AssociationSet,
+    RelayerConfig,
+    DepositPermit,
+    SupportedMint,
+}
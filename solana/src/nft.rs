@@ -0,0 +1,61 @@
+//! Metaplex display NFTs for floating points.
+//!
+//! `AttachPointMetadata` mints a one-token SPL mint per point with Token
+//! Metadata naming the point's mass and creation time, so wallets and
+//! explorers list the point as an NFT. The token is frozen in the holder's
+//! associated token account and the mint authority is dropped: it is only a
+//! label. Spending or moving the point is still governed by the point account,
+//! and the NFT stays behind once the point is gone.
+
+use mpl_token_metadata::{accounts::Metadata, types::DataV2};
+use solana_program::pubkey::Pubkey;
+
+use crate::{
+    compression::{POINT_NFT_NAME, POINT_NFT_SYMBOL},
+    state::FloatingPoint,
+};
+
+pub use mpl_token_metadata::ID as TOKEN_METADATA_ID;
+
+/// PDA seed prefix of a point's NFT mint; the full seeds are `[MINT_SEED_PREFIX, point]`
+pub const MINT_SEED_PREFIX: &[u8] = b"point-nft";
+
+/// PDA seed of the freeze and update authority of every point NFT
+pub const AUTHORITY_SEED: &[u8] = b"point-nft-authority";
+
+/// The NFT mint of the point account at `point`
+pub fn find_mint(point: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MINT_SEED_PREFIX, point.as_ref()], program_id)
+}
+
+pub fn find_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AUTHORITY_SEED], program_id)
+}
+
+/// Token Metadata account of `mint`
+pub fn find_metadata(mint: &Pubkey) -> (Pubkey, u8) {
+    Metadata::find_pda(mint)
+}
+
+/// Display metadata for `point`
+/// 
+/// The URI is an inline JSON document, so no metadata server is needed for the mass and
+/// creation time to show up as attributes.
+pub fn point_metadata(point: &FloatingPoint) -> DataV2 {
+    let name = format!("{} x{}", POINT_NFT_NAME, point.mass);
+    let uri = format!(
+        "data:application/json,{{\"name\":\"{}\",\"symbol\":\"{}\",\"attributes\":[\
+         {{\"trait_type\":\"mass\",\"value\":{}}},\
+         {{\"trait_type\":\"created_at\",\"value\":{}}}]}}",
+        name, POINT_NFT_SYMBOL, point.mass, point.created_at
+    );
+    DataV2 {
+        name,
+        symbol: POINT_NFT_SYMBOL.to_string(),
+        uri,
+        seller_fee_basis_points: 0,
+        creators: None,
+        collection: None,
+        uses: None,
+    }
+}
@@ -0,0 +1,35 @@
+//! Address derivation for every program-derived account a client passes in.
+//!
+//! The processor checks each of these accounts against the same functions, so
+//! off-chain code (built with `no-entrypoint`) and the program can't disagree
+//! on an address.
+
+use solana_program::pubkey::Pubkey;
+
+use crate::state::{FloatingPoint, NullifierSet, ProtocolState, WithdrawalRequest};
+
+/// The protocol state, created by `Initialize`
+pub fn find_protocol_state(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ProtocolState::SEED], program_id)
+}
+
+/// The point holding `commitment`
+pub fn find_point_address(commitment: &[u8; 32], program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FloatingPoint::SEED_PREFIX, commitment], program_id)
+}
+
+/// The withdrawal request `user` opened under `nonce`
+///
+/// `FinalizeWithdrawal` uses the batch id as the nonce, so request nonces and
+/// batch ids share one namespace per user.
+pub fn find_withdrawal_request(user: &Pubkey, nonce: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[WithdrawalRequest::SEED_PREFIX, user.as_ref(), &nonce.to_le_bytes()],
+        program_id,
+    )
+}
+
+/// The record marking `nullifier` as spent
+pub fn find_nullifier(nullifier: &[u8; 32], program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[NullifierSet::SEED_PREFIX, nullifier], program_id)
+}
@@ -1,23 +1,56 @@
-use borsh::BorshDeserialize;
+use borsh::{BorshDeserialize, BorshSerialize};
+use mpl_bubblegum::instructions::{BurnCpiBuilder, CreateTreeConfigCpiBuilder, MintV1CpiBuilder};
+use mpl_token_metadata::instructions::CreateMetadataAccountV3CpiBuilder;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    entrypoint::ProgramResult,
+    clock::Clock,
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
+    hash::hash,
     msg,
-    program::invoke_signed,
+    program::{invoke, invoke_signed, set_return_data},
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
     rent::Rent,
-    system_instruction,
+    system_instruction, system_program,
     sysvar::Sysvar,
-    clock::Clock,
 };
-use spl_token::state::Account as TokenAccount;
-
 use crate::{
+    clock::{ClockProvider, SyscallClock},
+    compression::{self, ACCOUNT_COMPRESSION_ID, BUBBLEGUM_ID, NOOP_ID},
+    concurrent_tree,
+    crypto::{
+        ed25519,
+        groth16::{self, Groth16Proof, Groth16VerifyingKey},
+        opening::{self, OpeningProof},
+        poseidon,
+        range_proof,
+        ring::{self, RingSignature},
+    },
+    decimals,
     error::FPPError,
-    instruction::FPPInstruction,
-    state::{FloatingPoint, ProtocolState, WithdrawalRequest},
+    events::FPPEvent,
+    instruction::{self, FPPInstruction},
+    lending::{self, ReserveKeys},
+    migration::{AccountKind, Versioned},
+    nft::{self, TOKEN_METADATA_ID},
+    pda,
+    state::{
+        AdminAction, AdminMultisig, AdminProposal, AssociationSet, AuditAction, AuditEntry,
+        AuditLog, BatchedPayment, CircuitInfo, CircuitKey, CommitmentTree, CompressedPoint,
+        ConcurrentTree, ContactBook, DenyList, DepositPermit, EncryptedNote, EpochStats,
+        FeeExemptions, FeeTier, FloatingPoint, MassDynamics, NullifierFilter, NullifierSet,
+        NullifierShard, PendingConfig, PendingUnpause, PointTree, PoolConfig, PoolNullifier,
+        PoolRoute, PoolState, ProtocolState, RateLimits, ReferralAccount, RelayerConfig,
+        RewardAccount, RewardConfig, RootHistory, SecurityEvent, SecurityEventKind,
+        SecuritySeverity, StagedPayment, StagedProof, SupportedMint, SupportedMintConfig,
+        TreasuryDeployment, UserActivity, WithdrawalBatch, WithdrawalQueue, WithdrawalRequest,
+        CONFIG_TIMELOCK, MAX_ADMIN_SIGNERS, MAX_DENOMINATIONS, MAX_FEE_TIERS, POINT_LOCK_DURATION,
+        POINT_VALUE, UNPAUSE_TIMELOCK, WITHDRAWAL_DELAY,
+    },
+    token,
+    validation,
+    zero_copy::ZeroCopy,
 };
 
 pub struct Processor;
@@ -28,22 +61,88 @@ impl Processor {
         accounts: &[AccountInfo],
         deposit_fee_rate: u16,
         withdrawal_fee_rate: u16,
+        root_history_size: u16,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let authority_info = next_account_info(account_info_iter)?;
         let protocol_state_info = next_account_info(account_info_iter)?;
         let treasury_info = next_account_info(account_info_iter)?;
         let usdt_mint_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let commitment_tree_info = next_account_info(account_info_iter)?;
+        let root_history_info = next_account_info(account_info_iter)?;
         
         if !authority_info.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
         
         // Validate fee rates
-        if deposit_fee_rate > 500 || withdrawal_fee_rate > 500 {
+        if deposit_fee_rate > ProtocolState::MAX_FEE_RATE
+            || withdrawal_fee_rate > ProtocolState::MAX_FEE_RATE
+        {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        if root_history_size == 0 || root_history_size > RootHistory::MAX_SIZE {
             return Err(FPPError::InvalidAmount.into());
         }
         
+        // Payouts are signed by the program, so the treasury must be its PDA
+        if *treasury_info.key != ProtocolState::find_treasury_authority(program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        // The mint's owner decides which token program every transfer goes through
+        token::unpack_mint(usdt_mint_info)?;
+        
+        let rent = Rent::get()?;
+        
+        // Create the commitment tree and root history PDAs
+        let (tree_address, tree_bump) = CommitmentTree::find_address(program_id);
+        if *commitment_tree_info.key != tree_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            authority_info,
+            commitment_tree_info,
+            system_program_info,
+            &rent,
+            CommitmentTree::LEN,
+            &[CommitmentTree::SEED, &[tree_bump]],
+        )?;
+        let commitment_tree = CommitmentTree::new()?;
+        commitment_tree.serialize(&mut &mut commitment_tree_info.data.borrow_mut()[..])?;
+        
+        let (history_address, history_bump) = RootHistory::find_address(program_id);
+        if *root_history_info.key != history_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            authority_info,
+            root_history_info,
+            system_program_info,
+            &rent,
+            RootHistory::space(root_history_size),
+            &[RootHistory::SEED, &[history_bump]],
+        )?;
+        let root_history = RootHistory::new(root_history_size, commitment_tree.current_root());
+        root_history.serialize(&mut &mut root_history_info.data.borrow_mut()[..])?;
+        
+        let (state_address, state_bump) = pda::find_protocol_state(program_id);
+        if *protocol_state_info.key != state_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            authority_info,
+            protocol_state_info,
+            system_program_info,
+            &rent,
+            ProtocolState::LEN,
+            &[ProtocolState::SEED, &[state_bump]],
+        )?;
         let protocol_state = ProtocolState {
             is_initialized: true,
             authority: *authority_info.key,
@@ -55,7 +154,38 @@ impl Processor {
             total_points: 0,
             deposit_fee_rate,
             withdrawal_fee_rate,
-            is_paused: false,
+            paused_flags: 0,
+            min_pool_denomination: ProtocolState::DEFAULT_MIN_POOL_DENOMINATION,
+            max_pool_denomination: ProtocolState::DEFAULT_MAX_POOL_DENOMINATION,
+            pool_creation_bond: ProtocolState::DEFAULT_POOL_CREATION_BOND,
+            require_commitment_openings: false,
+            guardian: *authority_info.key,
+            security_event_count: 0,
+            denominations: ProtocolState::DEFAULT_DENOMINATIONS,
+            emergency_penalty_rate: ProtocolState::DEFAULT_EMERGENCY_PENALTY_RATE,
+            pending_authority: Pubkey::default(),
+            pending_config: PendingConfig::default(),
+            fee_receiver: *authority_info.key,
+            rate_limits: RateLimits {
+                window: RateLimits::DEFAULT_WINDOW,
+                ..RateLimits::default()
+            },
+            min_exit_slots: ProtocolState::DEFAULT_MIN_EXIT_SLOTS,
+            token_program: *usdt_mint_info.owner,
+            version: ProtocolState::VERSION,
+            referral_fee_share: 0,
+            has_audit_log: false,
+            reject_cpi: false,
+            _padding: [0; 2],
+            rewards: RewardConfig::default(),
+            fee_tiers: [FeeTier::default(); MAX_FEE_TIERS],
+            value_locked: 0,
+            max_tvl: 0,
+            max_user_deposit: 0,
+            withdrawal_delay: WITHDRAWAL_DELAY,
+            point_lock_duration: POINT_LOCK_DURATION,
+            mass_dynamics: MassDynamics::default(),
+            pending_unpause: PendingUnpause::default(),
         };
         
         protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
@@ -68,254 +198,9854 @@ impl Processor {
         program_id: &Pubkey,
         accounts: &[AccountInfo],
         amount: u64,
+        denomination: u64,
+        commitments: Vec<[u8; 32]>,
+        opening_proofs: Vec<OpeningProof>,
+        referrer: Option<Pubkey>,
+    ) -> ProgramResult {
+        Self::deposit(
+            program_id,
+            accounts,
+            amount,
+            denomination,
+            commitments,
+            opening_proofs,
+            None,
+            referrer,
+        )
+    }
+    
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_delegated_deposit(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+        denomination: u64,
+        commitments: Vec<[u8; 32]>,
+        opening_proofs: Vec<OpeningProof>,
+        expiry: i64,
+        nonce: u64,
+        signature_offset: i8,
+    ) -> ProgramResult {
+        let permit = Some((expiry, nonce, signature_offset));
+        Self::deposit(
+            program_id,
+            accounts,
+            amount,
+            denomination,
+            commitments,
+            opening_proofs,
+            permit,
+            None,
+        )
+    }
+    
+    /// Body of `Deposit` and `DelegatedDeposit`
+    /// 
+    /// With a `permit` of `(expiry, nonce, signature_offset)` the sponsor and permit accounts
+    /// lead the deposit's own: the sponsor pays for new accounts and tokens move under the
+    /// permit authority the user approved as their delegate, instead of the user signing.
+    /// A `referrer` is owed its share of the fee, and its referral account follows the
+    /// epoch stats account. Once the protocol has a `ConcurrentTree`, its accounts trail
+    /// the point accounts and every commitment is appended to it as well.
+    #[allow(clippy::too_many_arguments)]
+    fn deposit<'a>(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo<'a>],
+        amount: u64,
+        denomination: u64,
         commitments: Vec<[u8; 32]>,
+        opening_proofs: Vec<OpeningProof>,
+        permit: Option<(i64, u64, i8)>,
+        referrer: Option<Pubkey>,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
+        let permit_infos = match permit {
+            Some(_) => Some((
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+                next_account_info(account_info_iter)?,
+            )),
+            None => None,
+        };
         let user_info = next_account_info(account_info_iter)?;
         let user_token_info = next_account_info(account_info_iter)?;
         let treasury_token_info = next_account_info(account_info_iter)?;
         let protocol_state_info = next_account_info(account_info_iter)?;
         let point_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
         let token_program_info = next_account_info(account_info_iter)?;
-        let clock_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let commitment_tree_info = next_account_info(account_info_iter)?;
+        let root_history_info = next_account_info(account_info_iter)?;
+        let user_activity_info = next_account_info(account_info_iter)?;
+        let deny_list_info = next_account_info(account_info_iter)?;
+        let supported_mint_info = next_account_info(account_info_iter)?;
+        let reward_account_info = next_account_info(account_info_iter)?;
+        let fee_exemptions_info = next_account_info(account_info_iter)?;
+        let epoch_stats_info = next_account_info(account_info_iter)?;
+        let cpi_guard_sysvar_info = next_account_info(account_info_iter)?;
+        let referral_info = match referrer {
+            Some(_) => Some(next_account_info(account_info_iter)?),
+            None => None,
+        };
         
-        if !user_info.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+        // The first commitment's point comes before the mint, any others trail the accounts
+        let point_infos = std::iter::once(Ok(point_info))
+            .chain(commitments.iter().skip(1).map(|_| next_account_info(account_info_iter)))
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        let clock = SyscallClock.clock()?;
+        let now = clock.unix_timestamp;
+        
+        // A sponsored deposit stands on the user's signed permit, used at most once
+        let (payer_info, authority_info, authority_bump) = match (permit, permit_infos) {
+            (
+                Some((expiry, nonce, signature_offset)),
+                Some((sponsor_info, permit_info, permit_authority_info, instructions_sysvar_info)),
+            ) => {
+                if !sponsor_info.is_signer {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                if now > expiry {
+                    return Err(FPPError::InvalidPermit.into());
+                }
+                let (authority, authority_bump) = DepositPermit::find_authority(program_id);
+                if *permit_authority_info.key != authority {
+                    return Err(FPPError::InvalidAccount.into());
+                }
+                let message = DepositPermit::message(
+                    program_id,
+                    mint_info.key,
+                    amount,
+                    denomination,
+                    &commitments,
+                    expiry,
+                    nonce,
+                );
+                ed25519::verify_signature_instruction(
+                    instructions_sysvar_info,
+                    signature_offset,
+                    user_info.key,
+                    &message,
+                )?;
+                
+                let (permit_address, permit_bump) =
+                    DepositPermit::find_address(user_info.key, nonce, program_id);
+                if *permit_info.key != permit_address {
+                    return Err(FPPError::InvalidAccount.into());
+                }
+                Self::create_pda_account(
+                    program_id,
+                    sponsor_info,
+                    permit_info,
+                    system_program_info,
+                    &Rent::get()?,
+                    DepositPermit::LEN,
+                    &[
+                        DepositPermit::SEED_PREFIX,
+                        user_info.key.as_ref(),
+                        &nonce.to_le_bytes(),
+                        &[permit_bump],
+                    ],
+                )?;
+                let record = DepositPermit {
+                    version: DepositPermit::VERSION,
+                    is_initialized: true,
+                    user: *user_info.key,
+                    nonce,
+                    sponsor: *sponsor_info.key,
+                    used_at: now,
+                };
+                record.serialize(&mut &mut permit_info.data.borrow_mut()[..])?;
+                
+                (sponsor_info, permit_authority_info, Some(authority_bump))
+            }
+            _ => {
+                if !user_info.is_signer {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                (user_info, user_info, None)
+            }
+        };
+        let authority_bump_seed = [authority_bump.unwrap_or_default()];
+        let permit_signer: [&[&[u8]]; 1] = [&[DepositPermit::AUTHORITY_SEED, &authority_bump_seed]];
+        let transfer_seeds: &[&[&[u8]]] = match authority_bump {
+            Some(_) => &permit_signer,
+            None => &[],
+        };
+        
+        if *commitment_tree_info.key != CommitmentTree::find_address(program_id).0
+            || commitment_tree_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if *root_history_info.key != RootHistory::find_address(program_id).0
+            || root_history_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
         }
         
         // Load protocol state
-        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
         
-        if protocol_state.is_paused {
+        if protocol_state.is_paused(ProtocolState::PAUSE_DEPOSITS) {
             return Err(FPPError::Unauthorized.into());
         }
+        if protocol_state.reject_cpi {
+            validation::check_top_level(program_id, cpi_guard_sysvar_info)?;
+        }
+        
+        let mut entry =
+            Self::load_mint_entry(program_id, protocol_state, mint_info, supported_mint_info)?;
+        validation::check_token_program(token_program_info, &entry.token_program)?;
+        let mint = token::unpack_mint(mint_info)?;
+        validation::check_treasury_token_account(protocol_state, &entry, treasury_token_info)?;
+        
+        let deny_list = Self::load_deny_list(program_id, deny_list_info)?;
+        if deny_list.contains_key(user_info.key)
+            || deny_list.contains_key(payer_info.key)
+            || deny_list.contains_key(user_token_info.key)
+            || commitments.iter().any(|commitment| deny_list.contains(commitment))
+        {
+            return Err(FPPError::DeniedAddress.into());
+        }
         
-        // Validate amount
-        if amount < 10_000_000 || amount > 100_000_000_000 {
+        // Validate amount against the chosen denomination; every point gets its own commitment
+        if !entry.config.is_denomination(denomination)
+            || amount < denomination
+            || amount % denomination != 0
+            || commitments.len() as u64 != amount / denomination
+        {
             return Err(FPPError::InvalidAmount.into());
         }
+        if !entry.accepts_deposit(amount) || !protocol_state.accepts_value(amount) {
+            return Err(FPPError::DepositCapExceeded.into());
+        }
+        let mass = denomination / POINT_VALUE;
+        
+        // Supplied openings are always checked; enforcement makes them mandatory
+        if protocol_state.require_commitment_openings && opening_proofs.len() != commitments.len() {
+            return Err(FPPError::InvalidCommitment.into());
+        }
+        if !opening_proofs.is_empty() {
+            if opening_proofs.len() != commitments.len() {
+                return Err(FPPError::InvalidCommitment.into());
+            }
+            for (commitment, proof) in commitments.iter().zip(opening_proofs.iter()) {
+                if !opening::verify_opening(commitment, denomination, user_info.key, proof) {
+                    return Err(FPPError::InvalidCommitment.into());
+                }
+            }
+        }
+        
+        // Calculate fees; the fee is paid on top so the points stay fully backed
+        let fee_exemptions = Self::load_fee_exemptions(program_id, fee_exemptions_info)?;
+        let fee_rate = if fee_exemptions.is_exempt(user_info.key) {
+            0
+        } else {
+            protocol_state.deposit_fee_rate_for(amount, entry.config.deposit_fee_rate)
+        };
+        let fee = (amount as u128 * fee_rate as u128 / 10000) as u64;
+        let gross_amount = amount.checked_add(fee).ok_or(FPPError::InvalidAmount)?;
         
-        // Calculate fees
-        let fee = (amount as u128 * protocol_state.deposit_fee_rate as u128 / 10000) as u64;
-        let net_amount = amount.checked_sub(fee).ok_or(FPPError::InvalidAmount)?;
+        // Transfer tokens to treasury in the mint's decimals, covering any Token-2022
+        // transfer fee on top
+        let token_amount = decimals::to_token_amount_ceil(gross_amount, mint.decimals)
+            .ok_or(FPPError::InvalidAmount)?;
+        let sent_amount = token::gross_up(mint_info, clock.epoch, token_amount)?;
         
-        // Transfer tokens to treasury
-        let transfer_ix = spl_token::instruction::transfer(
-            token_program_info.key,
-            user_token_info.key,
-            treasury_token_info.key,
+        // SOL deposits wrap whatever the user's wSOL account is short of from their lamports;
+        // a sponsored user has to hold the wSOL already
+        if token::is_native_mint(mint_info.key) && authority_bump.is_none() {
+            let held = token::unpack_account(user_token_info)?.amount;
+            if held < sent_amount {
+                token::wrap_sol(
+                    token_program_info,
+                    system_program_info,
+                    user_info,
+                    user_token_info,
+                    sent_amount - held,
+                )?;
+            }
+        }
+        token::transfer(
+            token_program_info,
+            user_token_info,
+            mint_info,
+            treasury_token_info,
+            authority_info,
+            sent_amount,
+            mint.decimals,
+            transfer_seeds,
+        )?;
+        let mut activity = Self::load_user_activity(
+            program_id,
             user_info.key,
-            &[],
-            net_amount,
+            payer_info,
+            user_activity_info,
+            system_program_info,
+            now,
         )?;
-        
-        invoke_signed(
-            &transfer_ix,
-            &[
-                user_token_info.clone(),
-                treasury_token_info.clone(),
-                user_info.clone(),
-                token_program_info.clone(),
-            ],
-            &[],
+        activity.record_deposit(&protocol_state.rate_limits, now, amount)?;
+        activity.record_total_deposit(protocol_state.max_user_deposit, amount)?;
+        let new_depositor = activity.record_deposit_epoch(clock.epoch);
+        activity.serialize(&mut &mut user_activity_info.data.borrow_mut()[..])?;
+        let mut rewards = Self::load_reward_account(
+            program_id,
+            user_info.key,
+            payer_info,
+            reward_account_info,
+            system_program_info,
+            now,
         )?;
+        rewards.record_deposit(&protocol_state.rewards, now, amount);
+        rewards.serialize(&mut &mut reward_account_info.data.borrow_mut()[..])?;
+        
+        // The referrer's share comes out of the fee, never out of the points' backing
+        let referral_fee = match (referrer, referral_info) {
+            (Some(referrer), Some(referral_info)) => {
+                if referrer == *user_info.key {
+                    return Err(FPPError::InvalidAccount.into());
+                }
+                let mut referral = Self::load_referral_account(
+                    program_id,
+                    &referrer,
+                    &entry.mint,
+                    payer_info,
+                    referral_info,
+                    system_program_info,
+                )?;
+                let share = ReferralAccount::fee_share(fee, protocol_state.referral_fee_share);
+                referral.deposit_count = referral.deposit_count.saturating_add(1);
+                referral.volume = referral.volume.saturating_add(amount);
+                referral.accrued_fees =
+                    referral.accrued_fees.checked_add(share).ok_or(FPPError::InvalidAmount)?;
+                referral.serialize(&mut &mut referral_info.data.borrow_mut()[..])?;
+                share
+            }
+            _ => 0,
+        };
         
-        // Create floating point
-        let clock = Clock::from_account_info(clock_info)?;
-        let num_points = amount / 10_000_000; // 10 USDT per point
+        // Create one floating point PDA per commitment
+        let num_points = amount / denomination;
         
-        for commitment in commitments.iter() {
+        let rent = Rent::get()?;
+        for (point_info, commitment) in point_infos.iter().zip(commitments.iter()) {
+            let (point_address, point_bump) = pda::find_point_address(commitment, program_id);
+            if *point_info.key != point_address {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            Self::create_pda_account(
+                program_id,
+                payer_info,
+                point_info,
+                system_program_info,
+                &rent,
+                FloatingPoint::LEN,
+                &[FloatingPoint::SEED_PREFIX, commitment, &[point_bump]],
+            )?;
             let floating_point = FloatingPoint {
+                version: FloatingPoint::VERSION,
                 is_initialized: true,
                 commitment: *commitment,
-                created_at: clock.unix_timestamp,
-                mass: 1,
+                created_at: now,
+                mass,
                 is_active: true,
                 creator: *user_info.key,
-                locked_until: clock.unix_timestamp + 12, // 12 second lock
+                locked_until: now + protocol_state.point_lock_duration,
+                created_slot: clock.slot,
+                mint: entry.mint,
+                rent_payer: *payer_info.key,
+                accrued: 0,
+                mass_updated_at: 0,
             };
             
             floating_point.serialize(&mut &mut point_info.data.borrow_mut()[..])?;
         }
         
-        // Update protocol state
-        protocol_state.total_deposited = protocol_state
+        // Append commitments to the global tree
+        let mut commitment_tree_data = commitment_tree_info.try_borrow_mut_data()?;
+        let commitment_tree = CommitmentTree::load_mut(&mut commitment_tree_data)?;
+        let first_leaf_index = commitment_tree.next_index;
+        for commitment in commitments.iter() {
+            commitment_tree.insert(*commitment)?;
+        }
+        let merkle_root = commitment_tree.current_root();
+        
+        let mut root_history = RootHistory::try_from_slice(&root_history_info.data.borrow())?;
+        root_history.push(merkle_root);
+        root_history.serialize(&mut &mut root_history_info.data.borrow_mut()[..])?;
+        
+        if commitment_tree.has_concurrent_tree {
+            let concurrent_infos = account_info_iter.as_slice();
+            Self::append_to_concurrent_tree(program_id, concurrent_infos, &commitments)?;
+        }
+        
+        // Update the mint's totals and the protocol state
+        entry.total_deposited = entry
             .total_deposited
             .checked_add(amount)
             .ok_or(FPPError::InvalidAmount)?;
+        entry.total_fees = entry
+            .total_fees
+            .checked_add(fee - referral_fee)
+            .ok_or(FPPError::InvalidAmount)?;
+        Self::store_mint_entry(protocol_state, &entry, supported_mint_info)?;
         protocol_state.total_points = protocol_state
             .total_points
             .checked_add(num_points)
             .ok_or(FPPError::InvalidAmount)?;
-        protocol_state.total_fees = protocol_state
-            .total_fees
-            .checked_add(fee)
+        protocol_state.value_locked = protocol_state
+            .value_locked
+            .checked_add(amount)
             .ok_or(FPPError::InvalidAmount)?;
         
-        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        let mut stats = Self::load_epoch_stats(
+            program_id,
+            clock.epoch,
+            payer_info,
+            epoch_stats_info,
+            system_program_info,
+        )?;
+        let points = protocol_state.total_points;
+        stats.record_deposit(amount, fee - referral_fee, new_depositor, points);
+        stats.serialize(&mut &mut epoch_stats_info.data.borrow_mut()[..])?;
         
-        msg!("Deposited {} USDT, created {} points", amount, num_points);
+        FPPEvent::Deposit {
+            depositor: *user_info.key,
+            mint: entry.mint,
+            amount,
+            fee,
+            denomination,
+            commitments,
+            first_leaf_index,
+            tree_generation: commitment_tree.generation,
+            merkle_root,
+        }
+        .emit();
         Ok(())
     }
     
+    #[allow(clippy::too_many_arguments)]
     pub fn process_privacy_payment(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
+        merkle_root: [u8; 32],
         input_nullifiers: Vec<[u8; 32]>,
         output_commitments: Vec<[u8; 32]>,
         proof: Vec<u8>,
         ring_signature: Vec<u8>,
+        range_proof_offset: i8,
+        encrypted_notes: Vec<Vec<u8>>,
     ) -> ProgramResult {
-        // Note: This is a simplified implementation
-        // In production, you would need to:
-        // 1. Verify ZK proof using a verifier program
-        // 2. Verify ring signature
-        // 3. Check nullifiers haven't been used
-        // 4. Validate input/output balance
-        // 5. Create output points
+        let account_info_iter = &mut accounts.iter();
+        let sender_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let recipient_info = next_account_info(account_info_iter)?;
         
-        msg!("Privacy payment processed (simplified)");
-        msg!("Inputs: {}, Outputs: {}", input_nullifiers.len(), output_commitments.len());
+        if !sender_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
         
-        // TODO: Implement full privacy payment logic
-        Ok(())
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state_data = protocol_state_info.try_borrow_data()?;
+        let protocol_state = ProtocolState::load(&protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_PRIVACY_PAYMENTS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        Self::check_encrypted_notes(&encrypted_notes, &output_commitments)?;
+        let signature = Self::parse_ring_signature(&ring_signature, &input_nullifiers)?;
+        
+        let ring_len = signature.ring_size() * input_nullifiers.len();
+        let (ring_members, ring_points) = Self::load_ring(program_id, account_info_iter, ring_len)?;
+        let input = Self::ring_input(&ring_points, input_nullifiers.len())?;
+        
+        // The key images are the nullifiers any other spend of the same points would publish
+        // (`ring::verify_spend`), so a paid-out point can't be withdrawn later. Which member
+        // was spent stays hidden, which is why no point account is deactivated here.
+        let message = ring::signing_message(&input_nullifiers, &output_commitments, &proof);
+        if !ring::verify(&message, &ring_members, &signature) {
+            return Err(FPPError::InvalidRingSignature.into());
+        }
+        
+        let nullifier_infos = input_nullifiers
+            .iter()
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        let output_infos = output_commitments
+            .iter()
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        let note_infos = output_commitments
+            .iter()
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        let verifier_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let root_source_info = next_account_info(account_info_iter)?;
+        let instructions_sysvar_info = next_account_info(account_info_iter)?;
+        let nullifier_filter_info = next_account_info(account_info_iter)?;
+        let commitment_tree_info = next_account_info(account_info_iter)?;
+        let root_history_info = next_account_info(account_info_iter)?;
+        if !proof.is_empty() {
+            let circuit_key_info = next_account_info(account_info_iter)?;
+            Self::verify_payment_proof(
+                program_id,
+                verifier_program_info,
+                circuit_key_info,
+                &proof,
+                &merkle_root,
+                &input_nullifiers,
+                &output_commitments,
+            )?;
+        }
+        let clock = SyscallClock.clock()?;
+        let now = clock.unix_timestamp;
+        
+        // Any ring member could be the real input, so none may be freshly deposited
+        if ring_points
+            .iter()
+            .any(|point| !point.can_exit_at(clock.slot, protocol_state.min_exit_slots))
+        {
+            return Err(FPPError::FlashLoanDetected.into());
+        }
+        
+        Self::settle_privacy_payment(
+            program_id,
+            sender_info,
+            &nullifier_infos,
+            &note_infos,
+            system_program_info,
+            root_source_info,
+            instructions_sysvar_info,
+            nullifier_filter_info,
+            account_info_iter.as_slice(),
+            range_proof_offset,
+            merkle_root,
+            input_nullifiers,
+            output_commitments.clone(),
+            encrypted_notes,
+            now,
+        )?;
+        // Only once the root is checked, since the output's root may push it out of history
+        Self::create_payment_output(
+            program_id,
+            sender_info,
+            recipient_info.key,
+            &output_infos,
+            commitment_tree_info,
+            root_history_info,
+            system_program_info,
+            &output_commitments,
+            input,
+            protocol_state.point_lock_duration,
+            &clock,
+        )
     }
     
-    pub fn process_request_withdrawal(
+    /// Apply several privacy payments, checking all their proofs with one batched pairing
+    /// 
+    /// Every ring signature is checked before the batch goes to the verifier program, and
+    /// nothing is spent until it has passed. One bad proof fails the whole batch.
+    pub fn process_batch_privacy_payment(
         program_id: &Pubkey,
         accounts: &[AccountInfo],
-        point_ids: Vec<Pubkey>,
-        nullifiers: Vec<[u8; 32]>,
+        payments: Vec<BatchedPayment>,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
-        let user_info = next_account_info(account_info_iter)?;
+        let sender_info = next_account_info(account_info_iter)?;
         let protocol_state_info = next_account_info(account_info_iter)?;
-        let withdrawal_request_info = next_account_info(account_info_iter)?;
-        let clock_info = next_account_info(account_info_iter)?;
+        let verifier_program_info = next_account_info(account_info_iter)?;
+        let circuit_key_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let root_source_info = next_account_info(account_info_iter)?;
+        let instructions_sysvar_info = next_account_info(account_info_iter)?;
+        let nullifier_filter_info = next_account_info(account_info_iter)?;
+        let commitment_tree_info = next_account_info(account_info_iter)?;
+        let root_history_info = next_account_info(account_info_iter)?;
         
-        if !user_info.is_signer {
+        if !sender_info.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
+        if payments.is_empty() || payments.len() > groth16::MAX_BATCH_PROOFS {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state_data = protocol_state_info.try_borrow_data()?;
+        let protocol_state = ProtocolState::load(&protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_PRIVACY_PAYMENTS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        let clock = SyscallClock.clock()?;
         
-        let clock = Clock::from_account_info(clock_info)?;
-        let amount = point_ids.len() as u64 * 10_000_000; // 10 USDT per point
+        let mut payment_infos = Vec::with_capacity(payments.len());
+        for payment in &payments {
+            Self::check_encrypted_notes(&payment.encrypted_notes, &payment.output_commitments)?;
+            let signature =
+                Self::parse_ring_signature(&payment.ring_signature, &payment.input_nullifiers)?;
+            let recipient_info = next_account_info(account_info_iter)?;
+            let ring_len = signature.ring_size() * payment.input_nullifiers.len();
+            let (ring_members, ring_points) =
+                Self::load_ring(program_id, account_info_iter, ring_len)?;
+            let input = Self::ring_input(&ring_points, payment.input_nullifiers.len())?;
+            let message = ring::signing_message(
+                &payment.input_nullifiers,
+                &payment.output_commitments,
+                &payment.proof.try_to_vec()?,
+            );
+            if !ring::verify(&message, &ring_members, &signature) {
+                return Err(FPPError::InvalidRingSignature.into());
+            }
+            if ring_points
+                .iter()
+                .any(|point| !point.can_exit_at(clock.slot, protocol_state.min_exit_slots))
+            {
+                return Err(FPPError::FlashLoanDetected.into());
+            }
+            
+            let nullifier_infos = payment
+                .input_nullifiers
+                .iter()
+                .map(|_| next_account_info(account_info_iter))
+                .collect::<Result<Vec<_>, _>>()?;
+            let output_infos = payment
+                .output_commitments
+                .iter()
+                .map(|_| next_account_info(account_info_iter))
+                .collect::<Result<Vec<_>, _>>()?;
+            let note_infos = payment
+                .output_commitments
+                .iter()
+                .map(|_| next_account_info(account_info_iter))
+                .collect::<Result<Vec<_>, _>>()?;
+            payment_infos.push((recipient_info, input, nullifier_infos, output_infos, note_infos));
+        }
         
-        let withdrawal_request = WithdrawalRequest {
+        let circuit_key =
+            Self::load_circuit_key(program_id, CircuitInfo::PAYMENT_CIRCUIT_ID, circuit_key_info)?;
+        let proofs = payments
+            .iter()
+            .map(|payment| payment.proof.clone())
+            .collect::<Vec<_>>();
+        let public_inputs = payments
+            .iter()
+            .map(|payment| {
+                [
+                    payment.merkle_root,
+                    circuit_key.payment_binding(
+                        &payment.input_nullifiers,
+                        &payment.output_commitments,
+                    ),
+                ]
+            })
+            .collect::<Vec<_>>();
+        validation::check_verifier_program(verifier_program_info)?;
+        let valid = fpp_verifier::cpi::verify_batch(
+            verifier_program_info,
+            &circuit_key.verifying_key,
+            &proofs,
+            &public_inputs,
+        )?;
+        if !valid {
+            return Err(FPPError::InvalidProof.into());
+        }
+        
+        let nullifier_shard_infos = account_info_iter.as_slice();
+        let mut outputs = Vec::with_capacity(payments.len());
+        for (payment, (recipient_info, input, nullifier_infos, output_infos, note_infos)) in
+            payments.into_iter().zip(payment_infos)
+        {
+            Self::settle_privacy_payment(
+                program_id,
+                sender_info,
+                &nullifier_infos,
+                &note_infos,
+                system_program_info,
+                root_source_info,
+                instructions_sysvar_info,
+                nullifier_filter_info,
+                nullifier_shard_infos,
+                payment.range_proof_offset,
+                payment.merkle_root,
+                payment.input_nullifiers,
+                payment.output_commitments.clone(),
+                payment.encrypted_notes,
+                clock.unix_timestamp,
+            )?;
+            outputs.push((recipient_info, input, output_infos, payment.output_commitments));
+        }
+        // Every root is checked before any output's root can push one out of history
+        for (recipient_info, input, output_infos, output_commitments) in outputs {
+            Self::create_payment_output(
+                program_id,
+                sender_info,
+                recipient_info.key,
+                &output_infos,
+                commitment_tree_info,
+                root_history_info,
+                system_program_info,
+                &output_commitments,
+                input,
+                protocol_state.point_lock_duration,
+                &clock,
+            )?;
+        }
+        Ok(())
+    }
+    
+    /// Load the next `ring_len` point accounts of a payment's ring
+    /// 
+    /// Ring members are the commitments of the input point accounts (row-major).
+    fn load_ring<'a, 'b>(
+        program_id: &Pubkey,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        ring_len: usize,
+    ) -> Result<(Vec<[u8; 32]>, Vec<FloatingPoint>), ProgramError> {
+        let mut ring_members = Vec::with_capacity(ring_len);
+        let mut ring_points = Vec::with_capacity(ring_len);
+        for _ in 0..ring_len {
+            let point_info = next_account_info(account_info_iter)?;
+            let point = Self::load_point(program_id, point_info)?;
+            if !point.is_initialized {
+                return Err(FPPError::AccountNotInitialized.into());
+            }
+            ring_members.push(point.commitment);
+            ring_points.push(point);
+        }
+        Ok((ring_members, ring_points))
+    }
+    
+    /// Mint and combined mass a payment spends from its ring
+    ///
+    /// Which member of each column is spent stays hidden, so every member of a column must
+    /// hold the same mass, and every column the same mint.
+    fn ring_input(
+        ring_points: &[FloatingPoint],
+        columns: usize,
+    ) -> Result<(Pubkey, u64), ProgramError> {
+        let first_row = ring_points.get(..columns).ok_or(FPPError::InvalidRingSignature)?;
+        let mint = first_row.first().ok_or(FPPError::InvalidRingSignature)?.mint;
+        for (index, point) in ring_points.iter().enumerate() {
+            let column = &first_row[index % columns];
+            if point.mint != mint || point.mass == 0 || point.mass != column.mass {
+                return Err(FPPError::MassNotConserved.into());
+            }
+        }
+        let mass = first_row
+            .iter()
+            .try_fold(0u64, |total, point| total.checked_add(point.mass))
+            .ok_or(FPPError::InvalidAmount)?;
+        Ok((mint, mass))
+    }
+    
+    /// Create a payment's output point for `recipient` and add its commitment to the tree
+    ///
+    /// A payment has one output, holding the whole `input` mass of its mint, so the amount
+    /// its commitment hides can only be what the ring spent. The output starts its own lock
+    /// and exit delay; what the spent points accrued isn't carried over.
+    #[allow(clippy::too_many_arguments)]
+    fn create_payment_output<'a>(
+        program_id: &Pubkey,
+        payer_info: &AccountInfo<'a>,
+        recipient: &Pubkey,
+        output_infos: &[&AccountInfo<'a>],
+        commitment_tree_info: &AccountInfo<'a>,
+        root_history_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        output_commitments: &[[u8; 32]],
+        (mint, mass): (Pubkey, u64),
+        point_lock_duration: i64,
+        clock: &Clock,
+    ) -> ProgramResult {
+        let ([output_info], [commitment]) = (output_infos, output_commitments) else {
+            return Err(FPPError::InvalidInstruction.into());
+        };
+        if *commitment_tree_info.key != CommitmentTree::find_address(program_id).0
+            || commitment_tree_info.owner != program_id
+            || *root_history_info.key != RootHistory::find_address(program_id).0
+            || root_history_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let (output_address, output_bump) = pda::find_point_address(commitment, program_id);
+        if *output_info.key != output_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let now = clock.unix_timestamp;
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            output_info,
+            system_program_info,
+            &Rent::get()?,
+            FloatingPoint::LEN,
+            &[FloatingPoint::SEED_PREFIX, commitment, &[output_bump]],
+        )?;
+        let output = FloatingPoint {
+            version: FloatingPoint::VERSION,
             is_initialized: true,
-            requester: *user_info.key,
-            amount,
-            request_time: clock.unix_timestamp,
-            unlock_time: clock.unix_timestamp + 86400, // 24 hours
-            completed: false,
-            cancelled: false,
+            commitment: *commitment,
+            created_at: now,
+            mass,
+            is_active: true,
+            creator: *recipient,
+            locked_until: now + point_lock_duration,
+            created_slot: clock.slot,
+            mint,
+            rent_payer: *payer_info.key,
+            accrued: 0,
+            mass_updated_at: 0,
         };
+        output.serialize(&mut &mut output_info.data.borrow_mut()[..])?;
         
-        withdrawal_request.serialize(&mut &mut withdrawal_request_info.data.borrow_mut()[..])?;
+        let mut commitment_tree_data = commitment_tree_info.try_borrow_mut_data()?;
+        let commitment_tree = CommitmentTree::load_mut(&mut commitment_tree_data)?;
+        let first_leaf_index = commitment_tree.insert(*commitment)?;
+        let merkle_root = commitment_tree.current_root();
         
-        msg!("Withdrawal requested: {} USDT", amount);
+        let mut root_history = RootHistory::try_from_slice(&root_history_info.data.borrow())?;
+        root_history.push(merkle_root);
+        root_history.serialize(&mut &mut root_history_info.data.borrow_mut()[..])?;
+        
+        FPPEvent::CommitmentsInserted {
+            commitments: vec![*commitment],
+            first_leaf_index,
+            tree_generation: commitment_tree.generation,
+            merkle_root,
+        }
+        .emit();
         Ok(())
     }
     
-    pub fn process_complete_withdrawal(
+    /// Check a payment's Groth16 proof against the key of `CircuitInfo::PAYMENT_CIRCUIT_ID`
+    ///
+    /// The ring signature covers the proof bytes, so a proof can't be swapped for another.
+    fn verify_payment_proof(
         program_id: &Pubkey,
-        accounts: &[AccountInfo],
+        verifier_program_info: &AccountInfo,
+        circuit_key_info: &AccountInfo,
+        proof: &[u8],
+        merkle_root: &[u8; 32],
+        input_nullifiers: &[[u8; 32]],
+        output_commitments: &[[u8; 32]],
     ) -> ProgramResult {
-        let account_info_iter = &mut accounts.iter();
-        let user_info = next_account_info(account_info_iter)?;
-        let user_token_info = next_account_info(account_info_iter)?;
-        let treasury_token_info = next_account_info(account_info_iter)?;
-        let protocol_state_info = next_account_info(account_info_iter)?;
-        let withdrawal_request_info = next_account_info(account_info_iter)?;
-        let clock_info = next_account_info(account_info_iter)?;
+        let proof = Groth16Proof::try_from_slice(proof).map_err(|_| FPPError::InvalidProof)?;
+        let circuit_key =
+            Self::load_circuit_key(program_id, CircuitInfo::PAYMENT_CIRCUIT_ID, circuit_key_info)?;
+        let public_inputs = [
+            *merkle_root,
+            circuit_key.payment_binding(input_nullifiers, output_commitments),
+        ];
+        if !Self::verify_groth16(
+            verifier_program_info,
+            &circuit_key.verifying_key,
+            &proof,
+            &public_inputs,
+        )? {
+            return Err(FPPError::InvalidProof.into());
+        }
+        Ok(())
+    }
+    
+    /// Every output carries a memo so recipients can find it with their viewing key
+    fn check_encrypted_notes(
+        encrypted_notes: &[Vec<u8>],
+        output_commitments: &[[u8; 32]],
+    ) -> ProgramResult {
+        if encrypted_notes.len() != output_commitments.len()
+            || encrypted_notes
+                .iter()
+                .any(|note| note.is_empty() || note.len() > EncryptedNote::MAX_CIPHERTEXT_LEN)
+        {
+            return Err(FPPError::InvalidEncryptedNote.into());
+        }
+        Ok(())
+    }
+    
+    /// Decode a payment's ring signature and check its shape against the nullifiers
+    fn parse_ring_signature(
+        ring_signature: &[u8],
+        input_nullifiers: &[[u8; 32]],
+    ) -> Result<RingSignature, ProgramError> {
+        // Key images double as nullifiers, so they must match one-to-one
+        let signature = RingSignature::try_from_slice(ring_signature)
+            .map_err(|_| FPPError::InvalidRingSignature)?;
+        if input_nullifiers.is_empty() || signature.key_images != input_nullifiers {
+            return Err(FPPError::InvalidRingSignature.into());
+        }
+        let ring_len = signature.ring_size() * input_nullifiers.len();
+        if ring_len == 0 || ring_len > ring::MAX_RING_SIZE * input_nullifiers.len() {
+            return Err(FPPError::InvalidRingSignature.into());
+        }
+        Ok(signature)
+    }
+    
+    /// Apply a privacy payment whose ring signature has been verified
+    ///
+    /// Checks the range proof and the root, spends the nullifiers and writes the notes.
+    #[allow(clippy::too_many_arguments)]
+    fn settle_privacy_payment<'a>(
+        program_id: &Pubkey,
+        sender_info: &AccountInfo<'a>,
+        nullifier_infos: &[&AccountInfo<'a>],
+        note_infos: &[&AccountInfo<'a>],
+        system_program_info: &AccountInfo<'a>,
+        root_history_info: &AccountInfo<'a>,
+        instructions_sysvar_info: &AccountInfo<'a>,
+        nullifier_filter_info: &AccountInfo<'a>,
+        nullifier_shard_infos: &[AccountInfo<'a>],
+        range_proof_offset: i8,
+        merkle_root: [u8; 32],
+        input_nullifiers: Vec<[u8; 32]>,
+        output_commitments: Vec<[u8; 32]>,
+        encrypted_notes: Vec<Vec<u8>>,
+        now: i64,
+    ) -> ProgramResult {
+        // Hidden output amounts must be range-proven by a sibling instruction
+        range_proof::verify_range_proof_instruction(
+            instructions_sysvar_info,
+            range_proof_offset,
+            &output_commitments,
+        )?;
         
-        if !user_info.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+        // Proofs may be built against any recent root, or the final root of an archived tree
+        validation::check_program_owned(program_id, root_history_info)?;
+        if *root_history_info.key == RootHistory::find_address(program_id).0 {
+            let root_history = RootHistory::try_from_slice(&root_history_info.data.borrow())?;
+            if !root_history.contains(&merkle_root) {
+                return Err(FPPError::UnknownMerkleRoot.into());
+            }
+        } else {
+            let archived = CommitmentTree::try_from_slice(&root_history_info.data.borrow())?;
+            if *root_history_info.key
+                != CommitmentTree::find_archive_address(archived.generation, program_id).0
+            {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            if !archived.is_frozen || archived.current_root() != merkle_root {
+                return Err(FPPError::UnknownMerkleRoot.into());
+            }
         }
         
-        let mut withdrawal_request = WithdrawalRequest::try_from_slice(
-            &withdrawal_request_info.data.borrow()
+        Self::consume_nullifiers(
+            program_id,
+            sender_info,
+            nullifier_infos,
+            nullifier_filter_info,
+            nullifier_shard_infos,
+            system_program_info,
+            &input_nullifiers,
+            &Pubkey::default(),
+            now,
         )?;
         
-        let clock = Clock::from_account_info(clock_info)?;
+        let rent = Rent::get()?;
+        for ((note_info, commitment), ciphertext) in note_infos
+            .iter()
+            .zip(output_commitments.iter())
+            .zip(encrypted_notes.into_iter())
+        {
+            let (note_address, note_bump) = EncryptedNote::find_address(commitment, program_id);
+            if *note_info.key != note_address {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            Self::create_pda_account(
+                program_id,
+                sender_info,
+                note_info,
+                system_program_info,
+                &rent,
+                EncryptedNote::space(ciphertext.len()),
+                &[EncryptedNote::SEED_PREFIX, commitment, &[note_bump]],
+            )?;
+            let note = EncryptedNote {
+                version: EncryptedNote::VERSION,
+                is_initialized: true,
+                commitment: *commitment,
+                created_at: now,
+                ciphertext,
+            };
+            note.serialize(&mut &mut note_info.data.borrow_mut()[..])?;
+        }
         
-        // Validate withdrawal is unlocked
-        if clock.unix_timestamp < withdrawal_request.unlock_time {
-            return Err(FPPError::WithdrawalNotReady.into());
+        FPPEvent::PrivacyPayment {
+            merkle_root,
+            input_nullifiers,
+            output_commitments,
         }
+        .emit();
+        Ok(())
+    }
+    
+    /// Open a staged payment's scratch account, or append the next chunk of its payload
+    pub fn process_stage_proof(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        nonce: u64,
+        payload_len: u32,
+        chunk: Vec<u8>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_info = next_account_info(account_info_iter)?;
+        let staged_proof_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
         
-        if withdrawal_request.completed || withdrawal_request.cancelled {
-            return Err(FPPError::Unauthorized.into());
+        if !owner_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
         }
+        let (staged_address, staged_bump) =
+            StagedProof::find_address(owner_info.key, nonce, program_id);
+        if *staged_proof_info.key != staged_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let now = SyscallClock.unix_timestamp()?;
         
-        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        let mut staged = if staged_proof_info.data_is_empty() {
+            if payload_len == 0 || payload_len > StagedProof::MAX_PAYLOAD_LEN {
+                return Err(FPPError::InvalidInstruction.into());
+            }
+            Self::create_pda_account(
+                program_id,
+                owner_info,
+                staged_proof_info,
+                system_program_info,
+                &Rent::get()?,
+                StagedProof::space(payload_len),
+                &[
+                    StagedProof::SEED_PREFIX,
+                    owner_info.key.as_ref(),
+                    &nonce.to_le_bytes(),
+                    &[staged_bump],
+                ],
+            )?;
+            StagedProof {
+                version: StagedProof::VERSION,
+                is_initialized: true,
+                owner: *owner_info.key,
+                nonce,
+                expires_at: now + StagedProof::TTL,
+                payload_len,
+                written: 0,
+                verified_rows: 0,
+                challenge: [0u8; 32],
+                verified: false,
+            }
+        } else {
+            validation::check_program_owned(program_id, staged_proof_info)?;
+            let staged = StagedProof::load(&staged_proof_info.data.borrow())?;
+            if !staged.is_initialized {
+                return Err(FPPError::AccountNotInitialized.into());
+            }
+            staged
+        };
+        if staged.is_expired(now) {
+            return Err(FPPError::StagedProofExpired.into());
+        }
         
-        // Calculate fee
-        let fee = (withdrawal_request.amount as u128 * protocol_state.withdrawal_fee_rate as u128 / 10000) as u64;
-        let net_amount = withdrawal_request.amount.checked_sub(fee).ok_or(FPPError::InvalidAmount)?;
+        // Chunks go in order, and the payload is fixed once verification has started
+        let start = staged.written as usize;
+        let end = start + chunk.len();
+        if payload_len != staged.payload_len
+            || staged.verified_rows != 0
+            || end > staged.payload_len as usize
+        {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        let mut data = staged_proof_info.try_borrow_mut_data()?;
+        data[StagedProof::LEN + start..StagedProof::LEN + end].copy_from_slice(&chunk);
+        staged.written = end as u32;
+        staged.serialize(&mut &mut data[..])?;
+        Ok(())
+    }
+    
+    /// Check the next `rows` rows of a fully staged payment's ring signature
+    ///
+    /// The challenge leaving the last row is kept in the account for the next call; the
+    /// payment is verified once the final row brings it back to `c_0`.
+    pub fn process_verify_staged_proof(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        rows: u8,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let staged_proof_info = next_account_info(account_info_iter)?;
         
-        // Transfer from treasury to user
-        // Note: In production, this would use treasury PDA authority
-        msg!("Withdrawal completed: {} USDT (fee: {})", net_amount, fee);
+        if !owner_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state_data = protocol_state_info.try_borrow_data()?;
+        let protocol_state = ProtocolState::load(&protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_PRIVACY_PAYMENTS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        let clock = SyscallClock.clock()?;
         
-        withdrawal_request.completed = true;
-        withdrawal_request.serialize(&mut &mut withdrawal_request_info.data.borrow_mut()[..])?;
+        let (mut staged, payment) = Self::load_staged_payment(
+            program_id,
+            owner_info,
+            staged_proof_info,
+            clock.unix_timestamp,
+        )?;
+        if staged.verified {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        Self::check_encrypted_notes(&payment.encrypted_notes, &payment.output_commitments)?;
+        let signature =
+            Self::parse_ring_signature(&payment.ring_signature, &payment.input_nullifiers)?;
+        let columns = payment.input_nullifiers.len();
+        let ring_size = signature.ring_size();
+        if payment.ring.len() != ring_size * columns {
+            return Err(FPPError::InvalidRingSignature.into());
+        }
         
-        protocol_state.total_withdrawn = protocol_state
-            .total_withdrawn
-            .checked_add(withdrawal_request.amount)
-            .ok_or(FPPError::InvalidAmount)?;
-        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        let first_row = staged.verified_rows as usize;
+        let rows = rows as usize;
+        if rows == 0 || first_row + rows > ring_size {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        // Ring members come from the point accounts the payload names, in its order
+        let first_row_points =
+            Self::load_staged_ring(program_id, account_info_iter, &payment.ring[..columns])?;
+        let row_points = Self::load_staged_ring(
+            program_id,
+            account_info_iter,
+            &payment.ring[first_row * columns..(first_row + rows) * columns],
+        )?;
+        // Any ring member could be the real input, so none may be freshly deposited
+        if row_points
+            .iter()
+            .any(|point| !point.can_exit_at(clock.slot, protocol_state.min_exit_slots))
+        {
+            return Err(FPPError::FlashLoanDetected.into());
+        }
+        // Execution pays out what the first row holds, so every row must hold the same
+        let checked: Vec<FloatingPoint> =
+            first_row_points.iter().chain(&row_points).cloned().collect();
+        Self::ring_input(&checked, columns)?;
+        let ring_members: Vec<[u8; 32]> =
+            row_points.iter().map(|point| point.commitment).collect();
         
+        if first_row == 0 {
+            staged.challenge = signature.challenge;
+        }
+        let message = ring::signing_message(
+            &payment.input_nullifiers,
+            &payment.output_commitments,
+            &payment.proof,
+        );
+        staged.challenge =
+            ring::verify_rows(&message, &ring_members, &signature, first_row, &staged.challenge)
+                .ok_or(FPPError::InvalidRingSignature)?;
+        staged.verified_rows += rows as u16;
+        if first_row + rows == ring_size {
+            if staged.challenge != signature.challenge {
+                return Err(FPPError::InvalidRingSignature.into());
+            }
+            staged.verified = true;
+        }
+        staged.serialize(&mut &mut staged_proof_info.data.borrow_mut()[..])?;
         Ok(())
     }
-}
-
-pub fn process_instruction(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    instruction_data: &[u8],
-) -> ProgramResult {
-    let instruction = FPPInstruction::try_from_slice(instruction_data)?;
     
-    match instruction {
-        FPPInstruction::Initialize {
-            deposit_fee_rate,
-            withdrawal_fee_rate,
-        } => {
-            msg!("Instruction: Initialize");
-            Processor::process_initialize(program_id, accounts, deposit_fee_rate, withdrawal_fee_rate)
-        }
-        FPPInstruction::Deposit { amount, commitments } => {
-            msg!("Instruction: Deposit");
-            Processor::process_deposit(program_id, accounts, amount, commitments)
-        }
-        FPPInstruction::PrivacyPayment {
+    /// Apply a verified staged payment and close its scratch account
+    pub fn process_execute_staged_payment(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        range_proof_offset: i8,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let staged_proof_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let root_source_info = next_account_info(account_info_iter)?;
+        let instructions_sysvar_info = next_account_info(account_info_iter)?;
+        let nullifier_filter_info = next_account_info(account_info_iter)?;
+        let recipient_info = next_account_info(account_info_iter)?;
+        let commitment_tree_info = next_account_info(account_info_iter)?;
+        let root_history_info = next_account_info(account_info_iter)?;
+        
+        if !owner_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state_data = protocol_state_info.try_borrow_data()?;
+        let protocol_state = ProtocolState::load(&protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_PRIVACY_PAYMENTS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        let clock = SyscallClock.clock()?;
+        let now = clock.unix_timestamp;
+        
+        let (staged, payment) =
+            Self::load_staged_payment(program_id, owner_info, staged_proof_info, now)?;
+        if !staged.verified {
+            return Err(FPPError::StagedProofNotReady.into());
+        }
+        // Verification held every row to the first, so the first row says what is spent
+        let columns = payment.input_nullifiers.len();
+        let first_row_points =
+            Self::load_staged_ring(program_id, account_info_iter, &payment.ring[..columns])?;
+        let input = Self::ring_input(&first_row_points, columns)?;
+        let nullifier_infos = payment
+            .input_nullifiers
+            .iter()
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        let output_infos = payment
+            .output_commitments
+            .iter()
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        let note_infos = payment
+            .output_commitments
+            .iter()
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !payment.proof.is_empty() {
+            let verifier_program_info = next_account_info(account_info_iter)?;
+            let circuit_key_info = next_account_info(account_info_iter)?;
+            Self::verify_payment_proof(
+                program_id,
+                verifier_program_info,
+                circuit_key_info,
+                &payment.proof,
+                &payment.merkle_root,
+                &payment.input_nullifiers,
+                &payment.output_commitments,
+            )?;
+        }
+        
+        Self::settle_privacy_payment(
+            program_id,
+            owner_info,
+            &nullifier_infos,
+            &note_infos,
+            system_program_info,
+            root_source_info,
+            instructions_sysvar_info,
+            nullifier_filter_info,
+            account_info_iter.as_slice(),
+            range_proof_offset,
+            payment.merkle_root,
+            payment.input_nullifiers,
+            payment.output_commitments.clone(),
+            payment.encrypted_notes,
+            now,
+        )?;
+        Self::create_payment_output(
+            program_id,
+            owner_info,
+            recipient_info.key,
+            &output_infos,
+            commitment_tree_info,
+            root_history_info,
+            system_program_info,
+            &payment.output_commitments,
+            input,
+            protocol_state.point_lock_duration,
+            &clock,
+        )?;
+        Self::close_account(staged_proof_info, owner_info)
+    }
+    
+    /// Load the staged payment's ring points `ring` from the next accounts, in order
+    fn load_staged_ring<'a, 'b>(
+        program_id: &Pubkey,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        ring: &[Pubkey],
+    ) -> Result<Vec<FloatingPoint>, ProgramError> {
+        ring.iter()
+            .map(|point_id| {
+                let point_info = next_account_info(account_info_iter)?;
+                if point_info.key != point_id {
+                    return Err(FPPError::InvalidAccount.into());
+                }
+                let point = Self::load_point(program_id, point_info)?;
+                if !point.is_initialized {
+                    return Err(FPPError::AccountNotInitialized.into());
+                }
+                Ok(point)
+            })
+            .collect()
+    }
+    
+    /// Close a staged payment's scratch account, returning its rent to the owner
+    ///
+    /// The owner can abandon a payment at any time; once it has expired anyone can clean
+    /// it up.
+    pub fn process_close_staged_proof(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let caller_info = next_account_info(account_info_iter)?;
+        let staged_proof_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        
+        if !caller_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        validation::check_program_owned(program_id, staged_proof_info)?;
+        let staged = StagedProof::load(&staged_proof_info.data.borrow())?;
+        if !staged.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        if *owner_info.key != staged.owner {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let now = SyscallClock.unix_timestamp()?;
+        if *caller_info.key != staged.owner && !staged.is_expired(now) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        Self::close_account(staged_proof_info, owner_info)
+    }
+    
+    /// Load an unexpired, fully written staged proof of `owner_info` and decode its payload
+    fn load_staged_payment(
+        program_id: &Pubkey,
+        owner_info: &AccountInfo,
+        staged_proof_info: &AccountInfo,
+        now: i64,
+    ) -> Result<(StagedProof, StagedPayment), ProgramError> {
+        validation::check_program_owned(program_id, staged_proof_info)?;
+        let data = staged_proof_info.try_borrow_data()?;
+        let staged = StagedProof::load(&data)?;
+        if !staged.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        if staged.owner != *owner_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        if staged.is_expired(now) {
+            return Err(FPPError::StagedProofExpired.into());
+        }
+        if !staged.is_written() {
+            return Err(FPPError::StagedProofNotReady.into());
+        }
+        let payment = StagedPayment::try_from_slice(staged.payload(&data)?)
+            .map_err(|_| FPPError::InvalidInstruction)?;
+        Ok((staged, payment))
+    }
+    
+    pub fn process_request_withdrawal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        point_ids: Vec<Pubkey>,
+        nullifiers: Vec<[u8; 32]>,
+        nonce: u64,
+        spend_signature: Vec<u8>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let withdrawal_request_info = next_account_info(account_info_iter)?;
+        
+        if !user_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if point_ids.is_empty() || nullifiers.len() != point_ids.len() {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        let point_infos = Self::next_point_accounts(account_info_iter, &point_ids)?;
+        let nullifier_infos = nullifiers
+            .iter()
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let user_activity_info = next_account_info(account_info_iter)?;
+        let nullifier_filter_info = next_account_info(account_info_iter)?;
+        let reward_account_info = next_account_info(account_info_iter)?;
+        let withdrawal_queue_info = next_account_info(account_info_iter)?;
+        let instructions_sysvar_info = next_account_info(account_info_iter)?;
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_WITHDRAWALS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        if protocol_state.reject_cpi {
+            validation::check_top_level(program_id, instructions_sysvar_info)?;
+        }
+        
+        let clock = SyscallClock.clock()?;
+        let now = clock.unix_timestamp;
+        
+        Self::check_spend_signature(
+            program_id,
+            &point_infos,
+            &nullifiers,
+            &spend_signature,
+            &WithdrawalRequest::spend_message(withdrawal_request_info.key),
+        )?;
+        let (amount, mint) = Self::retire_points(
+            program_id,
+            &point_infos,
+            Some(user_info.key),
+            &clock,
+            protocol_state.min_exit_slots,
+        )?;
+        Self::consume_nullifiers(
+            program_id,
+            user_info,
+            &nullifier_infos,
+            nullifier_filter_info,
+            account_info_iter.as_slice(),
+            system_program_info,
+            &nullifiers,
+            withdrawal_request_info.key,
+            now,
+        )?;
+        
+        protocol_state.total_points = protocol_state
+            .total_points
+            .checked_sub(point_ids.len() as u64)
+            .ok_or(FPPError::InvalidAmount)?;
+        
+        let mut activity = Self::load_user_activity(
+            program_id,
+            user_info.key,
+            user_info,
+            user_activity_info,
+            system_program_info,
+            now,
+        )?;
+        activity.record_withdrawal(&protocol_state.rate_limits, now, amount)?;
+        activity.serialize(&mut &mut user_activity_info.data.borrow_mut()[..])?;
+        Self::record_reward_withdrawal(
+            program_id,
+            user_info.key,
+            reward_account_info,
+            &protocol_state.rewards,
+            now,
+            amount,
+        )?;
+        
+        let points_hash = point_ids
+            .iter()
+            .zip(nullifiers.iter())
+            .fold([0u8; 32], |hash, (point, nullifier)| {
+                WithdrawalRequest::chain_points_hash(&hash, point, nullifier)
+            });
+        
+        let withdrawal_request = WithdrawalRequest {
+            version: WithdrawalRequest::VERSION,
+            is_initialized: true,
+            requester: *user_info.key,
+            amount,
+            request_time: now,
+            unlock_time: now + protocol_state.withdrawal_delay,
+            completed: false,
+            cancelled: false,
+            point_count: point_ids.len() as u32,
+            points_hash,
+            compliance_root: [0u8; 32],
+            mint,
+            nonce,
+            relayer: Pubkey::default(),
+            relayer_fee: 0,
+        };
+        
+        Self::create_withdrawal_request(
+            program_id,
+            user_info.key,
+            user_info,
+            withdrawal_request_info,
+            system_program_info,
+            nonce,
+        )?;
+        withdrawal_request.serialize(&mut &mut withdrawal_request_info.data.borrow_mut()[..])?;
+        Self::enqueue_withdrawal(
+            program_id,
+            user_info,
+            withdrawal_queue_info,
+            system_program_info,
+            withdrawal_request_info.key,
+        )?;
+        
+        FPPEvent::WithdrawalRequested {
+            requester: *user_info.key,
+            request: *withdrawal_request_info.key,
+            nonce,
+            mint,
+            amount,
+            point_count: withdrawal_request.point_count,
+            unlock_time: withdrawal_request.unlock_time,
+        }
+        .emit();
+        Ok(())
+    }
+    
+    pub fn process_complete_withdrawal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        unwrap_sol: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_info = next_account_info(account_info_iter)?;
+        let user_token_info = next_account_info(account_info_iter)?;
+        let treasury_token_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let withdrawal_request_info = next_account_info(account_info_iter)?;
+        let treasury_authority_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let deny_list_info = next_account_info(account_info_iter)?;
+        let association_set_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let supported_mint_info = next_account_info(account_info_iter)?;
+        let fee_exemptions_info = next_account_info(account_info_iter)?;
+        let epoch_stats_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let withdrawal_queue_info = next_account_info(account_info_iter)?;
+        
+        if !user_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info)
+            || withdrawal_request_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut withdrawal_request = WithdrawalRequest::try_from_slice(
+            &withdrawal_request_info.data.borrow()
+        )?;
+        if !withdrawal_request.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        if *withdrawal_request_info.key != withdrawal_request.address(program_id) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if withdrawal_request.requester != *user_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        // Relayed requests pay the relayer too, so only `CompleteRelayedWithdrawal` settles them
+        if withdrawal_request.is_relayed() {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let clock = SyscallClock.clock()?;
+        let now = clock.unix_timestamp;
+        
+        // Validate withdrawal is unlocked
+        if now < withdrawal_request.unlock_time {
+            return Err(FPPError::WithdrawalNotReady.into());
+        }
+        
+        if withdrawal_request.completed || withdrawal_request.cancelled {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_WITHDRAWALS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        Self::check_exit_destination(program_id, deny_list_info, user_info, user_token_info)?;
+        Self::check_withdrawal_compliance(program_id, association_set_info, &withdrawal_request)?;
+        
+        // Points pay out in the mint they were deposited in
+        if *mint_info.key != withdrawal_request.mint {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut entry =
+            Self::load_mint_entry(program_id, protocol_state, mint_info, supported_mint_info)?;
+        
+        // Calculate fee
+        let fee_rate = Self::withdrawal_fee_rate(
+            program_id,
+            fee_exemptions_info,
+            protocol_state,
+            &entry,
+            &withdrawal_request,
+        )?;
+        let fee = (withdrawal_request.amount as u128 * fee_rate as u128 / 10000) as u64;
+        let net_amount = withdrawal_request.amount.checked_sub(fee).ok_or(FPPError::InvalidAmount)?;
+        
+        // Transfer from treasury to user; the fee stays in the treasury
+        let sent_amount = Self::transfer_from_treasury(
+            program_id,
+            protocol_state,
+            &entry,
+            treasury_token_info,
+            user_token_info,
+            treasury_authority_info,
+            token_program_info,
+            mint_info,
+            net_amount,
+        )?;
+        let token_fee = token::transfer_fee(mint_info, clock.epoch, sent_amount)?;
+        FPPEvent::WithdrawalCompleted {
+            requester: withdrawal_request.requester,
+            request: *withdrawal_request_info.key,
+            mint: entry.mint,
+            amount: withdrawal_request.amount,
+            fee,
+            penalty: 0,
+            token_fee,
+            emergency: false,
+        }
+        .emit();
+        if unwrap_sol {
+            Self::unwrap_payout(token_program_info, mint_info, user_info, user_token_info)?;
+        }
+        
+        withdrawal_request.completed = true;
+        withdrawal_request.serialize(&mut &mut withdrawal_request_info.data.borrow_mut()[..])?;
+        Self::dequeue_withdrawal(program_id, withdrawal_queue_info, withdrawal_request_info.key)?;
+        
+        entry.total_withdrawn = entry
+            .total_withdrawn
+            .checked_add(withdrawal_request.amount)
+            .ok_or(FPPError::InvalidAmount)?;
+        protocol_state.release_value(withdrawal_request.amount);
+        entry.total_fees = entry.total_fees.checked_add(fee).ok_or(FPPError::InvalidAmount)?;
+        Self::store_mint_entry(protocol_state, &entry, supported_mint_info)?;
+        Self::record_epoch_withdrawal(
+            program_id,
+            clock.epoch,
+            user_info,
+            epoch_stats_info,
+            system_program_info,
+            protocol_state,
+            withdrawal_request.amount,
+            fee,
+        )?;
+        
+        Ok(())
+    }
+    
+    pub fn process_emergency_withdraw(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        unwrap_sol: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_info = next_account_info(account_info_iter)?;
+        let user_token_info = next_account_info(account_info_iter)?;
+        let treasury_token_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let withdrawal_request_info = next_account_info(account_info_iter)?;
+        let treasury_authority_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let deny_list_info = next_account_info(account_info_iter)?;
+        let association_set_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let supported_mint_info = next_account_info(account_info_iter)?;
+        let fee_exemptions_info = next_account_info(account_info_iter)?;
+        let epoch_stats_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let withdrawal_queue_info = next_account_info(account_info_iter)?;
+        
+        if !user_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info)
+            || withdrawal_request_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut withdrawal_request = WithdrawalRequest::try_from_slice(
+            &withdrawal_request_info.data.borrow()
+        )?;
+        if !withdrawal_request.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        if *withdrawal_request_info.key != withdrawal_request.address(program_id) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if withdrawal_request.requester != *user_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        // An emergency exit would leave the relayer unpaid
+        if withdrawal_request.is_relayed() {
+            return Err(FPPError::Unauthorized.into());
+        }
+        if withdrawal_request.completed || withdrawal_request.cancelled {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        // Past the delay the regular path is free of penalty
+        let clock = SyscallClock.clock()?;
+        let now = clock.unix_timestamp;
+        if now >= withdrawal_request.unlock_time {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_WITHDRAWALS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        Self::check_exit_destination(program_id, deny_list_info, user_info, user_token_info)?;
+        Self::check_withdrawal_compliance(program_id, association_set_info, &withdrawal_request)?;
+        
+        if *mint_info.key != withdrawal_request.mint {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut entry =
+            Self::load_mint_entry(program_id, protocol_state, mint_info, supported_mint_info)?;
+        
+        // An exemption waives the fee but not the penalty for skipping the delay
+        let fee_rate = Self::withdrawal_fee_rate(
+            program_id,
+            fee_exemptions_info,
+            protocol_state,
+            &entry,
+            &withdrawal_request,
+        )?;
+        let amount = withdrawal_request.amount as u128;
+        let fee = (amount * fee_rate as u128 / 10000) as u64;
+        let penalty = (amount * protocol_state.emergency_penalty_rate as u128 / 10000) as u64;
+        let net_amount = withdrawal_request
+            .amount
+            .checked_sub(fee)
+            .and_then(|amount| amount.checked_sub(penalty))
+            .ok_or(FPPError::InvalidAmount)?;
+        
+        // Transfer from treasury to user; the fee and penalty stay in the treasury
+        let sent_amount = Self::transfer_from_treasury(
+            program_id,
+            protocol_state,
+            &entry,
+            treasury_token_info,
+            user_token_info,
+            treasury_authority_info,
+            token_program_info,
+            mint_info,
+            net_amount,
+        )?;
+        let token_fee = token::transfer_fee(mint_info, clock.epoch, sent_amount)?;
+        FPPEvent::WithdrawalCompleted {
+            requester: withdrawal_request.requester,
+            request: *withdrawal_request_info.key,
+            mint: entry.mint,
+            amount: withdrawal_request.amount,
+            fee,
+            penalty,
+            token_fee,
+            emergency: true,
+        }
+        .emit();
+        if unwrap_sol {
+            Self::unwrap_payout(token_program_info, mint_info, user_info, user_token_info)?;
+        }
+        
+        withdrawal_request.completed = true;
+        withdrawal_request.serialize(&mut &mut withdrawal_request_info.data.borrow_mut()[..])?;
+        Self::dequeue_withdrawal(program_id, withdrawal_queue_info, withdrawal_request_info.key)?;
+        
+        entry.total_withdrawn = entry
+            .total_withdrawn
+            .checked_add(withdrawal_request.amount)
+            .ok_or(FPPError::InvalidAmount)?;
+        protocol_state.release_value(withdrawal_request.amount);
+        entry.total_fees = entry
+            .total_fees
+            .checked_add(fee)
+            .and_then(|total| total.checked_add(penalty))
+            .ok_or(FPPError::InvalidAmount)?;
+        Self::store_mint_entry(protocol_state, &entry, supported_mint_info)?;
+        Self::record_epoch_withdrawal(
+            program_id,
+            clock.epoch,
+            user_info,
+            epoch_stats_info,
+            system_program_info,
+            protocol_state,
+            withdrawal_request.amount,
+            fee + penalty,
+        )?;
+        
+        Ok(())
+    }
+    
+    pub fn process_set_emergency_penalty(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        penalty_rate: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if penalty_rate > ProtocolState::MAX_EMERGENCY_PENALTY_RATE {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        let previous = protocol_state.emergency_penalty_rate;
+        protocol_state.emergency_penalty_rate = penalty_rate;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        Self::log_admin_action(
+            program_id,
+            &protocol_state,
+            account_info_iter.as_slice(),
+            authority_info.key,
+            AuditAction::SetEmergencyPenalty,
+            AuditEntry::value(&previous.to_le_bytes()),
+            AuditEntry::value(&penalty_rate.to_le_bytes()),
+        )?;
+        
+        msg!("Emergency penalty set to {} bps", penalty_rate);
+        Ok(())
+    }
+    
+    pub fn process_cancel_withdrawal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        permanent: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let withdrawal_request_info = next_account_info(account_info_iter)?;
+        
+        if !user_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if withdrawal_request_info.owner != program_id
+            || !validation::is_protocol_state(program_id, protocol_state_info)
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut withdrawal_request = WithdrawalRequest::try_from_slice(
+            &withdrawal_request_info.data.borrow()
+        )?;
+        if !withdrawal_request.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        if *withdrawal_request_info.key != withdrawal_request.address(program_id) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        // A relayed request's rent was paid by the relayer, who is owed its fee on completion
+        if withdrawal_request.requester != *user_info.key
+            || withdrawal_request.is_relayed()
+            || withdrawal_request.completed
+            || withdrawal_request.cancelled
+        {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let point_count = withdrawal_request.point_count as usize;
+        let point_infos = (0..point_count)
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        let nullifier_infos = (0..point_count)
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        let user_activity_info = next_account_info(account_info_iter)?;
+        let reward_account_info = next_account_info(account_info_iter)?;
+        let withdrawal_queue_info = next_account_info(account_info_iter)?;
+        
+        // The supplied points and nullifiers must be exactly the ones withdrawn
+        let mut points_hash = [0u8; 32];
+        for (point_info, nullifier_info) in point_infos.iter().zip(nullifier_infos.iter()) {
+            validation::check_program_owned(program_id, nullifier_info)?;
+            let record = NullifierSet::try_from_slice(&nullifier_info.data.borrow())?;
+            if *nullifier_info.key != pda::find_nullifier(&record.nullifier, program_id).0 {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            points_hash = WithdrawalRequest::chain_points_hash(
+                &points_hash,
+                point_info.key,
+                &record.nullifier,
+            );
+        }
+        if points_hash != withdrawal_request.points_hash {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        for point_info in point_infos.iter() {
+            let mut point = Self::load_point(program_id, point_info)?;
+            if permanent {
+                point.mass = 0;
+            } else {
+                point.is_active = true;
+            }
+            point.serialize(&mut &mut point_info.data.borrow_mut()[..])?;
+        }
+        
+        // Reactivated points must be spendable again, so release their nullifiers
+        if !permanent {
+            for nullifier_info in nullifier_infos.iter() {
+                let lamports = nullifier_info.lamports();
+                **nullifier_info.try_borrow_mut_lamports()? = 0;
+                **user_info.try_borrow_mut_lamports()? = user_info
+                    .lamports()
+                    .checked_add(lamports)
+                    .ok_or(FPPError::InvalidAmount)?;
+                nullifier_info.data.borrow_mut().fill(0);
+            }
+        
+        }
+        
+        // Undo what the request counted; burned points take their value out of the pool
+        let now = SyscallClock.unix_timestamp()?;
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if permanent {
+            protocol_state.release_value(withdrawal_request.amount);
+        } else {
+            protocol_state.total_points = protocol_state
+                .total_points
+                .checked_add(point_count as u64)
+                .ok_or(FPPError::InvalidAmount)?;
+            Self::restore_reward_balance(
+                program_id,
+                user_info.key,
+                reward_account_info,
+                &protocol_state.rewards,
+                now,
+                withdrawal_request.amount,
+            )?;
+        }
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        
+        if *user_activity_info.key != UserActivity::find_address(user_info.key, program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        validation::check_program_owned(program_id, user_activity_info)?;
+        let mut activity = UserActivity::try_from_slice(&user_activity_info.data.borrow())?;
+        activity.undo_withdrawal(withdrawal_request.request_time, withdrawal_request.amount);
+        activity.serialize(&mut &mut user_activity_info.data.borrow_mut()[..])?;
+        
+        withdrawal_request.cancelled = true;
+        withdrawal_request.serialize(&mut &mut withdrawal_request_info.data.borrow_mut()[..])?;
+        Self::dequeue_withdrawal(program_id, withdrawal_queue_info, withdrawal_request_info.key)?;
+        
+        if permanent {
+            msg!("Withdrawal cancelled, {} points burned", point_count);
+        } else {
+            msg!("Withdrawal cancelled, {} points reactivated", point_count);
+        }
+        Ok(())
+    }
+    
+    pub fn process_close_spent_point(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let point_info = next_account_info(account_info_iter)?;
+        let rent_payer_info = next_account_info(account_info_iter)?;
+        
+        let point = Self::load_point(program_id, point_info)?;
+        if !point.is_spent() {
+            return Err(FPPError::AccountInUse.into());
+        }
+        if point.rent_payer != *rent_payer_info.key {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        Self::close_account(point_info, rent_payer_info)?;
+        
+        msg!("Spent point {} closed", point_info.key);
+        Ok(())
+    }
+    
+    pub fn process_close_withdrawal_request(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let withdrawal_request_info = next_account_info(account_info_iter)?;
+        let rent_payer_info = next_account_info(account_info_iter)?;
+        
+        validation::check_program_owned(program_id, withdrawal_request_info)?;
+        let withdrawal_request = WithdrawalRequest::try_from_slice(
+            &withdrawal_request_info.data.borrow()
+        )?;
+        if !withdrawal_request.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        if *withdrawal_request_info.key != withdrawal_request.address(program_id) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if !withdrawal_request.completed && !withdrawal_request.cancelled {
+            return Err(FPPError::AccountInUse.into());
+        }
+        // Whoever opened the request paid for it: the relayer for relayed ones
+        let rent_payer = if withdrawal_request.is_relayed() {
+            withdrawal_request.relayer
+        } else {
+            withdrawal_request.requester
+        };
+        if *rent_payer_info.key != rent_payer {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        // A completed request's points were paid out, so they give up their mass and become
+        // closable. Cancelled requests already reactivated or burned theirs.
+        if withdrawal_request.completed {
+            let point_count = withdrawal_request.point_count as usize;
+            let point_infos = (0..point_count)
+                .map(|_| next_account_info(account_info_iter))
+                .collect::<Result<Vec<_>, _>>()?;
+            let nullifier_infos = (0..point_count)
+                .map(|_| next_account_info(account_info_iter))
+                .collect::<Result<Vec<_>, _>>()?;
+            
+            let mut points_hash = [0u8; 32];
+            for (point_info, nullifier_info) in point_infos.iter().zip(nullifier_infos.iter()) {
+                validation::check_program_owned(program_id, nullifier_info)?;
+                let record = NullifierSet::try_from_slice(&nullifier_info.data.borrow())?;
+                if *nullifier_info.key != pda::find_nullifier(&record.nullifier, program_id).0 {
+                    return Err(FPPError::InvalidAccount.into());
+                }
+                points_hash = WithdrawalRequest::chain_points_hash(
+                    &points_hash,
+                    point_info.key,
+                    &record.nullifier,
+                );
+            }
+            if points_hash != withdrawal_request.points_hash {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            
+            for point_info in point_infos.iter() {
+                let mut point = Self::load_point(program_id, point_info)?;
+                point.mass = 0;
+                point.serialize(&mut &mut point_info.data.borrow_mut()[..])?;
+            }
+        }
+        
+        Self::close_account(withdrawal_request_info, rent_payer_info)?;
+        
+        msg!("Withdrawal request {} closed", withdrawal_request_info.key);
+        Ok(())
+    }
+    
+    pub fn process_compact_nullifiers(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        count: u8,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let nullifier_filter_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info)
+            || nullifier_filter_info.owner != program_id
+            || *nullifier_filter_info.key != NullifierFilter::find_address(program_id).0
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let fee_receiver = {
+            let protocol_state_data = protocol_state_info.try_borrow_data()?;
+            ProtocolState::load(&protocol_state_data)?.fee_receiver
+        };
+        // Spends only look for compacted nullifiers on a filter hit, so it must be complete
+        if !NullifierFilter::split_mut(&mut nullifier_filter_info.try_borrow_mut_data()?)?
+            .0
+            .is_initialized
+        {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        
+        let rent = Rent::get()?;
+        for _ in 0..count {
+            let nullifier_info = next_account_info(account_info_iter)?;
+            let payer_info = next_account_info(account_info_iter)?;
+            let withdrawal_request_info = next_account_info(account_info_iter)?;
+            let shard_info = next_account_info(account_info_iter)?;
+            
+            validation::check_program_owned(program_id, nullifier_info)?;
+            let record = NullifierSet::try_from_slice(&nullifier_info.data.borrow())?;
+            if !record.is_initialized {
+                return Err(FPPError::AccountNotInitialized.into());
+            }
+            if *nullifier_info.key != pda::find_nullifier(&record.nullifier, program_id).0 {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            let payer = if record.payer == Pubkey::default() {
+                fee_receiver
+            } else {
+                record.payer
+            };
+            if *payer_info.key != payer
+                || *withdrawal_request_info.key != record.withdrawal_request
+            {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            
+            // A cancellation needs the record to release the nullifier, so one held by a
+            // request must wait until the request is closed or has been burned
+            if withdrawal_request_info.owner == program_id
+                && !withdrawal_request_info.data_is_empty()
+            {
+                let withdrawal_request = WithdrawalRequest::try_from_slice(
+                    &withdrawal_request_info.data.borrow()
+                )?;
+                if !withdrawal_request.cancelled {
+                    return Err(FPPError::AccountInUse.into());
+                }
+            }
+            
+            // The shard grows by one entry, paid for out of the record's rent
+            let (shard_address, shard_bump) =
+                NullifierShard::find_address(&record.nullifier, program_id);
+            if *shard_info.key != shard_address {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            let is_new = shard_info.owner != program_id;
+            let new_len = if is_new {
+                NullifierShard::HEADER_LEN + NullifierShard::ENTRY_LEN
+            } else {
+                shard_info.data_len() + NullifierShard::ENTRY_LEN
+            };
+            let top_up = rent.minimum_balance(new_len).saturating_sub(shard_info.lamports());
+            **nullifier_info.try_borrow_mut_lamports()? = nullifier_info
+                .lamports()
+                .checked_sub(top_up)
+                .ok_or(FPPError::InvalidAmount)?;
+            **shard_info.try_borrow_mut_lamports()? = shard_info
+                .lamports()
+                .checked_add(top_up)
+                .ok_or(FPPError::InvalidAmount)?;
+            if is_new {
+                // Already funded, so the record stands in as the payer and is never charged
+                let prefix = NullifierShard::prefix_of(&record.nullifier);
+                Self::create_pda_account(
+                    program_id,
+                    nullifier_info,
+                    shard_info,
+                    system_program_info,
+                    &rent,
+                    new_len,
+                    &[NullifierShard::SEED_PREFIX, &prefix, &[shard_bump]],
+                )?;
+                let header = NullifierShard {
+                    version: NullifierShard::VERSION,
+                    is_initialized: true,
+                    prefix,
+                    count: 0,
+                };
+                header.serialize(&mut &mut shard_info.data.borrow_mut()[..])?;
+            } else {
+                shard_info.realloc(new_len, false)?;
+                Self::check_rent_exempt(shard_info)?;
+            }
+            NullifierShard::insert(&mut shard_info.try_borrow_mut_data()?, &record.nullifier)?;
+            
+            {
+                let mut filter_data = nullifier_filter_info.try_borrow_mut_data()?;
+                let (filter, bits) = NullifierFilter::split_mut(&mut filter_data)?;
+                filter.insert(bits, &record.nullifier);
+            }
+            
+            Self::close_account(nullifier_info, payer_info)?;
+        }
+        
+        msg!("Compacted {} nullifiers", count);
+        Ok(())
+    }
+    
+    pub fn process_update_fees(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        deposit_fee_rate: u16,
+        withdrawal_fee_rate: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if deposit_fee_rate > ProtocolState::MAX_FEE_RATE
+            || withdrawal_fee_rate > ProtocolState::MAX_FEE_RATE
+        {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        // Depositors get the full timelock to exit before new fees apply
+        if deposit_fee_rate == protocol_state.deposit_fee_rate
+            && withdrawal_fee_rate == protocol_state.withdrawal_fee_rate
+        {
+            protocol_state.pending_config = PendingConfig::default();
+            msg!("Pending fee change cancelled");
+        } else {
+            let now = SyscallClock.unix_timestamp()?;
+            protocol_state.pending_config = PendingConfig {
+                deposit_fee_rate,
+                withdrawal_fee_rate,
+                effective_at: now + CONFIG_TIMELOCK,
+                ..PendingConfig::default()
+            };
+            msg!(
+                "Fees queued: deposit {} bps, withdrawal {} bps, effective at {}",
+                deposit_fee_rate,
+                withdrawal_fee_rate,
+                protocol_state.pending_config.effective_at
+            );
+        }
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        
+        // A queued change is logged when it is made, not when it takes effect
+        let rates = |deposit: u16, withdrawal: u16| {
+            AuditEntry::value(&[deposit.to_le_bytes(), withdrawal.to_le_bytes()].concat())
+        };
+        Self::log_admin_action(
+            program_id,
+            &protocol_state,
+            account_info_iter.as_slice(),
+            authority_info.key,
+            AuditAction::UpdateFees,
+            rates(protocol_state.deposit_fee_rate, protocol_state.withdrawal_fee_rate),
+            rates(deposit_fee_rate, withdrawal_fee_rate),
+        )?;
+        Ok(())
+    }
+    
+    pub fn process_apply_pending_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        let pending = protocol_state.pending_config;
+        if !pending.is_pending() {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        let now = SyscallClock.unix_timestamp()?;
+        if now < pending.effective_at {
+            return Err(FPPError::ConfigTimelockActive.into());
+        }
+        
+        protocol_state.deposit_fee_rate = pending.deposit_fee_rate;
+        protocol_state.withdrawal_fee_rate = pending.withdrawal_fee_rate;
+        protocol_state.pending_config = PendingConfig::default();
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        
+        FPPEvent::FeeUpdated {
+            deposit_fee_rate: pending.deposit_fee_rate,
+            withdrawal_fee_rate: pending.withdrawal_fee_rate,
+        }
+        .emit();
+        Ok(())
+    }
+    
+    pub fn process_set_paused(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        paused: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        // The guardian can halt the protocol, but only the authority can resume it
+        let is_guardian_pause = paused && protocol_state.guardian == *authority_info.key;
+        if protocol_state.authority != *authority_info.key && !is_guardian_pause {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let previous = protocol_state.paused_flags;
+        let flags = if paused { ProtocolState::PAUSE_ALL } else { 0 };
+        Self::update_paused_flags(&mut protocol_state, flags)?;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        Self::log_admin_action(
+            program_id,
+            &protocol_state,
+            account_info_iter.as_slice(),
+            authority_info.key,
+            AuditAction::SetPaused,
+            AuditEntry::value(&[previous]),
+            AuditEntry::value(&[flags]),
+        )?;
+        
+        if paused {
+            msg!("Protocol paused");
+        }
+        Ok(())
+    }
+    
+    pub fn process_set_paused_flags(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        flags: u8,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if flags & !ProtocolState::PAUSE_ALL != 0 {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        let previous = protocol_state.paused_flags;
+        Self::update_paused_flags(&mut protocol_state, flags)?;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        Self::log_admin_action(
+            program_id,
+            &protocol_state,
+            account_info_iter.as_slice(),
+            authority_info.key,
+            AuditAction::SetPausedFlags,
+            AuditEntry::value(&[previous]),
+            AuditEntry::value(&[flags]),
+        )?;
+        
+        msg!("Pause flags set to {:#05b}", protocol_state.paused_flags);
+        Ok(())
+    }
+    
+    /// Pause newly set bits at once, and queue lifting any cleared ones until
+    /// `UNPAUSE_TIMELOCK` has passed
+    fn update_paused_flags(protocol_state: &mut ProtocolState, flags: u8) -> ProgramResult {
+        protocol_state.paused_flags |= flags;
+        if protocol_state.paused_flags == flags {
+            // Nothing to lift, which also supersedes an unpause still waiting
+            protocol_state.pending_unpause = PendingUnpause::default();
+            return Ok(());
+        }
+        
+        let effective_at = SyscallClock
+            .unix_timestamp()?
+            .checked_add(UNPAUSE_TIMELOCK)
+            .ok_or(FPPError::InvalidAmount)?;
+        protocol_state.pending_unpause = PendingUnpause {
+            effective_at,
+            paused_flags: flags,
+            ..PendingUnpause::default()
+        };
+        FPPEvent::UnpauseQueued {
+            paused_flags: flags,
+            effective_at,
+        }
+        .emit();
+        msg!("Pause flags {:#05b} queued, effective at {}", flags, effective_at);
+        Ok(())
+    }
+    
+    pub fn process_apply_unpause(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        let pending = protocol_state.pending_unpause;
+        if !pending.is_pending() {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        if SyscallClock.unix_timestamp()? < pending.effective_at {
+            return Err(FPPError::ConfigTimelockActive.into());
+        }
+        
+        protocol_state.paused_flags = pending.paused_flags;
+        protocol_state.pending_unpause = PendingUnpause::default();
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        
+        FPPEvent::Unpaused {
+            paused_flags: pending.paused_flags,
+        }
+        .emit();
+        Ok(())
+    }
+    
+    pub fn process_set_cpi_guard(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        enabled: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let previous = protocol_state.reject_cpi;
+        protocol_state.reject_cpi = enabled;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        Self::log_admin_action(
+            program_id,
+            &protocol_state,
+            account_info_iter.as_slice(),
+            authority_info.key,
+            AuditAction::SetCpiGuard,
+            AuditEntry::value(&[previous as u8]),
+            AuditEntry::value(&[enabled as u8]),
+        )?;
+        
+        msg!("Deposits and withdrawal requests via CPI rejected: {}", enabled);
+        Ok(())
+    }
+    
+    pub fn process_set_min_exit_slots(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        slots: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if slots > ProtocolState::MAX_MIN_EXIT_SLOTS {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        let previous = protocol_state.min_exit_slots;
+        protocol_state.min_exit_slots = slots;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        Self::log_admin_action(
+            program_id,
+            &protocol_state,
+            account_info_iter.as_slice(),
+            authority_info.key,
+            AuditAction::SetMinExitSlots,
+            AuditEntry::value(&previous.to_le_bytes()),
+            AuditEntry::value(&slots.to_le_bytes()),
+        )?;
+        
+        msg!("Minimum exit distance set to {} slots", slots);
+        Ok(())
+    }
+    
+    pub fn process_add_to_deny_list(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        entry: [u8; 32],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let deny_list_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let rent = Rent::get()?;
+        let mut deny_list = Self::load_deny_list(program_id, deny_list_info)?;
+        if !deny_list.is_initialized {
+            let (_, bump) = DenyList::find_address(program_id);
+            Self::create_pda_account(
+                program_id,
+                payer_info,
+                deny_list_info,
+                system_program_info,
+                &rent,
+                DenyList::space(0),
+                &[DenyList::SEED, &[bump]],
+            )?;
+            deny_list.is_initialized = true;
+            deny_list.version = DenyList::VERSION;
+        }
+        
+        if deny_list.contains(&entry) || deny_list.entries.len() >= DenyList::MAX_ENTRIES {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        deny_list.entries.push(entry);
+        Self::realloc_account(
+            deny_list_info,
+            payer_info,
+            system_program_info,
+            &rent,
+            DenyList::space(deny_list.entries.len()),
+        )?;
+        deny_list.serialize(&mut &mut deny_list_info.data.borrow_mut()[..])?;
+        Self::log_admin_action(
+            program_id,
+            &protocol_state,
+            account_info_iter.as_slice(),
+            authority_info.key,
+            AuditAction::AddToDenyList,
+            [0u8; 32],
+            entry,
+        )?;
+        
+        msg!("Deny list entry added ({} total)", deny_list.entries.len());
+        Ok(())
+    }
+    
+    pub fn process_remove_from_deny_list(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        entry: [u8; 32],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let deny_list_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let mut deny_list = Self::load_deny_list(program_id, deny_list_info)?;
+        let position = deny_list
+            .entries
+            .iter()
+            .position(|existing| *existing == entry)
+            .ok_or(FPPError::InvalidInstruction)?;
+        deny_list.entries.swap_remove(position);
+        deny_list.serialize(&mut &mut deny_list_info.data.borrow_mut()[..])?;
+        Self::log_admin_action(
+            program_id,
+            &protocol_state,
+            account_info_iter.as_slice(),
+            authority_info.key,
+            AuditAction::RemoveFromDenyList,
+            entry,
+            [0u8; 32],
+        )?;
+        
+        msg!("Deny list entry removed ({} left)", deny_list.entries.len());
+        Ok(())
+    }
+    
+    pub fn process_add_fee_exemption(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        address: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let fee_exemptions_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let rent = Rent::get()?;
+        let mut fee_exemptions = Self::load_fee_exemptions(program_id, fee_exemptions_info)?;
+        if !fee_exemptions.is_initialized {
+            let (_, bump) = FeeExemptions::find_address(program_id);
+            Self::create_pda_account(
+                program_id,
+                payer_info,
+                fee_exemptions_info,
+                system_program_info,
+                &rent,
+                FeeExemptions::space(0),
+                &[FeeExemptions::SEED, &[bump]],
+            )?;
+            fee_exemptions.is_initialized = true;
+            fee_exemptions.version = FeeExemptions::VERSION;
+        }
+        
+        if fee_exemptions.is_exempt(&address)
+            || fee_exemptions.addresses.len() >= FeeExemptions::MAX_ENTRIES
+        {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        fee_exemptions.addresses.push(address);
+        Self::realloc_account(
+            fee_exemptions_info,
+            payer_info,
+            system_program_info,
+            &rent,
+            FeeExemptions::space(fee_exemptions.addresses.len()),
+        )?;
+        fee_exemptions.serialize(&mut &mut fee_exemptions_info.data.borrow_mut()[..])?;
+        
+        FPPEvent::FeeExemptionAdded { address }.emit();
+        msg!("Fee exemption added for {}", address);
+        Ok(())
+    }
+    
+    pub fn process_remove_fee_exemption(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        address: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let fee_exemptions_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let mut fee_exemptions = Self::load_fee_exemptions(program_id, fee_exemptions_info)?;
+        let position = fee_exemptions
+            .addresses
+            .iter()
+            .position(|existing| *existing == address)
+            .ok_or(FPPError::InvalidInstruction)?;
+        fee_exemptions.addresses.swap_remove(position);
+        fee_exemptions.serialize(&mut &mut fee_exemptions_info.data.borrow_mut()[..])?;
+        
+        FPPEvent::FeeExemptionRemoved { address }.emit();
+        msg!("Fee exemption removed for {}", address);
+        Ok(())
+    }
+    
+    pub fn process_resize_root_history(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        capacity: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let root_history_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if *root_history_info.key != RootHistory::find_address(program_id).0
+            || root_history_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut root_history = RootHistory::try_from_slice(&root_history_info.data.borrow())?;
+        if capacity as usize <= root_history.roots.len() || capacity > RootHistory::MAX_SIZE {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        root_history.grow(capacity);
+        Self::realloc_account(
+            root_history_info,
+            payer_info,
+            system_program_info,
+            &Rent::get()?,
+            RootHistory::space(capacity),
+        )?;
+        root_history.serialize(&mut &mut root_history_info.data.borrow_mut()[..])?;
+        
+        msg!("Root history resized to {} roots", capacity);
+        Ok(())
+    }
+    
+    pub fn process_migrate_state(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        kind: AccountKind,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let account_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        // Earlier layouts of the protocol state are a prefix of the current one, so they load
+        // once padded out; the authority sits in the prefix
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state_data = protocol_state_info.data.borrow().to_vec();
+        protocol_state_data.resize(ProtocolState::LEN, 0);
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_data)?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        validation::check_program_owned(program_id, account_info)?;
+        let infos = (account_info, payer_info, system_program_info);
+        let from = match kind {
+            AccountKind::ProtocolState => Self::migrate_account::<ProtocolState>(infos)?,
+            AccountKind::CommitmentTree => Self::migrate_account::<CommitmentTree>(infos)?,
+            AccountKind::NullifierFilter => Self::migrate_account::<NullifierFilter>(infos)?,
+            AccountKind::FloatingPoint => Self::migrate_account::<FloatingPoint>(infos)?,
+            AccountKind::PointTree => Self::migrate_account::<PointTree>(infos)?,
+            AccountKind::WithdrawalRequest => {
+                Self::migrate_account::<WithdrawalRequest>(infos)?
+            }
+            AccountKind::WithdrawalBatch => Self::migrate_account::<WithdrawalBatch>(infos)?,
+            AccountKind::NullifierSet => Self::migrate_account::<NullifierSet>(infos)?,
+            AccountKind::RootHistory => Self::migrate_account::<RootHistory>(infos)?,
+            AccountKind::PoolConfig => Self::migrate_account::<PoolConfig>(infos)?,
+            AccountKind::CircuitInfo => Self::migrate_account::<CircuitInfo>(infos)?,
+            AccountKind::EncryptedNote => Self::migrate_account::<EncryptedNote>(infos)?,
+            AccountKind::SecurityEvent => Self::migrate_account::<SecurityEvent>(infos)?,
+            AccountKind::ContactBook => Self::migrate_account::<ContactBook>(infos)?,
+            AccountKind::AdminMultisig => Self::migrate_account::<AdminMultisig>(infos)?,
+            AccountKind::AdminProposal => Self::migrate_account::<AdminProposal>(infos)?,
+            AccountKind::UserActivity => Self::migrate_account::<UserActivity>(infos)?,
+            AccountKind::DenyList => Self::migrate_account::<DenyList>(infos)?,
+            AccountKind::AssociationSet => Self::migrate_account::<AssociationSet>(infos)?,
+            AccountKind::RelayerConfig => Self::migrate_account::<RelayerConfig>(infos)?,
+            AccountKind::DepositPermit => Self::migrate_account::<DepositPermit>(infos)?,
+            AccountKind::SupportedMint => Self::migrate_account::<SupportedMint>(infos)?,
+        };
+        
+        msg!("{:?} account migrated from version {}", kind, from);
+        Ok(())
+    }
+    
+    pub fn process_extend_nullifier_filter(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let filter_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let (expected, bump) = NullifierFilter::find_address(program_id);
+        if *filter_info.key != expected {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        // Accounts grow at most `MAX_PERMITTED_DATA_INCREASE` per instruction, even when
+        // created through a CPI, so the filter is allocated over several calls
+        let rent = Rent::get()?;
+        if filter_info.owner != program_id {
+            Self::create_pda_account(
+                program_id,
+                payer_info,
+                filter_info,
+                system_program_info,
+                &rent,
+                NullifierFilter::LEN.min(MAX_PERMITTED_DATA_INCREASE),
+                &[NullifierFilter::SEED, &[bump]],
+            )?;
+        } else {
+            let current_len = filter_info.data_len();
+            if current_len >= NullifierFilter::LEN {
+                return Err(FPPError::AccountAlreadyInitialized.into());
+            }
+            Self::realloc_account(
+                filter_info,
+                payer_info,
+                system_program_info,
+                &rent,
+                NullifierFilter::LEN.min(current_len + MAX_PERMITTED_DATA_INCREASE),
+            )?;
+        }
+        
+        if filter_info.data_len() < NullifierFilter::LEN {
+            msg!(
+                "Nullifier filter allocated {} of {} bytes",
+                filter_info.data_len(),
+                NullifierFilter::LEN
+            );
+            return Ok(());
+        }
+        
+        let clock = SyscallClock.clock()?;
+        let mut filter_data = filter_info.try_borrow_mut_data()?;
+        let (filter, _) = NullifierFilter::split_mut(&mut filter_data)?;
+        filter.created_epoch = clock.epoch;
+        filter.num_hashes = NullifierFilter::NUM_HASHES;
+        filter.is_initialized = true;
+        filter.version = NullifierFilter::VERSION;
+        
+        msg!("Nullifier filter ready from epoch {}", clock.epoch);
+        Ok(())
+    }
+    
+    pub fn process_update_association_set(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        flagged_root: [u8; 32],
+        required: bool,
+        verifying_key: Option<Groth16VerifyingKey>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let association_set_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if !poseidon::is_field_element(&flagged_root) {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        let now = SyscallClock.unix_timestamp()?;
+        let existing = Self::load_association_set(program_id, association_set_info)?;
+        let mut association_set = match existing {
+            Some(set) => set,
+            None => {
+                let verifying_key = verifying_key.clone().ok_or(FPPError::InvalidInstruction)?;
+                let (_, bump) = AssociationSet::find_address(program_id);
+                Self::create_pda_account(
+                    program_id,
+                    payer_info,
+                    association_set_info,
+                    system_program_info,
+                    &Rent::get()?,
+                    AssociationSet::LEN,
+                    &[AssociationSet::SEED, &[bump]],
+                )?;
+                AssociationSet {
+                    version: AssociationSet::VERSION,
+                    is_initialized: true,
+                    flagged_root,
+                    required,
+                    updated_at: now,
+                    verifying_key,
+                }
+            }
+        };
+        
+        association_set.flagged_root = flagged_root;
+        association_set.required = required;
+        association_set.updated_at = now;
+        if let Some(verifying_key) = verifying_key {
+            association_set.verifying_key = verifying_key;
+        }
+        association_set.serialize(&mut &mut association_set_info.data.borrow_mut()[..])?;
+        
+        msg!("Association set updated (proofs required: {})", required);
+        Ok(())
+    }
+    
+    pub fn process_prove_withdrawal_compliance(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proof: Groth16Proof,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_info = next_account_info(account_info_iter)?;
+        let withdrawal_request_info = next_account_info(account_info_iter)?;
+        let association_set_info = next_account_info(account_info_iter)?;
+        let verifier_program_info = next_account_info(account_info_iter)?;
+        
+        if !user_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        let mut withdrawal_request =
+            Self::load_provable_request(program_id, user_info, withdrawal_request_info)?;
+        
+        let association_set = Self::load_association_set(program_id, association_set_info)?
+            .ok_or(FPPError::AccountNotInitialized)?;
+        let public_inputs = [
+            association_set.flagged_root,
+            groth16::hash_to_field(&withdrawal_request.points_hash),
+        ];
+        if !Self::verify_groth16(
+            verifier_program_info,
+            &association_set.verifying_key,
+            &proof,
+            &public_inputs,
+        )? {
+            return Err(FPPError::InvalidComplianceProof.into());
+        }
+        
+        withdrawal_request.compliance_root = association_set.flagged_root;
+        withdrawal_request.serialize(&mut &mut withdrawal_request_info.data.borrow_mut()[..])?;
+        
+        msg!("Withdrawal proven outside the flagged deposit set");
+        Ok(())
+    }
+    
+    /// Prove several pending withdrawals compliant with one batched pairing check
+    ///
+    /// Relayers holding proofs for many requests pay for `n + 3` pairings instead of `4n`.
+    /// One bad proof fails the whole batch.
+    pub fn process_batch_prove_withdrawal_compliance(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        proofs: Vec<Groth16Proof>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_info = next_account_info(account_info_iter)?;
+        let association_set_info = next_account_info(account_info_iter)?;
+        
+        if !user_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if proofs.is_empty() || proofs.len() > groth16::MAX_BATCH_PROOFS {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        let request_infos = proofs
+            .iter()
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        let verifier_program_info = next_account_info(account_info_iter)?;
+        // A request proven twice in one batch would only have its first write survive
+        if request_infos
+            .iter()
+            .enumerate()
+            .any(|(i, info)| request_infos[..i].iter().any(|other| other.key == info.key))
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut requests = request_infos
+            .iter()
+            .map(|info| Self::load_provable_request(program_id, user_info, info))
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        let association_set = Self::load_association_set(program_id, association_set_info)?
+            .ok_or(FPPError::AccountNotInitialized)?;
+        let public_inputs = requests
+            .iter()
+            .map(|request| {
+                [association_set.flagged_root, groth16::hash_to_field(&request.points_hash)]
+            })
+            .collect::<Vec<_>>();
+        validation::check_verifier_program(verifier_program_info)?;
+        let valid = fpp_verifier::cpi::verify_batch(
+            verifier_program_info,
+            &association_set.verifying_key,
+            &proofs,
+            &public_inputs,
+        )?;
+        if !valid {
+            return Err(FPPError::InvalidComplianceProof.into());
+        }
+        
+        for (request, info) in requests.iter_mut().zip(request_infos.iter()) {
+            request.compliance_root = association_set.flagged_root;
+            request.serialize(&mut &mut info.data.borrow_mut()[..])?;
+        }
+        
+        msg!("{} withdrawals proven outside the flagged deposit set", requests.len());
+        Ok(())
+    }
+    
+    /// Load a pending withdrawal request `user_info` may prove compliance for
+    fn load_provable_request(
+        program_id: &Pubkey,
+        user_info: &AccountInfo,
+        withdrawal_request_info: &AccountInfo,
+    ) -> Result<WithdrawalRequest, ProgramError> {
+        validation::check_program_owned(program_id, withdrawal_request_info)?;
+        let withdrawal_request = WithdrawalRequest::try_from_slice(
+            &withdrawal_request_info.data.borrow()
+        )?;
+        if !withdrawal_request.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        if *withdrawal_request_info.key != withdrawal_request.address(program_id) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        // The relayer proves for relayed requests, so the recipient never has to sign
+        let is_party = withdrawal_request.requester == *user_info.key
+            || (withdrawal_request.is_relayed() && withdrawal_request.relayer == *user_info.key);
+        if !is_party {
+            return Err(FPPError::Unauthorized.into());
+        }
+        if withdrawal_request.completed || withdrawal_request.cancelled {
+            return Err(FPPError::Unauthorized.into());
+        }
+        Ok(withdrawal_request)
+    }
+    
+    /// Register a mint besides the primary one, or update its settings
+    ///
+    /// Fee rates of a registered mint may only go down here; raising them
+    /// immediately would sidestep the timelock that guards protocol fees.
+    pub fn process_add_supported_mint(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        mint: Pubkey,
+        config: SupportedMintConfig,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let supported_mint_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        // The primary mint is configured through the protocol state
+        if *mint_info.key != mint || mint == protocol_state.usdt_mint {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        token::unpack_mint(mint_info)?;
+        if !config.is_valid() {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        let (expected, bump) = SupportedMint::find_address(&mint, program_id);
+        if *supported_mint_info.key != expected {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let entry = if supported_mint_info.owner == program_id {
+            let mut entry = SupportedMint::try_from_slice(&supported_mint_info.data.borrow())?;
+            if config.deposit_fee_rate > entry.config.deposit_fee_rate
+                || config.withdrawal_fee_rate > entry.config.withdrawal_fee_rate
+            {
+                return Err(FPPError::InvalidInstruction.into());
+            }
+            entry.config = config;
+            entry
+        } else {
+            Self::create_pda_account(
+                program_id,
+                payer_info,
+                supported_mint_info,
+                system_program_info,
+                &Rent::get()?,
+                SupportedMint::LEN,
+                &[SupportedMint::SEED_PREFIX, mint.as_ref(), &[bump]],
+            )?;
+            SupportedMint {
+                version: SupportedMint::VERSION,
+                is_initialized: true,
+                mint,
+                token_program: *mint_info.owner,
+                config,
+                total_deposited: 0,
+                total_withdrawn: 0,
+                total_fees: 0,
+            }
+        };
+        entry.serialize(&mut &mut supported_mint_info.data.borrow_mut()[..])?;
+        
+        msg!("Supported mint {} configured", mint);
+        Ok(())
+    }
+    
+    pub fn process_set_rate_limits(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        limits: RateLimits,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if limits.window <= 0 {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        protocol_state.rate_limits = limits;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        
+        msg!(
+            "Rate limits set: {} deposits / {} withdrawals per {}s",
+            limits.max_deposits,
+            limits.max_withdrawals,
+            limits.window
+        );
+        Ok(())
+    }
+    
+    pub fn process_create_pool(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        denomination: u64,
+        circuit_id: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let creator_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let pool_config_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let circuit_info = next_account_info(account_info_iter)?;
+        
+        if !creator_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        
+        // Pools may only be created for whitelisted mints within governance bounds
+        if *mint_info.key != protocol_state.usdt_mint {
+            return Err(FPPError::MintNotSupported.into());
+        }
+        if denomination < protocol_state.min_pool_denomination
+            || denomination > protocol_state.max_pool_denomination
+        {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        // The selected circuit must be registered and still accepting new pools
+        if *circuit_info.key != CircuitInfo::find_address(circuit_id, program_id).0
+            || circuit_info.owner != program_id
+        {
+            return Err(FPPError::InvalidCircuit.into());
+        }
+        let circuit = CircuitInfo::try_from_slice(&circuit_info.data.borrow())?;
+        if !circuit.is_initialized || !circuit.is_active {
+            return Err(FPPError::InvalidCircuit.into());
+        }
+        
+        let (pool_address, pool_bump) = PoolConfig::find_address(mint_info.key, denomination, program_id);
+        if *pool_config_info.key != pool_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            creator_info,
+            pool_config_info,
+            system_program_info,
+            &Rent::get()?,
+            PoolConfig::LEN,
+            &[
+                PoolConfig::SEED_PREFIX,
+                mint_info.key.as_ref(),
+                &denomination.to_le_bytes(),
+                &[pool_bump],
+            ],
+        )?;
+        
+        // The bond stays in the pool account on top of its rent
+        let bond = protocol_state.pool_creation_bond;
+        if bond > 0 {
+            invoke(
+                &system_instruction::transfer(creator_info.key, pool_config_info.key, bond),
+                &[
+                    creator_info.clone(),
+                    pool_config_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        }
+        
+        let pool_config = PoolConfig {
+            version: PoolConfig::VERSION,
+            is_initialized: true,
+            creator: *creator_info.key,
+            mint: *mint_info.key,
+            denomination,
+            bond,
+            created_at: SyscallClock.unix_timestamp()?,
+            total_points: 0,
+            circuit_id,
+            tree_depth: circuit.tree_depth,
+            max_inputs: circuit.max_inputs,
+            max_outputs: circuit.max_outputs,
+        };
+        pool_config.serialize(&mut &mut pool_config_info.data.borrow_mut()[..])?;
+        
+        msg!(
+            "Pool created: denomination {}, bond {}, circuit {}",
+            denomination,
+            bond,
+            circuit_id
+        );
+        Ok(())
+    }
+    
+    pub fn process_set_pool_bounds(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        min_denomination: u64,
+        max_denomination: u64,
+        creation_bond: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if min_denomination == 0 || min_denomination > max_denomination {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        protocol_state.min_pool_denomination = min_denomination;
+        protocol_state.max_pool_denomination = max_denomination;
+        protocol_state.pool_creation_bond = creation_bond;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        
+        msg!(
+            "Pool bounds updated: {}..={}, bond {}",
+            min_denomination,
+            max_denomination,
+            creation_bond
+        );
+        Ok(())
+    }
+    
+    pub fn process_register_circuit(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        circuit: CircuitInfo,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let circuit_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer || !payer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if circuit.tree_depth == 0
+            || circuit.tree_depth > CircuitInfo::MAX_TREE_DEPTH
+            || circuit.max_inputs == 0
+            || circuit.max_inputs > CircuitInfo::MAX_INPUTS
+            || circuit.max_outputs == 0
+            || circuit.max_outputs > CircuitInfo::MAX_OUTPUTS
+        {
+            return Err(FPPError::InvalidCircuit.into());
+        }
+        
+        let (circuit_address, circuit_bump) = CircuitInfo::find_address(circuit.circuit_id, program_id);
+        if *circuit_info.key != circuit_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            circuit_info,
+            system_program_info,
+            &Rent::get()?,
+            CircuitInfo::LEN,
+            &[
+                CircuitInfo::SEED_PREFIX,
+                &circuit.circuit_id.to_le_bytes(),
+                &[circuit_bump],
+            ],
+        )?;
+        circuit.serialize(&mut &mut circuit_info.data.borrow_mut()[..])?;
+        
+        msg!(
+            "Circuit {} registered: depth {}, {} inputs, {} outputs",
+            circuit.circuit_id,
+            circuit.tree_depth,
+            circuit.max_inputs,
+            circuit.max_outputs
+        );
+        Ok(())
+    }
+    
+    /// Publish the first verifying key of a circuit, or queue its replacement (admin only)
+    /// 
+    /// The first key must hash to the one the circuit was registered with and takes effect
+    /// at once. Later keys need a higher `circuit_version` and wait out `CONFIG_TIMELOCK`;
+    /// resubmitting the current key and version cancels a queued one.
+    pub fn process_update_verifying_key(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        circuit_id: u16,
+        circuit_version: u16,
+        verifying_key: Groth16VerifyingKey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let circuit_info = next_account_info(account_info_iter)?;
+        let circuit_key_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer || !payer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if *circuit_info.key != CircuitInfo::find_address(circuit_id, program_id).0
+            || circuit_info.owner != program_id
+        {
+            return Err(FPPError::InvalidCircuit.into());
+        }
+        let circuit = CircuitInfo::try_from_slice(&circuit_info.data.borrow())?;
+        if !circuit.is_initialized {
+            return Err(FPPError::InvalidCircuit.into());
+        }
+        
+        let (key_address, key_bump) = CircuitKey::find_address(circuit_id, program_id);
+        if *circuit_key_info.key != key_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let now = SyscallClock.unix_timestamp()?;
+        
+        if circuit_key_info.owner != program_id {
+            if hash(&verifying_key.try_to_vec()?).to_bytes() != circuit.verifying_key_hash {
+                return Err(FPPError::InvalidCircuit.into());
+            }
+            Self::create_pda_account(
+                program_id,
+                payer_info,
+                circuit_key_info,
+                system_program_info,
+                &Rent::get()?,
+                CircuitKey::LEN,
+                &[CircuitKey::SEED_PREFIX, &circuit_id.to_le_bytes(), &[key_bump]],
+            )?;
+            let circuit_key = CircuitKey {
+                version: CircuitKey::VERSION,
+                is_initialized: true,
+                circuit_id,
+                circuit_version,
+                pending_key: verifying_key.clone(),
+                verifying_key,
+                updated_at: now,
+                pending_circuit_version: 0,
+                pending_effective_at: 0,
+            };
+            circuit_key.serialize(&mut &mut circuit_key_info.data.borrow_mut()[..])?;
+            
+            FPPEvent::VerifyingKeyUpdated {
+                circuit_id,
+                circuit_version,
+            }
+            .emit();
+            return Ok(());
+        }
+        
+        let mut circuit_key = CircuitKey::try_from_slice(&circuit_key_info.data.borrow())?;
+        if circuit_version == circuit_key.circuit_version
+            && verifying_key == circuit_key.verifying_key
+        {
+            circuit_key.pending_effective_at = 0;
+            msg!("Pending key for circuit {} cancelled", circuit_id);
+        } else if circuit_version <= circuit_key.circuit_version {
+            return Err(FPPError::InvalidCircuit.into());
+        } else {
+            circuit_key.pending_circuit_version = circuit_version;
+            circuit_key.pending_key = verifying_key;
+            circuit_key.pending_effective_at = now + CONFIG_TIMELOCK;
+            msg!(
+                "Circuit {} version {} queued, effective at {}",
+                circuit_id,
+                circuit_version,
+                circuit_key.pending_effective_at
+            );
+        }
+        circuit_key.serialize(&mut &mut circuit_key_info.data.borrow_mut()[..])?;
+        Ok(())
+    }
+    
+    /// Install a queued verifying key once its timelock has passed (permissionless)
+    /// 
+    /// The circuit's `verifying_key_hash` follows the key, so it keeps describing the proofs
+    /// the program accepts.
+    pub fn process_apply_verifying_key(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let circuit_info = next_account_info(account_info_iter)?;
+        let circuit_key_info = next_account_info(account_info_iter)?;
+        
+        validation::check_program_owned(program_id, circuit_key_info)?;
+        let mut circuit_key = CircuitKey::try_from_slice(&circuit_key_info.data.borrow())?;
+        if *circuit_key_info.key != CircuitKey::find_address(circuit_key.circuit_id, program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if *circuit_info.key != CircuitInfo::find_address(circuit_key.circuit_id, program_id).0 {
+            return Err(FPPError::InvalidCircuit.into());
+        }
+        validation::check_program_owned(program_id, circuit_info)?;
+        let mut circuit = CircuitInfo::try_from_slice(&circuit_info.data.borrow())?;
+        
+        if !circuit_key.is_pending() {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        let now = SyscallClock.unix_timestamp()?;
+        if now < circuit_key.pending_effective_at {
+            return Err(FPPError::ConfigTimelockActive.into());
+        }
+        
+        circuit_key.circuit_version = circuit_key.pending_circuit_version;
+        circuit_key.verifying_key = circuit_key.pending_key.clone();
+        circuit_key.updated_at = now;
+        circuit_key.pending_effective_at = 0;
+        circuit_key.serialize(&mut &mut circuit_key_info.data.borrow_mut()[..])?;
+        
+        circuit.verifying_key_hash = hash(&circuit_key.verifying_key.try_to_vec()?).to_bytes();
+        circuit.serialize(&mut &mut circuit_info.data.borrow_mut()[..])?;
+        
+        FPPEvent::VerifyingKeyUpdated {
+            circuit_id: circuit_key.circuit_id,
+            circuit_version: circuit_key.circuit_version,
+        }
+        .emit();
+        Ok(())
+    }
+    
+    /// Give a pool its own commitment tree and root history (permissionless)
+    /// 
+    /// Withdrawals are checked against the `CircuitKey` of the pool's circuit, so whoever
+    /// pays for the accounts has no say over which proofs the pool accepts.
+    pub fn process_open_pool(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_info = next_account_info(account_info_iter)?;
+        let pool_config_info = next_account_info(account_info_iter)?;
+        let circuit_info = next_account_info(account_info_iter)?;
+        let pool_state_info = next_account_info(account_info_iter)?;
+        let pool_tree_info = next_account_info(account_info_iter)?;
+        let pool_roots_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !payer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        validation::check_program_owned(program_id, pool_config_info)?;
+        let pool_config = PoolConfig::try_from_slice(&pool_config_info.data.borrow())?;
+        if !pool_config.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        
+        if *circuit_info.key != CircuitInfo::find_address(pool_config.circuit_id, program_id).0
+            || circuit_info.owner != program_id
+        {
+            return Err(FPPError::InvalidCircuit.into());
+        }
+        let circuit = CircuitInfo::try_from_slice(&circuit_info.data.borrow())?;
+        if !circuit.is_initialized {
+            return Err(FPPError::InvalidCircuit.into());
+        }
+        
+        let pool = pool_config_info.key;
+        let (state_address, state_bump) = PoolState::find_address(pool, program_id);
+        let (tree_address, tree_bump) = PoolState::find_tree_address(pool, program_id);
+        let (roots_address, roots_bump) = PoolState::find_root_history_address(pool, program_id);
+        if *pool_state_info.key != state_address
+            || *pool_tree_info.key != tree_address
+            || *pool_roots_info.key != roots_address
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let rent = Rent::get()?;
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            pool_tree_info,
+            system_program_info,
+            &rent,
+            CommitmentTree::LEN,
+            &[PoolState::TREE_SEED_PREFIX, pool.as_ref(), &[tree_bump]],
+        )?;
+        let tree = CommitmentTree::new()?;
+        tree.serialize(&mut &mut pool_tree_info.data.borrow_mut()[..])?;
+        
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            pool_roots_info,
+            system_program_info,
+            &rent,
+            RootHistory::space(RootHistory::DEFAULT_SIZE),
+            &[PoolState::ROOT_HISTORY_SEED_PREFIX, pool.as_ref(), &[roots_bump]],
+        )?;
+        let root_history = RootHistory::new(RootHistory::DEFAULT_SIZE, tree.current_root());
+        root_history.serialize(&mut &mut pool_roots_info.data.borrow_mut()[..])?;
+        
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            pool_state_info,
+            system_program_info,
+            &rent,
+            PoolState::LEN,
+            &[PoolState::SEED_PREFIX, pool.as_ref(), &[state_bump]],
+        )?;
+        let pool_state = PoolState {
+            version: PoolState::VERSION,
+            is_initialized: true,
+            pool: *pool,
+            deposit_count: 0,
+            withdrawal_count: 0,
+            opened_at: SyscallClock.unix_timestamp()?,
+        };
+        pool_state.serialize(&mut &mut pool_state_info.data.borrow_mut()[..])?;
+        
+        msg!("Pool {} opened with circuit {}", pool, pool_config.circuit_id);
+        Ok(())
+    }
+    
+    /// Deposit exactly one pool denomination behind `commitment`
+    /// 
+    /// Unlike `Deposit` no point account is created: the commitment only enters the pool's
+    /// tree, and the deposit can only leave through `PoolWithdraw`.
+    pub fn process_pool_deposit(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        commitment: [u8; 32],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let depositor_info = next_account_info(account_info_iter)?;
+        let depositor_token_info = next_account_info(account_info_iter)?;
+        let treasury_token_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let pool_config_info = next_account_info(account_info_iter)?;
+        let pool_state_info = next_account_info(account_info_iter)?;
+        let pool_tree_info = next_account_info(account_info_iter)?;
+        let pool_roots_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let supported_mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let deny_list_info = next_account_info(account_info_iter)?;
+        
+        if !depositor_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        validation::check_program_owned(program_id, pool_config_info)?;
+        let mut pool_config = PoolConfig::try_from_slice(&pool_config_info.data.borrow())?;
+        let mut pool_state =
+            Self::load_pool_state(program_id, pool_config_info.key, pool_state_info)?;
+        if *pool_tree_info.key != PoolState::find_tree_address(pool_config_info.key, program_id).0
+            || *pool_roots_info.key
+                != PoolState::find_root_history_address(pool_config_info.key, program_id).0
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_DEPOSITS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if *mint_info.key != pool_config.mint {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut entry =
+            Self::load_mint_entry(program_id, protocol_state, mint_info, supported_mint_info)?;
+        validation::check_token_program(token_program_info, &entry.token_program)?;
+        validation::check_treasury_token_account(protocol_state, &entry, treasury_token_info)?;
+        let mint = token::unpack_mint(mint_info)?;
+        
+        let deny_list = Self::load_deny_list(program_id, deny_list_info)?;
+        if deny_list.contains_key(depositor_info.key)
+            || deny_list.contains_key(depositor_token_info.key)
+            || deny_list.contains(&commitment)
+        {
+            return Err(FPPError::DeniedAddress.into());
+        }
+        
+        let amount = pool_config.denomination;
+        if !entry.accepts_deposit(amount) || !protocol_state.accepts_value(amount) {
+            return Err(FPPError::DepositCapExceeded.into());
+        }
+        
+        // The fee comes on top, so every leaf in the pool is backed by the same amount
+        let fee_rate = protocol_state.deposit_fee_rate_for(amount, entry.config.deposit_fee_rate);
+        let fee = (amount as u128 * fee_rate as u128 / 10000) as u64;
+        let gross_amount = amount.checked_add(fee).ok_or(FPPError::InvalidAmount)?;
+        let clock = SyscallClock.clock()?;
+        let token_amount = decimals::to_token_amount_ceil(gross_amount, mint.decimals)
+            .ok_or(FPPError::InvalidAmount)?;
+        let sent_amount = token::gross_up(mint_info, clock.epoch, token_amount)?;
+        token::transfer(
+            token_program_info,
+            depositor_token_info,
+            mint_info,
+            treasury_token_info,
+            depositor_info,
+            sent_amount,
+            mint.decimals,
+            &[],
+        )?;
+        
+        validation::check_program_owned(program_id, pool_tree_info)?;
+        let mut pool_tree_data = pool_tree_info.try_borrow_mut_data()?;
+        let pool_tree = CommitmentTree::load_mut(&mut pool_tree_data)?;
+        let leaf_index = pool_tree.insert(commitment)?;
+        let merkle_root = pool_tree.current_root();
+        
+        let mut root_history = RootHistory::try_from_slice(&pool_roots_info.data.borrow())?;
+        root_history.push(merkle_root);
+        root_history.serialize(&mut &mut pool_roots_info.data.borrow_mut()[..])?;
+        
+        entry.total_deposited = entry
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(FPPError::InvalidAmount)?;
+        entry.total_fees = entry.total_fees.checked_add(fee).ok_or(FPPError::InvalidAmount)?;
+        Self::store_mint_entry(protocol_state, &entry, supported_mint_info)?;
+        protocol_state.value_locked = protocol_state
+            .value_locked
+            .checked_add(amount)
+            .ok_or(FPPError::InvalidAmount)?;
+        
+        pool_config.total_points += 1;
+        pool_config.serialize(&mut &mut pool_config_info.data.borrow_mut()[..])?;
+        pool_state.deposit_count += 1;
+        pool_state.serialize(&mut &mut pool_state_info.data.borrow_mut()[..])?;
+        
+        FPPEvent::PoolDeposit {
+            pool: *pool_config_info.key,
+            commitment,
+            leaf_index,
+            merkle_root,
+        }
+        .emit();
+        Ok(())
+    }
+    
+    /// Pay out one pool deposit against a proof of membership in the pool's tree
+    /// 
+    /// The submitter is the relayer: the proof binds its key and `relayer_fee` together with
+    /// the recipient token account, and it is paid at once; there is no withdrawal delay to
+    /// wait out, as nothing on chain links the nullifier to a deposit.
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_pool_withdraw(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        merkle_root: [u8; 32],
+        nullifier: [u8; 32],
+        recipient: Pubkey,
+        relayer_fee: u64,
+        proof: Groth16Proof,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let relayer_info = next_account_info(account_info_iter)?;
+        let recipient_token_info = next_account_info(account_info_iter)?;
+        let relayer_token_info = next_account_info(account_info_iter)?;
+        let treasury_token_info = next_account_info(account_info_iter)?;
+        let treasury_authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let pool_config_info = next_account_info(account_info_iter)?;
+        let pool_state_info = next_account_info(account_info_iter)?;
+        let pool_roots_info = next_account_info(account_info_iter)?;
+        let pool_nullifier_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let supported_mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let deny_list_info = next_account_info(account_info_iter)?;
+        let verifier_program_info = next_account_info(account_info_iter)?;
+        let circuit_key_info = next_account_info(account_info_iter)?;
+        
+        if !relayer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if *recipient_token_info.key != recipient {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        validation::check_program_owned(program_id, pool_config_info)?;
+        let mut pool_config = PoolConfig::try_from_slice(&pool_config_info.data.borrow())?;
+        let pool = pool_config_info.key;
+        let mut pool_state = Self::load_pool_state(program_id, pool, pool_state_info)?;
+        if *pool_roots_info.key != PoolState::find_root_history_address(pool, program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        validation::check_program_owned(program_id, pool_roots_info)?;
+        let root_history = RootHistory::try_from_slice(&pool_roots_info.data.borrow())?;
+        if !root_history.contains(&merkle_root) {
+            return Err(FPPError::UnknownMerkleRoot.into());
+        }
+        
+        let circuit_key =
+            Self::load_circuit_key(program_id, pool_config.circuit_id, circuit_key_info)?;
+        let binding = PoolState::withdrawal_binding(
+            pool,
+            circuit_key.circuit_version,
+            pool_config.denomination,
+            &nullifier,
+            &recipient,
+            relayer_info.key,
+            relayer_fee,
+        )?;
+        let public_inputs = [merkle_root, binding];
+        if !Self::verify_groth16(
+            verifier_program_info,
+            &circuit_key.verifying_key,
+            &proof,
+            &public_inputs,
+        )? {
+            return Err(FPPError::InvalidProof.into());
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_WITHDRAWALS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let now = SyscallClock.unix_timestamp()?;
+        Self::spend_pool_nullifier(
+            program_id,
+            pool,
+            relayer_info,
+            pool_nullifier_info,
+            system_program_info,
+            &nullifier,
+            now,
+        )?;
+        
+        Self::check_exit_destination(
+            program_id,
+            deny_list_info,
+            recipient_token_info,
+            recipient_token_info,
+        )?;
+        if relayer_fee > 0 && token::unpack_account(relayer_token_info)?.owner != *relayer_info.key
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        if *mint_info.key != pool_config.mint {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut entry =
+            Self::load_mint_entry(program_id, protocol_state, mint_info, supported_mint_info)?;
+        let amount = pool_config.denomination;
+        let fee_rate =
+            protocol_state.withdrawal_fee_rate_for(amount, entry.config.withdrawal_fee_rate);
+        let fee = (amount as u128 * fee_rate as u128 / 10000) as u64;
+        let recipient_amount = amount
+            .checked_sub(fee)
+            .and_then(|amount| amount.checked_sub(relayer_fee))
+            .ok_or(FPPError::InvalidAmount)?;
+        
+        Self::transfer_from_treasury(
+            program_id,
+            protocol_state,
+            &entry,
+            treasury_token_info,
+            recipient_token_info,
+            treasury_authority_info,
+            token_program_info,
+            mint_info,
+            recipient_amount,
+        )?;
+        if relayer_fee > 0 {
+            Self::transfer_from_treasury(
+                program_id,
+                protocol_state,
+                &entry,
+                treasury_token_info,
+                relayer_token_info,
+                treasury_authority_info,
+                token_program_info,
+                mint_info,
+                relayer_fee,
+            )?;
+        }
+        
+        entry.total_withdrawn = entry
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(FPPError::InvalidAmount)?;
+        entry.total_fees = entry.total_fees.checked_add(fee).ok_or(FPPError::InvalidAmount)?;
+        Self::store_mint_entry(protocol_state, &entry, supported_mint_info)?;
+        protocol_state.release_value(amount);
+        
+        pool_config.total_points = pool_config.total_points.saturating_sub(1);
+        pool_config.serialize(&mut &mut pool_config_info.data.borrow_mut()[..])?;
+        pool_state.withdrawal_count += 1;
+        pool_state.serialize(&mut &mut pool_state_info.data.borrow_mut()[..])?;
+        
+        FPPEvent::PoolWithdrawal {
+            pool: *pool,
+            nullifier,
+            recipient,
+            relayer: *relayer_info.key,
+            relayer_fee,
+            fee,
+        }
+        .emit();
+        Ok(())
+    }
+    
+    /// Let notes move from one pool to another of the same mint (permissionless)
+    /// 
+    /// Transfers are proven against the `CircuitKey` of `circuit_id`, whose input and output
+    /// counts bound every transfer along the route.
+    pub fn process_open_pool_route(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        circuit_id: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let circuit_info = next_account_info(account_info_iter)?;
+        let route_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !payer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        validation::check_program_owned(program_id, source_info)?;
+        validation::check_program_owned(program_id, destination_info)?;
+        let source = PoolConfig::try_from_slice(&source_info.data.borrow())?;
+        let destination = PoolConfig::try_from_slice(&destination_info.data.borrow())?;
+        if !source.is_initialized || !destination.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        if source_info.key == destination_info.key || source.mint != destination.mint {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        if *circuit_info.key != CircuitInfo::find_address(circuit_id, program_id).0
+            || circuit_info.owner != program_id
+        {
+            return Err(FPPError::InvalidCircuit.into());
+        }
+        let circuit = CircuitInfo::try_from_slice(&circuit_info.data.borrow())?;
+        if !circuit.is_initialized || !circuit.is_active {
+            return Err(FPPError::InvalidCircuit.into());
+        }
+        
+        let (route_address, route_bump) =
+            PoolRoute::find_address(source_info.key, destination_info.key, program_id);
+        if *route_info.key != route_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            route_info,
+            system_program_info,
+            &Rent::get()?,
+            PoolRoute::LEN,
+            &[
+                PoolRoute::SEED_PREFIX,
+                source_info.key.as_ref(),
+                destination_info.key.as_ref(),
+                &[route_bump],
+            ],
+        )?;
+        let route = PoolRoute {
+            version: PoolRoute::VERSION,
+            is_initialized: true,
+            source: *source_info.key,
+            destination: *destination_info.key,
+            circuit_id,
+            max_inputs: circuit.max_inputs,
+            max_outputs: circuit.max_outputs,
+            transfer_count: 0,
+        };
+        route.serialize(&mut &mut route_info.data.borrow_mut()[..])?;
+        
+        msg!(
+            "Pool route opened: {} -> {} (circuit {})",
+            source_info.key,
+            destination_info.key,
+            circuit_id
+        );
+        Ok(())
+    }
+    
+    /// Spend notes of one pool into new notes of another without a transparent payout
+    /// 
+    /// Nothing moves in the treasury: the inputs and outputs are worth the same, which the
+    /// program checks from the two public denominations, and both pools share the treasury.
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_pool_transfer(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        merkle_root: [u8; 32],
+        nullifiers: Vec<[u8; 32]>,
+        commitments: Vec<[u8; 32]>,
+        proof: Groth16Proof,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let submitter_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let route_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let source_state_info = next_account_info(account_info_iter)?;
+        let source_roots_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let destination_state_info = next_account_info(account_info_iter)?;
+        let destination_tree_info = next_account_info(account_info_iter)?;
+        let destination_roots_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let verifier_program_info = next_account_info(account_info_iter)?;
+        let circuit_key_info = next_account_info(account_info_iter)?;
+        let nullifier_infos = nullifiers
+            .iter()
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        
+        if !submitter_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state_data = protocol_state_info.try_borrow_data()?;
+        let protocol_state = ProtocolState::load(&protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_PRIVACY_PAYMENTS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let source = source_info.key;
+        let destination = destination_info.key;
+        if *route_info.key != PoolRoute::find_address(source, destination, program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        validation::check_program_owned(program_id, route_info)?;
+        let mut route = PoolRoute::try_from_slice(&route_info.data.borrow())?;
+        if nullifiers.len() > route.max_inputs as usize
+            || commitments.len() > route.max_outputs as usize
+        {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        let mut source_pool = PoolConfig::try_from_slice(&source_info.data.borrow())?;
+        let mut destination_pool = PoolConfig::try_from_slice(&destination_info.data.borrow())?;
+        if !PoolRoute::conserves_value(
+            nullifiers.len(),
+            source_pool.denomination,
+            commitments.len(),
+            destination_pool.denomination,
+        ) {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        let mut source_state = Self::load_pool_state(program_id, source, source_state_info)?;
+        let mut destination_state =
+            Self::load_pool_state(program_id, destination, destination_state_info)?;
+        if *source_roots_info.key != PoolState::find_root_history_address(source, program_id).0
+            || *destination_tree_info.key
+                != PoolState::find_tree_address(destination, program_id).0
+            || *destination_roots_info.key
+                != PoolState::find_root_history_address(destination, program_id).0
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        validation::check_program_owned(program_id, source_roots_info)?;
+        let source_roots = RootHistory::try_from_slice(&source_roots_info.data.borrow())?;
+        if !source_roots.contains(&merkle_root) {
+            return Err(FPPError::UnknownMerkleRoot.into());
+        }
+        let circuit_key = Self::load_circuit_key(program_id, route.circuit_id, circuit_key_info)?;
+        let binding = PoolRoute::transfer_binding(
+            route_info.key,
+            circuit_key.circuit_version,
+            &nullifiers,
+            &commitments,
+        )?;
+        let public_inputs = [merkle_root, binding];
+        if !Self::verify_groth16(
+            verifier_program_info,
+            &circuit_key.verifying_key,
+            &proof,
+            &public_inputs,
+        )? {
+            return Err(FPPError::InvalidProof.into());
+        }
+        
+        let now = SyscallClock.unix_timestamp()?;
+        for (nullifier_info, nullifier) in nullifier_infos.iter().zip(nullifiers.iter()) {
+            Self::spend_pool_nullifier(
+                program_id,
+                source,
+                submitter_info,
+                nullifier_info,
+                system_program_info,
+                nullifier,
+                now,
+            )?;
+        }
+        
+        validation::check_program_owned(program_id, destination_tree_info)?;
+        let mut tree_data = destination_tree_info.try_borrow_mut_data()?;
+        let tree = CommitmentTree::load_mut(&mut tree_data)?;
+        let first_leaf_index = tree.next_index;
+        for commitment in commitments.iter() {
+            tree.insert(*commitment)?;
+        }
+        let merkle_root = tree.current_root();
+        let mut destination_roots =
+            RootHistory::try_from_slice(&destination_roots_info.data.borrow())?;
+        destination_roots.push(merkle_root);
+        destination_roots.serialize(&mut &mut destination_roots_info.data.borrow_mut()[..])?;
+        
+        source_pool.total_points = source_pool.total_points.saturating_sub(nullifiers.len() as u64);
+        source_pool.serialize(&mut &mut source_info.data.borrow_mut()[..])?;
+        destination_pool.total_points += commitments.len() as u64;
+        destination_pool.serialize(&mut &mut destination_info.data.borrow_mut()[..])?;
+        source_state.withdrawal_count += nullifiers.len() as u64;
+        source_state.serialize(&mut &mut source_state_info.data.borrow_mut()[..])?;
+        destination_state.deposit_count += commitments.len() as u64;
+        destination_state.serialize(&mut &mut destination_state_info.data.borrow_mut()[..])?;
+        route.transfer_count += 1;
+        route.serialize(&mut &mut route_info.data.borrow_mut()[..])?;
+        
+        FPPEvent::PoolTransfer {
+            source: *source,
+            destination: *destination,
+            nullifiers,
+            commitments,
+            first_leaf_index,
+            merkle_root,
+        }
+        .emit();
+        Ok(())
+    }
+    
+    pub fn process_set_commitment_validation(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        required: bool,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        protocol_state.require_commitment_openings = required;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        
+        msg!("Commitment opening proofs required: {}", required);
+        Ok(())
+    }
+    
+    pub fn process_open_withdrawal_batch(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        batch_id: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_info = next_account_info(account_info_iter)?;
+        let batch_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !user_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        let (batch_address, batch_bump) =
+            WithdrawalBatch::find_address(user_info.key, batch_id, program_id);
+        if *batch_info.key != batch_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let now = SyscallClock.unix_timestamp()?;
+        let rent = Rent::get()?;
+        Self::create_pda_account(
+            program_id,
+            user_info,
+            batch_info,
+            system_program_info,
+            &rent,
+            WithdrawalBatch::LEN,
+            &[
+                WithdrawalBatch::SEED_PREFIX,
+                user_info.key.as_ref(),
+                &batch_id.to_le_bytes(),
+                &[batch_bump],
+            ],
+        )?;
+        
+        let batch = WithdrawalBatch {
+            version: WithdrawalBatch::VERSION,
+            is_initialized: true,
+            requester: *user_info.key,
+            batch_id,
+            point_count: 0,
+            amount: 0,
+            opened_at: now,
+            points_hash: [0u8; 32],
+            mint: Pubkey::default(),
+        };
+        batch.serialize(&mut &mut batch_info.data.borrow_mut()[..])?;
+        
+        msg!("Withdrawal batch {} opened", batch_id);
+        Ok(())
+    }
+    
+    pub fn process_append_to_withdrawal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        batch_id: u64,
+        point_ids: Vec<Pubkey>,
+        nullifiers: Vec<[u8; 32]>,
+        spend_signature: Vec<u8>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_info = next_account_info(account_info_iter)?;
+        let batch_info = next_account_info(account_info_iter)?;
+        
+        if !user_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if point_ids.is_empty()
+            || point_ids.len() > WithdrawalBatch::MAX_POINTS_PER_APPEND
+            || nullifiers.len() != point_ids.len()
+        {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        let mut batch = Self::load_withdrawal_batch(program_id, user_info, batch_info, batch_id)?;
+        
+        let point_infos = Self::next_point_accounts(account_info_iter, &point_ids)?;
+        let nullifier_infos = nullifiers
+            .iter()
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let nullifier_filter_info = next_account_info(account_info_iter)?;
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state_data = protocol_state_info.try_borrow_data()?;
+        let protocol_state = ProtocolState::load(&protocol_state_data)?;
+        
+        let clock = SyscallClock.clock()?;
+        let now = clock.unix_timestamp;
+        
+        // The nullifiers belong to the request the batch finalizes into, keyed by the batch id
+        let (withdrawal_request, _) =
+            pda::find_withdrawal_request(user_info.key, batch_id, program_id);
+        Self::check_spend_signature(
+            program_id,
+            &point_infos,
+            &nullifiers,
+            &spend_signature,
+            &WithdrawalRequest::spend_message(&withdrawal_request),
+        )?;
+        let (amount, mint) = Self::retire_points(
+            program_id,
+            &point_infos,
+            Some(user_info.key),
+            &clock,
+            protocol_state.min_exit_slots,
+        )?;
+        Self::consume_nullifiers(
+            program_id,
+            user_info,
+            &nullifier_infos,
+            nullifier_filter_info,
+            account_info_iter.as_slice(),
+            system_program_info,
+            &nullifiers,
+            &withdrawal_request,
+            now,
+        )?;
+        
+        // A batch pays out in a single mint, fixed by its first append
+        if batch.point_count == 0 {
+            batch.mint = mint;
+        } else if batch.mint != mint {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        for (point, nullifier) in point_ids.iter().zip(nullifiers.iter()) {
+            batch.points_hash =
+                WithdrawalRequest::chain_points_hash(&batch.points_hash, point, nullifier);
+        }
+        batch.point_count = batch
+            .point_count
+            .checked_add(point_ids.len() as u32)
+            .ok_or(FPPError::InvalidAmount)?;
+        batch.amount = batch.amount.checked_add(amount).ok_or(FPPError::InvalidAmount)?;
+        batch.serialize(&mut &mut batch_info.data.borrow_mut()[..])?;
+        
+        msg!("Appended {} points to withdrawal batch {}", point_ids.len(), batch_id);
+        Ok(())
+    }
+    
+    pub fn process_finalize_withdrawal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        batch_id: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let batch_info = next_account_info(account_info_iter)?;
+        let withdrawal_request_info = next_account_info(account_info_iter)?;
+        let user_activity_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let reward_account_info = next_account_info(account_info_iter)?;
+        
+        if !user_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        let batch = Self::load_withdrawal_batch(program_id, user_info, batch_info, batch_id)?;
+        if batch.point_count == 0 {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        // The batch id doubles as the request's nonce
+        Self::create_withdrawal_request(
+            program_id,
+            user_info.key,
+            user_info,
+            withdrawal_request_info,
+            system_program_info,
+            batch_id,
+        )?;
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_WITHDRAWALS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        let now = SyscallClock.unix_timestamp()?;
+        
+        let withdrawal_request = WithdrawalRequest {
+            version: WithdrawalRequest::VERSION,
+            is_initialized: true,
+            requester: *user_info.key,
+            amount: batch.amount,
+            request_time: now,
+            unlock_time: now + protocol_state.withdrawal_delay,
+            completed: false,
+            cancelled: false,
+            point_count: batch.point_count,
+            points_hash: batch.points_hash,
+            compliance_root: [0u8; 32],
+            mint: batch.mint,
+            nonce: batch_id,
+            relayer: Pubkey::default(),
+            relayer_fee: 0,
+        };
+        withdrawal_request.serialize(&mut &mut withdrawal_request_info.data.borrow_mut()[..])?;
+        
+        protocol_state.total_points = protocol_state
+            .total_points
+            .checked_sub(batch.point_count as u64)
+            .ok_or(FPPError::InvalidAmount)?;
+        
+        let mut activity = Self::load_user_activity(
+            program_id,
+            user_info.key,
+            user_info,
+            user_activity_info,
+            system_program_info,
+            now,
+        )?;
+        activity.record_withdrawal(&protocol_state.rate_limits, now, batch.amount)?;
+        activity.serialize(&mut &mut user_activity_info.data.borrow_mut()[..])?;
+        Self::record_reward_withdrawal(
+            program_id,
+            user_info.key,
+            reward_account_info,
+            &protocol_state.rewards,
+            now,
+            batch.amount,
+        )?;
+        
+        // Close the batch and refund its rent
+        let batch_lamports = batch_info.lamports();
+        **batch_info.try_borrow_mut_lamports()? = 0;
+        **user_info.try_borrow_mut_lamports()? = user_info
+            .lamports()
+            .checked_add(batch_lamports)
+            .ok_or(FPPError::InvalidAmount)?;
+        batch_info.data.borrow_mut().fill(0);
+        
+        FPPEvent::WithdrawalRequested {
+            requester: *user_info.key,
+            request: *withdrawal_request_info.key,
+            nonce: batch_id,
+            mint: batch.mint,
+            amount: batch.amount,
+            point_count: batch.point_count,
+            unlock_time: withdrawal_request.unlock_time,
+        }
+        .emit();
+        Ok(())
+    }
+    
+    pub fn process_propose_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        new_authority: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let previous = protocol_state.pending_authority;
+        protocol_state.pending_authority = new_authority;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        Self::log_admin_action(
+            program_id,
+            &protocol_state,
+            account_info_iter.as_slice(),
+            authority_info.key,
+            AuditAction::ProposeAuthority,
+            previous.to_bytes(),
+            new_authority.to_bytes(),
+        )?;
+        
+        if new_authority == Pubkey::default() {
+            msg!("Authority proposal withdrawn");
+        } else {
+            msg!("Authority transfer to {} proposed", new_authority);
+        }
+        Ok(())
+    }
+    
+    pub fn process_accept_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let new_authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !new_authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.pending_authority == Pubkey::default()
+            || protocol_state.pending_authority != *new_authority_info.key
+        {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let previous = protocol_state.authority;
+        protocol_state.authority = protocol_state.pending_authority;
+        protocol_state.pending_authority = Pubkey::default();
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        Self::log_admin_action(
+            program_id,
+            &protocol_state,
+            account_info_iter.as_slice(),
+            new_authority_info.key,
+            AuditAction::AcceptAuthority,
+            previous.to_bytes(),
+            protocol_state.authority.to_bytes(),
+        )?;
+        
+        msg!("Authority transferred from {} to {}", previous, protocol_state.authority);
+        Ok(())
+    }
+    
+    pub fn process_set_guardian(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        guardian: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let previous = protocol_state.guardian;
+        protocol_state.guardian = guardian;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        Self::log_admin_action(
+            program_id,
+            &protocol_state,
+            account_info_iter.as_slice(),
+            authority_info.key,
+            AuditAction::SetGuardian,
+            previous.to_bytes(),
+            guardian.to_bytes(),
+        )?;
+        
+        msg!("Guardian set to {}", guardian);
+        Ok(())
+    }
+    
+    pub fn process_set_fee_receiver(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        fee_receiver: Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let previous = protocol_state.fee_receiver;
+        protocol_state.fee_receiver = fee_receiver;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        Self::log_admin_action(
+            program_id,
+            &protocol_state,
+            account_info_iter.as_slice(),
+            authority_info.key,
+            AuditAction::SetFeeReceiver,
+            previous.to_bytes(),
+            fee_receiver.to_bytes(),
+        )?;
+        
+        msg!("Fee receiver set to {}", fee_receiver);
+        Ok(())
+    }
+    
+    pub fn process_collect_fees(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let treasury_token_info = next_account_info(account_info_iter)?;
+        let treasury_authority_info = next_account_info(account_info_iter)?;
+        let receiver_token_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let supported_mint_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let mut entry =
+            Self::load_mint_entry(program_id, &protocol_state, mint_info, supported_mint_info)?;
+        
+        // Fees may only go to the fee receiver
+        let receiver_token = token::unpack_account(receiver_token_info)?;
+        if receiver_token.owner != protocol_state.fee_receiver
+            || receiver_token.mint != entry.mint
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let treasury_token =
+            validation::check_treasury_token_account(&protocol_state, &entry, treasury_token_info)?;
+        let mint = token::unpack_mint(mint_info)?;
+        let held = decimals::to_base_amount(treasury_token.amount, mint.decimals)
+            .ok_or(FPPError::InvalidAmount)?;
+        let amount = entry.total_fees.min(held);
+        if amount == 0 {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        Self::transfer_from_treasury(
+            program_id,
+            &protocol_state,
+            &entry,
+            treasury_token_info,
+            receiver_token_info,
+            treasury_authority_info,
+            token_program_info,
+            mint_info,
+            amount,
+        )?;
+        
+        entry.total_fees -= amount;
+        Self::store_mint_entry(&mut protocol_state, &entry, supported_mint_info)?;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        
+        msg!(
+            "Collected {} of mint {} in fees, {} still accrued",
+            amount,
+            entry.mint,
+            entry.total_fees
+        );
+        Ok(())
+    }
+    
+    /// Choose the lending reserve a mint's treasury may be lent to, and how much of it
+    ///
+    /// The reserve can only change while nothing is lent to the current one.
+    pub fn process_set_treasury_deployment(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        reserve: ReserveKeys,
+        max_deployed_rate: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let deployment_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let supported_mint_info = next_account_info(account_info_iter)?;
+        let collateral_token_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state_data = protocol_state_info.try_borrow_data()?;
+        let protocol_state = ProtocolState::load(&protocol_state_data)?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if max_deployed_rate > TreasuryDeployment::MAX_DEPLOYED_RATE {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        if !lending::is_lending_program(&reserve.lending_program) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let entry =
+            Self::load_mint_entry(program_id, protocol_state, mint_info, supported_mint_info)?;
+        
+        // Collateral is held by the treasury authority, like the liquidity it stands for
+        let collateral_token = token::unpack_account(collateral_token_info)?;
+        if collateral_token.owner != protocol_state.treasury
+            || collateral_token.mint != reserve.collateral_mint
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let (deployment_address, deployment_bump) =
+            TreasuryDeployment::find_address(&entry.mint, program_id);
+        if *deployment_info.key != deployment_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let deployment = if deployment_info.owner != program_id {
+            Self::create_pda_account(
+                program_id,
+                payer_info,
+                deployment_info,
+                system_program_info,
+                &Rent::get()?,
+                TreasuryDeployment::LEN,
+                &[TreasuryDeployment::SEED_PREFIX, entry.mint.as_ref(), &[deployment_bump]],
+            )?;
+            TreasuryDeployment {
+                version: TreasuryDeployment::VERSION,
+                is_initialized: true,
+                mint: entry.mint,
+                reserve,
+                collateral_token: *collateral_token_info.key,
+                max_deployed_rate,
+                deployed: 0,
+                total_yield: 0,
+                total_loss: 0,
+            }
+        } else {
+            let current = TreasuryDeployment::try_from_slice(&deployment_info.data.borrow())?;
+            let moved = current.reserve != reserve
+                || current.collateral_token != *collateral_token_info.key;
+            if moved && current.deployed != 0 {
+                return Err(FPPError::AccountInUse.into());
+            }
+            TreasuryDeployment {
+                reserve,
+                collateral_token: *collateral_token_info.key,
+                max_deployed_rate,
+                ..current
+            }
+        };
+        deployment.serialize(&mut &mut deployment_info.data.borrow_mut()[..])?;
+        
+        msg!(
+            "Treasury of mint {} may lend up to {} bps to reserve {}",
+            entry.mint,
+            max_deployed_rate,
+            reserve.reserve
+        );
+        Ok(())
+    }
+    
+    /// Lend `amount` base units of a mint's treasury to its configured reserve
+    ///
+    /// The treasury keeps at least `10000 - max_deployed_rate` basis points of what it
+    /// holds and has lent, so withdrawals can still be paid out of it.
+    pub fn process_deploy_treasury(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let (mut deployment, treasury) =
+            Self::next_treasury_deployment(program_id, account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state_data = protocol_state_info.try_borrow_data()?;
+        let protocol_state = ProtocolState::load(&protocol_state_data)?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let entry = Self::load_mint_entry(
+            program_id,
+            protocol_state,
+            treasury.mint,
+            treasury.supported_mint,
+        )?;
+        let bump = treasury.check(program_id, protocol_state, &entry, &deployment)?;
+        let mint = token::unpack_mint(treasury.mint)?;
+        
+        let treasury_token =
+            validation::check_treasury_token_account(protocol_state, &entry, treasury.token)?;
+        let held = treasury_token.amount;
+        let liquid =
+            decimals::to_base_amount(held, mint.decimals).ok_or(FPPError::InvalidAmount)?;
+        let token_amount =
+            decimals::to_token_amount(amount, mint.decimals).ok_or(FPPError::InvalidAmount)?;
+        let deployed = deployment.deployed.checked_add(amount).ok_or(FPPError::InvalidAmount)?;
+        if amount == 0 || token_amount > held {
+            return Err(FPPError::InsufficientBalance.into());
+        }
+        if !deployment.within_limit(liquid.saturating_sub(amount), deployed) {
+            return Err(FPPError::DeploymentLimitExceeded.into());
+        }
+        
+        lending::deposit_reserve_liquidity(
+            &treasury.reserve,
+            treasury.token,
+            treasury.collateral,
+            treasury.authority,
+            treasury.clock,
+            treasury.token_program,
+            token_amount,
+            &[&[ProtocolState::TREASURY_SEED, &[bump]]],
+        )?;
+        
+        deployment.deployed = deployed;
+        deployment.serialize(&mut &mut treasury.deployment.data.borrow_mut()[..])?;
+        
+        msg!("Lent {} of mint {}, {} lent in total", amount, entry.mint, deployed);
+        Ok(())
+    }
+    
+    /// Redeem `collateral_amount` of a mint's reserve collateral back into its treasury
+    ///
+    /// The guardian may recall as well as the authority, so withdrawal liquidity can be
+    /// restored without waiting on a multisig. Anything received above the principal
+    /// retired becomes protocol fees.
+    pub fn process_recall_treasury(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        collateral_amount: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let caller_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let (mut deployment, treasury) =
+            Self::next_treasury_deployment(program_id, account_info_iter)?;
+        
+        if !caller_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        if protocol_state.authority != *caller_info.key
+            && protocol_state.guardian != *caller_info.key
+        {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let mut entry = Self::load_mint_entry(
+            program_id,
+            protocol_state,
+            treasury.mint,
+            treasury.supported_mint,
+        )?;
+        let bump = treasury.check(program_id, protocol_state, &entry, &deployment)?;
+        let mint = token::unpack_mint(treasury.mint)?;
+        
+        let collateral_held = token::unpack_account(treasury.collateral)?.amount;
+        if collateral_amount == 0 || collateral_amount > collateral_held {
+            return Err(FPPError::InsufficientBalance.into());
+        }
+        let treasury_token =
+            validation::check_treasury_token_account(protocol_state, &entry, treasury.token)?;
+        let before = treasury_token.amount;
+        
+        lending::redeem_reserve_collateral(
+            &treasury.reserve,
+            treasury.collateral,
+            treasury.token,
+            treasury.authority,
+            treasury.clock,
+            treasury.token_program,
+            collateral_amount,
+            &[&[ProtocolState::TREASURY_SEED, &[bump]]],
+        )?;
+        
+        // Measure what actually arrived rather than trusting the reserve's exchange rate
+        let after = token::unpack_account(treasury.token)?.amount;
+        let received = decimals::to_base_amount(after.saturating_sub(before), mint.decimals)
+            .ok_or(FPPError::InvalidAmount)?;
+        let principal = deployment.principal_share(collateral_amount, collateral_held);
+        deployment.deployed -= principal;
+        if received >= principal {
+            let gain = received - principal;
+            deployment.total_yield =
+                deployment.total_yield.checked_add(gain).ok_or(FPPError::InvalidAmount)?;
+            entry.total_fees = entry.total_fees.checked_add(gain).ok_or(FPPError::InvalidAmount)?;
+            Self::store_mint_entry(protocol_state, &entry, treasury.supported_mint)?;
+        } else {
+            deployment.total_loss = deployment
+                .total_loss
+                .checked_add(principal - received)
+                .ok_or(FPPError::InvalidAmount)?;
+        }
+        deployment.serialize(&mut &mut treasury.deployment.data.borrow_mut()[..])?;
+        
+        msg!(
+            "Recalled {} of mint {} for {} principal, {} still lent",
+            received,
+            entry.mint,
+            principal,
+            deployment.deployed
+        );
+        Ok(())
+    }
+    
+    /// Set the reward rate and the vault rewards are paid from
+    /// 
+    /// The vault can only change while the pool it holds is empty.
+    pub fn process_set_reward_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        rate: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let (rewards_authority, _) = ProtocolState::find_rewards_authority(program_id);
+        token::unpack_mint(mint_info)?;
+        let vault = token::unpack_account(vault_info)?;
+        if vault.owner != rewards_authority || vault.mint != *mint_info.key {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let rewards = &mut protocol_state.rewards;
+        if rewards.vault != *vault_info.key && rewards.pool_balance != 0 {
+            return Err(FPPError::AccountInUse.into());
+        }
+        rewards.rate = rate;
+        rewards.mint = *mint_info.key;
+        rewards.vault = *vault_info.key;
+        
+        msg!("Rewards accrue at {} per token per day, paid from {}", rate, vault_info.key);
+        Ok(())
+    }
+    
+    /// Add tokens to the rewards pool; anyone may fund it
+    pub fn process_fund_rewards(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        amount: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let funder_info = next_account_info(account_info_iter)?;
+        let funder_token_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        
+        if !funder_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if amount == 0 {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        let rewards = &mut protocol_state.rewards;
+        if !rewards.is_configured()
+            || *vault_info.key != rewards.vault
+            || *mint_info.key != rewards.mint
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        validation::check_token_program(token_program_info, vault_info.owner)?;
+        
+        // Count what the vault received, net of any Token-2022 transfer fee
+        let before = token::unpack_account(vault_info)?.amount;
+        token::transfer(
+            token_program_info,
+            funder_token_info,
+            mint_info,
+            vault_info,
+            funder_info,
+            amount,
+            token::unpack_mint(mint_info)?.decimals,
+            &[],
+        )?;
+        let received = token::unpack_account(vault_info)?.amount.saturating_sub(before);
+        rewards.pool_balance =
+            rewards.pool_balance.checked_add(received).ok_or(FPPError::InvalidAmount)?;
+        
+        msg!("Rewards pool funded with {}, now {}", received, rewards.pool_balance);
+        Ok(())
+    }
+    
+    /// Pay out a user's accrued rewards, as far as the pool covers them
+    pub fn process_claim_rewards(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let user_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let reward_account_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
+        let rewards_authority_info = next_account_info(account_info_iter)?;
+        let user_token_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        
+        if !user_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        let config = &mut protocol_state.rewards;
+        if !config.is_configured()
+            || *vault_info.key != config.vault
+            || *mint_info.key != config.mint
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let (rewards_authority, bump) = ProtocolState::find_rewards_authority(program_id);
+        if *rewards_authority_info.key != rewards_authority {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        validation::check_token_program(token_program_info, vault_info.owner)?;
+        
+        validation::check_program_owned(program_id, reward_account_info)?;
+        if *reward_account_info.key != RewardAccount::find_address(user_info.key, program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut rewards = RewardAccount::try_from_slice(&reward_account_info.data.borrow())?;
+        let now = SyscallClock.unix_timestamp()?;
+        rewards.accrue(config, now);
+        
+        // What the pool can't cover stays accrued for a later claim
+        let amount = rewards.accrued.min(config.pool_balance);
+        if amount == 0 {
+            return Err(FPPError::InsufficientBalance.into());
+        }
+        token::transfer(
+            token_program_info,
+            vault_info,
+            mint_info,
+            user_token_info,
+            rewards_authority_info,
+            amount,
+            token::unpack_mint(mint_info)?.decimals,
+            &[&[ProtocolState::REWARDS_SEED, &[bump]]],
+        )?;
+        
+        rewards.accrued -= amount;
+        rewards.total_claimed = rewards.total_claimed.saturating_add(amount);
+        rewards.serialize(&mut &mut reward_account_info.data.borrow_mut()[..])?;
+        config.pool_balance -= amount;
+        config.total_claimed = config.total_claimed.saturating_add(amount);
+        
+        msg!("Claimed {} in rewards, {} still accrued", amount, rewards.accrued);
+        Ok(())
+    }
+    
+    pub fn process_set_referral_fee_share(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        share: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if share > ProtocolState::MAX_REFERRAL_FEE_SHARE {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        let previous = protocol_state.referral_fee_share;
+        protocol_state.referral_fee_share = share;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        Self::log_admin_action(
+            program_id,
+            &protocol_state,
+            account_info_iter.as_slice(),
+            authority_info.key,
+            AuditAction::SetReferralFeeShare,
+            AuditEntry::value(&previous.to_le_bytes()),
+            AuditEntry::value(&share.to_le_bytes()),
+        )?;
+        
+        msg!("Referral fee share set to {} bps", share);
+        Ok(())
+    }
+    
+    pub fn process_update_fee_tiers(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        tiers: [FeeTier; MAX_FEE_TIERS],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if !FeeTier::is_valid_table(&tiers) {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        let previous = protocol_state.fee_tiers;
+        protocol_state.fee_tiers = tiers;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        Self::log_admin_action(
+            program_id,
+            &protocol_state,
+            account_info_iter.as_slice(),
+            authority_info.key,
+            AuditAction::UpdateFeeTiers,
+            AuditEntry::value(&previous.try_to_vec()?),
+            AuditEntry::value(&tiers.try_to_vec()?),
+        )?;
+        
+        msg!("Fee tiers updated: {} in use", tiers.iter().filter(|tier| tier.is_used()).count());
+        Ok(())
+    }
+    
+    /// Cap the value held by the protocol and what one user may deposit; zero lifts a cap
+    pub fn process_set_deposit_caps(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        max_tvl: u64,
+        max_user_deposit: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        // A user cap above the global one could never be reached
+        if max_tvl != 0 && max_user_deposit > max_tvl {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        // Lowering `max_tvl` below `value_locked` only stops new deposits
+        protocol_state.max_tvl = max_tvl;
+        protocol_state.max_user_deposit = max_user_deposit;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        
+        msg!("Deposit caps set: {} total, {} per user", max_tvl, max_user_deposit);
+        Ok(())
+    }
+    
+    /// Set the withdrawal delay and point lock duration (admin only)
+    pub fn process_update_timelocks(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        withdrawal_delay: i64,
+        point_lock_duration: i64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let delays = ProtocolState::MIN_WITHDRAWAL_DELAY..=ProtocolState::MAX_WITHDRAWAL_DELAY;
+        if !delays.contains(&withdrawal_delay)
+            || !(0..=ProtocolState::MAX_POINT_LOCK_DURATION).contains(&point_lock_duration)
+        {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        // Requests already open keep the unlock time they were given
+        protocol_state.withdrawal_delay = withdrawal_delay;
+        protocol_state.point_lock_duration = point_lock_duration;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        
+        msg!(
+            "Timelocks set: {}s withdrawal delay, {}s point lock",
+            withdrawal_delay,
+            point_lock_duration
+        );
+        Ok(())
+    }
+    
+    /// Set how points accrete and decay (admin only)
+    pub fn process_set_mass_dynamics(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        dynamics: MassDynamics,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        if !dynamics.is_valid() {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        // New rates apply from each point's next `UpdateMass`, to the time since its last one
+        protocol_state.mass_dynamics = dynamics;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        
+        msg!(
+            "Mass dynamics set: {} bps/year accretion after {}s, {} bps/year decay after {}s",
+            dynamics.accretion_rate,
+            dynamics.maturity,
+            dynamics.decay_rate,
+            dynamics.dormancy
+        );
+        Ok(())
+    }
+    
+    /// Settle accretion and decay on active points up to now (permissionless)
+    ///
+    /// Accretion is paid out of the uncollected fees of the point's mint and stops when they
+    /// run out; decay goes back into them.
+    pub fn process_update_mass(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let cranker_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let pairs: Vec<_> = account_info_iter.collect();
+        
+        if !cranker_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if pairs.is_empty() || pairs.len() % 2 != 0 {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        let dynamics = protocol_state.mass_dynamics;
+        let now = SyscallClock.unix_timestamp()?;
+        
+        for pair in pairs.chunks(2) {
+            let (point_info, supported_mint_info) = (pair[0], pair[1]);
+            
+            // Points from before layout 3 are upgraded here, at the cranker's expense
+            validation::check_program_owned(program_id, point_info)?;
+            Self::migrate_account::<FloatingPoint>((
+                point_info,
+                cranker_info,
+                system_program_info,
+            ))?;
+            let mut point = Self::load_point(program_id, point_info)?;
+            if !point.is_initialized || !point.is_active {
+                return Err(FPPError::PointNotActive.into());
+            }
+            
+            if *supported_mint_info.key != SupportedMint::find_address(&point.mint, program_id).0
+            {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            let mut entry = if point.mint == protocol_state.usdt_mint {
+                SupportedMint::primary(protocol_state)
+            } else {
+                validation::check_program_owned(program_id, supported_mint_info)?;
+                SupportedMint::try_from_slice(&supported_mint_info.data.borrow())?
+            };
+            
+            let principal = point.principal().ok_or(FPPError::InvalidAmount)?;
+            let value = point.value().ok_or(FPPError::InvalidAmount)?;
+            let from = point.mass_updated_at.max(point.created_at);
+            let (gain, loss) = dynamics.change(principal, point.created_at, from, now);
+            let gain = gain.min(entry.total_fees);
+            let loss = loss.min(value);
+            
+            point.accrued = i64::try_from(gain)
+                .ok()
+                .and_then(|gain| point.accrued.checked_add(gain))
+                .and_then(|accrued| accrued.checked_sub(i64::try_from(loss).ok()?))
+                .ok_or(FPPError::InvalidAmount)?;
+            point.mass_updated_at = now;
+            point.serialize(&mut &mut point_info.data.borrow_mut()[..])?;
+            
+            entry.total_fees = entry
+                .total_fees
+                .checked_sub(gain)
+                .and_then(|fees| fees.checked_add(loss))
+                .ok_or(FPPError::InvalidAmount)?;
+            Self::store_mint_entry(protocol_state, &entry, supported_mint_info)?;
+            protocol_state.value_locked = protocol_state.value_locked.saturating_add(gain);
+            protocol_state.release_value(loss);
+        }
+        
+        msg!("Updated mass of {} points", pairs.len() / 2);
+        Ok(())
+    }
+    
+    /// Pay a referrer the fees its referrals earned in one mint, from the treasury
+    pub fn process_claim_referral_fees(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let referrer_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let referral_info = next_account_info(account_info_iter)?;
+        let treasury_token_info = next_account_info(account_info_iter)?;
+        let treasury_authority_info = next_account_info(account_info_iter)?;
+        let referrer_token_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let supported_mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        
+        if !referrer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state_data = protocol_state_info.try_borrow_data()?;
+        let protocol_state = ProtocolState::load(&protocol_state_data)?;
+        let entry =
+            Self::load_mint_entry(program_id, protocol_state, mint_info, supported_mint_info)?;
+        
+        validation::check_program_owned(program_id, referral_info)?;
+        let (address, _) =
+            ReferralAccount::find_address(referrer_info.key, &entry.mint, program_id);
+        if *referral_info.key != address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut referral = ReferralAccount::try_from_slice(&referral_info.data.borrow())?;
+        let amount = referral.accrued_fees;
+        if amount == 0 {
+            return Err(FPPError::InsufficientBalance.into());
+        }
+        
+        Self::transfer_from_treasury(
+            program_id,
+            protocol_state,
+            &entry,
+            treasury_token_info,
+            referrer_token_info,
+            treasury_authority_info,
+            token_program_info,
+            mint_info,
+            amount,
+        )?;
+        
+        referral.accrued_fees = 0;
+        referral.total_claimed = referral.total_claimed.saturating_add(amount);
+        referral.serialize(&mut &mut referral_info.data.borrow_mut()[..])?;
+        
+        msg!("Paid {} of mint {} in referral fees", amount, entry.mint);
+        Ok(())
+    }
+    
+    pub fn process_record_security_event(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        kind: SecurityEventKind,
+        severity: SecuritySeverity,
+        subject: Pubkey,
+        message: String,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let guardian_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let event_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !guardian_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.guardian != *guardian_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if message.is_empty() || message.len() > SecurityEvent::MAX_MESSAGE_LEN {
+            return Err(FPPError::InvalidSecurityEvent.into());
+        }
+        
+        let index = protocol_state.security_event_count;
+        let (event_address, event_bump) = SecurityEvent::find_address(index, program_id);
+        if *event_info.key != event_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let now = SyscallClock.unix_timestamp()?;
+        let rent = Rent::get()?;
+        Self::create_pda_account(
+            program_id,
+            guardian_info,
+            event_info,
+            system_program_info,
+            &rent,
+            SecurityEvent::space(message.len()),
+            &[SecurityEvent::SEED_PREFIX, &index.to_le_bytes(), &[event_bump]],
+        )?;
+        
+        let event = SecurityEvent {
+            version: SecurityEvent::VERSION,
+            is_initialized: true,
+            index,
+            kind,
+            severity,
+            subject,
+            guardian: *guardian_info.key,
+            recorded_at: now,
+            resolved_at: 0,
+            message,
+        };
+        event.serialize(&mut &mut event_info.data.borrow_mut()[..])?;
+        
+        protocol_state.security_event_count = index.checked_add(1).ok_or(FPPError::InvalidAmount)?;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        
+        msg!("Security event {} recorded: {:?} ({:?})", index, kind, severity);
+        Ok(())
+    }
+    
+    pub fn process_resolve_security_event(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        index: u64,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let guardian_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let event_info = next_account_info(account_info_iter)?;
+        
+        if !guardian_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.guardian != *guardian_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if event_info.owner != program_id
+            || *event_info.key != SecurityEvent::find_address(index, program_id).0
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut event = SecurityEvent::try_from_slice(&event_info.data.borrow())?;
+        if !event.is_active() {
+            return Err(FPPError::InvalidSecurityEvent.into());
+        }
+        
+        event.resolved_at = SyscallClock.unix_timestamp()?;
+        event.serialize(&mut &mut event_info.data.borrow_mut()[..])?;
+        
+        msg!("Security event {} resolved", index);
+        Ok(())
+    }
+    
+    pub fn process_create_audit_log(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let audit_log_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer || !payer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let (audit_log_address, audit_log_bump) = AuditLog::find_address(program_id);
+        if *audit_log_info.key != audit_log_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            audit_log_info,
+            system_program_info,
+            &Rent::get()?,
+            AuditLog::LEN,
+            &[AuditLog::SEED, &[audit_log_bump]],
+        )?;
+        let audit_log = AuditLog {
+            version: AuditLog::VERSION,
+            is_initialized: true,
+            count: 0,
+        };
+        audit_log.serialize(&mut &mut audit_log_info.data.borrow_mut()[..])?;
+        
+        protocol_state.has_audit_log = true;
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        
+        msg!("Audit log created at {}", audit_log_address);
+        Ok(())
+    }
+    
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_split_point(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        nullifier: [u8; 32],
+        output_commitments: Vec<[u8; 32]>,
+        output_masses: Vec<u64>,
+        opening_proofs: Vec<OpeningProof>,
+        spend_signature: Vec<u8>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let holder_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let point_info = next_account_info(account_info_iter)?;
+        let nullifier_info = next_account_info(account_info_iter)?;
+        let output_infos = output_commitments
+            .iter()
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let commitment_tree_info = next_account_info(account_info_iter)?;
+        let root_history_info = next_account_info(account_info_iter)?;
+        let nullifier_filter_info = next_account_info(account_info_iter)?;
+        
+        if !holder_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if output_commitments.len() < 2
+            || output_commitments.len() > FloatingPoint::MAX_SPLIT_OUTPUTS
+            || output_masses.len() != output_commitments.len()
+        {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_PRIVACY_PAYMENTS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if *commitment_tree_info.key != CommitmentTree::find_address(program_id).0
+            || commitment_tree_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if *root_history_info.key != RootHistory::find_address(program_id).0
+            || root_history_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let mut point = Self::load_point(program_id, point_info)?;
+        if !point.is_initialized || !point.is_active {
+            return Err(FPPError::PointNotActive.into());
+        }
+        if point.creator != *holder_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        Self::check_spend_signature(
+            program_id,
+            &[point_info],
+            &[nullifier],
+            &spend_signature,
+            &FloatingPoint::split_message(holder_info.key, &output_commitments, &output_masses),
+        )?;
+        
+        // Outputs are fresh keys, so only their public masses have to add up
+        let total_mass = output_masses
+            .iter()
+            .try_fold(0u64, |total, &mass| match mass {
+                0 => None,
+                _ => total.checked_add(mass),
+            })
+            .ok_or(FPPError::MassNotConserved)?;
+        if total_mass != point.mass {
+            return Err(FPPError::MassNotConserved.into());
+        }
+        
+        if protocol_state.require_commitment_openings
+            && opening_proofs.len() != output_commitments.len()
+        {
+            return Err(FPPError::InvalidCommitment.into());
+        }
+        if !opening_proofs.is_empty() {
+            if opening_proofs.len() != output_commitments.len() {
+                return Err(FPPError::InvalidCommitment.into());
+            }
+            for ((commitment, mass), proof) in output_commitments
+                .iter()
+                .zip(output_masses.iter())
+                .zip(opening_proofs.iter())
+            {
+                let amount = mass.checked_mul(POINT_VALUE).ok_or(FPPError::InvalidAmount)?;
+                if !opening::verify_opening(commitment, amount, holder_info.key, proof) {
+                    return Err(FPPError::InvalidCommitment.into());
+                }
+            }
+        }
+        
+        let now = SyscallClock.unix_timestamp()?;
+        Self::consume_nullifiers(
+            program_id,
+            holder_info,
+            &[nullifier_info],
+            nullifier_filter_info,
+            account_info_iter.as_slice(),
+            system_program_info,
+            &[nullifier],
+            &Pubkey::default(),
+            now,
+        )?;
+        
+        // The outputs now hold the point's mass, and what it accrued in proportion to their mass
+        let accrued = point.accrued;
+        let mut unassigned = accrued;
+        point.is_active = false;
+        point.mass = 0;
+        point.accrued = 0;
+        point.serialize(&mut &mut point_info.data.borrow_mut()[..])?;
+        
+        // Outputs inherit the split point's lock and deposit slot so splitting can't bypass them
+        let rent = Rent::get()?;
+        let mut commitment_tree_data = commitment_tree_info.try_borrow_mut_data()?;
+        let commitment_tree = CommitmentTree::load_mut(&mut commitment_tree_data)?;
+        let first_leaf_index = commitment_tree.next_index;
+        for (index, ((output_info, commitment), mass)) in output_infos
+            .iter()
+            .zip(output_commitments.iter())
+            .zip(output_masses.iter())
+            .enumerate()
+        {
+            let output_accrued = if index + 1 == output_commitments.len() {
+                unassigned
+            } else {
+                (accrued as i128 * *mass as i128 / total_mass as i128) as i64
+            };
+            unassigned -= output_accrued;
+            let (output_address, output_bump) = pda::find_point_address(commitment, program_id);
+            if *output_info.key != output_address {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            Self::create_pda_account(
+                program_id,
+                holder_info,
+                output_info,
+                system_program_info,
+                &rent,
+                FloatingPoint::LEN,
+                &[FloatingPoint::SEED_PREFIX, commitment, &[output_bump]],
+            )?;
+            let output = FloatingPoint {
+                version: FloatingPoint::VERSION,
+                is_initialized: true,
+                commitment: *commitment,
+                created_at: now,
+                mass: *mass,
+                is_active: true,
+                creator: *holder_info.key,
+                locked_until: point.locked_until.max(now + protocol_state.point_lock_duration),
+                created_slot: point.created_slot,
+                mint: point.mint,
+                rent_payer: *holder_info.key,
+                accrued: output_accrued,
+                mass_updated_at: now,
+            };
+            output.serialize(&mut &mut output_info.data.borrow_mut()[..])?;
+            
+            commitment_tree.insert(*commitment)?;
+        }
+        let merkle_root = commitment_tree.current_root();
+        
+        let mut root_history = RootHistory::try_from_slice(&root_history_info.data.borrow())?;
+        root_history.push(merkle_root);
+        root_history.serialize(&mut &mut root_history_info.data.borrow_mut()[..])?;
+        
+        protocol_state.total_points = protocol_state
+            .total_points
+            .checked_add(output_commitments.len() as u64 - 1)
+            .ok_or(FPPError::InvalidAmount)?;
+        
+        msg!("Split point of mass {} into {} points", total_mass, output_commitments.len());
+        FPPEvent::CommitmentsInserted {
+            commitments: output_commitments,
+            first_leaf_index,
+            tree_generation: commitment_tree.generation,
+            merkle_root,
+        }
+        .emit();
+        Ok(())
+    }
+    
+    pub fn process_transfer_point(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        new_owner: Pubkey,
+        new_commitment: [u8; 32],
+        nullifier: [u8; 32],
+        spend_signature: Vec<u8>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let holder_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let point_info = next_account_info(account_info_iter)?;
+        let commitment_tree_info = next_account_info(account_info_iter)?;
+        let root_history_info = next_account_info(account_info_iter)?;
+        let new_point_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let nullifier_info = next_account_info(account_info_iter)?;
+        let nullifier_filter_info = next_account_info(account_info_iter)?;
+        
+        if !holder_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state_data = protocol_state_info.try_borrow_data()?;
+        let protocol_state = ProtocolState::load(&protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_PRIVACY_PAYMENTS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if *commitment_tree_info.key != CommitmentTree::find_address(program_id).0
+            || commitment_tree_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if *root_history_info.key != RootHistory::find_address(program_id).0
+            || root_history_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let mut point = Self::load_point(program_id, point_info)?;
+        if !point.is_initialized || !point.is_active {
+            return Err(FPPError::PointNotActive.into());
+        }
+        if point.creator != *holder_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        // The old commitment leaves circulation, so its key image can't appear in a ring again
+        Self::check_spend_signature(
+            program_id,
+            &[point_info],
+            &[nullifier],
+            &spend_signature,
+            &FloatingPoint::transfer_message(holder_info.key, &new_owner, &new_commitment),
+        )?;
+        Self::consume_nullifiers(
+            program_id,
+            holder_info,
+            &[nullifier_info],
+            nullifier_filter_info,
+            account_info_iter.as_slice(),
+            system_program_info,
+            &[nullifier],
+            &Pubkey::default(),
+            SyscallClock.unix_timestamp()?,
+        )?;
+        
+        // Points live at their commitment's PDA, so the point moves and the old account closes
+        let (new_address, new_bump) = pda::find_point_address(&new_commitment, program_id);
+        if *new_point_info.key != new_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            holder_info,
+            new_point_info,
+            system_program_info,
+            &Rent::get()?,
+            FloatingPoint::LEN,
+            &[FloatingPoint::SEED_PREFIX, &new_commitment, &[new_bump]],
+        )?;
+        point.creator = new_owner;
+        point.commitment = new_commitment;
+        point.rent_payer = *holder_info.key;
+        point.serialize(&mut &mut new_point_info.data.borrow_mut()[..])?;
+        
+        let point_lamports = point_info.lamports();
+        **point_info.try_borrow_mut_lamports()? = 0;
+        **holder_info.try_borrow_mut_lamports()? = holder_info
+            .lamports()
+            .checked_add(point_lamports)
+            .ok_or(FPPError::InvalidAmount)?;
+        point_info.data.borrow_mut().fill(0);
+        
+        let mut commitment_tree_data = commitment_tree_info.try_borrow_mut_data()?;
+        let commitment_tree = CommitmentTree::load_mut(&mut commitment_tree_data)?;
+        let index = commitment_tree.insert(new_commitment)?;
+        let merkle_root = commitment_tree.current_root();
+        
+        let mut root_history = RootHistory::try_from_slice(&root_history_info.data.borrow())?;
+        root_history.push(merkle_root);
+        root_history.serialize(&mut &mut root_history_info.data.borrow_mut()[..])?;
+        
+        msg!("Point {} transferred to {} at {}", point_info.key, new_owner, new_point_info.key);
+        FPPEvent::CommitmentsInserted {
+            commitments: vec![new_commitment],
+            first_leaf_index: index,
+            tree_generation: commitment_tree.generation,
+            merkle_root,
+        }
+        .emit();
+        Ok(())
+    }
+    
+    pub fn process_create_point_tree(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let point_tree_info = next_account_info(account_info_iter)?;
+        let tree_config_info = next_account_info(account_info_iter)?;
+        let merkle_tree_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let log_wrapper_info = next_account_info(account_info_iter)?;
+        let compression_program_info = next_account_info(account_info_iter)?;
+        let bubblegum_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        Self::check_compression_programs(
+            log_wrapper_info,
+            compression_program_info,
+            bubblegum_program_info,
+        )?;
+        let (expected, bump) = PointTree::find_address(program_id);
+        if *point_tree_info.key != expected {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            point_tree_info,
+            system_program_info,
+            &Rent::get()?,
+            PointTree::LEN,
+            &[PointTree::SEED, &[bump]],
+        )?;
+        
+        // A private tree: only its creator, the point tree PDA, can mint
+        CreateTreeConfigCpiBuilder::new(bubblegum_program_info)
+            .tree_config(tree_config_info)
+            .merkle_tree(merkle_tree_info)
+            .payer(payer_info)
+            .tree_creator(point_tree_info)
+            .log_wrapper(log_wrapper_info)
+            .compression_program(compression_program_info)
+            .system_program(system_program_info)
+            .max_depth(max_depth)
+            .max_buffer_size(max_buffer_size)
+            .public(false)
+            .invoke_signed(&[&[PointTree::SEED, &[bump]]])?;
+        
+        let point_tree = PointTree {
+            version: PointTree::VERSION,
+            is_initialized: true,
+            merkle_tree: *merkle_tree_info.key,
+            compressed_points: 0,
+        };
+        point_tree.serialize(&mut &mut point_tree_info.data.borrow_mut()[..])?;
+        
+        msg!("Point tree {} created (depth {})", merkle_tree_info.key, max_depth);
+        Ok(())
+    }
+    
+    /// Take over an empty spl-account-compression tree for deposit commitments (admin only)
+    /// 
+    /// The creator allocates `merkle_tree` for the compression program beforehand, sized by
+    /// `concurrent_tree::account_len` for the requested canopy; the size is checked here so
+    /// the canopy can't silently differ from `canopy_depth`.
+    pub fn process_create_concurrent_tree(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        max_depth: u32,
+        max_buffer_size: u32,
+        canopy_depth: u32,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let concurrent_tree_info = next_account_info(account_info_iter)?;
+        let commitment_tree_info = next_account_info(account_info_iter)?;
+        let merkle_tree_info = next_account_info(account_info_iter)?;
+        let log_wrapper_info = next_account_info(account_info_iter)?;
+        let compression_program_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer || !payer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if *log_wrapper_info.key != NOOP_ID
+            || *compression_program_info.key != ACCOUNT_COMPRESSION_ID
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if !concurrent_tree::is_supported(max_depth, max_buffer_size)
+            || !concurrent_tree::is_valid_canopy(max_depth, canopy_depth)
+        {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        if merkle_tree_info.owner != &ACCOUNT_COMPRESSION_ID
+            || merkle_tree_info.data_len()
+                != concurrent_tree::account_len(max_depth, max_buffer_size, canopy_depth)
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if *commitment_tree_info.key != CommitmentTree::find_address(program_id).0
+            || commitment_tree_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let (address, bump) = ConcurrentTree::find_address(program_id);
+        if *concurrent_tree_info.key != address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            concurrent_tree_info,
+            system_program_info,
+            &Rent::get()?,
+            ConcurrentTree::LEN,
+            &[ConcurrentTree::SEED, &[bump]],
+        )?;
+        
+        // The tree PDA is the only authority that can append
+        invoke_signed(
+            &concurrent_tree::init_empty_merkle_tree(
+                merkle_tree_info.key,
+                &address,
+                max_depth,
+                max_buffer_size,
+            ),
+            &[
+                merkle_tree_info.clone(),
+                concurrent_tree_info.clone(),
+                log_wrapper_info.clone(),
+                compression_program_info.clone(),
+            ],
+            &[&[ConcurrentTree::SEED, &[bump]]],
+        )?;
+        
+        let tree = ConcurrentTree {
+            version: ConcurrentTree::VERSION,
+            is_initialized: true,
+            merkle_tree: *merkle_tree_info.key,
+            max_depth,
+            max_buffer_size,
+            canopy_depth,
+            leaf_count: 0,
+        };
+        tree.serialize(&mut &mut concurrent_tree_info.data.borrow_mut()[..])?;
+        
+        let mut commitment_tree_data = commitment_tree_info.try_borrow_mut_data()?;
+        CommitmentTree::load_mut(&mut commitment_tree_data)?.has_concurrent_tree = true;
+        
+        msg!(
+            "Concurrent tree {} created (depth {}, canopy {})",
+            merkle_tree_info.key,
+            max_depth,
+            canopy_depth
+        );
+        Ok(())
+    }
+    
+    /// Check that `commitment` sits at `leaf_index` of the concurrent tree under `root`
+    /// (permissionless)
+    /// 
+    /// `root` may be any root still in the tree's change log buffer. The trailing accounts
+    /// are the proof nodes below the canopy, from the leaf's sibling upwards.
+    pub fn process_verify_concurrent_commitment(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        root: [u8; 32],
+        commitment: [u8; 32],
+        leaf_index: u32,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let concurrent_tree_info = next_account_info(account_info_iter)?;
+        let merkle_tree_info = next_account_info(account_info_iter)?;
+        let compression_program_info = next_account_info(account_info_iter)?;
+        let proof_infos = account_info_iter.as_slice();
+        
+        if *compression_program_info.key != ACCOUNT_COMPRESSION_ID {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if *concurrent_tree_info.key != ConcurrentTree::find_address(program_id).0
+            || concurrent_tree_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let tree = ConcurrentTree::try_from_slice(&concurrent_tree_info.data.borrow())?;
+        if tree.merkle_tree != *merkle_tree_info.key {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if proof_infos.len() != concurrent_tree::proof_len(tree.max_depth, tree.canopy_depth) {
+            return Err(FPPError::InvalidProof.into());
+        }
+        
+        let proof = proof_infos
+            .iter()
+            .map(|info| info.key.to_bytes())
+            .collect::<Vec<_>>();
+        let mut cpi_accounts = vec![merkle_tree_info.clone(), compression_program_info.clone()];
+        cpi_accounts.extend(proof_infos.iter().cloned());
+        invoke(
+            &concurrent_tree::verify_leaf(
+                merkle_tree_info.key,
+                &root,
+                &commitment,
+                leaf_index,
+                &proof,
+            ),
+            &cpi_accounts,
+        )
+        .map_err(|_| FPPError::InvalidProof)?;
+        
+        msg!("Commitment verified at leaf {}", leaf_index);
+        Ok(())
+    }
+    
+    pub fn process_compress_point(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        nullifier: [u8; 32],
+        spend_signature: Vec<u8>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let holder_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let point_info = next_account_info(account_info_iter)?;
+        let point_tree_info = next_account_info(account_info_iter)?;
+        let tree_config_info = next_account_info(account_info_iter)?;
+        let merkle_tree_info = next_account_info(account_info_iter)?;
+        let log_wrapper_info = next_account_info(account_info_iter)?;
+        let compression_program_info = next_account_info(account_info_iter)?;
+        let bubblegum_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let nullifier_info = next_account_info(account_info_iter)?;
+        let nullifier_filter_info = next_account_info(account_info_iter)?;
+        
+        if !holder_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state_data = protocol_state_info.try_borrow_data()?;
+        let protocol_state = ProtocolState::load(&protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_PRIVACY_PAYMENTS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        Self::check_compression_programs(
+            log_wrapper_info,
+            compression_program_info,
+            bubblegum_program_info,
+        )?;
+        let mut point_tree = Self::load_point_tree(program_id, point_tree_info, merkle_tree_info)?;
+        
+        let point = Self::load_point(program_id, point_info)?;
+        if !point.is_initialized || !point.is_active {
+            return Err(FPPError::PointNotActive.into());
+        }
+        if point.creator != *holder_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        let now = SyscallClock.unix_timestamp()?;
+        if now < point.locked_until {
+            return Err(FPPError::PointLocked.into());
+        }
+        // A compressed leaf only records the principal, so accrued value would be lost
+        if point.accrued != 0 {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        // The point comes back under a new commitment, so this one's key image is spent now
+        Self::check_spend_signature(
+            program_id,
+            &[point_info],
+            &[nullifier],
+            &spend_signature,
+            &FloatingPoint::compress_message(holder_info.key, merkle_tree_info.key),
+        )?;
+        Self::consume_nullifiers(
+            program_id,
+            holder_info,
+            &[nullifier_info],
+            nullifier_filter_info,
+            account_info_iter.as_slice(),
+            system_program_info,
+            &[nullifier],
+            &Pubkey::default(),
+            now,
+        )?;
+        
+        let compressed = CompressedPoint::from(&point);
+        let (_, bump) = PointTree::find_address(program_id);
+        MintV1CpiBuilder::new(bubblegum_program_info)
+            .tree_config(tree_config_info)
+            .leaf_owner(holder_info)
+            .leaf_delegate(holder_info)
+            .merkle_tree(merkle_tree_info)
+            .payer(holder_info)
+            .tree_creator_or_delegate(point_tree_info)
+            .log_wrapper(log_wrapper_info)
+            .compression_program(compression_program_info)
+            .system_program(system_program_info)
+            .metadata(compression::point_metadata(&compressed)?)
+            .invoke_signed(&[&[PointTree::SEED, &[bump]]])?;
+        
+        let point_lamports = point_info.lamports();
+        **point_info.try_borrow_mut_lamports()? = 0;
+        **holder_info.try_borrow_mut_lamports()? = holder_info
+            .lamports()
+            .checked_add(point_lamports)
+            .ok_or(FPPError::InvalidAmount)?;
+        point_info.data.borrow_mut().fill(0);
+        
+        point_tree.compressed_points = point_tree.compressed_points.saturating_add(1);
+        point_tree.serialize(&mut &mut point_tree_info.data.borrow_mut()[..])?;
+        
+        msg!("Point {} compressed into {}", point_info.key, merkle_tree_info.key);
+        Ok(())
+    }
+    
+    pub fn process_decompress_point(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        point: CompressedPoint,
+        new_commitment: [u8; 32],
+        root: [u8; 32],
+        nonce: u64,
+        index: u32,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let holder_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let point_tree_info = next_account_info(account_info_iter)?;
+        let tree_config_info = next_account_info(account_info_iter)?;
+        let merkle_tree_info = next_account_info(account_info_iter)?;
+        let log_wrapper_info = next_account_info(account_info_iter)?;
+        let compression_program_info = next_account_info(account_info_iter)?;
+        let bubblegum_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let new_point_info = next_account_info(account_info_iter)?;
+        let commitment_tree_info = next_account_info(account_info_iter)?;
+        let root_history_info = next_account_info(account_info_iter)?;
+        let proof_infos: Vec<_> = account_info_iter
+            .map(|node| (node, false, false))
+            .collect();
+        
+        if !holder_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state_data = protocol_state_info.try_borrow_data()?;
+        let protocol_state = ProtocolState::load(&protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_PRIVACY_PAYMENTS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        Self::check_compression_programs(
+            log_wrapper_info,
+            compression_program_info,
+            bubblegum_program_info,
+        )?;
+        let mut point_tree = Self::load_point_tree(program_id, point_tree_info, merkle_tree_info)?;
+        if *commitment_tree_info.key != CommitmentTree::find_address(program_id).0
+            || commitment_tree_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if *root_history_info.key != RootHistory::find_address(program_id).0
+            || root_history_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        // Bubblegum rejects the burn unless the leaf holds exactly this point's metadata
+        let (data_hash, creator_hash) = compression::leaf_hashes(&point)?;
+        BurnCpiBuilder::new(bubblegum_program_info)
+            .tree_config(tree_config_info)
+            .leaf_owner(holder_info, true)
+            .leaf_delegate(holder_info, false)
+            .merkle_tree(merkle_tree_info)
+            .log_wrapper(log_wrapper_info)
+            .compression_program(compression_program_info)
+            .system_program(system_program_info)
+            .root(root)
+            .data_hash(data_hash)
+            .creator_hash(creator_hash)
+            .nonce(nonce)
+            .index(index)
+            .add_remaining_accounts(&proof_infos)
+            .invoke()?;
+        
+        let (new_address, new_bump) = pda::find_point_address(&new_commitment, program_id);
+        if *new_point_info.key != new_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            holder_info,
+            new_point_info,
+            system_program_info,
+            &Rent::get()?,
+            FloatingPoint::LEN,
+            &[FloatingPoint::SEED_PREFIX, &new_commitment, &[new_bump]],
+        )?;
+        let restored = FloatingPoint {
+            version: FloatingPoint::VERSION,
+            is_initialized: true,
+            commitment: new_commitment,
+            created_at: point.created_at,
+            mass: point.mass,
+            is_active: true,
+            creator: *holder_info.key,
+            locked_until: 0,
+            created_slot: point.created_slot,
+            mint: point.mint,
+            rent_payer: *holder_info.key,
+            accrued: 0,
+            // No accretion for the time spent compressed
+            mass_updated_at: SyscallClock.unix_timestamp()?,
+        };
+        restored.serialize(&mut &mut new_point_info.data.borrow_mut()[..])?;
+        
+        point_tree.compressed_points = point_tree.compressed_points.saturating_sub(1);
+        point_tree.serialize(&mut &mut point_tree_info.data.borrow_mut()[..])?;
+        
+        let mut commitment_tree_data = commitment_tree_info.try_borrow_mut_data()?;
+        let commitment_tree = CommitmentTree::load_mut(&mut commitment_tree_data)?;
+        let leaf_index = commitment_tree.insert(new_commitment)?;
+        let merkle_root = commitment_tree.current_root();
+        
+        let mut root_history = RootHistory::try_from_slice(&root_history_info.data.borrow())?;
+        root_history.push(merkle_root);
+        root_history.serialize(&mut &mut root_history_info.data.borrow_mut()[..])?;
+        
+        msg!("Compressed point {} restored at {}", nonce, new_point_info.key);
+        FPPEvent::CommitmentsInserted {
+            commitments: vec![new_commitment],
+            first_leaf_index: leaf_index,
+            tree_generation: commitment_tree.generation,
+            merkle_root,
+        }
+        .emit();
+        Ok(())
+    }
+    
+    pub fn process_attach_point_metadata(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let holder_info = next_account_info(account_info_iter)?;
+        let point_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let holder_token_info = next_account_info(account_info_iter)?;
+        let metadata_info = next_account_info(account_info_iter)?;
+        let authority_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let associated_token_program_info = next_account_info(account_info_iter)?;
+        let metadata_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !holder_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        let point = Self::load_point(program_id, point_info)?;
+        if !point.is_initialized || !point.is_active {
+            return Err(FPPError::PointNotActive.into());
+        }
+        if point.creator != *holder_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let (mint_address, mint_bump) = nft::find_mint(point_info.key, program_id);
+        let (authority_address, authority_bump) = nft::find_authority(program_id);
+        if *mint_info.key != mint_address || *authority_info.key != authority_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if *token_program_info.key != spl_token::id()
+            || *associated_token_program_info.key != spl_associated_token_account::id()
+            || *metadata_program_info.key != TOKEN_METADATA_ID
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let authority_seeds: &[&[u8]] = &[nft::AUTHORITY_SEED, &[authority_bump]];
+        
+        // One mint per point, so a second attach fails here
+        Self::create_pda_account(
+            &spl_token::id(),
+            holder_info,
+            mint_info,
+            system_program_info,
+            &Rent::get()?,
+            spl_token::state::Mint::LEN,
+            &[nft::MINT_SEED_PREFIX, point_info.key.as_ref(), &[mint_bump]],
+        )?;
+        invoke(
+            &spl_token::instruction::initialize_mint2(
+                &spl_token::id(),
+                mint_info.key,
+                authority_info.key,
+                Some(authority_info.key),
+                0,
+            )?,
+            &[mint_info.clone(), token_program_info.clone()],
+        )?;
+        
+        invoke(
+            &spl_associated_token_account::instruction::create_associated_token_account(
+                holder_info.key,
+                holder_info.key,
+                mint_info.key,
+                &spl_token::id(),
+            ),
+            &[
+                holder_info.clone(),
+                holder_token_info.clone(),
+                mint_info.clone(),
+                system_program_info.clone(),
+                token_program_info.clone(),
+                associated_token_program_info.clone(),
+            ],
+        )?;
+        invoke_signed(
+            &spl_token::instruction::mint_to(
+                &spl_token::id(),
+                mint_info.key,
+                holder_token_info.key,
+                authority_info.key,
+                &[],
+                1,
+            )?,
+            &[
+                mint_info.clone(),
+                holder_token_info.clone(),
+                authority_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[authority_seeds],
+        )?;
+        
+        CreateMetadataAccountV3CpiBuilder::new(metadata_program_info)
+            .metadata(metadata_info)
+            .mint(mint_info)
+            .mint_authority(authority_info)
+            .payer(holder_info)
+            .update_authority(authority_info, true)
+            .system_program(system_program_info)
+            .data(nft::point_metadata(&point))
+            .is_mutable(false)
+            .invoke_signed(&[authority_seeds])?;
+        
+        // A label, not a claim on the point: it can't be moved and no more can be minted
+        invoke_signed(
+            &spl_token::instruction::freeze_account(
+                &spl_token::id(),
+                holder_token_info.key,
+                mint_info.key,
+                authority_info.key,
+                &[],
+            )?,
+            &[
+                holder_token_info.clone(),
+                mint_info.clone(),
+                authority_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[authority_seeds],
+        )?;
+        invoke_signed(
+            &spl_token::instruction::set_authority(
+                &spl_token::id(),
+                mint_info.key,
+                None,
+                spl_token::instruction::AuthorityType::MintTokens,
+                authority_info.key,
+                &[],
+            )?,
+            &[mint_info.clone(), authority_info.clone(), token_program_info.clone()],
+            &[authority_seeds],
+        )?;
+        
+        msg!("Point {} labelled with NFT mint {}", point_info.key, mint_info.key);
+        Ok(())
+    }
+    
+    pub fn process_set_relayer_config(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        max_fee_rate: u16,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let relayer_config_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if max_fee_rate > RelayerConfig::MAX_FEE_RATE {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        let (config_address, config_bump) = RelayerConfig::find_address(program_id);
+        if *relayer_config_info.key != config_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if relayer_config_info.owner != program_id {
+            Self::create_pda_account(
+                program_id,
+                payer_info,
+                relayer_config_info,
+                system_program_info,
+                &Rent::get()?,
+                RelayerConfig::LEN,
+                &[RelayerConfig::SEED, &[config_bump]],
+            )?;
+        }
+        
+        let config = RelayerConfig {
+            version: RelayerConfig::VERSION,
+            is_initialized: true,
+            max_fee_rate,
+        };
+        config.serialize(&mut &mut relayer_config_info.data.borrow_mut()[..])?;
+        
+        msg!("Relayer config set (max fee {} bps)", max_fee_rate);
+        Ok(())
+    }
+    
+    #[allow(clippy::too_many_arguments)]
+    pub fn process_relay_withdrawal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        point_ids: Vec<Pubkey>,
+        nullifiers: Vec<[u8; 32]>,
+        nonce: u64,
+        recipient: Pubkey,
+        relayer_fee: u64,
+        spend_signature: Vec<u8>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let relayer_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let withdrawal_request_info = next_account_info(account_info_iter)?;
+        
+        if !relayer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if point_ids.is_empty() || nullifiers.len() != point_ids.len() {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        let point_infos = Self::next_point_accounts(account_info_iter, &point_ids)?;
+        let nullifier_infos = nullifiers
+            .iter()
+            .map(|_| next_account_info(account_info_iter))
+            .collect::<Result<Vec<_>, _>>()?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let user_activity_info = next_account_info(account_info_iter)?;
+        let relayer_config_info = next_account_info(account_info_iter)?;
+        let nullifier_filter_info = next_account_info(account_info_iter)?;
+        let reward_account_info = next_account_info(account_info_iter)?;
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_WITHDRAWALS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if relayer_config_info.owner != program_id
+            || *relayer_config_info.key != RelayerConfig::find_address(program_id).0
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let config = RelayerConfig::deserialize(&mut &relayer_config_info.data.borrow()[..])?;
+        if !config.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        
+        let points_hash = point_ids
+            .iter()
+            .zip(nullifiers.iter())
+            .fold([0u8; 32], |hash, (point, nullifier)| {
+                WithdrawalRequest::chain_points_hash(&hash, point, nullifier)
+            });
+        let relay_hash =
+            WithdrawalRequest::relay_hash(&recipient, relayer_info.key, relayer_fee, nonce);
+        
+        let clock = SyscallClock.clock()?;
+        let now = clock.unix_timestamp;
+        
+        // The spend signature stands in for the recipient's: the points' keys sign who is paid
+        // and the relayer's cut, so a relayer can neither redirect the funds nor raise its fee
+        Self::check_spend_signature(
+            program_id,
+            &point_infos,
+            &nullifiers,
+            &spend_signature,
+            &WithdrawalRequest::relay_spend_message(&relay_hash),
+        )?;
+        let (amount, mint) = Self::retire_points(
+            program_id,
+            &point_infos,
+            None,
+            &clock,
+            protocol_state.min_exit_slots,
+        )?;
+        Self::consume_nullifiers(
+            program_id,
+            relayer_info,
+            &nullifier_infos,
+            nullifier_filter_info,
+            account_info_iter.as_slice(),
+            system_program_info,
+            &nullifiers,
+            withdrawal_request_info.key,
+            now,
+        )?;
+        
+        protocol_state.total_points = protocol_state
+            .total_points
+            .checked_sub(point_ids.len() as u64)
+            .ok_or(FPPError::InvalidAmount)?;
+        
+        if relayer_fee as u128 * 10000 > amount as u128 * config.max_fee_rate as u128 {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        // Rate limits follow the recipient, whoever relays for them
+        let mut activity = Self::load_user_activity(
+            program_id,
+            &recipient,
+            relayer_info,
+            user_activity_info,
+            system_program_info,
+            now,
+        )?;
+        activity.record_withdrawal(&protocol_state.rate_limits, now, amount)?;
+        activity.serialize(&mut &mut user_activity_info.data.borrow_mut()[..])?;
+        Self::record_reward_withdrawal(
+            program_id,
+            &recipient,
+            reward_account_info,
+            &protocol_state.rewards,
+            now,
+            amount,
+        )?;
+        
+        let withdrawal_request = WithdrawalRequest {
+            version: WithdrawalRequest::VERSION,
+            is_initialized: true,
+            requester: recipient,
+            amount,
+            request_time: now,
+            unlock_time: now + protocol_state.withdrawal_delay,
+            completed: false,
+            cancelled: false,
+            point_count: point_ids.len() as u32,
+            points_hash,
+            compliance_root: [0u8; 32],
+            mint,
+            nonce,
+            relayer: *relayer_info.key,
+            relayer_fee,
+        };
+        
+        Self::create_withdrawal_request(
+            program_id,
+            &recipient,
+            relayer_info,
+            withdrawal_request_info,
+            system_program_info,
+            nonce,
+        )?;
+        withdrawal_request.serialize(&mut &mut withdrawal_request_info.data.borrow_mut()[..])?;
+        
+        FPPEvent::WithdrawalRequested {
+            requester: recipient,
+            request: *withdrawal_request_info.key,
+            nonce,
+            mint,
+            amount,
+            point_count: withdrawal_request.point_count,
+            unlock_time: withdrawal_request.unlock_time,
+        }
+        .emit();
+        Ok(())
+    }
+    
+    pub fn process_complete_relayed_withdrawal(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let submitter_info = next_account_info(account_info_iter)?;
+        let recipient_token_info = next_account_info(account_info_iter)?;
+        let relayer_token_info = next_account_info(account_info_iter)?;
+        let treasury_token_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let withdrawal_request_info = next_account_info(account_info_iter)?;
+        let treasury_authority_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let deny_list_info = next_account_info(account_info_iter)?;
+        let association_set_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let supported_mint_info = next_account_info(account_info_iter)?;
+        let fee_exemptions_info = next_account_info(account_info_iter)?;
+        let epoch_stats_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !submitter_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info)
+            || withdrawal_request_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut withdrawal_request = WithdrawalRequest::try_from_slice(
+            &withdrawal_request_info.data.borrow()
+        )?;
+        if !withdrawal_request.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        if *withdrawal_request_info.key != withdrawal_request.address(program_id) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if !withdrawal_request.is_relayed() {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        let clock = SyscallClock.clock()?;
+        if clock.unix_timestamp < withdrawal_request.unlock_time {
+            return Err(FPPError::WithdrawalNotReady.into());
+        }
+        if withdrawal_request.completed || withdrawal_request.cancelled {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_WITHDRAWALS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        // Anyone may settle, so the payouts may only go to the parties the proof named
+        if token::unpack_account(recipient_token_info)?.owner != withdrawal_request.requester
+            || token::unpack_account(relayer_token_info)?.owner != withdrawal_request.relayer
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        // The recipient is never passed as an account; its token account stands in for it
+        Self::check_exit_destination(
+            program_id,
+            deny_list_info,
+            recipient_token_info,
+            recipient_token_info,
+        )?;
+        Self::check_withdrawal_compliance(program_id, association_set_info, &withdrawal_request)?;
+        
+        if *mint_info.key != withdrawal_request.mint {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut entry =
+            Self::load_mint_entry(program_id, protocol_state, mint_info, supported_mint_info)?;
+        
+        // The relayer's cut is owed whether or not the recipient is exempt
+        let fee_rate = Self::withdrawal_fee_rate(
+            program_id,
+            fee_exemptions_info,
+            protocol_state,
+            &entry,
+            &withdrawal_request,
+        )?;
+        let fee = (withdrawal_request.amount as u128 * fee_rate as u128 / 10000) as u64;
+        let recipient_amount = withdrawal_request
+            .amount
+            .checked_sub(fee)
+            .and_then(|amount| amount.checked_sub(withdrawal_request.relayer_fee))
+            .ok_or(FPPError::InvalidAmount)?;
+        
+        let mut sent_amount = Self::transfer_from_treasury(
+            program_id,
+            protocol_state,
+            &entry,
+            treasury_token_info,
+            recipient_token_info,
+            treasury_authority_info,
+            token_program_info,
+            mint_info,
+            recipient_amount,
+        )?;
+        let mut token_fee = token::transfer_fee(mint_info, clock.epoch, sent_amount)?;
+        if withdrawal_request.relayer_fee > 0 {
+            sent_amount = Self::transfer_from_treasury(
+                program_id,
+                protocol_state,
+                &entry,
+                treasury_token_info,
+                relayer_token_info,
+                treasury_authority_info,
+                token_program_info,
+                mint_info,
+                withdrawal_request.relayer_fee,
+            )?;
+            token_fee += token::transfer_fee(mint_info, clock.epoch, sent_amount)?;
+        }
+        FPPEvent::WithdrawalCompleted {
+            requester: withdrawal_request.requester,
+            request: *withdrawal_request_info.key,
+            mint: entry.mint,
+            amount: withdrawal_request.amount,
+            fee,
+            penalty: 0,
+            token_fee,
+            emergency: false,
+        }
+        .emit();
+        
+        withdrawal_request.completed = true;
+        withdrawal_request.serialize(&mut &mut withdrawal_request_info.data.borrow_mut()[..])?;
+        
+        entry.total_withdrawn = entry
+            .total_withdrawn
+            .checked_add(withdrawal_request.amount)
+            .ok_or(FPPError::InvalidAmount)?;
+        protocol_state.release_value(withdrawal_request.amount);
+        entry.total_fees = entry.total_fees.checked_add(fee).ok_or(FPPError::InvalidAmount)?;
+        Self::store_mint_entry(protocol_state, &entry, supported_mint_info)?;
+        Self::record_epoch_withdrawal(
+            program_id,
+            clock.epoch,
+            submitter_info,
+            epoch_stats_info,
+            system_program_info,
+            protocol_state,
+            withdrawal_request.amount,
+            fee,
+        )?;
+        
+        Ok(())
+    }
+    
+    /// Settle matured requests at the front of the withdrawal queue (permissionless)
+    /// 
+    /// Each passed group must be the queue's next entry. Requests their requester already
+    /// completed or cancelled, or whose account was since closed, are dropped without a
+    /// payout, as are relayed ones, which only their relayer settles. Requests that can't pay
+    /// out yet (still locked, a denied destination, or compliance not yet proven) keep their
+    /// place for a later crank without holding back the entries behind them; the rest pay the
+    /// requester as `CompleteWithdrawal` would, less the crank's tip out of the protocol's fee.
+    pub fn process_withdrawal_queue(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        count: u8,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let cranker_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let withdrawal_queue_info = next_account_info(account_info_iter)?;
+        let treasury_authority_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let deny_list_info = next_account_info(account_info_iter)?;
+        let association_set_info = next_account_info(account_info_iter)?;
+        let fee_exemptions_info = next_account_info(account_info_iter)?;
+        let epoch_stats_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !cranker_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if count == 0 {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info)
+            || *withdrawal_queue_info.key != WithdrawalQueue::find_address(program_id).0
+            || withdrawal_queue_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut queue =
+            WithdrawalQueue::deserialize(&mut &withdrawal_queue_info.data.borrow()[..])?;
+        if count as usize > queue.requests.len() {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        let mut protocol_state_data = protocol_state_info.try_borrow_mut_data()?;
+        let protocol_state = ProtocolState::load_mut(&mut protocol_state_data)?;
+        if protocol_state.is_paused(ProtocolState::PAUSE_WITHDRAWALS) {
+            return Err(FPPError::Unauthorized.into());
+        }
+        let clock = SyscallClock.clock()?;
+        
+        let mut deferred = Vec::new();
+        for queued in queue.requests.iter().take(count as usize) {
+            let withdrawal_request_info = next_account_info(account_info_iter)?;
+            let requester_token_info = next_account_info(account_info_iter)?;
+            let treasury_token_info = next_account_info(account_info_iter)?;
+            let mint_info = next_account_info(account_info_iter)?;
+            let supported_mint_info = next_account_info(account_info_iter)?;
+            let tip_token_info = next_account_info(account_info_iter)?;
+            
+            if withdrawal_request_info.key != queued {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            if withdrawal_request_info.owner != program_id {
+                continue;
+            }
+            let mut withdrawal_request = WithdrawalRequest::try_from_slice(
+                &withdrawal_request_info.data.borrow()
+            )?;
+            if withdrawal_request.completed || withdrawal_request.cancelled {
+                continue;
+            }
+            // Relayed requests are never queued, and must not pay out without their relayer
+            if withdrawal_request.is_relayed() {
+                continue;
+            }
+            // A changed delay can leave a later entry unlocking before this one
+            if clock.unix_timestamp < withdrawal_request.unlock_time {
+                deferred.push(*queued);
+                continue;
+            }
+            
+            if token::unpack_account(requester_token_info)?.owner != withdrawal_request.requester {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            let payable = Self::check_exit_destination(
+                program_id,
+                deny_list_info,
+                requester_token_info,
+                requester_token_info,
+            )
+            .and_then(|_| {
+                Self::check_withdrawal_compliance(
+                    program_id,
+                    association_set_info,
+                    &withdrawal_request,
+                )
+            });
+            match payable {
+                Ok(()) => {}
+                // Either can clear later: the authority lifts the denial or the requester proves
+                Err(ProgramError::Custom(code))
+                    if code == FPPError::DeniedAddress as u32
+                        || code == FPPError::InvalidComplianceProof as u32 =>
+                {
+                    deferred.push(*queued);
+                    continue;
+                }
+                Err(err) => return Err(err),
+            }
+            
+            if *mint_info.key != withdrawal_request.mint {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            let mut entry =
+                Self::load_mint_entry(program_id, protocol_state, mint_info, supported_mint_info)?;
+            
+            let fee_rate = Self::withdrawal_fee_rate(
+                program_id,
+                fee_exemptions_info,
+                protocol_state,
+                &entry,
+                &withdrawal_request,
+            )?;
+            let fee = (withdrawal_request.amount as u128 * fee_rate as u128 / 10000) as u64;
+            let tip = WithdrawalQueue::tip(fee);
+            let net_amount =
+                withdrawal_request.amount.checked_sub(fee).ok_or(FPPError::InvalidAmount)?;
+            
+            let sent_amount = Self::transfer_from_treasury(
+                program_id,
+                protocol_state,
+                &entry,
+                treasury_token_info,
+                requester_token_info,
+                treasury_authority_info,
+                token_program_info,
+                mint_info,
+                net_amount,
+            )?;
+            let token_fee = token::transfer_fee(mint_info, clock.epoch, sent_amount)?;
+            if tip > 0 {
+                Self::transfer_from_treasury(
+                    program_id,
+                    protocol_state,
+                    &entry,
+                    treasury_token_info,
+                    tip_token_info,
+                    treasury_authority_info,
+                    token_program_info,
+                    mint_info,
+                    tip,
+                )?;
+            }
+            FPPEvent::WithdrawalCompleted {
+                requester: withdrawal_request.requester,
+                request: *withdrawal_request_info.key,
+                mint: entry.mint,
+                amount: withdrawal_request.amount,
+                fee,
+                penalty: 0,
+                token_fee,
+                emergency: false,
+            }
+            .emit();
+            
+            withdrawal_request.completed = true;
+            withdrawal_request.serialize(&mut &mut withdrawal_request_info.data.borrow_mut()[..])?;
+            
+            entry.total_withdrawn = entry
+                .total_withdrawn
+                .checked_add(withdrawal_request.amount)
+                .ok_or(FPPError::InvalidAmount)?;
+            protocol_state.release_value(withdrawal_request.amount);
+            entry.total_fees =
+                entry.total_fees.checked_add(fee - tip).ok_or(FPPError::InvalidAmount)?;
+            Self::store_mint_entry(protocol_state, &entry, supported_mint_info)?;
+            Self::record_epoch_withdrawal(
+                program_id,
+                clock.epoch,
+                cranker_info,
+                epoch_stats_info,
+                system_program_info,
+                protocol_state,
+                withdrawal_request.amount,
+                fee - tip,
+            )?;
+        }
+        
+        // Settled and dropped entries leave their space behind; deferred ones go to the back
+        // so they can't hold up the requests behind them
+        let settled = count as usize - deferred.len();
+        queue.requests.drain(..count as usize);
+        queue.requests.extend(deferred);
+        queue.serialize(&mut &mut withdrawal_queue_info.data.borrow_mut()[..])?;
+        
+        msg!("Withdrawal queue processed: {} settled, {} waiting", settled, queue.requests.len());
+        Ok(())
+    }
+    
+    pub fn process_report_tree_health(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let commitment_tree_info = next_account_info(account_info_iter)?;
+        
+        if *commitment_tree_info.key != CommitmentTree::find_address(program_id).0
+            || commitment_tree_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut commitment_tree = CommitmentTree::try_from_slice(&commitment_tree_info.data.borrow())?;
+        let now = SyscallClock.unix_timestamp()?;
+        
+        let health = commitment_tree.health(now);
+        commitment_tree.roll_rate_window(now);
+        commitment_tree.serialize(&mut &mut commitment_tree_info.data.borrow_mut()[..])?;
+        
+        msg!(
+            "Tree generation {}: {}/{} leaves, {} per day",
+            health.generation,
+            health.leaves,
+            health.capacity,
+            health.leaves_per_day
+        );
+        match health.seconds_to_full {
+            Some(seconds) => msg!("Estimated seconds to full: {}", seconds),
+            None => msg!("Estimated seconds to full: unknown"),
+        }
+        if health.needs_rollover() {
+            msg!("Tree rollover recommended");
+        }
+        set_return_data(&health.try_to_vec()?);
+        Ok(())
+    }
+    
+    pub fn process_rollover_tree(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let payer_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let commitment_tree_info = next_account_info(account_info_iter)?;
+        let archive_info = next_account_info(account_info_iter)?;
+        let root_history_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !payer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        
+        if *commitment_tree_info.key != CommitmentTree::find_address(program_id).0
+            || commitment_tree_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if *root_history_info.key != RootHistory::find_address(program_id).0
+            || root_history_info.owner != program_id
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let mut archived = CommitmentTree::try_from_slice(&commitment_tree_info.data.borrow())?;
+        if archived.next_index == 0 {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        // Governance may roll over early; a nearly full tree rolls over for anyone
+        let by_authority = protocol_state.authority == *payer_info.key
+            || account_info_iter.next().is_some_and(|authority_info| {
+                authority_info.is_signer && protocol_state.authority == *authority_info.key
+            });
+        if !by_authority && archived.remaining() >= CommitmentTree::AUTO_ROLLOVER_REMAINING {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        // Keep the full tree, frozen, so its final root stays available to spends
+        let (archive_address, archive_bump) =
+            CommitmentTree::find_archive_address(archived.generation, program_id);
+        if *archive_info.key != archive_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            archive_info,
+            system_program_info,
+            &Rent::get()?,
+            CommitmentTree::LEN,
+            &[
+                CommitmentTree::ARCHIVE_SEED_PREFIX,
+                &archived.generation.to_le_bytes(),
+                &[archive_bump],
+            ],
+        )?;
+        archived.is_frozen = true;
+        archived.serialize(&mut &mut archive_info.data.borrow_mut()[..])?;
+        
+        let now = SyscallClock.unix_timestamp()?;
+        let mut successor = CommitmentTree::new()?;
+        successor.generation = archived
+            .generation
+            .checked_add(1)
+            .ok_or(FPPError::InvalidAmount)?;
+        successor.has_concurrent_tree = archived.has_concurrent_tree;
+        successor.roll_rate_window(now);
+        successor.serialize(&mut &mut commitment_tree_info.data.borrow_mut()[..])?;
+        
+        let mut root_history = RootHistory::try_from_slice(&root_history_info.data.borrow())?;
+        root_history.push(successor.current_root());
+        root_history.serialize(&mut &mut root_history_info.data.borrow_mut()[..])?;
+        
+        msg!(
+            "Tree generation {} archived with {} leaves",
+            archived.generation,
+            archived.next_index
+        );
+        Ok(())
+    }
+    
+    pub fn process_set_denominations(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        denominations: Vec<u64>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        // Whole points only, ascending, so every point has an integer mass
+        if denominations.is_empty()
+            || denominations.len() > MAX_DENOMINATIONS
+            || denominations.windows(2).any(|pair| pair[0] >= pair[1])
+            || denominations
+                .iter()
+                .any(|denomination| *denomination == 0 || denomination % POINT_VALUE != 0)
+        {
+            return Err(FPPError::InvalidAmount.into());
+        }
+        
+        protocol_state.denominations = [0; MAX_DENOMINATIONS];
+        protocol_state.denominations[..denominations.len()].copy_from_slice(&denominations);
+        protocol_state.serialize(&mut &mut protocol_state_info.data.borrow_mut()[..])?;
+        
+        msg!("Deposit denominations set to {:?}", denominations);
+        Ok(())
+    }
+    
+    pub fn process_update_contact_book(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        expected_revision: u64,
+        ciphertext: Vec<u8>,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_info = next_account_info(account_info_iter)?;
+        let contact_book_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !owner_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if ciphertext.len() > ContactBook::MAX_CIPHERTEXT_LEN {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        
+        let (book_address, book_bump) = ContactBook::find_address(owner_info.key, program_id);
+        if *contact_book_info.key != book_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let revision = if contact_book_info.owner == program_id {
+            // Allocated at full size, so the serialized book is followed by padding
+            let book = ContactBook::deserialize(&mut &contact_book_info.data.borrow()[..])?;
+            book.revision
+        } else {
+            Self::create_pda_account(
+                program_id,
+                owner_info,
+                contact_book_info,
+                system_program_info,
+                &Rent::get()?,
+                ContactBook::LEN,
+                &[ContactBook::SEED_PREFIX, owner_info.key.as_ref(), &[book_bump]],
+            )?;
+            0
+        };
+        
+        // Compare-and-swap, so concurrent devices can't silently drop each other's edits
+        if revision != expected_revision {
+            return Err(FPPError::StaleContactBook.into());
+        }
+        
+        let book = ContactBook {
+            version: ContactBook::VERSION,
+            is_initialized: true,
+            owner: *owner_info.key,
+            revision: revision.checked_add(1).ok_or(FPPError::InvalidAmount)?,
+            ciphertext,
+        };
+        book.serialize(&mut &mut contact_book_info.data.borrow_mut()[..])?;
+        
+        msg!("Contact book updated to revision {}", book.revision);
+        Ok(())
+    }
+    
+    pub fn process_close_contact_book(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let owner_info = next_account_info(account_info_iter)?;
+        let contact_book_info = next_account_info(account_info_iter)?;
+        
+        if !owner_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if contact_book_info.owner != program_id
+            || *contact_book_info.key != ContactBook::find_address(owner_info.key, program_id).0
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let book_lamports = contact_book_info.lamports();
+        **contact_book_info.try_borrow_mut_lamports()? = 0;
+        **owner_info.try_borrow_mut_lamports()? = owner_info
+            .lamports()
+            .checked_add(book_lamports)
+            .ok_or(FPPError::InvalidAmount)?;
+        contact_book_info.data.borrow_mut().fill(0);
+        
+        msg!("Contact book closed");
+        Ok(())
+    }
+    
+    pub fn process_create_admin_multisig(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let multisig_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        if !validation::is_protocol_state(program_id, protocol_state_info) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let protocol_state = ProtocolState::try_from_slice(&protocol_state_info.data.borrow())?;
+        if protocol_state.authority != *authority_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        
+        if !AdminMultisig::is_valid_config(&signers, threshold) {
+            return Err(FPPError::InvalidMultisig.into());
+        }
+        
+        let (multisig_address, multisig_bump) = AdminMultisig::find_address(program_id);
+        if *multisig_info.key != multisig_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let rent = Rent::get()?;
+        Self::create_pda_account(
+            program_id,
+            authority_info,
+            multisig_info,
+            system_program_info,
+            &rent,
+            AdminMultisig::LEN,
+            &[AdminMultisig::SEED, &[multisig_bump]],
+        )?;
+        
+        let mut multisig = AdminMultisig {
+            version: AdminMultisig::VERSION,
+            is_initialized: true,
+            threshold,
+            signer_count: 0,
+            signers: [Pubkey::default(); MAX_ADMIN_SIGNERS],
+            signer_set_version: 0,
+            proposal_count: 0,
+        };
+        multisig.set_signers(&signers, threshold);
+        multisig.serialize(&mut &mut multisig_info.data.borrow_mut()[..])?;
+        
+        msg!("Admin multisig created: {} of {}", threshold, signers.len());
+        Ok(())
+    }
+    
+    pub fn process_propose_admin_action(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        action: AdminAction,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let proposer_info = next_account_info(account_info_iter)?;
+        let multisig_info = next_account_info(account_info_iter)?;
+        let proposal_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        
+        if !proposer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        let mut multisig = Self::load_admin_multisig(program_id, multisig_info)?;
+        let signer_index = multisig
+            .signer_index(proposer_info.key)
+            .ok_or(FPPError::Unauthorized)?;
+        
+        if let AdminAction::UpdateSigners { signers, threshold } = &action {
+            if !AdminMultisig::is_valid_config(signers, *threshold) {
+                return Err(FPPError::InvalidMultisig.into());
+            }
+        }
+        
+        let index = multisig.proposal_count;
+        let (proposal_address, proposal_bump) = AdminProposal::find_address(index, program_id);
+        if *proposal_info.key != proposal_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let now = SyscallClock.unix_timestamp()?;
+        let action_len = action.try_to_vec()?.len();
+        let rent = Rent::get()?;
+        Self::create_pda_account(
+            program_id,
+            proposer_info,
+            proposal_info,
+            system_program_info,
+            &rent,
+            AdminProposal::space(action_len),
+            &[AdminProposal::SEED_PREFIX, &index.to_le_bytes(), &[proposal_bump]],
+        )?;
+        
+        let proposal = AdminProposal {
+            version: AdminProposal::VERSION,
+            is_initialized: true,
+            index,
+            proposer: *proposer_info.key,
+            signer_set_version: multisig.signer_set_version,
+            approvals: 1 << signer_index,
+            created_at: now,
+            executed: false,
+            action,
+        };
+        proposal.serialize(&mut &mut proposal_info.data.borrow_mut()[..])?;
+        
+        multisig.proposal_count = index.checked_add(1).ok_or(FPPError::InvalidAmount)?;
+        multisig.serialize(&mut &mut multisig_info.data.borrow_mut()[..])?;
+        
+        msg!("Admin proposal {} created: {:?}", index, proposal.action);
+        Ok(())
+    }
+    
+    pub fn process_approve_admin_action(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let signer_info = next_account_info(account_info_iter)?;
+        let multisig_info = next_account_info(account_info_iter)?;
+        let proposal_info = next_account_info(account_info_iter)?;
+        
+        if !signer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        let multisig = Self::load_admin_multisig(program_id, multisig_info)?;
+        let signer_index = multisig
+            .signer_index(signer_info.key)
+            .ok_or(FPPError::Unauthorized)?;
+        let mut proposal = Self::load_admin_proposal(program_id, proposal_info, &multisig)?;
+        
+        proposal.approvals |= 1 << signer_index;
+        proposal.serialize(&mut &mut proposal_info.data.borrow_mut()[..])?;
+        
+        msg!(
+            "Admin proposal {} approved ({} of {})",
+            proposal.index,
+            proposal.approval_count(),
+            multisig.threshold
+        );
+        Ok(())
+    }
+    
+    pub fn process_execute_admin_action(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let executor_info = next_account_info(account_info_iter)?;
+        let multisig_info = next_account_info(account_info_iter)?;
+        let proposal_info = next_account_info(account_info_iter)?;
+        let protocol_state_info = next_account_info(account_info_iter)?;
+        let program_info = next_account_info(account_info_iter)?;
+        
+        if !executor_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        
+        let mut multisig = Self::load_admin_multisig(program_id, multisig_info)?;
+        let mut proposal = Self::load_admin_proposal(program_id, proposal_info, &multisig)?;
+        if proposal.approval_count() < multisig.threshold as u32 {
+            return Err(FPPError::ProposalNotApproved.into());
+        }
+        
+        // Marked executed before acting so the proposal can't be replayed
+        proposal.executed = true;
+        proposal.serialize(&mut &mut proposal_info.data.borrow_mut()[..])?;
+        msg!("Executing admin proposal {}: {:?}", proposal.index, proposal.action);
+        
+        let multisig_key = multisig_info.key;
+        let protocol_state_key = protocol_state_info.key;
+        let ix = match proposal.action {
+            AdminAction::UpdateSigners { signers, threshold } => {
+                multisig.set_signers(&signers, threshold);
+                multisig.signer_set_version = multisig
+                    .signer_set_version
+                    .checked_add(1)
+                    .ok_or(FPPError::InvalidAmount)?;
+                multisig.serialize(&mut &mut multisig_info.data.borrow_mut()[..])?;
+                return Ok(());
+            }
+            AdminAction::UpdateFees {
+                deposit_fee_rate,
+                withdrawal_fee_rate,
+            } => instruction::update_fees(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                deposit_fee_rate,
+                withdrawal_fee_rate,
+            )?,
+            AdminAction::SetPaused { paused } => {
+                instruction::set_paused(program_id, multisig_key, protocol_state_key, paused)?
+            }
+            AdminAction::SetPausedFlags { flags } => {
+                instruction::set_paused_flags(program_id, multisig_key, protocol_state_key, flags)?
+            }
+            AdminAction::SetRateLimits { limits } => {
+                instruction::set_rate_limits(program_id, multisig_key, protocol_state_key, limits)?
+            }
+            AdminAction::SetMinExitSlots { slots } => instruction::set_min_exit_slots(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                slots,
+            )?,
+            AdminAction::SetPoolBounds {
+                min_denomination,
+                max_denomination,
+                creation_bond,
+            } => instruction::set_pool_bounds(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                min_denomination,
+                max_denomination,
+                creation_bond,
+            )?,
+            AdminAction::SetCommitmentValidation { required } => {
+                instruction::set_commitment_validation(
+                    program_id,
+                    multisig_key,
+                    protocol_state_key,
+                    required,
+                )?
+            }
+            AdminAction::SetGuardian { guardian } => {
+                instruction::set_guardian(program_id, multisig_key, protocol_state_key, guardian)?
+            }
+            AdminAction::SetDenominations { denominations } => instruction::set_denominations(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                denominations,
+            )?,
+            AdminAction::SetEmergencyPenalty { penalty_rate } => {
+                instruction::set_emergency_penalty(
+                    program_id,
+                    multisig_key,
+                    protocol_state_key,
+                    penalty_rate,
+                )?
+            }
+            AdminAction::SetFeeReceiver { fee_receiver } => instruction::set_fee_receiver(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                fee_receiver,
+            )?,
+            AdminAction::ProposeAuthority { new_authority } => instruction::propose_authority(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                new_authority,
+            )?,
+            AdminAction::AcceptAuthority => {
+                instruction::accept_authority(program_id, multisig_key, protocol_state_key)?
+            }
+            AdminAction::AddToDenyList { entry } => instruction::add_to_deny_list(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                executor_info.key,
+                entry,
+            )?,
+            AdminAction::RemoveFromDenyList { entry } => instruction::remove_from_deny_list(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                entry,
+            )?,
+            AdminAction::UpdateAssociationSet {
+                flagged_root,
+                required,
+                verifying_key,
+            } => instruction::update_association_set(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                executor_info.key,
+                flagged_root,
+                required,
+                verifying_key,
+            )?,
+            AdminAction::AddSupportedMint { mint, config } => instruction::add_supported_mint(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                executor_info.key,
+                mint,
+                config,
+            )?,
+            AdminAction::ResizeRootHistory { capacity } => instruction::resize_root_history(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                executor_info.key,
+                capacity,
+            )?,
+            AdminAction::ExtendNullifierFilter => instruction::extend_nullifier_filter(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                executor_info.key,
+            )?,
+            AdminAction::CreatePointTree {
+                merkle_tree,
+                max_depth,
+                max_buffer_size,
+            } => instruction::create_point_tree(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                executor_info.key,
+                &merkle_tree,
+                max_depth,
+                max_buffer_size,
+            )?,
+            AdminAction::SetRelayerConfig { max_fee_rate } => instruction::set_relayer_config(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                executor_info.key,
+                max_fee_rate,
+            )?,
+            AdminAction::MigrateState { kind, account } => instruction::migrate_state(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                executor_info.key,
+                kind,
+                &account,
+            )?,
+            AdminAction::SetTreasuryDeployment {
+                mint,
+                collateral_token,
+                reserve,
+                max_deployed_rate,
+            } => instruction::set_treasury_deployment(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                executor_info.key,
+                &mint,
+                &collateral_token,
+                reserve,
+                max_deployed_rate,
+            )?,
+            AdminAction::DeployTreasury {
+                mint,
+                token_program,
+                treasury_token,
+                collateral_token,
+                reserve,
+                amount,
+            } => instruction::deploy_treasury(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                &mint,
+                &token_program,
+                &treasury_token,
+                &collateral_token,
+                &reserve,
+                amount,
+            )?,
+            AdminAction::RecallTreasury {
+                mint,
+                token_program,
+                treasury_token,
+                collateral_token,
+                reserve,
+                collateral_amount,
+            } => instruction::recall_treasury(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                &mint,
+                &token_program,
+                &treasury_token,
+                &collateral_token,
+                &reserve,
+                collateral_amount,
+            )?,
+            AdminAction::SetRewardConfig { mint, vault, rate } => instruction::set_reward_config(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                &vault,
+                &mint,
+                rate,
+            )?,
+            AdminAction::SetReferralFeeShare { share } => instruction::set_referral_fee_share(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                share,
+            )?,
+            AdminAction::UpdateFeeTiers { tiers } => {
+                instruction::update_fee_tiers(program_id, multisig_key, protocol_state_key, tiers)?
+            }
+            AdminAction::AddFeeExemption { address } => instruction::add_fee_exemption(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                executor_info.key,
+                &address,
+            )?,
+            AdminAction::RemoveFeeExemption { address } => instruction::remove_fee_exemption(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                &address,
+            )?,
+            AdminAction::SetDepositCaps {
+                max_tvl,
+                max_user_deposit,
+            } => instruction::set_deposit_caps(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                max_tvl,
+                max_user_deposit,
+            )?,
+            AdminAction::UpdateTimelocks {
+                withdrawal_delay,
+                point_lock_duration,
+            } => instruction::update_timelocks(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                withdrawal_delay,
+                point_lock_duration,
+            )?,
+            AdminAction::SetMassDynamics { dynamics } => instruction::set_mass_dynamics(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                dynamics,
+            )?,
+            AdminAction::SetCpiGuard { enabled } => {
+                instruction::set_cpi_guard(program_id, multisig_key, protocol_state_key, enabled)?
+            }
+            AdminAction::RegisterCircuit {
+                circuit_id,
+                tree_depth,
+                max_inputs,
+                max_outputs,
+                verifying_key_hash,
+            } => instruction::register_circuit(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                executor_info.key,
+                circuit_id,
+                tree_depth,
+                max_inputs,
+                max_outputs,
+                verifying_key_hash,
+            )?,
+            AdminAction::UpdateVerifyingKey {
+                circuit_id,
+                circuit_version,
+                verifying_key,
+            } => instruction::update_verifying_key(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                executor_info.key,
+                circuit_id,
+                circuit_version,
+                verifying_key,
+            )?,
+            AdminAction::CollectFees {
+                mint,
+                token_program,
+                treasury_token,
+                fee_receiver_token,
+            } => instruction::collect_fees(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                &treasury_token,
+                &fee_receiver_token,
+                &mint,
+                &token_program,
+            )?,
+            AdminAction::CreateAuditLog => instruction::create_audit_log(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                executor_info.key,
+            )?,
+            AdminAction::CreateConcurrentTree {
+                merkle_tree,
+                max_depth,
+                max_buffer_size,
+                canopy_depth,
+            } => instruction::create_concurrent_tree(
+                program_id,
+                multisig_key,
+                protocol_state_key,
+                executor_info.key,
+                &merkle_tree,
+                max_depth,
+                max_buffer_size,
+                canopy_depth,
+            )?,
+            AdminAction::RolloverTree { generation } => instruction::rollover_tree(
+                program_id,
+                executor_info.key,
+                protocol_state_key,
+                generation,
+                Some(multisig_key),
+            )?,
+        };
+        
+        // The multisig PDA signs as protocol authority in a call back into this program
+        if program_info.key != program_id {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let (_, multisig_bump) = AdminMultisig::find_address(program_id);
+        let mut action_accounts = vec![
+            multisig_info.clone(),
+            protocol_state_info.clone(),
+            program_info.clone(),
+            executor_info.clone(),
+        ];
+        action_accounts.extend(account_info_iter.cloned());
+        invoke_signed(
+            &ix,
+            &action_accounts,
+            &[&[AdminMultisig::SEED, &[multisig_bump]]],
+        )
+    }
+    
+    /// Append an admin action to the audit log, which follows the instruction's own accounts
+    /// once `CreateAuditLog` has run
+    #[allow(clippy::too_many_arguments)]
+    fn log_admin_action(
+        program_id: &Pubkey,
+        protocol_state: &ProtocolState,
+        trailing_infos: &[AccountInfo],
+        actor: &Pubkey,
+        action: AuditAction,
+        old_value: [u8; 32],
+        new_value: [u8; 32],
+    ) -> ProgramResult {
+        if !protocol_state.has_audit_log {
+            return Ok(());
+        }
+        let audit_log_info = trailing_infos.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if audit_log_info.owner != program_id
+            || *audit_log_info.key != AuditLog::find_address(program_id).0
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let mut entry = AuditEntry {
+            sequence: 0,
+            actor: *actor,
+            action,
+            old_value,
+            new_value,
+            timestamp: SyscallClock.unix_timestamp()?,
+        };
+        entry.sequence =
+            AuditLog::append(&mut audit_log_info.try_borrow_mut_data()?, entry.clone())?;
+        FPPEvent::AdminActionLogged { entry }.emit();
+        Ok(())
+    }
+    
+    fn load_admin_multisig(
+        program_id: &Pubkey,
+        multisig_info: &AccountInfo,
+    ) -> Result<AdminMultisig, ProgramError> {
+        if multisig_info.owner != program_id
+            || *multisig_info.key != AdminMultisig::find_address(program_id).0
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let multisig = AdminMultisig::try_from_slice(&multisig_info.data.borrow())?;
+        if !multisig.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        Ok(multisig)
+    }
+    
+    /// Load an open proposal whose approvals refer to the multisig's current signer set
+    fn load_admin_proposal(
+        program_id: &Pubkey,
+        proposal_info: &AccountInfo,
+        multisig: &AdminMultisig,
+    ) -> Result<AdminProposal, ProgramError> {
+        validation::check_program_owned(program_id, proposal_info)?;
+        let proposal = AdminProposal::try_from_slice(&proposal_info.data.borrow())?;
+        if *proposal_info.key != AdminProposal::find_address(proposal.index, program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if proposal.executed {
+            return Err(FPPError::ProposalAlreadyExecuted.into());
+        }
+        if proposal.signer_set_version != multisig.signer_set_version {
+            return Err(FPPError::InvalidMultisig.into());
+        }
+        Ok(proposal)
+    }
+    
+    /// Append `commitments` to the protocol's concurrent tree, whose accounts come next
+    fn append_to_concurrent_tree(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        commitments: &[[u8; 32]],
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let concurrent_tree_info = next_account_info(account_info_iter)?;
+        let merkle_tree_info = next_account_info(account_info_iter)?;
+        let log_wrapper_info = next_account_info(account_info_iter)?;
+        let compression_program_info = next_account_info(account_info_iter)?;
+        
+        if *log_wrapper_info.key != NOOP_ID
+            || *compression_program_info.key != ACCOUNT_COMPRESSION_ID
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let (address, bump) = ConcurrentTree::find_address(program_id);
+        if *concurrent_tree_info.key != address || concurrent_tree_info.owner != program_id {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut tree = ConcurrentTree::try_from_slice(&concurrent_tree_info.data.borrow())?;
+        if tree.merkle_tree != *merkle_tree_info.key {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let leaf_count = tree
+            .leaf_count
+            .checked_add(commitments.len() as u64)
+            .ok_or(FPPError::InvalidAmount)?;
+        if leaf_count > tree.capacity() {
+            return Err(FPPError::CommitmentTreeFull.into());
+        }
+        
+        for commitment in commitments {
+            invoke_signed(
+                &concurrent_tree::append(merkle_tree_info.key, &address, commitment),
+                &[
+                    merkle_tree_info.clone(),
+                    concurrent_tree_info.clone(),
+                    log_wrapper_info.clone(),
+                    compression_program_info.clone(),
+                ],
+                &[&[ConcurrentTree::SEED, &[bump]]],
+            )?;
+        }
+        
+        FPPEvent::ConcurrentTreeAppend {
+            merkle_tree: tree.merkle_tree,
+            first_leaf_index: tree.leaf_count,
+            count: commitments.len() as u32,
+        }
+        .emit();
+        tree.leaf_count = leaf_count;
+        tree.serialize(&mut &mut concurrent_tree_info.data.borrow_mut()[..])?;
+        Ok(())
+    }
+    
+    fn check_compression_programs(
+        log_wrapper_info: &AccountInfo,
+        compression_program_info: &AccountInfo,
+        bubblegum_program_info: &AccountInfo,
+    ) -> ProgramResult {
+        if *log_wrapper_info.key != NOOP_ID
+            || *compression_program_info.key != ACCOUNT_COMPRESSION_ID
+            || *bubblegum_program_info.key != BUBBLEGUM_ID
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Ok(())
+    }
+    
+    /// Load the point tree, which must be the one `merkle_tree_info` belongs to
+    fn load_point_tree(
+        program_id: &Pubkey,
+        point_tree_info: &AccountInfo,
+        merkle_tree_info: &AccountInfo,
+    ) -> Result<PointTree, ProgramError> {
+        if point_tree_info.owner != program_id
+            || *point_tree_info.key != PointTree::find_address(program_id).0
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let point_tree = PointTree::try_from_slice(&point_tree_info.data.borrow())?;
+        if !point_tree.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        if point_tree.merkle_tree != *merkle_tree_info.key {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Ok(point_tree)
+    }
+    
+    /// Load the deny list, treating one that hasn't been created yet as empty
+    fn load_deny_list(
+        program_id: &Pubkey,
+        deny_list_info: &AccountInfo,
+    ) -> Result<DenyList, ProgramError> {
+        if *deny_list_info.key != DenyList::find_address(program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if deny_list_info.owner != program_id {
+            return Ok(DenyList::default());
+        }
+        Ok(DenyList::deserialize(&mut &deny_list_info.data.borrow()[..])?)
+    }
+    
+    /// State of the pool at `pool`, which must have been opened with `OpenPool`
+    fn load_pool_state(
+        program_id: &Pubkey,
+        pool: &Pubkey,
+        pool_state_info: &AccountInfo,
+    ) -> Result<PoolState, ProgramError> {
+        if *pool_state_info.key != PoolState::find_address(pool, program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if pool_state_info.owner != program_id {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        Ok(PoolState::try_from_slice(&pool_state_info.data.borrow())?)
+    }
+    
+    /// Current key of `circuit_id`, which must have been published with `UpdateVerifyingKey`
+    fn load_circuit_key(
+        program_id: &Pubkey,
+        circuit_id: u16,
+        circuit_key_info: &AccountInfo,
+    ) -> Result<CircuitKey, ProgramError> {
+        if *circuit_key_info.key != CircuitKey::find_address(circuit_id, program_id).0
+            || circuit_key_info.owner != program_id
+        {
+            return Err(FPPError::InvalidCircuit.into());
+        }
+        Ok(CircuitKey::try_from_slice(&circuit_key_info.data.borrow())?)
+    }
+    
+    /// Check a proof through the verifier program, which must be the one the protocol pins
+    fn verify_groth16(
+        verifier_program_info: &AccountInfo,
+        verifying_key: &Groth16VerifyingKey,
+        proof: &Groth16Proof,
+        public_inputs: &[[u8; 32]; groth16::ASSOCIATION_PUBLIC_INPUTS],
+    ) -> Result<bool, ProgramError> {
+        validation::check_verifier_program(verifier_program_info)?;
+        fpp_verifier::cpi::verify(verifier_program_info, verifying_key, proof, public_inputs)
+    }
+    
+    /// Record `nullifier` as spent in the pool at `pool`, failing if it already was
+    fn spend_pool_nullifier<'a>(
+        program_id: &Pubkey,
+        pool: &Pubkey,
+        payer_info: &AccountInfo<'a>,
+        pool_nullifier_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        nullifier: &[u8; 32],
+        timestamp: i64,
+    ) -> ProgramResult {
+        // The pool's nullifier domain: a spent nullifier already has its PDA
+        let (nullifier_address, nullifier_bump) =
+            PoolNullifier::find_address(pool, nullifier, program_id);
+        if *pool_nullifier_info.key != nullifier_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if pool_nullifier_info.owner == program_id || !pool_nullifier_info.data_is_empty() {
+            return Err(FPPError::NullifierAlreadyUsed.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            pool_nullifier_info,
+            system_program_info,
+            &Rent::get()?,
+            PoolNullifier::LEN,
+            &[PoolNullifier::SEED_PREFIX, pool.as_ref(), nullifier, &[nullifier_bump]],
+        )?;
+        let record = PoolNullifier {
+            version: PoolNullifier::VERSION,
+            is_initialized: true,
+            pool: *pool,
+            nullifier: *nullifier,
+            spent_at: timestamp,
+        };
+        record.serialize(&mut &mut pool_nullifier_info.data.borrow_mut()[..])?;
+        Ok(())
+    }
+    
+    /// Append `request` to the withdrawal queue, creating or growing it at `payer_info`'s expense
+    fn enqueue_withdrawal<'a>(
+        program_id: &Pubkey,
+        payer_info: &AccountInfo<'a>,
+        withdrawal_queue_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        request: &Pubkey,
+    ) -> ProgramResult {
+        let (address, bump) = WithdrawalQueue::find_address(program_id);
+        if *withdrawal_queue_info.key != address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        
+        let rent = Rent::get()?;
+        let mut queue = if withdrawal_queue_info.owner == program_id {
+            WithdrawalQueue::deserialize(&mut &withdrawal_queue_info.data.borrow()[..])?
+        } else {
+            Self::create_pda_account(
+                program_id,
+                payer_info,
+                withdrawal_queue_info,
+                system_program_info,
+                &rent,
+                WithdrawalQueue::space(0),
+                &[WithdrawalQueue::SEED, &[bump]],
+            )?;
+            WithdrawalQueue {
+                version: WithdrawalQueue::VERSION,
+                is_initialized: true,
+                requests: Vec::new(),
+            }
+        };
+        
+        // A full queue mustn't block requests; this one is left for its requester to complete
+        if queue.requests.len() >= WithdrawalQueue::MAX_ENTRIES {
+            msg!("Withdrawal queue full, {} not queued", request);
+            return Ok(());
+        }
+        queue.requests.push(*request);
+        Self::realloc_account(
+            withdrawal_queue_info,
+            payer_info,
+            system_program_info,
+            &rent,
+            WithdrawalQueue::space(queue.requests.len()),
+        )?;
+        queue.serialize(&mut &mut withdrawal_queue_info.data.borrow_mut()[..])?;
+        Ok(())
+    }
+    
+    /// Drop `request` from the withdrawal queue once its requester has settled or cancelled it
+    fn dequeue_withdrawal(
+        program_id: &Pubkey,
+        withdrawal_queue_info: &AccountInfo,
+        request: &Pubkey,
+    ) -> ProgramResult {
+        if *withdrawal_queue_info.key != WithdrawalQueue::find_address(program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if withdrawal_queue_info.owner != program_id {
+            return Ok(());
+        }
+        let mut queue =
+            WithdrawalQueue::deserialize(&mut &withdrawal_queue_info.data.borrow()[..])?;
+        queue.requests.retain(|queued| queued != request);
+        queue.serialize(&mut &mut withdrawal_queue_info.data.borrow_mut()[..])?;
+        Ok(())
+    }
+    
+    /// Load the fee exemption list, empty if it hasn't been created yet
+    fn load_fee_exemptions(
+        program_id: &Pubkey,
+        fee_exemptions_info: &AccountInfo,
+    ) -> Result<FeeExemptions, ProgramError> {
+        if *fee_exemptions_info.key != FeeExemptions::find_address(program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if fee_exemptions_info.owner != program_id {
+            return Ok(FeeExemptions::default());
+        }
+        Ok(FeeExemptions::deserialize(&mut &fee_exemptions_info.data.borrow()[..])?)
+    }
+    
+    /// Fee rate owed on a withdrawal request's payout: zero for an exempt requester,
+    /// otherwise the mint's rate lowered by any fee tier the amount reaches
+    fn withdrawal_fee_rate(
+        program_id: &Pubkey,
+        fee_exemptions_info: &AccountInfo,
+        protocol_state: &ProtocolState,
+        entry: &SupportedMint,
+        withdrawal_request: &WithdrawalRequest,
+    ) -> Result<u16, ProgramError> {
+        let fee_exemptions = Self::load_fee_exemptions(program_id, fee_exemptions_info)?;
+        if fee_exemptions.is_exempt(&withdrawal_request.requester) {
+            return Ok(0);
+        }
+        Ok(protocol_state
+            .withdrawal_fee_rate_for(withdrawal_request.amount, entry.config.withdrawal_fee_rate))
+    }
+    
+    /// Reject a withdrawal payout to a denied wallet or token account
+    fn check_exit_destination(
+        program_id: &Pubkey,
+        deny_list_info: &AccountInfo,
+        user_info: &AccountInfo,
+        user_token_info: &AccountInfo,
+    ) -> ProgramResult {
+        let deny_list = Self::load_deny_list(program_id, deny_list_info)?;
+        if deny_list.entries.is_empty() {
+            return Ok(());
+        }
+        let token_owner = token::unpack_account(user_token_info)?.owner;
+        if deny_list.contains_key(user_info.key)
+            || deny_list.contains_key(user_token_info.key)
+            || deny_list.contains_key(&token_owner)
+        {
+            return Err(FPPError::DeniedAddress.into());
+        }
+        Ok(())
+    }
+    
+    /// Load the association set, or `None` if it hasn't been created yet
+    fn load_association_set(
+        program_id: &Pubkey,
+        association_set_info: &AccountInfo,
+    ) -> Result<Option<AssociationSet>, ProgramError> {
+        if *association_set_info.key != AssociationSet::find_address(program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if association_set_info.owner != program_id {
+            return Ok(None);
+        }
+        Ok(Some(AssociationSet::try_from_slice(&association_set_info.data.borrow())?))
+    }
+    
+    /// When proofs are required, the request must be proven against the current flagged root
+    fn check_withdrawal_compliance(
+        program_id: &Pubkey,
+        association_set_info: &AccountInfo,
+        withdrawal_request: &WithdrawalRequest,
+    ) -> ProgramResult {
+        match Self::load_association_set(program_id, association_set_info)? {
+            Some(set) if set.required && withdrawal_request.compliance_root != set.flagged_root => {
+                Err(FPPError::InvalidComplianceProof.into())
+            }
+            _ => Ok(()),
+        }
+    }
+    
+    /// Load `user`'s activity account, creating it at `payer_info`'s expense on first use
+    fn load_user_activity<'a>(
+        program_id: &Pubkey,
+        user: &Pubkey,
+        payer_info: &AccountInfo<'a>,
+        user_activity_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        now: i64,
+    ) -> Result<UserActivity, ProgramError> {
+        let (address, bump) = UserActivity::find_address(user, program_id);
+        if *user_activity_info.key != address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if user_activity_info.owner == program_id {
+            // Activity from an earlier layout is upgraded on its next use, at the payer's
+            // expense, rather than waiting on `MigrateState`
+            Self::migrate_account::<UserActivity>((
+                user_activity_info,
+                payer_info,
+                system_program_info,
+            ))?;
+            return Ok(UserActivity::try_from_slice(&user_activity_info.data.borrow())?);
+        }
+        
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            user_activity_info,
+            system_program_info,
+            &Rent::get()?,
+            UserActivity::LEN,
+            &[UserActivity::SEED_PREFIX, user.as_ref(), &[bump]],
+        )?;
+        Ok(UserActivity {
+            version: UserActivity::VERSION,
+            is_initialized: true,
+            user: *user,
+            window_start: now,
+            deposit_count: 0,
+            deposit_volume: 0,
+            withdrawal_count: 0,
+            withdrawal_volume: 0,
+            deposit_epoch: 0,
+            total_deposited: 0,
+        })
+    }
+    
+    /// Load the statistics of `epoch`, creating them at `payer_info`'s expense on first use
+    fn load_epoch_stats<'a>(
+        program_id: &Pubkey,
+        epoch: u64,
+        payer_info: &AccountInfo<'a>,
+        epoch_stats_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+    ) -> Result<EpochStats, ProgramError> {
+        let (address, bump) = EpochStats::find_address(epoch, program_id);
+        if *epoch_stats_info.key != address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if epoch_stats_info.owner == program_id {
+            return Ok(EpochStats::try_from_slice(&epoch_stats_info.data.borrow())?);
+        }
+        
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            epoch_stats_info,
+            system_program_info,
+            &Rent::get()?,
+            EpochStats::LEN,
+            &[EpochStats::SEED_PREFIX, &epoch.to_le_bytes(), &[bump]],
+        )?;
+        Ok(EpochStats {
+            version: EpochStats::VERSION,
+            is_initialized: true,
+            epoch,
+            ..EpochStats::default()
+        })
+    }
+    
+    /// Add a withdrawal payout and the fees it left in the treasury to the current epoch
+    #[allow(clippy::too_many_arguments)]
+    fn record_epoch_withdrawal<'a>(
+        program_id: &Pubkey,
+        epoch: u64,
+        payer_info: &AccountInfo<'a>,
+        epoch_stats_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        protocol_state: &ProtocolState,
+        amount: u64,
+        fees: u64,
+    ) -> ProgramResult {
+        let mut stats = Self::load_epoch_stats(
+            program_id,
+            epoch,
+            payer_info,
+            epoch_stats_info,
+            system_program_info,
+        )?;
+        stats.record_withdrawal(amount, fees, protocol_state.total_points);
+        stats.serialize(&mut &mut epoch_stats_info.data.borrow_mut()[..])?;
+        Ok(())
+    }
+    
+    /// Load `user`'s reward account, creating it at `payer_info`'s expense on first use
+    fn load_reward_account<'a>(
+        program_id: &Pubkey,
+        user: &Pubkey,
+        payer_info: &AccountInfo<'a>,
+        reward_account_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        now: i64,
+    ) -> Result<RewardAccount, ProgramError> {
+        let (address, bump) = RewardAccount::find_address(user, program_id);
+        if *reward_account_info.key != address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if reward_account_info.owner == program_id {
+            return Ok(RewardAccount::try_from_slice(&reward_account_info.data.borrow())?);
+        }
+        
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            reward_account_info,
+            system_program_info,
+            &Rent::get()?,
+            RewardAccount::LEN,
+            &[RewardAccount::SEED_PREFIX, user.as_ref(), &[bump]],
+        )?;
+        Ok(RewardAccount {
+            version: RewardAccount::VERSION,
+            is_initialized: true,
+            user: *user,
+            balance: 0,
+            accrued: 0,
+            last_accrued_at: now,
+            total_claimed: 0,
+        })
+    }
+    
+    /// Load the referral account of `referrer` in `mint`, creating it at `payer_info`'s expense
+    fn load_referral_account<'a>(
+        program_id: &Pubkey,
+        referrer: &Pubkey,
+        mint: &Pubkey,
+        payer_info: &AccountInfo<'a>,
+        referral_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+    ) -> Result<ReferralAccount, ProgramError> {
+        let (address, bump) = ReferralAccount::find_address(referrer, mint, program_id);
+        if *referral_info.key != address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if referral_info.owner == program_id {
+            return Ok(ReferralAccount::try_from_slice(&referral_info.data.borrow())?);
+        }
+        
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            referral_info,
+            system_program_info,
+            &Rent::get()?,
+            ReferralAccount::LEN,
+            &[ReferralAccount::SEED_PREFIX, referrer.as_ref(), mint.as_ref(), &[bump]],
+        )?;
+        Ok(ReferralAccount {
+            version: ReferralAccount::VERSION,
+            is_initialized: true,
+            referrer: *referrer,
+            mint: *mint,
+            deposit_count: 0,
+            volume: 0,
+            accrued_fees: 0,
+            total_claimed: 0,
+        })
+    }
+    
+    /// Take a withdrawal off `user`'s rewarded balance
+    /// 
+    /// Users who never deposited have no reward account and nothing to reduce.
+    fn record_reward_withdrawal(
+        program_id: &Pubkey,
+        user: &Pubkey,
+        reward_account_info: &AccountInfo,
+        config: &RewardConfig,
+        now: i64,
+        amount: u64,
+    ) -> ProgramResult {
+        if *reward_account_info.key != RewardAccount::find_address(user, program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if reward_account_info.owner != program_id {
+            return Ok(());
+        }
+        let mut rewards = RewardAccount::try_from_slice(&reward_account_info.data.borrow())?;
+        rewards.record_withdrawal(config, now, amount);
+        rewards.serialize(&mut &mut reward_account_info.data.borrow_mut()[..])?;
+        Ok(())
+    }
+    
+    /// Give back the reward balance a cancelled request took, its points being in the pool
+    /// again
+    fn restore_reward_balance(
+        program_id: &Pubkey,
+        user: &Pubkey,
+        reward_account_info: &AccountInfo,
+        config: &RewardConfig,
+        now: i64,
+        amount: u64,
+    ) -> ProgramResult {
+        if *reward_account_info.key != RewardAccount::find_address(user, program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        if reward_account_info.owner != program_id {
+            return Ok(());
+        }
+        let mut rewards = RewardAccount::try_from_slice(&reward_account_info.data.borrow())?;
+        rewards.record_deposit(config, now, amount);
+        rewards.serialize(&mut &mut reward_account_info.data.borrow_mut()[..])?;
+        Ok(())
+    }
+    
+    fn load_withdrawal_batch(
+        program_id: &Pubkey,
+        user_info: &AccountInfo,
+        batch_info: &AccountInfo,
+        batch_id: u64,
+    ) -> Result<WithdrawalBatch, ProgramError> {
+        let (batch_address, _) = WithdrawalBatch::find_address(user_info.key, batch_id, program_id);
+        if batch_info.owner != program_id || *batch_info.key != batch_address {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let batch = WithdrawalBatch::try_from_slice(&batch_info.data.borrow())?;
+        if !batch.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        if batch.requester != *user_info.key {
+            return Err(FPPError::Unauthorized.into());
+        }
+        Ok(batch)
+    }
+    
+    /// Next accounts, which must be the given points in order
+    fn next_point_accounts<'a, 'b>(
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+        point_ids: &[Pubkey],
+    ) -> Result<Vec<&'a AccountInfo<'b>>, ProgramError> {
+        point_ids
+            .iter()
+            .map(|point_id| {
+                let point_info = next_account_info(account_info_iter)?;
+                if point_info.key != point_id {
+                    return Err(FPPError::InvalidAccount.into());
+                }
+                Ok(point_info)
+            })
+            .collect()
+    }
+    
+    /// Check a spend signature over `message` by the keys of the given points
+    ///
+    /// Its key images must be `nullifiers`, one per point in order, so each point has a single
+    /// nullifier whichever instruction spends it (`ring::verify_spend`).
+    fn check_spend_signature(
+        program_id: &Pubkey,
+        point_infos: &[&AccountInfo],
+        nullifiers: &[[u8; 32]],
+        spend_signature: &[u8],
+        message: &[u8; 32],
+    ) -> ProgramResult {
+        let signature = RingSignature::try_from_slice(spend_signature)
+            .map_err(|_| FPPError::InvalidRingSignature)?;
+        let commitments = point_infos
+            .iter()
+            .map(|point_info| Ok(Self::load_point(program_id, point_info)?.commitment))
+            .collect::<Result<Vec<_>, ProgramError>>()?;
+        if !ring::verify_spend(message, &commitments, nullifiers, &signature) {
+            return Err(FPPError::InvalidRingSignature.into());
+        }
+        Ok(())
+    }
+    
+    /// Take active, unlocked points out of circulation for a withdrawal
+    ///
+    /// Returns their total value, accretion and decay included, and their mint; points of
+    /// different mints can't be mixed. With an `owner`, every point must have been created
+    /// by it, as in `TransferPoint`.
+    fn retire_points(
+        program_id: &Pubkey,
+        point_infos: &[&AccountInfo],
+        owner: Option<&Pubkey>,
+        clock: &Clock,
+        min_exit_slots: u64,
+    ) -> Result<(u64, Pubkey), ProgramError> {
+        let mut total_value = 0u64;
+        let mut mint = None;
+        for point_info in point_infos {
+            let mut point = Self::load_point(program_id, point_info)?;
+            if !point.is_initialized || !point.is_active {
+                return Err(FPPError::PointNotActive.into());
+            }
+            if owner.is_some_and(|owner| point.creator != *owner) {
+                return Err(FPPError::Unauthorized.into());
+            }
+            if clock.unix_timestamp < point.locked_until {
+                return Err(FPPError::PointLocked.into());
+            }
+            if !point.can_exit_at(clock.slot, min_exit_slots) {
+                return Err(FPPError::FlashLoanDetected.into());
+            }
+            if *mint.get_or_insert(point.mint) != point.mint {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            total_value = point
+                .value()
+                .and_then(|value| total_value.checked_add(value))
+                .ok_or(FPPError::InvalidAmount)?;
+            point.is_active = false;
+            point.serialize(&mut &mut point_info.data.borrow_mut()[..])?;
+        }
+        Ok((total_value, mint.unwrap_or_default()))
+    }
+    
+    /// Create one nullifier PDA per nullifier, failing if any already exists or was compacted
+    ///
+    /// `withdrawal_request` is the request the spend opens, or the default pubkey for spends
+    /// that can't be undone. `nullifier_shard_infos` are the instruction's trailing accounts,
+    /// among which a filter hit looks for the nullifier's shard.
+    #[allow(clippy::too_many_arguments)]
+    fn consume_nullifiers<'a>(
+        program_id: &Pubkey,
+        payer_info: &AccountInfo<'a>,
+        nullifier_infos: &[&AccountInfo<'a>],
+        nullifier_filter_info: &AccountInfo<'a>,
+        nullifier_shard_infos: &[AccountInfo<'a>],
+        system_program_info: &AccountInfo<'a>,
+        nullifiers: &[[u8; 32]],
+        withdrawal_request: &Pubkey,
+        timestamp: i64,
+    ) -> ProgramResult {
+        for (i, nullifier) in nullifiers.iter().enumerate() {
+            if nullifiers[..i].contains(nullifier) {
+                return Err(FPPError::NullifierAlreadyUsed.into());
+            }
+        }
+        
+        if *nullifier_filter_info.key != NullifierFilter::find_address(program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let mut filter_data = if nullifier_filter_info.owner == program_id {
+            Some(nullifier_filter_info.try_borrow_mut_data()?)
+        } else {
+            None
+        };
+        
+        let rent = Rent::get()?;
+        for (nullifier_info, nullifier) in nullifier_infos.iter().zip(nullifiers.iter()) {
+            let (expected, bump) = pda::find_nullifier(nullifier, program_id);
+            if *nullifier_info.key != expected {
+                return Err(FPPError::InvalidAccount.into());
+            }
+            
+            // Only a filter hit needs the exact check. A miss can still be a nullifier spent
+            // before the filter was completed, which `create_pda_account` refuses below.
+            // Compaction needs a complete filter, so nothing is compacted until then.
+            let (maybe_spent, maybe_compacted) = match filter_data.as_mut() {
+                // Still being allocated by `ExtendNullifierFilter` while shorter than `LEN`
+                Some(data) if data.len() >= NullifierFilter::LEN => {
+                    match NullifierFilter::split_mut(data)? {
+                        (filter, bits) if filter.is_initialized => {
+                            let hit = filter.insert(bits, nullifier);
+                            (hit, hit)
+                        }
+                        _ => (true, false),
+                    }
+                }
+                _ => (true, false),
+            };
+            
+            // An existing PDA means this nullifier was already spent. Compacted nullifiers
+            // are in the filter bits too, so a miss rules them out as well.
+            if maybe_spent
+                && (nullifier_info.owner == program_id
+                    || !nullifier_info.data_is_empty()
+                    || filter_data
+                        .as_ref()
+                        .is_some_and(|data| NullifierFilter::is_compacted(data, nullifier)))
+            {
+                return Err(FPPError::NullifierAlreadyUsed.into());
+            }
+            if maybe_compacted
+                && Self::is_in_nullifier_shard(program_id, nullifier_shard_infos, nullifier)?
+            {
+                return Err(FPPError::NullifierAlreadyUsed.into());
+            }
+            
+            Self::create_pda_account(
+                program_id,
+                payer_info,
+                nullifier_info,
+                system_program_info,
+                &rent,
+                NullifierSet::LEN,
+                &[NullifierSet::SEED_PREFIX, nullifier, &[bump]],
+            )?;
+            
+            let record = NullifierSet {
+                version: NullifierSet::VERSION,
+                is_initialized: true,
+                nullifier: *nullifier,
+                used: true,
+                timestamp,
+                payer: *payer_info.key,
+                withdrawal_request: *withdrawal_request,
+            };
+            record.serialize(&mut &mut nullifier_info.data.borrow_mut()[..])?;
+        }
+        
+        FPPEvent::NullifiersSpent {
+            nullifiers: nullifiers.to_vec(),
+        }
+        .emit();
+        Ok(())
+    }
+    
+    /// Whether `nullifier` was compacted into its shard, which must be among `shard_infos`
+    ///
+    /// A shard that was never created holds nothing.
+    fn is_in_nullifier_shard(
+        program_id: &Pubkey,
+        shard_infos: &[AccountInfo],
+        nullifier: &[u8; 32],
+    ) -> Result<bool, ProgramError> {
+        let (address, _) = NullifierShard::find_address(nullifier, program_id);
+        let shard_info = shard_infos
+            .iter()
+            .find(|info| *info.key == address)
+            .ok_or(FPPError::InvalidAccount)?;
+        if shard_info.owner != program_id {
+            return Ok(false);
+        }
+        Ok(NullifierShard::contains(&shard_info.data.borrow(), nullifier))
+    }
+    
+    /// Read a program-owned point, which must sit at its commitment's PDA
+    fn load_point(
+        program_id: &Pubkey,
+        point_info: &AccountInfo,
+    ) -> Result<FloatingPoint, ProgramError> {
+        validation::check_program_owned(program_id, point_info)?;
+        let point = FloatingPoint::try_from_slice(&point_info.data.borrow())?;
+        if *point_info.key != pda::find_point_address(&point.commitment, program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Ok(point)
+    }
+    
+    /// Create the withdrawal request PDA `user_info` opens under `nonce`
+    fn create_withdrawal_request<'a>(
+        program_id: &Pubkey,
+        requester: &Pubkey,
+        payer_info: &AccountInfo<'a>,
+        withdrawal_request_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        nonce: u64,
+    ) -> ProgramResult {
+        let (expected, bump) = pda::find_withdrawal_request(requester, nonce, program_id);
+        if *withdrawal_request_info.key != expected {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Self::create_pda_account(
+            program_id,
+            payer_info,
+            withdrawal_request_info,
+            system_program_info,
+            &Rent::get()?,
+            WithdrawalRequest::LEN,
+            &[
+                WithdrawalRequest::SEED_PREFIX,
+                requester.as_ref(),
+                &nonce.to_le_bytes(),
+                &[bump],
+            ],
+        )
+    }
+    
+    /// Settings and totals of the mint in `mint_info`
+    ///
+    /// The primary mint is read from the protocol state; any other mint must
+    /// have a `SupportedMint` registry entry at `supported_mint_info`.
+    fn load_mint_entry(
+        program_id: &Pubkey,
+        protocol_state: &ProtocolState,
+        mint_info: &AccountInfo,
+        supported_mint_info: &AccountInfo,
+    ) -> Result<SupportedMint, ProgramError> {
+        if *supported_mint_info.key != SupportedMint::find_address(mint_info.key, program_id).0 {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        let entry = if *mint_info.key == protocol_state.usdt_mint {
+            SupportedMint::primary(protocol_state)
+        } else {
+            validation::check_program_owned(program_id, supported_mint_info)?;
+            let entry = SupportedMint::try_from_slice(&supported_mint_info.data.borrow())?;
+            if !entry.is_initialized {
+                return Err(FPPError::AccountNotInitialized.into());
+            }
+            entry
+        };
+        if *mint_info.owner != entry.token_program {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        Ok(entry)
+    }
+    
+    /// Write back the totals of an entry from `load_mint_entry`
+    ///
+    /// Totals of the primary mint go to `protocol_state`, which the caller saves.
+    fn store_mint_entry(
+        protocol_state: &mut ProtocolState,
+        entry: &SupportedMint,
+        supported_mint_info: &AccountInfo,
+    ) -> ProgramResult {
+        if entry.mint == protocol_state.usdt_mint {
+            protocol_state.total_deposited = entry.total_deposited;
+            protocol_state.total_withdrawn = entry.total_withdrawn;
+            protocol_state.total_fees = entry.total_fees;
+        } else {
+            entry.serialize(&mut &mut supported_mint_info.data.borrow_mut()[..])?;
+        }
+        Ok(())
+    }
+    
+    /// Pay `amount` base units of `entry`'s mint out of the treasury, signed by the treasury
+    /// authority PDA, returning the token amount sent
+    ///
+    /// Any Token-2022 transfer fee is withheld from what the destination receives.
+    #[allow(clippy::too_many_arguments)]
+    fn transfer_from_treasury<'a>(
+        program_id: &Pubkey,
+        protocol_state: &ProtocolState,
+        entry: &SupportedMint,
+        treasury_token_info: &AccountInfo<'a>,
+        destination_info: &AccountInfo<'a>,
+        treasury_authority_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        mint_info: &AccountInfo<'a>,
+        amount: u64,
+    ) -> Result<u64, ProgramError> {
+        let bump = validation::check_treasury_authority(
+            program_id,
+            protocol_state,
+            treasury_authority_info,
+        )?;
+        validation::check_token_program(token_program_info, &entry.token_program)?;
+        if *mint_info.key != entry.mint {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        validation::check_treasury_token_account(protocol_state, entry, treasury_token_info)?;
+        let mint = token::unpack_mint(mint_info)?;
+        let token_amount =
+            decimals::to_token_amount(amount, mint.decimals).ok_or(FPPError::InvalidAmount)?;
+        
+        token::transfer(
+            token_program_info,
+            treasury_token_info,
+            mint_info,
+            destination_info,
+            treasury_authority_info,
+            token_amount,
+            mint.decimals,
+            &[&[ProtocolState::TREASURY_SEED, &[bump]]],
+        )?;
+        Ok(token_amount)
+    }
+    
+    /// Close the requester's wSOL account after a payout, returning its balance as SOL
+    fn unwrap_payout<'a>(
+        token_program_info: &AccountInfo<'a>,
+        mint_info: &AccountInfo<'a>,
+        user_info: &AccountInfo<'a>,
+        user_token_info: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        if !token::is_native_mint(mint_info.key) || !user_info.is_writable {
+            return Err(FPPError::InvalidInstruction.into());
+        }
+        validation::check_token_program(token_program_info, mint_info.owner)?;
+        if token::unpack_account(user_token_info)?.owner != *user_info.key {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        token::unwrap_sol(token_program_info, user_token_info, user_info)
+    }
+    
+    /// Upgrade an account to `T`'s current layout, returning the version it had
+    fn migrate_account<'a, T: Versioned + BorshDeserialize>(
+        (account_info, payer_info, system_program_info): (
+            &AccountInfo<'a>,
+            &AccountInfo<'a>,
+            &AccountInfo<'a>,
+        ),
+    ) -> Result<u8, ProgramError> {
+        let versioned_len = account_info.data_len().min(T::VERSIONED_LEN);
+        let mut data = account_info.data.borrow()[..versioned_len].to_vec();
+        let from = T::migrate(&mut data)?;
+        if from == T::VERSION {
+            return Ok(from);
+        }
+        
+        // An upgrade that doesn't decode means the account was never a `T`
+        T::deserialize(&mut &data[..]).map_err(|_| FPPError::InvalidAccount)?;
+        Self::realloc_account(
+            account_info,
+            payer_info,
+            system_program_info,
+            &Rent::get()?,
+            data.len(),
+        )?;
+        account_info.data.borrow_mut()[..data.len()].copy_from_slice(&data);
+        Ok(from)
+    }
+    
+    /// Close a program-owned account, sending its lamports to `destination_info`
+    fn close_account(account_info: &AccountInfo, destination_info: &AccountInfo) -> ProgramResult {
+        let lamports = account_info.lamports();
+        **account_info.try_borrow_mut_lamports()? = 0;
+        **destination_info.try_borrow_mut_lamports()? = destination_info
+            .lamports()
+            .checked_add(lamports)
+            .ok_or(FPPError::InvalidAmount)?;
+        account_info.data.borrow_mut().fill(0);
+        Ok(())
+    }
+    
+    /// Grow a program-owned account to `new_len`, topping its rent up from the payer
+    /// 
+    /// Accounts never shrink here. The runtime caps growth per instruction at
+    /// `MAX_PERMITTED_DATA_INCREASE` bytes.
+    fn realloc_account<'a>(
+        account_info: &AccountInfo<'a>,
+        payer_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        rent: &Rent,
+        new_len: usize,
+    ) -> ProgramResult {
+        let current_len = account_info.data_len();
+        if new_len <= current_len {
+            return Ok(());
+        }
+        validation::check_system_program(system_program_info)?;
+        if new_len - current_len > MAX_PERMITTED_DATA_INCREASE {
+            return Err(ProgramError::InvalidRealloc);
+        }
+        
+        let top_up = rent.minimum_balance(new_len).saturating_sub(account_info.lamports());
+        if top_up > 0 {
+            invoke(
+                &system_instruction::transfer(payer_info.key, account_info.key, top_up),
+                &[
+                    payer_info.clone(),
+                    account_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        }
+        account_info.realloc(new_len, false)?;
+        Self::check_rent_exempt(account_info)
+    }
+    
+    /// Allocate a rent-exempt, program-owned PDA of `space` bytes.
+    ///
+    /// PDA addresses are public, so anyone can pre-fund one with lamports to
+    /// make `create_account` fail. Such accounts are adopted instead: topped
+    /// up to rent exemption, then allocated and assigned with the PDA seeds.
+    fn create_pda_account<'a>(
+        program_id: &Pubkey,
+        payer_info: &AccountInfo<'a>,
+        new_account_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        rent: &Rent,
+        space: usize,
+        signer_seeds: &[&[u8]],
+    ) -> ProgramResult {
+        if new_account_info.owner == program_id || !new_account_info.data_is_empty() {
+            return Err(FPPError::AccountAlreadyInitialized.into());
+        }
+        validation::check_system_program(system_program_info)?;
+        
+        let required_lamports = rent.minimum_balance(space);
+        let current_lamports = new_account_info.lamports();
+        if current_lamports == 0 {
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer_info.key,
+                    new_account_info.key,
+                    required_lamports,
+                    space as u64,
+                    program_id,
+                ),
+                &[
+                    payer_info.clone(),
+                    new_account_info.clone(),
+                    system_program_info.clone(),
+                ],
+                &[signer_seeds],
+            )?;
+            return Self::check_rent_exempt(new_account_info);
+        }
+        
+        if *new_account_info.owner != system_program::id() {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        msg!("Adopting pre-funded account {}", new_account_info.key);
+        
+        let top_up = required_lamports.saturating_sub(current_lamports);
+        if top_up > 0 {
+            invoke(
+                &system_instruction::transfer(payer_info.key, new_account_info.key, top_up),
+                &[
+                    payer_info.clone(),
+                    new_account_info.clone(),
+                    system_program_info.clone(),
+                ],
+            )?;
+        }
+        invoke_signed(
+            &system_instruction::allocate(new_account_info.key, space as u64),
+            &[new_account_info.clone(), system_program_info.clone()],
+            &[signer_seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(new_account_info.key, program_id),
+            &[new_account_info.clone(), system_program_info.clone()],
+            &[signer_seeds],
+        )?;
+        Self::check_rent_exempt(new_account_info)
+    }
+    
+    /// Reject an account the program initializes or grows unless it is rent exempt at its size
+    ///
+    /// Checked against `Rent::get()` rather than the `rent` the caller funded the account
+    /// from, so a stale or mismatched rent can't leave an account short.
+    fn check_rent_exempt(account_info: &AccountInfo) -> ProgramResult {
+        if !Rent::get()?.is_exempt(account_info.lamports(), account_info.data_len()) {
+            return Err(FPPError::NotRentExempt.into());
+        }
+        Ok(())
+    }
+    
+    /// Take the accounts `DeployTreasury` and `RecallTreasury` share, after the first two
+    fn next_treasury_deployment<'a, 'b>(
+        program_id: &Pubkey,
+        account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    ) -> Result<(TreasuryDeployment, TreasuryAccounts<'a, 'b>), ProgramError> {
+        let deployment = next_account_info(account_info_iter)?;
+        let token = next_account_info(account_info_iter)?;
+        let authority = next_account_info(account_info_iter)?;
+        let collateral = next_account_info(account_info_iter)?;
+        let mint = next_account_info(account_info_iter)?;
+        let supported_mint = next_account_info(account_info_iter)?;
+        let token_program = next_account_info(account_info_iter)?;
+        let clock = next_account_info(account_info_iter)?;
+        let reserve = lending::ReserveAccounts {
+            lending_program: next_account_info(account_info_iter)?,
+            reserve: next_account_info(account_info_iter)?,
+            liquidity_supply: next_account_info(account_info_iter)?,
+            collateral_mint: next_account_info(account_info_iter)?,
+            lending_market: next_account_info(account_info_iter)?,
+            market_authority: next_account_info(account_info_iter)?,
+        };
+        
+        validation::check_program_owned(program_id, deployment)?;
+        let state = TreasuryDeployment::try_from_slice(&deployment.data.borrow())?;
+        if !state.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        let accounts = TreasuryAccounts {
+            deployment,
+            token,
+            authority,
+            collateral,
+            mint,
+            supported_mint,
+            token_program,
+            clock,
+            reserve,
+        };
+        Ok((state, accounts))
+    }
+}
+
+/// Accounts of a treasury deployment, as `DeployTreasury` and `RecallTreasury` take them
+struct TreasuryAccounts<'a, 'b> {
+    deployment: &'a AccountInfo<'b>,
+    token: &'a AccountInfo<'b>,
+    authority: &'a AccountInfo<'b>,
+    collateral: &'a AccountInfo<'b>,
+    mint: &'a AccountInfo<'b>,
+    supported_mint: &'a AccountInfo<'b>,
+    token_program: &'a AccountInfo<'b>,
+    clock: &'a AccountInfo<'b>,
+    reserve: lending::ReserveAccounts<'a, 'b>,
+}
+
+impl TreasuryAccounts<'_, '_> {
+    /// Check the accounts belong to `deployment` of `entry`'s mint, returning the treasury
+    /// authority's bump
+    fn check(
+        &self,
+        program_id: &Pubkey,
+        protocol_state: &ProtocolState,
+        entry: &SupportedMint,
+        deployment: &TreasuryDeployment,
+    ) -> Result<u8, ProgramError> {
+        if deployment.mint != entry.mint
+            || *self.deployment.key != TreasuryDeployment::find_address(&entry.mint, program_id).0
+            || *self.collateral.key != deployment.collateral_token
+        {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        self.reserve.check(&deployment.reserve)?;
+        validation::check_token_program(self.token_program, &entry.token_program)?;
+        validation::check_treasury_authority(program_id, protocol_state, self.authority)
+    }
+}
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let instruction = FPPInstruction::try_from_slice(instruction_data)?;
+    
+    match instruction {
+        FPPInstruction::Initialize {
+            deposit_fee_rate,
+            withdrawal_fee_rate,
+            root_history_size,
+        } => {
+            msg!("Instruction: Initialize");
+            Processor::process_initialize(
+                program_id,
+                accounts,
+                deposit_fee_rate,
+                withdrawal_fee_rate,
+                root_history_size,
+            )
+        }
+        FPPInstruction::Deposit {
+            amount,
+            denomination,
+            commitments,
+            opening_proofs,
+            referrer,
+        } => {
+            msg!("Instruction: Deposit");
+            Processor::process_deposit(
+                program_id,
+                accounts,
+                amount,
+                denomination,
+                commitments,
+                opening_proofs,
+                referrer,
+            )
+        }
+        FPPInstruction::PrivacyPayment {
+            merkle_root,
             input_nullifiers,
             output_commitments,
             proof,
             ring_signature,
+            range_proof_offset,
+            encrypted_notes,
         } => {
             msg!("Instruction: Privacy Payment");
             Processor::process_privacy_payment(
                 program_id,
                 accounts,
+                merkle_root,
                 input_nullifiers,
                 output_commitments,
                 proof,
                 ring_signature,
+                range_proof_offset,
+                encrypted_notes,
             )
         }
-        FPPInstruction::RequestWithdrawal { point_ids, nullifiers } => {
+        FPPInstruction::RequestWithdrawal {
+            point_ids,
+            nullifiers,
+            nonce,
+            spend_signature,
+        } => {
             msg!("Instruction: Request Withdrawal");
-            Processor::process_request_withdrawal(program_id, accounts, point_ids, nullifiers)
+            Processor::process_request_withdrawal(
+                program_id,
+                accounts,
+                point_ids,
+                nullifiers,
+                nonce,
+                spend_signature,
+            )
         }
-        FPPInstruction::CompleteWithdrawal => {
+        FPPInstruction::CompleteWithdrawal { unwrap_sol } => {
             msg!("Instruction: Complete Withdrawal");
-            Processor::process_complete_withdrawal(program_id, accounts)
+            Processor::process_complete_withdrawal(program_id, accounts, unwrap_sol)
+        }
+        FPPInstruction::UpdateFees {
+            deposit_fee_rate,
+            withdrawal_fee_rate,
+        } => {
+            msg!("Instruction: Update Fees");
+            Processor::process_update_fees(
+                program_id,
+                accounts,
+                deposit_fee_rate,
+                withdrawal_fee_rate,
+            )
+        }
+        FPPInstruction::SetPaused { paused } => {
+            msg!("Instruction: Set Paused");
+            Processor::process_set_paused(program_id, accounts, paused)
+        }
+        FPPInstruction::CreatePool {
+            denomination,
+            circuit_id,
+        } => {
+            msg!("Instruction: Create Pool");
+            Processor::process_create_pool(program_id, accounts, denomination, circuit_id)
+        }
+        FPPInstruction::SetPoolBounds {
+            min_denomination,
+            max_denomination,
+            creation_bond,
+        } => {
+            msg!("Instruction: Set Pool Bounds");
+            Processor::process_set_pool_bounds(
+                program_id,
+                accounts,
+                min_denomination,
+                max_denomination,
+                creation_bond,
+            )
+        }
+        FPPInstruction::RegisterCircuit {
+            circuit_id,
+            tree_depth,
+            max_inputs,
+            max_outputs,
+            verifying_key_hash,
+        } => {
+            msg!("Instruction: Register Circuit");
+            Processor::process_register_circuit(
+                program_id,
+                accounts,
+                CircuitInfo {
+                    version: CircuitInfo::VERSION,
+                    is_initialized: true,
+                    circuit_id,
+                    tree_depth,
+                    max_inputs,
+                    max_outputs,
+                    verifying_key_hash,
+                    is_active: true,
+                },
+            )
+        }
+        FPPInstruction::SetCommitmentValidation { required } => {
+            msg!("Instruction: Set Commitment Validation");
+            Processor::process_set_commitment_validation(program_id, accounts, required)
+        }
+        FPPInstruction::CancelWithdrawal { permanent } => {
+            msg!("Instruction: Cancel Withdrawal");
+            Processor::process_cancel_withdrawal(program_id, accounts, permanent)
+        }
+        FPPInstruction::OpenWithdrawalBatch { batch_id } => {
+            msg!("Instruction: Open Withdrawal Batch");
+            Processor::process_open_withdrawal_batch(program_id, accounts, batch_id)
+        }
+        FPPInstruction::AppendToWithdrawal {
+            batch_id,
+            point_ids,
+            nullifiers,
+            spend_signature,
+        } => {
+            msg!("Instruction: Append To Withdrawal");
+            Processor::process_append_to_withdrawal(
+                program_id,
+                accounts,
+                batch_id,
+                point_ids,
+                nullifiers,
+                spend_signature,
+            )
+        }
+        FPPInstruction::FinalizeWithdrawal { batch_id } => {
+            msg!("Instruction: Finalize Withdrawal");
+            Processor::process_finalize_withdrawal(program_id, accounts, batch_id)
+        }
+        FPPInstruction::SetGuardian { guardian } => {
+            msg!("Instruction: Set Guardian");
+            Processor::process_set_guardian(program_id, accounts, guardian)
+        }
+        FPPInstruction::RecordSecurityEvent {
+            kind,
+            severity,
+            subject,
+            message,
+        } => {
+            msg!("Instruction: Record Security Event");
+            Processor::process_record_security_event(
+                program_id,
+                accounts,
+                kind,
+                severity,
+                subject,
+                message,
+            )
+        }
+        FPPInstruction::ResolveSecurityEvent { index } => {
+            msg!("Instruction: Resolve Security Event");
+            Processor::process_resolve_security_event(program_id, accounts, index)
+        }
+        FPPInstruction::SplitPoint {
+            nullifier,
+            output_commitments,
+            output_masses,
+            opening_proofs,
+            spend_signature,
+        } => {
+            msg!("Instruction: Split Point");
+            Processor::process_split_point(
+                program_id,
+                accounts,
+                nullifier,
+                output_commitments,
+                output_masses,
+                opening_proofs,
+                spend_signature,
+            )
+        }
+        FPPInstruction::TransferPoint {
+            new_owner,
+            new_commitment,
+            nullifier,
+            spend_signature,
+        } => {
+            msg!("Instruction: Transfer Point");
+            Processor::process_transfer_point(
+                program_id,
+                accounts,
+                new_owner,
+                new_commitment,
+                nullifier,
+                spend_signature,
+            )
+        }
+        FPPInstruction::ReportTreeHealth => {
+            msg!("Instruction: Report Tree Health");
+            Processor::process_report_tree_health(program_id, accounts)
+        }
+        FPPInstruction::RolloverTree => {
+            msg!("Instruction: Rollover Tree");
+            Processor::process_rollover_tree(program_id, accounts)
+        }
+        FPPInstruction::SetDenominations { denominations } => {
+            msg!("Instruction: Set Denominations");
+            Processor::process_set_denominations(program_id, accounts, denominations)
+        }
+        FPPInstruction::EmergencyWithdraw { unwrap_sol } => {
+            msg!("Instruction: Emergency Withdraw");
+            Processor::process_emergency_withdraw(program_id, accounts, unwrap_sol)
+        }
+        FPPInstruction::SetEmergencyPenalty { penalty_rate } => {
+            msg!("Instruction: Set Emergency Penalty");
+            Processor::process_set_emergency_penalty(program_id, accounts, penalty_rate)
+        }
+        FPPInstruction::UpdateContactBook {
+            expected_revision,
+            ciphertext,
+        } => {
+            msg!("Instruction: Update Contact Book");
+            Processor::process_update_contact_book(
+                program_id,
+                accounts,
+                expected_revision,
+                ciphertext,
+            )
+        }
+        FPPInstruction::CloseContactBook => {
+            msg!("Instruction: Close Contact Book");
+            Processor::process_close_contact_book(program_id, accounts)
+        }
+        FPPInstruction::ProposeAuthority { new_authority } => {
+            msg!("Instruction: Propose Authority");
+            Processor::process_propose_authority(program_id, accounts, new_authority)
+        }
+        FPPInstruction::AcceptAuthority => {
+            msg!("Instruction: Accept Authority");
+            Processor::process_accept_authority(program_id, accounts)
+        }
+        FPPInstruction::CreateAdminMultisig { signers, threshold } => {
+            msg!("Instruction: Create Admin Multisig");
+            Processor::process_create_admin_multisig(program_id, accounts, signers, threshold)
+        }
+        FPPInstruction::ProposeAdminAction { action } => {
+            msg!("Instruction: Propose Admin Action");
+            Processor::process_propose_admin_action(program_id, accounts, action)
+        }
+        FPPInstruction::ApproveAdminAction => {
+            msg!("Instruction: Approve Admin Action");
+            Processor::process_approve_admin_action(program_id, accounts)
+        }
+        FPPInstruction::ExecuteAdminAction => {
+            msg!("Instruction: Execute Admin Action");
+            Processor::process_execute_admin_action(program_id, accounts)
+        }
+        FPPInstruction::ApplyPendingConfig => {
+            msg!("Instruction: Apply Pending Config");
+            Processor::process_apply_pending_config(program_id, accounts)
+        }
+        FPPInstruction::SetFeeReceiver { fee_receiver } => {
+            msg!("Instruction: Set Fee Receiver");
+            Processor::process_set_fee_receiver(program_id, accounts, fee_receiver)
+        }
+        FPPInstruction::CollectFees => {
+            msg!("Instruction: Collect Fees");
+            Processor::process_collect_fees(program_id, accounts)
+        }
+        FPPInstruction::SetPausedFlags { flags } => {
+            msg!("Instruction: Set Paused Flags");
+            Processor::process_set_paused_flags(program_id, accounts, flags)
+        }
+        FPPInstruction::SetRateLimits { limits } => {
+            msg!("Instruction: Set Rate Limits");
+            Processor::process_set_rate_limits(program_id, accounts, limits)
+        }
+        FPPInstruction::SetMinExitSlots { slots } => {
+            msg!("Instruction: Set Min Exit Slots");
+            Processor::process_set_min_exit_slots(program_id, accounts, slots)
+        }
+        FPPInstruction::AddToDenyList { entry } => {
+            msg!("Instruction: Add To Deny List");
+            Processor::process_add_to_deny_list(program_id, accounts, entry)
+        }
+        FPPInstruction::RemoveFromDenyList { entry } => {
+            msg!("Instruction: Remove From Deny List");
+            Processor::process_remove_from_deny_list(program_id, accounts, entry)
+        }
+        FPPInstruction::UpdateAssociationSet {
+            flagged_root,
+            required,
+            verifying_key,
+        } => {
+            msg!("Instruction: Update Association Set");
+            Processor::process_update_association_set(
+                program_id,
+                accounts,
+                flagged_root,
+                required,
+                verifying_key,
+            )
+        }
+        FPPInstruction::ProveWithdrawalCompliance { proof } => {
+            msg!("Instruction: Prove Withdrawal Compliance");
+            Processor::process_prove_withdrawal_compliance(program_id, accounts, proof)
+        }
+        FPPInstruction::AddSupportedMint { mint, config } => {
+            msg!("Instruction: Add Supported Mint");
+            Processor::process_add_supported_mint(program_id, accounts, mint, config)
+        }
+        FPPInstruction::ResizeRootHistory { capacity } => {
+            msg!("Instruction: Resize Root History");
+            Processor::process_resize_root_history(program_id, accounts, capacity)
+        }
+        FPPInstruction::ExtendNullifierFilter => {
+            msg!("Instruction: Extend Nullifier Filter");
+            Processor::process_extend_nullifier_filter(program_id, accounts)
+        }
+        FPPInstruction::CreatePointTree {
+            max_depth,
+            max_buffer_size,
+        } => {
+            msg!("Instruction: Create Point Tree");
+            Processor::process_create_point_tree(program_id, accounts, max_depth, max_buffer_size)
+        }
+        FPPInstruction::CompressPoint {
+            nullifier,
+            spend_signature,
+        } => {
+            msg!("Instruction: Compress Point");
+            Processor::process_compress_point(program_id, accounts, nullifier, spend_signature)
+        }
+        FPPInstruction::DecompressPoint {
+            point,
+            new_commitment,
+            root,
+            nonce,
+            index,
+        } => {
+            msg!("Instruction: Decompress Point");
+            Processor::process_decompress_point(
+                program_id,
+                accounts,
+                point,
+                new_commitment,
+                root,
+                nonce,
+                index,
+            )
+        }
+        FPPInstruction::AttachPointMetadata => {
+            msg!("Instruction: Attach Point Metadata");
+            Processor::process_attach_point_metadata(program_id, accounts)
+        }
+        FPPInstruction::SetRelayerConfig { max_fee_rate } => {
+            msg!("Instruction: Set Relayer Config");
+            Processor::process_set_relayer_config(program_id, accounts, max_fee_rate)
+        }
+        FPPInstruction::RelayWithdrawal {
+            point_ids,
+            nullifiers,
+            nonce,
+            recipient,
+            relayer_fee,
+            spend_signature,
+        } => {
+            msg!("Instruction: Relay Withdrawal");
+            Processor::process_relay_withdrawal(
+                program_id,
+                accounts,
+                point_ids,
+                nullifiers,
+                nonce,
+                recipient,
+                relayer_fee,
+                spend_signature,
+            )
+        }
+        FPPInstruction::CompleteRelayedWithdrawal => {
+            msg!("Instruction: Complete Relayed Withdrawal");
+            Processor::process_complete_relayed_withdrawal(program_id, accounts)
+        }
+        FPPInstruction::DelegatedDeposit {
+            amount,
+            denomination,
+            commitments,
+            opening_proofs,
+            expiry,
+            nonce,
+            signature_offset,
+        } => {
+            msg!("Instruction: Delegated Deposit");
+            Processor::process_delegated_deposit(
+                program_id,
+                accounts,
+                amount,
+                denomination,
+                commitments,
+                opening_proofs,
+                expiry,
+                nonce,
+                signature_offset,
+            )
+        }
+        FPPInstruction::MigrateState { kind } => {
+            msg!("Instruction: Migrate State");
+            Processor::process_migrate_state(program_id, accounts, kind)
+        }
+        FPPInstruction::CloseSpentPoint => {
+            msg!("Instruction: Close Spent Point");
+            Processor::process_close_spent_point(program_id, accounts)
+        }
+        FPPInstruction::CloseWithdrawalRequest => {
+            msg!("Instruction: Close Withdrawal Request");
+            Processor::process_close_withdrawal_request(program_id, accounts)
+        }
+        FPPInstruction::CompactNullifiers { count } => {
+            msg!("Instruction: Compact Nullifiers");
+            Processor::process_compact_nullifiers(program_id, accounts, count)
+        }
+        FPPInstruction::BatchProveWithdrawalCompliance { proofs } => {
+            msg!("Instruction: Batch Prove Withdrawal Compliance");
+            Processor::process_batch_prove_withdrawal_compliance(program_id, accounts, proofs)
+        }
+        FPPInstruction::StageProof {
+            nonce,
+            payload_len,
+            chunk,
+        } => {
+            msg!("Instruction: Stage Proof");
+            Processor::process_stage_proof(program_id, accounts, nonce, payload_len, chunk)
+        }
+        FPPInstruction::VerifyStagedProof { rows } => {
+            msg!("Instruction: Verify Staged Proof");
+            Processor::process_verify_staged_proof(program_id, accounts, rows)
+        }
+        FPPInstruction::ExecuteStagedPayment { range_proof_offset } => {
+            msg!("Instruction: Execute Staged Payment");
+            Processor::process_execute_staged_payment(program_id, accounts, range_proof_offset)
+        }
+        FPPInstruction::CloseStagedProof => {
+            msg!("Instruction: Close Staged Proof");
+            Processor::process_close_staged_proof(program_id, accounts)
+        }
+        FPPInstruction::SetTreasuryDeployment {
+            reserve,
+            max_deployed_rate,
+        } => {
+            msg!("Instruction: Set Treasury Deployment");
+            Processor::process_set_treasury_deployment(
+                program_id,
+                accounts,
+                reserve,
+                max_deployed_rate,
+            )
+        }
+        FPPInstruction::DeployTreasury { amount } => {
+            msg!("Instruction: Deploy Treasury");
+            Processor::process_deploy_treasury(program_id, accounts, amount)
+        }
+        FPPInstruction::RecallTreasury { collateral_amount } => {
+            msg!("Instruction: Recall Treasury");
+            Processor::process_recall_treasury(program_id, accounts, collateral_amount)
+        }
+        FPPInstruction::SetRewardConfig { rate } => {
+            msg!("Instruction: Set Reward Config");
+            Processor::process_set_reward_config(program_id, accounts, rate)
+        }
+        FPPInstruction::FundRewards { amount } => {
+            msg!("Instruction: Fund Rewards");
+            Processor::process_fund_rewards(program_id, accounts, amount)
+        }
+        FPPInstruction::ClaimRewards => {
+            msg!("Instruction: Claim Rewards");
+            Processor::process_claim_rewards(program_id, accounts)
+        }
+        FPPInstruction::SetReferralFeeShare { share } => {
+            msg!("Instruction: Set Referral Fee Share");
+            Processor::process_set_referral_fee_share(program_id, accounts, share)
+        }
+        FPPInstruction::ClaimReferralFees => {
+            msg!("Instruction: Claim Referral Fees");
+            Processor::process_claim_referral_fees(program_id, accounts)
+        }
+        FPPInstruction::UpdateFeeTiers { tiers } => {
+            msg!("Instruction: Update Fee Tiers");
+            Processor::process_update_fee_tiers(program_id, accounts, tiers)
+        }
+        FPPInstruction::AddFeeExemption { address } => {
+            msg!("Instruction: Add Fee Exemption");
+            Processor::process_add_fee_exemption(program_id, accounts, address)
+        }
+        FPPInstruction::RemoveFeeExemption { address } => {
+            msg!("Instruction: Remove Fee Exemption");
+            Processor::process_remove_fee_exemption(program_id, accounts, address)
+        }
+        FPPInstruction::SetDepositCaps {
+            max_tvl,
+            max_user_deposit,
+        } => {
+            msg!("Instruction: Set Deposit Caps");
+            Processor::process_set_deposit_caps(program_id, accounts, max_tvl, max_user_deposit)
+        }
+        FPPInstruction::ProcessWithdrawalQueue { count } => {
+            msg!("Instruction: Process Withdrawal Queue");
+            Processor::process_withdrawal_queue(program_id, accounts, count)
+        }
+        FPPInstruction::UpdateTimelocks {
+            withdrawal_delay,
+            point_lock_duration,
+        } => {
+            msg!("Instruction: Update Timelocks");
+            Processor::process_update_timelocks(
+                program_id,
+                accounts,
+                withdrawal_delay,
+                point_lock_duration,
+            )
+        }
+        FPPInstruction::SetMassDynamics { dynamics } => {
+            msg!("Instruction: Set Mass Dynamics");
+            Processor::process_set_mass_dynamics(program_id, accounts, dynamics)
+        }
+        FPPInstruction::UpdateMass => {
+            msg!("Instruction: Update Mass");
+            Processor::process_update_mass(program_id, accounts)
+        }
+        FPPInstruction::OpenPool => {
+            msg!("Instruction: Open Pool");
+            Processor::process_open_pool(program_id, accounts)
+        }
+        FPPInstruction::PoolDeposit { commitment } => {
+            msg!("Instruction: Pool Deposit");
+            Processor::process_pool_deposit(program_id, accounts, commitment)
+        }
+        FPPInstruction::PoolWithdraw {
+            merkle_root,
+            nullifier,
+            recipient,
+            relayer_fee,
+            proof,
+        } => {
+            msg!("Instruction: Pool Withdraw");
+            Processor::process_pool_withdraw(
+                program_id,
+                accounts,
+                merkle_root,
+                nullifier,
+                recipient,
+                relayer_fee,
+                proof,
+            )
+        }
+        FPPInstruction::OpenPoolRoute { circuit_id } => {
+            msg!("Instruction: Open Pool Route");
+            Processor::process_open_pool_route(program_id, accounts, circuit_id)
+        }
+        FPPInstruction::PoolTransfer {
+            merkle_root,
+            nullifiers,
+            commitments,
+            proof,
+        } => {
+            msg!("Instruction: Pool Transfer");
+            Processor::process_pool_transfer(
+                program_id,
+                accounts,
+                merkle_root,
+                nullifiers,
+                commitments,
+                proof,
+            )
+        }
+        FPPInstruction::UpdateVerifyingKey {
+            circuit_id,
+            circuit_version,
+            verifying_key,
+        } => {
+            msg!("Instruction: Update Verifying Key");
+            Processor::process_update_verifying_key(
+                program_id,
+                accounts,
+                circuit_id,
+                circuit_version,
+                verifying_key,
+            )
+        }
+        FPPInstruction::ApplyVerifyingKey => {
+            msg!("Instruction: Apply Verifying Key");
+            Processor::process_apply_verifying_key(program_id, accounts)
+        }
+        FPPInstruction::CreateConcurrentTree {
+            max_depth,
+            max_buffer_size,
+            canopy_depth,
+        } => {
+            msg!("Instruction: Create Concurrent Tree");
+            Processor::process_create_concurrent_tree(
+                program_id,
+                accounts,
+                max_depth,
+                max_buffer_size,
+                canopy_depth,
+            )
+        }
+        FPPInstruction::VerifyConcurrentCommitment {
+            root,
+            commitment,
+            leaf_index,
+        } => {
+            msg!("Instruction: Verify Concurrent Commitment");
+            Processor::process_verify_concurrent_commitment(
+                program_id,
+                accounts,
+                root,
+                commitment,
+                leaf_index,
+            )
+        }
+        FPPInstruction::CreateAuditLog => {
+            msg!("Instruction: Create Audit Log");
+            Processor::process_create_audit_log(program_id, accounts)
+        }
+        FPPInstruction::ApplyUnpause => {
+            msg!("Instruction: Apply Unpause");
+            Processor::process_apply_unpause(program_id, accounts)
+        }
+        FPPInstruction::SetCpiGuard { enabled } => {
+            msg!("Instruction: Set CPI Guard");
+            Processor::process_set_cpi_guard(program_id, accounts, enabled)
         }
-        _ => {
-            msg!("Instruction not implemented yet");
-            Err(FPPError::InvalidInstruction.into())
+        FPPInstruction::BatchPrivacyPayment { payments } => {
+            msg!("Instruction: Batch Privacy Payment");
+            Processor::process_batch_privacy_payment(program_id, accounts, payments)
         }
     }
 }
@@ -1,29 +1,447 @@
-use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::pubkey::Pubkey;
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use bytemuck::{CheckedBitPattern, NoUninit, Pod, Zeroable};
+use shank::ShankAccount;
+use solana_program::{hash::hashv, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{
+    bloom::{self, NullifierBloom},
+    crypto::{
+        groth16::{self, Groth16Proof, Groth16VerifyingKey},
+        poseidon, ring,
+    },
+    decimals,
+    error::FPPError,
+    lending::ReserveKeys,
+    merkle::{self, TREE_DEPTH},
+    migration::{self, AccountKind, Versioned},
+    pda,
+    zero_copy::ZeroCopy,
+};
+
+/// Value of one point of mass 1, in base units (`decimals::BASE_DECIMALS`): 10 USDT,
+/// or 10 whole tokens of any other mint
+pub const POINT_VALUE: u64 = 10_000_000;
+
+/// Configurable deposit denominations held in `ProtocolState`
+pub const MAX_DENOMINATIONS: usize = 4;
+
+/// Volume tiers in `ProtocolState::fee_tiers`
+pub const MAX_FEE_TIERS: usize = 4;
+
+/// Signer slots in an `AdminMultisig`
+pub const MAX_ADMIN_SIGNERS: usize = 10;
+
+/// Default for `ProtocolState::point_lock_duration`, the seconds a new point stays locked
+pub const POINT_LOCK_DURATION: i64 = 12;
+
+/// Default seconds between a withdrawal request and its completion
+/// (`ProtocolState::withdrawal_delay`)
+pub const WITHDRAWAL_DELAY: i64 = 24 * 60 * 60;
+
+/// Seconds between queuing a fee change and when it can be applied
+pub const CONFIG_TIMELOCK: i64 = 48 * 60 * 60;
+
+/// Seconds between lifting a pause and operations resuming, so users and monitors get notice
+pub const UNPAUSE_TIMELOCK: i64 = 6 * 60 * 60;
 
 /// Main protocol state account
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+///
+/// Zero-copy: fields are grouped by alignment, widest first, so the layout has
+/// no padding and matches the Borsh encoding.
+#[derive(
+    BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone, Copy, NoUninit,
+    CheckedBitPattern,
+)]
+#[repr(C)]
 pub struct ProtocolState {
-    pub is_initialized: bool,
-    pub authority: Pubkey,
-    pub treasury: Pubkey,
-    pub usdt_mint: Pubkey,
     pub total_deposited: u64,
     pub total_withdrawn: u64,
     pub total_fees: u64,
     pub total_points: u64,
+    // Governance bounds for permissionless pool creation
+    pub min_pool_denomination: u64,
+    pub max_pool_denomination: u64,
+    pub pool_creation_bond: u64, // lamports
+    pub security_event_count: u64,
+    /// Deposit denominations, multiples of `POINT_VALUE`; zero slots are unused
+    pub denominations: [u64; MAX_DENOMINATIONS],
+    /// Slots a point must age before it can be spent or withdrawn
+    pub min_exit_slots: u64,
+    /// Queued fee change, applied by `ApplyPendingConfig` once it takes effect
+    pub pending_config: PendingConfig,
+    pub rate_limits: RateLimits,
+    pub authority: Pubkey,
+    /// Treasury authority PDA (`find_treasury_authority`)
+    pub treasury: Pubkey,
+    pub usdt_mint: Pubkey,
+    /// Records security events; defaults to the authority
+    pub guardian: Pubkey,
+    /// Proposed next authority, or the default pubkey when none is pending
+    pub pending_authority: Pubkey,
+    /// Owner of the token account `CollectFees` sweeps accrued fees to
+    pub fee_receiver: Pubkey,
+    /// Owner of the USDT mint: classic SPL Token or Token-2022
+    pub token_program: Pubkey,
     pub deposit_fee_rate: u16,  // basis points (100 = 1%)
     pub withdrawal_fee_rate: u16,
-    pub is_paused: bool,
+    /// Extra fee for skipping the withdrawal delay, in basis points
+    pub emergency_penalty_rate: u16,
+    pub is_initialized: bool,
+    /// `PAUSE_*` bits; each halts one kind of operation
+    pub paused_flags: u8,
+    /// Deposits must prove knowledge of each commitment's secret key
+    pub require_commitment_openings: bool,
+    pub version: u8,
+    /// Share of a referred deposit's fee owed to the referrer, in basis points; taken from
+    /// padding, so layout 1 accounts read it as zero
+    pub referral_fee_share: u16,
+    /// Set by `CreateAuditLog`; admin instructions then append to the `AuditLog`. Taken from
+    /// padding like `referral_fee_share`
+    pub has_audit_log: bool,
+    /// `Deposit` and `RequestWithdrawal` must be top-level instructions rather than CPIs, so a
+    /// flash-loan wrapper can't borrow, deposit and withdraw within one instruction. Taken from
+    /// padding, set with `SetCpiGuard`
+    pub reject_cpi: bool,
+    pub _padding: [u8; 2],
+    /// Added by layout 2; layout 1 ends with `_padding`
+    pub rewards: RewardConfig,
+    /// Reduced rates for large deposits and withdrawals, added by layout 3
+    pub fee_tiers: [FeeTier; MAX_FEE_TIERS],
+    /// Deposits not yet paid out, across every mint; layout 4 onwards
+    pub value_locked: u64,
+    /// Ceiling on `value_locked`, or zero for none
+    pub max_tvl: u64,
+    /// Ceiling on what one user may deposit in total (`UserActivity::total_deposited`), or
+    /// zero for none
+    pub max_user_deposit: u64,
+    /// Seconds a withdrawal request waits before it can complete; layout 5 onwards
+    pub withdrawal_delay: i64,
+    /// Seconds a new point stays locked
+    pub point_lock_duration: i64,
+    /// Accretion and decay of point value, added by layout 6; all zero leaves points as
+    /// deposited
+    pub mass_dynamics: MassDynamics,
+    /// Pause flags to lift once `UNPAUSE_TIMELOCK` has passed; layout 7 onwards
+    pub pending_unpause: PendingUnpause,
 }
 
 impl ProtocolState {
-    pub const LEN: usize = 1 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 2 + 2 + 1;
+    pub const LEN: usize = Self::V6_LEN + PendingUnpause::LEN;
+    
+    /// Size of layout 6, before `pending_unpause`
+    pub const V6_LEN: usize = Self::V5_LEN + MassDynamics::LEN;
+    
+    /// Size of layout 5, before `mass_dynamics`
+    pub const V5_LEN: usize = Self::V4_LEN + 8 * 2;
+    
+    /// Size of layout 4, before `withdrawal_delay`
+    pub const V4_LEN: usize = Self::V3_LEN + 8 * 3;
+    
+    /// Size of layout 3, before `value_locked`
+    pub const V3_LEN: usize = Self::V2_LEN + FeeTier::LEN * MAX_FEE_TIERS;
+    
+    /// Size of layout 2, before `fee_tiers`
+    pub const V2_LEN: usize = Self::V1_LEN + RewardConfig::LEN;
+    
+    /// Size of layout 1, before `rewards`
+    pub const V1_LEN: usize = 8 * 8
+        + 8 * MAX_DENOMINATIONS
+        + 8
+        + PendingConfig::LEN
+        + RateLimits::LEN
+        + 32 * 7
+        + 2 * 3
+        + 1
+        + 1
+        + 1
+        + 1
+        + 2
+        + 4;
+    
+    /// `version` took the first byte of what was `_padding`
+    pub const VERSION_OFFSET: usize = Self::V1_LEN - 7;
+    
+    pub const DEFAULT_MIN_POOL_DENOMINATION: u64 = 1_000_000; // 1 USDT
+    pub const DEFAULT_MAX_POOL_DENOMINATION: u64 = 100_000_000_000; // 100,000 USDT
+    pub const DEFAULT_POOL_CREATION_BOND: u64 = 1_000_000_000; // 1 SOL
+    
+    /// 10, 100 and 1,000 USDT
+    pub const DEFAULT_DENOMINATIONS: [u64; MAX_DENOMINATIONS] =
+        [POINT_VALUE, 10 * POINT_VALUE, 100 * POINT_VALUE, 0];
+    
+    pub const MAX_FEE_RATE: u16 = 500; // 5%
+    
+    /// Rejects spending points in the slot they were deposited
+    pub const DEFAULT_MIN_EXIT_SLOTS: u64 = 1;
+    pub const MAX_MIN_EXIT_SLOTS: u64 = 9_000; // about an hour
+    
+    pub const DEFAULT_EMERGENCY_PENALTY_RATE: u16 = 500; // 5%
+    pub const MAX_EMERGENCY_PENALTY_RATE: u16 = 5000;
+    
+    pub const MAX_REFERRAL_FEE_SHARE: u16 = 5000;
+    
+    /// Bounds on `withdrawal_delay`: long enough to react to a bad withdrawal, short enough
+    /// that funds aren't stuck for weeks
+    pub const MIN_WITHDRAWAL_DELAY: i64 = 60;
+    pub const MAX_WITHDRAWAL_DELAY: i64 = 7 * 24 * 60 * 60;
+    /// A zero `point_lock_duration` leaves only `min_exit_slots` between deposit and spend
+    pub const MAX_POINT_LOCK_DURATION: i64 = 60 * 60;
+    
+    // `paused_flags` bits
+    pub const PAUSE_DEPOSITS: u8 = 1 << 0;
+    /// Privacy payments and the point operations that move value between points
+    pub const PAUSE_PRIVACY_PAYMENTS: u8 = 1 << 1;
+    pub const PAUSE_WITHDRAWALS: u8 = 1 << 2;
+    pub const PAUSE_ALL: u8 =
+        Self::PAUSE_DEPOSITS | Self::PAUSE_PRIVACY_PAYMENTS | Self::PAUSE_WITHDRAWALS;
+    
+    /// PDA seed of the protocol state itself
+    pub const SEED: &'static [u8] = b"protocol-state";
+    
+    /// PDA seed of the treasury authority, which owns the treasury token account
+    pub const TREASURY_SEED: &'static [u8] = b"treasury";
+    
+    pub fn find_treasury_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::TREASURY_SEED], program_id)
+    }
+    
+    /// PDA seed of the authority that owns the rewards vault, kept apart from the treasury
+    pub const REWARDS_SEED: &'static [u8] = b"rewards";
+    
+    pub fn find_rewards_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::REWARDS_SEED], program_id)
+    }
+    
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.paused_flags & flag != 0
+    }
+    
+    pub fn is_denomination(&self, denomination: u64) -> bool {
+        denomination != 0 && self.denominations.contains(&denomination)
+    }
+    
+    /// Deposit fee rate for `amount` base units, given the mint's own rate
+    pub fn deposit_fee_rate_for(&self, amount: u64, mint_rate: u16) -> u16 {
+        self.fee_tier_for(amount)
+            .map_or(mint_rate, |tier| tier.deposit_fee_rate.min(mint_rate))
+    }
+    
+    /// Withdrawal fee rate for `amount` base units, given the mint's own rate
+    pub fn withdrawal_fee_rate_for(&self, amount: u64, mint_rate: u16) -> u16 {
+        self.fee_tier_for(amount)
+            .map_or(mint_rate, |tier| tier.withdrawal_fee_rate.min(mint_rate))
+    }
+    
+    /// Take a payout of `amount` off `value_locked`
+    /// 
+    /// Saturates: deposits of other mints from before layout 4 were never counted in.
+    pub fn release_value(&mut self, amount: u64) {
+        self.value_locked = self.value_locked.saturating_sub(amount);
+    }
+    
+    /// Whether `amount` more can be deposited without passing `max_tvl`
+    pub fn accepts_value(&self, amount: u64) -> bool {
+        self.max_tvl == 0 || self.value_locked.saturating_add(amount) <= self.max_tvl
+    }
+    
+    /// Highest tier `amount` reaches
+    fn fee_tier_for(&self, amount: u64) -> Option<&FeeTier> {
+        self.fee_tiers
+            .iter()
+            .take_while(|tier| tier.is_used())
+            .filter(|tier| amount >= tier.min_amount)
+            .last()
+    }
+}
+
+/// Fee rates waiting out `CONFIG_TIMELOCK`
+#[derive(
+    BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, Default, PartialEq, Pod,
+    Zeroable,
+)]
+#[repr(C)]
+pub struct PendingConfig {
+    /// Zero when no change is queued
+    pub effective_at: i64,
+    pub deposit_fee_rate: u16,
+    pub withdrawal_fee_rate: u16,
+    pub _padding: [u8; 4],
+}
+
+impl PendingConfig {
+    pub const LEN: usize = 8 + 2 + 2 + 4;
+    
+    pub fn is_pending(&self) -> bool {
+        self.effective_at != 0
+    }
+}
+
+/// Pause flags waiting out `UNPAUSE_TIMELOCK`, applied by `ApplyUnpause`
+#[derive(
+    BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, Default, PartialEq, Pod,
+    Zeroable,
+)]
+#[repr(C)]
+pub struct PendingUnpause {
+    /// Zero when nothing is queued
+    pub effective_at: i64,
+    /// `ProtocolState::paused_flags` once applied; only ever clears bits
+    pub paused_flags: u8,
+    pub _padding: [u8; 7],
+}
+
+impl PendingUnpause {
+    pub const LEN: usize = 8 + 1 + 7;
+    
+    pub fn is_pending(&self) -> bool {
+        self.effective_at != 0
+    }
+}
+
+/// Per-user deposit and withdrawal limits, tracked in `UserActivity`; zero limits aren't enforced
+#[derive(
+    BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, Default, PartialEq, Pod,
+    Zeroable,
+)]
+#[repr(C)]
+pub struct RateLimits {
+    /// Window length in seconds
+    pub window: i64,
+    pub max_deposit_volume: u64,
+    pub max_withdrawal_volume: u64,
+    pub max_deposits: u32,
+    pub max_withdrawals: u32,
+}
+
+impl RateLimits {
+    pub const LEN: usize = 8 + 8 + 8 + 4 + 4;
+    
+    pub const DEFAULT_WINDOW: i64 = 24 * 60 * 60;
+}
+
+/// Fee rates for deposits and withdrawals of at least `min_amount`
+///
+/// A tier only ever lowers fees: a mint whose own rate is already lower keeps it.
+#[derive(
+    BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, Default, PartialEq, Pod,
+    Zeroable,
+)]
+#[repr(C)]
+pub struct FeeTier {
+    /// Threshold in base units; zero marks an unused slot
+    pub min_amount: u64,
+    pub deposit_fee_rate: u16,
+    pub withdrawal_fee_rate: u16,
+    pub _padding: [u8; 4],
+}
+
+impl FeeTier {
+    pub const LEN: usize = 8 + 2 + 2 + 4;
+    
+    pub fn is_used(&self) -> bool {
+        self.min_amount != 0
+    }
+    
+    /// Used tiers come first, with rising thresholds and rates within `MAX_FEE_RATE`
+    pub fn is_valid_table(tiers: &[FeeTier; MAX_FEE_TIERS]) -> bool {
+        let used = tiers.iter().take_while(|tier| tier.is_used()).count();
+        tiers[used..].iter().all(|tier| *tier == FeeTier::default())
+            && tiers[..used].windows(2).all(|pair| pair[0].min_amount < pair[1].min_amount)
+            && tiers.iter().all(|tier| {
+                tier.deposit_fee_rate <= ProtocolState::MAX_FEE_RATE
+                    && tier.withdrawal_fee_rate <= ProtocolState::MAX_FEE_RATE
+            })
+    }
+}
+
+/// How held points gain value over time and dormant ones lose it, settled by `UpdateMass`
+///
+/// A point accretes from `maturity` seconds after its creation until it turns dormant at
+/// `dormancy`, then decays. Rates are basis points of the point's principal per year.
+#[derive(
+    BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, Default, PartialEq, Pod,
+    Zeroable,
+)]
+#[repr(C)]
+pub struct MassDynamics {
+    pub maturity: i64,
+    /// Age at which a point counts as dormant; zero for never
+    pub dormancy: i64,
+    pub accretion_rate: u16,
+    pub decay_rate: u16,
+    pub _padding: [u8; 4],
+}
+
+impl MassDynamics {
+    pub const LEN: usize = 8 + 8 + 2 + 2 + 4;
+    
+    pub const MAX_ACCRETION_RATE: u16 = 1000; // 10% a year
+    pub const MAX_DECAY_RATE: u16 = 5000;
+    
+    const YEAR: i128 = 365 * 24 * 60 * 60;
+    
+    pub fn is_valid(&self) -> bool {
+        self.maturity >= 0
+            && (self.dormancy == 0 || self.dormancy > self.maturity)
+            && self.accretion_rate <= Self::MAX_ACCRETION_RATE
+            && self.decay_rate <= Self::MAX_DECAY_RATE
+            && self._padding == [0u8; 4]
+    }
+    
+    /// Base units gained and lost by `principal` in a point created at `created_at`, over the
+    /// seconds from `from` to `to`
+    pub fn change(&self, principal: u64, created_at: i64, from: i64, to: i64) -> (u64, u64) {
+        let matures_at = created_at.saturating_add(self.maturity);
+        let dormant_at = match self.dormancy {
+            0 => i64::MAX,
+            dormancy => created_at.saturating_add(dormancy),
+        };
+        let overlap = |start: i64, end: i64| (to.min(end) as i128 - from.max(start) as i128).max(0);
+        let share = |rate: u16, seconds: i128| {
+            (principal as i128 * rate as i128 * seconds / (10000 * Self::YEAR)) as u64
+        };
+        (
+            share(self.accretion_rate, overlap(matures_at, dormant_at)),
+            share(self.decay_rate, overlap(dormant_at, i64::MAX)),
+        )
+    }
+}
+
+/// Loyalty reward parameters and the funding of the rewards pool
+#[derive(
+    BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, Default, PartialEq, Pod,
+    Zeroable,
+)]
+#[repr(C)]
+pub struct RewardConfig {
+    /// Reward tokens, in the reward mint's units, accrued per day by each whole token
+    /// (`10^BASE_DECIMALS` base units) a user has deposited; zero stops accrual
+    pub rate: u64,
+    /// Tokens funded into the vault and not yet claimed
+    pub pool_balance: u64,
+    pub total_claimed: u64,
+    /// Mint rewards are paid in; the default pubkey until configured
+    pub mint: Pubkey,
+    /// Token account of `mint` owned by the rewards authority PDA
+    pub vault: Pubkey,
+}
+
+impl RewardConfig {
+    pub const LEN: usize = 8 + 8 + 8 + 32 + 32;
+    
+    /// Divides `balance * rate * seconds` down to reward tokens
+    pub const RATE_DENOMINATOR: u128 =
+        10u128.pow(decimals::BASE_DECIMALS as u32) * 24 * 60 * 60;
+    
+    pub fn is_configured(&self) -> bool {
+        self.vault != Pubkey::default()
+    }
 }
 
 /// Floating Point NFT state
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
 pub struct FloatingPoint {
+    pub version: u8,
     pub is_initialized: bool,
     pub commitment: [u8; 32],
     pub created_at: i64,
@@ -31,15 +449,176 @@ pub struct FloatingPoint {
     pub is_active: bool,
     pub creator: Pubkey,
     pub locked_until: i64,
+    /// Slot of the deposit the point's value entered through
+    pub created_slot: u64,
+    /// Mint the point's value was deposited in, and is withdrawn in
+    pub mint: Pubkey,
+    /// Account that funded the point's rent, refunded by `CloseSpentPoint`
+    pub rent_payer: Pubkey,
+    /// Base units gained through accretion less those lost to decay, paid on top of the
+    /// point's principal; layout 3 onwards
+    pub accrued: i64,
+    /// When `UpdateMass` last settled the point, or zero if it never has
+    pub mass_updated_at: i64,
 }
 
 impl FloatingPoint {
-    pub const LEN: usize = 1 + 32 + 8 + 8 + 1 + 32 + 8;
+    pub const LEN: usize = Self::V2_LEN + 8 + 8;
+    
+    /// Size of layout 2, before `accrued`
+    pub const V2_LEN: usize = 1 + 1 + 32 + 8 + 8 + 1 + 32 + 8 + 8 + 32 + 32;
+    
+    /// Size of layout 1, before `rent_payer`
+    pub const V1_LEN: usize = Self::V2_LEN - 32;
+    
+    const CREATOR_OFFSET: usize = 1 + 1 + 32 + 8 + 8 + 1;
+    
+    /// PDA seed prefix; the full seeds are `[SEED_PREFIX, commitment]`
+    pub const SEED_PREFIX: &'static [u8] = b"point";
+    
+    /// Outputs per `SplitPoint`
+    pub const MAX_SPLIT_OUTPUTS: usize = 8;
+    
+    pub fn find_address(commitment: &[u8; 32], program_id: &Pubkey) -> (Pubkey, u8) {
+        pda::find_point_address(commitment, program_id)
+    }
+    
+    /// What the point's spend signature binds when `holder` splits it into the outputs
+    pub fn split_message(
+        holder: &Pubkey,
+        output_commitments: &[[u8; 32]],
+        output_masses: &[u64],
+    ) -> [u8; 32] {
+        let masses: Vec<u8> = output_masses.iter().flat_map(|mass| mass.to_le_bytes()).collect();
+        ring::spend_message(b"split", &[holder.as_ref(), &output_commitments.concat(), &masses])
+    }
+    
+    /// What the point's spend signature binds when `holder` hands it to `new_owner`
+    pub fn transfer_message(
+        holder: &Pubkey,
+        new_owner: &Pubkey,
+        new_commitment: &[u8; 32],
+    ) -> [u8; 32] {
+        ring::spend_message(b"transfer", &[holder.as_ref(), new_owner.as_ref(), new_commitment])
+    }
+    
+    /// What the point's spend signature binds when `holder` compresses it into `merkle_tree`
+    pub fn compress_message(holder: &Pubkey, merkle_tree: &Pubkey) -> [u8; 32] {
+        ring::spend_message(b"compress", &[holder.as_ref(), merkle_tree.as_ref()])
+    }
+    
+    /// Whether `min_exit_slots` have passed since the point's deposit
+    pub fn can_exit_at(&self, slot: u64, min_exit_slots: u64) -> bool {
+        slot >= self.created_slot.saturating_add(min_exit_slots)
+    }
+    
+    /// Whether the point can never hold value again
+    ///
+    /// Points give up their mass when split, burned by a permanent cancellation, or paid out
+    /// by a withdrawal request that `CloseWithdrawalRequest` has settled. A withdrawn point
+    /// keeps its mass while the request is open, as cancelling it reactivates the point.
+    pub fn is_spent(&self) -> bool {
+        self.is_initialized && !self.is_active && self.mass == 0
+    }
+    
+    /// Value the point was deposited with
+    pub fn principal(&self) -> Option<u64> {
+        self.mass.checked_mul(POINT_VALUE)
+    }
+    
+    /// What withdrawing the point pays: its principal adjusted by accretion and decay
+    pub fn value(&self) -> Option<u64> {
+        let principal = self.principal()?;
+        if self.accrued >= 0 {
+            principal.checked_add(self.accrued as u64)
+        } else {
+            Some(principal.saturating_sub(self.accrued.unsigned_abs()))
+        }
+    }
+}
+
+/// Point fields kept in a compressed point NFT, enough to restore the `FloatingPoint`
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq)]
+pub struct CompressedPoint {
+    pub commitment: [u8; 32],
+    pub mass: u64,
+    pub mint: Pubkey,
+    pub created_at: i64,
+    pub created_slot: u64,
+}
+
+impl CompressedPoint {
+    pub const LEN: usize = 32 + 8 + 32 + 8 + 8;
+}
+
+impl From<&FloatingPoint> for CompressedPoint {
+    fn from(point: &FloatingPoint) -> Self {
+        Self {
+            commitment: point.commitment,
+            mass: point.mass,
+            mint: point.mint,
+            created_at: point.created_at,
+            created_slot: point.created_slot,
+        }
+    }
+}
+
+/// Bubblegum tree that floating points are compressed into
+/// 
+/// The account is the tree's creator, so only this program can mint into it.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct PointTree {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub merkle_tree: Pubkey,
+    /// Points currently held as compressed NFTs
+    pub compressed_points: u64,
+}
+
+impl PointTree {
+    pub const LEN: usize = 1 + 1 + 32 + 8;
+    
+    pub const SEED: &'static [u8] = b"point-tree";
+    
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+/// Authority and settings of the spl-account-compression tree that deposits append to
+/// 
+/// The Merkle tree account itself is allocated by the creator at
+/// `concurrent_tree::account_len`, so its canopy is fixed from the start.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct ConcurrentTree {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub merkle_tree: Pubkey,
+    pub max_depth: u32,
+    pub max_buffer_size: u32,
+    pub canopy_depth: u32,
+    /// Leaves appended so far; the next commitment lands at this index
+    pub leaf_count: u64,
+}
+
+impl ConcurrentTree {
+    pub const LEN: usize = 1 + 1 + 32 + 4 + 4 + 4 + 8;
+    
+    pub const SEED: &'static [u8] = b"concurrent-tree";
+    
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+    
+    pub fn capacity(&self) -> u64 {
+        1 << self.max_depth
+    }
 }
 
 /// Withdrawal request state
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
 pub struct WithdrawalRequest {
+    pub version: u8,
     pub is_initialized: bool,
     pub requester: Pubkey,
     pub amount: u64,
@@ -47,21 +626,2465 @@ pub struct WithdrawalRequest {
     pub unlock_time: i64,
     pub completed: bool,
     pub cancelled: bool,
+    pub point_count: u32,
+    /// Hash chain over the withdrawn points and their nullifiers, checked on cancellation
+    pub points_hash: [u8; 32],
+    /// Flagged set root the withdrawal was last proven against (zero if never)
+    pub compliance_root: [u8; 32],
+    /// Mint shared by every withdrawn point, paid out on completion
+    pub mint: Pubkey,
+    /// Requester-chosen nonce the account address is derived from
+    pub nonce: u64,
+    /// Relayer that submitted a `RelayWithdrawal` and is paid `relayer_fee` on completion;
+    /// the default pubkey when the requester submitted it
+    pub relayer: Pubkey,
+    pub relayer_fee: u64,
 }
 
 impl WithdrawalRequest {
-    pub const LEN: usize = 1 + 32 + 8 + 8 + 8 + 1 + 1;
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 8 + 8 + 1 + 1 + 4 + 32 + 32 + 32 + 8 + 32 + 8;
+    
+    /// PDA seed prefix; the full seeds are `[SEED_PREFIX, requester, nonce]`
+    pub const SEED_PREFIX: &'static [u8] = b"withdrawal-request";
+    
+    /// Address this request must live at (`pda::find_withdrawal_request`)
+    pub fn address(&self, program_id: &Pubkey) -> Pubkey {
+        pda::find_withdrawal_request(&self.requester, self.nonce, program_id).0
+    }
+    
+    /// Fold one withdrawn point into a `points_hash` chain
+    pub fn chain_points_hash(prev: &[u8; 32], point: &Pubkey, nullifier: &[u8; 32]) -> [u8; 32] {
+        hashv(&[prev, point.as_ref(), nullifier]).to_bytes()
+    }
+    
+    /// What the points' spend signature binds when they are withdrawn into `request`
+    ///
+    /// The request's address fixes its requester, who is paid, and its nonce.
+    pub fn spend_message(request: &Pubkey) -> [u8; 32] {
+        ring::spend_message(b"withdraw", &[request.as_ref()])
+    }
+    
+    /// What the points' spend signature binds when a relayer withdraws them: `relay_hash`
+    pub fn relay_spend_message(relay_hash: &[u8; 32]) -> [u8; 32] {
+        ring::spend_message(b"relay", &[relay_hash])
+    }
+    
+    /// Whether the request was opened by a relayer and must complete through one
+    pub fn is_relayed(&self) -> bool {
+        self.relayer != Pubkey::default()
+    }
+    
+    /// What a relay proof commits to besides the points: who is paid, and the relayer's cut
+    pub fn relay_hash(
+        recipient: &Pubkey,
+        relayer: &Pubkey,
+        relayer_fee: u64,
+        nonce: u64,
+    ) -> [u8; 32] {
+        hashv(&[
+            recipient.as_ref(),
+            relayer.as_ref(),
+            &relayer_fee.to_le_bytes(),
+            &nonce.to_le_bytes(),
+        ])
+        .to_bytes()
+    }
+}
+
+/// Withdrawal assembled over several transactions before it is finalized
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct WithdrawalBatch {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub requester: Pubkey,
+    pub batch_id: u64,
+    pub point_count: u32,
+    pub amount: u64,
+    pub opened_at: i64,
+    /// Carried into `WithdrawalRequest::points_hash` on finalization
+    pub points_hash: [u8; 32],
+    /// Set by the first append; every later point must share it
+    pub mint: Pubkey,
+}
+
+impl WithdrawalBatch {
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 4 + 8 + 8 + 32 + 32;
+    
+    pub const SEED_PREFIX: &'static [u8] = b"withdrawal-batch";
+    
+    /// Points per `AppendToWithdrawal`, keeping each transaction within packet and CU limits
+    pub const MAX_POINTS_PER_APPEND: usize = 8;
+    
+    pub fn find_address(requester: &Pubkey, batch_id: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, requester.as_ref(), &batch_id.to_le_bytes()],
+            program_id,
+        )
+    }
 }
 
 /// Nullifier tracking account
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
 pub struct NullifierSet {
+    pub version: u8,
     pub is_initialized: bool,
     pub nullifier: [u8; 32],
     pub used: bool,
     pub timestamp: i64,
+    /// Account that funded the record's rent; the default pubkey for records written before
+    /// layout 2, whose rent goes to the fee receiver on compaction
+    pub payer: Pubkey,
+    /// Withdrawal request that spent the nullifier, which may still release it on
+    /// cancellation; the default pubkey for every other spend
+    pub withdrawal_request: Pubkey,
 }
 
 impl NullifierSet {
-    pub const LEN: usize = 1 + 32 + 1 + 8;
+    pub const LEN: usize = 1 + 1 + 32 + 1 + 8 + 32 + 32;
+    
+    /// Size of layout 1, before `payer` and `withdrawal_request`
+    pub const V1_LEN: usize = Self::LEN - 64;
+    
+    /// PDA seed prefix; the full seeds are `[SEED_PREFIX, nullifier]`
+    pub const SEED_PREFIX: &'static [u8] = b"nullifier";
+    
+    pub fn find_address(nullifier: &[u8; 32], program_id: &Pubkey) -> (Pubkey, u8) {
+        pda::find_nullifier(nullifier, program_id)
+    }
+}
+
+/// Bloom filter over every nullifier spent since the filter was completed
+/// 
+/// The account holds this header followed by `NUM_BYTES` bytes of filter bits. Spends test
+/// and set the bits in place, so the account is never deserialized as a whole. A miss means
+/// the nullifier has not been spent since `created_epoch`; `NullifierSet` PDAs stay the
+/// exact record.
+#[repr(C)]
+#[derive(
+    BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone, Copy, NoUninit,
+    CheckedBitPattern,
+)]
+pub struct NullifierFilter {
+    /// Nullifiers inserted so far, to judge the false-positive rate
+    pub count: u64,
+    pub created_epoch: u64,
+    pub num_hashes: u8,
+    /// Set once the account has grown to `LEN`; spends ignore the filter until then
+    pub is_initialized: bool,
+    pub version: u8,
+    pub _padding: [u8; 5],
+}
+
+impl NullifierFilter {
+    pub const SEED: &'static [u8] = b"nullifier-filter";
+    
+    pub const HEADER_LEN: usize = 8 + 8 + 1 + 1 + 1 + 5;
+    
+    pub const VERSION_OFFSET: usize = Self::HEADER_LEN - 6;
+    
+    /// 2^19 bits, about a 1% false-positive rate after 55k spends
+    pub const NUM_BYTES: usize = 64 * 1024;
+    pub const NUM_HASHES: u8 = 7;
+    
+    pub const LEN: usize = Self::HEADER_LEN + Self::NUM_BYTES;
+    
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+    
+    /// Split account data into the header and the filter bits
+    pub fn split_mut(data: &mut [u8]) -> Result<(&mut Self, &mut [u8]), ProgramError> {
+        let data = data
+            .get_mut(..Self::LEN)
+            .ok_or(ProgramError::AccountDataTooSmall)?;
+        let (header, bits) = data.split_at_mut(Self::HEADER_LEN);
+        Ok((Self::load_mut(header)?, bits))
+    }
+    
+    /// Record `nullifier`, returning whether it may have been inserted before
+    pub fn insert(&mut self, bits: &mut [u8], nullifier: &[u8; 32]) -> bool {
+        let hit = bloom::contains_bytes(bits, self.num_hashes, nullifier);
+        bloom::insert_bytes(bits, self.num_hashes, nullifier);
+        self.count = self.count.saturating_add(1);
+        hit
+    }
+    
+    /// Nullifiers moved out of their `NullifierSet` PDAs by `CompactNullifiers` before it
+    /// wrote to `NullifierShard`s
+    ///
+    /// They are kept sorted after the filter bits, 32 bytes each, and their bits are set
+    /// in the filter, so only a filter hit needs to search them.
+    pub fn compacted(data: &[u8]) -> &[u8] {
+        data.get(Self::LEN..).unwrap_or_default()
+    }
+    
+    /// Whether `nullifier` is among the compacted nullifiers in the account `data`
+    pub fn is_compacted(data: &[u8], nullifier: &[u8; 32]) -> bool {
+        search_sorted(Self::compacted(data), nullifier).is_ok()
+    }
+    
+    /// Decode a completed filter account for client-side checks
+    pub fn unpack(data: &[u8]) -> Result<(Self, NullifierBloom), ProgramError> {
+        let data = data.get(..Self::LEN).ok_or(ProgramError::AccountDataTooSmall)?;
+        // Borsh rather than `load`, as fetched account data needn't be 8-byte aligned
+        let header = Self::try_from_slice(&data[..Self::HEADER_LEN])?;
+        if !header.is_initialized {
+            return Err(FPPError::AccountNotInitialized.into());
+        }
+        let filter = NullifierBloom::from_le_bytes(&data[Self::HEADER_LEN..], header.num_hashes);
+        Ok((header, filter))
+    }
+}
+
+
+/// Position of `key` among the sorted `key.len()`-byte entries, or where it would be inserted
+fn search_sorted(entries: &[u8], key: &[u8]) -> Result<usize, usize> {
+    let len = key.len();
+    let (mut low, mut high) = (0, entries.len() / len);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match entries[mid * len..mid * len + len].cmp(key) {
+            std::cmp::Ordering::Less => low = mid + 1,
+            std::cmp::Ordering::Greater => high = mid,
+            std::cmp::Ordering::Equal => return Ok(mid),
+        }
+    }
+    Err(low)
+}
+
+/// Insert `key` into the sorted entries, whose last `key.len()` bytes must be free space
+/// just added for it
+fn insert_sorted(entries: &mut [u8], key: &[u8]) -> Result<(), ProgramError> {
+    let len = key.len();
+    if entries.len() % len != 0 || entries.is_empty() {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    let stored = entries.len() - len;
+    let index = search_sorted(&entries[..stored], key)
+        .err()
+        .ok_or(FPPError::NullifierAlreadyUsed)?;
+    entries.copy_within(index * len..stored, index * len + len);
+    entries[index * len..index * len + len].copy_from_slice(key);
+    Ok(())
+}
+
+/// Compacted nullifiers sharing their first `PREFIX_LEN` bytes
+///
+/// The account holds this header followed by the sorted remainders of its nullifiers,
+/// `ENTRY_LEN` bytes each; the prefix is implied by the address. `CompactNullifiers` grows
+/// it by one entry per nullifier, so no single account has to hold every spend.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct NullifierShard {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub prefix: [u8; 2],
+    pub count: u32,
+}
+
+impl NullifierShard {
+    pub const HEADER_LEN: usize = 1 + 1 + Self::PREFIX_LEN + 4;
+    
+    /// 65,536 shards, each reaching the 10 MiB account limit after about 350k nullifiers
+    pub const PREFIX_LEN: usize = 2;
+    pub const ENTRY_LEN: usize = 32 - Self::PREFIX_LEN;
+    
+    /// PDA seed prefix; the full seeds are `[SEED_PREFIX, prefix]`
+    pub const SEED_PREFIX: &'static [u8] = b"nullifier-shard";
+    
+    pub fn prefix_of(nullifier: &[u8; 32]) -> [u8; Self::PREFIX_LEN] {
+        let mut prefix = [0u8; Self::PREFIX_LEN];
+        prefix.copy_from_slice(&nullifier[..Self::PREFIX_LEN]);
+        prefix
+    }
+    
+    /// Shard holding `nullifier` once it is compacted
+    pub fn find_address(nullifier: &[u8; 32], program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, &Self::prefix_of(nullifier)],
+            program_id,
+        )
+    }
+    
+    /// Decode the header of the account `data`, ignoring its entries
+    pub fn unpack_header(data: &[u8]) -> Result<Self, ProgramError> {
+        let header = data
+            .get(..Self::HEADER_LEN)
+            .ok_or(ProgramError::AccountDataTooSmall)?;
+        Ok(Self::try_from_slice(header)?)
+    }
+    
+    /// Whether `nullifier` is stored in the shard account `data`
+    pub fn contains(data: &[u8], nullifier: &[u8; 32]) -> bool {
+        let entries = data.get(Self::HEADER_LEN..).unwrap_or_default();
+        search_sorted(entries, &nullifier[Self::PREFIX_LEN..]).is_ok()
+    }
+    
+    /// Insert `nullifier` into the shard account `data`, whose last `ENTRY_LEN` bytes must be
+    /// free space just added for it
+    pub fn insert(data: &mut [u8], nullifier: &[u8; 32]) -> Result<(), ProgramError> {
+        let mut header = Self::unpack_header(data)?;
+        if header.prefix != Self::prefix_of(nullifier) {
+            return Err(FPPError::InvalidAccount.into());
+        }
+        insert_sorted(&mut data[Self::HEADER_LEN..], &nullifier[Self::PREFIX_LEN..])?;
+        header.count = header.count.saturating_add(1);
+        header.serialize(&mut &mut data[..Self::HEADER_LEN])?;
+        Ok(())
+    }
+}
+
+
+/// Incremental Merkle tree of every deposited commitment
+///
+/// Zero-copy like `ProtocolState`, so an insert rewrites only the touched subtrees.
+#[derive(
+    BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone, Copy, NoUninit,
+    CheckedBitPattern,
+)]
+#[repr(C)]
+pub struct CommitmentTree {
+    pub next_index: u64,
+    // Deposit-rate sampling window for health reports
+    pub rate_window_start: i64,
+    pub rate_window_index: u64,
+    pub root: [u8; 32],
+    pub filled_subtrees: [[u8; 32]; TREE_DEPTH],
+    /// Incremented on every rollover
+    pub generation: u32,
+    pub is_initialized: bool,
+    /// Archived trees are frozen and accept no more leaves
+    pub is_frozen: bool,
+    pub version: u8,
+    /// Set by `CreateConcurrentTree`; deposits then also append to the `ConcurrentTree`
+    pub has_concurrent_tree: bool,
+}
+
+impl CommitmentTree {
+    pub const LEN: usize = 8 + 8 + 8 + 32 + 32 * TREE_DEPTH + 4 + 1 + 1 + 1 + 1;
+    
+    pub const VERSION_OFFSET: usize = Self::LEN - 2;
+    
+    pub const SEED: &'static [u8] = b"commitment-tree";
+    
+    /// PDA seed prefix for archived trees; the full seeds are `[ARCHIVE_SEED_PREFIX, generation]`
+    pub const ARCHIVE_SEED_PREFIX: &'static [u8] = b"commitment-tree-archive";
+    
+    pub const CAPACITY: u64 = 1 << TREE_DEPTH;
+    
+    /// Anyone may roll the tree over once this few leaves remain, more than
+    /// any single transaction can insert
+    pub const AUTO_ROLLOVER_REMAINING: u64 = 64;
+    
+    /// Restart the rate sample once it covers this many seconds
+    pub const RATE_WINDOW: i64 = 7 * 24 * 60 * 60;
+    
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+    
+    pub fn find_archive_address(generation: u32, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::ARCHIVE_SEED_PREFIX, &generation.to_le_bytes()],
+            program_id,
+        )
+    }
+    
+    pub fn new() -> Result<Self, FPPError> {
+        let zeros = merkle::zero_hashes()?;
+        let mut filled_subtrees = [merkle::ZERO_LEAF; TREE_DEPTH];
+        filled_subtrees.copy_from_slice(&zeros[..TREE_DEPTH]);
+        Ok(Self {
+            next_index: 0,
+            rate_window_start: 0,
+            rate_window_index: 0,
+            root: zeros[TREE_DEPTH],
+            filled_subtrees,
+            generation: 0,
+            is_initialized: true,
+            is_frozen: false,
+            version: Self::VERSION,
+            has_concurrent_tree: false,
+        })
+    }
+    
+    /// Append a leaf, returning its index
+    pub fn insert(&mut self, leaf: [u8; 32]) -> Result<u64, FPPError> {
+        if !poseidon::is_field_element(&leaf) {
+            return Err(FPPError::InvalidCommitment);
+        }
+        if self.is_frozen || self.next_index >= Self::CAPACITY {
+            return Err(FPPError::CommitmentTreeFull);
+        }
+        
+        let index = self.next_index;
+        let mut current = leaf;
+        let mut position = index;
+        let mut zero = merkle::ZERO_LEAF;
+        for level in 0..TREE_DEPTH {
+            if position & 1 == 0 {
+                self.filled_subtrees[level] = current;
+                current = merkle::hash_pair(&current, &zero)?;
+            } else {
+                current = merkle::hash_pair(&self.filled_subtrees[level], &current)?;
+            }
+            zero = merkle::hash_pair(&zero, &zero)?;
+            position /= 2;
+        }
+        
+        self.root = current;
+        self.next_index += 1;
+        Ok(index)
+    }
+    
+    /// Root that spend proofs are verified against
+    pub fn current_root(&self) -> [u8; 32] {
+        self.root
+    }
+    
+    pub fn remaining(&self) -> u64 {
+        Self::CAPACITY.saturating_sub(self.next_index)
+    }
+    
+    /// Occupancy and time-to-full, extrapolating the rate since `rate_window_start`
+    pub fn health(&self, now: i64) -> TreeHealth {
+        let remaining = self.remaining();
+        let elapsed = now.saturating_sub(self.rate_window_start);
+        let inserted = self.next_index.saturating_sub(self.rate_window_index);
+        let leaves_per_day = if self.rate_window_start > 0 && elapsed > 0 {
+            (inserted as u128 * 86_400 / elapsed as u128) as u64
+        } else {
+            0
+        };
+        let seconds_to_full = if inserted > 0 && elapsed > 0 {
+            let seconds = remaining as u128 * elapsed as u128 / inserted as u128;
+            Some(u64::try_from(seconds).unwrap_or(u64::MAX))
+        } else {
+            None
+        };
+        TreeHealth {
+            generation: self.generation,
+            leaves: self.next_index,
+            capacity: Self::CAPACITY,
+            remaining,
+            leaves_per_day,
+            seconds_to_full,
+        }
+    }
+    
+    /// Start a fresh rate sample once the current one is older than `RATE_WINDOW`
+    pub fn roll_rate_window(&mut self, now: i64) {
+        if self.rate_window_start == 0 || now - self.rate_window_start >= Self::RATE_WINDOW {
+            self.rate_window_start = now;
+            self.rate_window_index = self.next_index;
+        }
+    }
+}
+
+impl ZeroCopy for ProtocolState {}
+impl ZeroCopy for CommitmentTree {}
+impl ZeroCopy for NullifierFilter {}
+
+// The zero-copy views are only sound while the layouts stay padding-free
+const _: () = assert!(std::mem::size_of::<ProtocolState>() == ProtocolState::LEN);
+const _: () = assert!(std::mem::size_of::<CommitmentTree>() == CommitmentTree::LEN);
+const _: () = assert!(std::mem::size_of::<NullifierFilter>() == NullifierFilter::HEADER_LEN);
+
+/// Commitment tree occupancy, returned by `ReportTreeHealth` and exported by the indexer
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq, Eq)]
+pub struct TreeHealth {
+    pub generation: u32,
+    pub leaves: u64,
+    pub capacity: u64,
+    pub remaining: u64,
+    pub leaves_per_day: u64,
+    /// `None` until deposits have been observed in the sampling window
+    pub seconds_to_full: Option<u64>,
+}
+
+impl TreeHealth {
+    /// Roll over once less than this share of leaves (in percent) remains
+    pub const ROLLOVER_REMAINING_PERCENT: u64 = 10;
+    /// ... or once the tree is projected to fill within this many seconds
+    pub const ROLLOVER_LEAD_TIME: u64 = 30 * 24 * 60 * 60;
+    
+    pub fn needs_rollover(&self) -> bool {
+        self.remaining * 100 < self.capacity * Self::ROLLOVER_REMAINING_PERCENT
+            || self
+                .seconds_to_full
+                .is_some_and(|seconds| seconds < Self::ROLLOVER_LEAD_TIME)
+    }
+}
+
+
+/// Ring buffer of recent commitment tree roots accepted by spend proofs
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct RootHistory {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub current_index: u32,
+    pub roots: Vec<[u8; 32]>,
+}
+
+impl RootHistory {
+    pub const SEED: &'static [u8] = b"root-history";
+    
+    pub const DEFAULT_SIZE: u16 = 30;
+    pub const MAX_SIZE: u16 = 256;
+    
+    pub fn space(capacity: u16) -> usize {
+        1 + 1 + 4 + 4 + 32 * capacity as usize
+    }
+    
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+    
+    pub fn new(capacity: u16, initial_root: [u8; 32]) -> Self {
+        let mut roots = vec![[0u8; 32]; capacity as usize];
+        roots[0] = initial_root;
+        Self {
+            version: Self::VERSION,
+            is_initialized: true,
+            current_index: 0,
+            roots,
+        }
+    }
+    
+    /// Record a new root, evicting the oldest one
+    pub fn push(&mut self, root: [u8; 32]) {
+        let next = (self.current_index as usize + 1) % self.roots.len();
+        self.roots[next] = root;
+        self.current_index = next as u32;
+    }
+    
+    /// Whether `root` is one of the last `roots.len()` tree roots
+    pub fn contains(&self, root: &[u8; 32]) -> bool {
+        *root != [0u8; 32] && self.roots.contains(root)
+    }
+    
+    /// Widen the buffer to `capacity` slots without dropping any stored root
+    /// 
+    /// The empty slots go right after the newest root, so `push` fills them before it
+    /// starts evicting the oldest roots again.
+    pub fn grow(&mut self, capacity: u16) {
+        let at = self.current_index as usize + 1;
+        let added = (capacity as usize).saturating_sub(self.roots.len());
+        self.roots.splice(at..at, std::iter::repeat([0u8; 32]).take(added));
+    }
+}
+
+
+/// Denomination pool created permissionlessly by a bonded creator
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct PoolConfig {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    pub denomination: u64,
+    pub bond: u64, // lamports held in the pool account above rent
+    pub created_at: i64,
+    pub total_points: u64,
+    // Verifier circuit chosen at creation, never changed afterwards
+    pub circuit_id: u16,
+    pub tree_depth: u8,
+    pub max_inputs: u8,
+    pub max_outputs: u8,
+}
+
+impl PoolConfig {
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 8 + 8 + 8 + 8 + 2 + 1 + 1 + 1;
+    
+    pub const SEED_PREFIX: &'static [u8] = b"pool";
+    
+    pub fn find_address(mint: &Pubkey, denomination: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, mint.as_ref(), &denomination.to_le_bytes()],
+            program_id,
+        )
+    }
+}
+
+/// Anonymity set of an opened pool and its counters
+/// 
+/// Every deposit into the pool is exactly its denomination, so a withdrawal reveals nothing
+/// about which deposit it spends. The pool keeps a `CommitmentTree` and `RootHistory` of its
+/// own at `find_tree_address` and `find_root_history_address`, and its nullifiers live
+/// under `PoolNullifier` seeds, apart from every other pool and from the point nullifiers.
+/// 
+/// Pool notes are `Poseidon(secret, denomination)` leaves. Withdrawals prove a one-input,
+/// no-output spend (`fpp_prover::SpendCircuit`) whose public inputs are a root from the
+/// pool's history and `withdrawal_binding`, against the `CircuitKey` of the pool's circuit.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct PoolState {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub pool: Pubkey,
+    pub deposit_count: u64,
+    pub withdrawal_count: u64,
+    pub opened_at: i64,
+}
+
+impl PoolState {
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 8 + 8;
+    
+    pub const SEED_PREFIX: &'static [u8] = b"pool-state";
+    pub const TREE_SEED_PREFIX: &'static [u8] = b"pool-tree";
+    pub const ROOT_HISTORY_SEED_PREFIX: &'static [u8] = b"pool-roots";
+    
+    pub fn find_address(pool: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, pool.as_ref()], program_id)
+    }
+    
+    pub fn find_tree_address(pool: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::TREE_SEED_PREFIX, pool.as_ref()], program_id)
+    }
+    
+    pub fn find_root_history_address(pool: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::ROOT_HISTORY_SEED_PREFIX, pool.as_ref()], program_id)
+    }
+    
+    /// Spend context of a withdrawal, the seed of its binding and the prover's
+    /// `SpendWitness::context`
+    ///
+    /// `circuit_version` is the one in the circuit's `CircuitKey`, so a proof built for a
+    /// replaced key doesn't verify under its successor.
+    pub fn withdrawal_context(
+        pool: &Pubkey,
+        circuit_version: u16,
+        recipient: &Pubkey,
+        relayer: &Pubkey,
+        relayer_fee: u64,
+    ) -> [u8; 32] {
+        hashv(&[
+            pool.as_ref(),
+            &circuit_version.to_le_bytes(),
+            recipient.as_ref(),
+            relayer.as_ref(),
+            &relayer_fee.to_le_bytes(),
+        ])
+        .to_bytes()
+    }
+    
+    /// What a withdrawal proof commits to besides the root, so that whoever submits it can
+    /// change neither the nullifier, the amount, the payee nor the relayer's cut
+    pub fn withdrawal_binding(
+        pool: &Pubkey,
+        circuit_version: u16,
+        denomination: u64,
+        nullifier: &[u8; 32],
+        recipient: &Pubkey,
+        relayer: &Pubkey,
+        relayer_fee: u64,
+    ) -> Result<[u8; 32], FPPError> {
+        let context =
+            Self::withdrawal_context(pool, circuit_version, recipient, relayer, relayer_fee);
+        poseidon::spend_binding(&context, &[*nullifier], &[], denomination)
+    }
+}
+
+/// Link between two pools of the same mint that notes can move along without leaving the
+/// shielded set
+/// 
+/// Transfers are spends (`fpp_prover::SpendCircuit`) paying nothing out, whose public inputs
+/// are a root from the source pool's history and `transfer_binding`. The proof shows each
+/// nullifier opens a source note and the outputs carry the same total; the program checks
+/// the note counts against both denominations, which are public.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct PoolRoute {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub circuit_id: u16,
+    pub max_inputs: u8,
+    pub max_outputs: u8,
+    pub transfer_count: u64,
+}
+
+impl PoolRoute {
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 2 + 1 + 1 + 8;
+    
+    pub const SEED_PREFIX: &'static [u8] = b"pool-route";
+    
+    pub fn find_address(
+        source: &Pubkey,
+        destination: &Pubkey,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, source.as_ref(), destination.as_ref()],
+            program_id,
+        )
+    }
+    
+    /// Spend context of a transfer along `route`, under the circuit's current key version
+    pub fn transfer_context(route: &Pubkey, circuit_version: u16) -> [u8; 32] {
+        hashv(&[route.as_ref(), &circuit_version.to_le_bytes()]).to_bytes()
+    }
+    
+    /// Everything a transfer proof commits to besides the root
+    pub fn transfer_binding(
+        route: &Pubkey,
+        circuit_version: u16,
+        nullifiers: &[[u8; 32]],
+        commitments: &[[u8; 32]],
+    ) -> Result<[u8; 32], FPPError> {
+        let context = Self::transfer_context(route, circuit_version);
+        poseidon::spend_binding(&context, nullifiers, commitments, 0)
+    }
+    
+    /// Whether `inputs` notes of `source_denomination` are worth exactly `outputs` notes of
+    /// `destination_denomination`
+    pub fn conserves_value(
+        inputs: usize,
+        source_denomination: u64,
+        outputs: usize,
+        destination_denomination: u64,
+    ) -> bool {
+        inputs > 0
+            && outputs > 0
+            && inputs as u128 * source_denomination as u128
+                == outputs as u128 * destination_denomination as u128
+    }
+}
+
+/// Spent nullifier of a pool note, withdrawn or transferred; seeded by the pool, so each pool
+/// is its own domain
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct PoolNullifier {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub pool: Pubkey,
+    pub nullifier: [u8; 32],
+    pub spent_at: i64,
+}
+
+impl PoolNullifier {
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 8;
+    
+    /// PDA seed prefix; the full seeds are `[SEED_PREFIX, pool, nullifier]`
+    pub const SEED_PREFIX: &'static [u8] = b"pool-nullifier";
+    
+    pub fn find_address(pool: &Pubkey, nullifier: &[u8; 32], program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, pool.as_ref(), nullifier], program_id)
+    }
+}
+
+
+/// Spend circuit registered by governance for pools to choose from
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct CircuitInfo {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub circuit_id: u16,
+    pub tree_depth: u8,
+    pub max_inputs: u8,
+    pub max_outputs: u8,
+    pub verifying_key_hash: [u8; 32],
+    pub is_active: bool,
+}
+
+impl CircuitInfo {
+    pub const LEN: usize = 1 + 1 + 2 + 1 + 1 + 1 + 32 + 1;
+    
+    pub const SEED_PREFIX: &'static [u8] = b"circuit";
+    
+    pub const MAX_TREE_DEPTH: u8 = 32;
+    pub const MAX_INPUTS: u8 = 16;
+    pub const MAX_OUTPUTS: u8 = 16;
+    
+    /// Circuit that `PrivacyPayment` proofs are checked against, whatever it proves about
+    /// the payment; its key's public inputs are the root and `CircuitKey::payment_binding`
+    pub const PAYMENT_CIRCUIT_ID: u16 = 0;
+    
+    pub fn find_address(circuit_id: u16, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, &circuit_id.to_le_bytes()], program_id)
+    }
+}
+
+/// Verifying key of a registered circuit, kept on chain so it can be replaced without
+/// redeploying
+/// 
+/// The first key is installed straight away and must hash to the circuit's
+/// `verifying_key_hash`. A replacement waits out `CONFIG_TIMELOCK` in the `pending_*`
+/// fields, so users see a new circuit coming before proofs are checked against it.
+/// `circuit_version` only increases and is committed in every proof's binding input.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct CircuitKey {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub circuit_id: u16,
+    pub circuit_version: u16,
+    pub verifying_key: Groth16VerifyingKey,
+    pub updated_at: i64,
+    pub pending_circuit_version: u16,
+    /// Zero when no replacement is queued
+    pub pending_effective_at: i64,
+    pub pending_key: Groth16VerifyingKey,
+}
+
+impl CircuitKey {
+    pub const LEN: usize =
+        1 + 1 + 2 + 2 + Groth16VerifyingKey::LEN + 8 + 2 + 8 + Groth16VerifyingKey::LEN;
+    
+    pub const SEED_PREFIX: &'static [u8] = b"circuit-key";
+    
+    pub fn find_address(circuit_id: u16, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, &circuit_id.to_le_bytes()], program_id)
+    }
+    
+    pub fn is_pending(&self) -> bool {
+        self.pending_effective_at != 0
+    }
+    
+    /// Second public input of a payment proof under this key: its nullifiers and outputs,
+    /// hashed and reduced to a field element
+    pub fn payment_binding(
+        &self,
+        input_nullifiers: &[[u8; 32]],
+        output_commitments: &[[u8; 32]],
+    ) -> [u8; 32] {
+        let circuit_version = self.circuit_version.to_le_bytes();
+        let mut fields: Vec<&[u8]> = vec![&circuit_version];
+        fields.extend(input_nullifiers.iter().map(|nullifier| nullifier.as_slice()));
+        fields.extend(output_commitments.iter().map(|commitment| commitment.as_slice()));
+        groth16::hash_to_field(&hashv(&fields).to_bytes())
+    }
+}
+
+
+/// Encrypted opening of an output commitment, readable with the recipient's viewing key
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct EncryptedNote {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub commitment: [u8; 32],
+    pub created_at: i64,
+    /// Ephemeral public key followed by the AEAD ciphertext
+    pub ciphertext: Vec<u8>,
+}
+
+impl EncryptedNote {
+    pub const SEED_PREFIX: &'static [u8] = b"note";
+    
+    pub const MAX_CIPHERTEXT_LEN: usize = 256;
+    
+    pub fn space(ciphertext_len: usize) -> usize {
+        1 + 1 + 32 + 8 + 4 + ciphertext_len
+    }
+    
+    pub fn find_address(commitment: &[u8; 32], program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, commitment], program_id)
+    }
+}
+
+
+/// Category of a guardian-recorded security event
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityEventKind {
+    PauseReason,
+    CircuitAdvisory,
+    CompromisedRelayer,
+    Other,
+}
+
+/// How prominently SDKs should surface a security event
+#[derive(
+    BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, PartialEq, Eq, PartialOrd,
+    Ord,
+)]
+pub enum SecuritySeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// Entry in the guardian's on-chain incident log
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct SecurityEvent {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub index: u64,
+    pub kind: SecurityEventKind,
+    pub severity: SecuritySeverity,
+    /// Affected account (circuit, relayer, ...), or the default pubkey
+    pub subject: Pubkey,
+    pub guardian: Pubkey,
+    pub recorded_at: i64,
+    /// Zero while the event is still active
+    pub resolved_at: i64,
+    pub message: String,
+}
+
+impl SecurityEvent {
+    pub const SEED_PREFIX: &'static [u8] = b"security-event";
+    
+    pub const MAX_MESSAGE_LEN: usize = 280;
+    
+    pub fn space(message_len: usize) -> usize {
+        1 + 1 + 8 + 1 + 1 + 32 + 32 + 8 + 8 + 4 + message_len
+    }
+    
+    pub fn find_address(index: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, &index.to_le_bytes()], program_id)
+    }
+    
+    pub fn is_active(&self) -> bool {
+        self.resolved_at == 0
+    }
+}
+
+/// Governance operation recorded in the `AuditLog`
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    UpdateFees,
+    SetEmergencyPenalty,
+    SetReferralFeeShare,
+    UpdateFeeTiers,
+    SetPaused,
+    SetPausedFlags,
+    SetMinExitSlots,
+    AddToDenyList,
+    RemoveFromDenyList,
+    ProposeAuthority,
+    AcceptAuthority,
+    SetGuardian,
+    SetFeeReceiver,
+    SetCpiGuard,
+}
+
+/// One admin action in the `AuditLog`
+///
+/// Values are the setting before and after the action, packed by `AuditEntry::value`.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// Position in the log since it was created
+    pub sequence: u64,
+    pub actor: Pubkey,
+    pub action: AuditAction,
+    pub old_value: [u8; 32],
+    pub new_value: [u8; 32],
+    pub timestamp: i64,
+}
+
+impl AuditEntry {
+    pub const LEN: usize = 8 + 32 + 1 + 32 + 32 + 8;
+    
+    /// A value of up to 32 bytes, zero-padded, or the hash of a longer one
+    pub fn value(bytes: &[u8]) -> [u8; 32] {
+        if bytes.len() > 32 {
+            return hashv(&[bytes]).to_bytes();
+        }
+        let mut value = [0u8; 32];
+        value[..bytes.len()].copy_from_slice(bytes);
+        value
+    }
+}
+
+/// Append-only record of admin actions, for integrators watching governance
+///
+/// The account holds this header followed by `CAPACITY` entry slots; entry `sequence`
+/// is written to slot `sequence % CAPACITY` and never changed until `CAPACITY` newer
+/// entries wrap around to it. Every entry is also emitted as `AdminActionLogged`.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct AuditLog {
+    pub version: u8,
+    pub is_initialized: bool,
+    /// Entries written so far
+    pub count: u64,
+}
+
+impl AuditLog {
+    pub const SEED: &'static [u8] = b"audit-log";
+    
+    pub const HEADER_LEN: usize = 1 + 1 + 8;
+    
+    /// Fits the 10 KiB an account can be created with from inside the program
+    pub const CAPACITY: usize = 64;
+    
+    pub const LEN: usize = Self::HEADER_LEN + AuditEntry::LEN * Self::CAPACITY;
+    
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+    
+    /// Decode the header of the account `data`, ignoring its entries
+    pub fn unpack_header(data: &[u8]) -> Result<Self, ProgramError> {
+        let header = data
+            .get(..Self::HEADER_LEN)
+            .ok_or(ProgramError::AccountDataTooSmall)?;
+        Ok(Self::try_from_slice(header)?)
+    }
+    
+    fn slot(sequence: u64) -> std::ops::Range<usize> {
+        let index = (sequence % Self::CAPACITY as u64) as usize;
+        let start = Self::HEADER_LEN + index * AuditEntry::LEN;
+        start..start + AuditEntry::LEN
+    }
+    
+    /// Write `entry` as the next one in the account `data`, assigning its sequence
+    pub fn append(data: &mut [u8], mut entry: AuditEntry) -> Result<u64, ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        let mut header = Self::unpack_header(data)?;
+        entry.sequence = header.count;
+        entry.serialize(&mut &mut data[Self::slot(entry.sequence)])?;
+        header.count = header.count.checked_add(1).ok_or(FPPError::InvalidAmount)?;
+        header.serialize(&mut &mut data[..Self::HEADER_LEN])?;
+        Ok(entry.sequence)
+    }
+    
+    /// The entries still held in the account `data`, oldest first
+    pub fn entries(data: &[u8]) -> Result<Vec<AuditEntry>, ProgramError> {
+        if data.len() < Self::LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        let count = Self::unpack_header(data)?.count;
+        let first = count.saturating_sub(Self::CAPACITY as u64);
+        (first..count)
+            .map(|sequence| Ok(AuditEntry::try_from_slice(&data[Self::slot(sequence)])?))
+            .collect()
+    }
+}
+
+/// A user's saved recipients, encrypted client-side to the user's own key
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct ContactBook {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub owner: Pubkey,
+    /// Bumped on every update; writers must name the version they read
+    /// Bumped on every update, for compare-and-swap writes
+    pub revision: u64,
+    pub ciphertext: Vec<u8>,
+}
+
+impl ContactBook {
+    pub const SEED_PREFIX: &'static [u8] = b"contact-book";
+    
+    pub const MAX_CIPHERTEXT_LEN: usize = 1024;
+    
+    /// Allocated at full size so updates never need to resize the account
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 4 + Self::MAX_CIPHERTEXT_LEN;
+    
+    pub fn find_address(owner: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, owner.as_ref()], program_id)
+    }
+}
+
+
+/// M-of-N signer set that can hold the protocol authority
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct AdminMultisig {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub threshold: u8,
+    pub signer_count: u8,
+    /// First `signer_count` slots are in use
+    pub signers: [Pubkey; MAX_ADMIN_SIGNERS],
+    /// Bumped whenever the signer set changes, invalidating open proposals
+    pub signer_set_version: u32,
+    pub proposal_count: u64,
+}
+
+impl AdminMultisig {
+    pub const SEED: &'static [u8] = b"admin-multisig";
+    
+    pub const LEN: usize = 1 + 1 + 1 + 1 + 32 * MAX_ADMIN_SIGNERS + 4 + 8;
+    
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+    
+    /// Unique, non-default signers and a threshold between 1 and their count
+    pub fn is_valid_config(signers: &[Pubkey], threshold: u8) -> bool {
+        !signers.is_empty()
+            && signers.len() <= MAX_ADMIN_SIGNERS
+            && threshold >= 1
+            && threshold as usize <= signers.len()
+            && signers
+                .iter()
+                .enumerate()
+                .all(|(i, signer)| *signer != Pubkey::default() && !signers[..i].contains(signer))
+    }
+    
+    pub fn set_signers(&mut self, signers: &[Pubkey], threshold: u8) {
+        self.signers = [Pubkey::default(); MAX_ADMIN_SIGNERS];
+        self.signers[..signers.len()].copy_from_slice(signers);
+        self.signer_count = signers.len() as u8;
+        self.threshold = threshold;
+    }
+    
+    pub fn signer_index(&self, key: &Pubkey) -> Option<usize> {
+        self.signers[..self.signer_count as usize]
+            .iter()
+            .position(|signer| signer == key)
+    }
+}
+
+/// Governance operation a multisig proposal carries out once approved
+///
+/// Each variant except `UpdateSigners` executes as the matching admin
+/// instruction, signed by the multisig PDA as protocol authority.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq, Eq)]
+pub enum AdminAction {
+    UpdateFees {
+        deposit_fee_rate: u16,
+        withdrawal_fee_rate: u16,
+    },
+    SetPaused {
+        paused: bool,
+    },
+    SetPoolBounds {
+        min_denomination: u64,
+        max_denomination: u64,
+        creation_bond: u64,
+    },
+    SetCommitmentValidation {
+        required: bool,
+    },
+    SetGuardian {
+        guardian: Pubkey,
+    },
+    SetDenominations {
+        denominations: Vec<u64>,
+    },
+    SetEmergencyPenalty {
+        penalty_rate: u16,
+    },
+    ProposeAuthority {
+        new_authority: Pubkey,
+    },
+    AcceptAuthority,
+    /// Replace the multisig's own signer set and threshold
+    UpdateSigners {
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    },
+    SetFeeReceiver {
+        fee_receiver: Pubkey,
+    },
+    SetPausedFlags {
+        flags: u8,
+    },
+    SetRateLimits {
+        limits: RateLimits,
+    },
+    SetMinExitSlots {
+        slots: u64,
+    },
+    AddToDenyList {
+        entry: [u8; 32],
+    },
+    RemoveFromDenyList {
+        entry: [u8; 32],
+    },
+    UpdateAssociationSet {
+        flagged_root: [u8; 32],
+        required: bool,
+        verifying_key: Option<Groth16VerifyingKey>,
+    },
+    AddSupportedMint {
+        mint: Pubkey,
+        config: SupportedMintConfig,
+    },
+    ResizeRootHistory {
+        capacity: u16,
+    },
+    ExtendNullifierFilter,
+    CreatePointTree {
+        merkle_tree: Pubkey,
+        max_depth: u32,
+        max_buffer_size: u32,
+    },
+    SetRelayerConfig {
+        max_fee_rate: u16,
+    },
+    MigrateState {
+        kind: AccountKind,
+        account: Pubkey,
+    },
+    SetTreasuryDeployment {
+        mint: Pubkey,
+        collateral_token: Pubkey,
+        reserve: ReserveKeys,
+        max_deployed_rate: u16,
+    },
+    /// Carries the deployment's accounts, since executing can't read them
+    DeployTreasury {
+        mint: Pubkey,
+        token_program: Pubkey,
+        treasury_token: Pubkey,
+        collateral_token: Pubkey,
+        reserve: ReserveKeys,
+        amount: u64,
+    },
+    RecallTreasury {
+        mint: Pubkey,
+        token_program: Pubkey,
+        treasury_token: Pubkey,
+        collateral_token: Pubkey,
+        reserve: ReserveKeys,
+        collateral_amount: u64,
+    },
+    SetRewardConfig {
+        mint: Pubkey,
+        vault: Pubkey,
+        rate: u64,
+    },
+    SetReferralFeeShare {
+        share: u16,
+    },
+    UpdateFeeTiers {
+        tiers: [FeeTier; MAX_FEE_TIERS],
+    },
+    AddFeeExemption {
+        address: Pubkey,
+    },
+    RemoveFeeExemption {
+        address: Pubkey,
+    },
+    SetDepositCaps {
+        max_tvl: u64,
+        max_user_deposit: u64,
+    },
+    UpdateTimelocks {
+        withdrawal_delay: i64,
+        point_lock_duration: i64,
+    },
+    SetMassDynamics {
+        dynamics: MassDynamics,
+    },
+    SetCpiGuard {
+        enabled: bool,
+    },
+    RegisterCircuit {
+        circuit_id: u16,
+        tree_depth: u8,
+        max_inputs: u8,
+        max_outputs: u8,
+        verifying_key_hash: [u8; 32],
+    },
+    UpdateVerifyingKey {
+        circuit_id: u16,
+        circuit_version: u16,
+        verifying_key: Groth16VerifyingKey,
+    },
+    /// Carries the fee accounts, since executing can't read them
+    CollectFees {
+        mint: Pubkey,
+        token_program: Pubkey,
+        treasury_token: Pubkey,
+        fee_receiver_token: Pubkey,
+    },
+    CreateAuditLog,
+    CreateConcurrentTree {
+        merkle_tree: Pubkey,
+        max_depth: u32,
+        max_buffer_size: u32,
+        canopy_depth: u32,
+    },
+    /// An early rollover of tree `generation`
+    RolloverTree {
+        generation: u32,
+    },
+}
+
+/// Pending multisig proposal
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct AdminProposal {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub index: u64,
+    pub proposer: Pubkey,
+    /// Signer set the approvals refer to
+    pub signer_set_version: u32,
+    /// Bit `i` is set once signer `i` has approved
+    pub approvals: u16,
+    pub created_at: i64,
+    pub executed: bool,
+    pub action: AdminAction,
+}
+
+impl AdminProposal {
+    pub const SEED_PREFIX: &'static [u8] = b"admin-proposal";
+    
+    pub fn space(action_len: usize) -> usize {
+        1 + 1 + 8 + 32 + 4 + 2 + 8 + 1 + action_len
+    }
+    
+    pub fn find_address(index: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, &index.to_le_bytes()], program_id)
+    }
+    
+    pub fn approval_count(&self) -> u32 {
+        self.approvals.count_ones()
+    }
+}
+
+/// A user's deposits and withdrawal requests in the current rate limit window
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct UserActivity {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub user: Pubkey,
+    /// Counters reset on the first action at least `RateLimits::window` after this
+    pub window_start: i64,
+    pub deposit_count: u32,
+    pub deposit_volume: u64,
+    pub withdrawal_count: u32,
+    pub withdrawal_volume: u64,
+    /// One more than the epoch of the user's last deposit, or zero before any; added by
+    /// layout 2 so `EpochStats` can count each depositor once
+    pub deposit_epoch: u64,
+    /// Everything the user has deposited, never reduced by withdrawals (which can't be tied
+    /// back to a depositor); added by layout 3 and held under `max_user_deposit`
+    pub total_deposited: u64,
+}
+
+impl UserActivity {
+    pub const SEED_PREFIX: &'static [u8] = b"user-activity";
+    
+    pub const LEN: usize = Self::V2_LEN + 8;
+    
+    /// Size of layout 2, before `total_deposited`
+    pub const V2_LEN: usize = Self::V1_LEN + 8;
+    
+    /// Size of layout 1, before `deposit_epoch`
+    pub const V1_LEN: usize = 1 + 1 + 32 + 8 + 4 + 8 + 4 + 8;
+    
+    pub fn find_address(user: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, user.as_ref()], program_id)
+    }
+    
+    pub fn record_deposit(
+        &mut self,
+        limits: &RateLimits,
+        now: i64,
+        amount: u64,
+    ) -> Result<(), FPPError> {
+        self.roll_window(limits, now);
+        let (count, volume) = Self::add(self.deposit_count, self.deposit_volume, amount)?;
+        Self::check(count, volume, limits.max_deposits, limits.max_deposit_volume)?;
+        self.deposit_count = count;
+        self.deposit_volume = volume;
+        Ok(())
+    }
+    
+    pub fn record_withdrawal(
+        &mut self,
+        limits: &RateLimits,
+        now: i64,
+        amount: u64,
+    ) -> Result<(), FPPError> {
+        self.roll_window(limits, now);
+        let (count, volume) = Self::add(self.withdrawal_count, self.withdrawal_volume, amount)?;
+        Self::check(count, volume, limits.max_withdrawals, limits.max_withdrawal_volume)?;
+        self.withdrawal_count = count;
+        self.withdrawal_volume = volume;
+        Ok(())
+    }
+    
+    /// Take back a cancelled withdrawal request made at `requested_at`; one from an earlier
+    /// window went when the counters were reset
+    pub fn undo_withdrawal(&mut self, requested_at: i64, amount: u64) {
+        if requested_at >= self.window_start {
+            self.withdrawal_count = self.withdrawal_count.saturating_sub(1);
+            self.withdrawal_volume = self.withdrawal_volume.saturating_sub(amount);
+        }
+    }
+    
+    /// Add `amount` to the user's lifetime deposits, unless that passes a non-zero `cap`
+    pub fn record_total_deposit(&mut self, cap: u64, amount: u64) -> Result<(), FPPError> {
+        let total = self
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(FPPError::InvalidAmount)?;
+        if cap != 0 && total > cap {
+            return Err(FPPError::DepositCapExceeded);
+        }
+        self.total_deposited = total;
+        Ok(())
+    }
+    
+    /// Note a deposit in `epoch`, returning whether it is the user's first there
+    pub fn record_deposit_epoch(&mut self, epoch: u64) -> bool {
+        let marker = epoch.saturating_add(1);
+        let first = self.deposit_epoch != marker;
+        self.deposit_epoch = marker;
+        first
+    }
+    
+    fn roll_window(&mut self, limits: &RateLimits, now: i64) {
+        if now.saturating_sub(self.window_start) >= limits.window {
+            self.window_start = now;
+            self.deposit_count = 0;
+            self.deposit_volume = 0;
+            self.withdrawal_count = 0;
+            self.withdrawal_volume = 0;
+        }
+    }
+    
+    fn add(count: u32, volume: u64, amount: u64) -> Result<(u32, u64), FPPError> {
+        Ok((
+            count.checked_add(1).ok_or(FPPError::InvalidAmount)?,
+            volume.checked_add(amount).ok_or(FPPError::InvalidAmount)?,
+        ))
+    }
+    
+    fn check(count: u32, volume: u64, max_count: u32, max_volume: u64) -> Result<(), FPPError> {
+        if (max_count != 0 && count > max_count) || (max_volume != 0 && volume > max_volume) {
+            return Err(FPPError::RateLimitExceeded);
+        }
+        Ok(())
+    }
+}
+
+/// Deposits a referrer brought in for one mint, and their unclaimed share of the fees
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct ReferralAccount {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub referrer: Pubkey,
+    pub mint: Pubkey,
+    pub deposit_count: u64,
+    /// Referred deposit volume, in base units
+    pub volume: u64,
+    /// Fees owed to the referrer, in base units
+    pub accrued_fees: u64,
+    pub total_claimed: u64,
+}
+
+impl ReferralAccount {
+    pub const SEED_PREFIX: &'static [u8] = b"referral";
+    
+    pub const LEN: usize = 1 + 1 + 32 + 32 + 8 + 8 + 8 + 8;
+    
+    pub fn find_address(referrer: &Pubkey, mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, referrer.as_ref(), mint.as_ref()],
+            program_id,
+        )
+    }
+    
+    /// The referrer's cut of `fee` at `share` basis points
+    pub fn fee_share(fee: u64, share: u16) -> u64 {
+        (fee as u128 * share as u128 / 10000) as u64
+    }
+}
+
+/// Activity in one Solana epoch, so dashboards don't have to replay history
+/// 
+/// Volumes and fee revenue add up base units across every mint. Deposits are counted when
+/// made and withdrawals when paid out; fee revenue is what the protocol keeps, after any
+/// referrer's share and including emergency penalties.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone, Default)]
+pub struct EpochStats {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub epoch: u64,
+    pub deposit_count: u64,
+    pub deposit_volume: u64,
+    pub withdrawal_count: u64,
+    pub withdrawal_volume: u64,
+    pub fee_revenue: u64,
+    /// Distinct addresses that deposited during the epoch
+    pub unique_depositors: u64,
+    /// Active points after the epoch's latest recorded deposit or withdrawal
+    pub anonymity_set: u64,
+}
+
+impl EpochStats {
+    pub const SEED_PREFIX: &'static [u8] = b"epoch-stats";
+    
+    pub const LEN: usize = 1 + 1 + 8 * 8;
+    
+    pub fn find_address(epoch: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, &epoch.to_le_bytes()], program_id)
+    }
+    
+    // Statistics saturate rather than fail the deposit or withdrawal they describe
+    
+    pub fn record_deposit(&mut self, amount: u64, fee: u64, new_depositor: bool, points: u64) {
+        self.deposit_count = self.deposit_count.saturating_add(1);
+        self.deposit_volume = self.deposit_volume.saturating_add(amount);
+        self.fee_revenue = self.fee_revenue.saturating_add(fee);
+        if new_depositor {
+            self.unique_depositors = self.unique_depositors.saturating_add(1);
+        }
+        self.anonymity_set = points;
+    }
+    
+    pub fn record_withdrawal(&mut self, amount: u64, fees: u64, points: u64) {
+        self.withdrawal_count = self.withdrawal_count.saturating_add(1);
+        self.withdrawal_volume = self.withdrawal_volume.saturating_add(amount);
+        self.fee_revenue = self.fee_revenue.saturating_add(fees);
+        self.anonymity_set = points;
+    }
+}
+
+/// A user's loyalty rewards
+/// 
+/// `balance` is what the user has deposited less what they have requested to withdraw,
+/// in base units, and accrues rewards for as long as it stays in.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct RewardAccount {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub user: Pubkey,
+    pub balance: u64,
+    /// Rewards earned and not yet claimed, in the reward mint's units
+    pub accrued: u64,
+    pub last_accrued_at: i64,
+    pub total_claimed: u64,
+}
+
+impl RewardAccount {
+    pub const SEED_PREFIX: &'static [u8] = b"reward-account";
+    
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 8 + 8 + 8;
+    
+    pub fn find_address(user: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, user.as_ref()], program_id)
+    }
+    
+    /// Credit what `balance` earned at `config.rate` since the last update
+    pub fn accrue(&mut self, config: &RewardConfig, now: i64) {
+        let elapsed = now.saturating_sub(self.last_accrued_at).max(0) as u128;
+        let earned = self.balance as u128 * config.rate as u128 * elapsed
+            / RewardConfig::RATE_DENOMINATOR;
+        self.accrued = self.accrued.saturating_add(earned.min(u64::MAX as u128) as u64);
+        self.last_accrued_at = now;
+    }
+    
+    pub fn record_deposit(&mut self, config: &RewardConfig, now: i64, amount: u64) {
+        self.accrue(config, now);
+        self.balance = self.balance.saturating_add(amount);
+    }
+    
+    /// Withdrawals stop at zero: value received through private payments was never counted
+    pub fn record_withdrawal(&mut self, config: &RewardConfig, now: i64, amount: u64) {
+        self.accrue(config, now);
+        self.balance = self.balance.saturating_sub(amount);
+    }
+}
+
+/// Addresses and commitments barred from entering or leaving the pool
+/// 
+/// Only checked where funds cross the boundary (deposits and withdrawal payouts), so
+/// shielded transfers reveal nothing new.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone, Default)]
+pub struct DenyList {
+    pub version: u8,
+    pub is_initialized: bool,
+    /// Wallet or token account addresses (as bytes) and note commitments
+    pub entries: Vec<[u8; 32]>,
+}
+
+impl DenyList {
+    pub const SEED: &'static [u8] = b"deny-list";
+    
+    pub const MAX_ENTRIES: usize = 256;
+    
+    /// Account size for `entries` entries
+    /// 
+    /// The account starts empty and is reallocated as entries are added; removals keep
+    /// the space for the next addition.
+    pub fn space(entries: usize) -> usize {
+        1 + 1 + 4 + 32 * entries
+    }
+    
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+    
+    pub fn contains(&self, entry: &[u8; 32]) -> bool {
+        self.entries.contains(entry)
+    }
+    
+    pub fn contains_key(&self, key: &Pubkey) -> bool {
+        self.contains(&key.to_bytes())
+    }
+}
+
+/// Addresses that pay no deposit or withdrawal fee, such as market makers and integrators
+/// 
+/// Deposits are matched on the depositor and withdrawals on the request's requester.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone, Default)]
+pub struct FeeExemptions {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub addresses: Vec<Pubkey>,
+}
+
+impl FeeExemptions {
+    pub const SEED: &'static [u8] = b"fee-exemptions";
+    
+    pub const MAX_ENTRIES: usize = 64;
+    
+    /// Account size for `entries` addresses; grows with additions like `DenyList`
+    pub fn space(entries: usize) -> usize {
+        1 + 1 + 4 + 32 * entries
+    }
+    
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+    
+    pub fn is_exempt(&self, address: &Pubkey) -> bool {
+        self.addresses.contains(address)
+    }
+}
+
+/// Pending withdrawal requests in the order they were opened
+/// 
+/// `RequestWithdrawal` appends each request and `ProcessWithdrawalQueue` settles matured ones
+/// from the front, so users needn't come back at unlock time. Requests completed or cancelled
+/// by their requester leave the queue then, and ones the crank can't pay yet go to the back.
+/// Requests made while the queue holds `MAX_ENTRIES` aren't queued, and are completed by
+/// their requester instead.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone, Default)]
+pub struct WithdrawalQueue {
+    pub version: u8,
+    pub is_initialized: bool,
+    /// Withdrawal request addresses, oldest first
+    pub requests: Vec<Pubkey>,
+}
+
+impl WithdrawalQueue {
+    pub const SEED: &'static [u8] = b"withdrawal-queue";
+    
+    pub const MAX_ENTRIES: usize = 1024;
+    
+    /// Share of a settled request's withdrawal fee paid to the crank, in basis points
+    pub const TIP_SHARE: u16 = 1000;
+    
+    /// Account size for `entries` requests; the account only grows, reusing settled space
+    pub fn space(entries: usize) -> usize {
+        1 + 1 + 4 + 32 * entries
+    }
+    
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+    
+    /// The crank's cut of a withdrawal `fee`
+    pub fn tip(fee: u64) -> u64 {
+        (fee as u128 * Self::TIP_SHARE as u128 / 10000) as u64
+    }
+}
+
+/// Flagged deposit set that withdrawals can be proven not to descend from
+/// 
+/// The association set circuit takes two public inputs: `flagged_root` and the
+/// withdrawal's `points_hash` reduced with `groth16::hash_to_field`.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct AssociationSet {
+    pub version: u8,
+    pub is_initialized: bool,
+    /// Merkle root of the flagged deposit commitments
+    pub flagged_root: [u8; 32],
+    /// Withdrawals must be proven against the current root before they pay out
+    pub required: bool,
+    pub updated_at: i64,
+    pub verifying_key: Groth16VerifyingKey,
+}
+
+impl AssociationSet {
+    pub const SEED: &'static [u8] = b"association-set";
+    
+    pub const LEN: usize = 1 + 1 + 32 + 1 + 8 + Groth16VerifyingKey::LEN;
+    
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+/// Fee cap for relayer-submitted withdrawals
+/// 
+/// Accounts written before layout 2 are longer, holding a relay circuit key after the fields,
+/// so the program reads this with `deserialize` rather than `try_from_slice`.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct RelayerConfig {
+    pub version: u8,
+    pub is_initialized: bool,
+    /// Largest relayer fee, in basis points of the withdrawn amount
+    pub max_fee_rate: u16,
+}
+
+impl RelayerConfig {
+    pub const SEED: &'static [u8] = b"relayer-config";
+    
+    pub const LEN: usize = 1 + 1 + 2;
+    
+    /// Size of layout 1, which ended with the relay circuit's verifying key
+    pub const V1_LEN: usize = Self::LEN + Groth16VerifyingKey::LEN;
+    
+    pub const MAX_FEE_RATE: u16 = 1000; // 10%
+    
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+/// Marks a deposit permit as used, so a sponsor can't submit it twice
+/// 
+/// The user signs `DepositPermit::message` off-chain and approves the permit authority PDA
+/// as a delegate on their token account; a sponsor then submits `DelegatedDeposit` and pays
+/// for the accounts it creates, this one included.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct DepositPermit {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub user: Pubkey,
+    pub nonce: u64,
+    pub sponsor: Pubkey,
+    pub used_at: i64,
+}
+
+impl DepositPermit {
+    pub const SEED_PREFIX: &'static [u8] = b"deposit-permit";
+    
+    pub const AUTHORITY_SEED: &'static [u8] = b"permit-authority";
+    
+    /// Prefix of every signed permit, so the signature can't be replayed as anything else
+    pub const DOMAIN: &'static [u8] = b"FPP deposit permit v1";
+    
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 32 + 8;
+    
+    pub fn find_address(user: &Pubkey, nonce: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, user.as_ref(), &nonce.to_le_bytes()],
+            program_id,
+        )
+    }
+    
+    /// The token delegate users approve for sponsored deposits
+    pub fn find_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::AUTHORITY_SEED], program_id)
+    }
+    
+    /// Bytes the user signs to authorize a sponsored deposit
+    pub fn message(
+        program_id: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+        denomination: u64,
+        commitments: &[[u8; 32]],
+        expiry: i64,
+        nonce: u64,
+    ) -> Vec<u8> {
+        let commitments_hash =
+            hashv(&commitments.iter().map(|c| c.as_ref()).collect::<Vec<_>>()).to_bytes();
+        let mut message = Vec::with_capacity(Self::DOMAIN.len() + 32 * 3 + 8 * 4);
+        message.extend_from_slice(Self::DOMAIN);
+        message.extend_from_slice(program_id.as_ref());
+        message.extend_from_slice(mint.as_ref());
+        message.extend_from_slice(&amount.to_le_bytes());
+        message.extend_from_slice(&denomination.to_le_bytes());
+        message.extend_from_slice(&commitments_hash);
+        message.extend_from_slice(&expiry.to_le_bytes());
+        message.extend_from_slice(&nonce.to_le_bytes());
+        message
+    }
+}
+
+/// Deposit and withdrawal settings of a `SupportedMint`
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, Copy, Default, PartialEq)]
+pub struct SupportedMintConfig {
+    /// Multiples of `POINT_VALUE`, ascending; zero slots are unused
+    pub denominations: [u64; MAX_DENOMINATIONS],
+    pub deposit_fee_rate: u16,
+    pub withdrawal_fee_rate: u16,
+    /// Largest single deposit in base units; zero for no limit
+    pub max_deposit: u64,
+    /// Most the pool may hold in this mint (deposited minus withdrawn) in base units; zero for
+    /// no limit
+    pub deposit_cap: u64,
+}
+
+impl SupportedMintConfig {
+    pub const LEN: usize = 8 * MAX_DENOMINATIONS + 2 + 2 + 8 + 8;
+    
+    pub fn is_valid(&self) -> bool {
+        let used = self.denominations.iter().take_while(|d| **d != 0).count();
+        used > 0
+            && self.denominations[used..].iter().all(|d| *d == 0)
+            && self.denominations[..used].windows(2).all(|pair| pair[0] < pair[1])
+            && self.denominations[..used].iter().all(|d| d % POINT_VALUE == 0)
+            && self.deposit_fee_rate <= ProtocolState::MAX_FEE_RATE
+            && self.withdrawal_fee_rate <= ProtocolState::MAX_FEE_RATE
+    }
+    
+    pub fn is_denomination(&self, denomination: u64) -> bool {
+        denomination != 0 && self.denominations.contains(&denomination)
+    }
+}
+
+/// Registry entry for a mint accepted besides the protocol's primary `usdt_mint`
+/// 
+/// The primary mint keeps its settings and totals in `ProtocolState`;
+/// `SupportedMint::primary` presents them in this shape.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct SupportedMint {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    pub config: SupportedMintConfig,
+    /// Totals in base units, like every amount the program records
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    /// Accrued and not yet collected
+    pub total_fees: u64,
+}
+
+impl SupportedMint {
+    pub const SEED_PREFIX: &'static [u8] = b"supported-mint";
+    
+    pub const LEN: usize = 1 + 1 + 32 + 32 + SupportedMintConfig::LEN + 8 + 8 + 8;
+    
+    /// Largest deposit into the primary mint
+    pub const PRIMARY_MAX_DEPOSIT: u64 = 100_000_000_000;
+    
+    pub fn find_address(mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, mint.as_ref()], program_id)
+    }
+    
+    /// The primary mint's settings and totals, held in the protocol state
+    pub fn primary(protocol_state: &ProtocolState) -> Self {
+        Self {
+            version: Self::VERSION,
+            is_initialized: true,
+            mint: protocol_state.usdt_mint,
+            token_program: protocol_state.token_program,
+            config: SupportedMintConfig {
+                denominations: protocol_state.denominations,
+                deposit_fee_rate: protocol_state.deposit_fee_rate,
+                withdrawal_fee_rate: protocol_state.withdrawal_fee_rate,
+                max_deposit: Self::PRIMARY_MAX_DEPOSIT,
+                deposit_cap: 0,
+            },
+            total_deposited: protocol_state.total_deposited,
+            total_withdrawn: protocol_state.total_withdrawn,
+            total_fees: protocol_state.total_fees,
+        }
+    }
+    
+    /// Whether `amount` more may be deposited under the per-deposit limit and the cap
+    pub fn accepts_deposit(&self, amount: u64) -> bool {
+        let outstanding = self.total_deposited.saturating_sub(self.total_withdrawn);
+        (self.config.max_deposit == 0 || amount <= self.config.max_deposit)
+            && (self.config.deposit_cap == 0
+                || outstanding.saturating_add(amount) <= self.config.deposit_cap)
+    }
+}
+
+/// Scratch account for a privacy payment too large for one transaction
+/// 
+/// `StageProof` appends the serialized `StagedPayment` after the header in chunks;
+/// `VerifyStagedProof` then checks its ring signature a few rows per call, carrying the
+/// running challenge here, and `ExecuteStagedPayment` applies the payment and closes the
+/// account. Once expired it can only be closed.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct StagedProof {
+    pub version: u8,
+    pub is_initialized: bool,
+    /// Stages, verifies and executes the payment, and paid the rent
+    pub owner: Pubkey,
+    pub nonce: u64,
+    pub expires_at: i64,
+    /// Payload bytes reserved after the header
+    pub payload_len: u32,
+    /// Payload bytes written so far; chunks are appended in order
+    pub written: u32,
+    /// Ring rows checked so far
+    pub verified_rows: u16,
+    /// Challenge leaving the last checked row
+    pub challenge: [u8; 32],
+    /// Every ring row checked out and the challenge came back to `c_0`
+    pub verified: bool,
+}
+
+impl StagedProof {
+    pub const SEED_PREFIX: &'static [u8] = b"staged-proof";
+    
+    /// Header size; the payload follows it
+    pub const LEN: usize = 1 + 1 + 32 + 8 + 8 + 4 + 4 + 2 + 32 + 1;
+    
+    /// Largest payload; the owner pays rent on all of it up front
+    pub const MAX_PAYLOAD_LEN: u32 = 16 * 1024;
+    
+    /// Seconds from `StageProof` opening the account until it expires
+    pub const TTL: i64 = 30 * 60;
+    
+    pub fn find_address(owner: &Pubkey, nonce: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED_PREFIX, owner.as_ref(), &nonce.to_le_bytes()],
+            program_id,
+        )
+    }
+    
+    pub fn space(payload_len: u32) -> usize {
+        Self::LEN + payload_len as usize
+    }
+    
+    /// Read the header, ignoring the payload behind it
+    pub fn load(data: &[u8]) -> Result<Self, ProgramError> {
+        let header = data.get(..Self::LEN).ok_or(ProgramError::InvalidAccountData)?;
+        Ok(Self::try_from_slice(header)?)
+    }
+    
+    /// The payload written so far
+    pub fn payload<'a>(&self, data: &'a [u8]) -> Result<&'a [u8], ProgramError> {
+        data.get(Self::LEN..Self::LEN + self.written as usize)
+            .ok_or(ProgramError::InvalidAccountData)
+    }
+    
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.expires_at
+    }
+    
+    pub fn is_written(&self) -> bool {
+        self.written == self.payload_len
+    }
+}
+
+/// A privacy payment as staged in a `StagedProof`
+/// 
+/// The fields are `PrivacyPayment`'s, plus the ring, which `PrivacyPayment` takes as
+/// accounts; verification checks each batch of ring accounts against it.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq)]
+pub struct StagedPayment {
+    pub merkle_root: [u8; 32],
+    pub input_nullifiers: Vec<[u8; 32]>,
+    pub output_commitments: Vec<[u8; 32]>,
+    pub proof: Vec<u8>,
+    pub ring_signature: Vec<u8>,
+    /// Point accounts whose commitments form the ring, row-major
+    pub ring: Vec<Pubkey>,
+    pub encrypted_notes: Vec<Vec<u8>>,
+}
+
+/// One payment of a `BatchPrivacyPayment`
+/// 
+/// The fields are `PrivacyPayment`'s, except that the proof is required; the ring
+/// signature signs its Borsh encoding.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq)]
+pub struct BatchedPayment {
+    pub merkle_root: [u8; 32],
+    pub input_nullifiers: Vec<[u8; 32]>,
+    pub output_commitments: Vec<[u8; 32]>,
+    pub proof: Groth16Proof,
+    pub ring_signature: Vec<u8>,
+    /// Relative index of the range proof over `output_commitments`
+    pub range_proof_offset: i8,
+    pub encrypted_notes: Vec<Vec<u8>>,
+}
+
+/// Treasury liquidity of one mint lent to a lending reserve
+/// 
+/// `deployed` is principal, not value: the reserve's collateral is worth more as interest
+/// accrues. Redemptions retire principal in proportion to the collateral redeemed, and
+/// anything received above it is credited to the mint's fees.
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, ShankAccount, Debug, Clone)]
+pub struct TreasuryDeployment {
+    pub version: u8,
+    pub is_initialized: bool,
+    pub mint: Pubkey,
+    pub reserve: ReserveKeys,
+    /// Treasury authority's token account for the reserve's collateral
+    pub collateral_token: Pubkey,
+    /// Largest share of the mint's treasury that may be lent, in basis points
+    pub max_deployed_rate: u16,
+    /// Principal lent and not yet redeemed, in base units
+    pub deployed: u64,
+    /// Redeemed above principal, in base units
+    pub total_yield: u64,
+    /// Principal a redemption didn't return, in base units
+    pub total_loss: u64,
+}
+
+impl TreasuryDeployment {
+    pub const SEED_PREFIX: &'static [u8] = b"treasury-deployment";
+    
+    pub const LEN: usize = 1 + 1 + 32 + ReserveKeys::LEN + 32 + 2 + 8 + 8 + 8;
+    
+    /// Ceiling on `max_deployed_rate`, so at least half of every treasury stays liquid
+    pub const MAX_DEPLOYED_RATE: u16 = 5000;
+    
+    pub fn find_address(mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED_PREFIX, mint.as_ref()], program_id)
+    }
+    
+    /// Whether lending `deployed` in total, with `liquid` left in the treasury, stays
+    /// within `max_deployed_rate`
+    pub fn within_limit(&self, liquid: u64, deployed: u64) -> bool {
+        let backing = liquid as u128 + deployed as u128;
+        deployed as u128 * 10_000 <= backing * self.max_deployed_rate as u128
+    }
+    
+    /// Principal retired by redeeming `collateral` out of `collateral_held`
+    pub fn principal_share(&self, collateral: u64, collateral_held: u64) -> u64 {
+        if collateral_held == 0 {
+            return 0;
+        }
+        (self.deployed as u128 * collateral as u128 / collateral_held as u128) as u64
+    }
+}
+
+// Zero-copy accounts keep `version` in former padding, where unversioned accounts have 0
+
+impl Versioned for ProtocolState {
+    const VERSION: u8 = 7;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        Self::version_of(data) == 0
+    }
+    
+    fn version_of(data: &[u8]) -> u8 {
+        migration::version_at(data, Self::VERSION_OFFSET)
+    }
+    
+    /// Layout 2 appends `rewards`, which starts out unconfigured, layout 3 an empty
+    /// `fee_tiers` table, layout 4 the deposit caps, unset, layout 5 the timelocks, at the
+    /// values that used to be compiled in, layout 6 mass dynamics, switched off, and layout 7
+    /// an empty `pending_unpause`
+    /// 
+    /// `value_locked` starts from the primary mint's outstanding deposits, the only totals
+    /// the protocol state holds; deposits of other mints made before the upgrade aren't
+    /// counted against `max_tvl`.
+    fn upgrade(from: u8, data: &mut Vec<u8>) -> Result<(), ProgramError> {
+        match from {
+            0 => migration::upgrade_at(from, data, Self::VERSION_OFFSET),
+            1 if data.len() == Self::V1_LEN => {
+                data[Self::VERSION_OFFSET] = 2;
+                data.resize(Self::V2_LEN, 0);
+                Ok(())
+            }
+            2 if data.len() == Self::V2_LEN => {
+                data[Self::VERSION_OFFSET] = 3;
+                data.resize(Self::V3_LEN, 0);
+                Ok(())
+            }
+            3 if data.len() == Self::V3_LEN => {
+                // `total_deposited` and `total_withdrawn` lead the account
+                let total = |at: usize| u64::from_le_bytes(data[at..at + 8].try_into().unwrap());
+                let outstanding = total(0).saturating_sub(total(8));
+                data[Self::VERSION_OFFSET] = 4;
+                data.extend_from_slice(&outstanding.to_le_bytes());
+                data.resize(Self::V4_LEN, 0);
+                Ok(())
+            }
+            4 if data.len() == Self::V4_LEN => {
+                data[Self::VERSION_OFFSET] = 5;
+                data.extend_from_slice(&WITHDRAWAL_DELAY.to_le_bytes());
+                data.extend_from_slice(&POINT_LOCK_DURATION.to_le_bytes());
+                Ok(())
+            }
+            5 if data.len() == Self::V5_LEN => {
+                data[Self::VERSION_OFFSET] = 6;
+                data.resize(Self::V6_LEN, 0);
+                Ok(())
+            }
+            6 if data.len() == Self::V6_LEN => {
+                data[Self::VERSION_OFFSET] = 7;
+                data.resize(Self::LEN, 0);
+                Ok(())
+            }
+            _ => Err(FPPError::UnsupportedVersion.into()),
+        }
+    }
+}
+
+impl Versioned for CommitmentTree {
+    const VERSION: u8 = 1;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        Self::version_of(data) == 0
+    }
+    
+    fn version_of(data: &[u8]) -> u8 {
+        migration::version_at(data, Self::VERSION_OFFSET)
+    }
+    
+    fn upgrade(from: u8, data: &mut Vec<u8>) -> Result<(), ProgramError> {
+        migration::upgrade_at(from, data, Self::VERSION_OFFSET)
+    }
+}
+
+impl Versioned for NullifierFilter {
+    const VERSION: u8 = 1;
+    
+    const VERSIONED_LEN: usize = Self::HEADER_LEN;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        Self::version_of(data) == 0
+    }
+    
+    fn version_of(data: &[u8]) -> u8 {
+        migration::version_at(data, Self::VERSION_OFFSET)
+    }
+    
+    fn upgrade(from: u8, data: &mut Vec<u8>) -> Result<(), ProgramError> {
+        migration::upgrade_at(from, data, Self::VERSION_OFFSET)
+    }
+}
+
+// Fixed-size Borsh accounts were one byte shorter before `version`
+
+impl Versioned for FloatingPoint {
+    const VERSION: u8 = 3;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() == Self::V1_LEN - 1
+    }
+    
+    /// Layout 2 adds `rent_payer`. Points before it were mostly paid for by their creator;
+    /// a sponsor that paid for one can't be told apart, so the creator gets the refund.
+    /// Layout 3 adds the mass dynamics, with nothing accrued yet.
+    fn upgrade(from: u8, data: &mut Vec<u8>) -> Result<(), ProgramError> {
+        match from {
+            0 => migration::prefix_version(data),
+            1 if data.len() == Self::V1_LEN => {
+                let creator = data[Self::CREATOR_OFFSET..Self::CREATOR_OFFSET + 32].to_vec();
+                data[0] = 2;
+                data.extend_from_slice(&creator);
+                Ok(())
+            }
+            2 if data.len() == Self::V2_LEN => {
+                data[0] = 3;
+                data.resize(Self::LEN, 0);
+                Ok(())
+            }
+            _ => Err(FPPError::UnsupportedVersion.into()),
+        }
+    }
+}
+
+impl Versioned for PointTree {
+    const VERSION: u8 = 1;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() == Self::LEN - 1
+    }
+}
+
+impl Versioned for AuditLog {
+    const VERSION: u8 = 1;
+    
+    const VERSIONED_LEN: usize = Self::HEADER_LEN;
+    
+    // Versioned since its introduction
+    fn is_unversioned(_data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl Versioned for NullifierShard {
+    const VERSION: u8 = 1;
+    
+    const VERSIONED_LEN: usize = Self::HEADER_LEN;
+    
+    // Versioned since its introduction
+    fn is_unversioned(_data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl Versioned for ConcurrentTree {
+    const VERSION: u8 = 1;
+    
+    // Versioned since its introduction
+    fn is_unversioned(_data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl Versioned for WithdrawalRequest {
+    const VERSION: u8 = 1;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() == Self::LEN - 1
+    }
+}
+
+impl Versioned for WithdrawalBatch {
+    const VERSION: u8 = 1;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() == Self::LEN - 1
+    }
+}
+
+impl Versioned for NullifierSet {
+    const VERSION: u8 = 2;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() == Self::V1_LEN - 1
+    }
+    
+    /// Layout 2 adds `payer` and `withdrawal_request`, unknown for older records
+    fn upgrade(from: u8, data: &mut Vec<u8>) -> Result<(), ProgramError> {
+        match from {
+            0 => migration::prefix_version(data),
+            1 if data.len() == Self::V1_LEN => {
+                data[0] = 2;
+                data.resize(Self::LEN, 0);
+                Ok(())
+            }
+            _ => Err(FPPError::UnsupportedVersion.into()),
+        }
+    }
+}
+
+impl Versioned for PoolConfig {
+    const VERSION: u8 = 1;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() == Self::LEN - 1
+    }
+}
+
+impl Versioned for PoolState {
+    const VERSION: u8 = 1;
+    
+    // Pools were opened only once accounts were versioned
+    fn is_unversioned(_data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl Versioned for PoolRoute {
+    const VERSION: u8 = 1;
+    
+    // Routes came after pools, which were versioned from the start
+    fn is_unversioned(_data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl Versioned for PoolNullifier {
+    const VERSION: u8 = 1;
+    
+    // Versioned since the first pool withdrawal
+    fn is_unversioned(_data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl Versioned for CircuitKey {
+    const VERSION: u8 = 1;
+    
+    // Keys moved on chain after accounts had a version byte
+    fn is_unversioned(_data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl Versioned for CircuitInfo {
+    const VERSION: u8 = 1;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() == Self::LEN - 1
+    }
+}
+
+impl Versioned for ContactBook {
+    const VERSION: u8 = 1;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() == Self::LEN - 1
+    }
+}
+
+impl Versioned for AdminMultisig {
+    const VERSION: u8 = 1;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() == Self::LEN - 1
+    }
+}
+
+impl Versioned for UserActivity {
+    const VERSION: u8 = 3;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() == Self::V1_LEN - 1
+    }
+    
+    /// Layout 2 appends `deposit_epoch`, where zero reads as no deposit yet, and layout 3
+    /// `total_deposited`, counting from the upgrade
+    fn upgrade(from: u8, data: &mut Vec<u8>) -> Result<(), ProgramError> {
+        match from {
+            0 => migration::prefix_version(data),
+            1 if data.len() == Self::V1_LEN => {
+                data[0] = 2;
+                data.resize(Self::V2_LEN, 0);
+                Ok(())
+            }
+            2 if data.len() == Self::V2_LEN => {
+                data[0] = 3;
+                data.resize(Self::LEN, 0);
+                Ok(())
+            }
+            _ => Err(FPPError::UnsupportedVersion.into()),
+        }
+    }
+}
+
+impl Versioned for AssociationSet {
+    const VERSION: u8 = 1;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() == Self::LEN - 1
+    }
+}
+
+impl Versioned for RelayerConfig {
+    const VERSION: u8 = 2;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() == Self::V1_LEN - 1
+    }
+    
+    /// Layout 2 drops the verifying key, since spend signatures replaced the relay proof.
+    /// Accounts never shrink, so the key's bytes stay behind unread.
+    fn upgrade(from: u8, data: &mut Vec<u8>) -> Result<(), ProgramError> {
+        match from {
+            0 => migration::prefix_version(data),
+            1 if data.len() == Self::V1_LEN => {
+                data[0] = 2;
+                data.truncate(Self::LEN);
+                Ok(())
+            }
+            _ => Err(FPPError::UnsupportedVersion.into()),
+        }
+    }
+}
+
+impl Versioned for DepositPermit {
+    const VERSION: u8 = 1;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() == Self::LEN - 1
+    }
+}
+
+impl Versioned for SupportedMint {
+    const VERSION: u8 = 1;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() == Self::LEN - 1
+    }
+}
+
+// The rest end in a `Vec`, `String` or enum and are sized to fit it
+
+impl Versioned for RootHistory {
+    const VERSION: u8 = 1;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        migration::is_unversioned_with_tail(data, 1 + 4, 32)
+    }
+}
+
+impl Versioned for EncryptedNote {
+    const VERSION: u8 = 1;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        migration::is_unversioned_with_tail(data, 1 + 32 + 8, 1)
+    }
+}
+
+impl Versioned for SecurityEvent {
+    const VERSION: u8 = 1;
+    
+    fn is_unversioned(data: &[u8]) -> bool {
+        migration::is_unversioned_with_tail(data, 1 + 8 + 1 + 1 + 32 + 32 + 8 + 8, 1)
+    }
+}
+
+impl Versioned for AdminProposal {
+    const VERSION: u8 = 1;
+    
+    // The action has no length prefix, so check the whole account decodes once prefixed
+    fn is_unversioned(data: &[u8]) -> bool {
+        let prefixed = [&[Self::VERSION][..], data].concat();
+        Self::try_from_slice(&prefixed).is_ok()
+    }
+}
+
+impl Versioned for DenyList {
+    const VERSION: u8 = 1;
+    
+    // Removals leave slack behind the entries, so only the 32-byte stride is reliable
+    fn is_unversioned(data: &[u8]) -> bool {
+        data.len() % 32 == 1 + 4
+    }
+}
+
+impl Versioned for FeeExemptions {
+    const VERSION: u8 = 1;
+    
+    // The allowlist postdates the `version` field
+    fn is_unversioned(_data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl Versioned for WithdrawalQueue {
+    const VERSION: u8 = 1;
+    
+    // Created after accounts started carrying a version
+    fn is_unversioned(_data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl Versioned for StagedProof {
+    const VERSION: u8 = 1;
+    
+    // Staged proofs were added after accounts were versioned
+    fn is_unversioned(_data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl Versioned for TreasuryDeployment {
+    const VERSION: u8 = 1;
+    
+    // Every deployment account has carried a version byte
+    fn is_unversioned(_data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl Versioned for RewardAccount {
+    const VERSION: u8 = 1;
+    
+    // Introduced at layout 1
+    fn is_unversioned(_data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl Versioned for EpochStats {
+    const VERSION: u8 = 1;
+    
+    // No epoch was recorded before accounts carried a version
+    fn is_unversioned(_data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl Versioned for ReferralAccount {
+    const VERSION: u8 = 1;
+    
+    // Created with a version byte from the start
+    fn is_unversioned(_data: &[u8]) -> bool {
+        false
+    }
 }
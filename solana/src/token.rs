@@ -0,0 +1,160 @@
+//! Token program access covering classic SPL Token and Token-2022.
+//!
+//! The protocol's token program is fixed at initialization from the owner of
+//! the USDT mint. Transfers always use `transfer_checked`, which both programs
+//! support and Token-2022 requires for mints with a transfer fee. Accounts and
+//! mints are unpacked with the Token-2022 layout, which reads classic accounts
+//! as a base state without extensions.
+//!
+//! Native SOL goes through the wrapped SOL mint: deposits top up the user's
+//! wSOL account from their lamports and payouts can close it back into SOL.
+
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Epoch,
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction,
+};
+use spl_token_2022::extension::{
+    transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions,
+};
+
+pub use spl_token_2022::state::{Account as TokenAccount, Mint};
+
+use crate::error::FPPError;
+
+/// Whether `key` is one of the supported token programs
+pub fn is_token_program(key: &Pubkey) -> bool {
+    *key == spl_token::id() || *key == spl_token_2022::id()
+}
+
+/// Whether `mint` is the wrapped SOL mint of either token program
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == spl_token::native_mint::id() || *mint == spl_token_2022::native_mint::id()
+}
+
+/// Unpack a token account owned by either token program
+pub fn unpack_account(account_info: &AccountInfo) -> Result<TokenAccount, ProgramError> {
+    if !is_token_program(account_info.owner) {
+        return Err(FPPError::InvalidAccount.into());
+    }
+    let data = account_info.data.borrow();
+    Ok(StateWithExtensions::<TokenAccount>::unpack(&data)?.base)
+}
+
+/// Unpack a mint owned by either token program
+pub fn unpack_mint(mint_info: &AccountInfo) -> Result<Mint, ProgramError> {
+    if !is_token_program(mint_info.owner) {
+        return Err(FPPError::InvalidAccount.into());
+    }
+    let data = mint_info.data.borrow();
+    Ok(StateWithExtensions::<Mint>::unpack(&data)?.base)
+}
+
+/// Fee the mint withholds when `amount` is transferred in `epoch`
+pub fn transfer_fee(
+    mint_info: &AccountInfo,
+    epoch: Epoch,
+    amount: u64,
+) -> Result<u64, ProgramError> {
+    let data = mint_info.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    match mint.get_extension::<TransferFeeConfig>() {
+        Ok(config) => Ok(config
+            .calculate_epoch_fee(epoch, amount)
+            .ok_or(FPPError::InvalidAmount)?),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Amount to send so that `amount` arrives after the mint's transfer fee
+pub fn gross_up(
+    mint_info: &AccountInfo,
+    epoch: Epoch,
+    amount: u64,
+) -> Result<u64, ProgramError> {
+    let data = mint_info.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    let fee = match mint.get_extension::<TransferFeeConfig>() {
+        Ok(config) => config
+            .calculate_inverse_epoch_fee(epoch, amount)
+            .ok_or(FPPError::InvalidAmount)?,
+        Err(_) => 0,
+    };
+    Ok(amount.checked_add(fee).ok_or(FPPError::InvalidAmount)?)
+}
+
+/// `transfer_checked` through whichever token program owns the accounts
+#[allow(clippy::too_many_arguments)]
+pub fn transfer<'a>(
+    token_program_info: &AccountInfo<'a>,
+    source_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    authority_info: &AccountInfo<'a>,
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let transfer_ix = spl_token_2022::instruction::transfer_checked(
+        token_program_info.key,
+        source_info.key,
+        mint_info.key,
+        destination_info.key,
+        authority_info.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    invoke_signed(
+        &transfer_ix,
+        &[
+            source_info.clone(),
+            mint_info.clone(),
+            destination_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+        signer_seeds,
+    )
+}
+
+/// Move `lamports` from `payer_info` into the wSOL account `account_info` and sync its balance
+pub fn wrap_sol<'a>(
+    token_program_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    lamports: u64,
+) -> ProgramResult {
+    invoke(
+        &system_instruction::transfer(payer_info.key, account_info.key, lamports),
+        &[payer_info.clone(), account_info.clone(), system_program_info.clone()],
+    )?;
+    invoke(
+        &spl_token_2022::instruction::sync_native(token_program_info.key, account_info.key)?,
+        &[account_info.clone(), token_program_info.clone()],
+    )
+}
+
+/// Close the wSOL account `account_info`, paying its whole balance to `owner_info` as SOL
+pub fn unwrap_sol<'a>(
+    token_program_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    owner_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    let close_ix = spl_token_2022::instruction::close_account(
+        token_program_info.key,
+        account_info.key,
+        owner_info.key,
+        owner_info.key,
+        &[],
+    )?;
+    invoke(
+        &close_ix,
+        &[account_info.clone(), owner_info.clone(), token_program_info.clone()],
+    )
+}
@@ -0,0 +1,115 @@
+//! Account checks shared by the instruction handlers.
+//!
+//! Handlers take their accounts by position, so every account is pinned to
+//! what it claims to be before it is read, written or invoked: program state
+//! must be owned by the program and sit at its PDA, treasury accounts must be
+//! the protocol's own, and programs the processor calls into must be the real
+//! system and token programs. Each check fails with `FPPError::InvalidAccount`,
+//! except `check_top_level`, which guards against CPI callers.
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+
+use crate::{
+    error::FPPError,
+    pda,
+    state::{ProtocolState, SupportedMint},
+    token::{self, TokenAccount},
+};
+
+/// Whether `protocol_state_info` is the program's protocol state PDA
+pub fn is_protocol_state(program_id: &Pubkey, protocol_state_info: &AccountInfo) -> bool {
+    protocol_state_info.owner == program_id
+        && *protocol_state_info.key == pda::find_protocol_state(program_id).0
+}
+
+/// Require an account the program wrote, rather than one an attacker created with the same
+/// layout under another owner
+pub fn check_program_owned(program_id: &Pubkey, account_info: &AccountInfo) -> ProgramResult {
+    if account_info.owner != program_id {
+        return Err(FPPError::InvalidAccount.into());
+    }
+    Ok(())
+}
+
+/// Require the Groth16 verifier program, whose verdicts are trusted without re-checking
+pub fn check_verifier_program(verifier_program_info: &AccountInfo) -> ProgramResult {
+    if *verifier_program_info.key != fpp_verifier::id() || !verifier_program_info.executable {
+        return Err(FPPError::InvalidAccount.into());
+    }
+    Ok(())
+}
+
+/// Require the system program, before creating or funding accounts through it
+pub fn check_system_program(system_program_info: &AccountInfo) -> ProgramResult {
+    if *system_program_info.key != system_program::id() {
+        return Err(FPPError::InvalidAccount.into());
+    }
+    Ok(())
+}
+
+/// Require `expected`, which must itself be SPL Token or Token-2022
+///
+/// `expected` is the token program recorded for the mint being moved, so a mint can't be
+/// paid out through the other token program either.
+pub fn check_token_program(token_program_info: &AccountInfo, expected: &Pubkey) -> ProgramResult {
+    if *token_program_info.key != *expected || !token::is_token_program(expected) {
+        return Err(FPPError::InvalidAccount.into());
+    }
+    Ok(())
+}
+
+/// Unpack the treasury token account, checking it is the protocol's account for `entry`
+pub fn check_treasury_token_account(
+    protocol_state: &ProtocolState,
+    entry: &SupportedMint,
+    treasury_token_info: &AccountInfo,
+) -> Result<TokenAccount, ProgramError> {
+    if *treasury_token_info.owner != entry.token_program {
+        return Err(FPPError::InvalidAccount.into());
+    }
+    let treasury_token = token::unpack_account(treasury_token_info)?;
+    if treasury_token.owner != protocol_state.treasury || treasury_token.mint != entry.mint {
+        return Err(FPPError::InvalidAccount.into());
+    }
+    Ok(treasury_token)
+}
+
+/// Check the treasury authority PDA that signs payouts, returning its bump
+pub fn check_treasury_authority(
+    program_id: &Pubkey,
+    protocol_state: &ProtocolState,
+    treasury_authority_info: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (treasury_authority, bump) = ProtocolState::find_treasury_authority(program_id);
+    if *treasury_authority_info.key != treasury_authority
+        || protocol_state.treasury != treasury_authority
+    {
+        return Err(FPPError::InvalidAccount.into());
+    }
+    Ok(bump)
+}
+
+/// Check that the executing instruction is a top-level instruction of this program rather
+/// than a CPI from another one
+///
+/// The instructions sysvar only lists top-level instructions, so a CPI shows up as the
+/// calling program's instruction at the current index; the runtime already forbids re-entering
+/// this program through another one. Fails with `FPPError::CpiNotAllowed`.
+pub fn check_top_level(
+    program_id: &Pubkey,
+    instructions_sysvar_info: &AccountInfo,
+) -> ProgramResult {
+    let current = load_current_index_checked(instructions_sysvar_info)?;
+    let instruction = load_instruction_at_checked(current as usize, instructions_sysvar_info)?;
+    if instruction.program_id != *program_id {
+        return Err(FPPError::CpiNotAllowed.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,29 @@
+//! In-place access to hot state accounts.
+//!
+//! `ProtocolState` and `CommitmentTree` are `#[repr(C)]` with every field
+//! ordered so there is no padding. Their memory layout is therefore exactly
+//! their Borsh encoding: handlers on the deposit and spend paths borrow the
+//! account data as the struct and mutate it in place, while clients and the
+//! colder admin handlers keep using Borsh unchanged.
+
+use bytemuck::{CheckedBitPattern, NoUninit};
+use solana_program::program_error::ProgramError;
+
+pub trait ZeroCopy: CheckedBitPattern + NoUninit {
+    /// Account data size, equal to both `size_of::<Self>()` and the Borsh length
+    const SIZE: usize = std::mem::size_of::<Self>();
+    
+    /// View the start of `data` as `Self`, checking every `bool` is 0 or 1
+    fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        let bytes = data.get(..Self::SIZE).ok_or(ProgramError::AccountDataTooSmall)?;
+        bytemuck::checked::try_from_bytes(bytes).map_err(|_| ProgramError::InvalidAccountData)
+    }
+    
+    /// Mutable view of the start of `data`; writes land directly in the account
+    fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let bytes = data
+            .get_mut(..Self::SIZE)
+            .ok_or(ProgramError::AccountDataTooSmall)?;
+        bytemuck::checked::try_from_bytes_mut(bytes).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
@@ -0,0 +1,379 @@
+mod common;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    compression::ACCOUNT_COMPRESSION_ID,
+    concurrent_tree,
+    crypto::groth16::Groth16VerifyingKey,
+    error::FPPError,
+    instruction,
+    pda,
+    state::{AdminAction, AdminMultisig, CircuitKey, CommitmentTree, ProtocolState},
+};
+use solana_sdk::{
+    hash::hash,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+/// Propose `action` as proposal `index`, approve it with `approver` and execute it
+/// 
+/// `signed` is the instruction the multisig ends up signing; its accounts follow the
+/// execution's own.
+fn pass_proposal(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    approver: &Pubkey,
+    index: u64,
+    action: AdminAction,
+    signed: &Instruction,
+) -> Vec<Instruction> {
+    let (protocol_state, _) = pda::find_protocol_state(program_id);
+    let mut execute =
+        instruction::execute_admin_action(program_id, proposer, &protocol_state, index).unwrap();
+    execute.accounts.extend(signed.accounts.iter().map(|meta| AccountMeta {
+        is_signer: false,
+        ..meta.clone()
+    }));
+    vec![
+        instruction::propose_admin_action(program_id, proposer, index, action).unwrap(),
+        instruction::approve_admin_action(program_id, approver, index).unwrap(),
+        execute,
+    ]
+}
+
+#[tokio::test]
+async fn multisig_takes_over_authority_and_enforces_threshold() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let second = Keypair::new();
+    let third = Keypair::new();
+    let (multisig, _) = AdminMultisig::find_address(&program_id);
+    
+    let create = instruction::create_admin_multisig(
+        &program_id,
+        &authority,
+        &protocol_state,
+        vec![authority, second.pubkey(), third.pubkey()],
+        2,
+    )
+    .unwrap();
+    let propose_multisig =
+        instruction::propose_authority(&program_id, &authority, &protocol_state, multisig).unwrap();
+    harness.process(&[create, propose_multisig], &[]).await.unwrap();
+    
+    // Proposal 0: the multisig accepts the authority
+    let accept = instruction::propose_admin_action(
+        &program_id,
+        &authority,
+        0,
+        AdminAction::AcceptAuthority,
+    )
+    .unwrap();
+    let approve = instruction::approve_admin_action(&program_id, &second.pubkey(), 0).unwrap();
+    let execute =
+        instruction::execute_admin_action(&program_id, &authority, &protocol_state, 0).unwrap();
+    harness.process(&[accept, approve, execute], &[&second]).await.unwrap();
+    
+    let state = harness.context.banks_client.get_account(protocol_state).await.unwrap().unwrap();
+    assert_eq!(ProtocolState::try_from_slice(&state.data).unwrap().authority, multisig);
+    
+    // The old authority alone can no longer change the pause flags; as the guardian it could
+    // still send `SetPaused { paused: true }`
+    let direct = instruction::set_paused_flags(
+        &program_id,
+        &authority,
+        &protocol_state,
+        ProtocolState::PAUSE_ALL,
+    )
+    .unwrap();
+    harness.expect_unauthorized(&[direct], &[]).await;
+    
+    // Proposal 1: pausing needs a second approval before it can execute
+    let pause = instruction::propose_admin_action(
+        &program_id,
+        &authority,
+        1,
+        AdminAction::SetPaused { paused: true },
+    )
+    .unwrap();
+    harness.process(&[pause], &[]).await.unwrap();
+    let execute =
+        instruction::execute_admin_action(&program_id, &authority, &protocol_state, 1).unwrap();
+    harness.expect_error(&[execute.clone()], &[], FPPError::ProposalNotApproved).await;
+    
+    let approve = instruction::approve_admin_action(&program_id, &third.pubkey(), 1).unwrap();
+    harness.process(&[approve, execute], &[&third]).await.unwrap();
+    
+    let state = harness.context.banks_client.get_account(protocol_state).await.unwrap().unwrap();
+    assert_eq!(
+        ProtocolState::try_from_slice(&state.data).unwrap().paused_flags,
+        ProtocolState::PAUSE_ALL
+    );
+}
+
+#[tokio::test]
+async fn multisig_runs_circuit_fee_tree_and_audit_log_actions() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token = common::add_token_account(&mut program_test, &mint, &treasury, 1_000);
+    let fee_receiver = Pubkey::new_unique();
+    let fee_receiver_token = common::add_token_account(&mut program_test, &mint, &fee_receiver, 0);
+    // Sized without the canopy the proposal asks for
+    let merkle_tree = common::add_program_account(
+        &mut program_test,
+        &ACCOUNT_COMPRESSION_ID,
+        concurrent_tree::account_len(20, 64, 0),
+    );
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let second = Keypair::new();
+    let (multisig, _) = AdminMultisig::find_address(&program_id);
+    
+    let setup = [
+        instruction::create_admin_multisig(
+            &program_id,
+            &authority,
+            &protocol_state,
+            vec![authority, second.pubkey()],
+            2,
+        )
+        .unwrap(),
+        instruction::propose_authority(&program_id, &authority, &protocol_state, multisig)
+            .unwrap(),
+    ];
+    harness.process(&setup, &[]).await.unwrap();
+    let accept = instruction::accept_authority(&program_id, &multisig, &protocol_state).unwrap();
+    let accept = pass_proposal(
+        &program_id,
+        &authority,
+        &second.pubkey(),
+        0,
+        AdminAction::AcceptAuthority,
+        &accept,
+    );
+    harness.process(&accept, &[&second]).await.unwrap();
+    
+    // The executor pays for the circuit and its key, which the multisig PDA can't
+    let verifying_key = Groth16VerifyingKey {
+        alpha_g1: [1u8; 64],
+        beta_g2: [1u8; 128],
+        gamma_g2: [1u8; 128],
+        delta_g2: [1u8; 128],
+        ic: [[1u8; 64]; 3],
+    };
+    let key_hash = hash(&verifying_key.try_to_vec().unwrap()).to_bytes();
+    let register = instruction::register_circuit(
+        &program_id,
+        &multisig,
+        &protocol_state,
+        &authority,
+        1,
+        20,
+        1,
+        1,
+        key_hash,
+    )
+    .unwrap();
+    let register = pass_proposal(
+        &program_id,
+        &authority,
+        &second.pubkey(),
+        1,
+        AdminAction::RegisterCircuit {
+            circuit_id: 1,
+            tree_depth: 20,
+            max_inputs: 1,
+            max_outputs: 1,
+            verifying_key_hash: key_hash,
+        },
+        &register,
+    );
+    harness.process(&register, &[&second]).await.unwrap();
+    let publish = instruction::update_verifying_key(
+        &program_id,
+        &multisig,
+        &protocol_state,
+        &authority,
+        1,
+        1,
+        verifying_key.clone(),
+    )
+    .unwrap();
+    let publish = pass_proposal(
+        &program_id,
+        &authority,
+        &second.pubkey(),
+        2,
+        AdminAction::UpdateVerifyingKey {
+            circuit_id: 1,
+            circuit_version: 1,
+            verifying_key: verifying_key.clone(),
+        },
+        &publish,
+    );
+    harness.process(&publish, &[&second]).await.unwrap();
+    let (key_address, _) = CircuitKey::find_address(1, &program_id);
+    let account = harness.context.banks_client.get_account(key_address).await.unwrap().unwrap();
+    assert_eq!(CircuitKey::try_from_slice(&account.data).unwrap().verifying_key, verifying_key);
+    
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| {
+            state.total_fees = 1_000;
+            state.fee_receiver = fee_receiver;
+        })
+        .await;
+    let collect = instruction::collect_fees(
+        &program_id,
+        &multisig,
+        &protocol_state,
+        &treasury_token,
+        &fee_receiver_token,
+        &mint,
+        &spl_token::id(),
+    )
+    .unwrap();
+    let collect = pass_proposal(
+        &program_id,
+        &authority,
+        &second.pubkey(),
+        3,
+        AdminAction::CollectFees {
+            mint,
+            token_program: spl_token::id(),
+            treasury_token,
+            fee_receiver_token,
+        },
+        &collect,
+    );
+    harness.process(&collect, &[&second]).await.unwrap();
+    assert_eq!(harness.token_balance(&fee_receiver_token).await, 1_000);
+    
+    // An early rollover needs the authority's signature, which the old authority no longer has
+    let (tree_address, _) = CommitmentTree::find_address(&program_id);
+    harness
+        .update_account::<CommitmentTree>(&tree_address, |tree| {
+            tree.insert([1u8; 32]).unwrap();
+        })
+        .await;
+    let direct =
+        instruction::rollover_tree(&program_id, &authority, &protocol_state, 0, Some(&authority))
+            .unwrap();
+    harness.expect_unauthorized(&[direct], &[]).await;
+    let rollover =
+        instruction::rollover_tree(&program_id, &authority, &protocol_state, 0, Some(&multisig))
+            .unwrap();
+    let rollover = pass_proposal(
+        &program_id,
+        &authority,
+        &second.pubkey(),
+        4,
+        AdminAction::RolloverTree { generation: 0 },
+        &rollover,
+    );
+    harness.process(&rollover, &[&second]).await.unwrap();
+    let account = harness.context.banks_client.get_account(tree_address).await.unwrap().unwrap();
+    assert_eq!(CommitmentTree::try_from_slice(&account.data).unwrap().generation, 1);
+    
+    // The tree's shape is still checked when the multisig signs
+    let create_tree = instruction::create_concurrent_tree(
+        &program_id,
+        &multisig,
+        &protocol_state,
+        &authority,
+        &merkle_tree,
+        20,
+        64,
+        5,
+    )
+    .unwrap();
+    let create_tree = pass_proposal(
+        &program_id,
+        &authority,
+        &second.pubkey(),
+        5,
+        AdminAction::CreateConcurrentTree {
+            merkle_tree,
+            max_depth: 20,
+            max_buffer_size: 64,
+            canopy_depth: 5,
+        },
+        &create_tree,
+    );
+    harness.expect_error(&create_tree, &[&second], FPPError::InvalidAccount).await;
+    
+    let create_log =
+        instruction::create_audit_log(&program_id, &multisig, &protocol_state, &authority)
+            .unwrap();
+    let create_log = pass_proposal(
+        &program_id,
+        &authority,
+        &second.pubkey(),
+        5,
+        AdminAction::CreateAuditLog,
+        &create_log,
+    );
+    harness.process(&create_log, &[&second]).await.unwrap();
+    let state = harness.context.banks_client.get_account(protocol_state).await.unwrap().unwrap();
+    assert!(ProtocolState::try_from_slice(&state.data).unwrap().has_audit_log);
+}
+
+#[tokio::test]
+async fn only_multisig_signers_act_through_it() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let stranger = common::add_signer(&mut program_test);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let second = Keypair::new();
+    let signers = vec![authority, second.pubkey()];
+    let create = |caller: &Pubkey| {
+        instruction::create_admin_multisig(&program_id, caller, &protocol_state, signers.clone(), 2)
+            .unwrap()
+    };
+    
+    // Neither the protocol state nor the multisig can be swapped out
+    harness.expect_authority_only(create, &[1, 2]).await;
+    harness.process(&[create(&authority)], &[]).await.unwrap();
+    
+    let propose = |proposer: &Pubkey| {
+        let action = AdminAction::SetPaused { paused: true };
+        instruction::propose_admin_action(&program_id, proposer, 0, action).unwrap()
+    };
+    harness.expect_unauthorized(&[propose(&stranger.pubkey())], &[&stranger]).await;
+    for index in [1, 2] {
+        let elsewhere = common::with_account(propose(&authority), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[], FPPError::InvalidAccount).await;
+    }
+    // Admin proposals aren't held up by a pause, which they may be needed to lift
+    harness.pause(ProtocolState::PAUSE_ALL).await;
+    harness.process(&[propose(&authority)], &[]).await.unwrap();
+    
+    let approve = |signer: &Pubkey| {
+        instruction::approve_admin_action(&program_id, signer, 0).unwrap()
+    };
+    harness.expect_unauthorized(&[approve(&stranger.pubkey())], &[&stranger]).await;
+    for index in [1, 2] {
+        let elsewhere =
+            common::with_account(approve(&second.pubkey()), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[&second], FPPError::InvalidAccount).await;
+    }
+    
+    let execute =
+        instruction::execute_admin_action(&program_id, &authority, &protocol_state, 0).unwrap();
+    for index in [1, 2] {
+        let elsewhere = common::with_account(execute.clone(), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[], FPPError::InvalidAccount).await;
+    }
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    crypto::groth16::{Groth16Proof, Groth16VerifyingKey},
+    error::FPPError,
+    instruction,
+    pda,
+    state::{ProtocolState, WITHDRAWAL_DELAY},
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+fn verifying_key() -> Groth16VerifyingKey {
+    Groth16VerifyingKey {
+        alpha_g1: [1u8; 64],
+        beta_g2: [1u8; 128],
+        gamma_g2: [1u8; 128],
+        delta_g2: [1u8; 128],
+        ic: [[1u8; 64]; 3],
+    }
+}
+
+fn proof() -> Groth16Proof {
+    Groth16Proof {
+        a: [1u8; 64],
+        b: [1u8; 128],
+        c: [1u8; 64],
+    }
+}
+
+/// A protocol requiring compliance proofs, with a withdrawal of one point requested
+async fn requested_withdrawal() -> (TestHarness, Pubkey) {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let key = SpendKey::new(3);
+    let point = common::add_point(&mut program_test, &program_id, &common::point(&key, &mint));
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    harness.set_point_creator(&point, user).await;
+    
+    let update = instruction::update_association_set(
+        &program_id,
+        &user,
+        &protocol_state,
+        &user,
+        [9u8; 32],
+        true,
+        Some(verifying_key()),
+    )
+    .unwrap();
+    harness.process(&[update], &[]).await.unwrap();
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 1)
+        .await;
+    
+    let request = instruction::request_withdrawal(
+        &program_id,
+        &user,
+        &protocol_state,
+        vec![point],
+        vec![key.nullifier()],
+        0,
+        common::sign_withdrawal(&program_id, &user, 0, &[&key]),
+    )
+    .unwrap();
+    harness.process(&[request], &[]).await.unwrap();
+    (harness, mint)
+}
+
+#[tokio::test]
+async fn required_proof_blocks_unproven_withdrawals() {
+    let (mut harness, mint) = requested_withdrawal().await;
+    let program_id = harness.program_id;
+    let user = harness.payer();
+    let (withdrawal_request, _) = pda::find_withdrawal_request(&user, 0, &program_id);
+    harness.warp_seconds(WITHDRAWAL_DELAY).await;
+    
+    let epoch = harness.clock().await.epoch;
+    let complete = instruction::complete_withdrawal(
+        &program_id,
+        &user,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &harness.protocol_state(),
+        &withdrawal_request,
+        &mint,
+        &spl_token::id(),
+        false,
+        epoch,
+    )
+    .unwrap();
+    harness.expect_error(&[complete], &[], FPPError::InvalidComplianceProof).await;
+}
+
+#[tokio::test]
+async fn only_the_authority_updates_the_association_set() {
+    let (mut harness, _) = requested_withdrawal().await;
+    let program_id = harness.program_id;
+    let protocol_state = harness.protocol_state();
+    let update = |authority: &Pubkey| {
+        instruction::update_association_set(
+            &program_id,
+            authority,
+            &protocol_state,
+            authority,
+            [8u8; 32],
+            true,
+            None,
+        )
+        .unwrap()
+    };
+    
+    // Neither the protocol state nor the association set can be swapped out
+    harness.expect_authority_only(update, &[1, 2]).await;
+}
+
+#[tokio::test]
+async fn only_the_requester_proves_compliance() {
+    let (mut harness, _) = requested_withdrawal().await;
+    let program_id = harness.program_id;
+    let user = harness.payer();
+    let (withdrawal_request, _) = pda::find_withdrawal_request(&user, 0, &program_id);
+    let stranger = Keypair::new();
+    let prove = |user: &Pubkey| {
+        instruction::prove_withdrawal_compliance(&program_id, user, &withdrawal_request, proof())
+            .unwrap()
+    };
+    
+    harness.expect_unauthorized(&[prove(&stranger.pubkey())], &[&stranger]).await;
+    for index in [1, 2] {
+        let elsewhere = common::with_account(prove(&user), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[], FPPError::InvalidAccount).await;
+    }
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use borsh::BorshSerialize;
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    state::{AuditAction, AuditEntry, AuditLog, ProtocolState},
+};
+use solana_sdk::pubkey::Pubkey;
+
+#[tokio::test]
+async fn admin_actions_are_appended_once_the_log_exists() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let (audit_log, _) = AuditLog::find_address(&program_id);
+    
+    // Not recorded: there is no log yet
+    let pause = instruction::set_paused_flags(
+        &program_id,
+        &user,
+        &protocol_state,
+        ProtocolState::PAUSE_DEPOSITS,
+    )
+    .unwrap();
+    harness.process(&[pause], &[]).await.unwrap();
+    
+    let create = instruction::create_audit_log(&program_id, &user, &protocol_state, &user).unwrap();
+    let unpause = instruction::set_paused_flags(&program_id, &user, &protocol_state, 0).unwrap();
+    let fees = instruction::update_fees(&program_id, &user, &protocol_state, 20, 30).unwrap();
+    harness.process(&[create, unpause, fees.clone()], &[]).await.unwrap();
+    
+    // Once the log exists, admin actions can't leave it out
+    let unlogged = common::with_account(fees, 2, Pubkey::new_unique());
+    harness.expect_error(&[unlogged], &[], FPPError::InvalidAccount).await;
+    
+    let account = harness
+        .context
+        .banks_client
+        .get_account(audit_log)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), AuditLog::LEN);
+    assert_eq!(AuditLog::unpack_header(&account.data).unwrap().count, 2);
+    
+    let entries = AuditLog::entries(&account.data).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].sequence, 0);
+    assert_eq!(entries[0].actor, user);
+    assert_eq!(entries[0].action, AuditAction::SetPausedFlags);
+    assert_eq!(entries[0].old_value, AuditEntry::value(&[ProtocolState::PAUSE_DEPOSITS]));
+    assert_eq!(entries[0].new_value, AuditEntry::value(&[0]));
+    assert_eq!(entries[1].sequence, 1);
+    assert_eq!(entries[1].action, AuditAction::UpdateFees);
+    assert_eq!(entries[1].new_value, AuditEntry::value(&[20, 0, 30, 0]));
+}
+
+#[tokio::test]
+async fn only_the_authority_creates_the_audit_log() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    let create = |authority: &Pubkey| {
+        instruction::create_audit_log(&program_id, authority, &protocol_state, authority).unwrap()
+    };
+    
+    // Neither the protocol state nor the log can be swapped out
+    harness.expect_authority_only(create, &[1, 2]).await;
+}
+
+#[test]
+fn log_keeps_the_latest_entries() {
+    let mut data = vec![0u8; AuditLog::LEN];
+    let header = AuditLog {
+        version: 1,
+        is_initialized: true,
+        count: 0,
+    };
+    header.serialize(&mut &mut data[..]).unwrap();
+    
+    let entry = |value: u64| AuditEntry {
+        sequence: 0,
+        actor: Pubkey::default(),
+        action: AuditAction::SetMinExitSlots,
+        old_value: [0u8; 32],
+        new_value: AuditEntry::value(&value.to_le_bytes()),
+        timestamp: 0,
+    };
+    for value in 0..AuditLog::CAPACITY as u64 + 3 {
+        assert_eq!(AuditLog::append(&mut data, entry(value)).unwrap(), value);
+    }
+    
+    let entries = AuditLog::entries(&data).unwrap();
+    assert_eq!(entries.len(), AuditLog::CAPACITY);
+    assert_eq!(entries[0].sequence, 3);
+    assert_eq!(entries[0].new_value, AuditEntry::value(&3u64.to_le_bytes()));
+    assert_eq!(entries.last().unwrap().sequence, AuditLog::CAPACITY as u64 + 2);
+    
+    // Longer values are hashed rather than truncated
+    assert_ne!(AuditEntry::value(&[1u8; 33]), AuditEntry::value(&[1u8; 32]));
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    pda,
+    state::{
+        FloatingPoint, NullifierSet, ProtocolState, UserActivity, WithdrawalQueue,
+        WithdrawalRequest, POINT_VALUE,
+    },
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn cancel_reactivates_points_and_releases_nullifiers() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let key = SpendKey::new(3);
+    let point = common::add_point(&mut program_test, &program_id, &common::point(&key, &mint));
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    harness.set_point_creator(&point, user).await;
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 1)
+        .await;
+    let (withdrawal_request, _) = pda::find_withdrawal_request(&user, 0, &program_id);
+    let nullifier = key.nullifier();
+    
+    let request = instruction::request_withdrawal(
+        &program_id,
+        &user,
+        &protocol_state,
+        vec![point],
+        vec![nullifier],
+        0,
+        common::sign_withdrawal(&program_id, &user, 0, &[&key]),
+    )
+    .unwrap();
+    harness.process(&[request], &[]).await.unwrap();
+    
+    let stranger = Keypair::new();
+    let cancel = |user: &Pubkey| {
+        instruction::cancel_withdrawal(
+            &program_id,
+            user,
+            &protocol_state,
+            &withdrawal_request,
+            &[point],
+            &[nullifier],
+            false,
+        )
+        .unwrap()
+    };
+    harness.expect_unauthorized(&[cancel(&stranger.pubkey())], &[&stranger]).await;
+    // The protocol state, request, point, nullifier, activity, rewards and queue can't be
+    // swapped out
+    for index in [1, 2, 3, 4, 5, 6, 7] {
+        let elsewhere = common::with_account(cancel(&user), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[], FPPError::InvalidAccount).await;
+    }
+    
+    // A pause never traps points in a pending withdrawal
+    harness.pause(ProtocolState::PAUSE_ALL).await;
+    harness.process(&[cancel(&user)], &[]).await.unwrap();
+    
+    let banks = &mut harness.context.banks_client;
+    let request = banks.get_account(withdrawal_request).await.unwrap().unwrap();
+    assert!(WithdrawalRequest::try_from_slice(&request.data).unwrap().cancelled);
+    
+    let point = banks.get_account(point).await.unwrap().unwrap();
+    assert!(FloatingPoint::try_from_slice(&point.data).unwrap().is_active);
+    
+    let (nullifier_address, _) = NullifierSet::find_address(&nullifier, &program_id);
+    assert!(banks.get_account(nullifier_address).await.unwrap().is_none());
+    
+    let state = banks.get_account(protocol_state).await.unwrap().unwrap();
+    assert_eq!(ProtocolState::try_from_slice(&state.data).unwrap().total_points, 1);
+    
+    // The cancelled request no longer counts against the user's rate limit
+    let (activity, _) = UserActivity::find_address(&user, &program_id);
+    let activity = banks.get_account(activity).await.unwrap().unwrap();
+    let activity = UserActivity::try_from_slice(&activity.data).unwrap();
+    assert_eq!((activity.withdrawal_count, activity.withdrawal_volume), (0, 0));
+    
+    // Nor does it wait in the queue for the crank
+    let (queue, _) = WithdrawalQueue::find_address(&program_id);
+    let queue = banks.get_account(queue).await.unwrap().unwrap();
+    assert!(WithdrawalQueue::deserialize(&mut &queue.data[..]).unwrap().requests.is_empty());
+}
+
+#[tokio::test]
+async fn permanent_cancel_releases_the_burned_value() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let keys: Vec<_> = (3..=4u8).map(SpendKey::new).collect();
+    let points: Vec<_> = keys
+        .iter()
+        .map(|key| common::add_point(&mut program_test, &program_id, &common::point(key, &mint)))
+        .collect();
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    for point in &points {
+        harness.set_point_creator(point, user).await;
+    }
+    // The points were added directly rather than deposited, so count them here
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| {
+            state.total_points = 2;
+            state.value_locked = 2 * POINT_VALUE;
+        })
+        .await;
+    
+    let request = instruction::request_withdrawal(
+        &program_id,
+        &user,
+        &protocol_state,
+        vec![points[0]],
+        vec![keys[0].nullifier()],
+        0,
+        common::sign_withdrawal(&program_id, &user, 0, &[&keys[0]]),
+    )
+    .unwrap();
+    harness.process(&[request], &[]).await.unwrap();
+    
+    let (withdrawal_request, _) = pda::find_withdrawal_request(&user, 0, &program_id);
+    let cancel = instruction::cancel_withdrawal(
+        &program_id,
+        &user,
+        &protocol_state,
+        &withdrawal_request,
+        &[points[0]],
+        &[keys[0].nullifier()],
+        true,
+    )
+    .unwrap();
+    harness.process(&[cancel], &[]).await.unwrap();
+    
+    // Only the other point still backs the pool
+    let banks = &mut harness.context.banks_client;
+    let state = banks.get_account(protocol_state).await.unwrap().unwrap();
+    let state = ProtocolState::try_from_slice(&state.data).unwrap();
+    assert_eq!(state.value_locked, POINT_VALUE);
+    assert_eq!(state.total_points, 1);
+    let point = banks.get_account(points[0]).await.unwrap().unwrap();
+    let point = FloatingPoint::try_from_slice(&point.data).unwrap();
+    assert!(!point.is_active);
+    assert_eq!(point.mass, 0);
+    
+    let (activity, _) = UserActivity::find_address(&user, &program_id);
+    let activity = banks.get_account(activity).await.unwrap().unwrap();
+    let activity = UserActivity::try_from_slice(&activity.data).unwrap();
+    assert_eq!((activity.withdrawal_count, activity.withdrawal_volume), (0, 0));
+}
@@ -0,0 +1,138 @@
+mod common;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    pda,
+    state::{FloatingPoint, NullifierFilter, NullifierSet, NullifierShard, ProtocolState},
+};
+use solana_sdk::pubkey::Pubkey;
+
+#[tokio::test]
+async fn burned_withdrawal_accounts_are_reclaimed() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let rent_payer = Pubkey::new_unique();
+    let key = SpendKey::new(3);
+    let point = FloatingPoint {
+        rent_payer,
+        ..common::point(&key, &mint)
+    };
+    let burned = common::add_point(&mut program_test, &program_id, &point);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    harness.set_point_creator(&burned, user).await;
+    let (withdrawal_request, _) = pda::find_withdrawal_request(&user, 0, &program_id);
+    let nullifier = key.nullifier();
+    let (nullifier_address, _) = NullifierSet::find_address(&nullifier, &program_id);
+    
+    let extend =
+        instruction::extend_nullifier_filter(&program_id, &user, &protocol_state, &user).unwrap();
+    harness.process(&vec![extend; instruction::nullifier_filter_calls()], &[]).await.unwrap();
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 1)
+        .await;
+    
+    let request = instruction::request_withdrawal(
+        &program_id,
+        &user,
+        &protocol_state,
+        vec![burned],
+        vec![nullifier],
+        0,
+        common::sign_withdrawal(&program_id, &user, 0, &[&key]),
+    )
+    .unwrap();
+    harness.process(&[request], &[]).await.unwrap();
+    
+    // An open request still holds its points and nullifiers
+    let record = fetch::<NullifierSet>(&mut harness, nullifier_address).await;
+    assert_eq!(record.payer, user);
+    assert_eq!(record.withdrawal_request, withdrawal_request);
+    let compact =
+        instruction::compact_nullifiers(&program_id, &protocol_state, &user, &[record]).unwrap();
+    harness.expect_error(&[compact.clone()], &[], FPPError::AccountInUse).await;
+    let close_point = instruction::close_spent_point(&program_id, &burned, &rent_payer).unwrap();
+    harness.expect_error(&[close_point.clone()], &[], FPPError::AccountInUse).await;
+    
+    let cancel = instruction::cancel_withdrawal(
+        &program_id,
+        &user,
+        &protocol_state,
+        &withdrawal_request,
+        &[burned],
+        &[nullifier],
+        true,
+    )
+    .unwrap();
+    harness.process(&[cancel], &[]).await.unwrap();
+    
+    let close_request =
+        instruction::close_withdrawal_request(&program_id, &withdrawal_request, &user, &[], &[])
+            .unwrap();
+    
+    // Anyone may close, but the rent only goes back to whoever paid it, and the protocol's
+    // own accounts can't be swapped out
+    let misdirected = [
+        (close_point.clone(), 1),
+        (close_request.clone(), 0),
+        (close_request.clone(), 1),
+        (compact.clone(), 0),
+        (compact.clone(), 1),
+        (compact.clone(), 4),
+        (compact.clone(), 6),
+    ];
+    for (instruction, index) in misdirected {
+        let elsewhere = common::with_account(instruction, index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[], FPPError::InvalidAccount).await;
+    }
+    
+    harness.process(&[close_point, compact, close_request], &[]).await.unwrap();
+    
+    let banks = &mut harness.context.banks_client;
+    assert!(banks.get_account(burned).await.unwrap().is_none());
+    assert!(banks.get_account(rent_payer).await.unwrap().is_some());
+    assert!(banks.get_account(withdrawal_request).await.unwrap().is_none());
+    assert!(banks.get_account(nullifier_address).await.unwrap().is_none());
+    let filter = banks
+        .get_account(NullifierFilter::find_address(&program_id).0)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(filter.data.len(), NullifierFilter::LEN);
+    let shard = banks
+        .get_account(NullifierShard::find_address(&nullifier, &program_id).0)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(shard.data.len(), NullifierShard::HEADER_LEN + NullifierShard::ENTRY_LEN);
+    assert_eq!(NullifierShard::unpack_header(&shard.data).unwrap().count, 1);
+    assert!(NullifierShard::contains(&shard.data, &nullifier));
+    
+    // A compacted nullifier can't be spent again, even by a point restored at its commitment
+    let restored = FloatingPoint {
+        creator: user,
+        ..point
+    };
+    harness.set_program_account(&burned, restored.try_to_vec().unwrap());
+    let respend = instruction::request_withdrawal(
+        &program_id,
+        &user,
+        &protocol_state,
+        vec![burned],
+        vec![nullifier],
+        1,
+        common::sign_withdrawal(&program_id, &user, 1, &[&key]),
+    )
+    .unwrap();
+    harness.expect_error(&[respend], &[], FPPError::NullifierAlreadyUsed).await;
+}
+
+async fn fetch<T: BorshDeserialize>(harness: &mut TestHarness, address: Pubkey) -> T {
+    let account = harness.context.banks_client.get_account(address).await.unwrap().unwrap();
+    T::try_from_slice(&account.data).unwrap()
+}
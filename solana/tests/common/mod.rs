@@ -0,0 +1,568 @@
+//! Shared harness for `solana-program-test` integration tests.
+//!
+//! Time-dependent paths read the Clock sysvar, so `warp_seconds` rewrites it
+//! directly instead of waiting on real slots. `TestHarness::start_initialized`
+//! is the usual starting point: a protocol the payer initialized, whose state
+//! tests then adjust with `update_account` rather than through admin flows.
+
+#![allow(dead_code)]
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, scalar::Scalar};
+use floating_point_protocol_solana::{
+    crypto::{
+        opening::{self, OpeningProof},
+        poseidon,
+        ring::{self, RingSignature},
+    },
+    error::FPPError,
+    instruction,
+    migration::Versioned,
+    pda, process_instruction,
+    state::{FloatingPoint, ProtocolState, RootHistory, WithdrawalRequest},
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    clock::Clock,
+    instruction::{Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::{TransferFee, TransferFeeAmount, TransferFeeConfig},
+        BaseStateWithExtensionsMut, ExtensionType, StateWithExtensions, StateWithExtensionsMut,
+    },
+    state::{Account as TokenAccount, AccountState, Mint},
+};
+
+/// Deposit and withdrawal fee rate of `TestHarness::start_initialized`, in basis points
+pub const FEE_RATE: u16 = 10;
+
+pub fn program_test(program_id: Pubkey) -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "floating_point_protocol_solana",
+        program_id,
+        processor!(process_instruction),
+    );
+    program_test.add_program(
+        "fpp_verifier",
+        fpp_verifier::id(),
+        processor!(fpp_verifier::process_instruction),
+    );
+    program_test
+}
+
+/// Pre-allocate a zeroed, rent-exempt account owned by the program
+pub fn add_program_account(program_test: &mut ProgramTest, program_id: &Pubkey, len: usize) -> Pubkey {
+    add_program_account_with_data(program_test, program_id, vec![0; len])
+}
+
+/// Add a rent-exempt, program-owned account holding `data`
+pub fn add_program_account_with_data(
+    program_test: &mut ProgramTest,
+    program_id: &Pubkey,
+    data: Vec<u8>,
+) -> Pubkey {
+    let address = Pubkey::new_unique();
+    program_test.add_account(
+        address,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: *program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    address
+}
+
+/// A keypair holding one SOL, for a signer other than the payer
+pub fn add_signer(program_test: &mut ProgramTest) -> Keypair {
+    let signer = Keypair::new();
+    program_test.add_account(
+        signer.pubkey(),
+        Account {
+            lamports: LAMPORTS_PER_SOL,
+            ..Account::default()
+        },
+    );
+    signer
+}
+
+/// An active, unlocked point of one unit of `mint` behind `key`
+///
+/// Its creator is unset; the payer only exists once the harness starts, so
+/// tests hand it the point with `TestHarness::set_point_creator`.
+pub fn point(key: &SpendKey, mint: &Pubkey) -> FloatingPoint {
+    FloatingPoint {
+        version: FloatingPoint::VERSION,
+        is_initialized: true,
+        commitment: key.commitment(),
+        created_at: 0,
+        mass: 1,
+        is_active: true,
+        creator: Pubkey::default(),
+        locked_until: 0,
+        created_slot: 0,
+        mint: *mint,
+        rent_payer: Pubkey::default(),
+        accrued: 0,
+        mass_updated_at: 0,
+    }
+}
+
+/// Add `point` at the PDA of its commitment
+pub fn add_point(
+    program_test: &mut ProgramTest,
+    program_id: &Pubkey,
+    point: &FloatingPoint,
+) -> Pubkey {
+    let (address, _) = pda::find_point_address(&point.commitment, program_id);
+    program_test.add_account(
+        address,
+        Account {
+            lamports: Rent::default().minimum_balance(FloatingPoint::LEN),
+            data: point.try_to_vec().unwrap(),
+            owner: *program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    address
+}
+
+/// Secret key of a point a test spends; the point's commitment is `secret·G`
+pub struct SpendKey(Scalar);
+
+impl SpendKey {
+    /// Re-sampled like a wallet's, until the commitment is a valid tree leaf
+    pub fn new(seed: u8) -> Self {
+        (0u8..)
+            .map(|attempt| {
+                let input = [b"test-spend-key".as_slice(), &[seed, attempt]].concat();
+                Self(ring::hash_to_scalar(&input))
+            })
+            .find(|key| poseidon::is_field_element(&key.commitment()))
+            .unwrap()
+    }
+    
+    pub fn commitment(&self) -> [u8; 32] {
+        (self.0 * RISTRETTO_BASEPOINT_POINT).compress().to_bytes()
+    }
+    
+    /// The point's key image, the only nullifier it can be spent under
+    pub fn nullifier(&self) -> [u8; 32] {
+        (self.0 * ring::hash_to_point(&self.commitment())).compress().to_bytes()
+    }
+    
+    /// Proof that `depositor` knows the key, for a deposit of `amount`
+    pub fn opening_proof(&self, amount: u64, depositor: &Pubkey) -> OpeningProof {
+        let commitment = self.commitment();
+        let nonce = ring::hash_to_scalar(&[self.0.as_bytes().as_slice(), &commitment].concat());
+        let nonce_commitment = (nonce * RISTRETTO_BASEPOINT_POINT).compress().to_bytes();
+        let challenge = opening::challenge(&commitment, amount, depositor, &nonce_commitment);
+        OpeningProof {
+            nonce_commitment,
+            response: (nonce + challenge * self.0).to_bytes(),
+        }
+    }
+}
+
+/// Borsh-encoded spend signature by `keys` over `message`: a one-row ring of their commitments
+pub fn sign_spend(message: &[u8; 32], keys: &[&SpendKey]) -> Vec<u8> {
+    let alphas: Vec<Scalar> = keys
+        .iter()
+        .map(|key| ring::hash_to_scalar(&[key.0.as_bytes().as_slice(), message].concat()))
+        .collect();
+    let mut transcript = message.to_vec();
+    for (alpha, key) in alphas.iter().zip(keys) {
+        let hashed = ring::hash_to_point(&key.commitment());
+        transcript.extend_from_slice((alpha * RISTRETTO_BASEPOINT_POINT).compress().as_bytes());
+        transcript.extend_from_slice((alpha * hashed).compress().as_bytes());
+    }
+    let challenge = ring::hash_to_scalar(&transcript);
+    RingSignature {
+        key_images: keys.iter().map(|key| key.nullifier()).collect(),
+        challenge: challenge.to_bytes(),
+        responses: alphas
+            .iter()
+            .zip(keys)
+            .map(|(alpha, key)| (alpha - challenge * key.0).to_bytes())
+            .collect(),
+    }
+    .try_to_vec()
+    .unwrap()
+}
+
+/// `sign_spend` for withdrawing into the request `requester` opens under `nonce`
+pub fn sign_withdrawal(
+    program_id: &Pubkey,
+    requester: &Pubkey,
+    nonce: u64,
+    keys: &[&SpendKey],
+) -> Vec<u8> {
+    let (request, _) = pda::find_withdrawal_request(requester, nonce, program_id);
+    sign_spend(&WithdrawalRequest::spend_message(&request), keys)
+}
+
+/// `instruction` with the account at `index` replaced by `address`, keeping its flags
+pub fn with_account(mut instruction: Instruction, index: usize, address: Pubkey) -> Instruction {
+    instruction.accounts[index].pubkey = address;
+    instruction
+}
+
+/// Add an initialized SPL token mint with no mint authority
+pub fn add_mint(program_test: &mut ProgramTest, decimals: u8) -> Pubkey {
+    let mint = spl_token::state::Mint {
+        decimals,
+        is_initialized: true,
+        ..Default::default()
+    };
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    mint.pack_into_slice(&mut data);
+    add_token_program_account(program_test, data, spl_token::id())
+}
+
+/// Add a Token-2022 mint charging `basis_points` (capped at `maximum_fee`) on every transfer
+pub fn add_token_2022_mint_with_fee(
+    program_test: &mut ProgramTest,
+    decimals: u8,
+    basis_points: u16,
+    maximum_fee: u64,
+) -> Pubkey {
+    let space =
+        ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig])
+            .unwrap();
+    let mut data = vec![0; space];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    let fee = TransferFee {
+        epoch: 0.into(),
+        maximum_fee: maximum_fee.into(),
+        transfer_fee_basis_points: basis_points.into(),
+    };
+    let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+    config.older_transfer_fee = fee;
+    config.newer_transfer_fee = fee;
+    state.base = Mint {
+        decimals,
+        is_initialized: true,
+        ..Default::default()
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    add_token_program_account(program_test, data, spl_token_2022::id())
+}
+
+/// Add a Token-2022 account of a transfer-fee `mint` owned by `owner` holding `amount`
+pub fn add_token_2022_account(
+    program_test: &mut ProgramTest,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Pubkey {
+    let space = ExtensionType::try_calculate_account_len::<TokenAccount>(&[
+        ExtensionType::TransferFeeAmount,
+    ])
+    .unwrap();
+    let mut data = vec![0; space];
+    let mut state =
+        StateWithExtensionsMut::<TokenAccount>::unpack_uninitialized(&mut data).unwrap();
+    state.init_extension::<TransferFeeAmount>(true).unwrap();
+    state.base = TokenAccount {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: AccountState::Initialized,
+        ..Default::default()
+    };
+    state.pack_base();
+    state.init_account_type().unwrap();
+    add_token_program_account(program_test, data, spl_token_2022::id())
+}
+
+/// Add an SPL token account of `mint` owned by `owner` holding `amount`
+pub fn add_token_account(
+    program_test: &mut ProgramTest,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Pubkey {
+    let account = spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    };
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    account.pack_into_slice(&mut data);
+    add_token_program_account(program_test, data, spl_token::id())
+}
+
+/// Add an SPL token account like `add_token_account`, with `delegate` approved for all of it
+pub fn add_delegated_token_account(
+    program_test: &mut ProgramTest,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    delegate: &Pubkey,
+    amount: u64,
+) -> Pubkey {
+    let account = spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        delegate: COption::Some(*delegate),
+        delegated_amount: amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    };
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    account.pack_into_slice(&mut data);
+    add_token_program_account(program_test, data, spl_token::id())
+}
+
+/// Add the SPL Token wrapped SOL mint at its fixed address
+pub fn add_native_mint(program_test: &mut ProgramTest) -> Pubkey {
+    let mint = spl_token::state::Mint {
+        decimals: 9,
+        is_initialized: true,
+        ..Default::default()
+    };
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    mint.pack_into_slice(&mut data);
+    program_test.add_account(
+        spl_token::native_mint::id(),
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    spl_token::native_mint::id()
+}
+
+/// Add an empty wSOL account owned by `owner`
+pub fn add_native_token_account(program_test: &mut ProgramTest, owner: &Pubkey) -> Pubkey {
+    let rent_exempt_reserve = Rent::default().minimum_balance(spl_token::state::Account::LEN);
+    let account = spl_token::state::Account {
+        mint: spl_token::native_mint::id(),
+        owner: *owner,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::Some(rent_exempt_reserve),
+        ..Default::default()
+    };
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    account.pack_into_slice(&mut data);
+    add_token_program_account(program_test, data, spl_token::id())
+}
+
+fn add_token_program_account(
+    program_test: &mut ProgramTest,
+    data: Vec<u8>,
+    owner: Pubkey,
+) -> Pubkey {
+    let address = Pubkey::new_unique();
+    program_test.add_account(
+        address,
+        Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    address
+}
+
+pub struct TestHarness {
+    pub context: ProgramTestContext,
+    pub program_id: Pubkey,
+}
+
+impl TestHarness {
+    pub async fn start(program_test: ProgramTest, program_id: Pubkey) -> Self {
+        Self {
+            context: program_test.start_with_context().await,
+            program_id,
+        }
+    }
+    
+    /// `start`, then initialize the protocol over `mint` with the payer as authority,
+    /// `FEE_RATE` fees and the default root history
+    pub async fn start_initialized(
+        program_test: ProgramTest,
+        program_id: Pubkey,
+        mint: &Pubkey,
+    ) -> Self {
+        let mut harness = Self::start(program_test, program_id).await;
+        let init = instruction::initialize(
+            &program_id,
+            &harness.payer(),
+            &harness.protocol_state(),
+            mint,
+            FEE_RATE,
+            FEE_RATE,
+            RootHistory::DEFAULT_SIZE,
+        )
+        .unwrap();
+        harness.process(&[init], &[]).await.unwrap();
+        harness
+    }
+    
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+    
+    pub fn protocol_state(&self) -> Pubkey {
+        pda::find_protocol_state(&self.program_id).0
+    }
+    
+    /// Sign with the payer plus `signers` and process `instructions` atomically
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        self.context.banks_client.process_transaction(tx).await
+    }
+    
+    /// Process `instructions`, expecting the last one to fail with `error`
+    pub async fn expect_error(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+        error: impl Into<ProgramError>,
+    ) {
+        let err = self.process(instructions, signers).await.unwrap_err().unwrap();
+        let expected = InstructionError::from(u64::from(error.into()));
+        assert_eq!(
+            err,
+            TransactionError::InstructionError(instructions.len() as u8 - 1, expected)
+        );
+    }
+    
+    /// `expect_error` with `FPPError::Unauthorized`, what paused operations and
+    /// signers without the authority fail with
+    pub async fn expect_unauthorized(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) {
+        self.expect_error(instructions, signers, FPPError::Unauthorized).await;
+    }
+    
+    /// Check an authority-only instruction built for a given signer by `build`: a
+    /// stranger is refused, and the authority can't swap out any account at `pinned`
+    pub async fn expect_authority_only(
+        &mut self,
+        build: impl Fn(&Pubkey) -> Instruction,
+        pinned: &[usize],
+    ) {
+        let stranger = Keypair::new();
+        self.expect_unauthorized(&[build(&stranger.pubkey())], &[&stranger]).await;
+        let authority = self.payer();
+        for &index in pinned {
+            let elsewhere = with_account(build(&authority), index, Pubkey::new_unique());
+            self.expect_error(&[elsewhere], &[], FPPError::InvalidAccount).await;
+        }
+    }
+    
+    /// Set the protocol's pause flags directly, skipping the guardian and the unpause timelock
+    pub async fn pause(&mut self, flags: u8) {
+        let protocol_state = self.protocol_state();
+        self.update_account::<ProtocolState>(&protocol_state, |state| state.paused_flags = flags)
+            .await;
+    }
+    
+    /// Make `creator` the creator of the point at `point`, as if it had deposited it
+    pub async fn set_point_creator(&mut self, point: &Pubkey, creator: Pubkey) {
+        self.update_account::<FloatingPoint>(point, |point| point.creator = creator).await;
+    }
+    
+    /// Rewrite a borsh-encoded account in place, bypassing the program
+    pub async fn update_account<T: BorshDeserialize + BorshSerialize>(
+        &mut self,
+        address: &Pubkey,
+        update: impl FnOnce(&mut T),
+    ) {
+        let mut account = self
+            .context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+            .unwrap();
+        let mut state = T::try_from_slice(&account.data).unwrap();
+        update(&mut state);
+        state.serialize(&mut &mut account.data[..]).unwrap();
+        self.context.set_account(address, &AccountSharedData::from(account));
+    }
+    
+    /// Create or overwrite a rent-exempt, program-owned account holding `data`
+    pub fn set_program_account(&mut self, address: &Pubkey, data: Vec<u8>) {
+        let account = Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: self.program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context.set_account(address, &AccountSharedData::from(account));
+    }
+    
+    pub async fn token_balance(&mut self, address: &Pubkey) -> u64 {
+        let account = self
+            .context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+            .unwrap();
+        // Reads classic accounts too, as a base state without extensions
+        StateWithExtensions::<TokenAccount>::unpack(&account.data)
+            .unwrap()
+            .base
+            .amount
+    }
+    
+    pub async fn clock(&mut self) -> Clock {
+        self.context.banks_client.get_sysvar::<Clock>().await.unwrap()
+    }
+    
+    /// Advance cluster time by exactly `seconds` and move to the next slot
+    pub async fn warp_seconds(&mut self, seconds: i64) {
+        let before = self.clock().await;
+        self.context.warp_to_slot(before.slot + 1).unwrap();
+        
+        let mut warped = self.clock().await;
+        warped.unix_timestamp = before.unix_timestamp + seconds;
+        self.context.set_sysvar(&warped);
+    }
+    
+    /// Warp so that the cluster time equals `unix_timestamp`
+    pub async fn warp_to_timestamp(&mut self, unix_timestamp: i64) {
+        let now = self.clock().await.unix_timestamp;
+        self.warp_seconds(unix_timestamp - now).await;
+    }
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    compression::ACCOUNT_COMPRESSION_ID,
+    concurrent_tree,
+    error::FPPError,
+    instruction,
+};
+use solana_sdk::{keccak, pubkey::Pubkey};
+
+#[test]
+fn account_sizes_match_the_compression_program() {
+    // What the compression SDK quotes for depth 14 with a 64-entry buffer
+    assert_eq!(concurrent_tree::account_len(14, 64, 0), 31_800);
+    assert_eq!(concurrent_tree::account_len(3, 8, 0), 1_304);
+    
+    // A canopy caches every level it covers, except the root
+    assert_eq!(concurrent_tree::canopy_len(1), 2 * 32);
+    assert_eq!(concurrent_tree::canopy_len(10), 2046 * 32);
+    assert_eq!(concurrent_tree::proof_len(24, 10), 14);
+    assert!(concurrent_tree::is_valid_canopy(24, 10));
+    assert!(!concurrent_tree::is_valid_canopy(24, 0));
+}
+
+#[test]
+fn proofs_hash_from_the_leaf_upwards() {
+    let leaf = [1u8; 32];
+    let sibling = [2u8; 32];
+    let uncle = [3u8; 32];
+    
+    // Leaf 2 is a left child whose parent is a right child
+    let parent = keccak::hashv(&[&leaf, &sibling]).to_bytes();
+    let root = keccak::hashv(&[&uncle, &parent]).to_bytes();
+    assert_eq!(concurrent_tree::compute_root(&leaf, 2, &[sibling, uncle]), root);
+    assert_ne!(concurrent_tree::compute_root(&leaf, 3, &[sibling, uncle]), root);
+}
+
+#[tokio::test]
+async fn merkle_tree_must_be_sized_for_the_canopy() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    // Sized for no canopy, but created with one
+    let merkle_tree = common::add_program_account(
+        &mut program_test,
+        &ACCOUNT_COMPRESSION_ID,
+        concurrent_tree::account_len(20, 64, 0),
+    );
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    let create = |max_depth, canopy_depth| {
+        instruction::create_concurrent_tree(
+            &program_id,
+            &user,
+            &protocol_state,
+            &user,
+            &merkle_tree,
+            max_depth,
+            64,
+            canopy_depth,
+        )
+        .unwrap()
+    };
+    harness.expect_error(&[create(20, 5)], &[], FPPError::InvalidAccount).await;
+    // 21 levels with a 64-entry buffer isn't a shape the compression program knows
+    harness.expect_error(&[create(21, 5)], &[], FPPError::InvalidInstruction).await;
+}
+
+#[tokio::test]
+async fn only_the_authority_creates_the_concurrent_tree() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let merkle_tree = common::add_program_account(
+        &mut program_test,
+        &ACCOUNT_COMPRESSION_ID,
+        concurrent_tree::account_len(20, 64, 5),
+    );
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    let create = |authority: &Pubkey| {
+        instruction::create_concurrent_tree(
+            &program_id,
+            authority,
+            &protocol_state,
+            authority,
+            &merkle_tree,
+            20,
+            64,
+            5,
+        )
+        .unwrap()
+    };
+    
+    // The protocol state, both trees and the no-op program can't be swapped out
+    harness.expect_authority_only(create, &[1, 2, 3, 5]).await;
+    
+    // Nothing verifies against a tree the protocol never created, or through another program
+    let verify = instruction::verify_concurrent_commitment(
+        &program_id,
+        &merkle_tree,
+        [1u8; 32],
+        [2u8; 32],
+        0,
+        &[],
+    )
+    .unwrap();
+    harness.expect_error(&[verify.clone()], &[], FPPError::InvalidAccount).await;
+    let elsewhere = common::with_account(verify, 2, Pubkey::new_unique());
+    harness.expect_error(&[elsewhere], &[], FPPError::InvalidAccount).await;
+}
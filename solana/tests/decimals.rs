@@ -0,0 +1,76 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    decimals,
+    instruction,
+    pda,
+    state::{FloatingPoint, ProtocolState, POINT_VALUE},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+#[test]
+fn conversions_round_in_favour_of_the_treasury() {
+    assert_eq!(decimals::to_token_amount(POINT_VALUE, 6), Some(POINT_VALUE));
+    assert_eq!(decimals::to_token_amount(POINT_VALUE, 8), Some(1_000_000_000));
+    assert_eq!(decimals::to_token_amount(POINT_VALUE, 9), Some(10_000_000_000));
+    assert_eq!(decimals::to_base_amount(10_000_000_999, 9), Some(POINT_VALUE));
+    
+    // Fewer decimals than the base unit: charges round up, payouts round down
+    assert_eq!(decimals::to_token_amount_ceil(10_001, 2), Some(101));
+    assert_eq!(decimals::to_token_amount(10_001, 2), Some(100));
+    assert_eq!(decimals::to_token_amount(u64::MAX, 9), None);
+}
+
+/// Deposit one 10-unit point into a protocol whose mint has `decimals`, returning
+/// the treasury's token balance and the point's mass
+async fn deposit_one_point(decimals: u8) -> (u64, u64) {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, decimals);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token = common::add_token_account(&mut program_test, &mint, &treasury, 0);
+    let depositor = common::add_signer(&mut program_test);
+    let user_token =
+        common::add_token_account(&mut program_test, &mint, &depositor.pubkey(), u64::MAX / 2);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    
+    let epoch = harness.clock().await.epoch;
+    let deposit = instruction::deposit(
+        &program_id,
+        &depositor.pubkey(),
+        &user_token,
+        &treasury_token,
+        &harness.protocol_state(),
+        &mint,
+        &spl_token::id(),
+        POINT_VALUE,
+        POINT_VALUE,
+        vec![[5u8; 32]],
+        vec![],
+        epoch,
+    )
+    .unwrap();
+    harness.process(&[deposit], &[&depositor]).await.unwrap();
+    
+    let balance = harness.token_balance(&treasury_token).await;
+    let (point, _) = pda::find_point_address(&[5u8; 32], &program_id);
+    let point = harness.context.banks_client.get_account(point).await.unwrap().unwrap();
+    (balance, FloatingPoint::try_from_slice(&point.data).unwrap().mass)
+}
+
+#[tokio::test]
+async fn six_decimal_mint_takes_base_units_as_is() {
+    assert_eq!(deposit_one_point(6).await, (10_010_000, 1));
+}
+
+#[tokio::test]
+async fn eight_decimal_mint_scales_by_a_hundred() {
+    assert_eq!(deposit_one_point(8).await, (1_001_000_000, 1));
+}
+
+#[tokio::test]
+async fn nine_decimal_mint_scales_by_a_thousand() {
+    assert_eq!(deposit_one_point(9).await, (10_010_000_000, 1));
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    pda,
+    state::{DepositPermit, FloatingPoint, ProtocolState, POINT_VALUE},
+};
+use solana_sdk::{
+    ed25519_program,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+// An Ed25519 program instruction carrying one signature, its key and message inline
+fn ed25519_instruction(signer: &Keypair, message: &[u8]) -> Instruction {
+    let signature = signer.sign_message(message);
+    let (key_offset, signature_offset, message_offset) = (16u16, 48u16, 112u16);
+    let mut data = vec![1u8, 0];
+    for field in [
+        signature_offset,
+        u16::MAX,
+        key_offset,
+        u16::MAX,
+        message_offset,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&field.to_le_bytes());
+    }
+    data.extend_from_slice(signer.pubkey().as_ref());
+    data.extend_from_slice(signature.as_ref());
+    data.extend_from_slice(message);
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+#[tokio::test]
+async fn sponsor_deposits_once_under_a_signed_permit() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token = common::add_token_account(&mut program_test, &mint, &treasury, 0);
+    
+    // The user holds tokens but no SOL, and never signs a transaction
+    let user = Keypair::new();
+    let (permit_authority, _) = DepositPermit::find_authority(&program_id);
+    let user_token = common::add_delegated_token_account(
+        &mut program_test,
+        &mint,
+        &user.pubkey(),
+        &permit_authority,
+        20 * POINT_VALUE,
+    );
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let sponsor = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    let amount = 10 * POINT_VALUE;
+    let commitment = [7u8; 32];
+    let expiry = harness.clock().await.unix_timestamp + 3600;
+    let message =
+        DepositPermit::message(&program_id, &mint, amount, amount, &[commitment], expiry, 0);
+    let epoch = harness.clock().await.epoch;
+    let deposit = |commitment: [u8; 32]| {
+        instruction::delegated_deposit(
+            &program_id,
+            &sponsor,
+            &user.pubkey(),
+            &user_token,
+            &treasury_token,
+            &protocol_state,
+            &mint,
+            &spl_token::id(),
+            amount,
+            amount,
+            vec![commitment],
+            vec![],
+            expiry,
+            0,
+            -1,
+            epoch,
+        )
+        .unwrap()
+    };
+    
+    let permitted = |signer: &Keypair, deposit: Instruction| {
+        [ed25519_instruction(signer, &message), deposit]
+    };
+    
+    // Only the user's own signature is a permit
+    let stranger = Keypair::new();
+    harness
+        .expect_error(&permitted(&stranger, deposit(commitment)), &[], FPPError::InvalidPermit)
+        .await;
+    // Neither the permit nor the protocol state can be swapped out
+    for index in [1, 7] {
+        let elsewhere = common::with_account(deposit(commitment), index, Pubkey::new_unique());
+        harness
+            .expect_error(&permitted(&user, elsewhere), &[], FPPError::InvalidAccount)
+            .await;
+    }
+    harness.pause(ProtocolState::PAUSE_DEPOSITS).await;
+    harness.expect_unauthorized(&permitted(&user, deposit(commitment)), &[]).await;
+    harness.pause(0).await;
+    
+    harness.process(&permitted(&user, deposit(commitment)), &[]).await.unwrap();
+    assert_eq!(harness.token_balance(&treasury_token).await, amount + POINT_VALUE / 100);
+    let (point, _) = pda::find_point_address(&commitment, &program_id);
+    let point = harness.context.banks_client.get_account(point).await.unwrap().unwrap();
+    assert_eq!(FloatingPoint::try_from_slice(&point.data).unwrap().creator, user.pubkey());
+    
+    // The same permit can't be used again, even for the commitment it names
+    harness
+        .expect_error(
+            &permitted(&user, deposit(commitment)),
+            &[],
+            FPPError::AccountAlreadyInitialized,
+        )
+        .await;
+    
+    // Nor can its signature authorize a different commitment
+    harness
+        .expect_error(&permitted(&user, deposit([8u8; 32])), &[], FPPError::InvalidPermit)
+        .await;
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    pda,
+    state::{ProtocolState, POINT_VALUE, WITHDRAWAL_DELAY},
+};
+use solana_sdk::pubkey::Pubkey;
+
+#[tokio::test]
+async fn denied_destination_cannot_receive_withdrawal() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let key = SpendKey::new(3);
+    let point = common::add_point(&mut program_test, &program_id, &common::point(&key, &mint));
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token =
+        common::add_token_account(&mut program_test, &mint, &treasury, POINT_VALUE);
+    let sanctioned = Pubkey::new_unique();
+    let user_token = common::add_token_account(&mut program_test, &mint, &sanctioned, 0);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    harness.set_point_creator(&point, user).await;
+    let (withdrawal_request, _) = pda::find_withdrawal_request(&user, 0, &program_id);
+    
+    let deny = instruction::add_to_deny_list(
+        &program_id,
+        &user,
+        &protocol_state,
+        &user,
+        sanctioned.to_bytes(),
+    )
+    .unwrap();
+    harness.process(&[deny], &[]).await.unwrap();
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 1)
+        .await;
+    
+    let request = instruction::request_withdrawal(
+        &program_id,
+        &user,
+        &protocol_state,
+        vec![point],
+        vec![key.nullifier()],
+        0,
+        common::sign_withdrawal(&program_id, &user, 0, &[&key]),
+    )
+    .unwrap();
+    harness.process(&[request], &[]).await.unwrap();
+    harness.warp_seconds(WITHDRAWAL_DELAY).await;
+    
+    let epoch = harness.clock().await.epoch;
+    let complete = instruction::complete_withdrawal(
+        &program_id,
+        &user,
+        &user_token,
+        &treasury_token,
+        &protocol_state,
+        &withdrawal_request,
+        &mint,
+        &spl_token::id(),
+        false,
+        epoch,
+    )
+    .unwrap();
+    harness.expect_error(&[complete.clone()], &[], FPPError::DeniedAddress).await;
+    
+    let lift = instruction::remove_from_deny_list(
+        &program_id,
+        &user,
+        &protocol_state,
+        sanctioned.to_bytes(),
+    )
+    .unwrap();
+    harness.process(&[lift, complete], &[]).await.unwrap();
+    assert!(harness.token_balance(&user_token).await > 0);
+}
+
+#[tokio::test]
+async fn only_the_authority_edits_the_deny_list() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let entry = Pubkey::new_unique().to_bytes();
+    
+    let deny = |authority: &Pubkey| {
+        instruction::add_to_deny_list(&program_id, authority, &protocol_state, &user, entry)
+            .unwrap()
+    };
+    let lift = |authority: &Pubkey| {
+        instruction::remove_from_deny_list(&program_id, authority, &protocol_state, entry).unwrap()
+    };
+    // Neither the protocol state nor the deny list can be swapped out
+    harness.expect_authority_only(deny, &[1, 2]).await;
+    harness.process(&[deny(&user)], &[]).await.unwrap();
+    harness.expect_authority_only(lift, &[1, 2]).await;
+    harness.process(&[lift(&user)], &[]).await.unwrap();
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    state::{ProtocolState, POINT_VALUE},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+#[tokio::test]
+async fn deposits_stop_at_the_user_and_global_caps() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token = common::add_token_account(&mut program_test, &mint, &treasury, 0);
+    let depositors = [
+        common::add_signer(&mut program_test),
+        common::add_signer(&mut program_test),
+    ];
+    let mut user_tokens = vec![];
+    for depositor in &depositors {
+        user_tokens.push(common::add_token_account(
+            &mut program_test,
+            &mint,
+            &depositor.pubkey(),
+            10 * POINT_VALUE,
+        ));
+    }
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    let caps = |max_tvl, max_user_deposit| {
+        instruction::set_deposit_caps(
+            &program_id,
+            &authority,
+            &protocol_state,
+            max_tvl,
+            max_user_deposit,
+        )
+        .unwrap()
+    };
+    // A user cap above the global cap is rejected
+    let unreachable = caps(POINT_VALUE, 2 * POINT_VALUE);
+    harness.expect_error(&[unreachable], &[], FPPError::InvalidAmount).await;
+    harness.process(&[caps(3 * POINT_VALUE, 2 * POINT_VALUE)], &[]).await.unwrap();
+    
+    let epoch = harness.clock().await.epoch;
+    let deposit = |user: usize, commitment: [u8; 32]| {
+        instruction::deposit(
+            &program_id,
+            &depositors[user].pubkey(),
+            &user_tokens[user],
+            &treasury_token,
+            &protocol_state,
+            &mint,
+            &spl_token::id(),
+            POINT_VALUE,
+            POINT_VALUE,
+            vec![commitment],
+            vec![],
+            epoch,
+        )
+        .unwrap()
+    };
+    let cap_exceeded = FPPError::DepositCapExceeded;
+    
+    harness.process(&[deposit(0, [1u8; 32])], &[&depositors[0]]).await.unwrap();
+    harness.process(&[deposit(0, [2u8; 32])], &[&depositors[0]]).await.unwrap();
+    harness.expect_error(&[deposit(0, [3u8; 32])], &[&depositors[0]], cap_exceeded).await;
+    
+    // The second user fits once more before the protocol is full
+    harness.process(&[deposit(1, [4u8; 32])], &[&depositors[1]]).await.unwrap();
+    harness.expect_error(&[deposit(1, [5u8; 32])], &[&depositors[1]], cap_exceeded).await;
+    
+    let state = harness.context.banks_client.get_account(protocol_state).await.unwrap().unwrap();
+    let state = ProtocolState::try_from_slice(&state.data).unwrap();
+    assert_eq!(state.value_locked, 3 * POINT_VALUE);
+    
+    // Lifting the caps lets deposits through again
+    harness.process(&[caps(0, 0)], &[]).await.unwrap();
+    harness.process(&[deposit(1, [5u8; 32])], &[&depositors[1]]).await.unwrap();
+}
+
+#[tokio::test]
+async fn only_the_authority_sets_deposit_caps() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    let caps = |authority: &Pubkey| {
+        instruction::set_deposit_caps(&program_id, authority, &protocol_state, 0, 0).unwrap()
+    };
+    
+    harness.expect_authority_only(caps, &[1]).await;
+}
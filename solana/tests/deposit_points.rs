@@ -0,0 +1,162 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    crypto::opening::OpeningProof,
+    error::FPPError,
+    instruction,
+    pda,
+    state::{FloatingPoint, ProtocolState, POINT_VALUE},
+};
+use solana_program_test::ProgramTest;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+/// A depositor holding SOL and plenty of `mint`, and the treasury's empty token account
+fn add_depositor(
+    program_test: &mut ProgramTest,
+    program_id: &Pubkey,
+    mint: &Pubkey,
+) -> (Keypair, Pubkey, Pubkey) {
+    let (treasury, _) = ProtocolState::find_treasury_authority(program_id);
+    let treasury_token = common::add_token_account(program_test, mint, &treasury, 0);
+    let depositor = common::add_signer(program_test);
+    let user_token =
+        common::add_token_account(program_test, mint, &depositor.pubkey(), u64::MAX / 2);
+    (depositor, user_token, treasury_token)
+}
+
+#[tokio::test]
+async fn each_commitment_gets_its_own_point_pda() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (depositor, user_token, treasury_token) =
+        add_depositor(&mut program_test, &program_id, &mint);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let commitments = vec![[5u8; 32], [6u8; 32], [7u8; 32]];
+    
+    let epoch = harness.clock().await.epoch;
+    let deposit = |amount, commitments: &[[u8; 32]]| {
+        instruction::deposit(
+            &program_id,
+            &depositor.pubkey(),
+            &user_token,
+            &treasury_token,
+            &protocol_state,
+            &mint,
+            &spl_token::id(),
+            amount,
+            POINT_VALUE,
+            commitments.to_vec(),
+            vec![],
+            epoch,
+        )
+        .unwrap()
+    };
+    
+    // The amount has to be exactly one denomination per commitment
+    let short = deposit(3 * POINT_VALUE, &commitments[..2]);
+    harness.expect_error(&[short], &[&depositor], FPPError::InvalidAmount).await;
+    let uneven = deposit(3 * POINT_VALUE + 1, &commitments);
+    harness.expect_error(&[uneven], &[&depositor], FPPError::InvalidAmount).await;
+    
+    let deposit = deposit(3 * POINT_VALUE, &commitments);
+    
+    // Trailing points in the wrong order don't sit at their commitments' PDAs
+    let mut swapped = deposit.clone();
+    let len = swapped.accounts.len();
+    swapped.accounts.swap(len - 1, len - 2);
+    assert!(harness.process(&[swapped], &[&depositor]).await.is_err());
+    
+    harness.pause(ProtocolState::PAUSE_DEPOSITS).await;
+    harness.expect_unauthorized(&[deposit.clone()], &[&depositor]).await;
+    harness.pause(0).await;
+    
+    harness.process(&[deposit], &[&depositor]).await.unwrap();
+    
+    for commitment in &commitments {
+        let (address, _) = pda::find_point_address(commitment, &program_id);
+        let account = harness.context.banks_client.get_account(address).await.unwrap().unwrap();
+        let point = FloatingPoint::try_from_slice(&account.data).unwrap();
+        assert_eq!(point.commitment, *commitment);
+        assert_eq!(point.mass, 1);
+        assert_eq!(point.rent_payer, depositor.pubkey());
+    }
+}
+
+#[tokio::test]
+async fn required_openings_prove_the_depositor_knows_each_key() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (depositor, user_token, treasury_token) =
+        add_depositor(&mut program_test, &program_id, &mint);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let key = SpendKey::new(5);
+    
+    let require =
+        instruction::set_commitment_validation(&program_id, &authority, &protocol_state, true)
+            .unwrap();
+    harness.process(&[require], &[]).await.unwrap();
+    
+    let epoch = harness.clock().await.epoch;
+    let deposit = |opening_proofs: Vec<OpeningProof>| {
+        instruction::deposit(
+            &program_id,
+            &depositor.pubkey(),
+            &user_token,
+            &treasury_token,
+            &protocol_state,
+            &mint,
+            &spl_token::id(),
+            POINT_VALUE,
+            POINT_VALUE,
+            vec![key.commitment()],
+            opening_proofs,
+            epoch,
+        )
+        .unwrap()
+    };
+    
+    // Missing, for another depositor, for another amount, or by another key
+    let attempts = [
+        vec![],
+        vec![key.opening_proof(POINT_VALUE, &authority)],
+        vec![key.opening_proof(2 * POINT_VALUE, &depositor.pubkey())],
+        vec![SpendKey::new(6).opening_proof(POINT_VALUE, &depositor.pubkey())],
+    ];
+    for opening_proofs in attempts {
+        harness
+            .expect_error(&[deposit(opening_proofs)], &[&depositor], FPPError::InvalidCommitment)
+            .await;
+    }
+    
+    let opening_proof = key.opening_proof(POINT_VALUE, &depositor.pubkey());
+    harness.process(&[deposit(vec![opening_proof])], &[&depositor]).await.unwrap();
+    let (address, _) = pda::find_point_address(&key.commitment(), &program_id);
+    let account = harness.context.banks_client.get_account(address).await.unwrap().unwrap();
+    assert_eq!(FloatingPoint::try_from_slice(&account.data).unwrap().commitment, key.commitment());
+}
+
+#[tokio::test]
+async fn only_the_authority_requires_openings() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    let require = |authority: &Pubkey| {
+        instruction::set_commitment_validation(&program_id, authority, &protocol_state, true)
+            .unwrap()
+    };
+    
+    harness.expect_authority_only(require, &[1]).await;
+}
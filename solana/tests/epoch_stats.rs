@@ -0,0 +1,79 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    state::{EpochStats, ProtocolState, POINT_VALUE},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+#[tokio::test]
+async fn deposits_are_tallied_per_epoch() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token = common::add_token_account(&mut program_test, &mint, &treasury, 0);
+    let depositor = common::add_signer(&mut program_test);
+    let user_token =
+        common::add_token_account(&mut program_test, &mint, &depositor.pubkey(), 4 * POINT_VALUE);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    
+    let epoch = harness.clock().await.epoch;
+    let deposit = |amount, commitments: Vec<[u8; 32]>| {
+        instruction::deposit(
+            &program_id,
+            &depositor.pubkey(),
+            &user_token,
+            &treasury_token,
+            &protocol_state,
+            &mint,
+            &spl_token::id(),
+            amount,
+            POINT_VALUE,
+            commitments,
+            vec![],
+            epoch,
+        )
+        .unwrap()
+    };
+    let first = deposit(2 * POINT_VALUE, vec![[1u8; 32], [2u8; 32]]);
+    harness.process(&[first], &[&depositor]).await.unwrap();
+    let second = deposit(POINT_VALUE, vec![[3u8; 32]]);
+    harness.process(&[second], &[&depositor]).await.unwrap();
+    
+    let (address, _) = EpochStats::find_address(epoch, &program_id);
+    let account = harness.context.banks_client.get_account(address).await.unwrap().unwrap();
+    let stats = EpochStats::try_from_slice(&account.data).unwrap();
+    assert_eq!(stats.epoch, epoch);
+    assert_eq!(stats.deposit_count, 2);
+    assert_eq!(stats.deposit_volume, 3 * POINT_VALUE);
+    // A second deposit from the same address isn't another depositor
+    assert_eq!(stats.unique_depositors, 1);
+    assert_eq!(stats.anonymity_set, 3);
+    
+    let state = harness.context.banks_client.get_account(protocol_state).await.unwrap().unwrap();
+    let state = ProtocolState::try_from_slice(&state.data).unwrap();
+    assert_eq!(stats.fee_revenue, state.total_fees);
+    
+    // Stats of another epoch can't stand in for the current one
+    let stale = instruction::deposit(
+        &program_id,
+        &depositor.pubkey(),
+        &user_token,
+        &treasury_token,
+        &protocol_state,
+        &mint,
+        &spl_token::id(),
+        POINT_VALUE,
+        POINT_VALUE,
+        vec![[4u8; 32]],
+        vec![],
+        epoch + 1,
+    )
+    .unwrap();
+    harness.expect_error(&[stale], &[&depositor], FPPError::InvalidAccount).await;
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    instruction,
+    state::{FeeExemptions, ProtocolState, POINT_VALUE},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+#[tokio::test]
+async fn exempt_depositors_pay_no_fee() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token = common::add_token_account(&mut program_test, &mint, &treasury, 0);
+    let depositor = common::add_signer(&mut program_test);
+    let funded = 4 * POINT_VALUE;
+    let user_token =
+        common::add_token_account(&mut program_test, &mint, &depositor.pubkey(), funded);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    let add = instruction::add_fee_exemption(
+        &program_id,
+        &authority,
+        &protocol_state,
+        &authority,
+        &depositor.pubkey(),
+    )
+    .unwrap();
+    harness.process(&[add], &[]).await.unwrap();
+    
+    let (list, _) = FeeExemptions::find_address(&program_id);
+    let account = harness.context.banks_client.get_account(list).await.unwrap().unwrap();
+    let exemptions = FeeExemptions::try_from_slice(&account.data).unwrap();
+    assert_eq!(exemptions.addresses, vec![depositor.pubkey()]);
+    
+    let epoch = harness.clock().await.epoch;
+    let deposit = |commitment: [u8; 32]| {
+        instruction::deposit(
+            &program_id,
+            &depositor.pubkey(),
+            &user_token,
+            &treasury_token,
+            &protocol_state,
+            &mint,
+            &spl_token::id(),
+            POINT_VALUE,
+            POINT_VALUE,
+            vec![commitment],
+            vec![],
+            epoch,
+        )
+        .unwrap()
+    };
+    harness.process(&[deposit([1u8; 32])], &[&depositor]).await.unwrap();
+    assert_eq!(harness.token_balance(&user_token).await, funded - POINT_VALUE);
+    
+    // Once removed, the 0.1% fee applies again
+    let remove = instruction::remove_fee_exemption(
+        &program_id,
+        &authority,
+        &protocol_state,
+        &depositor.pubkey(),
+    )
+    .unwrap();
+    harness.process(&[remove], &[]).await.unwrap();
+    harness.process(&[deposit([2u8; 32])], &[&depositor]).await.unwrap();
+    let spent = 2 * POINT_VALUE + POINT_VALUE / 1000;
+    assert_eq!(harness.token_balance(&user_token).await, funded - spent);
+}
+
+#[tokio::test]
+async fn only_the_authority_manages_fee_exemptions() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let depositor = common::add_signer(&mut program_test);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let add = |authority: &Pubkey| {
+        instruction::add_fee_exemption(
+            &program_id,
+            authority,
+            &protocol_state,
+            authority,
+            &depositor.pubkey(),
+        )
+        .unwrap()
+    };
+    let remove = |authority: &Pubkey| {
+        instruction::remove_fee_exemption(
+            &program_id,
+            authority,
+            &protocol_state,
+            &depositor.pubkey(),
+        )
+        .unwrap()
+    };
+    
+    // No one can exempt themselves, or lift anyone else's exemption
+    harness.expect_unauthorized(&[add(&depositor.pubkey())], &[&depositor]).await;
+    // Neither the protocol state nor the list can be swapped out
+    harness.expect_authority_only(add, &[1, 2]).await;
+    harness.process(&[add(&authority)], &[]).await.unwrap();
+    harness.expect_unauthorized(&[remove(&depositor.pubkey())], &[&depositor]).await;
+    harness.expect_authority_only(remove, &[1, 2]).await;
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    state::{FeeTier, ProtocolState, MAX_FEE_TIERS, POINT_VALUE},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+/// An 8 bps tier from 100 USDT and a 5 bps tier from 1,000; the rest unused
+fn tiers() -> [FeeTier; MAX_FEE_TIERS] {
+    let tier = |min_amount, rate| FeeTier {
+        min_amount,
+        deposit_fee_rate: rate,
+        withdrawal_fee_rate: rate,
+        ..FeeTier::default()
+    };
+    let mut tiers = [FeeTier::default(); MAX_FEE_TIERS];
+    tiers[0] = tier(10 * POINT_VALUE, 8);
+    tiers[1] = tier(100 * POINT_VALUE, 5);
+    tiers
+}
+
+#[tokio::test]
+async fn large_deposits_pay_the_tier_rate() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token = common::add_token_account(&mut program_test, &mint, &treasury, 0);
+    let depositor = common::add_signer(&mut program_test);
+    let funded = 200 * POINT_VALUE;
+    let user_token =
+        common::add_token_account(&mut program_test, &mint, &depositor.pubkey(), funded);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    // Thresholds out of order are rejected
+    let mut tiers = tiers();
+    tiers.swap(0, 1);
+    let update = |tiers| {
+        instruction::update_fee_tiers(&program_id, &authority, &protocol_state, tiers).unwrap()
+    };
+    harness.expect_error(&[update(tiers)], &[], FPPError::InvalidAmount).await;
+    
+    tiers.swap(0, 1);
+    harness.process(&[update(tiers)], &[]).await.unwrap();
+    
+    let epoch = harness.clock().await.epoch;
+    let deposit = |denomination, commitment: [u8; 32]| {
+        instruction::deposit(
+            &program_id,
+            &depositor.pubkey(),
+            &user_token,
+            &treasury_token,
+            &protocol_state,
+            &mint,
+            &spl_token::id(),
+            denomination,
+            denomination,
+            vec![commitment],
+            vec![],
+            epoch,
+        )
+        .unwrap()
+    };
+    
+    // Below the first tier the mint's 10 bps apply; 1,000 USDT reaches the 5 bps tier
+    harness.process(&[deposit(POINT_VALUE, [1u8; 32])], &[&depositor]).await.unwrap();
+    harness.process(&[deposit(100 * POINT_VALUE, [2u8; 32])], &[&depositor]).await.unwrap();
+    
+    let fees = POINT_VALUE / 1000 + 100 * POINT_VALUE * 5 / 10_000;
+    let spent = POINT_VALUE + 100 * POINT_VALUE + fees;
+    assert_eq!(harness.token_balance(&user_token).await, funded - spent);
+}
+
+#[tokio::test]
+async fn only_the_authority_updates_fee_tiers() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    let update = |authority: &Pubkey| {
+        instruction::update_fee_tiers(&program_id, authority, &protocol_state, tiers()).unwrap()
+    };
+    
+    harness.expect_authority_only(update, &[1]).await;
+}
@@ -0,0 +1,106 @@
+mod common;
+
+use borsh::BorshSerialize;
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    pda,
+    state::{FloatingPoint, ProtocolState},
+};
+use solana_sdk::{instruction::AccountMeta, program_error::ProgramError, pubkey::Pubkey, sysvar};
+
+#[tokio::test]
+async fn points_cannot_be_withdrawn_in_their_deposit_slot() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 1)
+        .await;
+    
+    // A point deposited in the current slot
+    let slot = harness.clock().await.slot;
+    let key = SpendKey::new(3);
+    let point = FloatingPoint {
+        creator: user,
+        created_slot: slot,
+        rent_payer: user,
+        ..common::point(&key, &mint)
+    };
+    let (point_address, _) = pda::find_point_address(&point.commitment, &program_id);
+    harness.set_program_account(&point_address, point.try_to_vec().unwrap());
+    
+    let request = instruction::request_withdrawal(
+        &program_id,
+        &user,
+        &protocol_state,
+        vec![point_address],
+        vec![key.nullifier()],
+        0,
+        common::sign_withdrawal(&program_id, &user, 0, &[&key]),
+    )
+    .unwrap();
+    harness.expect_error(&[request.clone()], &[], FPPError::FlashLoanDetected).await;
+    
+    harness.warp_seconds(1).await;
+    harness.process(&[request], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn cpi_guard_still_admits_top_level_requests() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let key = SpendKey::new(3);
+    let point = common::add_point(&mut program_test, &program_id, &common::point(&key, &mint));
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    harness.set_point_creator(&point, user).await;
+    
+    let guard = instruction::set_cpi_guard(&program_id, &user, &protocol_state, true).unwrap();
+    harness.process(&[guard], &[]).await.unwrap();
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 1)
+        .await;
+    
+    // The guard can't be checked without the real instructions sysvar
+    let request = instruction::request_withdrawal(
+        &program_id,
+        &user,
+        &protocol_state,
+        vec![point],
+        vec![key.nullifier()],
+        0,
+        common::sign_withdrawal(&program_id, &user, 0, &[&key]),
+    )
+    .unwrap();
+    let mut spoofed = request.clone();
+    let sysvar_meta = spoofed
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == sysvar::instructions::id())
+        .unwrap();
+    *sysvar_meta = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+    harness.expect_error(&[spoofed], &[], ProgramError::UnsupportedSysvar).await;
+    
+    harness.process(&[request], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn only_the_authority_sets_the_cpi_guard() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    let guard = |authority: &Pubkey| {
+        instruction::set_cpi_guard(&program_id, authority, &protocol_state, true).unwrap()
+    };
+    
+    harness.expect_authority_only(guard, &[1]).await;
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    pda,
+    state::{FloatingPoint, MassDynamics, ProtocolState, WithdrawalRequest, POINT_VALUE},
+};
+use solana_sdk::pubkey::Pubkey;
+
+#[tokio::test]
+async fn accretion_is_funded_by_fees_and_paid_on_withdrawal() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    // Created at the epoch, so the point is decades old by the test clock
+    let key = SpendKey::new(3);
+    let point_address =
+        common::add_point(&mut program_test, &program_id, &common::point(&key, &mint));
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    harness.set_point_creator(&point_address, authority).await;
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| {
+            state.total_points = 1;
+            state.total_fees = POINT_VALUE / 2;
+        })
+        .await;
+    
+    let set = |dynamics| {
+        instruction::set_mass_dynamics(&program_id, &authority, &protocol_state, dynamics)
+            .unwrap()
+    };
+    let dormant_first = MassDynamics {
+        maturity: 60,
+        dormancy: 60,
+        accretion_rate: 1000,
+        ..MassDynamics::default()
+    };
+    harness.expect_error(&[set(dormant_first)], &[], FPPError::InvalidAmount).await;
+    let accreting = MassDynamics {
+        accretion_rate: MassDynamics::MAX_ACCRETION_RATE,
+        ..MassDynamics::default()
+    };
+    harness.process(&[set(accreting)], &[]).await.unwrap();
+    
+    let update =
+        instruction::update_mass(&program_id, &authority, &protocol_state, &[(point_address, mint)])
+            .unwrap();
+    // Anyone may crank, but only over the protocol's own points and mint entries
+    for index in [1, 3, 4] {
+        let elsewhere = common::with_account(update.clone(), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[], FPPError::InvalidAccount).await;
+    }
+    harness.process(&[update], &[]).await.unwrap();
+    
+    // Decades at 10% a year would be several times the principal, but only the fees pay for it
+    let account = harness.context.banks_client.get_account(point_address).await.unwrap().unwrap();
+    let updated = FloatingPoint::try_from_slice(&account.data).unwrap();
+    assert_eq!(updated.accrued, (POINT_VALUE / 2) as i64);
+    assert!(updated.mass_updated_at > 0);
+    let account = harness.context.banks_client.get_account(protocol_state).await.unwrap().unwrap();
+    let state = ProtocolState::try_from_slice(&account.data).unwrap();
+    assert_eq!(state.total_fees, 0);
+    
+    let request = instruction::request_withdrawal(
+        &program_id,
+        &authority,
+        &protocol_state,
+        vec![point_address],
+        vec![key.nullifier()],
+        0,
+        common::sign_withdrawal(&program_id, &authority, 0, &[&key]),
+    )
+    .unwrap();
+    harness.process(&[request], &[]).await.unwrap();
+    
+    let (withdrawal_request, _) = pda::find_withdrawal_request(&authority, 0, &program_id);
+    let account = harness
+        .context
+        .banks_client
+        .get_account(withdrawal_request)
+        .await
+        .unwrap()
+        .unwrap();
+    let request = WithdrawalRequest::try_from_slice(&account.data).unwrap();
+    assert_eq!(request.amount, POINT_VALUE + POINT_VALUE / 2);
+}
+
+#[tokio::test]
+async fn only_the_authority_sets_mass_dynamics() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    let set = |authority: &Pubkey| {
+        let dynamics = MassDynamics {
+            accretion_rate: MassDynamics::MAX_ACCRETION_RATE,
+            ..MassDynamics::default()
+        };
+        instruction::set_mass_dynamics(&program_id, authority, &protocol_state, dynamics).unwrap()
+    };
+    
+    harness.expect_authority_only(set, &[1]).await;
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    migration::{AccountKind, Versioned},
+    pda,
+    state::{FloatingPoint, ProtocolState, POINT_LOCK_DURATION, WITHDRAWAL_DELAY},
+};
+use solana_sdk::pubkey::Pubkey;
+
+async fn account_data(harness: &mut TestHarness, address: Pubkey) -> Vec<u8> {
+    let account = harness.context.banks_client.get_account(address).await.unwrap().unwrap();
+    account.data
+}
+
+#[tokio::test]
+async fn unversioned_accounts_upgrade_in_place() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    // A point written before accounts had a version byte, or a rent payer
+    let point = FloatingPoint {
+        commitment: [3u8; 32],
+        creator: authority,
+        ..common::point(&SpendKey::new(3), &mint)
+    };
+    let (point_address, _) = pda::find_point_address(&point.commitment, &program_id);
+    let unversioned = point.try_to_vec().unwrap()[1..FloatingPoint::V1_LEN].to_vec();
+    harness.set_program_account(&point_address, unversioned);
+    
+    // An unversioned protocol state has zero where `version` now is, and ends before `rewards`
+    let mut unversioned = account_data(&mut harness, protocol_state).await;
+    unversioned.truncate(ProtocolState::V1_LEN);
+    unversioned[ProtocolState::VERSION_OFFSET] = 0;
+    harness.set_program_account(&protocol_state, unversioned);
+    
+    let migrate_as = |authority: &Pubkey, kind, account: &Pubkey| {
+        instruction::migrate_state(
+            &program_id,
+            authority,
+            &protocol_state,
+            authority,
+            kind,
+            account,
+        )
+        .unwrap()
+    };
+    let migrate = |kind, account: &Pubkey| migrate_as(&authority, kind, account);
+    
+    // Only the authority may migrate, and only the protocol's own accounts
+    let migrate_point = |authority: &Pubkey| {
+        migrate_as(authority, AccountKind::FloatingPoint, &point_address)
+    };
+    harness.expect_authority_only(migrate_point, &[1]).await;
+    let foreign = migrate(AccountKind::FloatingPoint, &Pubkey::new_unique());
+    harness.expect_error(&[foreign], &[], FPPError::InvalidAccount).await;
+    
+    harness
+        .process(
+            &[
+                migrate(AccountKind::FloatingPoint, &point_address),
+                migrate(AccountKind::ProtocolState, &protocol_state),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+    
+    let data = account_data(&mut harness, point_address).await;
+    assert_eq!(data.len(), FloatingPoint::LEN);
+    let migrated = FloatingPoint::try_from_slice(&data).unwrap();
+    assert_eq!(migrated.version, FloatingPoint::VERSION);
+    assert_eq!(migrated.commitment, point.commitment);
+    assert_eq!(migrated.mint, mint);
+    assert_eq!(migrated.rent_payer, authority);
+    assert_eq!(migrated.accrued, 0);
+    
+    let data = account_data(&mut harness, protocol_state).await;
+    assert_eq!(data.len(), ProtocolState::LEN);
+    let state = ProtocolState::try_from_slice(&data).unwrap();
+    assert_eq!(state.version, ProtocolState::VERSION);
+    assert_eq!(state.authority, authority);
+    assert!(!state.rewards.is_configured());
+    assert!(state.fee_tiers.iter().all(|tier| !tier.is_used()));
+    assert_eq!((state.max_tvl, state.max_user_deposit), (0, 0));
+    assert_eq!(state.withdrawal_delay, WITHDRAWAL_DELAY);
+    assert_eq!(state.point_lock_duration, POINT_LOCK_DURATION);
+    assert!(!state.pending_unpause.is_pending());
+    
+    // Current accounts are left alone
+    harness
+        .process(&[migrate(AccountKind::FloatingPoint, &point_address)], &[])
+        .await
+        .unwrap();
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use borsh::BorshSerialize;
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    bloom::{self, NullifierBloom},
+    error::FPPError,
+    instruction,
+    migration::Versioned,
+    state::{NullifierFilter, NullifierShard, ProtocolState},
+};
+use solana_sdk::pubkey::Pubkey;
+
+#[test]
+fn byte_updates_match_word_filter() {
+    let mut bits = vec![0u8; 1024];
+    let mut words = NullifierBloom::new(1024 * 8, 5);
+    for i in 0..50u8 {
+        bloom::insert_bytes(&mut bits, 5, &[i; 32]);
+        words.insert(&[i; 32]);
+    }
+    
+    assert_eq!(NullifierBloom::from_le_bytes(&bits, 5), words);
+    assert!(bloom::contains_bytes(&bits, 5, &[7u8; 32]));
+}
+
+#[test]
+fn shards_keep_nullifiers_sorted_without_their_prefix() {
+    let program_id = Pubkey::new_unique();
+    let mut first = [5u8; 32];
+    first[2] = 9;
+    let second = [5u8; 32];
+    assert_eq!(
+        NullifierShard::find_address(&first, &program_id),
+        NullifierShard::find_address(&second, &program_id)
+    );
+    assert_ne!(
+        NullifierShard::find_address(&first, &program_id),
+        NullifierShard::find_address(&[6u8; 32], &program_id)
+    );
+    
+    let header = NullifierShard {
+        version: NullifierShard::VERSION,
+        is_initialized: true,
+        prefix: [5, 5],
+        count: 0,
+    };
+    let mut data = header.try_to_vec().unwrap();
+    for nullifier in [first, second] {
+        data.resize(data.len() + NullifierShard::ENTRY_LEN, 0);
+        NullifierShard::insert(&mut data, &nullifier).unwrap();
+    }
+    assert_eq!(NullifierShard::unpack_header(&data).unwrap().count, 2);
+    assert_eq!(&data[NullifierShard::HEADER_LEN..][..NullifierShard::ENTRY_LEN], &second[2..]);
+    assert!(NullifierShard::contains(&data, &first));
+    let mut absent = second;
+    absent[31] = 1;
+    assert!(!NullifierShard::contains(&data, &absent));
+    
+    // Repeats and nullifiers of another shard are refused
+    data.resize(data.len() + NullifierShard::ENTRY_LEN, 0);
+    assert!(NullifierShard::insert(&mut data, &second).is_err());
+    assert!(NullifierShard::insert(&mut data, &[6u8; 32]).is_err());
+}
+
+#[tokio::test]
+async fn spends_are_recorded_in_the_filter() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let key = SpendKey::new(3);
+    let point = common::add_point(&mut program_test, &program_id, &common::point(&key, &mint));
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    harness.set_point_creator(&point, user).await;
+    
+    let extend =
+        instruction::extend_nullifier_filter(&program_id, &user, &protocol_state, &user).unwrap();
+    let setup = vec![extend.clone(); instruction::nullifier_filter_calls()];
+    harness.process(&setup, &[]).await.unwrap();
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 1)
+        .await;
+    
+    // The filter is complete, so another call has nothing left to allocate
+    harness.expect_error(&[extend], &[], FPPError::AccountAlreadyInitialized).await;
+    
+    let nullifier = key.nullifier();
+    let request = instruction::request_withdrawal(
+        &program_id,
+        &user,
+        &protocol_state,
+        vec![point],
+        vec![nullifier],
+        0,
+        common::sign_withdrawal(&program_id, &user, 0, &[&key]),
+    )
+    .unwrap();
+    harness.process(&[request], &[]).await.unwrap();
+    
+    let account = harness
+        .context
+        .banks_client
+        .get_account(NullifierFilter::find_address(&program_id).0)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), NullifierFilter::LEN);
+    let (header, filter) = NullifierFilter::unpack(&account.data).unwrap();
+    assert_eq!(header.count, 1);
+    assert!(filter.contains(&nullifier));
+    assert!(!filter.contains(&[8u8; 32]));
+}
+
+#[tokio::test]
+async fn only_the_authority_extends_the_filter() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    let extend = |authority: &Pubkey| {
+        instruction::extend_nullifier_filter(&program_id, authority, &protocol_state, authority)
+            .unwrap()
+    };
+    
+    // Neither the protocol state nor the filter can be swapped out
+    harness.expect_authority_only(extend, &[1, 2]).await;
+}
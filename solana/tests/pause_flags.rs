@@ -0,0 +1,175 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    state::{ProtocolState, POINT_VALUE, UNPAUSE_TIMELOCK},
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn paused_deposits_still_allow_withdrawals() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let key = SpendKey::new(3);
+    let point = common::add_point(&mut program_test, &program_id, &common::point(&key, &mint));
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    harness.set_point_creator(&point, user).await;
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 1)
+        .await;
+    
+    let pause = instruction::set_paused_flags(
+        &program_id,
+        &user,
+        &protocol_state,
+        ProtocolState::PAUSE_DEPOSITS,
+    )
+    .unwrap();
+    harness.process(&[pause], &[]).await.unwrap();
+    
+    // Unknown bits are rejected
+    let invalid =
+        instruction::set_paused_flags(&program_id, &user, &protocol_state, 1 << 7).unwrap();
+    harness.expect_error(&[invalid], &[], FPPError::InvalidInstruction).await;
+    
+    let epoch = harness.clock().await.epoch;
+    let deposit = instruction::deposit(
+        &program_id,
+        &user,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        &protocol_state,
+        &mint,
+        &spl_token::id(),
+        POINT_VALUE,
+        POINT_VALUE,
+        vec![[5u8; 32]],
+        vec![],
+        epoch,
+    )
+    .unwrap();
+    harness.expect_unauthorized(&[deposit], &[]).await;
+    
+    let request = instruction::request_withdrawal(
+        &program_id,
+        &user,
+        &protocol_state,
+        vec![point],
+        vec![key.nullifier()],
+        0,
+        common::sign_withdrawal(&program_id, &user, 0, &[&key]),
+    )
+    .unwrap();
+    harness.process(&[request], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn only_the_authority_sets_pause_flags() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    let pause = |authority: &Pubkey| {
+        let flags = ProtocolState::PAUSE_WITHDRAWALS;
+        instruction::set_paused_flags(&program_id, authority, &protocol_state, flags).unwrap()
+    };
+    
+    harness.expect_authority_only(pause, &[1]).await;
+    
+    let apply = instruction::apply_unpause(&program_id, &protocol_state).unwrap();
+    let elsewhere = common::with_account(apply, 0, Pubkey::new_unique());
+    harness.expect_error(&[elsewhere], &[], FPPError::InvalidAccount).await;
+}
+
+#[tokio::test]
+async fn guardian_can_pause_but_not_resume() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let guardian = Keypair::new();
+    
+    let appoint =
+        instruction::set_guardian(&program_id, &user, &protocol_state, guardian.pubkey()).unwrap();
+    let pause =
+        instruction::set_paused(&program_id, &guardian.pubkey(), &protocol_state, true).unwrap();
+    harness.process(&[appoint, pause], &[&guardian]).await.unwrap();
+    
+    let state = harness.context.banks_client.get_account(protocol_state).await.unwrap().unwrap();
+    assert_eq!(
+        ProtocolState::try_from_slice(&state.data).unwrap().paused_flags,
+        ProtocolState::PAUSE_ALL
+    );
+    
+    let unpause =
+        instruction::set_paused(&program_id, &guardian.pubkey(), &protocol_state, false).unwrap();
+    harness.expect_unauthorized(&[unpause], &[&guardian]).await;
+    let flags =
+        instruction::set_paused_flags(&program_id, &guardian.pubkey(), &protocol_state, 0).unwrap();
+    harness.expect_unauthorized(&[flags], &[&guardian]).await;
+    let fees =
+        instruction::update_fees(&program_id, &guardian.pubkey(), &protocol_state, 0, 0).unwrap();
+    harness.expect_unauthorized(&[fees], &[&guardian]).await;
+    
+    // The authority can queue the unpause
+    let unpause = instruction::set_paused(&program_id, &user, &protocol_state, false).unwrap();
+    harness.process(&[unpause], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn unpausing_waits_out_the_timelock() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    let pause = instruction::set_paused(&program_id, &user, &protocol_state, true).unwrap();
+    // Lifting only the deposit pause is queued, not applied
+    let resume_deposits = instruction::set_paused_flags(
+        &program_id,
+        &user,
+        &protocol_state,
+        ProtocolState::PAUSE_PRIVACY_PAYMENTS | ProtocolState::PAUSE_WITHDRAWALS,
+    )
+    .unwrap();
+    harness.process(&[pause, resume_deposits], &[]).await.unwrap();
+    
+    let state = harness.context.banks_client.get_account(protocol_state).await.unwrap().unwrap();
+    let state = ProtocolState::try_from_slice(&state.data).unwrap();
+    assert_eq!(state.paused_flags, ProtocolState::PAUSE_ALL);
+    assert!(state.pending_unpause.is_pending());
+    
+    let apply = instruction::apply_unpause(&program_id, &protocol_state).unwrap();
+    harness.expect_error(&[apply.clone()], &[], FPPError::ConfigTimelockActive).await;
+    
+    harness.warp_seconds(UNPAUSE_TIMELOCK).await;
+    harness.process(&[apply.clone()], &[]).await.unwrap();
+    let state = harness.context.banks_client.get_account(protocol_state).await.unwrap().unwrap();
+    let state = ProtocolState::try_from_slice(&state.data).unwrap();
+    assert_eq!(
+        state.paused_flags,
+        ProtocolState::PAUSE_PRIVACY_PAYMENTS | ProtocolState::PAUSE_WITHDRAWALS
+    );
+    assert!(!state.pending_unpause.is_pending());
+    
+    // Pausing again cancels a queued unpause
+    let unpause = instruction::set_paused(&program_id, &user, &protocol_state, false).unwrap();
+    let pause = instruction::set_paused(&program_id, &user, &protocol_state, true).unwrap();
+    harness.process(&[unpause, pause], &[]).await.unwrap();
+    harness.warp_seconds(UNPAUSE_TIMELOCK).await;
+    harness.expect_error(&[apply], &[], FPPError::InvalidInstruction).await;
+}
@@ -0,0 +1,309 @@
+mod common;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    crypto::groth16::{Groth16Proof, Groth16VerifyingKey},
+    error::FPPError,
+    instruction,
+    state::{CommitmentTree, PoolConfig, PoolState, ProtocolState},
+};
+use solana_program_test::ProgramTest;
+use solana_sdk::{
+    hash::hash,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const DENOMINATION: u64 = 100_000_000;
+
+fn verifying_key() -> Groth16VerifyingKey {
+    Groth16VerifyingKey {
+        alpha_g1: [1u8; 64],
+        beta_g2: [1u8; 128],
+        gamma_g2: [1u8; 128],
+        delta_g2: [1u8; 128],
+        ic: [[1u8; 64]; 3],
+    }
+}
+
+fn proof() -> Groth16Proof {
+    Groth16Proof {
+        a: [1u8; 64],
+        b: [1u8; 128],
+        c: [1u8; 64],
+    }
+}
+
+/// A protocol with circuit 1 registered and a pool of `mint` on it created, but not opened
+async fn created_pool(
+    program_test: ProgramTest,
+    program_id: Pubkey,
+    mint: &Pubkey,
+) -> (TestHarness, Pubkey) {
+    let mut harness = TestHarness::start_initialized(program_test, program_id, mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    let register = instruction::register_circuit(
+        &program_id,
+        &user,
+        &protocol_state,
+        &user,
+        1,
+        20,
+        1,
+        1,
+        hash(&verifying_key().try_to_vec().unwrap()).to_bytes(),
+    )
+    .unwrap();
+    let create =
+        instruction::create_pool(&program_id, &user, &protocol_state, mint, DENOMINATION, 1)
+            .unwrap();
+    harness.process(&[register, create], &[]).await.unwrap();
+    (harness, PoolConfig::find_address(mint, DENOMINATION, &program_id).0)
+}
+
+#[tokio::test]
+async fn pool_deposits_fill_the_pools_own_tree() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token = common::add_token_account(&mut program_test, &mint, &treasury, 0);
+    let depositor = Keypair::new();
+    let depositor_token =
+        common::add_token_account(&mut program_test, &mint, &depositor.pubkey(), 2 * DENOMINATION);
+    let (mut harness, pool) = created_pool(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    // Only the registered key can be published for the circuit
+    let wrong_key = Groth16VerifyingKey {
+        alpha_g1: [2u8; 64],
+        ..verifying_key()
+    };
+    let publish = |verifying_key| {
+        instruction::update_verifying_key(
+            &program_id,
+            &user,
+            &protocol_state,
+            &user,
+            1,
+            1,
+            verifying_key,
+        )
+        .unwrap()
+    };
+    harness.expect_error(&[publish(wrong_key)], &[], FPPError::InvalidCircuit).await;
+    harness.process(&[publish(verifying_key())], &[]).await.unwrap();
+    
+    // Anyone may open a pool, but only into the accounts derived from it
+    let open = instruction::open_pool(&program_id, &user, &pool, 1).unwrap();
+    let misdirected = [
+        (1, FPPError::InvalidAccount),
+        (2, FPPError::InvalidCircuit),
+        (4, FPPError::InvalidAccount),
+    ];
+    for (index, error) in misdirected {
+        let elsewhere = common::with_account(open.clone(), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[], error).await;
+    }
+    harness.process(&[open], &[]).await.unwrap();
+    
+    let deposit = instruction::pool_deposit(
+        &program_id,
+        &depositor.pubkey(),
+        &depositor_token,
+        &treasury_token,
+        &protocol_state,
+        &pool,
+        &mint,
+        &spl_token::id(),
+        [1u8; 32],
+    )
+    .unwrap();
+    // Neither the protocol state nor the pool's tree can be swapped out
+    for index in [3, 6] {
+        let elsewhere = common::with_account(deposit.clone(), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[&depositor], FPPError::InvalidAccount).await;
+    }
+    harness.pause(ProtocolState::PAUSE_DEPOSITS).await;
+    harness.expect_unauthorized(&[deposit.clone()], &[&depositor]).await;
+    harness.pause(0).await;
+    harness.process(&[deposit], &[&depositor]).await.unwrap();
+    
+    let fee = DENOMINATION / 1000;
+    assert_eq!(harness.token_balance(&depositor_token).await, DENOMINATION - fee);
+    assert_eq!(harness.token_balance(&treasury_token).await, DENOMINATION + fee);
+    
+    let (tree_address, _) = PoolState::find_tree_address(&pool, &program_id);
+    let account = harness.context.banks_client.get_account(tree_address).await.unwrap().unwrap();
+    let tree = CommitmentTree::try_from_slice(&account.data).unwrap();
+    assert_eq!(tree.next_index, 1);
+    let (state_address, _) = PoolState::find_address(&pool, &program_id);
+    let account = harness.context.banks_client.get_account(state_address).await.unwrap().unwrap();
+    assert_eq!(PoolState::try_from_slice(&account.data).unwrap().deposit_count, 1);
+}
+
+#[tokio::test]
+async fn pool_withdrawals_need_a_proof_for_their_recipient() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token =
+        common::add_token_account(&mut program_test, &mint, &treasury, DENOMINATION);
+    let recipient_token =
+        common::add_token_account(&mut program_test, &mint, &Pubkey::new_unique(), 0);
+    let (mut harness, pool) = created_pool(program_test, program_id, &mint).await;
+    let relayer = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    let publish = instruction::update_verifying_key(
+        &program_id,
+        &relayer,
+        &protocol_state,
+        &relayer,
+        1,
+        1,
+        verifying_key(),
+    )
+    .unwrap();
+    let open = instruction::open_pool(&program_id, &relayer, &pool, 1).unwrap();
+    harness.process(&[publish, open], &[]).await.unwrap();
+    
+    let (tree_address, _) = PoolState::find_tree_address(&pool, &program_id);
+    let account = harness.context.banks_client.get_account(tree_address).await.unwrap().unwrap();
+    let root = CommitmentTree::try_from_slice(&account.data).unwrap().root;
+    let withdraw = |merkle_root| {
+        instruction::pool_withdraw(
+            &program_id,
+            &relayer,
+            &recipient_token,
+            &recipient_token,
+            &treasury_token,
+            &protocol_state,
+            &pool,
+            1,
+            &mint,
+            &spl_token::id(),
+            merkle_root,
+            [1u8; 32],
+            0,
+            proof(),
+        )
+        .unwrap()
+    };
+    
+    // The proof binds the recipient, and is checked against the pool's own roots and key
+    let misdirected = [
+        (1, FPPError::InvalidAccount),
+        (7, FPPError::InvalidAccount),
+        (8, FPPError::InvalidAccount),
+        (16, FPPError::InvalidCircuit),
+    ];
+    for (index, error) in misdirected {
+        let elsewhere = common::with_account(withdraw(root), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[], error).await;
+    }
+    harness.expect_error(&[withdraw([9u8; 32])], &[], FPPError::UnknownMerkleRoot).await;
+    harness.expect_error(&[withdraw(root)], &[], FPPError::InvalidProof).await;
+    assert_eq!(harness.token_balance(&treasury_token).await, DENOMINATION);
+}
+
+#[tokio::test]
+async fn transfers_between_pools_must_conserve_value() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    let key_hash = hash(&verifying_key().try_to_vec().unwrap()).to_bytes();
+    let mut setup = vec![
+        instruction::register_circuit(
+            &program_id,
+            &user,
+            &protocol_state,
+            &user,
+            1,
+            20,
+            1,
+            1,
+            key_hash,
+        )
+        .unwrap(),
+        instruction::register_circuit(
+            &program_id,
+            &user,
+            &protocol_state,
+            &user,
+            2,
+            20,
+            16,
+            2,
+            key_hash,
+        )
+        .unwrap(),
+        instruction::update_verifying_key(
+            &program_id,
+            &user,
+            &protocol_state,
+            &user,
+            2,
+            1,
+            verifying_key(),
+        )
+        .unwrap(),
+    ];
+    let small = DENOMINATION;
+    let large = 2 * small;
+    for denomination in [small, large] {
+        setup.push(
+            instruction::create_pool(&program_id, &user, &protocol_state, &mint, denomination, 1)
+                .unwrap(),
+        );
+    }
+    harness.process(&setup, &[]).await.unwrap();
+    let (source, _) = PoolConfig::find_address(&mint, small, &program_id);
+    let (destination, _) = PoolConfig::find_address(&mint, large, &program_id);
+    let open = [
+        instruction::open_pool(&program_id, &user, &source, 1).unwrap(),
+        instruction::open_pool(&program_id, &user, &destination, 1).unwrap(),
+        instruction::open_pool_route(&program_id, &user, &source, &destination, 2).unwrap(),
+    ];
+    harness.process(&open, &[]).await.unwrap();
+    
+    let (tree_address, _) = PoolState::find_tree_address(&source, &program_id);
+    let account = harness.context.banks_client.get_account(tree_address).await.unwrap().unwrap();
+    let root = CommitmentTree::try_from_slice(&account.data).unwrap().root;
+    let transfer = |inputs: u8| {
+        instruction::pool_transfer(
+            &program_id,
+            &user,
+            &protocol_state,
+            &source,
+            &destination,
+            2,
+            root,
+            (0..inputs).map(|i| [i + 1; 32]).collect(),
+            vec![[1u8; 32]],
+            proof(),
+        )
+        .unwrap()
+    };
+    
+    // One 100 USDT note falls short of a 200 USDT note; larger merges only differ in count
+    harness.expect_error(&[transfer(1)], &[], FPPError::InvalidAmount).await;
+    harness.expect_error(&[transfer(2)], &[], FPPError::InvalidProof).await;
+    
+    // Neither the protocol state nor the route can be swapped out
+    for index in [1, 2] {
+        let elsewhere = common::with_account(transfer(2), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[], FPPError::InvalidAccount).await;
+    }
+    harness.pause(ProtocolState::PAUSE_PRIVACY_PAYMENTS).await;
+    harness.expect_unauthorized(&[transfer(2)], &[]).await;
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    state::{ProtocolState, RateLimits},
+};
+use solana_sdk::pubkey::Pubkey;
+
+/// One withdrawal request an hour
+fn limits() -> RateLimits {
+    RateLimits {
+        window: 60 * 60,
+        max_withdrawals: 1,
+        ..RateLimits::default()
+    }
+}
+
+#[tokio::test]
+async fn withdrawal_requests_are_limited_per_window() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let keys: Vec<_> = (1..=3u8).map(SpendKey::new).collect();
+    let points: Vec<_> = keys
+        .iter()
+        .map(|key| common::add_point(&mut program_test, &program_id, &common::point(key, &mint)))
+        .collect();
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    for point in &points {
+        harness.set_point_creator(point, user).await;
+    }
+    
+    let set_limits =
+        instruction::set_rate_limits(&program_id, &user, &protocol_state, limits()).unwrap();
+    harness.process(&[set_limits], &[]).await.unwrap();
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 3)
+        .await;
+    
+    let request = |i: usize| {
+        instruction::request_withdrawal(
+            &program_id,
+            &user,
+            &protocol_state,
+            vec![points[i]],
+            vec![keys[i].nullifier()],
+            i as u64,
+            common::sign_withdrawal(&program_id, &user, i as u64, &[&keys[i]]),
+        )
+        .unwrap()
+    };
+    harness.process(&[request(0)], &[]).await.unwrap();
+    harness.expect_error(&[request(1)], &[], FPPError::RateLimitExceeded).await;
+    
+    // A new window starts once the current one has passed
+    harness.warp_seconds(limits().window).await;
+    harness.process(&[request(2)], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn only_the_authority_sets_rate_limits() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    let set_limits = |authority: &Pubkey| {
+        instruction::set_rate_limits(&program_id, authority, &protocol_state, limits()).unwrap()
+    };
+    
+    harness.expect_authority_only(set_limits, &[1]).await;
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    state::{DenyList, RootHistory},
+};
+use solana_sdk::{account::Account, pubkey::Pubkey, rent::Rent};
+
+async fn get_account(harness: &mut TestHarness, address: Pubkey) -> Account {
+    let account = harness
+        .context
+        .banks_client
+        .get_account(address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(account.lamports >= Rent::default().minimum_balance(account.data.len()));
+    account
+}
+
+#[tokio::test]
+async fn deny_list_and_root_history_grow_in_place() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    let deny = |entry| {
+        instruction::add_to_deny_list(&program_id, &authority, &protocol_state, &authority, entry)
+            .unwrap()
+    };
+    harness.process(&[deny([1u8; 32]), deny([2u8; 32])], &[]).await.unwrap();
+    
+    let deny_list = get_account(&mut harness, DenyList::find_address(&program_id).0).await;
+    assert_eq!(deny_list.data.len(), DenyList::space(2));
+    
+    let (history_address, _) = RootHistory::find_address(&program_id);
+    let history = get_account(&mut harness, history_address).await;
+    assert_eq!(history.data.len(), RootHistory::space(RootHistory::DEFAULT_SIZE));
+    let initial_root = RootHistory::try_from_slice(&history.data).unwrap().roots[0];
+    
+    let capacity = RootHistory::DEFAULT_SIZE + 3;
+    let resize = |capacity| {
+        instruction::resize_root_history(
+            &program_id,
+            &authority,
+            &protocol_state,
+            &authority,
+            capacity,
+        )
+        .unwrap()
+    };
+    harness.process(&[resize(capacity)], &[]).await.unwrap();
+    
+    let history = get_account(&mut harness, history_address).await;
+    assert_eq!(history.data.len(), RootHistory::space(capacity));
+    let history = RootHistory::try_from_slice(&history.data).unwrap();
+    assert_eq!(history.roots.len(), capacity as usize);
+    assert!(history.contains(&initial_root));
+    
+    // The history only grows
+    harness.expect_error(&[resize(capacity - 1)], &[], FPPError::InvalidAmount).await;
+}
+
+#[tokio::test]
+async fn only_the_authority_resizes_the_root_history() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    let resize = |authority: &Pubkey| {
+        instruction::resize_root_history(
+            &program_id,
+            authority,
+            &protocol_state,
+            authority,
+            RootHistory::DEFAULT_SIZE + 1,
+        )
+        .unwrap()
+    };
+    
+    // Neither the protocol state nor the root history can be swapped out
+    harness.expect_authority_only(resize, &[1, 2]).await;
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    state::{ProtocolState, ReferralAccount, POINT_VALUE},
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn referrers_claim_their_share_of_deposit_fees() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token = common::add_token_account(&mut program_test, &mint, &treasury, 0);
+    let depositor = common::add_signer(&mut program_test);
+    let user_token =
+        common::add_token_account(&mut program_test, &mint, &depositor.pubkey(), 4 * POINT_VALUE);
+    let referrer = Keypair::new();
+    let referrer_token = common::add_token_account(&mut program_test, &mint, &referrer.pubkey(), 0);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let stranger = Keypair::new();
+    
+    // Half of the 0.1% deposit fee goes to referrers
+    let share =
+        instruction::set_referral_fee_share(&program_id, &authority, &protocol_state, 5000)
+            .unwrap();
+    harness.process(&[share], &[]).await.unwrap();
+    
+    let epoch = harness.clock().await.epoch;
+    let deposit = |referrer: Pubkey, commitment: [u8; 32]| {
+        instruction::deposit_with_referrer(
+            &program_id,
+            &depositor.pubkey(),
+            &user_token,
+            &treasury_token,
+            &protocol_state,
+            &mint,
+            &spl_token::id(),
+            POINT_VALUE,
+            POINT_VALUE,
+            vec![commitment],
+            vec![],
+            Some(referrer),
+            epoch,
+        )
+        .unwrap()
+    };
+    
+    // Referring yourself would just be a fee discount
+    let own = deposit(depositor.pubkey(), [1u8; 32]);
+    harness.expect_error(&[own], &[&depositor], FPPError::InvalidAccount).await;
+    
+    let referred = deposit(referrer.pubkey(), [2u8; 32]);
+    harness.process(&[referred], &[&depositor]).await.unwrap();
+    
+    let (referral, _) = ReferralAccount::find_address(&referrer.pubkey(), &mint, &program_id);
+    let account = harness.context.banks_client.get_account(referral).await.unwrap().unwrap();
+    let record = ReferralAccount::try_from_slice(&account.data).unwrap();
+    assert_eq!(record.deposit_count, 1);
+    assert_eq!(record.volume, POINT_VALUE);
+    assert_eq!(record.accrued_fees, POINT_VALUE / 2000);
+    
+    // The protocol keeps the other half for `CollectFees`
+    let state = harness.context.banks_client.get_account(protocol_state).await.unwrap().unwrap();
+    assert_eq!(ProtocolState::try_from_slice(&state.data).unwrap().total_fees, POINT_VALUE / 2000);
+    
+    let claim = |referrer: &Pubkey| {
+        instruction::claim_referral_fees(
+            &program_id,
+            referrer,
+            &protocol_state,
+            &treasury_token,
+            &referrer_token,
+            &mint,
+            &spl_token::id(),
+        )
+        .unwrap()
+    };
+    // No one else can claim the referrer's fees
+    let hijack = common::with_account(claim(&stranger.pubkey()), 2, referral);
+    harness.expect_error(&[hijack], &[&stranger], FPPError::InvalidAccount).await;
+    let elsewhere = common::with_account(claim(&referrer.pubkey()), 1, Pubkey::new_unique());
+    harness.expect_error(&[elsewhere], &[&referrer], FPPError::InvalidAccount).await;
+    
+    let claim = claim(&referrer.pubkey());
+    harness.process(&[claim.clone()], &[&referrer]).await.unwrap();
+    assert_eq!(harness.token_balance(&referrer_token).await, POINT_VALUE / 2000);
+    
+    // Nothing is left to claim until the next referral
+    harness.warp_seconds(1).await;
+    harness.expect_error(&[claim], &[&referrer], FPPError::InsufficientBalance).await;
+}
+
+#[tokio::test]
+async fn only_the_authority_sets_the_referral_share() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    let share = |authority: &Pubkey| {
+        instruction::set_referral_fee_share(&program_id, authority, &protocol_state, 5000).unwrap()
+    };
+    
+    harness.expect_authority_only(share, &[1]).await;
+}
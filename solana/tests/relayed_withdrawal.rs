@@ -0,0 +1,290 @@
+mod common;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    migration::Versioned,
+    pda,
+    state::{FloatingPoint, ProtocolState, WithdrawalRequest, POINT_VALUE, WITHDRAWAL_DELAY},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+#[tokio::test]
+async fn relayed_request_pays_recipient_and_relayer() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token =
+        common::add_token_account(&mut program_test, &mint, &treasury, POINT_VALUE);
+    let recipient = Pubkey::new_unique();
+    let relayer = Pubkey::new_unique();
+    let recipient_token = common::add_token_account(&mut program_test, &mint, &recipient, 0);
+    let relayer_token = common::add_token_account(&mut program_test, &mint, &relayer, 0);
+    let other_token = common::add_token_account(&mut program_test, &mint, &Pubkey::new_unique(), 0);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    // Stand in for a `RelayWithdrawal` whose delay has passed
+    let relayer_fee = POINT_VALUE / 100;
+    let request = WithdrawalRequest {
+        version: WithdrawalRequest::VERSION,
+        is_initialized: true,
+        requester: recipient,
+        amount: POINT_VALUE,
+        request_time: 0,
+        unlock_time: 0,
+        completed: false,
+        cancelled: false,
+        point_count: 1,
+        points_hash: [0u8; 32],
+        compliance_root: [0u8; 32],
+        mint,
+        nonce: 0,
+        relayer,
+        relayer_fee,
+    };
+    let (withdrawal_request, _) = pda::find_withdrawal_request(&recipient, 0, &program_id);
+    harness.set_program_account(&withdrawal_request, request.try_to_vec().unwrap());
+    
+    let epoch = harness.clock().await.epoch;
+    let complete = |recipient_token: &Pubkey| {
+        instruction::complete_relayed_withdrawal(
+            &program_id,
+            &authority,
+            recipient_token,
+            &relayer_token,
+            &treasury_token,
+            &protocol_state,
+            &withdrawal_request,
+            &mint,
+            &spl_token::id(),
+            epoch,
+        )
+        .unwrap()
+    };
+    
+    // Anyone may settle, but only into the accounts the request names
+    harness.expect_error(&[complete(&other_token)], &[], FPPError::InvalidAccount).await;
+    // Neither the protocol state nor the request can be swapped out
+    for index in [4, 5] {
+        let elsewhere =
+            common::with_account(complete(&recipient_token), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[], FPPError::InvalidAccount).await;
+    }
+    harness.pause(ProtocolState::PAUSE_WITHDRAWALS).await;
+    harness.expect_unauthorized(&[complete(&recipient_token)], &[]).await;
+    harness.pause(0).await;
+    
+    harness.process(&[complete(&recipient_token)], &[]).await.unwrap();
+    let account = harness
+        .context
+        .banks_client
+        .get_account(withdrawal_request)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(WithdrawalRequest::try_from_slice(&account.data).unwrap().completed);
+    
+    // 10 bps protocol fee stays in the treasury; the relayer's fee comes out of the rest
+    let fee = POINT_VALUE / 1000;
+    assert_eq!(harness.token_balance(&recipient_token).await, POINT_VALUE - fee - relayer_fee);
+    assert_eq!(harness.token_balance(&relayer_token).await, relayer_fee);
+    assert_eq!(harness.token_balance(&treasury_token).await, fee);
+}
+
+#[tokio::test]
+async fn relayed_request_rejects_other_payout_paths() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token =
+        common::add_token_account(&mut program_test, &mint, &treasury, POINT_VALUE);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let recipient = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let recipient_token = Pubkey::new_unique();
+    
+    let request = WithdrawalRequest {
+        version: WithdrawalRequest::VERSION,
+        is_initialized: true,
+        requester: recipient,
+        amount: POINT_VALUE,
+        request_time: 0,
+        unlock_time: 0,
+        completed: false,
+        cancelled: false,
+        point_count: 0,
+        points_hash: [0u8; 32],
+        compliance_root: [0u8; 32],
+        mint,
+        nonce: 0,
+        relayer: Pubkey::new_unique(),
+        relayer_fee: 0,
+    };
+    let (withdrawal_request, _) = pda::find_withdrawal_request(&recipient, 0, &program_id);
+    harness.set_program_account(&withdrawal_request, request.try_to_vec().unwrap());
+    
+    // Even the recipient can't take the payout without the relayer's share
+    let epoch = harness.clock().await.epoch;
+    let complete = instruction::complete_withdrawal(
+        &program_id,
+        &recipient,
+        &recipient_token,
+        &treasury_token,
+        &protocol_state,
+        &withdrawal_request,
+        &mint,
+        &spl_token::id(),
+        false,
+        epoch,
+    )
+    .unwrap();
+    assert!(harness.process(&[complete], &[]).await.is_err());
+    
+    let cancel = instruction::cancel_withdrawal(
+        &program_id,
+        &recipient,
+        &protocol_state,
+        &withdrawal_request,
+        &[],
+        &[],
+        false,
+    )
+    .unwrap();
+    assert!(harness.process(&[cancel], &[]).await.is_err());
+}
+
+#[tokio::test]
+async fn relay_is_bound_by_the_points_spend_signature() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token =
+        common::add_token_account(&mut program_test, &mint, &treasury, POINT_VALUE);
+    let key = SpendKey::new(3);
+    let point = common::add_point(&mut program_test, &program_id, &common::point(&key, &mint));
+    // The recipient never signs, so it needs no funded account
+    let recipient = Pubkey::new_unique();
+    let relayer = common::add_signer(&mut program_test);
+    let stranger = common::add_signer(&mut program_test);
+    let recipient_token = common::add_token_account(&mut program_test, &mint, &recipient, 0);
+    let relayer_token = common::add_token_account(&mut program_test, &mint, &relayer.pubkey(), 0);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    let config =
+        instruction::set_relayer_config(&program_id, &authority, &protocol_state, &authority, 100)
+            .unwrap();
+    harness.process(&[config], &[]).await.unwrap();
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 1)
+        .await;
+    
+    // The owner signs for this recipient, relayer and fee
+    let relayer_fee = POINT_VALUE / 100;
+    let relay_hash = WithdrawalRequest::relay_hash(&recipient, &relayer.pubkey(), relayer_fee, 0);
+    let spend_signature =
+        common::sign_spend(&WithdrawalRequest::relay_spend_message(&relay_hash), &[&key]);
+    let relay = |relayer: &Pubkey, recipient: Pubkey, relayer_fee: u64| {
+        instruction::relay_withdrawal(
+            &program_id,
+            relayer,
+            &protocol_state,
+            vec![point],
+            vec![key.nullifier()],
+            0,
+            recipient,
+            relayer_fee,
+            spend_signature.clone(),
+        )
+        .unwrap()
+    };
+    
+    // The relayer can neither redirect the withdrawal nor raise its fee, and no one
+    // else can relay it
+    let tampered = [
+        (relay(&relayer.pubkey(), relayer.pubkey(), relayer_fee), &relayer),
+        (relay(&relayer.pubkey(), recipient, 2 * relayer_fee), &relayer),
+        (relay(&stranger.pubkey(), recipient, relayer_fee), &stranger),
+    ];
+    for (tampered, signer) in tampered {
+        harness.expect_error(&[tampered], &[signer], FPPError::InvalidRingSignature).await;
+    }
+    // Neither the protocol state nor the relayer config can be swapped out
+    for index in [1, 7] {
+        let elsewhere = common::with_account(
+            relay(&relayer.pubkey(), recipient, relayer_fee),
+            index,
+            Pubkey::new_unique(),
+        );
+        harness.expect_error(&[elsewhere], &[&relayer], FPPError::InvalidAccount).await;
+    }
+    harness.pause(ProtocolState::PAUSE_WITHDRAWALS).await;
+    let paused = relay(&relayer.pubkey(), recipient, relayer_fee);
+    harness.expect_unauthorized(&[paused], &[&relayer]).await;
+    harness.pause(0).await;
+    
+    harness
+        .process(&[relay(&relayer.pubkey(), recipient, relayer_fee)], &[&relayer])
+        .await
+        .unwrap();
+    let account = harness.context.banks_client.get_account(point).await.unwrap().unwrap();
+    assert!(!FloatingPoint::try_from_slice(&account.data).unwrap().is_active);
+    
+    let (withdrawal_request, _) = pda::find_withdrawal_request(&recipient, 0, &program_id);
+    let account = harness
+        .context
+        .banks_client
+        .get_account(withdrawal_request)
+        .await
+        .unwrap()
+        .unwrap();
+    let request = WithdrawalRequest::try_from_slice(&account.data).unwrap();
+    assert_eq!(request.requester, recipient);
+    assert_eq!(request.relayer, relayer.pubkey());
+    assert_eq!(request.relayer_fee, relayer_fee);
+    
+    harness.warp_seconds(WITHDRAWAL_DELAY).await;
+    let epoch = harness.clock().await.epoch;
+    let complete = instruction::complete_relayed_withdrawal(
+        &program_id,
+        &authority,
+        &recipient_token,
+        &relayer_token,
+        &treasury_token,
+        &protocol_state,
+        &withdrawal_request,
+        &mint,
+        &spl_token::id(),
+        epoch,
+    )
+    .unwrap();
+    harness.process(&[complete], &[]).await.unwrap();
+    
+    let fee = POINT_VALUE / 1000;
+    assert_eq!(harness.token_balance(&recipient_token).await, POINT_VALUE - fee - relayer_fee);
+    assert_eq!(harness.token_balance(&relayer_token).await, relayer_fee);
+}
+
+#[tokio::test]
+async fn only_the_authority_configures_relaying() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    let config = |authority: &Pubkey| {
+        instruction::set_relayer_config(&program_id, authority, &protocol_state, authority, 100)
+            .unwrap()
+    };
+    
+    // Neither the protocol state nor the relayer config can be swapped out
+    harness.expect_authority_only(config, &[1, 2]).await;
+}
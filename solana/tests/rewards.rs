@@ -0,0 +1,152 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    state::{ProtocolState, RewardAccount, POINT_VALUE},
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn deposits_accrue_rewards_paid_from_the_pool() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token = common::add_token_account(&mut program_test, &mint, &treasury, 0);
+    let reward_mint = common::add_mint(&mut program_test, 6);
+    let (rewards_authority, _) = ProtocolState::find_rewards_authority(&program_id);
+    let vault = common::add_token_account(&mut program_test, &reward_mint, &rewards_authority, 0);
+    let depositor = common::add_signer(&mut program_test);
+    let user_token =
+        common::add_token_account(&mut program_test, &mint, &depositor.pubkey(), 2 * POINT_VALUE);
+    let user_reward_token =
+        common::add_token_account(&mut program_test, &reward_mint, &depositor.pubkey(), 0);
+    let funder = Keypair::new();
+    let funder_token =
+        common::add_token_account(&mut program_test, &reward_mint, &funder.pubkey(), 4_000);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let stranger = Keypair::new();
+    let (reward_account, _) = RewardAccount::find_address(&depositor.pubkey(), &program_id);
+    
+    // 1,000 reward units a day for every whole token deposited
+    let configure = instruction::set_reward_config(
+        &program_id,
+        &authority,
+        &protocol_state,
+        &vault,
+        &reward_mint,
+        1_000,
+    )
+    .unwrap();
+    harness.process(&[configure], &[]).await.unwrap();
+    
+    let epoch = harness.clock().await.epoch;
+    let deposit = instruction::deposit(
+        &program_id,
+        &depositor.pubkey(),
+        &user_token,
+        &treasury_token,
+        &protocol_state,
+        &mint,
+        &spl_token::id(),
+        POINT_VALUE,
+        POINT_VALUE,
+        vec![[5u8; 32]],
+        vec![],
+        epoch,
+    )
+    .unwrap();
+    harness.process(&[deposit], &[&depositor]).await.unwrap();
+    
+    // A day on, 10 tokens have earned 10,000 but the pool only holds 4,000
+    harness.warp_seconds(24 * 60 * 60).await;
+    let fund = instruction::fund_rewards(
+        &program_id,
+        &funder.pubkey(),
+        &funder_token,
+        &vault,
+        &protocol_state,
+        &reward_mint,
+        &spl_token::id(),
+        4_000,
+    )
+    .unwrap();
+    // Funding only ever goes into the configured vault
+    let elsewhere = common::with_account(fund.clone(), 2, funder_token);
+    harness.expect_error(&[elsewhere], &[&funder], FPPError::InvalidAccount).await;
+    harness.process(&[fund], &[&funder]).await.unwrap();
+    
+    let claim = |user: &Pubkey| {
+        instruction::claim_rewards(
+            &program_id,
+            user,
+            &protocol_state,
+            &vault,
+            &user_reward_token,
+            &reward_mint,
+            &spl_token::id(),
+        )
+        .unwrap()
+    };
+    // No one else can claim the depositor's rewards, and the vault pays out only under
+    // the rewards authority
+    let hijack = common::with_account(claim(&stranger.pubkey()), 2, reward_account);
+    harness.expect_error(&[hijack], &[&stranger], FPPError::InvalidAccount).await;
+    for index in [1, 4] {
+        let elsewhere =
+            common::with_account(claim(&depositor.pubkey()), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[&depositor], FPPError::InvalidAccount).await;
+    }
+    
+    let claim = claim(&depositor.pubkey());
+    harness.process(&[claim.clone()], &[&depositor]).await.unwrap();
+    assert_eq!(harness.token_balance(&user_reward_token).await, 4_000);
+    
+    let account = harness.context.banks_client.get_account(reward_account).await.unwrap().unwrap();
+    let rewards = RewardAccount::try_from_slice(&account.data).unwrap();
+    assert_eq!(rewards.balance, POINT_VALUE);
+    assert_eq!(rewards.total_claimed, 4_000);
+    assert!(rewards.accrued >= 6_000);
+    
+    // The rest waits for the pool to be topped up
+    harness.warp_seconds(1).await;
+    harness.expect_error(&[claim], &[&depositor], FPPError::InsufficientBalance).await;
+}
+
+#[tokio::test]
+async fn only_the_authority_configures_rewards() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let reward_mint = common::add_mint(&mut program_test, 6);
+    let (rewards_authority, _) = ProtocolState::find_rewards_authority(&program_id);
+    let vault = common::add_token_account(&mut program_test, &reward_mint, &rewards_authority, 0);
+    let decoy =
+        common::add_token_account(&mut program_test, &reward_mint, &Pubkey::new_unique(), 0);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let configure = |authority: &Pubkey, vault: &Pubkey| {
+        instruction::set_reward_config(
+            &program_id,
+            authority,
+            &protocol_state,
+            vault,
+            &reward_mint,
+            1_000,
+        )
+        .unwrap()
+    };
+    
+    harness.expect_authority_only(|authority| configure(authority, &vault), &[1]).await;
+    // The vault has to be held by the rewards authority
+    harness.expect_error(&[configure(&authority, &decoy)], &[], FPPError::InvalidAccount).await;
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    pda,
+    state::{FloatingPoint, ProtocolState},
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn only_the_creator_can_split_a_point() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let owner = common::add_signer(&mut program_test);
+    let stranger = common::add_signer(&mut program_test);
+    let key = SpendKey::new(3);
+    let point = FloatingPoint {
+        mass: 2,
+        creator: owner.pubkey(),
+        rent_payer: owner.pubkey(),
+        ..common::point(&key, &mint)
+    };
+    let point = common::add_point(&mut program_test, &program_id, &point);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 1)
+        .await;
+    
+    let outputs = vec![SpendKey::new(4).commitment(), SpendKey::new(5).commitment()];
+    let split = |holder: &Keypair, signer: &SpendKey| {
+        let message = FloatingPoint::split_message(&holder.pubkey(), &outputs, &[1, 1]);
+        instruction::split_point(
+            &program_id,
+            &holder.pubkey(),
+            &protocol_state,
+            &point,
+            key.nullifier(),
+            outputs.clone(),
+            vec![1, 1],
+            Vec::new(),
+            common::sign_spend(&message, &[signer]),
+        )
+        .unwrap()
+    };
+    
+    // Knowing the point's address and commitment isn't enough to split it
+    harness.expect_unauthorized(&[split(&stranger, &key)], &[&stranger]).await;
+    // Nor is being its creator without its key
+    let forged = split(&owner, &SpendKey::new(6));
+    harness.expect_error(&[forged], &[&owner], FPPError::InvalidRingSignature).await;
+    // The protocol state, point, commitment tree and root history can't be swapped out
+    for index in [1, 2, 7, 8] {
+        let elsewhere = common::with_account(split(&owner, &key), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[&owner], FPPError::InvalidAccount).await;
+    }
+    harness.pause(ProtocolState::PAUSE_PRIVACY_PAYMENTS).await;
+    harness.expect_unauthorized(&[split(&owner, &key)], &[&owner]).await;
+    harness.pause(0).await;
+    
+    harness.process(&[split(&owner, &key)], &[&owner]).await.unwrap();
+    let banks = &mut harness.context.banks_client;
+    let account = banks.get_account(point).await.unwrap().unwrap();
+    let split_point = FloatingPoint::try_from_slice(&account.data).unwrap();
+    assert!(!split_point.is_active);
+    assert_eq!(split_point.mass, 0);
+    for commitment in &outputs {
+        let (address, _) = pda::find_point_address(commitment, &program_id);
+        let account = banks.get_account(address).await.unwrap().unwrap();
+        let output = FloatingPoint::try_from_slice(&account.data).unwrap();
+        assert_eq!(output.creator, owner.pubkey());
+        assert_eq!(output.mass, 1);
+    }
+    
+    let state = banks.get_account(protocol_state).await.unwrap().unwrap();
+    assert_eq!(ProtocolState::try_from_slice(&state.data).unwrap().total_points, 2);
+}
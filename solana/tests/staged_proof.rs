@@ -0,0 +1,147 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    state::{ProtocolState, StagedPayment, StagedProof},
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+fn payment() -> StagedPayment {
+    StagedPayment {
+        merkle_root: [1u8; 32],
+        input_nullifiers: vec![[2u8; 32]],
+        output_commitments: vec![[3u8; 32]],
+        proof: vec![4u8; 64],
+        ring_signature: vec![5u8; 96],
+        ring: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+        encrypted_notes: vec![vec![6u8; 80]],
+    }
+}
+
+/// A protocol with the payer's `payment` fully staged under nonce 0
+async fn staged_payment(payment: &StagedPayment) -> TestHarness {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let owner = harness.payer();
+    
+    let chunks = instruction::stage_payment(&program_id, &owner, 0, payment, 128).unwrap();
+    assert!(chunks.len() > 1);
+    for chunk in chunks {
+        harness.process(&[chunk], &[]).await.unwrap();
+    }
+    harness
+}
+
+#[tokio::test]
+async fn staged_payment_is_written_in_chunks_and_expires() {
+    let payment = payment();
+    let mut harness = staged_payment(&payment).await;
+    let program_id = harness.program_id;
+    let owner = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let stranger = Keypair::new();
+    let (staged_proof, _) = StagedProof::find_address(&owner, 0, &program_id);
+    
+    let account = harness.context.banks_client.get_account(staged_proof).await.unwrap().unwrap();
+    let staged = StagedProof::load(&account.data).unwrap();
+    assert!(staged.is_written());
+    assert_eq!(
+        StagedPayment::try_from_slice(staged.payload(&account.data).unwrap()).unwrap(),
+        payment
+    );
+    
+    // Nothing more fits, and an unverified payment can't execute
+    let chunks = instruction::stage_payment(&program_id, &owner, 0, &payment, 128).unwrap();
+    let extra = chunks.last().unwrap().clone();
+    harness.expect_error(&[extra], &[], FPPError::InvalidInstruction).await;
+    let execute = instruction::execute_staged_payment(
+        &program_id,
+        &owner,
+        &protocol_state,
+        &Pubkey::new_unique(),
+        0,
+        &payment,
+        1,
+        None,
+    )
+    .unwrap();
+    harness.expect_error(&[execute], &[], FPPError::StagedProofNotReady).await;
+    
+    // Only the owner can close it early; once expired anyone can
+    let close =
+        instruction::close_staged_proof(&program_id, &stranger.pubkey(), &owner, 0).unwrap();
+    harness.expect_unauthorized(&[close.clone()], &[&stranger]).await;
+    harness.warp_seconds(StagedProof::TTL + 1).await;
+    harness.process(&[close], &[&stranger]).await.unwrap();
+    assert!(harness.context.banks_client.get_account(staged_proof).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn only_the_owner_works_on_a_staged_payment() {
+    let payment = payment();
+    let mut harness = staged_payment(&payment).await;
+    let program_id = harness.program_id;
+    let owner = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let stranger = Keypair::new();
+    let (staged_proof, _) = StagedProof::find_address(&owner, 0, &program_id);
+    let verify = |owner: &Pubkey| {
+        let first_row = &payment.ring[..1];
+        instruction::verify_staged_proof(
+            &program_id,
+            owner,
+            &protocol_state,
+            0,
+            first_row,
+            first_row,
+            1,
+        )
+        .unwrap()
+    };
+    let recipient = Pubkey::new_unique();
+    let execute = |owner: &Pubkey| {
+        instruction::execute_staged_payment(
+            &program_id,
+            owner,
+            &protocol_state,
+            &recipient,
+            0,
+            &payment,
+            1,
+            None,
+        )
+        .unwrap()
+    };
+    
+    // Another signer can't write into, verify or execute the owner's payment
+    let chunks =
+        instruction::stage_payment(&program_id, &stranger.pubkey(), 0, &payment, 128).unwrap();
+    let write = common::with_account(chunks[0].clone(), 1, staged_proof);
+    harness.expect_error(&[write], &[&stranger], FPPError::InvalidAccount).await;
+    for hijack in [verify(&stranger.pubkey()), execute(&stranger.pubkey())] {
+        let hijack = common::with_account(hijack, 2, staged_proof);
+        harness.expect_unauthorized(&[hijack], &[&stranger]).await;
+    }
+    
+    // Nor can the owner point either at another protocol state, or close into another
+    // account
+    for instruction in [verify(&owner), execute(&owner)] {
+        let elsewhere = common::with_account(instruction, 1, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[], FPPError::InvalidAccount).await;
+    }
+    let close = instruction::close_staged_proof(&program_id, &owner, &owner, 0).unwrap();
+    let elsewhere = common::with_account(close, 2, Pubkey::new_unique());
+    harness.expect_error(&[elsewhere], &[], FPPError::InvalidAccount).await;
+    
+    harness.pause(ProtocolState::PAUSE_PRIVACY_PAYMENTS).await;
+    harness.expect_unauthorized(&[verify(&owner)], &[]).await;
+    harness.expect_unauthorized(&[execute(&owner)], &[]).await;
+}
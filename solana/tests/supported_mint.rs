@@ -0,0 +1,128 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    pda,
+    state::{
+        FloatingPoint, ProtocolState, SupportedMint, SupportedMintConfig, MAX_DENOMINATIONS,
+        POINT_VALUE,
+    },
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+/// Five-unit points only, at a 0.2% fee, up to ten units outstanding
+fn config() -> SupportedMintConfig {
+    let mut denominations = [0u64; MAX_DENOMINATIONS];
+    denominations[0] = 5 * POINT_VALUE;
+    SupportedMintConfig {
+        denominations,
+        deposit_fee_rate: 20,
+        withdrawal_fee_rate: 20,
+        max_deposit: 0,
+        deposit_cap: 10 * POINT_VALUE,
+    }
+}
+
+#[tokio::test]
+async fn deposits_follow_the_mint_configuration_and_cap() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let usdt = common::add_mint(&mut program_test, 6);
+    let usdc = common::add_mint(&mut program_test, 6);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token = common::add_token_account(&mut program_test, &usdc, &treasury, 0);
+    let depositor = common::add_signer(&mut program_test);
+    let user_token =
+        common::add_token_account(&mut program_test, &usdc, &depositor.pubkey(), 20 * POINT_VALUE);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &usdt).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    
+    let add = instruction::add_supported_mint(
+        &program_id,
+        &authority,
+        &protocol_state,
+        &authority,
+        usdc,
+        config(),
+    )
+    .unwrap();
+    harness.process(&[add], &[]).await.unwrap();
+    
+    let epoch = harness.clock().await.epoch;
+    let deposit = |amount: u64, commitment: [u8; 32]| {
+        instruction::deposit(
+            &program_id,
+            &depositor.pubkey(),
+            &user_token,
+            &treasury_token,
+            &protocol_state,
+            &usdc,
+            &spl_token::id(),
+            amount,
+            5 * POINT_VALUE,
+            vec![commitment],
+            vec![],
+            epoch,
+        )
+        .unwrap()
+    };
+    
+    harness
+        .process(&[deposit(5 * POINT_VALUE, [5u8; 32])], &[&depositor])
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(&treasury_token).await, 5 * POINT_VALUE + POINT_VALUE / 100);
+    
+    let banks = &mut harness.context.banks_client;
+    let (point, _) = pda::find_point_address(&[5u8; 32], &program_id);
+    let point = banks.get_account(point).await.unwrap().unwrap();
+    assert_eq!(FloatingPoint::try_from_slice(&point.data).unwrap().mint, usdc);
+    let (entry_address, _) = SupportedMint::find_address(&usdc, &program_id);
+    let entry = banks.get_account(entry_address).await.unwrap().unwrap();
+    let entry = SupportedMint::try_from_slice(&entry.data).unwrap();
+    assert_eq!(entry.total_deposited, 5 * POINT_VALUE);
+    assert_eq!(entry.total_fees, POINT_VALUE / 100);
+    
+    // Ten units would take the outstanding total past the cap
+    harness
+        .expect_error(
+            &[deposit(10 * POINT_VALUE, [6u8; 32])],
+            &[&depositor],
+            FPPError::DepositCapExceeded,
+        )
+        .await;
+    
+    // Pausing deposits covers every mint, not just the primary one
+    harness.pause(ProtocolState::PAUSE_DEPOSITS).await;
+    harness.expect_unauthorized(&[deposit(5 * POINT_VALUE, [7u8; 32])], &[&depositor]).await;
+}
+
+#[tokio::test]
+async fn only_the_authority_adds_supported_mints() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let usdt = common::add_mint(&mut program_test, 6);
+    let usdc = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &usdt).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let add = |authority: &Pubkey| {
+        instruction::add_supported_mint(
+            &program_id,
+            authority,
+            &protocol_state,
+            authority,
+            usdc,
+            config(),
+        )
+        .unwrap()
+    };
+    
+    // Neither the protocol state nor the mint's entry can be swapped out
+    harness.expect_authority_only(add, &[1, 2]).await;
+    harness.process(&[add(&authority)], &[]).await.unwrap();
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    pda,
+    state::{ProtocolState, WithdrawalRequest, WITHDRAWAL_DELAY},
+};
+use solana_sdk::pubkey::Pubkey;
+
+#[tokio::test]
+async fn withdrawal_delay_follows_the_protocol_state() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let key = SpendKey::new(3);
+    let point = common::add_point(&mut program_test, &program_id, &common::point(&key, &mint));
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let authority = harness.payer();
+    let protocol_state = harness.protocol_state();
+    harness.set_point_creator(&point, authority).await;
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 1)
+        .await;
+    
+    let account = harness.context.banks_client.get_account(protocol_state).await.unwrap().unwrap();
+    let state = ProtocolState::try_from_slice(&account.data).unwrap();
+    assert_eq!(state.withdrawal_delay, WITHDRAWAL_DELAY);
+    
+    let update = |authority: &Pubkey, withdrawal_delay, point_lock_duration| {
+        instruction::update_timelocks(
+            &program_id,
+            authority,
+            &protocol_state,
+            withdrawal_delay,
+            point_lock_duration,
+        )
+        .unwrap()
+    };
+    
+    // Out-of-range values are rejected
+    for (delay, lock) in [
+        (ProtocolState::MIN_WITHDRAWAL_DELAY - 1, 0),
+        (ProtocolState::MAX_WITHDRAWAL_DELAY + 1, 0),
+        (WITHDRAWAL_DELAY, -1),
+        (WITHDRAWAL_DELAY, ProtocolState::MAX_POINT_LOCK_DURATION + 1),
+    ] {
+        let out_of_range = update(&authority, delay, lock);
+        harness.expect_error(&[out_of_range], &[], FPPError::InvalidAmount).await;
+    }
+    
+    harness.expect_authority_only(|authority| update(authority, 60, 0), &[1]).await;
+    
+    harness.process(&[update(&authority, 60, 0)], &[]).await.unwrap();
+    
+    let request = instruction::request_withdrawal(
+        &program_id,
+        &authority,
+        &protocol_state,
+        vec![point],
+        vec![key.nullifier()],
+        0,
+        common::sign_withdrawal(&program_id, &authority, 0, &[&key]),
+    )
+    .unwrap();
+    harness.process(&[request], &[]).await.unwrap();
+    
+    let (withdrawal_request, _) = pda::find_withdrawal_request(&authority, 0, &program_id);
+    let account = harness
+        .context
+        .banks_client
+        .get_account(withdrawal_request)
+        .await
+        .unwrap()
+        .unwrap();
+    let request = WithdrawalRequest::try_from_slice(&account.data).unwrap();
+    assert_eq!(request.unlock_time, request.request_time + 60);
+}
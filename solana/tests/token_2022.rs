@@ -0,0 +1,69 @@
+mod common;
+
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    state::{ProtocolState, POINT_VALUE},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+#[tokio::test]
+async fn deposit_covers_the_mint_transfer_fee() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    // 1% transfer fee
+    let mint = common::add_token_2022_mint_with_fee(&mut program_test, 6, 100, u64::MAX);
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token = common::add_token_2022_account(&mut program_test, &mint, &treasury, 0);
+    let decoy =
+        common::add_token_2022_account(&mut program_test, &mint, &Pubkey::new_unique(), 0);
+    let depositor = common::add_signer(&mut program_test);
+    let user_token = common::add_token_2022_account(
+        &mut program_test,
+        &mint,
+        &depositor.pubkey(),
+        2 * POINT_VALUE,
+    );
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    
+    let epoch = harness.clock().await.epoch;
+    let deposit = |treasury_token: &Pubkey, token_program: &Pubkey| {
+        instruction::deposit(
+            &program_id,
+            &depositor.pubkey(),
+            &user_token,
+            treasury_token,
+            &protocol_state,
+            &mint,
+            token_program,
+            POINT_VALUE,
+            POINT_VALUE,
+            vec![[5u8; 32]],
+            vec![],
+            epoch,
+        )
+        .unwrap()
+    };
+    
+    // The mint's own token program and the treasury's account are the only ones accepted
+    let classic = deposit(&treasury_token, &spl_token::id());
+    harness.expect_error(&[classic], &[&depositor], FPPError::InvalidAccount).await;
+    let misdirected = deposit(&decoy, &spl_token_2022::id());
+    harness.expect_error(&[misdirected], &[&depositor], FPPError::InvalidAccount).await;
+    harness.pause(ProtocolState::PAUSE_DEPOSITS).await;
+    let paused = deposit(&treasury_token, &spl_token_2022::id());
+    harness.expect_unauthorized(&[paused], &[&depositor]).await;
+    harness.pause(0).await;
+    
+    harness
+        .process(&[deposit(&treasury_token, &spl_token_2022::id())], &[&depositor])
+        .await
+        .unwrap();
+    
+    // The treasury holds the deposit plus the 10 bps protocol fee, net of the token's own fee
+    let protocol_fee = POINT_VALUE / 1000;
+    assert_eq!(harness.token_balance(&treasury_token).await, POINT_VALUE + protocol_fee);
+    assert!(harness.token_balance(&user_token).await < POINT_VALUE - protocol_fee);
+}
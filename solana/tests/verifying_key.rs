@@ -0,0 +1,107 @@
+mod common;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    crypto::groth16::Groth16VerifyingKey,
+    error::FPPError,
+    instruction,
+    state::{CircuitInfo, CircuitKey, CONFIG_TIMELOCK},
+};
+use solana_sdk::{
+    hash::hash,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn verifying_key_updates_wait_out_the_timelock() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    let stranger = Keypair::new();
+    
+    let first_key = Groth16VerifyingKey {
+        alpha_g1: [1u8; 64],
+        beta_g2: [1u8; 128],
+        gamma_g2: [1u8; 128],
+        delta_g2: [1u8; 128],
+        ic: [[1u8; 64]; 3],
+    };
+    let second_key = Groth16VerifyingKey {
+        alpha_g1: [2u8; 64],
+        ..first_key.clone()
+    };
+    let update = |authority: &Pubkey, circuit_version, verifying_key| {
+        instruction::update_verifying_key(
+            &program_id,
+            authority,
+            &protocol_state,
+            authority,
+            1,
+            circuit_version,
+            verifying_key,
+        )
+        .unwrap()
+    };
+    let register = instruction::register_circuit(
+        &program_id,
+        &user,
+        &protocol_state,
+        &user,
+        1,
+        20,
+        1,
+        1,
+        hash(&first_key.try_to_vec().unwrap()).to_bytes(),
+    )
+    .unwrap();
+    harness.process(&[register, update(&user, 1, first_key)], &[]).await.unwrap();
+    
+    // Only the authority publishes keys, and only into the circuit's own accounts
+    let queue = update(&stranger.pubkey(), 2, second_key.clone());
+    harness.expect_unauthorized(&[queue], &[&stranger]).await;
+    let misdirected = [
+        (1, FPPError::InvalidAccount),
+        (2, FPPError::InvalidCircuit),
+        (3, FPPError::InvalidAccount),
+    ];
+    for (index, error) in misdirected {
+        let queue = update(&user, 2, second_key.clone());
+        let elsewhere = common::with_account(queue, index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[], error).await;
+    }
+    
+    // Versions only move forward
+    let stale = update(&user, 1, second_key.clone());
+    harness.expect_error(&[stale], &[], FPPError::InvalidCircuit).await;
+    
+    let apply = instruction::apply_verifying_key(&program_id, 1).unwrap();
+    harness.process(&[update(&user, 2, second_key.clone())], &[]).await.unwrap();
+    harness.expect_error(&[apply.clone()], &[], FPPError::ConfigTimelockActive).await;
+    
+    harness.warp_seconds(CONFIG_TIMELOCK).await;
+    // Anyone may apply, but only to the circuit the key belongs to
+    for (index, error) in [(0, FPPError::InvalidCircuit), (1, FPPError::InvalidAccount)] {
+        let elsewhere = common::with_account(apply.clone(), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[], error).await;
+    }
+    harness.process(&[apply], &[]).await.unwrap();
+    
+    let (key_address, _) = CircuitKey::find_address(1, &program_id);
+    let account = harness.context.banks_client.get_account(key_address).await.unwrap().unwrap();
+    let circuit_key = CircuitKey::try_from_slice(&account.data).unwrap();
+    assert_eq!(circuit_key.circuit_version, 2);
+    assert_eq!(circuit_key.verifying_key, second_key);
+    assert!(!circuit_key.is_pending());
+    
+    let (circuit_address, _) = CircuitInfo::find_address(1, &program_id);
+    let account = harness.context.banks_client.get_account(circuit_address).await.unwrap().unwrap();
+    assert_eq!(
+        CircuitInfo::try_from_slice(&account.data).unwrap().verifying_key_hash,
+        hash(&second_key.try_to_vec().unwrap()).to_bytes()
+    );
+}
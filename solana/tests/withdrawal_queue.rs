@@ -0,0 +1,227 @@
+mod common;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction::{self, QueuedPayout},
+    migration::Versioned,
+    pda,
+    state::{
+        FloatingPoint, ProtocolState, WithdrawalQueue, WithdrawalRequest, POINT_VALUE,
+        WITHDRAWAL_DELAY,
+    },
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+#[tokio::test]
+async fn crank_pays_matured_requests_past_locked_ones_and_keeps_a_tip() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    // The crank pays the requester's own token account, so the requester needs a known key
+    let requester = common::add_signer(&mut program_test);
+    let cranker = common::add_signer(&mut program_test);
+    let keys = [SpendKey::new(3), SpendKey::new(4)];
+    let points: Vec<Pubkey> = keys
+        .iter()
+        .map(|key| {
+            let point = FloatingPoint {
+                creator: requester.pubkey(),
+                ..common::point(key, &mint)
+            };
+            common::add_point(&mut program_test, &program_id, &point)
+        })
+        .collect();
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token =
+        common::add_token_account(&mut program_test, &mint, &treasury, POINT_VALUE);
+    let user_token = common::add_token_account(&mut program_test, &mint, &requester.pubkey(), 0);
+    let tip_token = common::add_token_account(&mut program_test, &mint, &cranker.pubkey(), 0);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    let withdrawal_requests: Vec<Pubkey> = (0..2)
+        .map(|nonce| pda::find_withdrawal_request(&requester.pubkey(), nonce, &program_id).0)
+        .collect();
+    
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 2)
+        .await;
+    
+    for (nonce, (point, key)) in points.iter().zip(&keys).enumerate() {
+        let nonce = nonce as u64;
+        let request = instruction::request_withdrawal(
+            &program_id,
+            &requester.pubkey(),
+            &protocol_state,
+            vec![*point],
+            vec![key.nullifier()],
+            nonce,
+            common::sign_withdrawal(&program_id, &requester.pubkey(), nonce, &[key]),
+        )
+        .unwrap();
+        harness.process(&[request], &[&requester]).await.unwrap();
+    }
+    
+    let (queue_address, _) = WithdrawalQueue::find_address(&program_id);
+    let account = harness.context.banks_client.get_account(queue_address).await.unwrap().unwrap();
+    let queue = WithdrawalQueue::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(queue.requests, withdrawal_requests);
+    
+    let epoch = harness.clock().await.epoch;
+    let crank = |requests: &[Pubkey], requester_token: Pubkey| {
+        let payouts: Vec<QueuedPayout> = requests
+            .iter()
+            .map(|request| QueuedPayout {
+                request: *request,
+                requester_token,
+                treasury_token,
+                mint,
+                tip_token,
+            })
+            .collect();
+        instruction::process_withdrawal_queue(
+            &program_id,
+            &cranker.pubkey(),
+            &protocol_state,
+            &spl_token::id(),
+            &payouts,
+            epoch,
+        )
+        .unwrap()
+    };
+    
+    // Anyone may crank, but only the protocol's own queue, and never while withdrawals
+    // are paused
+    for index in [1, 2] {
+        let crank = crank(&withdrawal_requests, user_token);
+        let elsewhere = common::with_account(crank, index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[&cranker], FPPError::InvalidAccount).await;
+    }
+    harness.pause(ProtocolState::PAUSE_WITHDRAWALS).await;
+    harness.expect_unauthorized(&[crank(&withdrawal_requests, user_token)], &[&cranker]).await;
+    harness.pause(0).await;
+    
+    // Nothing has matured, so the first request goes behind the second rather than holding it up
+    harness.process(&[crank(&withdrawal_requests[..1], user_token)], &[&cranker]).await.unwrap();
+    let account = harness.context.banks_client.get_account(queue_address).await.unwrap().unwrap();
+    let queue = WithdrawalQueue::deserialize(&mut &account.data[..]).unwrap();
+    let reordered = vec![withdrawal_requests[1], withdrawal_requests[0]];
+    assert_eq!(queue.requests, reordered);
+    assert_eq!(harness.token_balance(&user_token).await, 0);
+    
+    // The first request was opened under a longer delay, so it unlocks after the second
+    harness
+        .update_account::<WithdrawalRequest>(&withdrawal_requests[0], |request| {
+            request.unlock_time += WITHDRAWAL_DELAY
+        })
+        .await;
+    harness.warp_seconds(WITHDRAWAL_DELAY).await;
+    // The cranker can't take the payout for themselves
+    let own_payout = crank(&reordered, tip_token);
+    harness.expect_error(&[own_payout], &[&cranker], FPPError::InvalidAccount).await;
+    harness.process(&[crank(&reordered, user_token)], &[&cranker]).await.unwrap();
+    
+    let banks = &mut harness.context.banks_client;
+    let mut completed = Vec::new();
+    for request in &withdrawal_requests {
+        let account = banks.get_account(*request).await.unwrap().unwrap();
+        completed.push(WithdrawalRequest::try_from_slice(&account.data).unwrap().completed);
+    }
+    assert_eq!(completed, vec![false, true]);
+    
+    let fee = POINT_VALUE / 1000;
+    let tip = WithdrawalQueue::tip(fee);
+    assert!(tip > 0);
+    assert_eq!(harness.token_balance(&user_token).await, POINT_VALUE - fee);
+    assert_eq!(harness.token_balance(&tip_token).await, tip);
+    assert_eq!(harness.token_balance(&treasury_token).await, fee - tip);
+    
+    let account = harness.context.banks_client.get_account(queue_address).await.unwrap().unwrap();
+    let queue = WithdrawalQueue::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(queue.requests, vec![withdrawal_requests[0]]);
+}
+
+#[tokio::test]
+async fn a_full_queue_leaves_new_requests_to_their_requester() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let keys = [SpendKey::new(3), SpendKey::new(4)];
+    let points: Vec<Pubkey> = keys
+        .iter()
+        .map(|key| common::add_point(&mut program_test, &program_id, &common::point(key, &mint)))
+        .collect();
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token =
+        common::add_token_account(&mut program_test, &mint, &treasury, 2 * POINT_VALUE);
+    let user_token = common::add_token_account(&mut program_test, &mint, &Pubkey::new_unique(), 0);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    for point in &points {
+        harness.set_point_creator(point, user).await;
+    }
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 2)
+        .await;
+    let withdrawal_requests: Vec<Pubkey> = (0..2)
+        .map(|nonce| pda::find_withdrawal_request(&user, nonce, &program_id).0)
+        .collect();
+    
+    // One slot left for the first request
+    let (queue_address, _) = WithdrawalQueue::find_address(&program_id);
+    let others: Vec<Pubkey> =
+        (1..WithdrawalQueue::MAX_ENTRIES).map(|_| Pubkey::new_unique()).collect();
+    let queue = WithdrawalQueue {
+        version: WithdrawalQueue::VERSION,
+        is_initialized: true,
+        requests: others.clone(),
+    };
+    harness.set_program_account(&queue_address, queue.try_to_vec().unwrap());
+    
+    for (nonce, (point, key)) in points.iter().zip(&keys).enumerate() {
+        let nonce = nonce as u64;
+        let request = instruction::request_withdrawal(
+            &program_id,
+            &user,
+            &protocol_state,
+            vec![*point],
+            vec![key.nullifier()],
+            nonce,
+            common::sign_withdrawal(&program_id, &user, nonce, &[key]),
+        )
+        .unwrap();
+        harness.process(&[request], &[]).await.unwrap();
+    }
+    let account = harness.context.banks_client.get_account(queue_address).await.unwrap().unwrap();
+    let queue = WithdrawalQueue::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(queue.requests.len(), WithdrawalQueue::MAX_ENTRIES);
+    assert_eq!(queue.requests.last(), Some(&withdrawal_requests[0]));
+    
+    // Both are the requester's to complete, and completing one frees its slot
+    harness.warp_seconds(WITHDRAWAL_DELAY).await;
+    let epoch = harness.clock().await.epoch;
+    for withdrawal_request in &withdrawal_requests {
+        let complete = instruction::complete_withdrawal(
+            &program_id,
+            &user,
+            &user_token,
+            &treasury_token,
+            &protocol_state,
+            withdrawal_request,
+            &mint,
+            &spl_token::id(),
+            false,
+            epoch,
+        )
+        .unwrap();
+        harness.process(&[complete], &[]).await.unwrap();
+    }
+    let account = harness.context.banks_client.get_account(queue_address).await.unwrap().unwrap();
+    let queue = WithdrawalQueue::deserialize(&mut &account.data[..]).unwrap();
+    assert_eq!(queue.requests, others);
+    
+    let fee = POINT_VALUE / 1000;
+    assert_eq!(harness.token_balance(&user_token).await, 2 * (POINT_VALUE - fee));
+}
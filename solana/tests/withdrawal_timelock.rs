@@ -0,0 +1,192 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{SpendKey, TestHarness};
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    pda,
+    state::{FloatingPoint, ProtocolState, WithdrawalRequest, POINT_VALUE, WITHDRAWAL_DELAY},
+};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn complete_withdrawal_waits_for_delay() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let key = SpendKey::new(3);
+    let point = common::add_point(&mut program_test, &program_id, &common::point(&key, &mint));
+    let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+    let treasury_token =
+        common::add_token_account(&mut program_test, &mint, &treasury, POINT_VALUE);
+    let user_token = common::add_token_account(&mut program_test, &mint, &Pubkey::new_unique(), 0);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    harness.set_point_creator(&point, user).await;
+    let (withdrawal_request, _) = pda::find_withdrawal_request(&user, 0, &program_id);
+    // The point was added directly rather than deposited, so count it here
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 1)
+        .await;
+    
+    let request = instruction::request_withdrawal(
+        &program_id,
+        &user,
+        &protocol_state,
+        vec![point],
+        vec![key.nullifier()],
+        0,
+        common::sign_withdrawal(&program_id, &user, 0, &[&key]),
+    )
+    .unwrap();
+    harness.process(&[request], &[]).await.unwrap();
+    
+    let epoch = harness.clock().await.epoch;
+    let complete = |user: &Pubkey| {
+        instruction::complete_withdrawal(
+            &program_id,
+            user,
+            &user_token,
+            &treasury_token,
+            &protocol_state,
+            &withdrawal_request,
+            &mint,
+            &spl_token::id(),
+            false,
+            epoch,
+        )
+        .unwrap()
+    };
+    
+    harness.warp_seconds(WITHDRAWAL_DELAY - 1).await;
+    harness.expect_error(&[complete(&user)], &[], FPPError::WithdrawalNotReady).await;
+    harness.warp_seconds(1).await;
+    
+    // Only the requester completes, and only from the protocol's own accounts
+    let stranger = Keypair::new();
+    harness.expect_unauthorized(&[complete(&stranger.pubkey())], &[&stranger]).await;
+    // Neither the treasury, protocol state, request nor treasury authority can be swapped out
+    for index in [2, 3, 4, 5] {
+        let elsewhere = common::with_account(complete(&user), index, Pubkey::new_unique());
+        harness.expect_error(&[elsewhere], &[], FPPError::InvalidAccount).await;
+    }
+    harness.pause(ProtocolState::PAUSE_WITHDRAWALS).await;
+    harness.expect_unauthorized(&[complete(&user)], &[]).await;
+    harness.pause(0).await;
+    
+    harness.process(&[complete(&user)], &[]).await.unwrap();
+    
+    let account = harness
+        .context
+        .banks_client
+        .get_account(withdrawal_request)
+        .await
+        .unwrap()
+        .unwrap();
+    let request = WithdrawalRequest::try_from_slice(&account.data).unwrap();
+    assert!(request.completed);
+    
+    // 10 bps fee stays in the treasury
+    let fee = POINT_VALUE / 1000;
+    assert_eq!(harness.token_balance(&user_token).await, POINT_VALUE - fee);
+    assert_eq!(harness.token_balance(&treasury_token).await, fee);
+}
+
+#[tokio::test]
+async fn only_the_creator_can_withdraw_a_point() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let owner = common::add_signer(&mut program_test);
+    let stranger = common::add_signer(&mut program_test);
+    let key = SpendKey::new(3);
+    let point = FloatingPoint {
+        creator: owner.pubkey(),
+        rent_payer: owner.pubkey(),
+        ..common::point(&key, &mint)
+    };
+    let point = common::add_point(&mut program_test, &program_id, &point);
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let protocol_state = harness.protocol_state();
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 1)
+        .await;
+    
+    let request = |requester: &Keypair| {
+        instruction::request_withdrawal(
+            &program_id,
+            &requester.pubkey(),
+            &protocol_state,
+            vec![point],
+            vec![key.nullifier()],
+            0,
+            common::sign_withdrawal(&program_id, &requester.pubkey(), 0, &[&key]),
+        )
+        .unwrap()
+    };
+    harness.expect_unauthorized(&[request(&stranger)], &[&stranger]).await;
+    
+    let elsewhere = common::with_account(request(&owner), 1, Pubkey::new_unique());
+    harness.expect_error(&[elsewhere], &[&owner], FPPError::InvalidAccount).await;
+    // The spend signature covers the request's address, so it can't be moved either
+    let elsewhere = common::with_account(request(&owner), 2, Pubkey::new_unique());
+    harness.expect_error(&[elsewhere], &[&owner], FPPError::InvalidRingSignature).await;
+    harness.pause(ProtocolState::PAUSE_WITHDRAWALS).await;
+    harness.expect_unauthorized(&[request(&owner)], &[&owner]).await;
+    harness.pause(0).await;
+    
+    harness.process(&[request(&owner)], &[&owner]).await.unwrap();
+    let account = harness.context.banks_client.get_account(point).await.unwrap().unwrap();
+    assert!(!FloatingPoint::try_from_slice(&account.data).unwrap().is_active);
+}
+
+#[tokio::test]
+async fn withdrawals_spend_the_points_key_images() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = common::program_test(program_id);
+    let mint = common::add_mint(&mut program_test, 6);
+    let key = SpendKey::new(3);
+    let point = common::add_point(&mut program_test, &program_id, &common::point(&key, &mint));
+    let mut harness = TestHarness::start_initialized(program_test, program_id, &mint).await;
+    let user = harness.payer();
+    let protocol_state = harness.protocol_state();
+    harness.set_point_creator(&point, user).await;
+    harness
+        .update_account::<ProtocolState>(&protocol_state, |state| state.total_points = 1)
+        .await;
+    
+    let request = |nullifier: [u8; 32], spend_signature: Vec<u8>| {
+        instruction::request_withdrawal(
+            &program_id,
+            &user,
+            &protocol_state,
+            vec![point],
+            vec![nullifier],
+            0,
+            spend_signature,
+        )
+        .unwrap()
+    };
+    let rejected = FPPError::InvalidRingSignature;
+    
+    // A nullifier of the caller's choosing, which would let the point be spent again
+    let signature = common::sign_withdrawal(&program_id, &user, 0, &[&key]);
+    harness.expect_error(&[request([7u8; 32], signature.clone())], &[], rejected).await;
+    
+    // A signature for another request can't be replayed
+    let other = common::sign_withdrawal(&program_id, &user, 1, &[&key]);
+    harness.expect_error(&[request(key.nullifier(), other)], &[], rejected).await;
+    
+    // Nor can another key sign for the point
+    let forged = common::sign_withdrawal(&program_id, &user, 0, &[&SpendKey::new(4)]);
+    harness.expect_error(&[request(SpendKey::new(4).nullifier(), forged)], &[], rejected).await;
+    
+    harness.process(&[request(key.nullifier(), signature)], &[]).await.unwrap();
+    let account = harness.context.banks_client.get_account(point).await.unwrap().unwrap();
+    assert!(!FloatingPoint::try_from_slice(&account.data).unwrap().is_active);
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use common::TestHarness;
+use floating_point_protocol_solana::{
+    error::FPPError,
+    instruction,
+    state::{ProtocolState, SupportedMintConfig, MAX_DENOMINATIONS, POINT_VALUE},
+};
+use solana_program_test::ProgramTest;
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+/// A protocol taking wSOL as a second mint, and a depositor holding 100 SOL and an
+/// empty wSOL account
+struct WrappedSol {
+    harness: TestHarness,
+    depositor: Keypair,
+    user_token: Pubkey,
+    treasury_token: Pubkey,
+}
+
+impl WrappedSol {
+    async fn start(configure: impl FnOnce(&mut ProgramTest)) -> Self {
+        let program_id = Pubkey::new_unique();
+        let mut program_test = common::program_test(program_id);
+        let usdt = common::add_mint(&mut program_test, 6);
+        let native_mint = common::add_native_mint(&mut program_test);
+        let (treasury, _) = ProtocolState::find_treasury_authority(&program_id);
+        let treasury_token = common::add_native_token_account(&mut program_test, &treasury);
+        let depositor = Keypair::new();
+        program_test.add_account(
+            depositor.pubkey(),
+            Account {
+                lamports: 100 * LAMPORTS_PER_SOL,
+                ..Account::default()
+            },
+        );
+        let user_token = common::add_native_token_account(&mut program_test, &depositor.pubkey());
+        configure(&mut program_test);
+        let mut harness = TestHarness::start_initialized(program_test, program_id, &usdt).await;
+        let authority = harness.payer();
+        
+        let mut denominations = [0u64; MAX_DENOMINATIONS];
+        denominations[0] = POINT_VALUE;
+        let config = SupportedMintConfig {
+            denominations,
+            deposit_fee_rate: 10,
+            withdrawal_fee_rate: 10,
+            max_deposit: 0,
+            deposit_cap: 0,
+        };
+        let add = instruction::add_supported_mint(
+            &program_id,
+            &authority,
+            &harness.protocol_state(),
+            &authority,
+            native_mint,
+            config,
+        )
+        .unwrap();
+        harness.process(&[add], &[]).await.unwrap();
+        Self {
+            harness,
+            depositor,
+            user_token,
+            treasury_token,
+        }
+    }
+    
+    async fn deposit(&mut self, treasury_token: Pubkey) -> Instruction {
+        let epoch = self.harness.clock().await.epoch;
+        instruction::deposit(
+            &self.harness.program_id,
+            &self.depositor.pubkey(),
+            &self.user_token,
+            &treasury_token,
+            &self.harness.protocol_state(),
+            &spl_token::native_mint::id(),
+            &spl_token::id(),
+            POINT_VALUE,
+            POINT_VALUE,
+            vec![[5u8; 32]],
+            vec![],
+            epoch,
+        )
+        .unwrap()
+    }
+}
+
+#[tokio::test]
+async fn deposit_wraps_lamports_into_the_wsol_account() {
+    let mut sol = WrappedSol::start(|_| {}).await;
+    
+    // The wSOL account starts empty; the deposit wraps the amount plus fee from lamports
+    let deposit = sol.deposit(sol.treasury_token).await;
+    sol.harness.process(&[deposit], &[&sol.depositor]).await.unwrap();
+    
+    // wSOL has 9 decimals, so a 10-unit point takes 10 SOL
+    let lamports = (POINT_VALUE + POINT_VALUE / 1000) * 1000;
+    assert_eq!(sol.harness.token_balance(&sol.treasury_token).await, lamports);
+    assert_eq!(sol.harness.token_balance(&sol.user_token).await, 0);
+    let depositor_account = sol
+        .harness
+        .context
+        .banks_client
+        .get_account(sol.depositor.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(depositor_account.lamports < 100 * LAMPORTS_PER_SOL - lamports);
+}
+
+#[tokio::test]
+async fn wrapped_deposits_only_pay_the_treasury_while_unpaused() {
+    let decoy_owner = Pubkey::new_unique();
+    let mut decoy = Pubkey::default();
+    let mut sol = WrappedSol::start(|program_test| {
+        decoy = common::add_native_token_account(program_test, &decoy_owner);
+    })
+    .await;
+    
+    // Any other wSOL account would take the lamports outside the treasury
+    let deposit = sol.deposit(decoy).await;
+    sol.harness.expect_error(&[deposit], &[&sol.depositor], FPPError::InvalidAccount).await;
+    
+    sol.harness.pause(ProtocolState::PAUSE_DEPOSITS).await;
+    let deposit = sol.deposit(sol.treasury_token).await;
+    sol.harness.expect_unauthorized(&[deposit], &[&sol.depositor]).await;
+}
@@ -0,0 +1,63 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use floating_point_protocol_solana::{
+    state::{CommitmentTree, ProtocolState},
+    zero_copy::ZeroCopy,
+};
+use solana_sdk::pubkey::Pubkey;
+
+/// Account-like buffer with the 8-byte alignment the runtime gives account data
+fn aligned(bytes: &[u8]) -> Vec<u64> {
+    let mut words = vec![0u64; bytes.len().div_ceil(8)];
+    bytemuck::cast_slice_mut::<u64, u8>(&mut words)[..bytes.len()].copy_from_slice(bytes);
+    words
+}
+
+#[test]
+fn memory_layout_matches_borsh() {
+    let mut state = ProtocolState::try_from_slice(&[0u8; ProtocolState::LEN]).unwrap();
+    state.total_points = 7;
+    state.denominations = ProtocolState::DEFAULT_DENOMINATIONS;
+    state.pending_config.effective_at = -3;
+    state.rate_limits.max_withdrawals = 9;
+    state.authority = Pubkey::new_unique();
+    state.withdrawal_fee_rate = 25;
+    state.is_initialized = true;
+    state.paused_flags = ProtocolState::PAUSE_WITHDRAWALS;
+    state.pending_unpause.paused_flags = ProtocolState::PAUSE_DEPOSITS;
+    
+    assert_eq!(state.try_to_vec().unwrap(), bytemuck::bytes_of(&state));
+    assert_eq!(ProtocolState::SIZE, ProtocolState::LEN);
+    assert_eq!(CommitmentTree::SIZE, CommitmentTree::LEN);
+}
+
+#[test]
+fn in_place_insert_matches_borsh_round_trip() {
+    let mut expected = CommitmentTree::new().unwrap();
+    let mut words = aligned(&expected.try_to_vec().unwrap());
+    let data = bytemuck::cast_slice_mut::<u64, u8>(&mut words);
+    
+    let leaf = [1u8; 32];
+    expected.insert(leaf).unwrap();
+    CommitmentTree::load_mut(data).unwrap().insert(leaf).unwrap();
+    
+    let stored = CommitmentTree::try_from_slice(&data[..CommitmentTree::LEN]).unwrap();
+    assert_eq!(stored.next_index, 1);
+    assert_eq!(stored.current_root(), expected.current_root());
+    assert_eq!(stored.filled_subtrees, expected.filled_subtrees);
+}
+
+#[test]
+fn load_rejects_invalid_flags_and_short_data() {
+    let tree = CommitmentTree::new().unwrap();
+    let mut bytes = tree.try_to_vec().unwrap();
+    let words = aligned(&bytes);
+    assert!(CommitmentTree::load(bytemuck::cast_slice(&words)).is_ok());
+    
+    // `is_initialized` sits after the generation, near the end of the layout
+    bytes[CommitmentTree::LEN - 4] = 2;
+    let words = aligned(&bytes);
+    assert!(CommitmentTree::load(bytemuck::cast_slice(&words)).is_err());
+    
+    let words = aligned(&bytes[..CommitmentTree::LEN - 8]);
+    assert!(CommitmentTree::load(bytemuck::cast_slice(&words)).is_err());
+}
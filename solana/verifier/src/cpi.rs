@@ -0,0 +1,72 @@
+//! Verification by CPI, for programs that rely on this one.
+//!
+//! `verifier_program` must be the account of this program (`crate::id()`);
+//! any other program could answer anything, so it is refused before the call
+//! and its return data ignored after.
+
+use borsh::BorshSerialize;
+use solana_program::{
+    account_info::AccountInfo,
+    instruction::Instruction,
+    program::{get_return_data, invoke},
+    program_error::ProgramError,
+};
+
+use crate::{
+    groth16::{Groth16Proof, Groth16VerifyingKey, ASSOCIATION_PUBLIC_INPUTS},
+    instruction::VerifierInstruction,
+};
+
+/// Whether `proof` verifies against `verifying_key` and `public_inputs`
+pub fn verify(
+    verifier_program: &AccountInfo,
+    verifying_key: &Groth16VerifyingKey,
+    proof: &Groth16Proof,
+    public_inputs: &[[u8; 32]; ASSOCIATION_PUBLIC_INPUTS],
+) -> Result<bool, ProgramError> {
+    invoke_verifier(
+        verifier_program,
+        &VerifierInstruction::VerifyGroth16 {
+            verifying_key: verifying_key.clone(),
+            proof: proof.clone(),
+            public_inputs: *public_inputs,
+        },
+    )
+}
+
+/// Whether every proof verifies, checked with one batched pairing
+pub fn verify_batch(
+    verifier_program: &AccountInfo,
+    verifying_key: &Groth16VerifyingKey,
+    proofs: &[Groth16Proof],
+    public_inputs: &[[[u8; 32]; ASSOCIATION_PUBLIC_INPUTS]],
+) -> Result<bool, ProgramError> {
+    invoke_verifier(
+        verifier_program,
+        &VerifierInstruction::VerifyGroth16Batch {
+            verifying_key: verifying_key.clone(),
+            proofs: proofs.to_vec(),
+            public_inputs: public_inputs.to_vec(),
+        },
+    )
+}
+
+fn invoke_verifier(
+    verifier_program: &AccountInfo,
+    instruction: &VerifierInstruction,
+) -> Result<bool, ProgramError> {
+    if *verifier_program.key != crate::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let instruction = Instruction {
+        program_id: crate::id(),
+        accounts: vec![],
+        data: instruction.try_to_vec()?,
+    };
+    invoke(&instruction, &[verifier_program.clone()])?;
+    
+    match get_return_data() {
+        Some((program_id, data)) if program_id == crate::id() => Ok(data == [1]),
+        _ => Ok(false),
+    }
+}
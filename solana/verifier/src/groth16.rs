@@ -0,0 +1,323 @@
+//! Groth16 verification over BN254 with the `alt_bn128` syscalls.
+//!
+//! Points use the EIP-197 big-endian encoding the syscalls expect: G1 as
+//! `x || y`, G2 as `x_im || x_re || y_im || y_re`. Proofs come straight from
+//! snarkjs/circom exports; `A` is negated here rather than by the prover.
+
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::{
+    alt_bn128::prelude::{alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing},
+    hash::hashv,
+};
+
+/// BN254 scalar field modulus, big-endian; public inputs must be below it
+const SCALAR_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+/// BN254 base field modulus, big-endian
+const BASE_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+/// Public inputs of the association set circuit
+pub const ASSOCIATION_PUBLIC_INPUTS: usize = 2;
+
+/// Most proofs `verify_batch` takes; four proofs already fill most of a transaction
+pub const MAX_BATCH_PROOFS: usize = 4;
+
+/// Domain separator for the batch's random weights
+const BATCH_DOMAIN: &[u8] = b"fpp-groth16-batch";
+
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq, Eq)]
+pub struct Groth16Proof {
+    pub a: [u8; 64],
+    pub b: [u8; 128],
+    pub c: [u8; 64],
+}
+
+/// Verifying key of a circuit with `ASSOCIATION_PUBLIC_INPUTS` public inputs
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq, Eq)]
+pub struct Groth16VerifyingKey {
+    pub alpha_g1: [u8; 64],
+    pub beta_g2: [u8; 128],
+    pub gamma_g2: [u8; 128],
+    pub delta_g2: [u8; 128],
+    /// One point per public input, after the constant term
+    pub ic: [[u8; 64]; ASSOCIATION_PUBLIC_INPUTS + 1],
+}
+
+impl Groth16VerifyingKey {
+    pub const LEN: usize = 64 + 128 * 3 + 64 * (ASSOCIATION_PUBLIC_INPUTS + 1);
+}
+
+/// Check `proof` against `public_inputs` (big-endian scalar field elements)
+pub fn verify(
+    vk: &Groth16VerifyingKey,
+    proof: &Groth16Proof,
+    public_inputs: &[[u8; 32]; ASSOCIATION_PUBLIC_INPUTS],
+) -> bool {
+    if public_inputs.iter().any(|input| !is_field_element(input)) {
+        return false;
+    }
+    check_pairing(vk, proof, public_inputs).unwrap_or(false)
+}
+
+/// Check several proofs against one verifying key with a single pairing
+///
+/// Each proof's equation is raised to a random weight and the results multiplied, so the
+/// `alpha`, `vk_x` and `C` terms share their pairings: `n + 3` pairings rather than `4n`.
+/// The weights hash every proof and input in the batch, so a prover can't pick proofs
+/// whose errors cancel out; the batch passes only if each proof would on its own.
+pub fn verify_batch(
+    vk: &Groth16VerifyingKey,
+    proofs: &[Groth16Proof],
+    public_inputs: &[[[u8; 32]; ASSOCIATION_PUBLIC_INPUTS]],
+) -> bool {
+    if proofs.is_empty()
+        || proofs.len() > MAX_BATCH_PROOFS
+        || proofs.len() != public_inputs.len()
+        || public_inputs.iter().flatten().any(|input| !is_field_element(input))
+    {
+        return false;
+    }
+    check_batch_pairing(vk, proofs, public_inputs).unwrap_or(false)
+}
+
+// e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1,
+// with vk_x = IC_0 + sum(input_i * IC_{i+1})
+fn check_pairing(
+    vk: &Groth16VerifyingKey,
+    proof: &Groth16Proof,
+    public_inputs: &[[u8; 32]],
+) -> Option<bool> {
+    let vk_x = prepare_inputs(vk, public_inputs)?;
+    let neg_a = negate_g1(&proof.a)?;
+    
+    let pairing_input = [
+        &neg_a[..],
+        &proof.b[..],
+        &vk.alpha_g1[..],
+        &vk.beta_g2[..],
+        &vk_x[..],
+        &vk.gamma_g2[..],
+        &proof.c[..],
+        &vk.delta_g2[..],
+    ]
+    .concat();
+    is_identity(&pairing_input)
+}
+
+// prod(e(-r_i * A_i, B_i)) * e(sum(r_i) * alpha, beta) * e(sum(r_i * vk_x_i), gamma)
+//     * e(sum(r_i * C_i), delta) == 1
+fn check_batch_pairing(
+    vk: &Groth16VerifyingKey,
+    proofs: &[Groth16Proof],
+    public_inputs: &[[[u8; 32]; ASSOCIATION_PUBLIC_INPUTS]],
+) -> Option<bool> {
+    let mut transcript: Vec<&[u8]> = vec![BATCH_DOMAIN];
+    for (proof, inputs) in proofs.iter().zip(public_inputs.iter()) {
+        transcript.extend_from_slice(&[&proof.a[..], &proof.b[..], &proof.c[..]]);
+        transcript.extend(inputs.iter().map(|input| &input[..]));
+    }
+    let seed = hashv(&transcript).to_bytes();
+    
+    // The point at infinity is all zeroes in the syscalls' encoding
+    let mut alpha = [0u8; 64];
+    let mut vk_x = [0u8; 64];
+    let mut c = [0u8; 64];
+    let mut pairing_input = Vec::with_capacity((proofs.len() + 3) * (64 + 128));
+    for (i, (proof, inputs)) in proofs.iter().zip(public_inputs.iter()).enumerate() {
+        let index = (i as u32).to_le_bytes();
+        let weight = hash_to_field(&hashv(&[BATCH_DOMAIN, &seed, &index]).to_bytes());
+        
+        let neg_a = negate_g1(&g1_mul(&proof.a, &weight)?)?;
+        pairing_input.extend_from_slice(&neg_a);
+        pairing_input.extend_from_slice(&proof.b);
+        
+        alpha = g1_add(&alpha, &g1_mul(&vk.alpha_g1, &weight)?)?;
+        vk_x = g1_add(&vk_x, &g1_mul(&prepare_inputs(vk, inputs)?, &weight)?)?;
+        c = g1_add(&c, &g1_mul(&proof.c, &weight)?)?;
+    }
+    for (g1, g2) in [(&alpha, &vk.beta_g2), (&vk_x, &vk.gamma_g2), (&c, &vk.delta_g2)] {
+        pairing_input.extend_from_slice(g1);
+        pairing_input.extend_from_slice(g2);
+    }
+    is_identity(&pairing_input)
+}
+
+// IC_0 + sum(input_i * IC_{i+1})
+fn prepare_inputs(vk: &Groth16VerifyingKey, public_inputs: &[[u8; 32]]) -> Option<[u8; 64]> {
+    let mut vk_x = vk.ic[0];
+    for (input, ic) in public_inputs.iter().zip(vk.ic[1..].iter()) {
+        let term = g1_mul(ic, input)?;
+        vk_x = g1_add(&vk_x, &term)?;
+    }
+    Some(vk_x)
+}
+
+// Whether the product of the pairings in `pairing_input` is 1
+fn is_identity(pairing_input: &[u8]) -> Option<bool> {
+    let result = alt_bn128_pairing(pairing_input).ok()?;
+    let mut one = [0u8; 32];
+    one[31] = 1;
+    Some(result == one)
+}
+
+/// Whether `bytes` (big-endian) is a canonical scalar field element
+pub fn is_field_element(bytes: &[u8; 32]) -> bool {
+    *bytes < SCALAR_FIELD_MODULUS
+}
+
+/// Reduce a 32-byte hash to a scalar field element by clearing its top bits
+pub fn hash_to_field(hash: &[u8; 32]) -> [u8; 32] {
+    let mut element = *hash;
+    element[0] &= 0x1f;
+    element
+}
+
+// -(x, y) = (x, q - y); the point at infinity is its own negation
+fn negate_g1(point: &[u8; 64]) -> Option<[u8; 64]> {
+    let mut negated = *point;
+    let y: [u8; 32] = point[32..].try_into().ok()?;
+    if y == [0u8; 32] {
+        return Some(negated);
+    }
+    if y >= BASE_FIELD_MODULUS {
+        return None;
+    }
+    
+    let mut borrow = 0i16;
+    for i in (0..32).rev() {
+        let mut digit = BASE_FIELD_MODULUS[i] as i16 - y[i] as i16 - borrow;
+        borrow = (digit < 0) as i16;
+        if digit < 0 {
+            digit += 256;
+        }
+        negated[32 + i] = digit as u8;
+    }
+    Some(negated)
+}
+
+fn g1_add(p: &[u8; 64], q: &[u8; 64]) -> Option<[u8; 64]> {
+    alt_bn128_addition(&[&p[..], &q[..]].concat()).ok()?.try_into().ok()
+}
+
+fn g1_mul(p: &[u8; 64], scalar: &[u8; 32]) -> Option<[u8; 64]> {
+    alt_bn128_multiplication(&[&p[..], &scalar[..]].concat()).ok()?.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// BN254 G1 generator `(1, 2)`
+    fn g1() -> [u8; 64] {
+        let mut point = [0u8; 64];
+        point[31] = 1;
+        point[63] = 2;
+        point
+    }
+    
+    /// BN254 G2 generator, as in EIP-197
+    fn g2() -> [u8; 128] {
+        let hex = concat!(
+            "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+            "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+            "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+            "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+        );
+        let mut point = [0u8; 128];
+        for (i, byte) in point.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        point
+    }
+    
+    fn scalar(value: u64) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[24..].copy_from_slice(&value.to_be_bytes());
+        bytes
+    }
+    
+    /// A key whose every point is a generator, so the pairing check reduces to
+    /// `-a + alpha + vk_x + c == 0` over multiples of the G1 generator
+    fn verifying_key() -> Groth16VerifyingKey {
+        Groth16VerifyingKey {
+            alpha_g1: g1(),
+            beta_g2: g2(),
+            gamma_g2: g2(),
+            delta_g2: g2(),
+            ic: [g1(); ASSOCIATION_PUBLIC_INPUTS + 1],
+        }
+    }
+    
+    /// The proof satisfying `verifying_key` for the inputs `x` and `y`
+    fn proof(x: u64, y: u64) -> (Groth16Proof, [[u8; 32]; ASSOCIATION_PUBLIC_INPUTS]) {
+        let proof = Groth16Proof {
+            a: g1_mul(&g1(), &scalar(3 + x + y)).unwrap(),
+            b: g2(),
+            c: g1(),
+        };
+        (proof, [scalar(x), scalar(y)])
+    }
+    
+    #[test]
+    fn proofs_verify_only_against_their_inputs() {
+        let vk = verifying_key();
+        let (proof, inputs) = proof(4, 9);
+        assert!(verify(&vk, &proof, &inputs));
+        assert!(!verify(&vk, &proof, &[scalar(4), scalar(10)]));
+        assert!(!verify(&vk, &proof, &[inputs[1], inputs[0]]));
+        // Inputs must be reduced, or one proof would verify for several encodings
+        assert!(!verify(&vk, &proof, &[inputs[0], SCALAR_FIELD_MODULUS]));
+        
+        let mut bad_point = proof.clone();
+        bad_point.a[63] ^= 1;
+        assert!(!verify(&vk, &bad_point, &inputs));
+    }
+    
+    #[test]
+    fn one_bad_proof_fails_the_batch() {
+        let vk = verifying_key();
+        let (proofs, inputs): (Vec<_>, Vec<_>) = [(4, 9), (1, 2), (7, 7)]
+            .into_iter()
+            .map(|(x, y)| proof(x, y))
+            .unzip();
+        assert!(verify_batch(&vk, &proofs, &inputs));
+        assert!(verify_batch(&vk, &proofs[..1], &inputs[..1]));
+        
+        let mut swapped = inputs.clone();
+        swapped.swap(0, 1);
+        assert!(!verify_batch(&vk, &proofs, &swapped));
+        let mut forged = proofs.clone();
+        forged[2].c = g1_mul(&g1(), &scalar(2)).unwrap();
+        assert!(!verify_batch(&vk, &forged, &inputs));
+        
+        assert!(!verify_batch(&vk, &[], &[]));
+        assert!(!verify_batch(&vk, &proofs, &inputs[..2]));
+        let (single, input) = proof(1, 1);
+        let oversized = vec![single; MAX_BATCH_PROOFS + 1];
+        assert!(!verify_batch(&vk, &oversized, &vec![input; MAX_BATCH_PROOFS + 1]));
+    }
+    
+    #[test]
+    fn negation_cancels_a_point() {
+        let negated = negate_g1(&g1()).unwrap();
+        assert_eq!(g1_add(&g1(), &negated), Some([0u8; 64]));
+        assert_eq!(negate_g1(&[0u8; 64]), Some([0u8; 64]));
+        
+        let mut out_of_field = g1();
+        out_of_field[32..].copy_from_slice(&BASE_FIELD_MODULUS);
+        assert_eq!(negate_g1(&out_of_field), None);
+    }
+    
+    #[test]
+    fn hashes_reduce_to_field_elements() {
+        assert!(is_field_element(&hash_to_field(&[0xff; 32])));
+        assert!(!is_field_element(&SCALAR_FIELD_MODULUS));
+        assert!(is_field_element(&scalar(u64::MAX)));
+    }
+}
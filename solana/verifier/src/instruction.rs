@@ -0,0 +1,65 @@
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use solana_program::{instruction::Instruction, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::groth16::{Groth16Proof, Groth16VerifyingKey, ASSOCIATION_PUBLIC_INPUTS};
+
+/// Instructions of the verifier program; none take accounts
+#[derive(BorshSerialize, BorshDeserialize, BorshSchema, Debug, Clone, PartialEq, Eq)]
+pub enum VerifierInstruction {
+    /// Check one proof; returns `[1]` if it verifies
+    VerifyGroth16 {
+        verifying_key: Groth16VerifyingKey,
+        proof: Groth16Proof,
+        public_inputs: [[u8; 32]; ASSOCIATION_PUBLIC_INPUTS],
+    },
+    
+    /// Check up to `groth16::MAX_BATCH_PROOFS` proofs of one circuit with a single pairing;
+    /// returns `[1]` only if every proof verifies
+    VerifyGroth16Batch {
+        verifying_key: Groth16VerifyingKey,
+        proofs: Vec<Groth16Proof>,
+        public_inputs: Vec<[[u8; 32]; ASSOCIATION_PUBLIC_INPUTS]>,
+    },
+}
+
+/// Creates a `VerifyGroth16` instruction
+pub fn verify_groth16(
+    program_id: &Pubkey,
+    verifying_key: Groth16VerifyingKey,
+    proof: Groth16Proof,
+    public_inputs: [[u8; 32]; ASSOCIATION_PUBLIC_INPUTS],
+) -> Result<Instruction, ProgramError> {
+    let data = VerifierInstruction::VerifyGroth16 {
+        verifying_key,
+        proof,
+        public_inputs,
+    }
+    .try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts: vec![],
+        data,
+    })
+}
+
+/// Creates a `VerifyGroth16Batch` instruction
+pub fn verify_groth16_batch(
+    program_id: &Pubkey,
+    verifying_key: Groth16VerifyingKey,
+    proofs: Vec<Groth16Proof>,
+    public_inputs: Vec<[[u8; 32]; ASSOCIATION_PUBLIC_INPUTS]>,
+) -> Result<Instruction, ProgramError> {
+    let data = VerifierInstruction::VerifyGroth16Batch {
+        verifying_key,
+        proofs,
+        public_inputs,
+    }
+    .try_to_vec()?;
+    
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts: vec![],
+        data,
+    })
+}
@@ -0,0 +1,32 @@
+//! Groth16 verifier program for the Floating Point Protocol.
+//!
+//! Holds no state and no funds: each instruction checks one proof, or a
+//! batch, against the verifying key it is given and reports the verdict as
+//! return data: `[1]` when valid, and empty otherwise (the runtime drops the
+//! trailing zero of `[0]`). A rejected proof is not an error, so callers can
+//! fail with their own error code rather than this program's. Malformed
+//! instruction data is.
+//!
+//! Keeping verification here lets circuits and the pairing code be upgraded
+//! without touching the program that holds deposits; callers only pin this
+//! program's id. Use `cpi::verify` and `cpi::verify_batch` from a program.
+
+use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey};
+
+pub mod cpi;
+pub mod groth16;
+pub mod instruction;
+pub mod processor;
+
+solana_program::declare_id!("23jFDibGsxs1KWxFLPpUMM9tzPcLZUiJzJu8xnrPLXnf");
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    processor::process_instruction(program_id, accounts, instruction_data)
+}
@@ -0,0 +1,39 @@
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program::set_return_data,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{groth16, instruction::VerifierInstruction};
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let instruction = VerifierInstruction::try_from_slice(instruction_data)
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    
+    let valid = match instruction {
+        VerifierInstruction::VerifyGroth16 {
+            verifying_key,
+            proof,
+            public_inputs,
+        } => groth16::verify(&verifying_key, &proof, &public_inputs),
+        VerifierInstruction::VerifyGroth16Batch {
+            verifying_key,
+            proofs,
+            public_inputs,
+        } => groth16::verify_batch(&verifying_key, &proofs, &public_inputs),
+    };
+    
+    if !valid {
+        msg!("Proof rejected");
+    }
+    set_return_data(&[valid as u8]);
+    Ok(())
+}
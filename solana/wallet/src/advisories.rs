@@ -0,0 +1,74 @@
+//! Security advisories from the guardian's on-chain incident log.
+//!
+//! Wallets should call `fetch_active` before building a transaction and put
+//! anything it returns in front of the user, most severe first.
+
+#[cfg(feature = "native")]
+use borsh::BorshDeserialize;
+#[cfg(feature = "native")]
+use floating_point_protocol_solana::state::ProtocolState;
+use floating_point_protocol_solana::state::{SecurityEvent, SecuritySeverity};
+#[cfg(feature = "native")]
+use solana_client::rpc_client::RpcClient;
+#[cfg(feature = "native")]
+use solana_sdk::pubkey::Pubkey;
+
+#[cfg(feature = "native")]
+use crate::error::WalletError;
+
+/// Most accounts `getMultipleAccounts` returns per call
+#[cfg(feature = "native")]
+const RPC_BATCH_SIZE: usize = 100;
+
+/// Unresolved security events, sorted most severe and most recent first
+#[cfg(feature = "native")]
+pub fn fetch_active(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    protocol_state: &Pubkey,
+) -> Result<Vec<SecurityEvent>, WalletError> {
+    let data = rpc
+        .get_account_data(protocol_state)
+        .map_err(|e| WalletError::Rpc(e.to_string()))?;
+    let state = ProtocolState::try_from_slice(&data)
+        .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+    
+    let addresses: Vec<Pubkey> = (0..state.security_event_count)
+        .map(|index| SecurityEvent::find_address(index, program_id).0)
+        .collect();
+    let mut events = Vec::new();
+    for chunk in addresses.chunks(RPC_BATCH_SIZE) {
+        let accounts = rpc
+            .get_multiple_accounts(chunk)
+            .map_err(|e| WalletError::Rpc(e.to_string()))?;
+        for account in accounts.into_iter().flatten() {
+            if account.owner != *program_id {
+                continue;
+            }
+            let event = SecurityEvent::try_from_slice(&account.data)
+                .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+            if event.is_active() {
+                events.push(event);
+            }
+        }
+    }
+    
+    sort_by_priority(&mut events);
+    Ok(events)
+}
+
+/// Order events the way they should be shown to users
+pub fn sort_by_priority(events: &mut [SecurityEvent]) {
+    events.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then(b.recorded_at.cmp(&a.recorded_at))
+    });
+}
+
+/// Whether any event is severe enough that the user should not transact
+pub fn has_critical(events: &[SecurityEvent]) -> bool {
+    events
+        .iter()
+        .any(|event| event.is_active() && event.severity == SecuritySeverity::Critical)
+}
@@ -0,0 +1,180 @@
+//! Encrypted contact book anchored on-chain.
+//!
+//! Saved recipients are encrypted to a key derived from the wallet secret and
+//! stored in the owner's `ContactBook` PDA, so they survive device loss.
+//! Updates are read-modify-write against the book's revision: the program
+//! rejects a write based on a stale read, and `update` retries on top of the
+//! newer book instead of overwriting it.
+
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+#[cfg(feature = "native")]
+use floating_point_protocol_solana::instruction;
+use floating_point_protocol_solana::state::ContactBook;
+#[cfg(feature = "native")]
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::hash::hashv;
+#[cfg(feature = "native")]
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+use crate::{error::WalletError, stealth::StealthAddress};
+
+const CONTACTS_FORMAT_VERSION: u8 = 1;
+const NONCE_LEN: usize = 24;
+
+/// Attempts before giving up on a book that keeps changing underneath us
+#[cfg(feature = "native")]
+const MAX_UPDATE_ATTEMPTS: usize = 3;
+
+/// Saved recipients by label
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContactList {
+    entries: BTreeMap<String, String>,
+}
+
+impl ContactList {
+    pub fn insert(&mut self, label: impl Into<String>, address: &StealthAddress) {
+        self.entries.insert(label.into(), address.to_string());
+    }
+    
+    pub fn remove(&mut self, label: &str) -> bool {
+        self.entries.remove(label).is_some()
+    }
+    
+    pub fn get(&self, label: &str) -> Result<Option<StealthAddress>, WalletError> {
+        self.entries
+            .get(label)
+            .map(|address| address.parse())
+            .transpose()
+    }
+    
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+}
+
+/// Symmetric key protecting the contact book, derived from the wallet secret
+pub struct ContactKey {
+    key: [u8; 32],
+}
+
+impl ContactKey {
+    pub fn derive(wallet_secret: &[u8; 32]) -> Self {
+        Self {
+            key: hashv(&[b"fpp-contact-book-key-v1", wallet_secret]).to_bytes(),
+        }
+    }
+    
+    pub fn encrypt(&self, contacts: &ContactList) -> Result<Vec<u8>, WalletError> {
+        let plaintext = contacts
+            .try_to_vec()
+            .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+        let cipher = XChaCha20Poly1305::new(&self.key.into());
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_ref())
+            .map_err(|_| WalletError::Crypto("contact book encryption failed".into()))?;
+        
+        let mut blob = Vec::with_capacity(1 + NONCE_LEN + ciphertext.len());
+        blob.push(CONTACTS_FORMAT_VERSION);
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&ciphertext);
+        if blob.len() > ContactBook::MAX_CIPHERTEXT_LEN {
+            return Err(WalletError::InvalidEncoding("contact book is too large".into()));
+        }
+        Ok(blob)
+    }
+    
+    pub fn decrypt(&self, blob: &[u8]) -> Result<ContactList, WalletError> {
+        if blob.len() < 1 + NONCE_LEN || blob[0] != CONTACTS_FORMAT_VERSION {
+            return Err(WalletError::InvalidEncoding("unsupported contact book".into()));
+        }
+        let nonce = XNonce::from_slice(&blob[1..1 + NONCE_LEN]);
+        let cipher = XChaCha20Poly1305::new(&self.key.into());
+        let plaintext = cipher
+            .decrypt(nonce, &blob[1 + NONCE_LEN..])
+            .map_err(|_| WalletError::Crypto("contact book failed authentication".into()))?;
+        ContactList::try_from_slice(&plaintext)
+            .map_err(|e| WalletError::InvalidEncoding(e.to_string()))
+    }
+}
+
+/// Current contacts and the revision to name in the next update; an empty
+/// list at revision 0 if the owner has no book yet
+#[cfg(feature = "native")]
+pub fn fetch(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    key: &ContactKey,
+) -> Result<(ContactList, u64), WalletError> {
+    let (address, _) = ContactBook::find_address(owner, program_id);
+    let account = rpc
+        .get_account_with_commitment(&address, rpc.commitment())
+        .map_err(|e| WalletError::Rpc(e.to_string()))?
+        .value;
+    let account = match account {
+        Some(account) if account.owner == *program_id => account,
+        _ => return Ok((ContactList::default(), 0)),
+    };
+    let book = ContactBook::deserialize(&mut &account.data[..])
+        .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+    if book.ciphertext.is_empty() {
+        return Ok((ContactList::default(), book.revision));
+    }
+    Ok((key.decrypt(&book.ciphertext)?, book.revision))
+}
+
+/// Apply `edit` to the latest contact book and write it back atomically
+#[cfg(feature = "native")]
+pub fn update(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    owner: &Keypair,
+    key: &ContactKey,
+    mut edit: impl FnMut(&mut ContactList),
+) -> Result<ContactList, WalletError> {
+    let mut last_error = None;
+    for _ in 0..MAX_UPDATE_ATTEMPTS {
+        let (mut contacts, revision) = fetch(rpc, program_id, &owner.pubkey(), key)?;
+        edit(&mut contacts);
+        
+        let ix = instruction::update_contact_book(
+            program_id,
+            &owner.pubkey(),
+            revision,
+            key.encrypt(&contacts)?,
+        )
+        .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+        let blockhash = rpc
+            .get_latest_blockhash()
+            .map_err(|e| WalletError::Rpc(e.to_string()))?;
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&owner.pubkey()),
+            &[owner],
+            blockhash,
+        );
+        match rpc.send_and_confirm_transaction(&tx) {
+            Ok(_) => return Ok(contacts),
+            Err(e) => {
+                // Retry only if another device got there first
+                let (_, current) = fetch(rpc, program_id, &owner.pubkey(), key)?;
+                if current == revision {
+                    return Err(WalletError::Rpc(e.to_string()));
+                }
+                last_error = Some(e);
+            }
+        }
+    }
+    Err(WalletError::Rpc(last_error.map(|e| e.to_string()).unwrap_or_default()))
+}
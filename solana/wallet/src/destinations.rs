@@ -0,0 +1,137 @@
+//! Withdrawal destination rotation.
+//!
+//! Withdrawing twice to the same token account links both withdrawals, so
+//! the rotator hands out a fresh associated token account per withdrawal,
+//! owned by keys derived along `m/44'/501'/<account>'/<index>'`. Every
+//! destination used is recorded in a `DestinationLedger`, including external
+//! ones, and a reuse is flagged or refused depending on the `ReusePolicy`.
+
+use std::collections::BTreeMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::{
+    derivation_path::DerivationPath,
+    pubkey::Pubkey,
+    signature::{keypair_from_seed_and_derivation_path, Keypair},
+    signer::Signer,
+};
+use spl_associated_token_account::get_associated_token_address;
+
+use crate::error::WalletError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReusePolicy {
+    /// Report the reuse and let the caller decide
+    Warn,
+    /// Reject any destination that has received a withdrawal before
+    Refuse,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Destination {
+    pub index: u32,
+    pub owner: Pubkey,
+    pub token_account: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DestinationCheck {
+    Fresh,
+    /// Already received `previous_withdrawals` withdrawals
+    Reused { previous_withdrawals: u32 },
+}
+
+/// Persistent record of handed-out indices and used destinations
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct DestinationLedger {
+    pub next_index: u32,
+    /// Withdrawals received per destination token account
+    pub used: BTreeMap<Pubkey, u32>,
+}
+
+pub struct DestinationRotator {
+    seed: Vec<u8>,
+    account: u32,
+    mint: Pubkey,
+    policy: ReusePolicy,
+    ledger: DestinationLedger,
+}
+
+impl DestinationRotator {
+    pub fn new(seed: &[u8], account: u32, mint: Pubkey, policy: ReusePolicy) -> Self {
+        Self::with_ledger(seed, account, mint, policy, DestinationLedger::default())
+    }
+    
+    /// Resume from a previously persisted ledger
+    pub fn with_ledger(
+        seed: &[u8],
+        account: u32,
+        mint: Pubkey,
+        policy: ReusePolicy,
+        ledger: DestinationLedger,
+    ) -> Self {
+        Self {
+            seed: seed.to_vec(),
+            account,
+            mint,
+            policy,
+            ledger,
+        }
+    }
+    
+    pub fn ledger(&self) -> &DestinationLedger {
+        &self.ledger
+    }
+    
+    /// Owner key for the destination at `index`
+    pub fn keypair(&self, index: u32) -> Result<Keypair, WalletError> {
+        let path = DerivationPath::new_bip44(Some(self.account), Some(index));
+        keypair_from_seed_and_derivation_path(&self.seed, Some(path))
+            .map_err(|e| WalletError::Crypto(e.to_string()))
+    }
+    
+    pub fn destination(&self, index: u32) -> Result<Destination, WalletError> {
+        let owner = self.keypair(index)?.pubkey();
+        Ok(Destination {
+            index,
+            owner,
+            token_account: get_associated_token_address(&owner, &self.mint),
+        })
+    }
+    
+    /// Reserve the next never-used destination
+    pub fn fresh(&mut self) -> Result<Destination, WalletError> {
+        loop {
+            let destination = self.destination(self.ledger.next_index)?;
+            self.ledger.next_index = self
+                .ledger
+                .next_index
+                .checked_add(1)
+                .ok_or_else(|| WalletError::Crypto("derivation index exhausted".to_string()))?;
+            if !self.ledger.used.contains_key(&destination.token_account) {
+                return Ok(destination);
+            }
+        }
+    }
+    
+    /// Check a destination token account before withdrawing to it
+    pub fn check(&self, token_account: &Pubkey) -> Result<DestinationCheck, WalletError> {
+        match self.ledger.used.get(token_account) {
+            None => Ok(DestinationCheck::Fresh),
+            Some(_) if self.policy == ReusePolicy::Refuse => {
+                Err(WalletError::DestinationReused(token_account.to_string()))
+            }
+            Some(&previous_withdrawals) => Ok(DestinationCheck::Reused { previous_withdrawals }),
+        }
+    }
+    
+    /// Record a withdrawal to `token_account`, enforcing the policy
+    pub fn record_withdrawal(
+        &mut self,
+        token_account: &Pubkey,
+    ) -> Result<DestinationCheck, WalletError> {
+        let check = self.check(token_account)?;
+        *self.ledger.used.entry(*token_account).or_insert(0) += 1;
+        Ok(check)
+    }
+}
@@ -0,0 +1,19 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum WalletError {
+    #[error("RPC Error: {0}")]
+    Rpc(String),
+    
+    #[error("Invalid Encoding: {0}")]
+    InvalidEncoding(String),
+    
+    #[error("Crypto Error: {0}")]
+    Crypto(String),
+    
+    #[error("Storage Error: {0}")]
+    Storage(String),
+    
+    #[error("Destination Reused: {0}")]
+    DestinationReused(String),
+}
@@ -0,0 +1,23 @@
+//! Floating Point Protocol wallet SDK.
+//!
+//! Key derivation, note encryption, detection and sync build anywhere,
+//! including `wasm32-unknown-unknown`. RPC access and file storage sit behind
+//! the default `native` feature; the `wasm` feature adds browser entry points.
+
+pub mod advisories;
+pub mod contacts;
+pub mod destinations;
+pub mod error;
+pub mod note;
+pub mod scan;
+pub mod spend;
+pub mod spent;
+pub mod stealth;
+pub mod sweep;
+pub mod sync;
+#[cfg(feature = "native")]
+pub mod trees;
+pub mod vault;
+pub mod viewing;
+#[cfg(feature = "wasm")]
+pub mod wasm;
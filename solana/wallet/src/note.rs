@@ -0,0 +1,68 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use curve25519_dalek::{constants::RISTRETTO_BASEPOINT_POINT, scalar::Scalar};
+use floating_point_protocol_solana::crypto::{
+    opening::{self, OpeningProof},
+    poseidon,
+};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{error::WalletError, viewing};
+
+/// A floating point owned by this wallet
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    /// On-chain commitment, doubling as the ring public key
+    pub commitment: [u8; 32],
+    /// Secret key for the commitment
+    pub secret: [u8; 32],
+    /// Key image published when the note is spent
+    pub nullifier: [u8; 32],
+    pub mass: u64,
+    /// Position in the commitment tree, once known
+    pub leaf_index: Option<u64>,
+    /// Generation of the commitment tree holding the leaf, alongside `leaf_index`
+    pub tree_generation: u32,
+    pub created_epoch: u64,
+}
+
+impl Note {
+    /// Fresh note of `mass` under a random secret, ready to deposit
+    ///
+    /// Deposited commitments are also commitment tree leaves, so secrets are
+    /// redrawn until the commitment is a BN254 field element (about one in five).
+    pub fn generate(mass: u64, created_epoch: u64) -> Self {
+        loop {
+            let secret = viewing::random_scalar();
+            let commitment = (secret * RISTRETTO_BASEPOINT_POINT).compress().to_bytes();
+            if !poseidon::is_field_element(&commitment) {
+                continue;
+            }
+            if let Some(note) = viewing::note_from_secret(secret, commitment, mass, created_epoch) {
+                return note;
+            }
+        }
+    }
+    
+    /// Note of a known `secret`, if it opens `commitment`
+    pub fn from_secret(commitment: [u8; 32], secret: [u8; 32], mass: u64) -> Option<Self> {
+        let secret = Scalar::from_canonical_bytes(secret)?;
+        viewing::note_from_secret(secret, commitment, mass, 0)
+    }
+    
+    /// Proof that `depositor` knows this note's secret, for a deposit of `amount`
+    pub fn opening_proof(
+        &self,
+        amount: u64,
+        depositor: &Pubkey,
+    ) -> Result<OpeningProof, WalletError> {
+        let secret = Scalar::from_canonical_bytes(self.secret)
+            .ok_or_else(|| WalletError::Crypto("invalid note secret".into()))?;
+        let nonce = viewing::random_scalar();
+        let nonce_commitment = (nonce * RISTRETTO_BASEPOINT_POINT).compress().to_bytes();
+        let challenge = opening::challenge(&self.commitment, amount, depositor, &nonce_commitment);
+        Ok(OpeningProof {
+            nonce_commitment,
+            response: (nonce + challenge * secret).to_bytes(),
+        })
+    }
+}
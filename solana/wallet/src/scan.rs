@@ -0,0 +1,426 @@
+//! Chain scanning and the local note database.
+//!
+//! Two sources are read over RPC. `EncryptedNote` accounts hold every output
+//! memo still on chain. The program's transaction history carries the same
+//! memos in `PrivacyPayment` data, and its events give the leaf index of
+//! every inserted commitment and every published nullifier, so walking it
+//! also fills in `leaf_index` and notices spends without a lookup per note.
+//! `NoteDatabase` keeps the notes and a history cursor between runs, so each
+//! scan only reads what is new.
+//!
+//! `export` seals the database under a passphrase (see `vault`), for backups
+//! and for keeping the file itself encrypted; `import` and `merge` restore it
+//! on another machine without rescanning from genesis.
+//!
+//! `ChainScanner` and the database's file storage need the `native` feature;
+//! without it, `NoteDetector` still tries memos fetched by other means.
+
+#[cfg(feature = "native")]
+use std::{collections::BTreeMap, fs, path::Path, str::FromStr};
+
+#[cfg(feature = "native")]
+use base64::Engine;
+use borsh::{BorshDeserialize, BorshSerialize};
+#[cfg(feature = "native")]
+use floating_point_protocol_solana::{
+    events::FPPEvent, instruction::FPPInstruction, state::EncryptedNote,
+};
+#[cfg(feature = "native")]
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::RpcTransactionConfig,
+};
+#[cfg(feature = "native")]
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+#[cfg(feature = "native")]
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::{
+    error::WalletError,
+    note::Note,
+    stealth::StealthKeys,
+    sync::WalletState,
+    vault::{self, KdfParams},
+    viewing::ViewingKey,
+};
+#[cfg(feature = "native")]
+use crate::spent::{RpcNullifierOracle, SpentNoteDetector, SpentStatus};
+
+#[cfg(feature = "native")]
+const DATA_LOG_PREFIX: &str = "Program data: ";
+
+/// Signatures per `getSignaturesForAddress` page
+#[cfg(feature = "native")]
+const SIGNATURE_PAGE: usize = 1000;
+
+/// Recognizes outputs addressed to the wallet
+pub trait NoteDetector {
+    fn detect_note(
+        &self,
+        commitment: &[u8; 32],
+        ciphertext: &[u8],
+        created_epoch: u64,
+    ) -> Option<Note>;
+}
+
+impl NoteDetector for ViewingKey {
+    fn detect_note(
+        &self,
+        commitment: &[u8; 32],
+        ciphertext: &[u8],
+        created_epoch: u64,
+    ) -> Option<Note> {
+        self.decrypt(commitment, ciphertext)?
+            .into_note(*commitment, created_epoch)
+    }
+}
+
+impl NoteDetector for StealthKeys {
+    fn detect_note(
+        &self,
+        commitment: &[u8; 32],
+        ciphertext: &[u8],
+        created_epoch: u64,
+    ) -> Option<Note> {
+        self.detect(commitment, ciphertext, created_epoch)
+    }
+}
+
+/// Notes plus how far the transaction history has been scanned
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct NoteDatabase {
+    pub state: WalletState,
+    /// Newest program transaction already scanned
+    pub cursor: Option<String>,
+    /// Commitment tree generation as of `cursor`
+    pub tree_generation: u32,
+}
+
+impl NoteDatabase {
+    /// Seal the database under `passphrase`, as a backup or an encrypted database file
+    pub fn export(&self, passphrase: &[u8]) -> Result<Vec<u8>, WalletError> {
+        let data = self
+            .try_to_vec()
+            .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+        vault::seal(&data, passphrase, KdfParams::default())
+    }
+    
+    /// Open a database sealed by `export`
+    pub fn import(sealed: &[u8], passphrase: &[u8]) -> Result<Self, WalletError> {
+        Self::try_from_slice(&vault::open(sealed, passphrase)?)
+            .map_err(|e| WalletError::InvalidEncoding(e.to_string()))
+    }
+    
+    /// Fold a restored backup into this database
+    ///
+    /// Notes, spends and labels merge as `WalletState` replicas do. The backup's
+    /// cursor is only taken when this database has never scanned, since the two
+    /// cursors can't be ordered without RPC; the next scan re-reads the gap, and
+    /// rescanning known transactions changes nothing.
+    pub fn merge(&mut self, backup: &NoteDatabase) {
+        self.state.merge(&backup.state);
+        if self.cursor.is_none() {
+            self.cursor = backup.cursor.clone();
+            self.tree_generation = backup.tree_generation;
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+impl NoteDatabase {
+    /// Load `path`, or start empty if it doesn't exist yet
+    pub fn load(path: &Path) -> Result<Self, WalletError> {
+        match fs::read(path) {
+            Ok(data) => Self::try_from_slice(&data)
+                .map_err(|e| WalletError::InvalidEncoding(e.to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(WalletError::Storage(e.to_string())),
+        }
+    }
+    
+    pub fn save(&self, path: &Path) -> Result<(), WalletError> {
+        let data = self
+            .try_to_vec()
+            .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+        write_atomically(path, &data)
+    }
+    
+    /// Load an encrypted database file, or start empty if it doesn't exist yet
+    pub fn load_encrypted(path: &Path, passphrase: &[u8]) -> Result<Self, WalletError> {
+        match fs::read(path) {
+            Ok(sealed) => Self::import(&sealed, passphrase),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(WalletError::Storage(e.to_string())),
+        }
+    }
+    
+    pub fn save_encrypted(&self, path: &Path, passphrase: &[u8]) -> Result<(), WalletError> {
+        write_atomically(path, &self.export(passphrase)?)
+    }
+    
+    fn by_nullifier(&self) -> BTreeMap<[u8; 32], [u8; 32]> {
+        self.state
+            .notes
+            .values()
+            .map(|note| (note.nullifier, note.commitment))
+            .collect()
+    }
+}
+
+/// Write via a temporary file so an interrupted save never truncates the database
+#[cfg(feature = "native")]
+fn write_atomically(path: &Path, data: &[u8]) -> Result<(), WalletError> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, data).map_err(|e| WalletError::Storage(e.to_string()))?;
+    fs::rename(tmp, path).map_err(|e| WalletError::Storage(e.to_string()))
+}
+
+/// What one scan changed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanReport {
+    pub transactions: usize,
+    pub notes_found: usize,
+    pub notes_spent: usize,
+}
+
+#[cfg(feature = "native")]
+pub struct ChainScanner<'a> {
+    rpc: &'a RpcClient,
+    program_id: Pubkey,
+}
+
+#[cfg(feature = "native")]
+impl<'a> ChainScanner<'a> {
+    pub fn new(rpc: &'a RpcClient, program_id: Pubkey) -> Self {
+        Self { rpc, program_id }
+    }
+    
+    /// Try every `EncryptedNote` account on chain
+    ///
+    /// Accounts don't record their epoch, so notes found here get epoch 0 and
+    /// `SpentNoteDetector` checks them exactly rather than trusting filters.
+    pub fn scan_accounts<D: NoteDetector>(
+        &self,
+        detector: &D,
+        db: &mut NoteDatabase,
+    ) -> Result<ScanReport, WalletError> {
+        let accounts = self
+            .rpc
+            .get_program_accounts(&self.program_id)
+            .map_err(|e| WalletError::Rpc(e.to_string()))?;
+        let mut report = ScanReport::default();
+        for (address, account) in accounts {
+            let Ok(memo) = EncryptedNote::deserialize(&mut &account.data[..]) else {
+                continue;
+            };
+            if !memo.is_initialized
+                || db.state.notes.contains_key(&memo.commitment)
+                || address != EncryptedNote::find_address(&memo.commitment, &self.program_id).0
+            {
+                continue;
+            }
+            if let Some(note) = detector.detect_note(&memo.commitment, &memo.ciphertext, 0) {
+                db.state.add_note(note);
+                report.notes_found += 1;
+            }
+        }
+        Ok(report)
+    }
+    
+    /// Walk program transactions newer than the cursor, oldest first
+    pub fn scan_transactions<D: NoteDetector>(
+        &self,
+        detector: &D,
+        db: &mut NoteDatabase,
+    ) -> Result<ScanReport, WalletError> {
+        let until = db
+            .cursor
+            .as_deref()
+            .map(Signature::from_str)
+            .transpose()
+            .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self
+                .rpc
+                .get_signatures_for_address_with_config(
+                    &self.program_id,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until,
+                        limit: Some(SIGNATURE_PAGE),
+                        commitment: Some(self.rpc.commitment()),
+                    },
+                )
+                .map_err(|e| WalletError::Rpc(e.to_string()))?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            before = Some(
+                Signature::from_str(&last.signature)
+                    .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?,
+            );
+            let full = page.len() == SIGNATURE_PAGE;
+            signatures.extend(page);
+            if !full {
+                break;
+            }
+        }
+        
+        let schedule = self
+            .rpc
+            .get_epoch_schedule()
+            .map_err(|e| WalletError::Rpc(e.to_string()))?;
+        let mut report = ScanReport::default();
+        for status in signatures.iter().rev() {
+            if status.err.is_none() {
+                let signature = Signature::from_str(&status.signature)
+                    .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+                let epoch = schedule.get_epoch(status.slot);
+                self.scan_transaction(&signature, epoch, detector, db, &mut report)?;
+                report.transactions += 1;
+            }
+            db.cursor = Some(status.signature.clone());
+        }
+        Ok(report)
+    }
+    
+    fn scan_transaction<D: NoteDetector>(
+        &self,
+        signature: &Signature,
+        epoch: u64,
+        detector: &D,
+        db: &mut NoteDatabase,
+        report: &mut ScanReport,
+    ) -> Result<(), WalletError> {
+        let tx = self
+            .rpc
+            .get_transaction_with_config(
+                signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(self.rpc.commitment()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .map_err(|e| WalletError::Rpc(e.to_string()))?;
+        let logs: Vec<String> = tx
+            .transaction
+            .meta
+            .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages))
+            .unwrap_or_default();
+        let Some(decoded) = tx.transaction.transaction.decode() else {
+            return Ok(());
+        };
+        let keys = decoded.message.static_account_keys();
+        let instructions: Vec<FPPInstruction> = decoded
+            .message
+            .instructions()
+            .iter()
+            .filter(|ix| keys.get(ix.program_id_index as usize) == Some(&self.program_id))
+            .filter_map(|ix| FPPInstruction::try_from_slice(&ix.data).ok())
+            .collect();
+        
+        for ix in &instructions {
+            if matches!(ix, FPPInstruction::RolloverTree) {
+                db.tree_generation += 1;
+            }
+            let outputs: Vec<(&[u8; 32], &Vec<u8>)> = match ix {
+                FPPInstruction::PrivacyPayment {
+                    output_commitments,
+                    encrypted_notes,
+                    ..
+                } => output_commitments.iter().zip(encrypted_notes).collect(),
+                FPPInstruction::BatchPrivacyPayment { payments } => payments
+                    .iter()
+                    .flat_map(|payment| {
+                        payment.output_commitments.iter().zip(&payment.encrypted_notes)
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            for (commitment, ciphertext) in outputs {
+                if db.state.notes.contains_key(commitment) {
+                    continue;
+                }
+                if let Some(note) = detector.detect_note(commitment, ciphertext, epoch) {
+                    db.state.add_note(note);
+                    report.notes_found += 1;
+                }
+            }
+        }
+        
+        // Events are logged wherever the program was called from, including other programs
+        let by_nullifier = db.by_nullifier();
+        for event in logs.iter().filter_map(|line| program_event(line)) {
+            match event {
+                FPPEvent::Deposit {
+                    commitments,
+                    first_leaf_index,
+                    tree_generation,
+                    ..
+                }
+                | FPPEvent::CommitmentsInserted {
+                    commitments,
+                    first_leaf_index,
+                    tree_generation,
+                    ..
+                } => {
+                    for (index, commitment) in (first_leaf_index..).zip(commitments) {
+                        if let Some(note) = db.state.notes.get_mut(&commitment) {
+                            note.leaf_index = Some(index);
+                            note.tree_generation = tree_generation;
+                        }
+                    }
+                }
+                FPPEvent::NullifiersSpent { nullifiers } => {
+                    for nullifier in nullifiers {
+                        if let Some(commitment) = by_nullifier.get(&nullifier) {
+                            if !db.state.is_spent(commitment) {
+                                db.state.mark_spent(*commitment);
+                                report.notes_spent += 1;
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    
+    /// Check every unspent note's nullifier, through `filters` where they cover it
+    pub fn refresh_spent(
+        &self,
+        filters: &SpentNoteDetector,
+        db: &mut NoteDatabase,
+    ) -> Result<ScanReport, WalletError> {
+        let oracle = RpcNullifierOracle::new(self.rpc, self.program_id);
+        let unspent: Vec<([u8; 32], [u8; 32], u64)> = db
+            .state
+            .unspent()
+            .map(|note| (note.commitment, note.nullifier, note.created_epoch))
+            .collect();
+        let mut report = ScanReport::default();
+        for (commitment, nullifier, created_epoch) in unspent {
+            if filters.check(&nullifier, created_epoch, &oracle)? == SpentStatus::Spent {
+                db.state.mark_spent(commitment);
+                report.notes_spent += 1;
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Decode a `Program data:` log line carrying one of the program's events
+#[cfg(feature = "native")]
+fn program_event(line: &str) -> Option<FPPEvent> {
+    let fields = line
+        .strip_prefix(DATA_LOG_PREFIX)?
+        .split_whitespace()
+        .map(|field| base64::engine::general_purpose::STANDARD.decode(field))
+        .collect::<Result<Vec<_>, _>>()
+        .ok()?;
+    FPPEvent::from_log_data(&fields)
+}
@@ -0,0 +1,145 @@
+//! Spending notes: decoy selection and MLSAG ring signing.
+//!
+//! The signer's half of `crypto::ring`. The real inputs sit at one random row
+//! of the ring; every other row is a decoy point per input, so the verifier
+//! learns only that some row was spent. Decoys are matched to the mass of the
+//! input in their column, since point masses are public.
+
+use std::collections::BTreeSet;
+
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+use floating_point_protocol_solana::{
+    crypto::ring::{self, RingSignature},
+    state::FloatingPoint,
+};
+
+use crate::{error::WalletError, note::Note, viewing};
+
+/// Pick `ring_size - 1` decoy rows for `inputs` from `candidates`
+///
+/// `candidates` should already be limited to points the program would accept
+/// in a ring: initialized, active and past the minimum exit age.
+pub fn select_decoys(
+    inputs: &[Note],
+    candidates: &[FloatingPoint],
+    ring_size: usize,
+) -> Result<Vec<Vec<[u8; 32]>>, WalletError> {
+    let own: BTreeSet<[u8; 32]> = inputs.iter().map(|note| note.commitment).collect();
+    let mut used = BTreeSet::new();
+    let mut rows = vec![Vec::with_capacity(inputs.len()); ring_size.saturating_sub(1)];
+    for input in inputs {
+        let mut pool: Vec<[u8; 32]> = candidates
+            .iter()
+            .filter(|point| point.mass == input.mass)
+            .map(|point| point.commitment)
+            .filter(|commitment| !own.contains(commitment) && !used.contains(commitment))
+            .collect();
+        if pool.len() < rows.len() {
+            return Err(WalletError::Crypto(format!(
+                "{} decoys of mass {} for a ring of {}",
+                pool.len(),
+                input.mass,
+                ring_size
+            )));
+        }
+        for row in rows.iter_mut() {
+            let pick = pool.swap_remove((OsRng.next_u64() % pool.len() as u64) as usize);
+            used.insert(pick);
+            row.push(pick);
+        }
+    }
+    Ok(rows)
+}
+
+/// Ring-sign `message`, spending `inputs` among the `decoys` rows
+///
+/// Returns the signature and the row-major ring of commitments it was made
+/// over, which is the order the ring's point accounts must be passed in.
+pub fn sign(
+    message: &[u8; 32],
+    inputs: &[Note],
+    decoys: &[Vec<[u8; 32]>],
+) -> Result<(RingSignature, Vec<[u8; 32]>), WalletError> {
+    let columns = inputs.len();
+    let rows = decoys.len() + 1;
+    if columns == 0 || rows > ring::MAX_RING_SIZE {
+        return Err(WalletError::Crypto(format!("cannot sign a {} x {} ring", rows, columns)));
+    }
+    if decoys.iter().any(|row| row.len() != columns) {
+        return Err(WalletError::Crypto("every decoy row needs one point per input".into()));
+    }
+    let secrets = inputs
+        .iter()
+        .map(|note| Scalar::from_canonical_bytes(note.secret))
+        .collect::<Option<Vec<Scalar>>>()
+        .ok_or_else(|| WalletError::Crypto("invalid note secret".into()))?;
+    
+    let real = (OsRng.next_u64() % rows as u64) as usize;
+    let mut decoy_rows = decoys.iter();
+    let mut ring = Vec::with_capacity(rows * columns);
+    for row in 0..rows {
+        if row == real {
+            ring.extend(inputs.iter().map(|note| note.commitment));
+        } else if let Some(decoy_row) = decoy_rows.next() {
+            ring.extend_from_slice(decoy_row);
+        }
+    }
+    let members = ring
+        .iter()
+        .map(|bytes| CompressedRistretto(*bytes).decompress())
+        .collect::<Option<Vec<RistrettoPoint>>>()
+        .ok_or_else(|| WalletError::Crypto("ring member is not a valid point".into()))?;
+    let hashed: Vec<RistrettoPoint> = ring.iter().map(ring::hash_to_point).collect();
+    let key_images: Vec<RistrettoPoint> = secrets
+        .iter()
+        .zip(inputs)
+        .map(|(secret, note)| secret * ring::hash_to_point(&note.commitment))
+        .collect();
+    
+    // Commit at the real row, then close the loop through every decoy row
+    let alphas: Vec<Scalar> = (0..columns).map(|_| viewing::random_scalar()).collect();
+    let mut transcript = message.to_vec();
+    for (col, alpha) in alphas.iter().enumerate() {
+        transcript.extend_from_slice((alpha * RISTRETTO_BASEPOINT_POINT).compress().as_bytes());
+        transcript.extend_from_slice((alpha * hashed[real * columns + col]).compress().as_bytes());
+    }
+    let mut c = ring::hash_to_scalar(&transcript);
+    let mut challenges = vec![Scalar::zero(); rows];
+    let mut responses = vec![Scalar::zero(); rows * columns];
+    let mut row = (real + 1) % rows;
+    while row != real {
+        challenges[row] = c;
+        let mut transcript = message.to_vec();
+        for col in 0..columns {
+            let idx = row * columns + col;
+            let s = viewing::random_scalar();
+            responses[idx] = s;
+            // L = s*G + c*P, R = s*Hp(P) + c*I, as the verifier recomputes them
+            let l = RistrettoPoint::vartime_double_scalar_mul_basepoint(&c, &members[idx], &s);
+            let r = s * hashed[idx] + c * key_images[col];
+            transcript.extend_from_slice(l.compress().as_bytes());
+            transcript.extend_from_slice(r.compress().as_bytes());
+        }
+        c = ring::hash_to_scalar(&transcript);
+        row = (row + 1) % rows;
+    }
+    challenges[real] = c;
+    for (col, (alpha, secret)) in alphas.iter().zip(secrets.iter()).enumerate() {
+        responses[real * columns + col] = alpha - c * secret;
+    }
+    
+    let signature = RingSignature {
+        key_images: key_images
+            .iter()
+            .map(|image| image.compress().to_bytes())
+            .collect(),
+        challenge: challenges[0].to_bytes(),
+        responses: responses.iter().map(Scalar::to_bytes).collect(),
+    };
+    Ok((signature, ring))
+}
@@ -0,0 +1,186 @@
+//! Spent-note detection backed by the indexer's per-epoch bloom filters.
+//!
+//! A miss in every filter since the note was created proves the note is
+//! unspent as of the newest filter. Hits, and any gap in filter coverage,
+//! fall back to an exact nullifier lookup. The program's own on-chain
+//! `NullifierFilter` can stand in for the indexer's filters for notes created
+//! after it.
+
+use std::collections::BTreeMap;
+
+use borsh::BorshDeserialize;
+#[cfg(feature = "native")]
+use floating_point_protocol_solana::state::{NullifierSet, NullifierShard};
+use floating_point_protocol_solana::{
+    bloom::{EpochBloom, NullifierBloom},
+    state::NullifierFilter,
+};
+#[cfg(feature = "native")]
+use solana_client::rpc_client::RpcClient;
+#[cfg(feature = "native")]
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::WalletError;
+
+/// Exact source of truth for nullifier status
+pub trait NullifierOracle {
+    fn is_spent(&self, nullifier: &[u8; 32]) -> Result<bool, WalletError>;
+}
+
+/// Checks for the nullifier PDA on chain, or for the nullifier among those compacted into
+/// its `NullifierShard` or, before shards, the `NullifierFilter` account
+#[cfg(feature = "native")]
+pub struct RpcNullifierOracle<'a> {
+    rpc: &'a RpcClient,
+    program_id: Pubkey,
+}
+
+#[cfg(feature = "native")]
+impl<'a> RpcNullifierOracle<'a> {
+    pub fn new(rpc: &'a RpcClient, program_id: Pubkey) -> Self {
+        Self { rpc, program_id }
+    }
+    
+    /// Data of the program's `NullifierFilter` account, if it exists
+    pub fn filter_account(&self) -> Result<Option<Vec<u8>>, WalletError> {
+        let (address, _) = NullifierFilter::find_address(&self.program_id);
+        self.program_account(&address)
+    }
+    
+    /// Data of the `NullifierShard` that would hold `nullifier`, if it exists
+    pub fn shard_account(&self, nullifier: &[u8; 32]) -> Result<Option<Vec<u8>>, WalletError> {
+        let (address, _) = NullifierShard::find_address(nullifier, &self.program_id);
+        self.program_account(&address)
+    }
+    
+    fn program_account(&self, address: &Pubkey) -> Result<Option<Vec<u8>>, WalletError> {
+        let account = self
+            .rpc
+            .get_account_with_commitment(address, self.rpc.commitment())
+            .map_err(|e| WalletError::Rpc(e.to_string()))?
+            .value;
+        Ok(account
+            .filter(|account| account.owner == self.program_id)
+            .map(|account| account.data))
+    }
+}
+
+#[cfg(feature = "native")]
+impl NullifierOracle for RpcNullifierOracle<'_> {
+    fn is_spent(&self, nullifier: &[u8; 32]) -> Result<bool, WalletError> {
+        let (address, _) = NullifierSet::find_address(nullifier, &self.program_id);
+        let account = self
+            .rpc
+            .get_account_with_commitment(&address, self.rpc.commitment())
+            .map_err(|e| WalletError::Rpc(e.to_string()))?
+            .value;
+        if account.is_some_and(|account| account.owner == self.program_id) {
+            return Ok(true);
+        }
+        if self
+            .shard_account(nullifier)?
+            .is_some_and(|data| NullifierShard::contains(&data, nullifier))
+        {
+            return Ok(true);
+        }
+        Ok(self
+            .filter_account()?
+            .is_some_and(|data| NullifierFilter::is_compacted(&data, nullifier)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpentStatus {
+    Spent,
+    Unspent,
+}
+
+#[derive(Debug, Default)]
+pub struct SpentNoteDetector {
+    filters: BTreeMap<u64, EpochBloom>,
+    /// On-chain filter and the epoch it was completed in
+    account_filter: Option<(u64, NullifierBloom)>,
+}
+
+impl SpentNoteDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Add a filter, keeping the freshest snapshot of each epoch
+    pub fn add_filter(&mut self, bloom: EpochBloom) {
+        match self.filters.get(&bloom.epoch) {
+            Some(existing) if existing.last_slot >= bloom.last_slot => {}
+            _ => {
+                self.filters.insert(bloom.epoch, bloom);
+            }
+        }
+    }
+    
+    /// Add a borsh-encoded filter as published by the indexer
+    pub fn add_encoded(&mut self, bytes: &[u8]) -> Result<(), WalletError> {
+        let bloom = EpochBloom::try_from_slice(bytes)
+            .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+        self.add_filter(bloom);
+        Ok(())
+    }
+    
+    /// Add a snapshot of the on-chain `NullifierFilter` account
+    pub fn add_account_filter(&mut self, data: &[u8]) -> Result<(), WalletError> {
+        let (header, filter) = NullifierFilter::unpack(data)
+            .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+        self.account_filter = Some((header.created_epoch, filter));
+        Ok(())
+    }
+    
+    /// Slot through which a filter miss is authoritative
+    pub fn synced_through_slot(&self) -> Option<u64> {
+        self.filters.values().next_back().map(|bloom| bloom.last_slot)
+    }
+    
+    /// Whether filters exist for every epoch from `from_epoch` to the newest one
+    pub fn covers_from(&self, from_epoch: u64) -> bool {
+        let Some(&newest) = self.filters.keys().next_back() else {
+            return false;
+        };
+        from_epoch <= newest && (from_epoch..=newest).all(|epoch| self.filters.contains_key(&epoch))
+    }
+    
+    /// Status of a note created in `created_epoch`, consulting `oracle` only when needed
+    pub fn check<O: NullifierOracle>(
+        &self,
+        nullifier: &[u8; 32],
+        created_epoch: u64,
+        oracle: &O,
+    ) -> Result<SpentStatus, WalletError> {
+        let maybe_spent = match &self.account_filter {
+            // A note created after the filter cannot have been spent before it
+            Some((since, filter)) if created_epoch > *since => filter.contains(nullifier),
+            _ => {
+                !self.covers_from(created_epoch)
+                    || self
+                        .filters
+                        .range(created_epoch..)
+                        .any(|(_, bloom)| bloom.filter.contains(nullifier))
+            }
+        };
+        
+        if maybe_spent && oracle.is_spent(nullifier)? {
+            Ok(SpentStatus::Spent)
+        } else {
+            Ok(SpentStatus::Unspent)
+        }
+    }
+    
+    /// Check many `(nullifier, created_epoch)` pairs
+    pub fn check_all<O: NullifierOracle>(
+        &self,
+        notes: &[([u8; 32], u64)],
+        oracle: &O,
+    ) -> Result<Vec<SpentStatus>, WalletError> {
+        notes
+            .iter()
+            .map(|(nullifier, created_epoch)| self.check(nullifier, *created_epoch, oracle))
+            .collect()
+    }
+}
@@ -0,0 +1,196 @@
+//! Stealth addresses.
+//!
+//! A recipient publishes a scan key `A = a·G` (their viewing key) and a spend
+//! key `B = b·G`. For every payment the sender picks a fresh `r`, derives the
+//! tweak `t = Hs(r·A)` and uses the one-time key `P = t·G + B` as the output
+//! commitment. `R = r·G` travels in the encrypted note header, so the
+//! recipient recomputes `t = Hs(a·R)` to recognise the output and spends it
+//! with `t + b`. Two payments to the same address share no public data.
+
+use std::{fmt, str::FromStr};
+
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+use sha3::{Digest, Sha3_512};
+
+use crate::{
+    error::WalletError,
+    note::Note,
+    viewing::{self, NotePayload, ViewingKey, ViewingPublicKey},
+};
+
+const SPEND_KEY_DOMAIN: &[u8] = b"fpp-spend-key-v1";
+const TWEAK_DOMAIN: &[u8] = b"fpp-stealth-tweak-v1";
+const ADDRESS_PREFIX: &str = "fpps";
+
+/// Published scan and spend keys of a recipient
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StealthAddress {
+    pub scan: ViewingPublicKey,
+    pub spend: [u8; 32],
+}
+
+/// Output produced by paying a stealth address
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StealthOutput {
+    /// One-time key, used as the output commitment
+    pub commitment: [u8; 32],
+    /// Note for the `EncryptedNote` account, carrying `R` in its header
+    pub encrypted_note: Vec<u8>,
+}
+
+/// Recipient keys: the viewing key detects outputs, the spend key unlocks them
+pub struct StealthKeys {
+    viewing: ViewingKey,
+    spend: Scalar,
+}
+
+impl StealthKeys {
+    pub fn derive(wallet_secret: &[u8; 32]) -> Self {
+        let mut hasher = Sha3_512::new();
+        hasher.update(SPEND_KEY_DOMAIN);
+        hasher.update(wallet_secret);
+        Self {
+            viewing: ViewingKey::derive(wallet_secret),
+            spend: Scalar::from_hash(hasher),
+        }
+    }
+    
+    pub fn address(&self) -> StealthAddress {
+        StealthAddress {
+            scan: self.viewing.public_key(),
+            spend: (self.spend * RISTRETTO_BASEPOINT_POINT).compress().to_bytes(),
+        }
+    }
+    
+    pub fn viewing_key(&self) -> &ViewingKey {
+        &self.viewing
+    }
+    
+    /// Recognise a stealth output and recover it as a spendable note
+    pub fn detect(
+        &self,
+        commitment: &[u8; 32],
+        ciphertext: &[u8],
+        created_epoch: u64,
+    ) -> Option<Note> {
+        let spend_public = self.spend * RISTRETTO_BASEPOINT_POINT;
+        let (tweak, payload) = detect_inner(&self.viewing, &spend_public, commitment, ciphertext)?;
+        viewing::note_from_secret(tweak + self.spend, *commitment, payload.mass, created_epoch)
+    }
+    
+    /// Scan `(commitment, ciphertext)` pairs for outputs paid to this address
+    pub fn scan<'a, I>(&self, memos: I, created_epoch: u64) -> Vec<Note>
+    where
+        I: IntoIterator<Item = (&'a [u8; 32], &'a [u8])>,
+    {
+        memos
+            .into_iter()
+            .filter_map(|(commitment, ciphertext)| {
+                self.detect(commitment, ciphertext, created_epoch)
+            })
+            .collect()
+    }
+}
+
+/// Watch-only detection: needs the viewing key and the public spend key only
+pub fn is_stealth_output_for(
+    viewing: &ViewingKey,
+    address: &StealthAddress,
+    commitment: &[u8; 32],
+    ciphertext: &[u8],
+) -> bool {
+    CompressedRistretto(address.spend)
+        .decompress()
+        .and_then(|spend| detect_inner(viewing, &spend, commitment, ciphertext))
+        .is_some()
+}
+
+/// Derive a fresh one-time output for `address` and encrypt its note
+pub fn pay(
+    address: &StealthAddress,
+    mass: u64,
+    memo: Vec<u8>,
+) -> Result<StealthOutput, WalletError> {
+    let scan = CompressedRistretto(address.scan.0)
+        .decompress()
+        .ok_or_else(|| WalletError::Crypto("invalid scan key".into()))?;
+    let spend = CompressedRistretto(address.spend)
+        .decompress()
+        .ok_or_else(|| WalletError::Crypto("invalid spend key".into()))?;
+    
+    let ephemeral_secret = viewing::random_scalar();
+    let tweak = stealth_tweak(&(ephemeral_secret * scan));
+    let commitment = (tweak * RISTRETTO_BASEPOINT_POINT + spend).compress().to_bytes();
+    
+    let payload = NotePayload {
+        secret: None,
+        mass,
+        memo,
+    };
+    let encrypted_note =
+        viewing::encrypt_note_with(ephemeral_secret, &address.scan, &commitment, &payload)?;
+    Ok(StealthOutput {
+        commitment,
+        encrypted_note,
+    })
+}
+
+fn detect_inner(
+    viewing: &ViewingKey,
+    spend_public: &RistrettoPoint,
+    commitment: &[u8; 32],
+    ciphertext: &[u8],
+) -> Option<(Scalar, NotePayload)> {
+    let ephemeral = viewing::ephemeral_key(ciphertext)?;
+    let tweak = stealth_tweak(&viewing.shared_secret(&ephemeral)?);
+    if (tweak * RISTRETTO_BASEPOINT_POINT + spend_public).compress().to_bytes() != *commitment {
+        return None;
+    }
+    let payload = viewing.decrypt(commitment, ciphertext)?;
+    Some((tweak, payload))
+}
+
+fn stealth_tweak(shared: &RistrettoPoint) -> Scalar {
+    let mut hasher = Sha3_512::new();
+    hasher.update(TWEAK_DOMAIN);
+    hasher.update(shared.compress().as_bytes());
+    Scalar::from_hash(hasher)
+}
+
+impl fmt::Display for StealthAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", ADDRESS_PREFIX)?;
+        for byte in self.scan.0.iter().chain(self.spend.iter()) {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for StealthAddress {
+    type Err = WalletError;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s
+            .strip_prefix(ADDRESS_PREFIX)
+            .filter(|hex| hex.len() == 128 && hex.is_ascii())
+            .ok_or_else(|| WalletError::InvalidEncoding("malformed stealth address".into()))?;
+        let mut bytes = [0u8; 64];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16)
+                .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+        }
+        let mut scan = [0u8; 32];
+        let mut spend = [0u8; 32];
+        scan.copy_from_slice(&bytes[..32]);
+        spend.copy_from_slice(&bytes[32..]);
+        Ok(Self {
+            scan: ViewingPublicKey(scan),
+            spend,
+        })
+    }
+}
@@ -0,0 +1,141 @@
+//! Hot-wallet sweep planning for high-volume receivers.
+//!
+//! Exchanges accumulate many small incoming notes. The planner consolidates
+//! them into a few large notes using the maximum-input spend circuit, but
+//! releases sweeps on a jittered, rate-limited schedule and only touches notes
+//! old enough to have blended into the anonymity set, so sweeps can't be
+//! correlated with the deposits that funded them.
+
+use std::collections::VecDeque;
+
+use chacha20poly1305::aead::{rand_core::RngCore, OsRng};
+use floating_point_protocol_solana::state::{CircuitInfo, PoolConfig};
+
+use crate::note::Note;
+
+const HOUR: u64 = 60 * 60;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepPolicy {
+    /// Inputs per consolidation, bounded by the pool's spend circuit
+    pub max_inputs: usize,
+    /// Don't bother sweeping fewer notes than this
+    pub min_inputs: usize,
+    /// Notes must be at least this many epochs old before they are swept
+    pub min_note_age_epochs: u64,
+    pub max_sweeps_per_hour: usize,
+    /// Minimum spacing between sweeps, before jitter
+    pub min_interval_secs: u64,
+    /// Random extra delay added to each interval
+    pub max_jitter_secs: u64,
+}
+
+impl SweepPolicy {
+    /// Default schedule, consolidating as many inputs as the pool's circuit allows
+    pub fn for_pool(pool: &PoolConfig) -> Self {
+        Self {
+            max_inputs: pool.max_inputs as usize,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for SweepPolicy {
+    fn default() -> Self {
+        Self {
+            max_inputs: CircuitInfo::MAX_INPUTS as usize,
+            min_inputs: 4,
+            min_note_age_epochs: 1,
+            max_sweeps_per_hour: 6,
+            min_interval_secs: 5 * 60,
+            max_jitter_secs: 5 * 60,
+        }
+    }
+}
+
+/// One consolidation: spend `inputs`, create a single note of `output_mass`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepBatch {
+    pub inputs: Vec<Note>,
+    pub output_mass: u64,
+}
+
+#[derive(Debug)]
+pub struct SweepPlanner {
+    policy: SweepPolicy,
+    recent_sweeps: VecDeque<u64>,
+    next_allowed_at: u64,
+}
+
+impl SweepPlanner {
+    pub fn new(policy: SweepPolicy) -> Self {
+        Self {
+            policy,
+            recent_sweeps: VecDeque::new(),
+            next_allowed_at: 0,
+        }
+    }
+    
+    /// Earliest unix time the next sweep may be submitted
+    pub fn next_allowed_at(&self) -> u64 {
+        self.next_allowed_at
+    }
+    
+    /// Pick the next batch to sweep, or `None` if the schedule or the
+    /// available notes don't allow one yet
+    pub fn next_batch(
+        &mut self,
+        unspent: &[Note],
+        now: u64,
+        current_epoch: u64,
+    ) -> Option<SweepBatch> {
+        self.expire(now);
+        if now < self.next_allowed_at
+            || self.recent_sweeps.len() >= self.policy.max_sweeps_per_hour
+        {
+            return None;
+        }
+        
+        let mut eligible: Vec<&Note> = unspent
+            .iter()
+            .filter(|note| {
+                current_epoch.saturating_sub(note.created_epoch) >= self.policy.min_note_age_epochs
+            })
+            .collect();
+        if eligible.len() < self.policy.min_inputs.max(2) {
+            return None;
+        }
+        
+        // Smallest notes first, so dust is consolidated before large notes
+        eligible.sort_by_key(|note| (note.mass, note.commitment));
+        let inputs: Vec<Note> = eligible
+            .into_iter()
+            .take(self.policy.max_inputs)
+            .cloned()
+            .collect();
+        let output_mass = inputs
+            .iter()
+            .try_fold(0u64, |total, note| total.checked_add(note.mass))?;
+        Some(SweepBatch { inputs, output_mass })
+    }
+    
+    /// Record that a sweep was submitted at `now` and schedule the next one
+    pub fn record_sweep(&mut self, now: u64) {
+        self.expire(now);
+        self.recent_sweeps.push_back(now);
+        let jitter = match self.policy.max_jitter_secs {
+            0 => 0,
+            max => OsRng.next_u64() % (max + 1),
+        };
+        self.next_allowed_at = now + self.policy.min_interval_secs + jitter;
+    }
+    
+    fn expire(&mut self, now: u64) {
+        while let Some(&at) = self.recent_sweeps.front() {
+            if now.saturating_sub(at) < HOUR {
+                break;
+            }
+            self.recent_sweeps.pop_front();
+        }
+    }
+}
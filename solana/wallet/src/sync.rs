@@ -0,0 +1,205 @@
+//! Encrypted, conflict-free wallet state sync between devices.
+//!
+//! `WalletState` is a state-based CRDT: notes form a grow-only set, spent
+//! flags only ever flip to `true`, and labels are last-writer-wins registers.
+//! Conflicting copies of a note or label are settled by a total order rather
+//! than by which arrived first, so any two replicas merge to the same state
+//! regardless of order, and devices can exchange snapshots through an
+//! untrusted blob store.
+
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(feature = "native")]
+use std::{fs, path::PathBuf};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use solana_sdk::hash::hashv;
+
+use crate::{error::WalletError, note::Note};
+
+const SYNC_FORMAT_VERSION: u8 = 1;
+const NONCE_LEN: usize = 24;
+
+/// Last-writer-wins label; ties broken by device id, then by value
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub timestamp: u64,
+    pub device_id: u32,
+    pub value: String,
+}
+
+impl Label {
+    fn wins_over(&self, other: &Label) -> bool {
+        (self.timestamp, self.device_id, &self.value)
+            > (other.timestamp, other.device_id, &other.value)
+    }
+}
+
+/// Whether a merge keeps `note` over another copy under the same commitment
+///
+/// A copy that knows its leaf wins; the rest of the order only settles copies that disagree
+/// otherwise, since the commitment already fixes the secret and nullifier.
+fn supersedes(note: &Note, other: &Note) -> bool {
+    let order = |note: &Note| {
+        let position = (note.leaf_index.is_some(), note.tree_generation, note.leaf_index);
+        (position, note.created_epoch, note.mass)
+    };
+    order(note) > order(other)
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct WalletState {
+    pub notes: BTreeMap<[u8; 32], Note>,
+    pub spent: BTreeSet<[u8; 32]>,
+    pub labels: BTreeMap<[u8; 32], Label>,
+}
+
+impl WalletState {
+    pub fn add_note(&mut self, note: Note) {
+        match self.notes.get(&note.commitment) {
+            Some(existing) if !supersedes(&note, existing) => {}
+            _ => {
+                self.notes.insert(note.commitment, note);
+            }
+        }
+    }
+    
+    pub fn mark_spent(&mut self, commitment: [u8; 32]) {
+        self.spent.insert(commitment);
+    }
+    
+    pub fn is_spent(&self, commitment: &[u8; 32]) -> bool {
+        self.spent.contains(commitment)
+    }
+    
+    pub fn set_label(&mut self, commitment: [u8; 32], label: Label) {
+        match self.labels.get(&commitment) {
+            Some(existing) if !label.wins_over(existing) => {}
+            _ => {
+                self.labels.insert(commitment, label);
+            }
+        }
+    }
+    
+    pub fn unspent(&self) -> impl Iterator<Item = &Note> {
+        self.notes
+            .values()
+            .filter(move |note| !self.spent.contains(&note.commitment))
+    }
+    
+    /// Merge another replica into this one
+    pub fn merge(&mut self, other: &WalletState) {
+        for note in other.notes.values() {
+            self.add_note(note.clone());
+        }
+        self.spent.extend(other.spent.iter().copied());
+        for (commitment, label) in &other.labels {
+            self.set_label(*commitment, label.clone());
+        }
+    }
+}
+
+/// Symmetric key protecting synced state, derived from the wallet secret
+pub struct SyncKey {
+    key: [u8; 32],
+}
+
+impl SyncKey {
+    pub fn derive(wallet_secret: &[u8; 32]) -> Self {
+        Self {
+            key: hashv(&[b"fpp-sync-key-v1", wallet_secret]).to_bytes(),
+        }
+    }
+    
+    /// Storage location that doesn't reveal the wallet identity
+    pub fn blob_id(&self) -> String {
+        let id = hashv(&[b"fpp-sync-id-v1", &self.key]).to_bytes();
+        id.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+    
+    pub fn encrypt(&self, state: &WalletState) -> Result<Vec<u8>, WalletError> {
+        let plaintext = state
+            .try_to_vec()
+            .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+        let cipher = XChaCha20Poly1305::new(&self.key.into());
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext.as_ref())
+            .map_err(|_| WalletError::Crypto("sync encryption failed".into()))?;
+        
+        let mut blob = Vec::with_capacity(1 + NONCE_LEN + ciphertext.len());
+        blob.push(SYNC_FORMAT_VERSION);
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&ciphertext);
+        Ok(blob)
+    }
+    
+    pub fn decrypt(&self, blob: &[u8]) -> Result<WalletState, WalletError> {
+        if blob.len() < 1 + NONCE_LEN || blob[0] != SYNC_FORMAT_VERSION {
+            return Err(WalletError::InvalidEncoding("unsupported sync blob".into()));
+        }
+        let nonce = XNonce::from_slice(&blob[1..1 + NONCE_LEN]);
+        let cipher = XChaCha20Poly1305::new(&self.key.into());
+        let plaintext = cipher
+            .decrypt(nonce, &blob[1 + NONCE_LEN..])
+            .map_err(|_| WalletError::Crypto("sync blob failed authentication".into()))?;
+        WalletState::try_from_slice(&plaintext)
+            .map_err(|e| WalletError::InvalidEncoding(e.to_string()))
+    }
+}
+
+/// Any storage that can hold opaque blobs by id
+pub trait BlobStore {
+    fn get(&self, id: &str) -> Result<Option<Vec<u8>>, WalletError>;
+    fn put(&self, id: &str, blob: &[u8]) -> Result<(), WalletError>;
+}
+
+/// Blob store backed by a local or network-mounted directory
+#[cfg(feature = "native")]
+pub struct DirBlobStore {
+    dir: PathBuf,
+}
+
+#[cfg(feature = "native")]
+impl DirBlobStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[cfg(feature = "native")]
+impl BlobStore for DirBlobStore {
+    fn get(&self, id: &str) -> Result<Option<Vec<u8>>, WalletError> {
+        match fs::read(self.dir.join(id)) {
+            Ok(blob) => Ok(Some(blob)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(WalletError::Storage(e.to_string())),
+        }
+    }
+    
+    fn put(&self, id: &str, blob: &[u8]) -> Result<(), WalletError> {
+        fs::create_dir_all(&self.dir).map_err(|e| WalletError::Storage(e.to_string()))?;
+        let tmp = self.dir.join(format!("{}.tmp", id));
+        fs::write(&tmp, blob).map_err(|e| WalletError::Storage(e.to_string()))?;
+        fs::rename(tmp, self.dir.join(id)).map_err(|e| WalletError::Storage(e.to_string()))
+    }
+}
+
+/// Pull the remote replica, merge it into `local`, and push the result.
+///
+/// Concurrent pushes can overwrite each other, but no update is lost: every
+/// device keeps its own replica and re-merges on its next sync.
+pub fn sync<S: BlobStore>(
+    store: &S,
+    key: &SyncKey,
+    local: &mut WalletState,
+) -> Result<(), WalletError> {
+    let id = key.blob_id();
+    if let Some(blob) = store.get(&id)? {
+        local.merge(&key.decrypt(&blob)?);
+    }
+    store.put(&id, &key.encrypt(local)?)
+}
@@ -0,0 +1,101 @@
+//! Commitment tree generations.
+//!
+//! The active tree lives at a fixed PDA and is archived, frozen, when it
+//! fills. `TreeSet` looks trees up by generation so spends of notes in an
+//! archived tree are proven against its final root, and `prepare_insert`
+//! prepends the permissionless rollover to transactions that would overflow
+//! the active tree. Callers never have to track rollovers themselves.
+
+use std::collections::BTreeMap;
+
+use borsh::BorshDeserialize;
+use floating_point_protocol_solana::{instruction, state::CommitmentTree};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::error::WalletError;
+
+pub struct TreeSet {
+    program_id: Pubkey,
+    active: CommitmentTree,
+    /// Archived trees never change, so they are cached once fetched
+    archived: BTreeMap<u32, CommitmentTree>,
+}
+
+impl TreeSet {
+    pub fn fetch(rpc: &RpcClient, program_id: Pubkey) -> Result<Self, WalletError> {
+        let (address, _) = CommitmentTree::find_address(&program_id);
+        Ok(Self {
+            program_id,
+            active: fetch_tree(rpc, &address)?,
+            archived: BTreeMap::new(),
+        })
+    }
+    
+    /// Re-read the active tree, e.g. after submitting an insert
+    pub fn refresh(&mut self, rpc: &RpcClient) -> Result<(), WalletError> {
+        let (address, _) = CommitmentTree::find_address(&self.program_id);
+        self.active = fetch_tree(rpc, &address)?;
+        Ok(())
+    }
+    
+    pub fn active(&self) -> &CommitmentTree {
+        &self.active
+    }
+    
+    pub fn tree(
+        &mut self,
+        rpc: &RpcClient,
+        generation: u32,
+    ) -> Result<&CommitmentTree, WalletError> {
+        if generation == self.active.generation {
+            return Ok(&self.active);
+        }
+        if !self.archived.contains_key(&generation) {
+            let (address, _) = CommitmentTree::find_archive_address(generation, &self.program_id);
+            let tree = fetch_tree(rpc, &address)?;
+            self.archived.insert(generation, tree);
+        }
+        Ok(&self.archived[&generation])
+    }
+    
+    /// Root to prove a spend from `generation` against, and the archived
+    /// generation to pass to `instruction::privacy_payment`
+    pub fn spend_root(
+        &mut self,
+        rpc: &RpcClient,
+        generation: u32,
+    ) -> Result<([u8; 32], Option<u32>), WalletError> {
+        let archived = generation != self.active.generation;
+        let root = self.tree(rpc, generation)?.current_root();
+        Ok((root, archived.then_some(generation)))
+    }
+    
+    /// Instructions to put ahead of one inserting `leaves` commitments
+    pub fn prepare_insert(
+        &self,
+        payer: &Pubkey,
+        protocol_state: &Pubkey,
+        leaves: u64,
+    ) -> Result<Vec<Instruction>, WalletError> {
+        if self.active.remaining() >= leaves {
+            return Ok(Vec::new());
+        }
+        let rollover = instruction::rollover_tree(
+            &self.program_id,
+            payer,
+            protocol_state,
+            self.active.generation,
+            None,
+        )
+        .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+        Ok(vec![rollover])
+    }
+}
+
+fn fetch_tree(rpc: &RpcClient, address: &Pubkey) -> Result<CommitmentTree, WalletError> {
+    let data = rpc
+        .get_account_data(address)
+        .map_err(|e| WalletError::Rpc(e.to_string()))?;
+    CommitmentTree::try_from_slice(&data).map_err(|e| WalletError::InvalidEncoding(e.to_string()))
+}
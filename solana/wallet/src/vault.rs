@@ -0,0 +1,122 @@
+//! Passphrase encryption for note files and backups.
+//!
+//! A sealed file is a header followed by the XChaCha20-Poly1305 ciphertext.
+//! The key is derived from the passphrase with Argon2id, so a stolen file
+//! costs an attacker one memory-hard derivation per guess. The header names
+//! the KDF parameters and salt and is authenticated as associated data, so
+//! a file stays readable if the defaults are raised later.
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+
+use crate::error::WalletError;
+
+/// Leading bytes of every sealed file
+pub const MAGIC: &[u8; 8] = b"fppvault";
+
+const VAULT_FORMAT_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4 * 3 + SALT_LEN + NONCE_LEN;
+
+/// Argon2id cost, recorded in each file's header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory in KiB
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// 64 MiB and three passes: about half a second on a laptop
+    fn default() -> Self {
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    fn derive_key(&self, passphrase: &[u8], salt: &[u8]) -> Result<[u8; 32], WalletError> {
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+            .map_err(|e| WalletError::Crypto(e.to_string()))?;
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase, salt, &mut key)
+            .map_err(|e| WalletError::Crypto(e.to_string()))?;
+        Ok(key)
+    }
+}
+
+/// Whether `data` is a sealed file rather than plain Borsh
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Encrypt `plaintext` under `passphrase`
+pub fn seal(plaintext: &[u8], passphrase: &[u8], kdf: KdfParams) -> Result<Vec<u8>, WalletError> {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    
+    let mut sealed = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+    sealed.extend_from_slice(MAGIC);
+    sealed.push(VAULT_FORMAT_VERSION);
+    sealed.extend_from_slice(&kdf.memory_kib.to_le_bytes());
+    sealed.extend_from_slice(&kdf.iterations.to_le_bytes());
+    sealed.extend_from_slice(&kdf.parallelism.to_le_bytes());
+    sealed.extend_from_slice(&salt);
+    sealed.extend_from_slice(&nonce);
+    
+    let key = kdf.derive_key(passphrase, &salt)?;
+    let ciphertext = XChaCha20Poly1305::new(&key.into())
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &sealed,
+            },
+        )
+        .map_err(|_| WalletError::Crypto("vault encryption failed".into()))?;
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypt a file made by `seal`; a wrong passphrase fails authentication
+pub fn open(sealed: &[u8], passphrase: &[u8]) -> Result<Vec<u8>, WalletError> {
+    if sealed.len() < HEADER_LEN
+        || !is_sealed(sealed)
+        || sealed[MAGIC.len()] != VAULT_FORMAT_VERSION
+    {
+        return Err(WalletError::InvalidEncoding("unsupported vault file".into()));
+    }
+    let (header, ciphertext) = sealed.split_at(HEADER_LEN);
+    let word = |i: usize| {
+        let start = MAGIC.len() + 1 + 4 * i;
+        u32::from_le_bytes(header[start..start + 4].try_into().unwrap_or_default())
+    };
+    let kdf = KdfParams {
+        memory_kib: word(0),
+        iterations: word(1),
+        parallelism: word(2),
+    };
+    let salt = &header[HEADER_LEN - NONCE_LEN - SALT_LEN..HEADER_LEN - NONCE_LEN];
+    let nonce = XNonce::from_slice(&header[HEADER_LEN - NONCE_LEN..]);
+    
+    let key = kdf.derive_key(passphrase, salt)?;
+    XChaCha20Poly1305::new(&key.into())
+        .decrypt(
+            nonce,
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| WalletError::Crypto("wrong passphrase or corrupted vault file".into()))
+}
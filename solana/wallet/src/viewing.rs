@@ -0,0 +1,207 @@
+//! Viewing keys and encrypted note payloads.
+//!
+//! Every output commitment carries an encrypted memo in its `EncryptedNote`
+//! account. The sender picks an ephemeral scalar `r`, publishes `R = r·G` and
+//! encrypts the note opening under a key derived from `r·V`, where `V` is the
+//! recipient's public viewing key. The recipient recomputes the same secret as
+//! `v·R` and can therefore scan every memo on chain without being able to
+//! spend; spending still requires the note secret inside the payload.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, KeyInit, OsRng, Payload},
+    ChaCha20Poly1305, Nonce,
+};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+};
+use floating_point_protocol_solana::{crypto::ring, state::EncryptedNote};
+use sha3::{Digest, Sha3_512};
+use solana_sdk::hash::hashv;
+
+use crate::{error::WalletError, note::Note};
+
+const NOTE_FORMAT_VERSION: u8 = 1;
+const VIEWING_KEY_DOMAIN: &[u8] = b"fpp-viewing-key-v1";
+const NOTE_KEY_DOMAIN: &[u8] = b"fpp-note-key-v1";
+const HEADER_LEN: usize = 1 + 32;
+
+/// Largest memo that still fits in `EncryptedNote::MAX_CIPHERTEXT_LEN`
+pub const MAX_MEMO_LEN: usize = EncryptedNote::MAX_CIPHERTEXT_LEN - HEADER_LEN - 16 - 33 - 8 - 4;
+
+/// Opening of an output commitment, readable by the holder of the viewing key
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NotePayload {
+    /// Secret key for the commitment; `None` for stealth outputs, whose key
+    /// the recipient derives from the stealth shared secret
+    pub secret: Option<[u8; 32]>,
+    pub mass: u64,
+    /// Free-form memo from the sender
+    pub memo: Vec<u8>,
+}
+
+/// Public half of a viewing key, shared with payers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViewingPublicKey(pub [u8; 32]);
+
+/// Key that can detect and decrypt incoming notes but not spend them
+pub struct ViewingKey {
+    secret: Scalar,
+}
+
+impl ViewingKey {
+    pub fn derive(wallet_secret: &[u8; 32]) -> Self {
+        let mut hasher = Sha3_512::new();
+        hasher.update(VIEWING_KEY_DOMAIN);
+        hasher.update(wallet_secret);
+        Self {
+            secret: Scalar::from_hash(hasher),
+        }
+    }
+    
+    pub fn public_key(&self) -> ViewingPublicKey {
+        ViewingPublicKey((self.secret * RISTRETTO_BASEPOINT_POINT).compress().to_bytes())
+    }
+    
+    /// Decrypt the memo attached to `commitment`, or `None` if it isn't ours
+    pub fn decrypt(&self, commitment: &[u8; 32], ciphertext: &[u8]) -> Option<NotePayload> {
+        let ephemeral = ephemeral_key(ciphertext)?;
+        let shared = self.shared_secret(&ephemeral)?;
+        let cipher = note_cipher(&shared, &ephemeral, commitment);
+        let plaintext = cipher
+            .decrypt(
+                &Nonce::default(),
+                Payload {
+                    msg: &ciphertext[HEADER_LEN..],
+                    aad: commitment,
+                },
+            )
+            .ok()?;
+        NotePayload::try_from_slice(&plaintext).ok()
+    }
+    
+    /// Diffie-Hellman secret with the sender's ephemeral key
+    pub(crate) fn shared_secret(&self, ephemeral: &[u8; 32]) -> Option<RistrettoPoint> {
+        Some(self.secret * CompressedRistretto(*ephemeral).decompress()?)
+    }
+    
+    /// Try every `(commitment, ciphertext)` pair and keep the notes we can open
+    pub fn scan<'a, I>(&self, memos: I, created_epoch: u64) -> Vec<Note>
+    where
+        I: IntoIterator<Item = (&'a [u8; 32], &'a [u8])>,
+    {
+        memos
+            .into_iter()
+            .filter_map(|(commitment, ciphertext)| {
+                let payload = self.decrypt(commitment, ciphertext)?;
+                payload.into_note(*commitment, created_epoch)
+            })
+            .collect()
+    }
+}
+
+impl NotePayload {
+    /// Turn a decrypted payload into a spendable note, rejecting openings
+    /// that don't match the commitment they were attached to
+    pub fn into_note(self, commitment: [u8; 32], created_epoch: u64) -> Option<Note> {
+        let secret = Scalar::from_canonical_bytes(self.secret?)?;
+        note_from_secret(secret, commitment, self.mass, created_epoch)
+    }
+}
+
+/// Build a note from its commitment secret, checking it opens `commitment`
+pub(crate) fn note_from_secret(
+    secret: Scalar,
+    commitment: [u8; 32],
+    mass: u64,
+    created_epoch: u64,
+) -> Option<Note> {
+    if (secret * RISTRETTO_BASEPOINT_POINT).compress().to_bytes() != commitment {
+        return None;
+    }
+    let nullifier = (secret * ring::hash_to_point(&commitment)).compress().to_bytes();
+    Some(Note {
+        commitment,
+        secret: secret.to_bytes(),
+        nullifier,
+        mass,
+        leaf_index: None,
+        tree_generation: 0,
+        created_epoch,
+    })
+}
+
+/// Sender's ephemeral public key from an encrypted note header
+pub(crate) fn ephemeral_key(ciphertext: &[u8]) -> Option<[u8; 32]> {
+    if ciphertext.len() < HEADER_LEN || ciphertext[0] != NOTE_FORMAT_VERSION {
+        return None;
+    }
+    let mut ephemeral = [0u8; 32];
+    ephemeral.copy_from_slice(&ciphertext[1..HEADER_LEN]);
+    Some(ephemeral)
+}
+
+/// Fresh uniformly random scalar
+pub(crate) fn random_scalar() -> Scalar {
+    let mut wide = [0u8; 64];
+    OsRng.fill_bytes(&mut wide);
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+/// Encrypt `payload` for `recipient`, bound to the output `commitment`
+pub fn encrypt_note(
+    recipient: &ViewingPublicKey,
+    commitment: &[u8; 32],
+    payload: &NotePayload,
+) -> Result<Vec<u8>, WalletError> {
+    encrypt_note_with(random_scalar(), recipient, commitment, payload)
+}
+
+/// Encrypt under a caller-chosen ephemeral secret, which must never be reused
+pub(crate) fn encrypt_note_with(
+    ephemeral_secret: Scalar,
+    recipient: &ViewingPublicKey,
+    commitment: &[u8; 32],
+    payload: &NotePayload,
+) -> Result<Vec<u8>, WalletError> {
+    if payload.memo.len() > MAX_MEMO_LEN {
+        return Err(WalletError::InvalidEncoding("memo too long".into()));
+    }
+    let recipient_point = CompressedRistretto(recipient.0)
+        .decompress()
+        .ok_or_else(|| WalletError::Crypto("invalid viewing key".into()))?;
+    
+    let ephemeral = (ephemeral_secret * RISTRETTO_BASEPOINT_POINT).compress().to_bytes();
+    let shared = ephemeral_secret * recipient_point;
+    
+    let plaintext = payload
+        .try_to_vec()
+        .map_err(|e| WalletError::InvalidEncoding(e.to_string()))?;
+    // Each key is used exactly once, so a fixed nonce is safe
+    let ciphertext = note_cipher(&shared, &ephemeral, commitment)
+        .encrypt(
+            &Nonce::default(),
+            Payload {
+                msg: &plaintext,
+                aad: commitment,
+            },
+        )
+        .map_err(|_| WalletError::Crypto("note encryption failed".into()))?;
+    
+    let mut out = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+    out.push(NOTE_FORMAT_VERSION);
+    out.extend_from_slice(&ephemeral);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+fn note_cipher(
+    shared: &RistrettoPoint,
+    ephemeral: &[u8; 32],
+    commitment: &[u8; 32],
+) -> ChaCha20Poly1305 {
+    let key = hashv(&[NOTE_KEY_DOMAIN, shared.compress().as_bytes(), ephemeral, commitment]);
+    ChaCha20Poly1305::new(&key.to_bytes().into())
+}
@@ -0,0 +1,59 @@
+//! wasm-bindgen entry points, so browser wallets scan notes client-side.
+//!
+//! The page fetches `EncryptedNote` accounts and `PrivacyPayment` memos over
+//! its own RPC connection and hands each commitment and ciphertext to
+//! `NoteScanner::detect`; the wallet secret never leaves the browser. Notes
+//! come back Borsh encoded, as `NoteDatabase` stores them.
+
+use borsh::BorshSerialize;
+use wasm_bindgen::prelude::*;
+
+use crate::{error::WalletError, scan::NoteDetector, stealth::StealthKeys};
+
+/// Viewing and stealth keys of one wallet
+#[wasm_bindgen]
+pub struct NoteScanner {
+    keys: StealthKeys,
+}
+
+#[wasm_bindgen]
+impl NoteScanner {
+    #[wasm_bindgen(constructor)]
+    pub fn new(wallet_secret: &[u8]) -> Result<NoteScanner, JsError> {
+        let wallet_secret: [u8; 32] = wallet_secret
+            .try_into()
+            .map_err(|_| WalletError::InvalidEncoding("wallet secret must be 32 bytes".into()))?;
+        Ok(Self {
+            keys: StealthKeys::derive(&wallet_secret),
+        })
+    }
+    
+    #[wasm_bindgen(js_name = viewingPublicKey)]
+    pub fn viewing_public_key(&self) -> Vec<u8> {
+        self.keys.viewing_key().public_key().0.to_vec()
+    }
+    
+    #[wasm_bindgen(js_name = stealthAddress)]
+    pub fn stealth_address(&self) -> String {
+        self.keys.address().to_string()
+    }
+    
+    /// The Borsh-encoded `Note` if the output was paid to this wallet's viewing key or
+    /// stealth address
+    pub fn detect(
+        &self,
+        commitment: &[u8],
+        ciphertext: &[u8],
+        created_epoch: u64,
+    ) -> Result<Option<Vec<u8>>, JsError> {
+        let commitment: [u8; 32] = commitment
+            .try_into()
+            .map_err(|_| WalletError::InvalidEncoding("commitment must be 32 bytes".into()))?;
+        let note = self
+            .keys
+            .viewing_key()
+            .detect_note(&commitment, ciphertext, created_epoch)
+            .or_else(|| self.keys.detect_note(&commitment, ciphertext, created_epoch));
+        Ok(note.map(|note| note.try_to_vec()).transpose()?)
+    }
+}
@@ -0,0 +1,69 @@
+use fpp_wallet::{
+    destinations::{DestinationCheck, DestinationRotator, ReusePolicy},
+    error::WalletError,
+};
+use solana_sdk::{pubkey::Pubkey, signer::Signer};
+use spl_associated_token_account::get_associated_token_address;
+
+const SEED: [u8; 64] = [7u8; 64];
+
+#[test]
+fn rotation_hands_out_a_fresh_derived_account_each_time() {
+    let mint = Pubkey::new_unique();
+    let mut rotator = DestinationRotator::new(&SEED, 0, mint, ReusePolicy::Refuse);
+    let first = rotator.fresh().unwrap();
+    let second = rotator.fresh().unwrap();
+    assert_eq!((first.index, second.index), (0, 1));
+    assert_ne!(first.owner, second.owner);
+    assert_eq!(first.token_account, get_associated_token_address(&first.owner, &mint));
+    assert_eq!(rotator.keypair(1).unwrap().pubkey(), second.owner);
+    
+    // The same seed derives the same destinations on another device, but not for another account
+    let restored = DestinationRotator::new(&SEED, 0, mint, ReusePolicy::Refuse);
+    assert_eq!(restored.destination(0).unwrap(), first);
+    let other_account = DestinationRotator::new(&SEED, 1, mint, ReusePolicy::Refuse);
+    assert_ne!(other_account.destination(0).unwrap().owner, first.owner);
+}
+
+#[test]
+fn reused_destinations_are_flagged_or_refused() {
+    let mint = Pubkey::new_unique();
+    let external = Pubkey::new_unique();
+    let mut warn = DestinationRotator::new(&SEED, 0, mint, ReusePolicy::Warn);
+    assert_eq!(warn.record_withdrawal(&external).unwrap(), DestinationCheck::Fresh);
+    assert_eq!(
+        warn.record_withdrawal(&external).unwrap(),
+        DestinationCheck::Reused {
+            previous_withdrawals: 1
+        }
+    );
+    assert_eq!(warn.ledger().used[&external], 2);
+    
+    let mut refuse = DestinationRotator::new(&SEED, 0, mint, ReusePolicy::Refuse);
+    refuse.record_withdrawal(&external).unwrap();
+    assert!(matches!(
+        refuse.record_withdrawal(&external),
+        Err(WalletError::DestinationReused(_))
+    ));
+    assert_eq!(refuse.ledger().used[&external], 1);
+}
+
+#[test]
+fn a_resumed_ledger_skips_destinations_already_used() {
+    let mint = Pubkey::new_unique();
+    let mut rotator = DestinationRotator::new(&SEED, 0, mint, ReusePolicy::Refuse);
+    let first = rotator.fresh().unwrap();
+    rotator.record_withdrawal(&first.token_account).unwrap();
+    // Paid to by hand, before the rotator reached it
+    let ahead = rotator.destination(1).unwrap();
+    rotator.record_withdrawal(&ahead.token_account).unwrap();
+    
+    let mut resumed = DestinationRotator::with_ledger(
+        &SEED,
+        0,
+        mint,
+        ReusePolicy::Refuse,
+        rotator.ledger().clone(),
+    );
+    assert_eq!(resumed.fresh().unwrap().index, 2);
+}
@@ -0,0 +1,50 @@
+use fpp_wallet::{
+    note::Note,
+    scan::NoteDetector,
+    viewing::{self, NotePayload, ViewingKey},
+};
+
+#[test]
+fn viewing_keys_detect_only_their_own_outputs() {
+    let key = ViewingKey::derive(&[1u8; 32]);
+    let note = Note::generate(5, 0);
+    let payload = NotePayload {
+        secret: Some(note.secret),
+        mass: 5,
+        memo: b"invoice 7".to_vec(),
+    };
+    let ciphertext = viewing::encrypt_note(&key.public_key(), &note.commitment, &payload).unwrap();
+    
+    let detected = key.detect_note(&note.commitment, &ciphertext, 3).unwrap();
+    assert_eq!(
+        detected,
+        Note {
+            created_epoch: 3,
+            ..note.clone()
+        }
+    );
+    
+    // Another wallet learns nothing, and a memo moved to another output doesn't open
+    let stranger = ViewingKey::derive(&[2u8; 32]);
+    assert!(stranger.detect_note(&note.commitment, &ciphertext, 3).is_none());
+    let other = Note::generate(5, 0);
+    assert!(key.detect_note(&other.commitment, &ciphertext, 3).is_none());
+}
+
+#[test]
+fn payloads_that_dont_open_their_commitment_are_dropped() {
+    let key = ViewingKey::derive(&[1u8; 32]);
+    let (note, other) = (Note::generate(5, 0), Note::generate(5, 0));
+    // A sender can attach any secret; only one opening the commitment becomes a note
+    let payload = NotePayload {
+        secret: Some(other.secret),
+        mass: 5,
+        memo: Vec::new(),
+    };
+    let ciphertext = viewing::encrypt_note(&key.public_key(), &note.commitment, &payload).unwrap();
+    assert!(key.decrypt(&note.commitment, &ciphertext).is_some());
+    assert!(key.detect_note(&note.commitment, &ciphertext, 0).is_none());
+    
+    let memos = [(&note.commitment, &ciphertext[..])];
+    assert!(key.scan(memos, 0).is_empty());
+}
@@ -0,0 +1,80 @@
+use floating_point_protocol_solana::{
+    crypto::ring,
+    migration::Versioned,
+    state::FloatingPoint,
+};
+use fpp_wallet::{error::WalletError, note::Note, spend};
+use solana_sdk::pubkey::Pubkey;
+
+/// An on-chain point of `mass` for someone else's note
+fn point(mass: u64) -> FloatingPoint {
+    FloatingPoint {
+        version: FloatingPoint::VERSION,
+        is_initialized: true,
+        commitment: Note::generate(mass, 0).commitment,
+        created_at: 0,
+        mass,
+        is_active: true,
+        creator: Pubkey::new_unique(),
+        locked_until: 0,
+        created_slot: 0,
+        mint: Pubkey::new_unique(),
+        rent_payer: Pubkey::new_unique(),
+        accrued: 0,
+        mass_updated_at: 0,
+    }
+}
+
+fn input_points(inputs: &[Note]) -> Vec<FloatingPoint> {
+    inputs
+        .iter()
+        .map(|note| FloatingPoint {
+            commitment: note.commitment,
+            ..point(note.mass)
+        })
+        .collect()
+}
+
+#[test]
+fn signed_spends_verify_under_the_notes_nullifiers() {
+    let inputs = vec![Note::generate(1, 0), Note::generate(2, 0)];
+    let mut candidates: Vec<FloatingPoint> = [1, 1, 1, 2, 2, 2, 3].map(point).to_vec();
+    candidates.extend(input_points(&inputs));
+    
+    let mass_of = |commitment: &[u8; 32]| {
+        candidates.iter().find(|point| point.commitment == *commitment).unwrap().mass
+    };
+    
+    let decoys = spend::select_decoys(&inputs, &candidates, 4).unwrap();
+    assert_eq!(decoys.len(), 3);
+    for row in &decoys {
+        // Each column's decoys match its input's public mass, and are never the input itself
+        let masses: Vec<u64> = row.iter().map(mass_of).collect();
+        assert_eq!(masses, [1, 2]);
+        assert!(inputs.iter().all(|note| !row.contains(&note.commitment)));
+    }
+    
+    let message = ring::signing_message(&[inputs[0].nullifier], &[[9u8; 32]], b"proof");
+    let (signature, members) = spend::sign(&message, &inputs, &decoys).unwrap();
+    assert_eq!(members.len(), 8);
+    assert!(ring::verify(&message, &members, &signature));
+    let nullifiers: Vec<[u8; 32]> = inputs.iter().map(|note| note.nullifier).collect();
+    assert_eq!(signature.key_images, nullifiers);
+    assert!(!ring::verify(&[0u8; 32], &members, &signature));
+}
+
+#[test]
+fn spends_without_enough_decoys_are_refused() {
+    let inputs = vec![Note::generate(1, 0)];
+    let candidates = [1, 1, 2].map(point).to_vec();
+    assert!(matches!(
+        spend::select_decoys(&inputs, &candidates, 4),
+        Err(WalletError::Crypto(_))
+    ));
+    
+    // Rows must cover every input, and the ring can't outgrow what the program verifies
+    let short_row = vec![Vec::new()];
+    assert!(spend::sign(&[0u8; 32], &inputs, &short_row).is_err());
+    let too_many = vec![vec![point(1).commitment]; ring::MAX_RING_SIZE];
+    assert!(spend::sign(&[0u8; 32], &inputs, &too_many).is_err());
+}
@@ -0,0 +1,57 @@
+use fpp_wallet::{
+    note::Note,
+    sweep::{SweepPlanner, SweepPolicy},
+};
+
+fn notes(masses: &[u64], created_epoch: u64) -> Vec<Note> {
+    masses.iter().map(|mass| Note::generate(*mass, created_epoch)).collect()
+}
+
+#[test]
+fn sweeps_take_the_smallest_aged_notes_up_to_the_circuit() {
+    let policy = SweepPolicy {
+        max_inputs: 3,
+        min_inputs: 2,
+        min_note_age_epochs: 2,
+        ..SweepPolicy::default()
+    };
+    let mut planner = SweepPlanner::new(policy);
+    let mut unspent = notes(&[9, 1, 5, 3], 10);
+    
+    // Too new to have blended in yet
+    assert_eq!(planner.next_batch(&unspent, 0, 11), None);
+    unspent.extend(notes(&[2], 11));
+    let batch = planner.next_batch(&unspent, 0, 12).unwrap();
+    let masses: Vec<u64> = batch.inputs.iter().map(|note| note.mass).collect();
+    assert_eq!(masses, [1, 3, 5]);
+    assert_eq!(batch.output_mass, 9);
+    
+    // Fewer eligible notes than the minimum aren't worth a sweep
+    assert_eq!(planner.next_batch(&unspent[..1], 0, 12), None);
+}
+
+#[test]
+fn sweeps_are_spaced_out_and_rate_limited() {
+    let policy = SweepPolicy {
+        min_inputs: 2,
+        max_sweeps_per_hour: 2,
+        min_interval_secs: 60,
+        max_jitter_secs: 30,
+        ..SweepPolicy::default()
+    };
+    let mut planner = SweepPlanner::new(policy);
+    let unspent = notes(&[1, 2], 0);
+    assert!(planner.next_batch(&unspent, 1_000, 5).is_some());
+    
+    planner.record_sweep(1_000);
+    let next = planner.next_allowed_at();
+    assert!((1_060..=1_090).contains(&next));
+    assert_eq!(planner.next_batch(&unspent, next - 1, 5), None);
+    assert!(planner.next_batch(&unspent, next, 5).is_some());
+    
+    // Two sweeps within the hour use up its allowance, whatever the spacing
+    planner.record_sweep(next);
+    let later = planner.next_allowed_at() + 60;
+    assert_eq!(planner.next_batch(&unspent, later, 5), None);
+    assert!(planner.next_batch(&unspent, 1_000 + 3_600, 5).is_some());
+}
@@ -0,0 +1,140 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use fpp_wallet::{
+    error::WalletError,
+    note::Note,
+    sync::{self, BlobStore, Label, SyncKey, WalletState},
+};
+
+/// Small xorshift generator, so every run replays the same replicas
+struct Rng(u64);
+
+impl Rng {
+    fn below(&mut self, bound: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % bound
+    }
+}
+
+/// A replica after a run of random edits over a handful of shared commitments
+///
+/// The ranges are kept narrow so replicas often hold conflicting copies of one note
+/// and labels written at the same time by the same device.
+fn replica(rng: &mut Rng) -> WalletState {
+    let mut state = WalletState::default();
+    for _ in 0..12 {
+        let commitment = [rng.below(4) as u8; 32];
+        match rng.below(3) {
+            0 => state.add_note(Note {
+                commitment,
+                secret: [1u8; 32],
+                nullifier: [2u8; 32],
+                mass: 1 + rng.below(2),
+                leaf_index: [None, Some(0), Some(1)][rng.below(3) as usize],
+                tree_generation: rng.below(2) as u32,
+                created_epoch: rng.below(2),
+            }),
+            1 => state.mark_spent(commitment),
+            _ => state.set_label(
+                commitment,
+                Label {
+                    timestamp: rng.below(2),
+                    device_id: rng.below(2) as u32,
+                    value: ["rent", "savings", "tips"][rng.below(3) as usize].to_string(),
+                },
+            ),
+        }
+    }
+    state
+}
+
+fn merged(a: &WalletState, b: &WalletState) -> WalletState {
+    let mut state = a.clone();
+    state.merge(b);
+    state
+}
+
+#[test]
+fn merges_commute_associate_and_are_idempotent() {
+    let mut rng = Rng(0x5eed);
+    for _ in 0..200 {
+        let (a, b, c) = (replica(&mut rng), replica(&mut rng), replica(&mut rng));
+        assert_eq!(merged(&a, &b), merged(&b, &a));
+        assert_eq!(merged(&merged(&a, &b), &c), merged(&a, &merged(&b, &c)));
+        assert_eq!(merged(&a, &a), a);
+        assert_eq!(merged(&merged(&a, &b), &b), merged(&a, &b));
+    }
+}
+
+#[test]
+fn replicas_converge_whatever_order_they_sync_in() {
+    let mut rng = Rng(0xc0ffee);
+    for _ in 0..50 {
+        let replicas: Vec<WalletState> = (0..3).map(|_| replica(&mut rng)).collect();
+        let orders = [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+        let results: Vec<WalletState> = orders
+            .iter()
+            .map(|order| {
+                let mut state = WalletState::default();
+                for index in order {
+                    state.merge(&replicas[*index]);
+                }
+                state
+            })
+            .collect();
+        assert!(results.windows(2).all(|pair| pair[0] == pair[1]));
+    }
+}
+
+#[test]
+fn simultaneous_labels_from_one_device_settle_on_one_value() {
+    let label = |value: &str| Label {
+        timestamp: 5,
+        device_id: 1,
+        value: value.to_string(),
+    };
+    let mut a = WalletState::default();
+    a.set_label([1u8; 32], label("rent"));
+    let mut b = WalletState::default();
+    b.set_label([1u8; 32], label("tips"));
+    
+    let (ab, ba) = (merged(&a, &b), merged(&b, &a));
+    assert_eq!(ab, ba);
+    assert_eq!(ab.labels[&[1u8; 32]].value, "tips");
+}
+
+/// Blob store held in memory
+#[derive(Default)]
+struct MemoryStore(RefCell<HashMap<String, Vec<u8>>>);
+
+impl BlobStore for MemoryStore {
+    fn get(&self, id: &str) -> Result<Option<Vec<u8>>, WalletError> {
+        Ok(self.0.borrow().get(id).cloned())
+    }
+    
+    fn put(&self, id: &str, blob: &[u8]) -> Result<(), WalletError> {
+        self.0.borrow_mut().insert(id.to_string(), blob.to_vec());
+        Ok(())
+    }
+}
+
+#[test]
+fn devices_converge_through_a_shared_store() {
+    let mut rng = Rng(0xfeed);
+    let store = MemoryStore::default();
+    let key = SyncKey::derive(&[7u8; 32]);
+    let (mut laptop, mut phone) = (replica(&mut rng), replica(&mut rng));
+    let expected = merged(&laptop, &phone);
+    
+    sync::sync(&store, &key, &mut laptop).unwrap();
+    sync::sync(&store, &key, &mut phone).unwrap();
+    sync::sync(&store, &key, &mut laptop).unwrap();
+    assert_eq!(laptop, expected);
+    assert_eq!(phone, expected);
+    
+    // The store only ever sees ciphertext, which another wallet's key can't open
+    let blob = store.get(&key.blob_id()).unwrap().unwrap();
+    assert!(SyncKey::derive(&[8u8; 32]).decrypt(&blob).is_err());
+}
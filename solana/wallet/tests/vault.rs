@@ -0,0 +1,94 @@
+use fpp_wallet::{
+    error::WalletError,
+    note::Note,
+    scan::NoteDatabase,
+    vault::{self, KdfParams},
+};
+
+/// Cheap enough to run many times in a test
+const FAST: KdfParams = KdfParams {
+    memory_kib: 1024,
+    iterations: 1,
+    parallelism: 1,
+};
+
+#[test]
+fn sealed_files_open_only_under_their_passphrase() {
+    let sealed = vault::seal(b"notes", b"correct horse", FAST).unwrap();
+    assert!(vault::is_sealed(&sealed));
+    assert!(!vault::is_sealed(b"notes"));
+    assert_eq!(vault::open(&sealed, b"correct horse").unwrap(), b"notes");
+    assert!(matches!(vault::open(&sealed, b"wrong horse"), Err(WalletError::Crypto(_))));
+    
+    // Fresh salt and nonce per file, so equal contents don't show
+    assert_ne!(sealed, vault::seal(b"notes", b"correct horse", FAST).unwrap());
+}
+
+#[test]
+fn tampered_or_foreign_files_are_refused() {
+    let sealed = vault::seal(b"notes", b"pass", FAST).unwrap();
+    
+    // The header is authenticated, so its KDF cost can't be lowered to speed up guessing
+    let mut cheaper = sealed.clone();
+    cheaper[vault::MAGIC.len() + 1] ^= 1;
+    assert!(vault::open(&cheaper, b"pass").is_err());
+    let mut flipped = sealed.clone();
+    *flipped.last_mut().unwrap() ^= 1;
+    assert!(matches!(vault::open(&flipped, b"pass"), Err(WalletError::Crypto(_))));
+    
+    let mut newer = sealed.clone();
+    newer[vault::MAGIC.len()] += 1;
+    assert!(matches!(vault::open(&newer, b"pass"), Err(WalletError::InvalidEncoding(_))));
+    assert!(matches!(
+        vault::open(&sealed[..20], b"pass"),
+        Err(WalletError::InvalidEncoding(_))
+    ));
+}
+
+fn database(cursor: Option<&str>, notes: &[Note]) -> NoteDatabase {
+    let mut db = NoteDatabase {
+        cursor: cursor.map(str::to_string),
+        tree_generation: 2,
+        ..NoteDatabase::default()
+    };
+    for note in notes {
+        db.state.add_note(note.clone());
+    }
+    db
+}
+
+#[test]
+fn databases_survive_a_backup_round_trip() {
+    let note = Note::generate(5, 1);
+    let mut db = database(Some("cursor"), &[note.clone()]);
+    db.state.mark_spent(note.commitment);
+    
+    let backup = db.export(b"backup pass").unwrap();
+    assert!(vault::is_sealed(&backup));
+    assert_eq!(NoteDatabase::import(&backup, b"backup pass").unwrap(), db);
+    assert!(NoteDatabase::import(&backup, b"other pass").is_err());
+    
+    let path = std::env::temp_dir().join(format!("fpp-notes-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(NoteDatabase::load(&path).unwrap(), NoteDatabase::default());
+    db.save(&path).unwrap();
+    assert_eq!(NoteDatabase::load(&path).unwrap(), db);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn restoring_a_backup_keeps_the_local_cursor() {
+    let (old, new) = (Note::generate(5, 1), Note::generate(7, 2));
+    let backup = database(Some("backup"), &[old.clone()]);
+    
+    // A database that has scanned keeps its cursor; the next scan fills the gap
+    let mut scanned = database(Some("local"), &[new.clone()]);
+    scanned.merge(&backup);
+    assert_eq!(scanned.cursor.as_deref(), Some("local"));
+    assert_eq!(scanned.state.notes.len(), 2);
+    
+    // A new one picks up where the backup left off
+    let mut fresh = NoteDatabase::default();
+    fresh.merge(&backup);
+    assert_eq!(fresh, backup);
+}